@@ -0,0 +1,37 @@
+using System.Collections.Generic;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Outcome of <see cref="Collection.DeleteBatched"/>
+/// </summary>
+public sealed class BatchDeleteResult
+{
+    internal BatchDeleteResult(int deleted, IReadOnlyList<string> failedIds, int batches, int failedBatches, bool cancelled, ChromaErrorInfo? error)
+    {
+        Deleted = deleted;
+        FailedIds = failedIds;
+        Batches = batches;
+        FailedBatches = failedBatches;
+        Cancelled = cancelled;
+        Error = error;
+    }
+
+    /// <summary>IDs in batches the native library deleted</summary>
+    public int Deleted { get; }
+
+    /// <summary>IDs in batches that failed; they can be passed to a retry</summary>
+    public IReadOnlyList<string> FailedIds { get; }
+
+    /// <summary>Batches sent to the native library</summary>
+    public int Batches { get; }
+
+    /// <summary>Batches that failed</summary>
+    public int FailedBatches { get; }
+
+    /// <summary>Whether the delete stopped early because it was cancelled</summary>
+    public bool Cancelled { get; }
+
+    /// <summary>Error of the first failed batch, or null if every batch succeeded</summary>
+    public ChromaErrorInfo? Error { get; }
+}
@@ -0,0 +1,112 @@
+using System;
+using System.Collections.Generic;
+using System.Linq;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// An embedding function backed by the persistent embedding cache of a client, see
+/// <see cref="ChromaClient.WithEmbeddingCache"/>
+/// </summary>
+internal sealed class CachedEmbeddingFunction : IEmbeddingFunction
+{
+    private readonly ChromaClient _client;
+    private readonly IEmbeddingFunction _inner;
+    private readonly string _model;
+
+    internal CachedEmbeddingFunction(ChromaClient client, IEmbeddingFunction inner, string model)
+    {
+        _client = client;
+        _inner = inner;
+        _model = model;
+    }
+
+    public object Configuration => _inner.Configuration;
+
+    public float[][] GenerateEmbeddings(IEnumerable<string> documents)
+    {
+        var texts = documents.ToArray();
+        var embeddings = new float[texts.Length][];
+        if (texts.Length == 0)
+            return embeddings;
+
+        var found = new bool[texts.Length];
+        var documentsPtr = Collection.MarshalStringArray(texts);
+        try
+        {
+            var result = NativeMethods.chroma_embedding_cache_get(
+                _client.GetHandleOrThrow(),
+                _model,
+                documentsPtr,
+                (UIntPtr)texts.Length,
+                found,
+                out var cachedPtr,
+                out var dimension,
+                out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                var dim = (int)dimension;
+                for (int i = 0; i < texts.Length; i++)
+                {
+                    if (!found[i])
+                        continue;
+                    embeddings[i] = new float[dim];
+                    Marshal.Copy(cachedPtr + i * dim * sizeof(float), embeddings[i], 0, dim);
+                }
+            }
+            finally
+            {
+                if (cachedPtr != IntPtr.Zero)
+                    NativeMethods.chroma_free(cachedPtr);
+            }
+        }
+        finally
+        {
+            Collection.FreeStringArray(documentsPtr, texts.Length);
+        }
+
+        var missing = Enumerable.Range(0, texts.Length).Where(i => !found[i]).ToArray();
+        if (missing.Length == 0)
+            return embeddings;
+
+        var missingTexts = missing.Select(i => texts[i]).ToArray();
+        var computed = _inner.GenerateEmbeddings(missingTexts);
+        for (int i = 0; i < missing.Length; i++)
+            embeddings[missing[i]] = computed[i];
+
+        Store(missingTexts, computed);
+        return embeddings;
+    }
+
+    private void Store(string[] texts, float[][] embeddings)
+    {
+        var dimension = embeddings[0].Length;
+        if (embeddings.Any(e => e.Length != dimension))
+            throw new ChromaException("Embedding function returned embeddings of different dimensions");
+
+        var flat = new float[texts.Length * dimension];
+        for (int i = 0; i < embeddings.Length; i++)
+            Array.Copy(embeddings[i], 0, flat, i * dimension, dimension);
+
+        var documentsPtr = Collection.MarshalStringArray(texts);
+        try
+        {
+            var result = NativeMethods.chroma_embedding_cache_put(
+                _client.GetHandleOrThrow(),
+                _model,
+                documentsPtr,
+                (UIntPtr)texts.Length,
+                flat,
+                (UIntPtr)dimension,
+                out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+        }
+        finally
+        {
+            Collection.FreeStringArray(documentsPtr, texts.Length);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+using System;
+using System.Runtime.InteropServices;
+using System.Threading;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// A callback registered with the native library. Disposing it unregisters the callback
+/// (<c>chroma_unregister_callback</c>): once <see cref="Dispose"/> returns the callback is never
+/// invoked again, and the state it captured is released.
+/// </summary>
+public sealed class CallbackRegistration : IDisposable
+{
+    private ulong _id;
+    private GCHandle _state;
+
+    internal CallbackRegistration(ulong id, GCHandle state)
+    {
+        _id = id;
+        _state = state;
+    }
+
+    public void Dispose()
+    {
+        var id = Interlocked.Exchange(ref _id, 0UL);
+        if (id == 0)
+            return;
+
+        var result = NativeMethods.chroma_unregister_callback(id, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+        _state.Free();
+    }
+}
@@ -0,0 +1,96 @@
+using System;
+using System.Collections.Generic;
+using System.Threading;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Adds, updates and deletes queued for one collection and applied atomically by
+/// <see cref="Commit"/>: the writes go to the write log in a single transaction, so either all of
+/// them are applied or none is. Begin one with <see cref="ChromaClient.BeginBatch"/>; disposing a
+/// batch that was not committed aborts it.
+/// </summary>
+public sealed class ChromaBatch : IDisposable
+{
+    private ulong _handle;
+
+    internal ChromaBatch(ulong handle)
+    {
+        _handle = handle;
+    }
+
+    /// <summary>
+    /// Queues documents to add; embeddings missing from documents with text are generated with
+    /// the embedding function of the collection. Adds of existing IDs are ignored on commit.
+    /// </summary>
+    public void Add(Collection collection, IEnumerable<ChromaDocument> documents)
+    {
+        if (collection == null)
+            throw new ArgumentNullException(nameof(collection));
+        collection.QueueWrites(GetHandleOrThrow(), documents, add: true);
+    }
+
+    /// <summary>
+    /// Queues document updates; updates of IDs that do not exist are ignored on commit
+    /// </summary>
+    public void Update(Collection collection, IEnumerable<ChromaDocument> documents)
+    {
+        if (collection == null)
+            throw new ArgumentNullException(nameof(collection));
+        collection.QueueWrites(GetHandleOrThrow(), documents, add: false);
+    }
+
+    /// <summary>
+    /// Queues a delete by IDs and/or filters; filters are evaluated on commit, against the
+    /// documents committed before the batch
+    /// </summary>
+    public void Delete(
+        Collection collection,
+        IEnumerable<string>? ids = null,
+        Dictionary<string, object>? whereFilter = null,
+        string? whereDocument = null)
+    {
+        if (collection == null)
+            throw new ArgumentNullException(nameof(collection));
+        collection.QueueDelete(GetHandleOrThrow(), ids, whereFilter, whereDocument);
+    }
+
+    /// <summary>
+    /// Applies every queued write atomically and closes the batch, whether or not it succeeds
+    /// </summary>
+    public void Commit()
+    {
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
+            throw new ObjectDisposedException(nameof(ChromaBatch));
+
+        var result = NativeMethods.chroma_batch_commit(handle, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Discards the queued writes and closes the batch
+    /// </summary>
+    public void Abort()
+    {
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
+            return;
+
+        var result = NativeMethods.chroma_batch_abort(handle, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+    }
+
+    public void Dispose()
+    {
+        Abort();
+    }
+
+    private ulong GetHandleOrThrow()
+    {
+        var handle = Volatile.Read(ref _handle);
+        if (handle == 0)
+            throw new ObjectDisposedException(nameof(ChromaBatch));
+        return handle;
+    }
+}
@@ -1,6 +1,7 @@
 using System;
 using System.Collections.Generic;
 using System.Linq;
+using System.Runtime.CompilerServices;
 using System.Runtime.InteropServices;
 using System.Text.Json;
 using System.Threading;
@@ -12,7 +13,11 @@ namespace ChromaDB.NET;
 /// </summary>
 public class ChromaClient : IDisposable
 {
-    private IntPtr _handle;
+    private ulong _handle;
+    // Held for as long as the native library may call it; shared with retained clients
+    private readonly StrongBox<NativeMethods.RerankCallback?> _reranker = new();
+    private readonly StrongBox<NativeMethods.AuthorizeCallback?> _authorizer = new();
+    private readonly StrongBox<NativeMethods.AuditCallback?> _auditLog = new();
 
     internal static ChromaErrorInfo MarshalError(IntPtr errorPtr)
     {
@@ -33,111 +38,1070 @@ public class ChromaClient : IDisposable
         if (nativeError.Details != IntPtr.Zero)
             details = Marshal.PtrToStringAnsi(nativeError.Details);
 
-        return new ChromaErrorInfo(nativeError.Code, message, source, details);
+        string requestId = null;
+        if (nativeError.RequestId != IntPtr.Zero)
+            requestId = Marshal.PtrToStringAnsi(nativeError.RequestId);
+
+        var chain = new string[(int)nativeError.ChainCount];
+        for (int i = 0; i < chain.Length; i++)
+            chain[i] = Marshal.PtrToStringAnsi(Marshal.ReadIntPtr(nativeError.Chain, i * IntPtr.Size));
+
+        string json = null;
+        if (NativeMethods.chroma_error_to_json(errorPtr, out var jsonPtr, out var jsonErrorPtr) == 0)
+        {
+            json = Marshal.PtrToStringAnsi(jsonPtr);
+            NativeMethods.chroma_free_string(jsonPtr);
+        }
+        else if (jsonErrorPtr != IntPtr.Zero)
+        {
+            NativeMethods.chroma_free_error(jsonErrorPtr);
+        }
+
+        return new ChromaErrorInfo(nativeError.Code, message, source, details, nativeError.Retryable, chain, json, requestId);
+    }
+
+    private static volatile bool _nativeLibraryChecked;
+
+    /// <summary>
+    /// Fails with a clear message when the native library on disk was built for another ABI
+    /// version than this assembly, before any call could misread its structs
+    /// </summary>
+    internal static void EnsureCompatibleNativeLibrary()
+    {
+        if (_nativeLibraryChecked)
+            return;
+
+        bool compatible;
+        uint actual;
+        try
+        {
+            compatible = NativeMethods.chroma_is_compatible(NativeMethods.AbiVersion);
+            actual = NativeMethods.chroma_abi_version();
+        }
+        catch (EntryPointNotFoundException)
+        {
+            throw new ChromaException(ChromaErrorCode.NotImplemented,
+                $"The native chroma_csharp library predates ABI versioning; ChromaDB.NET expects ABI version {NativeMethods.AbiVersion}. " +
+                "Deploy the native library built with this version of ChromaDB.NET.");
+        }
+
+        if (!compatible)
+            throw new ChromaException(ChromaErrorCode.NotImplemented,
+                $"The native chroma_csharp library has ABI version {actual}, but ChromaDB.NET expects {NativeMethods.AbiVersion}. " +
+                "Deploy the native library built with this version of ChromaDB.NET.");
+
+        _nativeLibraryChecked = true;
+    }
+
+    internal static void CheckError(int errorCode, IntPtr errorPtr)
+    {
+        if (errorCode == 0) // Success
+            return;
+
+        try
+        {
+            var errorInfo = MarshalError(errorPtr);
+            throw new ChromaException(errorInfo);
+        }
+        finally
+        {
+            if (errorPtr != IntPtr.Zero)
+                NativeMethods.chroma_free_error(errorPtr);
+        }
+    }
+
+    /// <summary>
+    /// Reads the last error recorded by the native library on the calling thread,
+    /// without marshaling a ChromaError. Returns Success and an empty message if
+    /// the last call succeeded.
+    /// </summary>
+    internal static (ChromaErrorCode Code, string Message) GetLastError()
+    {
+        var code = (ChromaErrorCode)NativeMethods.chroma_get_last_error_code();
+        var size = (int)NativeMethods.chroma_get_last_error_message(null, UIntPtr.Zero);
+        if (size == 0)
+            return (code, string.Empty);
+
+        var buffer = new byte[size];
+        NativeMethods.chroma_get_last_error_message(buffer, (UIntPtr)buffer.Length);
+        return (code, System.Text.Encoding.UTF8.GetString(buffer, 0, size - 1));
+    }
+
+    /// <summary>
+    /// Creates a new ChromaDB client
+    /// </summary>
+    /// <param name="persistDirectory">Directory for persisting data</param>
+    /// <param name="hnswCacheSize">Size of the HNSW index cache</param>
+    /// <param name="diskCache">Optional disk tier for the HNSW index cache</param>
+    /// <param name="collectionCache">Optional in-memory cache of collection lookups</param>
+    /// <param name="readOnly">Rejects every mutation with <see cref="ChromaErrorCode.ReadOnly"/>
+    /// and never writes to <paramref name="persistDirectory"/>, which must already hold a database</param>
+    /// <param name="lockOptions">Optional advisory lock on <paramref name="persistDirectory"/></param>
+    /// <param name="sqlite">Optional SQLite journal, durability and cache settings</param>
+    /// <param name="distributedConfigJson">Optional JSON object with the <c>sysdb</c>, <c>log</c> and
+    /// <c>executor</c> sections of a Chroma frontend configuration, connecting the client to a
+    /// distributed deployment over gRPC instead of embedding a single node; local storage options
+    /// must then be left out</param>
+    /// <param name="objectStoreConfigJson">Optional JSON object with a <c>storage</c> section of the
+    /// Chroma storage configuration and a key <c>prefix</c>, mirroring
+    /// <paramref name="persistDirectory"/> to an object store: a directory without a database is
+    /// restored from it on creation, and <see cref="Flush"/> uploads the files that changed</param>
+    /// <param name="encryptionKey">Optional 32-byte AES-256 key sealing
+    /// <paramref name="persistDirectory"/> while no client has it open: it is sealed once the
+    /// client is disposed and decrypted when opened with the same key. Its files are plaintext
+    /// while the client is open, and after a crash until <see cref="SealDirectory"/> or the next
+    /// client opened with the key seals them; defaults to the key set with
+    /// <see cref="SetDefaultEncryptionKey"/></param>
+    public ChromaClient(
+        string? persistDirectory = null,
+        int hnswCacheSize = 1000,
+        DiskCacheOptions? diskCache = null,
+        CollectionCacheOptions? collectionCache = null,
+        bool readOnly = false,
+        LockOptions? lockOptions = null,
+        SqliteOptions? sqlite = null,
+        string? distributedConfigJson = null,
+        string? objectStoreConfigJson = null,
+        byte[]? encryptionKey = null)
+    {
+        EnsureCompatibleNativeLibrary();
+
+        var sqlitePtr = IntPtr.Zero;
+        var diskCachePtr = IntPtr.Zero;
+        var dirPtr = IntPtr.Zero;
+        var collectionCachePtr = IntPtr.Zero;
+        var lockPtr = IntPtr.Zero;
+        var keyPtr = IntPtr.Zero;
+        var encryptionPtr = IntPtr.Zero;
+        try
+        {
+            if (sqlite != null)
+            {
+                var native = new SqliteConfigNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<SqliteConfigNative>(),
+                    Url = IntPtr.Zero,
+                    MigrationMode = sqlite.ValidateMigrations ? 1 : 0,
+                    JournalMode = (int)sqlite.JournalMode,
+                    Synchronous = (int)sqlite.Synchronous,
+                    BusyTimeoutMs = (uint)sqlite.BusyTimeout.TotalMilliseconds,
+                    CacheSizeKib = (uint)sqlite.CacheSizeKb
+                };
+                sqlitePtr = Marshal.AllocHGlobal(Marshal.SizeOf<SqliteConfigNative>());
+                Marshal.StructureToPtr(native, sqlitePtr, false);
+            }
+
+            if (diskCache != null)
+            {
+                dirPtr = Marshal.StringToCoTaskMemUTF8(diskCache.Directory);
+                var native = new DiskCacheConfigNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<DiskCacheConfigNative>(),
+                    Dir = dirPtr,
+                    DiskMib = (UIntPtr)diskCache.DiskSizeMb,
+                    MemoryMib = (UIntPtr)diskCache.MemorySizeMb
+                };
+                diskCachePtr = Marshal.AllocHGlobal(Marshal.SizeOf<DiskCacheConfigNative>());
+                Marshal.StructureToPtr(native, diskCachePtr, false);
+            }
+
+            if (collectionCache != null)
+            {
+                var native = new CollectionCacheConfigNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<CollectionCacheConfigNative>(),
+                    Capacity = (UIntPtr)collectionCache.Capacity,
+                    TtlSecs = (uint)collectionCache.Ttl.TotalSeconds,
+                    PermittedParallelism = (uint)collectionCache.MaxParallelism
+                };
+                collectionCachePtr = Marshal.AllocHGlobal(Marshal.SizeOf<CollectionCacheConfigNative>());
+                Marshal.StructureToPtr(native, collectionCachePtr, false);
+            }
+
+            if (lockOptions != null)
+            {
+                var native = new LockConfigNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<LockConfigNative>(),
+                    Mode = (int)lockOptions.Mode,
+                    WaitMs = (uint)lockOptions.Wait.TotalMilliseconds
+                };
+                lockPtr = Marshal.AllocHGlobal(Marshal.SizeOf<LockConfigNative>());
+                Marshal.StructureToPtr(native, lockPtr, false);
+            }
+
+            if (encryptionKey != null)
+            {
+                keyPtr = Marshal.AllocHGlobal(encryptionKey.Length);
+                Marshal.Copy(encryptionKey, 0, keyPtr, encryptionKey.Length);
+                var native = new EncryptionConfigNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<EncryptionConfigNative>(),
+                    Key = keyPtr,
+                    KeyLen = (UIntPtr)encryptionKey.Length
+                };
+                encryptionPtr = Marshal.AllocHGlobal(Marshal.SizeOf<EncryptionConfigNative>());
+                Marshal.StructureToPtr(native, encryptionPtr, false);
+            }
+
+            var result = NativeMethods.chroma_create_client(
+                false,
+                readOnly,
+                sqlitePtr,
+                (UIntPtr)hnswCacheSize,
+                diskCachePtr,
+                collectionCachePtr,
+                lockPtr,
+                persistDirectory,
+                distributedConfigJson,
+                objectStoreConfigJson,
+                encryptionPtr,
+                out _handle,
+                out var errorPtr);
+
+            CheckError(result, errorPtr);
+        }
+        finally
+        {
+            if (sqlitePtr != IntPtr.Zero)
+                Marshal.FreeHGlobal(sqlitePtr);
+            if (diskCachePtr != IntPtr.Zero)
+                Marshal.FreeHGlobal(diskCachePtr);
+            if (dirPtr != IntPtr.Zero)
+                Marshal.FreeCoTaskMem(dirPtr);
+            if (collectionCachePtr != IntPtr.Zero)
+                Marshal.FreeHGlobal(collectionCachePtr);
+            if (lockPtr != IntPtr.Zero)
+                Marshal.FreeHGlobal(lockPtr);
+            if (keyPtr != IntPtr.Zero)
+            {
+                // Do not leave the key behind in freed memory
+                Marshal.Copy(new byte[encryptionKey!.Length], 0, keyPtr, encryptionKey.Length);
+                Marshal.FreeHGlobal(keyPtr);
+            }
+            if (encryptionPtr != IntPtr.Zero)
+                Marshal.FreeHGlobal(encryptionPtr);
+        }
+    }
+
+    private ChromaClient(
+        ulong handle,
+        StrongBox<NativeMethods.RerankCallback?> reranker,
+        StrongBox<NativeMethods.AuthorizeCallback?> authorizer,
+        StrongBox<NativeMethods.AuditCallback?> auditLog)
+    {
+        _handle = handle;
+        _reranker = reranker;
+        _authorizer = authorizer;
+        _auditLog = auditLog;
+    }
+
+    /// <summary>
+    /// Creates a client from a YAML (<c>.yaml</c>, <c>.yml</c>) or TOML (<c>.toml</c>) file holding
+    /// the options of the native client (<c>chroma_create_client_from_config</c>), so they can be
+    /// tuned without recompiling. Without a path the file named by <c>CHROMA_CONFIG_PATH</c> is
+    /// read, if set. <c>CHROMA_*</c> environment variables override the file, with <c>__</c>
+    /// separating nested keys, e.g. <c>CHROMA_SQLITE__JOURNAL_MODE=wal</c>.
+    /// </summary>
+    public static ChromaClient FromConfig(string? configPath = null)
+    {
+        EnsureCompatibleNativeLibrary();
+
+        var result = NativeMethods.chroma_create_client_from_config(configPath, out var handle, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return new ChromaClient(handle, new(), new(), new());
+    }
+
+    /// <summary>
+    /// Returns another reference to the same native client (<c>chroma_client_retain</c>), so
+    /// components sharing the client can each dispose their own reference. The native client is
+    /// released once every reference and every collection opened from it are disposed.
+    /// </summary>
+    public ChromaClient Retain()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_client_retain(handle, out var retainedHandle, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return new ChromaClient(retainedHandle, _reranker, _authorizer, _auditLog);
+    }
+
+    public Collection CreateCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    {
+        return CreateOrGetCollection(name, false, embeddingFunction, metadata);
+    }
+
+    public Collection CreateOrGetCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    {
+        return CreateOrGetCollection(name, true, embeddingFunction, metadata);
+    }
+
+    private Collection CreateOrGetCollection(string name, bool getOrCreate, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        string configJson = null;
+        if (embeddingFunction != null)
+        {
+            configJson = JsonSerializer.Serialize(embeddingFunction.Configuration);
+        }
+
+        string metadataJson = metadata != null ? JsonSerializer.Serialize(metadata) : null;
+
+        var result = NativeMethods.chroma_create_collection(
+            handle,
+            name,
+            configJson,
+            metadataJson,
+            getOrCreate,
+            null,
+            null,
+            out var collectionHandle,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return new Collection(this, collectionHandle, embeddingFunction);
+    }
+
+    public Collection GetCollection(string name, IEmbeddingFunction embeddingFunction = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        var result = NativeMethods.chroma_get_collection(
+            handle,
+            name,
+            null,
+            null,
+            out var collectionHandle,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return new Collection(this, collectionHandle, embeddingFunction);
+    }
+
+    /// <summary>
+    /// Checks whether a collection exists without opening it
+    /// </summary>
+    public bool CollectionExists(string name, string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        var result = NativeMethods.chroma_collection_exists(
+            handle,
+            name,
+            tenant,
+            database,
+            out var exists,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return exists;
+    }
+
+    /// <summary>
+    /// Gets a collection by the UUID reported by <see cref="Collection.Id"/>
+    /// </summary>
+    public Collection GetCollectionById(string id, IEmbeddingFunction embeddingFunction = null, string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        var result = NativeMethods.chroma_get_collection_by_id(
+            handle,
+            id,
+            tenant,
+            database,
+            out var collectionHandle,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return new Collection(this, collectionHandle, embeddingFunction);
+    }
+
+    /// <summary>
+    /// Opens the collection an alias created with <see cref="Collection.CreateAlias"/> names
+    /// </summary>
+    public Collection GetCollectionByAlias(string alias, IEmbeddingFunction embeddingFunction = null, string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        var result = NativeMethods.chroma_resolve_alias(
+            handle,
+            alias,
+            tenant,
+            database,
+            out var collectionHandle,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return new Collection(this, collectionHandle, embeddingFunction);
+    }
+
+    /// <summary>
+    /// Removes an alias, leaving the collection it names untouched
+    /// </summary>
+    public void DeleteAlias(string alias, string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_delete_alias(handle, alias, tenant, database, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Gets several collections by name with a single native call that resolves them
+    /// concurrently. Names that cannot be resolved are reported in the result instead of throwing.
+    /// </summary>
+    public IReadOnlyList<CollectionLookup> GetCollections(IEnumerable<string> names, IEmbeddingFunction embeddingFunction = null, string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+        var nameArray = names.ToArray();
+        if (nameArray.Length == 0)
+            return Array.Empty<CollectionLookup>();
+
+        var handles = new ulong[nameArray.Length];
+        var errors = new IntPtr[nameArray.Length];
+        var namesPtr = Collection.MarshalStringArray(nameArray);
+        try
+        {
+            var result = NativeMethods.chroma_get_collections(
+                handle,
+                namesPtr,
+                (UIntPtr)nameArray.Length,
+                tenant,
+                database,
+                handles,
+                errors,
+                out var errorPtr);
+
+            CheckError(result, errorPtr);
+        }
+        finally
+        {
+            Collection.FreeStringArray(namesPtr, nameArray.Length);
+        }
+
+        var lookups = new List<CollectionLookup>(nameArray.Length);
+        for (int i = 0; i < nameArray.Length; i++)
+        {
+            if (errors[i] != IntPtr.Zero)
+            {
+                try
+                {
+                    lookups.Add(new CollectionLookup(nameArray[i], null, MarshalError(errors[i])));
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_error(errors[i]);
+                }
+            }
+            else
+            {
+                lookups.Add(new CollectionLookup(nameArray[i], new Collection(this, handles[i], embeddingFunction), null));
+            }
+        }
+
+        return lookups;
+    }
+
+    /// <summary>
+    /// Creates a collection filled with <paramref name="count"/> reproducible records generated
+    /// natively from <paramref name="seed"/>, for integration tests and demos: IDs <c>doc-0</c>,
+    /// <c>doc-1</c>, ..., random embeddings, eight-word documents and the metadata <c>index</c>,
+    /// <c>category</c>, <c>score</c> and <c>flag</c>. The same arguments always produce the same
+    /// records.
+    /// </summary>
+    /// <param name="name">Name of the new collection, in the default tenant and database</param>
+    /// <param name="count">Number of records to generate</param>
+    /// <param name="dimension">Embedding dimension</param>
+    /// <param name="seed">Seed of the generated records</param>
+    /// <param name="embeddingFunction">Embedding function used to query the collection by text</param>
+    public Collection GenerateTestCollection(string name, int count, int dimension, ulong seed = 0, IEmbeddingFunction embeddingFunction = null)
+    {
+        var handle = GetHandleOrThrow();
+
+        var result = NativeMethods.chroma_generate_test_collection(
+            handle,
+            name,
+            (uint)count,
+            (UIntPtr)dimension,
+            seed,
+            out var collectionHandle,
+            out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return new Collection(this, collectionHandle, embeddingFunction);
+    }
+
+    public Collection GetOrCreateCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    {
+        try
+        {
+            return GetCollection(name, embeddingFunction);
+        }
+        catch (ChromaException ex) when (ex.ErrorInfo.Code == ChromaErrorCode.NotFound)
+        {
+            return CreateCollection(name, embeddingFunction, metadata);
+        }
+    }
+
+    /// <summary>
+    /// Runs the same embedding query against several collections opened from this client,
+    /// concurrently inside the native library, e.g. per-tenant or sharded collections
+    /// </summary>
+    /// <returns>One result per collection, in the order the collections were given</returns>
+    public IReadOnlyList<QueryResult> QueryCollections(
+        IReadOnlyList<Collection> collections,
+        float[] queryEmbedding,
+        int nResults = 10,
+        Dictionary<string, object> whereFilter = null,
+        string whereDocument = null,
+        bool includeMetadatas = true,
+        bool includeDocuments = true)
+    {
+        GetHandleOrThrow();
+        return Collection.QueryMulti(collections, queryEmbedding, nResults, whereFilter, whereDocument, includeMetadatas, includeDocuments, false);
+    }
+
+    /// <summary>
+    /// Runs the same embedding query against several collections and merges the matches,
+    /// returning the <paramref name="nResults"/> closest ones over all collections
+    /// </summary>
+    public QueryResult QueryCollectionsMerged(
+        IReadOnlyList<Collection> collections,
+        float[] queryEmbedding,
+        int nResults = 10,
+        Dictionary<string, object> whereFilter = null,
+        string whereDocument = null,
+        bool includeMetadatas = true,
+        bool includeDocuments = true)
+    {
+        GetHandleOrThrow();
+        return Collection.QueryMulti(collections, queryEmbedding, nResults, whereFilter, whereDocument, includeMetadatas, includeDocuments, true)[0];
+    }
+
+    /// <summary>
+    /// Sets how the native library retries transient backend errors for this client
+    /// and every collection opened from it
+    /// </summary>
+    public void SetRetryPolicy(RetryPolicy policy)
+    {
+        var handle = GetHandleOrThrow();
+        var native = (policy ?? RetryPolicy.None).ToNative();
+        var result = NativeMethods.chroma_set_retry_policy(handle, ref native, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Sets the read consistency of this client and every collection opened from it.
+    /// <see cref="ConsistencyMode.Strong"/> applies writes still pending in the log, such as
+    /// writes of another process sharing the persist directory, before each read at the cost
+    /// of a native round trip; read-only clients only support <see cref="ConsistencyMode.Eventual"/>.
+    /// <see cref="ChromaConsistency.Begin"/> overrides the mode for a scope.
+    /// </summary>
+    public void SetConsistency(ConsistencyMode mode)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_set_consistency(handle, (int)mode, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Sets the tenant and database used by calls of this client that do not name one, such as
+    /// <see cref="CreateCollection"/> or <see cref="CreateDatabase"/>; null restores the
+    /// built-in default. The database must already exist. Collections already open keep the
+    /// tenant and database they were opened in.
+    /// </summary>
+    public void SetDefaultTenantDatabase(string? tenant, string? database)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_set_default_tenant_database(handle, tenant, database, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Tenant and database used by calls of this client that do not name one, see
+    /// <see cref="SetDefaultTenantDatabase"/>
+    /// </summary>
+    public (string Tenant, string Database) GetDefaultTenantDatabase()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_get_default_tenant_database(handle, out var tenantPtr, out var databasePtr, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        try
+        {
+            return (Marshal.PtrToStringUTF8(tenantPtr) ?? string.Empty, Marshal.PtrToStringUTF8(databasePtr) ?? string.Empty);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(tenantPtr);
+            NativeMethods.chroma_free_string(databasePtr);
+        }
+    }
+
+    /// <summary>
+    /// Replaces the key the persist directory of this encrypted client is sealed with once the
+    /// client is disposed; afterwards only <paramref name="newKey"/> opens the directory
+    /// </summary>
+    public void RotateEncryptionKey(byte[] newKey)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_rotate_encryption_key(handle, newKey, (UIntPtr)newKey.Length, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Sets the re-ranker used by queries of this client that set <see cref="QueryOptions.RerankQuery"/>
+    /// without their own <see cref="QueryOptions.Reranker"/>, or removes it when null
+    /// </summary>
+    public void SetReranker(IReranker? reranker)
+    {
+        var handle = GetHandleOrThrow();
+        var callback = reranker != null ? RerankerCallback.ToNative(reranker) : null;
+        var result = NativeMethods.chroma_set_reranker(handle, callback, IntPtr.Zero, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        _reranker.Value = callback;
+    }
+
+    /// <summary>
+    /// Sets the authorizer asked before every backend call of this client and the clients and
+    /// collections sharing it, or removes it when null. Denied calls fail with
+    /// <see cref="ChromaErrorCode.PermissionDenied"/>.
+    /// </summary>
+    public void SetAuthorizer(IAuthorizer? authorizer)
+    {
+        var handle = GetHandleOrThrow();
+        var callback = authorizer != null ? AuthorizerCallback.ToNative(authorizer) : null;
+        var result = NativeMethods.chroma_set_authorizer(handle, callback, IntPtr.Zero, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        _authorizer.Value = callback;
+    }
+
+    /// <summary>
+    /// Wraps an embedding function with the persistent embedding cache of this client: documents
+    /// whose text was already embedded by <paramref name="model"/> reuse the stored embedding
+    /// instead of calling <paramref name="embeddingFunction"/> again, so re-ingesting unchanged
+    /// documents is neither recomputed nor re-billed. Change <paramref name="model"/> whenever
+    /// the model behind the function changes.
+    /// </summary>
+    public IEmbeddingFunction WithEmbeddingCache(IEmbeddingFunction embeddingFunction, string model)
+    {
+        if (embeddingFunction == null)
+            throw new ArgumentNullException(nameof(embeddingFunction));
+        if (model == null)
+            throw new ArgumentNullException(nameof(model));
+
+        return new CachedEmbeddingFunction(this, embeddingFunction, model);
+    }
+
+    /// <summary>
+    /// Removes the cached embeddings of <paramref name="model"/>, or of every model when null
+    /// </summary>
+    public void ClearEmbeddingCache(string? model = null)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_embedding_cache_clear(handle, model, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Limits how many reads and writes of this client run at once, so bursts from the thread pool
+    /// queue up instead of contending for the native storage. Calls that find no room within
+    /// <see cref="ConcurrencyLimits.QueueTimeout"/> throw a <see cref="ChromaException"/> with
+    /// <see cref="ChromaErrorCode.Busy"/>. Null removes the limits.
+    /// </summary>
+    public void SetConcurrencyLimits(ConcurrencyLimits? limits)
+    {
+        var handle = GetHandleOrThrow();
+        var native = (limits ?? new ConcurrencyLimits()).ToNative();
+        var result = NativeMethods.chroma_set_concurrency_limits(handle, ref native, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Configures the native ingestion queue that <see cref="Collection.EnqueueAdd"/> fills; null
+    /// restores the defaults. Fewer workers take effect once their current batch is written.
+    /// </summary>
+    public void ConfigureIngestQueue(IngestQueueOptions? options)
+    {
+        var handle = GetHandleOrThrow();
+        var native = (options ?? new IngestQueueOptions()).ToNative();
+        var result = NativeMethods.chroma_configure_ingest_queue(handle, ref native, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Returns the counters of the native ingestion queue without waiting for it
+    /// </summary>
+    public IngestQueueStats GetIngestQueueStats()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_queue_stats(handle, out var stats, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return new IngestQueueStats(stats);
+    }
+
+    /// <summary>
+    /// Waits for every document queued with <see cref="Collection.EnqueueAdd"/> to be written, then
+    /// throws the error of the first add that failed since the last flush, if any. Throws a
+    /// <see cref="ChromaException"/> with <see cref="ChromaErrorCode.Busy"/> when documents are still
+    /// queued after <paramref name="timeout"/>; null waits however long it takes.
+    /// </summary>
+    public void FlushIngestQueue(TimeSpan? timeout = null)
+    {
+        if (timeout < TimeSpan.Zero)
+            throw new ArgumentOutOfRangeException(nameof(timeout));
+
+        var handle = GetHandleOrThrow();
+        var timeoutMs = timeout.HasValue
+            ? (uint)Math.Min(timeout.Value.TotalMilliseconds, uint.MaxValue - 1)
+            : uint.MaxValue;
+        var result = NativeMethods.chroma_queue_flush(handle, timeoutMs, out var errorPtr);
+
+        CheckError(result, errorPtr);
     }
 
-    internal static void CheckError(int errorCode, IntPtr errorPtr)
+    /// <summary>
+    /// Sets the quotas of this client, or of one tenant when <paramref name="tenant"/> is given,
+    /// or removes them when <paramref name="quotas"/> is null. Calls over a quota throw a
+    /// <see cref="ChromaException"/> with <see cref="ChromaErrorCode.QuotaExceeded"/>.
+    /// </summary>
+    public void SetQuotas(Quotas? quotas, string? tenant = null)
     {
-        if (errorCode == 0) // Success
+        var handle = GetHandleOrThrow();
+        int result;
+        IntPtr errorPtr;
+        if (quotas == null)
+        {
+            result = NativeMethods.chroma_set_quotas(handle, tenant, IntPtr.Zero, out errorPtr);
+        }
+        else
+        {
+            var native = quotas.ToNative();
+            result = NativeMethods.chroma_set_quotas(handle, tenant, ref native, out errorPtr);
+        }
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Records every add, update, upsert and delete, and every collection and database created,
+    /// changed or removed through this client, as one JSON line per mutation with its timestamp,
+    /// identity, target and record count. Lines are appended to <paramref name="path"/>, relative
+    /// to the persist directory unless absolute, and passed to <paramref name="callback"/>.
+    /// Passing neither turns the audit log off.
+    /// </summary>
+    public void SetAuditLog(string? path, Action<string>? callback = null)
+    {
+        var handle = GetHandleOrThrow();
+        if (path == null && callback == null)
+        {
+            CheckError(NativeMethods.chroma_set_audit_log(handle, IntPtr.Zero, out var clearError), clearError);
+            _auditLog.Value = null;
             return;
+        }
+
+        NativeMethods.AuditCallback? native = null;
+        if (callback != null)
+            native = (_, entry) => callback(Marshal.PtrToStringUTF8(entry) ?? string.Empty);
 
+        var pathPtr = path != null ? Marshal.StringToCoTaskMemUTF8(path) : IntPtr.Zero;
         try
         {
-            var errorInfo = MarshalError(errorPtr);
-            throw new ChromaException(errorInfo);
+            var options = new ChromaAuditLogOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaAuditLogOptionsNative>(),
+                Path = pathPtr,
+                Callback = native != null ? Marshal.GetFunctionPointerForDelegate(native) : IntPtr.Zero
+            };
+            var result = NativeMethods.chroma_set_audit_log(handle, ref options, out var errorPtr);
+
+            CheckError(result, errorPtr);
+            _auditLog.Value = native;
         }
         finally
         {
-            if (errorPtr != IntPtr.Zero)
-                NativeMethods.chroma_free_error(errorPtr);
+            if (pathPtr != IntPtr.Zero)
+                Marshal.FreeCoTaskMem(pathPtr);
         }
     }
 
     /// <summary>
-    /// Creates a new ChromaDB client
+    /// Sets the identity, as JSON, that the authorizer sees for calls made through this reference
+    /// and the collections opened from it afterwards, or clears it when null. Give each principal
+    /// its own reference with <see cref="Retain"/>.
     /// </summary>
-    /// <param name="persistDirectory">Directory for persisting data</param>
-    /// <param name="hnswCacheSize">Size of the HNSW index cache</param>
-    public ChromaClient(string? persistDirectory = null, int hnswCacheSize = 1000)
-    {
-        var result = NativeMethods.chroma_create_client(
-            false,
-            IntPtr.Zero,
-            (UIntPtr)hnswCacheSize,
-            persistDirectory,
-            out _handle,
-            out var errorPtr);
+    public void SetIdentity(string? principalJson)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_set_identity(handle, principalJson, out var errorPtr);
 
         CheckError(result, errorPtr);
     }
 
-    public Collection CreateCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    /// <summary>
+    /// Reads a blob stored with <see cref="Collection.PutBlobs"/> by its URI
+    /// </summary>
+    public byte[] GetBlob(string uri)
     {
-        return CreateOrGetCollection(name, false, embeddingFunction, metadata);
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_get_blob(handle, uri, out var bufferPtr, out var length, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return Collection.CopyAndFreeBuffer(bufferPtr, length);
     }
 
-    public Collection CreateOrGetCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    /// <summary>
+    /// Returns per-operation call counts, error counts and latency percentiles
+    /// recorded by the native library for this client, as JSON
+    /// </summary>
+    public string GetMetricsJson()
     {
-        return CreateOrGetCollection(name, true, embeddingFunction, metadata);
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_get_metrics_json(handle, out var jsonPtr, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        try
+        {
+            return Marshal.PtrToStringAnsi(jsonPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(jsonPtr);
+        }
     }
 
-    private Collection CreateOrGetCollection(string name, bool getOrCreate, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    /// <summary>
+    /// Forces the pending writes of every collection to disk and returns once they are durable:
+    /// the write log is applied to the local segments and the SQLite WAL is checkpointed.
+    /// Use <see cref="Collection.Flush"/> to flush a single collection.
+    /// </summary>
+    public void Flush()
     {
         var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_flush(handle, 0, out var errorPtr);
 
-        string configJson = null;
-        if (embeddingFunction != null)
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Flushes every collection in the background at the given interval, as <see cref="Flush"/>
+    /// does; <see cref="TimeSpan.Zero"/> stops automatic flushing. Failures are counted under
+    /// <c>flush</c> in <see cref="GetMetricsJson"/>.
+    /// </summary>
+    public void SetAutoFlushInterval(TimeSpan interval)
+    {
+        if (interval < TimeSpan.Zero || interval.TotalMilliseconds > uint.MaxValue)
+            throw new ArgumentOutOfRangeException(nameof(interval));
+
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_set_auto_flush_interval(handle, (uint)interval.TotalMilliseconds, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Clears the metrics recorded for this client
+    /// </summary>
+    public void ResetMetrics()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_reset_metrics(handle, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Returns the configuration the native client is running with, as JSON.
+    /// Passwords, secrets, tokens and URL credentials are redacted.
+    /// </summary>
+    public string GetEffectiveConfigJson()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_dump_effective_config(handle, out var jsonPtr, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        try
         {
-            configJson = JsonSerializer.Serialize(embeddingFunction.Configuration);
+            return Marshal.PtrToStringAnsi(jsonPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(jsonPtr);
         }
+    }
 
-        string metadataJson = metadata != null ? JsonSerializer.Serialize(metadata) : null;
+    /// <summary>
+    /// Returns the disk usage of the persist directory as JSON, broken down into the
+    /// SQLite database, its WAL and one entry per segment directory
+    /// </summary>
+    public string GetStorageInfoJson()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_get_storage_info(handle, out var jsonPtr, out var errorPtr);
 
-        var result = NativeMethods.chroma_create_collection(
-            handle,
-            name,
-            configJson,
-            metadataJson,
-            getOrCreate,
-            null,
-            null,
-            out var collectionHandle,
-            out var errorPtr);
+        CheckError(result, errorPtr);
+
+        try
+        {
+            return Marshal.PtrToStringAnsi(jsonPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(jsonPtr);
+        }
+    }
+
+    /// <summary>
+    /// Reports whether the database in a persist directory is fully migrated, as JSON, without
+    /// opening a client; use it to gate startup before creating a client with
+    /// <see cref="SqliteOptions.ValidateMigrations"/>
+    /// </summary>
+    public static string GetMigrationStatusJson(string persistDirectory)
+    {
+        var result = NativeMethods.chroma_get_migration_status(persistDirectory, IntPtr.Zero, out var jsonPtr, out var errorPtr);
 
         CheckError(result, errorPtr);
 
-        return new Collection(this, collectionHandle, embeddingFunction);
+        try
+        {
+            return Marshal.PtrToStringAnsi(jsonPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(jsonPtr);
+        }
     }
 
-    public Collection GetCollection(string name, IEmbeddingFunction embeddingFunction = null)
+    /// <summary>
+    /// Sets the 32-byte AES-256 key used by clients created afterwards without their own
+    /// <c>encryptionKey</c>, including <see cref="FromConfig"/> clients; pass null to clear it.
+    /// Clients already created keep their key.
+    /// </summary>
+    public static void SetDefaultEncryptionKey(byte[]? key)
     {
-        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_set_encryption_key(key, (UIntPtr)(key?.Length ?? 0), out var errorPtr);
 
-        var result = NativeMethods.chroma_get_collection(
-            handle,
-            name,
-            null,
-            null,
-            out var collectionHandle,
-            out var errorPtr);
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Seals the files an encrypted persist directory was left with in plaintext because its
+    /// client was never disposed, e.g. after a crash, without opening a client. Fails with
+    /// <see cref="ChromaErrorCode.ConcurrentAccess"/> while a client created with
+    /// <see cref="LockOptions"/> has the directory open (clients without a lock are not
+    /// detected), and with <see cref="ChromaErrorCode.InvalidArgument"/> if it is sealed with
+    /// another key.
+    /// </summary>
+    public static void SealDirectory(string persistDirectory, byte[] key)
+    {
+        var result = NativeMethods.chroma_seal_directory(persistDirectory, key, (UIntPtr)key.Length, out var errorPtr);
 
         CheckError(result, errorPtr);
+    }
 
-        return new Collection(this, collectionHandle, embeddingFunction);
+    /// <summary>
+    /// Writes the C header of the loaded native library, generated from its sources with
+    /// cbindgen, e.g. to check the P/Invoke declarations against it or generate them
+    /// </summary>
+    public static void EmitHeader(string path)
+    {
+        var result = NativeMethods.chroma_emit_header(path, out var errorPtr);
+
+        CheckError(result, errorPtr);
     }
 
-    public Collection GetOrCreateCollection(string name, IEmbeddingFunction embeddingFunction = null, Dictionary<string, object> metadata = null)
+    /// <summary>
+    /// Checks a metadata filter and a document filter given as JSON without running them, e.g.
+    /// before a delete with a user-supplied filter. Returns both filters as one JSON object with
+    /// shorthand equality written as <c>$eq</c> and multi-key objects as <c>$and</c>; an invalid
+    /// filter throws a <see cref="ChromaException"/> whose details give the path of the
+    /// rejected part, such as <c>where.$and[1].year</c>.
+    /// </summary>
+    public static string ValidateFilter(string? whereJson, string? whereDocument = null)
+    {
+        var result = NativeMethods.chroma_validate_filter(whereJson, whereDocument, out var jsonPtr, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        try
+        {
+            return Marshal.PtrToStringAnsi(jsonPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(jsonPtr);
+        }
+    }
+
+    /// <summary>
+    /// Checks a collection name against the naming rules applied on create without creating
+    /// anything, e.g. to validate user input. An invalid name throws a
+    /// <see cref="ChromaException"/> with <see cref="ChromaErrorCode.ValidationError"/> whose
+    /// details describe the rule it breaks, such as a disallowed character and its position.
+    /// </summary>
+    public static void ValidateCollectionName(string name)
+    {
+        var result = NativeMethods.chroma_validate_collection_name(name, out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Checks SQLite connectivity, persist directory writability, the index cache and the
+    /// executor, and returns a pass/warn/fail report as JSON, e.g. for a readiness probe
+    /// </summary>
+    public string GetHealthReportJson()
     {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_health_check(handle, out var jsonPtr, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
         try
         {
-            return GetCollection(name, embeddingFunction);
+            return Marshal.PtrToStringAnsi(jsonPtr);
         }
-        catch (ChromaException ex) when (ex.ErrorInfo.Code == ChromaErrorCode.NotFound)
+        finally
         {
-            return CreateCollection(name, embeddingFunction, metadata);
+            NativeMethods.chroma_free_string(jsonPtr);
         }
     }
 
+    /// <summary>
+    /// Loads the vector index of every collection in a database into the cache
+    /// </summary>
+    /// <returns>The number of collections preloaded; collections without embeddings are skipped</returns>
+    public int PreloadAll(string tenant = null, string database = null)
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_preload_all(handle, tenant, database, out var preloaded, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        return (int)preloaded;
+    }
+
     public ulong Heartbeat()
     {
         var handle = GetHandleOrThrow();
@@ -194,6 +1158,123 @@ public class ChromaClient : IDisposable
         CheckError(result, errorPtr);
     }
 
+    /// <summary>
+    /// Destroys every native client and collection, e.g. when the AppDomain or
+    /// AssemblyLoadContext hosting the library is unloaded. Existing instances
+    /// become unusable and report an invalid handle on further calls.
+    /// </summary>
+    public static void DestroyAll()
+    {
+        var result = NativeMethods.chroma_destroy_all(out var errorPtr);
+
+        CheckError(result, errorPtr);
+    }
+
+    /// <summary>
+    /// Forwards the log records of the native library and the Chroma backend up to
+    /// <paramref name="maxLevel"/> to <paramref name="callback"/>, which receives the level, the
+    /// emitting module and the message (<c>chroma_register_log_callback</c>). The callback runs on
+    /// the thread that logged and must not throw. Dispose the returned registration to stop it;
+    /// the callback is never invoked after <see cref="CallbackRegistration.Dispose"/> returns.
+    /// </summary>
+    public static unsafe CallbackRegistration RegisterLogCallback(
+        Action<ChromaLogLevel, string, string> callback,
+        ChromaLogLevel maxLevel = ChromaLogLevel.Info)
+    {
+        if (callback == null)
+            throw new ArgumentNullException(nameof(callback));
+
+        // A function pointer instead of a delegate, so nothing the GC may collect is handed to
+        // the library and the callback also works under NativeAOT
+        delegate* unmanaged[Cdecl]<IntPtr, int, IntPtr, IntPtr, void> onLog = &OnLog;
+        var state = GCHandle.Alloc(callback);
+        var result = NativeMethods.chroma_register_log_callback(
+            (IntPtr)onLog,
+            GCHandle.ToIntPtr(state),
+            (int)maxLevel,
+            out var callbackId,
+            out var errorPtr);
+
+        if (result != 0)
+        {
+            state.Free();
+            CheckError(result, errorPtr);
+        }
+        return new CallbackRegistration(callbackId, state);
+    }
+
+    [UnmanagedCallersOnly(CallConvs = new[] { typeof(CallConvCdecl) })]
+    private static void OnLog(IntPtr userData, int level, IntPtr target, IntPtr message)
+    {
+        try
+        {
+            var callback = (Action<ChromaLogLevel, string, string>)GCHandle.FromIntPtr(userData).Target!;
+            callback((ChromaLogLevel)level, Marshal.PtrToStringUTF8(target) ?? "", Marshal.PtrToStringUTF8(message) ?? "");
+        }
+        catch
+        {
+            // Exceptions cannot cross into native code; a failing log sink drops the record
+        }
+    }
+
+    /// <summary>
+    /// Returns the live native handles of one kind (0 = clients, 1 = collections) for diagnostics
+    /// </summary>
+    internal static ulong[] ListHandles(int kind)
+    {
+        var result = NativeMethods.chroma_list_handles(kind, out var handlesPtr, out var count, out var errorPtr);
+
+        CheckError(result, errorPtr);
+
+        try
+        {
+            var handles = new long[(int)count];
+            if (handles.Length > 0)
+                Marshal.Copy(handlesPtr, handles, 0, handles.Length);
+            return Array.ConvertAll(handles, h => (ulong)h);
+        }
+        finally
+        {
+            NativeMethods.chroma_free(handlesPtr);
+        }
+    }
+
+    /// <summary>
+    /// Shuts the client down gracefully: new operations are rejected, operations already running
+    /// get up to <paramref name="drainTimeout"/> to finish before they are aborted, pending writes
+    /// are flushed unless the client is read-only, and the client and the native handles of its
+    /// collections are released. Returns how many operations were aborted.
+    /// </summary>
+    public int Shutdown(TimeSpan drainTimeout)
+    {
+        if (drainTimeout < TimeSpan.Zero || drainTimeout.TotalMilliseconds > uint.MaxValue)
+            throw new ArgumentOutOfRangeException(nameof(drainTimeout));
+
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
+            throw new ObjectDisposedException(nameof(ChromaClient));
+        GC.SuppressFinalize(this);
+
+        ChromaRequestContext.ApplyToCurrentThread();
+        var result = NativeMethods.chroma_shutdown_client(handle, (uint)drainTimeout.TotalMilliseconds, out var aborted, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return (int)aborted;
+    }
+
+    /// <summary>
+    /// Begins a batch of writes to one collection, applied atomically by
+    /// <see cref="ChromaBatch.Commit"/>, e.g. to write a document together with its chunks
+    /// </summary>
+    public ChromaBatch BeginBatch()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_batch_begin(handle, out var batchHandle, out var errorPtr);
+
+        CheckError(result, errorPtr);
+        return new ChromaBatch(batchHandle);
+    }
+
     public void Dispose()
     {
         Dispose(true);
@@ -202,8 +1283,8 @@ public class ChromaClient : IDisposable
 
     protected virtual void Dispose(bool disposing)
     {
-        var handle = Interlocked.Exchange(ref _handle, IntPtr.Zero);
-        if (handle == IntPtr.Zero)
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
             return;
 
         var result = NativeMethods.chroma_destroy_client(handle, out var errorPtr);
@@ -227,11 +1308,17 @@ public class ChromaClient : IDisposable
         Dispose(false);
     }
 
-    internal IntPtr GetHandleOrThrow()
+    internal ulong GetHandleOrThrow()
     {
         var handle = Volatile.Read(ref _handle);
-        if (handle == IntPtr.Zero)
+        if (handle == 0)
             throw new ObjectDisposedException(nameof(ChromaClient));
+
+        // Every operation fetches a handle first, so this is where the request ID and
+        // consistency scope of the caller's flow are handed to the thread about to make the
+        // native call
+        ChromaRequestContext.ApplyToCurrentThread();
+        ChromaConsistency.ApplyToCurrentThread();
         return handle;
     }
 }
@@ -0,0 +1,66 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Overrides the read consistency of the ChromaDB operations made within a scope, whatever
+/// client they go through, e.g. to make a single query after an add strongly consistent
+/// without changing the mode of a shared client.
+/// </summary>
+/// <remarks>
+/// The scope flows with the async context like <see cref="AsyncLocal{T}"/>, so it covers
+/// operations made after an <c>await</c> on another thread.
+/// </remarks>
+public static class ChromaConsistency
+{
+    private const int ClientDefault = -1;
+
+    private static readonly AsyncLocal<ConsistencyMode?> _current = new();
+
+    // Mode last handed to the native library on this thread
+    [ThreadStatic]
+    private static ConsistencyMode? _applied;
+
+    /// <summary>Mode of the current scope, or null outside any scope</summary>
+    public static ConsistencyMode? Current => _current.Value;
+
+    /// <summary>
+    /// Starts a scope in which reads use <paramref name="mode"/>; disposing it restores the
+    /// mode of the enclosing scope
+    /// </summary>
+    public static IDisposable Begin(ConsistencyMode mode)
+    {
+        var scope = new Scope(_current.Value);
+        _current.Value = mode;
+        return scope;
+    }
+
+    internal static void ApplyToCurrentThread()
+    {
+        var mode = _current.Value;
+        if (mode == _applied)
+            return;
+
+        var result = NativeMethods.chroma_set_thread_consistency(mode.HasValue ? (int)mode.Value : ClientDefault, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+        _applied = mode;
+    }
+
+    private sealed class Scope : IDisposable
+    {
+        private readonly ConsistencyMode? _previous;
+        private bool _disposed;
+
+        public Scope(ConsistencyMode? previous)
+        {
+            _previous = previous;
+        }
+
+        public void Dispose()
+        {
+            if (_disposed)
+                return;
+
+            _disposed = true;
+            _current.Value = _previous;
+        }
+    }
+}
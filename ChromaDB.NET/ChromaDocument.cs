@@ -31,6 +31,18 @@ public class ChromaDocument
     /// </summary>
     public string Text { get; set; }
 
+    /// <summary>
+    /// Optional binary attachment, such as an image, stored as a file under the client's
+    /// persist path and recorded as the document's URI when the document is added
+    /// </summary>
+    public byte[] Blob { get; set; }
+
+    /// <summary>
+    /// URI recorded on the document, filled in by <see cref="Collection.GetOne"/> when
+    /// <see cref="Include.Uris"/> is requested
+    /// </summary>
+    public string Uri { get; set; }
+
     /// <summary>
     /// Creates a new document with the specified ID and text
     /// </summary>
@@ -29,5 +29,32 @@ public enum ChromaErrorCode
     InvalidUuid = 6,
 
     /// <summary>Operation not implemented</summary>
-    NotImplemented = 7
+    NotImplemented = 7,
+
+    /// <summary>Embedding dimension does not match the collection dimension</summary>
+    DimensionMismatch = 8,
+
+    /// <summary>Mutation attempted on a client opened in read-only mode</summary>
+    ReadOnly = 9,
+
+    /// <summary>Persist directory is locked by another client</summary>
+    ConcurrentAccess = 10,
+
+    /// <summary>Database migrations are pending and the client was asked only to validate them</summary>
+    MigrationsPending = 11,
+
+    /// <summary>A record changed since the version the caller expected</summary>
+    ConflictError = 12,
+
+    /// <summary>The authorizer set with <see cref="ChromaClient.SetAuthorizer"/> or the backend denied the call</summary>
+    PermissionDenied = 13,
+
+    /// <summary>The call would exceed a quota set with <see cref="ChromaClient.SetQuotas"/></summary>
+    QuotaExceeded = 14,
+
+    /// <summary>The limits set with <see cref="ChromaClient.SetConcurrencyLimits"/> or the backend left no room for the call in time; retry later</summary>
+    Busy = 15,
+
+    /// <summary>The collection was made read-only with <see cref="Collection.SetReadOnly"/></summary>
+    CollectionReadOnly = 16
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 using System;
+using System.Collections.Generic;
 
 namespace ChromaDB.NET;
 
@@ -19,12 +20,41 @@ public class ChromaErrorInfo
     /// <summary>Additional error details</summary>
     public string Details { get; }
 
+    /// <summary>Whether retrying the same operation may succeed</summary>
+    public bool Retryable { get; }
+
+    /// <summary>Messages of the backend error and its causes, outermost first</summary>
+    public IReadOnlyList<string> Chain { get; }
+
+    /// <summary>Machine-readable JSON form of the error, for log aggregators</summary>
+    public string Json { get; }
+
+    /// <summary>Request ID in effect when the error occurred, see <see cref="ChromaRequestContext"/></summary>
+    public string RequestId { get; }
+
     internal ChromaErrorInfo(ChromaErrorCode code, string message, string source, string details)
+        : this(code, message, source, details, false, null, null, null)
+    {
+    }
+
+    internal ChromaErrorInfo(
+        ChromaErrorCode code,
+        string message,
+        string source,
+        string details,
+        bool retryable,
+        IReadOnlyList<string> chain,
+        string json,
+        string requestId)
     {
         Code = code;
         Message = message ?? string.Empty;
         Source = source ?? string.Empty;
         Details = details ?? string.Empty;
+        Retryable = retryable;
+        Chain = chain ?? Array.Empty<string>();
+        Json = json ?? string.Empty;
+        RequestId = requestId ?? string.Empty;
     }
 
     /// <summary>
@@ -38,6 +68,9 @@ public class ChromaErrorInfo
         if (!string.IsNullOrEmpty(Source))
             builder.Append($" (in {Source})");
 
+        if (!string.IsNullOrEmpty(RequestId))
+            builder.Append($" [request {RequestId}]");
+
         if (!string.IsNullOrEmpty(Details))
             builder.Append($"\nDetails: {Details}");
 
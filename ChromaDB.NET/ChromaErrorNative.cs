@@ -16,4 +16,9 @@ internal struct ChromaErrorNative
     public IntPtr Message;
     public IntPtr Source;
     public IntPtr Details;
+    [MarshalAs(UnmanagedType.I1)]
+    public bool Retryable;
+    public IntPtr Chain;
+    public UIntPtr ChainCount;
+    public IntPtr RequestId;
 }
\ No newline at end of file
@@ -0,0 +1,22 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Level of a log record of the native library, from least to most verbose
+/// </summary>
+public enum ChromaLogLevel
+{
+    /// <summary>Failures</summary>
+    Error = 1,
+
+    /// <summary>Unexpected conditions the library recovered from</summary>
+    Warn = 2,
+
+    /// <summary>Notable events such as migrations and client creation</summary>
+    Info = 3,
+
+    /// <summary>Diagnostics of individual operations</summary>
+    Debug = 4,
+
+    /// <summary>Detailed tracing of the backend</summary>
+    Trace = 5
+}
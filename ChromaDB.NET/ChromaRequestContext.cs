@@ -0,0 +1,67 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Attaches a request or correlation ID, such as the ID of the HTTP request being served, to
+/// the ChromaDB operations made within a scope. The ID is recorded on the native tracing span
+/// of every backend call and on errors, as <see cref="ChromaErrorInfo.RequestId"/>.
+/// </summary>
+/// <remarks>
+/// The scope flows with the async context like <see cref="AsyncLocal{T}"/>, so it covers
+/// operations made after an <c>await</c> on another thread.
+/// </remarks>
+public static class ChromaRequestContext
+{
+    private static readonly AsyncLocal<string?> _current = new();
+
+    // Request ID last handed to the native library on this thread
+    [ThreadStatic]
+    private static string? _applied;
+
+    /// <summary>Request ID of the current scope, or null outside any scope</summary>
+    public static string? Current => _current.Value;
+
+    /// <summary>
+    /// Starts a scope in which operations carry <paramref name="requestId"/>; disposing it
+    /// restores the ID of the enclosing scope
+    /// </summary>
+    public static IDisposable Begin(string requestId)
+    {
+        if (requestId == null)
+            throw new ArgumentNullException(nameof(requestId));
+
+        var scope = new Scope(_current.Value);
+        _current.Value = requestId;
+        return scope;
+    }
+
+    internal static void ApplyToCurrentThread()
+    {
+        var requestId = _current.Value;
+        if (requestId == _applied)
+            return;
+
+        var result = NativeMethods.chroma_set_request_id(requestId, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+        _applied = requestId;
+    }
+
+    private sealed class Scope : IDisposable
+    {
+        private readonly string? _previous;
+        private bool _disposed;
+
+        public Scope(string? previous)
+        {
+            _previous = previous;
+        }
+
+        public void Dispose()
+        {
+            if (_disposed)
+                return;
+
+            _disposed = true;
+            _current.Value = _previous;
+        }
+    }
+}
@@ -0,0 +1,81 @@
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Version of the UUIDs generated by <see cref="ChromaUuid.New"/>
+/// </summary>
+public enum UuidVersion
+{
+    /// <summary>Random UUID</summary>
+    V4 = 4,
+
+    /// <summary>UUID starting with a millisecond timestamp, so IDs generated in order also sort in order</summary>
+    V7 = 7
+}
+
+/// <summary>
+/// Generates and checks UUIDs in the native library, so IDs use the hyphenated lowercase
+/// form it stores and returns rather than one of the formats of <see cref="Guid.ToString(string)"/>
+/// </summary>
+public static class ChromaUuid
+{
+    /// <summary>
+    /// Generates a UUID in hyphenated lowercase form, e.g. <c>67e55044-10b1-426f-9247-bb680e5fe0c8</c>
+    /// </summary>
+    public static string New(UuidVersion version = UuidVersion.V4)
+    {
+        var result = NativeMethods.chroma_new_uuid((int)version, out var idPtr, out var errorPtr);
+
+        ChromaClient.CheckError(result, errorPtr);
+
+        try
+        {
+            return Marshal.PtrToStringAnsi(idPtr) ?? string.Empty;
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(idPtr);
+        }
+    }
+
+    /// <summary>
+    /// Checks that <paramref name="value"/> is a UUID the native library accepts as an ID,
+    /// in hyphenated, simple, braced or <c>urn:uuid:</c> form, and returns it in the
+    /// hyphenated lowercase form the library stores and returns. Anything else throws a
+    /// <see cref="ChromaException"/> with <see cref="ChromaErrorCode.InvalidUuid"/>.
+    /// </summary>
+    public static string Normalize(string value)
+    {
+        var result = NativeMethods.chroma_validate_uuid(value, out var canonicalPtr, out var errorPtr);
+
+        ChromaClient.CheckError(result, errorPtr);
+
+        try
+        {
+            return Marshal.PtrToStringAnsi(canonicalPtr) ?? string.Empty;
+        }
+        finally
+        {
+            NativeMethods.chroma_free_string(canonicalPtr);
+        }
+    }
+
+    /// <summary>
+    /// Whether <paramref name="value"/> is a UUID the native library accepts as an ID, see
+    /// <see cref="Normalize"/>
+    /// </summary>
+    public static bool IsValid(string value)
+    {
+        var result = NativeMethods.chroma_validate_uuid(value, out var canonicalPtr, out var errorPtr);
+        if (result == (int)ChromaErrorCode.InvalidUuid)
+        {
+            NativeMethods.chroma_free_error(errorPtr);
+            return false;
+        }
+
+        ChromaClient.CheckError(result, errorPtr);
+        NativeMethods.chroma_free_string(canonicalPtr);
+        return true;
+    }
+}
@@ -1,6 +1,7 @@
 using System;
 using System.Collections.Generic;
 using System.Linq;
+using System.Runtime.CompilerServices;
 using System.Runtime.InteropServices;
 using System.Text.Json;
 using System.Threading;
@@ -13,7 +14,7 @@ namespace ChromaDB.NET
     /// </summary>
     public class Collection : IDisposable
     {
-        private IntPtr _handle;
+        private ulong _handle;
         private readonly ChromaClient _client;
         private readonly IEmbeddingFunction _embeddingFunction;
 
@@ -22,50 +23,1825 @@ namespace ChromaDB.NET
             Converters = { new WhereFilterConverter() }
         };
 
-        internal Collection(ChromaClient client, IntPtr handle, IEmbeddingFunction embeddingFunction)
+        internal Collection(ChromaClient client, ulong handle, IEmbeddingFunction embeddingFunction)
         {
             _client = client;
             _handle = handle;
             _embeddingFunction = embeddingFunction;
         }
 
-        private (IntPtr clientHandle, IntPtr collectionHandle) GetHandlesOrThrow()
+        /// <summary>
+        /// When true, metadata is returned as typed native values (string, long, double, bool)
+        /// instead of being parsed from JSON into <see cref="JsonElement"/> values
+        /// </summary>
+        public bool TypedMetadata { get; set; }
+
+        /// <summary>
+        /// Gets the UUID of the collection, which can be persisted and passed to
+        /// <see cref="ChromaClient.GetCollectionById"/> later
+        /// </summary>
+        public string Id
+        {
+            get
+            {
+                var collectionHandle = GetHandleOrThrow();
+                var result = NativeMethods.chroma_collection_get_id(collectionHandle, out var idPtr, out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return Marshal.PtrToStringAnsi(idPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_string(idPtr);
+                }
+            }
+        }
+
+        /// <summary>
+        /// Gets or sets the fields returned when a query or get leaves an include flag null;
+        /// metadatas, documents and distances until set. Setting <see cref="Include.Default"/>
+        /// restores those
+        /// </summary>
+        public Include DefaultIncludes
+        {
+            get
+            {
+                var collectionHandle = GetHandleOrThrow();
+                var result = NativeMethods.chroma_collection_get_default_includes(collectionHandle, out var flags, out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+                return (Include)flags;
+            }
+            set
+            {
+                var collectionHandle = GetHandleOrThrow();
+                var result = NativeMethods.chroma_collection_set_default_includes(collectionHandle, (uint)value, out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+        }
+
+        private int MetadataFormat => TypedMetadata ? MetadataFormatTyped : MetadataFormatJson;
+
+        private ulong GetHandleOrThrow()
+        {
+            // Native calls only take the collection handle, but a collection is
+            // unusable once the client it was opened from is disposed
+            _client.GetHandleOrThrow();
+
+            var handle = Volatile.Read(ref _handle);
+            if (handle == 0)
+                throw new ObjectDisposedException(nameof(Collection));
+            return handle;
+        }
+
+        /// <summary>
+        /// Gets the number of documents in the collection
+        /// </summary>
+        /// <returns>The document count</returns>
+        public uint Count()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_count(
+                collectionHandle,
+                out uint count,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return count;
+        }
+
+        /// <summary>
+        /// Gets the number of documents matching the filters without loading them, e.g. to
+        /// show the page count of a filtered list
+        /// </summary>
+        /// <param name="whereFilter">Optional metadata filter</param>
+        /// <param name="whereDocument">Optional document content filter</param>
+        /// <returns>The number of matching documents</returns>
+        public uint Count(Dictionary<string, object>? whereFilter, string? whereDocument = null)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            string whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            var result = NativeMethods.chroma_count_where(
+                collectionHandle,
+                whereFilterJson,
+                whereDocument,
+                out uint count,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return count;
+        }
+
+        /// <summary>
+        /// Checks which of the given IDs exist, without loading the documents
+        /// </summary>
+        /// <returns>One flag per ID, in the order the IDs were given; soft-deleted documents count as missing</returns>
+        public bool[] Exists(IEnumerable<string> ids)
+        {
+            var idsArray = ids.ToArray();
+            var exists = new bool[idsArray.Length];
+            if (idsArray.Length == 0)
+                return exists;
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
+
+            try
+            {
+                var result = NativeMethods.chroma_records_exist(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    exists,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+                return exists;
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+            }
+        }
+
+        /// <summary>
+        /// Checks whether a document exists, without loading it
+        /// </summary>
+        public bool Exists(string id)
+        {
+            return Exists(new[] { id })[0];
+        }
+
+        /// <summary>
+        /// Stores binary attachments for existing documents as files under the client's persist
+        /// path, recording each file's URI as the document's URI and replacing any previous blob
+        /// </summary>
+        /// <param name="ids">IDs of the documents; IDs that do not exist are skipped</param>
+        /// <param name="blobs">One blob per ID, or null to leave a document's blob unchanged</param>
+        /// <returns>The URI of each blob, or an empty string for skipped IDs and blobs</returns>
+        public string[] PutBlobs(IEnumerable<string> ids, IEnumerable<byte[]?> blobs)
+        {
+            var idsArray = ids.ToArray();
+            var blobsArray = blobs.ToArray();
+            if (blobsArray.Length != idsArray.Length)
+                throw new ArgumentException("Blobs must have one entry per ID", nameof(blobs));
+            if (idsArray.Length == 0)
+                return Array.Empty<string>();
+
+            var collectionHandle = GetHandleOrThrow();
+            var handles = new List<GCHandle>();
+            var idsPtr = MarshalStringArray(idsArray);
+            var urisPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var nativeBlobs = blobsArray.Select(blob =>
+                {
+                    if (blob == null)
+                        return new ChromaBlobNative();
+
+                    var handle = GCHandle.Alloc(blob, GCHandleType.Pinned);
+                    handles.Add(handle);
+                    return new ChromaBlobNative { Data = handle.AddrOfPinnedObject(), Len = (UIntPtr)blob.Length };
+                }).ToArray();
+
+                var result = NativeMethods.chroma_put_blobs(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    nativeBlobs,
+                    urisPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(urisPtr).ToArray();
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(urisPtr);
+                }
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+                Marshal.FreeHGlobal(urisPtr);
+                foreach (var handle in handles)
+                    handle.Free();
+            }
+        }
+
+        /// <summary>
+        /// Reads the URIs of documents, such as those recorded for their blobs
+        /// </summary>
+        /// <returns>One URI per ID, or an empty string for missing documents and documents without one</returns>
+        public string[] GetUris(IEnumerable<string> ids)
+        {
+            var idsArray = ids.ToArray();
+            if (idsArray.Length == 0)
+                return Array.Empty<string>();
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
+            var urisPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var result = NativeMethods.chroma_get_uris(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    urisPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(urisPtr).ToArray();
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(urisPtr);
+                }
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+                Marshal.FreeHGlobal(urisPtr);
+            }
+        }
+
+        /// <summary>
+        /// Reads the blob stored for a document
+        /// </summary>
+        /// <returns>The blob, or null if the document does not exist or has no blob</returns>
+        public byte[]? GetBlob(string id)
+        {
+            var uri = GetUris(new[] { id })[0];
+            return string.IsNullOrEmpty(uri) ? null : _client.GetBlob(uri);
+        }
+
+        /// <summary>
+        /// Forces the pending writes of this collection to disk and returns once they are durable,
+        /// e.g. before acknowledging an upstream message
+        /// </summary>
+        public void Flush()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_flush(_client.GetHandleOrThrow(), collectionHandle, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Compacts pending writes into the collection's local segments and purges the
+        /// compacted log, e.g. from a job scheduled during quiet hours after bursty ingestion
+        /// </summary>
+        public void Optimize()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_optimize_collection(collectionHandle, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Loads the collection's vector index into the cache, so the first query after
+        /// service start does not pay for reading it from disk
+        /// </summary>
+        public void Preload()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_preload_collection(collectionHandle, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Sets how long documents live: a document expires <paramref name="ttl"/> after the Unix
+        /// time in seconds stored under <paramref name="timestampKey"/> in its metadata. Documents
+        /// without that key never expire. <see cref="TimeSpan.Zero"/> removes the policy.
+        /// </summary>
+        public void SetTtl(TimeSpan ttl, string timestampKey = "created_at")
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_set_collection_ttl(collectionHandle, (uint)ttl.TotalSeconds, timestampKey, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Deletes the documents whose TTL has passed, e.g. from a timer in a cache or session store
+        /// </summary>
+        /// <returns>The number of documents deleted</returns>
+        public int PurgeExpired()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_purge_expired(collectionHandle, out var purged, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return (int)purged.ToUInt64();
+        }
+
+        /// <summary>
+        /// Declares the metadata keys used in where filters of this collection. The declaration is
+        /// stored in the collection metadata; filtered <c>Get</c> and <c>Query</c> calls log a
+        /// warning for other keys. An empty list removes the declaration.
+        /// </summary>
+        public void SetFilterableFields(IEnumerable<string> keys)
+        {
+            if (keys == null)
+                throw new ArgumentNullException(nameof(keys));
+
+            var keysArray = keys.ToArray();
+            var collectionHandle = GetHandleOrThrow();
+            var keysPtr = keysArray.Length > 0 ? MarshalStringArray(keysArray) : IntPtr.Zero;
+
+            try
+            {
+                var result = NativeMethods.chroma_set_filterable_fields(
+                    collectionHandle,
+                    keysPtr,
+                    (UIntPtr)keysArray.Length,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                if (keysPtr != IntPtr.Zero)
+                    FreeStringArray(keysPtr, keysArray.Length);
+            }
+        }
+
+        /// <summary>
+        /// Gets the declared filterable metadata keys, sorted; empty when none are declared
+        /// </summary>
+        public List<string> GetFilterableFields()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var keysPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var result = NativeMethods.chroma_get_filterable_fields(collectionHandle, keysPtr, out var errorPtr);
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(keysPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(keysPtr);
+                }
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(keysPtr);
+            }
+        }
+
+        /// <summary>
+        /// Attaches a metadata schema to this collection, or removes it when <paramref name="fields"/>
+        /// is null. Adds, updates, upserts and metadata patches are then validated natively and fail
+        /// with <see cref="ChromaErrorCode.ValidationError"/> naming each offending
+        /// <c>metadatas[index].key</c>; keys the schema does not declare are not checked.
+        /// </summary>
+        public void SetMetadataSchema(IReadOnlyDictionary<string, MetadataField>? fields)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var schemaJson = fields != null
+                ? JsonSerializer.Serialize(fields.ToDictionary(
+                    f => f.Key,
+                    f => new Dictionary<string, object>
+                    {
+                        ["type"] = f.Value.Type.ToString().ToLowerInvariant(),
+                        ["required"] = f.Value.Required
+                    }))
+                : null;
+
+            var result = NativeMethods.chroma_set_metadata_schema(collectionHandle, schemaJson, out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Gets the metadata schema of this collection, or null when it has none
+        /// </summary>
+        public Dictionary<string, MetadataField>? GetMetadataSchema()
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_get_metadata_schema(collectionHandle, out var jsonPtr, out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+
+            if (jsonPtr == IntPtr.Zero)
+                return null;
+
+            try
+            {
+                using var schema = JsonDocument.Parse(Marshal.PtrToStringUTF8(jsonPtr)!);
+                return schema.RootElement.EnumerateObject().ToDictionary(
+                    field => field.Name,
+                    field => new MetadataField(
+                        Enum.Parse<MetadataFieldType>(field.Value.GetProperty("type").GetString()!, ignoreCase: true),
+                        field.Value.GetProperty("required").GetBoolean()));
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Lists the metadata keys of a where filter that are not declared filterable, e.g. to check
+        /// user-supplied filters; empty when no keys are declared
+        /// </summary>
+        public List<string> GetUndeclaredFilterKeys(Dictionary<string, object> whereFilter)
+        {
+            if (whereFilter == null)
+                throw new ArgumentNullException(nameof(whereFilter));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions);
+            var keysPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var result = NativeMethods.chroma_undeclared_filter_keys(
+                    collectionHandle,
+                    whereFilterJson,
+                    keysPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(keysPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(keysPtr);
+                }
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(keysPtr);
+            }
+        }
+
+        /// <summary>
+        /// Copies the collection with its configuration, metadata and documents into a new
+        /// collection, e.g. to promote data from a staging tenant to production. The records are
+        /// copied inside the native library and do not pass through .NET.
+        /// </summary>
+        /// <param name="targetName">Name of the new collection</param>
+        /// <param name="tenant">Tenant of the new collection; the default tenant when null</param>
+        /// <param name="database">Database of the new collection; the default database when null</param>
+        /// <param name="embeddingFunction">Embedding function of the new collection; this
+        /// collection's when null</param>
+        /// <returns>The new collection</returns>
+        public Collection CopyTo(
+            string targetName,
+            string? tenant = null,
+            string? database = null,
+            IEmbeddingFunction? embeddingFunction = null)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var clientHandle = _client.GetHandleOrThrow();
+            var result = NativeMethods.chroma_copy_collection(
+                clientHandle,
+                collectionHandle,
+                tenant,
+                database,
+                targetName,
+                true, // includeEmbeddings; copies are written with add, which needs them
+                out var targetHandle,
+                out _,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return new Collection(_client, targetHandle, embeddingFunction ?? _embeddingFunction);
+        }
+
+        /// <summary>
+        /// Whether writes to the records of the collection are refused, e.g. while it is migrated
+        /// </summary>
+        public bool IsReadOnly
+        {
+            get
+            {
+                var collectionHandle = GetHandleOrThrow();
+                var result = NativeMethods.chroma_collection_is_readonly(collectionHandle, out var readOnly, out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+                return readOnly;
+            }
+        }
+
+        /// <summary>
+        /// Freezes the records of the collection, or makes them writable again. While frozen,
+        /// reads keep working and writes throw with <see cref="ChromaErrorCode.CollectionReadOnly"/>.
+        /// The flag is persisted in the collection metadata; collections opened by other clients
+        /// see it once reopened.
+        /// </summary>
+        /// <param name="readOnly">Whether to refuse writes</param>
+        public void SetReadOnly(bool readOnly)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_set_collection_readonly(collectionHandle, readOnly, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Creates a persisted alias naming this collection in its tenant and database, which
+        /// <see cref="ChromaClient.GetCollectionByAlias"/> opens. Throws with
+        /// <c>ChromaErrorCode.ConflictError</c> if the alias exists.
+        /// </summary>
+        /// <param name="alias">Alias name</param>
+        public void CreateAlias(string alias)
+        {
+            if (alias == null)
+                throw new ArgumentNullException(nameof(alias));
+
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_create_alias(collectionHandle, alias, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+        }
+
+        /// <summary>
+        /// Atomically repoints an existing alias to this collection, e.g. once a rebuilt copy is
+        /// ready for a blue/green switch. Collections already opened through the alias keep
+        /// using the previous collection.
+        /// </summary>
+        /// <param name="alias">Alias name</param>
+        /// <returns>ID of the collection the alias named before</returns>
+        public string SwapAlias(string alias)
+        {
+            if (alias == null)
+                throw new ArgumentNullException(nameof(alias));
+
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_swap_alias(collectionHandle, alias, out var previousPtr, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringAnsi(previousPtr)!;
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(previousPtr);
+            }
+        }
+
+        /// <summary>
+        /// Reads the writes made to the collection after <paramref name="sequence"/> from the write
+        /// log as JSON, e.g. to replicate them to a secondary store. Store the returned
+        /// <c>next_sequence</c> and pass it to the next call; when <c>truncated</c> is set the log
+        /// was purged past <paramref name="sequence"/> and the consumer has to resynchronize.
+        /// </summary>
+        /// <param name="sequence">Sequence number of the last change already consumed, 0 to start</param>
+        /// <param name="limit">Maximum number of changes to return, 0 for all</param>
+        public string GetChangesSinceJson(ulong sequence = 0, uint limit = 0)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_changes_since(collectionHandle, sequence, limit, out var jsonPtr, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringAnsi(jsonPtr);
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Restores documents removed with <c>Delete(soft: true)</c>, making them visible again
+        /// </summary>
+        /// <param name="ids">IDs to restore; IDs that are not soft-deleted are ignored</param>
+        /// <returns>The number of documents restored</returns>
+        public int RestoreRecords(IEnumerable<string> ids)
+        {
+            var idsArray = ids.ToArray();
+            if (idsArray.Length == 0)
+                return 0;
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
+
+            try
+            {
+                var result = NativeMethods.chroma_restore_records(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    out var restored,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+                return (int)restored.ToUInt64();
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+            }
+        }
+
+        /// <summary>
+        /// Permanently deletes documents that were soft-deleted at least <paramref name="olderThan"/> ago
+        /// </summary>
+        /// <returns>The number of documents deleted</returns>
+        public int PurgeDeleted(TimeSpan olderThan)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_purge_deleted(collectionHandle, (uint)olderThan.TotalSeconds, out var purged, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return (int)purged.ToUInt64();
+        }
+
+        /// <summary>
+        /// Adds documents to the collection. When no document has an ID, UUIDv7 IDs are
+        /// generated natively and assigned to <see cref="ChromaDocument.Id"/>.
+        /// </summary>
+        /// <param name="documents">Documents to add</param>
+        /// <param name="onConflict">What to do with IDs that already exist</param>
+        /// <returns>How many documents were inserted, skipped and overwritten</returns>
+        public AddResult Add(IEnumerable<ChromaDocument> documents, OnConflict onConflict = OnConflict.Fail)
+        {
+            var docs = documents.ToList();
+            if (docs.Count == 0)
+                return new AddResult(0, 0, 0);
+
+            var collectionHandle = GetHandleOrThrow();
+
+            if (_embeddingFunction != null)
+            {
+                var textsToEmbed = docs.Where(d => d.Embedding == null && !string.IsNullOrEmpty(d.Text))
+                    .Select(d => d.Text)
+                    .ToList();
+
+                if (textsToEmbed.Count > 0)
+                {
+                    var embeddings = _embeddingFunction.GenerateEmbeddings(textsToEmbed);
+                    int index = 0;
+
+                    foreach (var doc in docs.Where(d => d.Embedding == null && !string.IsNullOrEmpty(d.Text)))
+                    {
+                        doc.Embedding = embeddings[index++];
+                    }
+                }
+            }
+
+            var generateIds = docs.All(d => d.Id == null);
+            if (!generateIds && docs.Any(d => d.Id == null))
+                throw new ArgumentException("Either every document or none must have an ID", nameof(documents));
+
+            // Documents skipped because they already exist keep their blob
+            var blobDocs = docs.Where(d => d.Blob != null).ToList();
+            if (onConflict == OnConflict.Ignore && !generateIds && blobDocs.Count > 0)
+            {
+                var exists = Exists(blobDocs.Select(d => d.Id));
+                blobDocs = blobDocs.Where((_, i) => !exists[i]).ToList();
+            }
+
+            // Marshal IDs
+            var ids = docs.Select(d => d.Id).ToArray();
+            var idsPtr = generateIds ? IntPtr.Zero : MarshalStringArray(ids);
+            var generatedIdsPtr = generateIds
+                ? Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>())
+                : IntPtr.Zero;
+
+            // Marshal embeddings
+            var embeddingDim = docs[0].Embedding.Length;
+            var embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
+
+            // Marshal metadata
+            var metadataJsons = docs.Select(d => d.Metadata != null
+                ? MetadataConverter.SerializeMetadata(d.Metadata)
+                : null).ToArray();
+            var metadataPtr = MarshalStringArray(metadataJsons);
+
+            // Marshal documents
+            var texts = docs.Select(d => d.Text).ToArray();
+            var textsPtr = MarshalStringArray(texts);
+
+            try
+            {
+                var result = NativeMethods.chroma_add(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)ids.Length,
+                    embeddingsPtr,
+                    (UIntPtr)embeddingDim,
+                    metadataPtr,
+                    textsPtr,
+                    (int)onConflict,
+                    out var stats,
+                    generatedIdsPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                if (generateIds)
+                {
+                    try
+                    {
+                        var generated = MarshalResultSet(generatedIdsPtr);
+                        for (int i = 0; i < docs.Count; i++)
+                            docs[i].Id = generated[i];
+                    }
+                    finally
+                    {
+                        NativeMethods.chroma_free_result_set(generatedIdsPtr);
+                    }
+                }
+
+                if (blobDocs.Count > 0)
+                    PutBlobs(blobDocs.Select(d => d.Id), blobDocs.Select(d => d.Blob));
+
+                return new AddResult((int)stats.Inserted, (int)stats.Skipped, (int)stats.Overwritten);
+            }
+            finally
+            {
+                if (generatedIdsPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(generatedIdsPtr);
+                FreeStringArray(idsPtr, ids.Length);
+                FreeStringArray(metadataPtr, metadataJsons.Length);
+                FreeStringArray(textsPtr, texts.Length);
+                FreeEmbeddings(embeddingsPtr, docs.Count);
+            }
+        }
+
+        /// <summary>
+        /// Adds documents with half-precision embeddings, converted to f32 natively
+        /// </summary>
+        /// <param name="ids">Document IDs, or null to generate UUIDv7 IDs natively</param>
+        /// <param name="embeddings">Row-major embeddings, <paramref name="dimension"/> values per ID</param>
+        /// <param name="dimension">Embedding dimension</param>
+        /// <param name="documents">Optional document texts, one per ID</param>
+        /// <param name="metadatas">Optional metadata, one per ID</param>
+        /// <returns>The IDs of the added documents, in order</returns>
+        public IReadOnlyList<string> AddEmbeddings(
+            IReadOnlyList<string>? ids,
+            ReadOnlySpan<Half> embeddings,
+            int dimension,
+            IReadOnlyList<string>? documents = null,
+            IReadOnlyList<Dictionary<string, object>>? metadatas = null)
+        {
+            return AddEmbeddingBatch(ids, embeddings, EmbeddingDtypeF16, dimension, documents, metadatas);
+        }
+
+        /// <summary>
+        /// Adds documents with double-precision embeddings, converted to f32 natively
+        /// </summary>
+        /// <param name="ids">Document IDs, or null to generate UUIDv7 IDs natively</param>
+        /// <param name="embeddings">Row-major embeddings, <paramref name="dimension"/> values per ID</param>
+        /// <param name="dimension">Embedding dimension</param>
+        /// <param name="documents">Optional document texts, one per ID</param>
+        /// <param name="metadatas">Optional metadata, one per ID</param>
+        /// <returns>The IDs of the added documents, in order</returns>
+        public IReadOnlyList<string> AddEmbeddings(
+            IReadOnlyList<string>? ids,
+            ReadOnlySpan<double> embeddings,
+            int dimension,
+            IReadOnlyList<string>? documents = null,
+            IReadOnlyList<Dictionary<string, object>>? metadatas = null)
+        {
+            return AddEmbeddingBatch(ids, embeddings, EmbeddingDtypeF64, dimension, documents, metadatas);
+        }
+
+        private unsafe IReadOnlyList<string> AddEmbeddingBatch<T>(
+            IReadOnlyList<string>? ids,
+            ReadOnlySpan<T> embeddings,
+            int dtype,
+            int dimension,
+            IReadOnlyList<string>? documents,
+            IReadOnlyList<Dictionary<string, object>>? metadatas) where T : unmanaged
+        {
+            if (dimension <= 0)
+                throw new ArgumentOutOfRangeException(nameof(dimension), "Dimension must be positive");
+            var count = ids?.Count ?? embeddings.Length / dimension;
+            if (count == 0)
+                throw new ArgumentException("At least one ID is required", nameof(ids));
+            if (embeddings.Length != count * dimension)
+                throw new ArgumentException(
+                    $"Expected {count} embeddings of dimension {dimension}, got {embeddings.Length} values",
+                    nameof(embeddings));
+            if (documents != null && documents.Count != count)
+                throw new ArgumentException("Documents must have one entry per ID", nameof(documents));
+            if (metadatas != null && metadatas.Count != count)
+                throw new ArgumentException("Metadatas must have one entry per ID", nameof(metadatas));
+
+            var collectionHandle = GetHandleOrThrow();
+
+            var idsArray = ids?.ToArray();
+            var idsPtr = idsArray != null ? MarshalStringArray(idsArray) : IntPtr.Zero;
+            var generatedIdsPtr = idsArray == null
+                ? Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>())
+                : IntPtr.Zero;
+
+            var metadataJsons = metadatas?.Select(m => m != null
+                ? MetadataConverter.SerializeMetadata(m)
+                : null).ToArray();
+            var metadataPtr = metadataJsons != null ? MarshalStringArray(metadataJsons) : IntPtr.Zero;
+
+            var texts = documents?.ToArray();
+            var textsPtr = texts != null ? MarshalStringArray(texts) : IntPtr.Zero;
+
+            try
+            {
+                fixed (T* data = embeddings)
+                {
+                    var batch = new ChromaEmbeddingBatchNative
+                    {
+                        Data = (IntPtr)data,
+                        Dtype = dtype,
+                        Count = (UIntPtr)count,
+                        Dimension = (UIntPtr)dimension
+                    };
+
+                    var result = NativeMethods.chroma_add_batch(
+                        collectionHandle,
+                        idsPtr,
+                        (UIntPtr)count,
+                        ref batch,
+                        metadataPtr,
+                        textsPtr,
+                        (int)OnConflict.Fail,
+                        out _,
+                        generatedIdsPtr,
+                        out var errorPtr);
+
+                    ChromaClient.CheckError(result, errorPtr);
+                }
+
+                if (idsArray != null)
+                    return idsArray;
+                try
+                {
+                    return MarshalResultSet(generatedIdsPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(generatedIdsPtr);
+                }
+            }
+            finally
+            {
+                if (generatedIdsPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(generatedIdsPtr);
+                if (idsArray != null)
+                    FreeStringArray(idsPtr, idsArray.Length);
+                if (metadataJsons != null)
+                    FreeStringArray(metadataPtr, metadataJsons.Length);
+                if (texts != null)
+                    FreeStringArray(textsPtr, texts.Length);
+            }
+        }
+
+        /// <summary>
+        /// Adds records from a serialized Apache Arrow IPC stream without marshaling each field
+        /// </summary>
+        /// <remarks>
+        /// The stream needs an <c>id</c> string column and an <c>embedding</c> column of float lists;
+        /// optional <c>document</c> (string) and <c>metadata</c> (struct or JSON string) columns are read
+        /// when present. This matches the layout returned by <see cref="GetSerialized"/> with
+        /// <see cref="SerializationFormat.ArrowIpc"/>.
+        /// </remarks>
+        /// <param name="ipcStream">Arrow IPC stream bytes</param>
+        public unsafe void AddArrow(ReadOnlySpan<byte> ipcStream)
+        {
+            if (ipcStream.IsEmpty)
+                throw new ArgumentException("Arrow IPC stream is empty", nameof(ipcStream));
+
+            var collectionHandle = GetHandleOrThrow();
+
+            fixed (byte* buffer = ipcStream)
+            {
+                var result = NativeMethods.chroma_add_arrow(
+                    collectionHandle,
+                    (IntPtr)buffer,
+                    (UIntPtr)ipcStream.Length,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+        }
+
+        /// <summary>
+        /// Queries the collection for similar documents
+        /// </summary>
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeMetadatas">Include metadata in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeDocuments">Include document text in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeDistances">Include distance scores in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="allowedIds">Optional set of document IDs the search is restricted to</param>
+        /// <param name="metadataKeys">Optional metadata keys to return; other keys are dropped natively</param>
+        /// <param name="options">Optional post-processing of the hits, applied natively</param>
+        /// <returns>Query results</returns>
+        public QueryResult Query(
+            float[] queryEmbedding,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            string whereDocument = null,
+            bool? includeMetadatas = null,
+            bool? includeDocuments = null,
+            bool? includeDistances = null,
+            IEnumerable<string>? allowedIds = null,
+            IEnumerable<string>? metadataKeys = null,
+            QueryOptions? options = null)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            var defaults = includeMetadatas.HasValue && includeDocuments.HasValue && includeDistances.HasValue
+                ? Include.Default
+                : DefaultIncludes;
+
+            var allowedIdsArray = allowedIds?.ToArray();
+            if (allowedIdsArray != null && allowedIdsArray.Length == 0)
+                return new QueryResult();
+
+            var allowedIdsPtr = MarshalStringArray(allowedIdsArray);
+            var metadataKeysArray = metadataKeys?.ToArray();
+            var metadataKeysPtr = MarshalMetadataKeys(metadataKeysArray);
+            var optionsPtr = MarshalQueryOptions(options, out var reranker);
+            var embeddingPtr = Marshal.AllocHGlobal(queryEmbedding.Length * sizeof(float));
+            Marshal.Copy(queryEmbedding, 0, embeddingPtr, queryEmbedding.Length);
+
+            try
+            {
+                var result = NativeMethods.chroma_query(
+                    collectionHandle,
+                    embeddingPtr,
+                    (UIntPtr)queryEmbedding.Length,
+                    (uint)nResults,
+                    whereFilterJson,
+                    whereDocument,
+                    allowedIdsPtr,
+                    (UIntPtr)(allowedIdsArray?.Length ?? 0),
+                    false, // Don't include embeddings in response for simplicity
+                    includeMetadatas ?? defaults.HasFlag(Include.Metadatas),
+                    includeDocuments ?? defaults.HasFlag(Include.Documents),
+                    includeDistances ?? defaults.HasFlag(Include.Distances),
+                    metadataKeysPtr,
+                    (UIntPtr)(metadataKeysArray?.Length ?? 0),
+                    MetadataFormat,
+                    optionsPtr,
+                    out var queryResultPtr,
+                    out var errorPtr);
+                GC.KeepAlive(reranker);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalQueryResult(queryResultPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                }
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(embeddingPtr);
+                FreeQueryOptions(optionsPtr);
+                if (allowedIdsPtr != IntPtr.Zero)
+                    FreeStringArray(allowedIdsPtr, allowedIdsArray!.Length);
+                FreeMetadataKeys(metadataKeysPtr, metadataKeysArray);
+            }
+        }
+
+        /// <summary>
+        /// Queries the collection using text
+        /// </summary>
+        /// <param name="queryText">Text to search for</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeMetadatas">Include metadata in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeDocuments">Include document text in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeDistances">Include distance scores in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="allowedIds">Optional set of document IDs the search is restricted to</param>
+        /// <param name="metadataKeys">Optional metadata keys to return; other keys are dropped natively</param>
+        /// <param name="options">Optional post-processing of the hits, applied natively</param>
+        /// <returns>Query results</returns>
+        public QueryResult Query(
+            string queryText,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            string whereDocument = null,
+            bool? includeMetadatas = null,
+            bool? includeDocuments = null,
+            bool? includeDistances = null,
+            IEnumerable<string>? allowedIds = null,
+            IEnumerable<string>? metadataKeys = null,
+            QueryOptions? options = null)
+        {
+            if (_embeddingFunction == null)
+                throw new ChromaException("Cannot query by text without an embedding function");
+
+            var queryEmbedding = _embeddingFunction.GenerateEmbeddings(new[] { queryText })[0];
+
+            return Query(
+                queryEmbedding,
+                nResults,
+                whereFilter,
+                whereDocument,
+                includeMetadatas,
+                includeDocuments,
+                includeDistances,
+                allowedIds,
+                metadataKeys,
+                options);
+        }
+
+        /// <summary>
+        /// Queries the collection for the IDs of the nearest documents and, optionally, their
+        /// distances; documents, metadata and embeddings are neither read nor marshaled
+        /// </summary>
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeDistances">Include distance scores in results</param>
+        /// <returns>Query results holding only IDs and distances</returns>
+        public QueryResult QueryIds(
+            float[] queryEmbedding,
+            int nResults = 10,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            bool includeDistances = false)
+        {
+            if (queryEmbedding == null)
+                throw new ArgumentNullException(nameof(queryEmbedding));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var result = NativeMethods.chroma_query_ids(
+                collectionHandle,
+                queryEmbedding,
+                (UIntPtr)queryEmbedding.Length,
+                (uint)nResults,
+                whereFilterJson,
+                whereDocument,
+                includeDistances,
+                out var queryResultPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return MarshalQueryResult(queryResultPtr);
+            }
+            finally
+            {
+                NativeMethods.chroma_free_query_result(queryResultPtr);
+            }
+        }
+
+        /// <summary>
+        /// Prepares a query for repeated execution: the filters, includes, metadata projection and
+        /// options are parsed and validated natively once, and each
+        /// <see cref="PreparedQuery.Execute"/> only passes the query embedding
+        /// </summary>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="include">Fields to return; <see cref="Include.Default"/> for <see cref="DefaultIncludes"/> at the time of the call</param>
+        /// <param name="metadataKeys">Optional metadata keys to return; other keys are dropped natively</param>
+        /// <param name="options">Optional post-processing of the hits, applied natively</param>
+        /// <returns>The prepared query, to be disposed when no longer needed</returns>
+        public PreparedQuery PrepareQuery(
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            Include include = Include.Default,
+            IEnumerable<string>? metadataKeys = null,
+            QueryOptions? options = null)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var metadataKeysArray = metadataKeys?.ToArray();
+            var metadataKeysPtr = MarshalMetadataKeys(metadataKeysArray);
+            var optionsPtr = MarshalQueryOptions(options, out var reranker);
+            var whereFilterPtr = whereFilterJson != null ? Marshal.StringToHGlobalAnsi(whereFilterJson) : IntPtr.Zero;
+            var whereDocumentPtr = whereDocument != null ? Marshal.StringToHGlobalAnsi(whereDocument) : IntPtr.Zero;
+
+            try
+            {
+                var template = new ChromaQueryTemplateNative
+                {
+                    StructSize = (UIntPtr)Marshal.SizeOf<ChromaQueryTemplateNative>(),
+                    WhereFilterJson = whereFilterPtr,
+                    WhereDocumentFilter = whereDocumentPtr,
+                    IncludeFlags = (uint)include,
+                    MetadataKeys = metadataKeysPtr,
+                    MetadataKeysCount = (UIntPtr)(metadataKeysArray?.Length ?? 0),
+                    MetadataFormat = MetadataFormat,
+                    Options = optionsPtr
+                };
+
+                var result = NativeMethods.chroma_prepare_query(
+                    collectionHandle,
+                    ref template,
+                    out var preparedHandle,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+                return new PreparedQuery(_client, preparedHandle, reranker);
+            }
+            finally
+            {
+                if (whereFilterPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(whereFilterPtr);
+                if (whereDocumentPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(whereDocumentPtr);
+                FreeQueryOptions(optionsPtr);
+                FreeMetadataKeys(metadataKeysPtr, metadataKeysArray);
+            }
+        }
+
+        /// <summary>
+        /// Runs a query and reports how it was executed as JSON, to diagnose slow queries: the
+        /// segments of the collection, entries still in the write log, the record and filter
+        /// candidate counts, the number of hits and the duration of each stage. The hits
+        /// themselves are not returned.
+        /// </summary>
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="nResults">Number of results to search for</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        public string ExplainQueryJson(
+            float[] queryEmbedding,
+            int nResults = 10,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null)
+        {
+            if (queryEmbedding == null)
+                throw new ArgumentNullException(nameof(queryEmbedding));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var result = NativeMethods.chroma_query_explain(
+                collectionHandle,
+                queryEmbedding,
+                (UIntPtr)queryEmbedding.Length,
+                (uint)nResults,
+                whereFilterJson,
+                whereDocument,
+                out var jsonPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringAnsi(jsonPtr);
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Aggregates the metadata of the matching records natively and returns the result as
+        /// JSON, without reading the records into .NET: <c>{"groups": [{"group": value, "values":
+        /// {"count": 3, "max:year": 2024, ...}}]}</c>, with a single group whose <c>group</c> is
+        /// null when <paramref name="groupByKey"/> is null.
+        /// </summary>
+        /// <param name="aggregationsJson">JSON array of aggregations such as <c>{"op": "count"}</c>,
+        /// <c>{"op": "min", "key": "year"}</c>, <c>{"op": "max", "key": "year"}</c> or
+        /// <c>{"op": "distinct", "key": "author"}</c></param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="groupByKey">Metadata key to group the records by</param>
+        public string AggregateJson(
+            string aggregationsJson,
+            Dictionary<string, object>? whereFilter = null,
+            string? groupByKey = null)
+        {
+            if (aggregationsJson == null)
+                throw new ArgumentNullException(nameof(aggregationsJson));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var result = NativeMethods.chroma_aggregate(
+                collectionHandle,
+                whereFilterJson,
+                groupByKey,
+                aggregationsJson,
+                out var jsonPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringUTF8(jsonPtr)!;
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Benchmarks adds without marshaling costs: generates <paramref name="count"/> records with
+        /// random embeddings natively and adds them in batches, reporting throughput and per-batch
+        /// latency as JSON (<c>records_per_second</c>, <c>latency.p50_ms</c>, ...). The records are
+        /// really added, so run it on a scratch collection.
+        /// </summary>
+        /// <param name="count">Number of records to add</param>
+        /// <param name="dimension">Embedding dimension</param>
+        /// <param name="batchSize">Records per add call, 0 for 100</param>
+        /// <param name="seed">Seed of the generated embeddings</param>
+        public string BenchmarkAddJson(int count, int dimension, int batchSize = 0, ulong seed = 0)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_bench_add(
+                collectionHandle,
+                (uint)count,
+                (UIntPtr)dimension,
+                (uint)batchSize,
+                seed,
+                out var jsonPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringUTF8(jsonPtr)!;
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Benchmarks queries without marshaling costs: runs <paramref name="queries"/> queries with
+        /// random embeddings generated natively, reporting throughput and per-query latency as JSON
+        /// (<c>queries_per_second</c>, <c>latency.p95_ms</c>, ...).
+        /// </summary>
+        /// <param name="queries">Number of queries to run</param>
+        /// <param name="dimension">Embedding dimension</param>
+        /// <param name="nResults">Number of results per query</param>
+        /// <param name="seed">Seed of the generated embeddings</param>
+        public string BenchmarkQueryJson(int queries, int dimension, int nResults = 10, ulong seed = 0)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_bench_query(
+                collectionHandle,
+                (uint)queries,
+                (UIntPtr)dimension,
+                (uint)nResults,
+                seed,
+                out var jsonPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return Marshal.PtrToStringUTF8(jsonPtr)!;
+            }
+            finally
+            {
+                NativeMethods.chroma_free_string(jsonPtr);
+            }
+        }
+
+        /// <summary>
+        /// Lists the distinct values of a metadata key in sorted order, e.g. for filter dropdowns.
+        /// Strings are returned as is and other values as JSON text, such as <c>2024</c> or <c>true</c>.
+        /// </summary>
+        /// <param name="key">Metadata key</param>
+        /// <param name="limit">Maximum number of values to return, 0 for all</param>
+        public List<string> GetDistinctMetadataValues(string key, int limit = 0)
+        {
+            if (key == null)
+                throw new ArgumentNullException(nameof(key));
+
+            var collectionHandle = GetHandleOrThrow();
+            var valuesPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var result = NativeMethods.chroma_distinct_metadata_values(
+                    collectionHandle, key, (uint)limit, valuesPtr, out var errorPtr);
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(valuesPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(valuesPtr);
+                }
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(valuesPtr);
+            }
+        }
+
+        /// <summary>
+        /// Queries the collection with a half-precision embedding, converted to f32 natively
+        /// </summary>
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeMetadatas">Include metadata in results</param>
+        /// <param name="includeDocuments">Include document text in results</param>
+        /// <param name="includeDistances">Include distance scores in results</param>
+        /// <returns>Query results</returns>
+        public QueryResult Query(
+            ReadOnlySpan<Half> queryEmbedding,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            string whereDocument = null,
+            bool includeMetadatas = true,
+            bool includeDocuments = true,
+            bool includeDistances = true)
+        {
+            return QueryEmbeddingBatch(queryEmbedding, EmbeddingDtypeF16, nResults, whereFilter, whereDocument,
+                includeMetadatas, includeDocuments, includeDistances);
+        }
+
+        /// <summary>
+        /// Queries the collection with a double-precision embedding, converted to f32 natively
+        /// </summary>
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeMetadatas">Include metadata in results</param>
+        /// <param name="includeDocuments">Include document text in results</param>
+        /// <param name="includeDistances">Include distance scores in results</param>
+        /// <returns>Query results</returns>
+        public QueryResult Query(
+            ReadOnlySpan<double> queryEmbedding,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            string whereDocument = null,
+            bool includeMetadatas = true,
+            bool includeDocuments = true,
+            bool includeDistances = true)
+        {
+            return QueryEmbeddingBatch(queryEmbedding, EmbeddingDtypeF64, nResults, whereFilter, whereDocument,
+                includeMetadatas, includeDocuments, includeDistances);
+        }
+
+        private unsafe QueryResult QueryEmbeddingBatch<T>(
+            ReadOnlySpan<T> queryEmbedding,
+            int dtype,
+            int nResults,
+            Dictionary<string, object> whereFilter,
+            string whereDocument,
+            bool includeMetadatas,
+            bool includeDocuments,
+            bool includeDistances) where T : unmanaged
+        {
+            if (queryEmbedding.IsEmpty)
+                throw new ArgumentException("Query embedding is empty", nameof(queryEmbedding));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            fixed (T* data = queryEmbedding)
+            {
+                var batch = new ChromaEmbeddingBatchNative
+                {
+                    Data = (IntPtr)data,
+                    Dtype = dtype,
+                    Count = (UIntPtr)1,
+                    Dimension = (UIntPtr)queryEmbedding.Length
+                };
+
+                var result = NativeMethods.chroma_query_batch(
+                    collectionHandle,
+                    ref batch,
+                    (uint)nResults,
+                    whereFilterJson,
+                    whereDocument,
+                    IntPtr.Zero,
+                    UIntPtr.Zero,
+                    false,
+                    includeMetadatas,
+                    includeDocuments,
+                    includeDistances,
+                    IntPtr.Zero,
+                    UIntPtr.Zero,
+                    MetadataFormat,
+                    out var queryResultPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalQueryResult(queryResultPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                }
+            }
+        }
+
+        /// <summary>
+        /// Queries the collection and returns the full response encoded in a single buffer,
+        /// ready for a MessagePack or CBOR deserializer
+        /// </summary>
+        /// <param name="queryEmbeddings">Query embedding vectors, all of the same dimension</param>
+        /// <param name="nResults">Number of results to return per query</param>
+        /// <param name="format">Encoding of the returned buffer</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="whereDocument">Document content filter</param>
+        /// <param name="includeEmbeddings">Include embeddings in results</param>
+        /// <param name="includeMetadatas">Include metadata in results</param>
+        /// <param name="includeDocuments">Include document text in results</param>
+        /// <param name="includeDistances">Include distance scores in results</param>
+        /// <returns>The encoded query response</returns>
+        public byte[] QuerySerialized(
+            IReadOnlyList<float[]> queryEmbeddings,
+            int nResults = 10,
+            SerializationFormat format = SerializationFormat.MessagePack,
+            Dictionary<string, object> whereFilter = null,
+            string whereDocument = null,
+            bool includeEmbeddings = false,
+            bool includeMetadatas = true,
+            bool includeDocuments = true,
+            bool includeDistances = true)
+        {
+            if (queryEmbeddings == null || queryEmbeddings.Count == 0)
+                throw new ArgumentException("At least one query embedding is required", nameof(queryEmbeddings));
+
+            var dimension = queryEmbeddings[0].Length;
+            if (queryEmbeddings.Any(e => e.Length != dimension))
+                throw new ArgumentException("All query embeddings must have the same dimension", nameof(queryEmbeddings));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var flat = queryEmbeddings.SelectMany(e => e).ToArray();
+            var embeddingsPtr = Marshal.AllocHGlobal(flat.Length * sizeof(float));
+            Marshal.Copy(flat, 0, embeddingsPtr, flat.Length);
+
+            try
+            {
+                var result = NativeMethods.chroma_query_serialized(
+                    collectionHandle,
+                    embeddingsPtr,
+                    (UIntPtr)dimension,
+                    (UIntPtr)queryEmbeddings.Count,
+                    (uint)nResults,
+                    whereFilterJson,
+                    whereDocument,
+                    includeEmbeddings,
+                    includeMetadatas,
+                    includeDocuments,
+                    includeDistances,
+                    (int)format,
+                    out var bufferPtr,
+                    out var length,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                return CopyAndFreeBuffer(bufferPtr, length);
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(embeddingsPtr);
+            }
+        }
+
+        /// <summary>
+        /// Searches the collection by keywords using the full-text index, ranked with BM25
+        /// </summary>
+        /// <param name="queryText">Keywords to search for</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter</param>
+        /// <param name="matchAllTerms">Require every keyword to appear in a document</param>
+        /// <returns>Search results with BM25 scores</returns>
+        public QueryResult KeywordSearch(
+            string queryText,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            bool matchAllTerms = false)
         {
-            var clientHandle = _client.GetHandleOrThrow();
-            var collectionHandle = Volatile.Read(ref _handle);
-            if (collectionHandle == IntPtr.Zero)
-                throw new ObjectDisposedException(nameof(Collection));
-            return (clientHandle, collectionHandle);
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var rankOptions = new ChromaRankOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaRankOptionsNative>(),
+                K1 = 1.2f,
+                B = 0.75f,
+                MatchAllTerms = matchAllTerms,
+                MaxCandidates = 0
+            };
+
+            var result = NativeMethods.chroma_search(
+                collectionHandle,
+                queryText,
+                (uint)nResults,
+                whereFilterJson,
+                ref rankOptions,
+                out var queryResultPtr,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            try
+            {
+                return MarshalQueryResult(queryResultPtr);
+            }
+            finally
+            {
+                NativeMethods.chroma_free_query_result(queryResultPtr);
+            }
         }
 
         /// <summary>
-        /// Gets the number of documents in the collection
+        /// Runs a vector similarity query and a keyword search together and fuses their results
         /// </summary>
-        /// <returns>The document count</returns>
-        public uint Count()
+        /// <param name="queryEmbedding">Query embedding vector</param>
+        /// <param name="queryText">Keywords to search for</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter applied to both searches</param>
+        /// <param name="fusion">How to combine the vector and keyword rankings</param>
+        /// <param name="vectorWeight">Weight of vector similarity when using weighted fusion</param>
+        /// <returns>Fused results; Scores holds the fused score, Distances and TextScores the per-retriever scores</returns>
+        public QueryResult HybridSearch(
+            float[] queryEmbedding,
+            string queryText,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            FusionMethod fusion = FusionMethod.ReciprocalRank,
+            float vectorWeight = 0.5f)
         {
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
-            var result = NativeMethods.chroma_count(
-                clientHandle,
-                collectionHandle,
-                out uint count,
-                out var errorPtr);
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
 
-            ChromaClient.CheckError(result, errorPtr);
-            return count;
+            var rankOptions = new ChromaRankOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaRankOptionsNative>(),
+                K1 = 1.2f,
+                B = 0.75f,
+                MatchAllTerms = false,
+                MaxCandidates = 0
+            };
+
+            var fusionOptions = new ChromaFusionOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaFusionOptionsNative>(),
+                Method = (int)fusion,
+                RrfK = 60f,
+                VectorWeight = vectorWeight,
+                CandidateK = 0
+            };
+
+            var embeddingPtr = Marshal.AllocHGlobal(queryEmbedding.Length * sizeof(float));
+            Marshal.Copy(queryEmbedding, 0, embeddingPtr, queryEmbedding.Length);
+
+            try
+            {
+                var result = NativeMethods.chroma_hybrid_search(
+                    collectionHandle,
+                    embeddingPtr,
+                    (UIntPtr)queryEmbedding.Length,
+                    queryText,
+                    (uint)nResults,
+                    whereFilterJson,
+                    ref rankOptions,
+                    ref fusionOptions,
+                    out var queryResultPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalQueryResult(queryResultPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                }
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(embeddingPtr);
+            }
         }
 
         /// <summary>
-        /// Adds documents to the collection
+        /// Runs a hybrid search, embedding the query text with the collection's embedding function
         /// </summary>
-        /// <param name="documents">Documents to add</param>
-        public void Add(IEnumerable<ChromaDocument> documents)
+        /// <param name="queryText">Text to search for</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="whereFilter">Metadata filter applied to both searches</param>
+        /// <param name="fusion">How to combine the vector and keyword rankings</param>
+        /// <param name="vectorWeight">Weight of vector similarity when using weighted fusion</param>
+        /// <returns>Fused results</returns>
+        public QueryResult HybridSearch(
+            string queryText,
+            int nResults = 10,
+            Dictionary<string, object> whereFilter = null,
+            FusionMethod fusion = FusionMethod.ReciprocalRank,
+            float vectorWeight = 0.5f)
+        {
+            if (_embeddingFunction == null)
+                throw new ChromaException("Cannot run a hybrid search by text without an embedding function");
+
+            var queryEmbedding = _embeddingFunction.GenerateEmbeddings(new[] { queryText })[0];
+            return HybridSearch(queryEmbedding, queryText, nResults, whereFilter, fusion, vectorWeight);
+        }
+
+        /// <summary>
+        /// Attaches sparse vectors to existing documents, next to their dense embeddings
+        /// </summary>
+        /// <param name="ids">IDs of the documents; IDs that do not exist are ignored</param>
+        /// <param name="vectors">One sparse vector per ID</param>
+        /// <param name="sparseKey">Metadata key the vectors are stored under, or null for <c>sparse_embedding</c></param>
+        public void AddSparse(IEnumerable<string> ids, IEnumerable<SparseVector> vectors, string? sparseKey = null)
+        {
+            var idsArray = ids.ToArray();
+            var vectorsArray = vectors.ToArray();
+            if (vectorsArray.Length != idsArray.Length)
+                throw new ArgumentException("Sparse vectors must have one entry per ID", nameof(vectors));
+            if (idsArray.Length == 0)
+                return;
+
+            var collectionHandle = GetHandleOrThrow();
+            var handles = new List<GCHandle>();
+            var idsPtr = MarshalStringArray(idsArray);
+
+            try
+            {
+                var nativeVectors = vectorsArray.Select(v => PinSparseVector(v, handles)).ToArray();
+                var result = NativeMethods.chroma_add_sparse(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    nativeVectors,
+                    sparseKey,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+                foreach (var handle in handles)
+                    handle.Free();
+            }
+        }
+
+        /// <summary>
+        /// Searches by a sparse vector, scoring documents by the dot product with their sparse
+        /// vector, optionally fused with a dense vector query
+        /// </summary>
+        /// <param name="query">Sparse query vector</param>
+        /// <param name="nResults">Number of results to return</param>
+        /// <param name="queryEmbedding">Dense query embedding to fuse with, or null for a sparse-only search</param>
+        /// <param name="whereFilter">Metadata filter; every matching document is scanned, so narrow large collections</param>
+        /// <param name="fusion">How to combine the dense and sparse rankings</param>
+        /// <param name="vectorWeight">Weight of dense similarity when using weighted fusion</param>
+        /// <param name="sparseKey">Metadata key the sparse vectors are stored under, or null for <c>sparse_embedding</c></param>
+        /// <returns>
+        /// Without a dense query, Scores holds the sparse scores; with one, Scores holds the fused score,
+        /// Distances the dense distances and TextScores the sparse scores
+        /// </returns>
+        public QueryResult QuerySparse(
+            SparseVector query,
+            int nResults = 10,
+            float[]? queryEmbedding = null,
+            Dictionary<string, object>? whereFilter = null,
+            FusionMethod fusion = FusionMethod.ReciprocalRank,
+            float vectorWeight = 0.5f,
+            string? sparseKey = null)
+        {
+            if (query == null)
+                throw new ArgumentNullException(nameof(query));
+            if (queryEmbedding != null && queryEmbedding.Length == 0)
+                throw new ArgumentException("Query embedding is empty", nameof(queryEmbedding));
+
+            var collectionHandle = GetHandleOrThrow();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var fusionOptions = new ChromaFusionOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaFusionOptionsNative>(),
+                Method = (int)fusion,
+                RrfK = 60f,
+                VectorWeight = vectorWeight,
+                CandidateK = 0
+            };
+
+            var handles = new List<GCHandle>();
+            var embeddingPtr = IntPtr.Zero;
+
+            try
+            {
+                var nativeQuery = PinSparseVector(query, handles);
+                if (queryEmbedding != null)
+                {
+                    embeddingPtr = Marshal.AllocHGlobal(queryEmbedding.Length * sizeof(float));
+                    Marshal.Copy(queryEmbedding, 0, embeddingPtr, queryEmbedding.Length);
+                }
+
+                var result = NativeMethods.chroma_query_sparse(
+                    collectionHandle,
+                    ref nativeQuery,
+                    sparseKey,
+                    embeddingPtr,
+                    (UIntPtr)(queryEmbedding?.Length ?? 0),
+                    (uint)nResults,
+                    whereFilterJson,
+                    ref fusionOptions,
+                    out var queryResultPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalQueryResult(queryResultPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                }
+            }
+            finally
+            {
+                if (embeddingPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(embeddingPtr);
+                foreach (var handle in handles)
+                    handle.Free();
+            }
+        }
+
+        private static ChromaSparseVectorNative PinSparseVector(SparseVector vector, List<GCHandle> handles)
+        {
+            var indices = GCHandle.Alloc(vector.Indices, GCHandleType.Pinned);
+            handles.Add(indices);
+            var values = GCHandle.Alloc(vector.Values, GCHandleType.Pinned);
+            handles.Add(values);
+
+            return new ChromaSparseVectorNative
+            {
+                Indices = indices.AddrOfPinnedObject(),
+                Values = values.AddrOfPinnedObject(),
+                Count = (UIntPtr)vector.Indices.Length
+            };
+        }
+
+        /// <summary>
+        /// Updates existing documents in the collection
+        /// </summary>
+        /// <param name="documents">Documents to update</param>
+        /// <param name="expectedVersions">Optional version of each document, as read with
+        /// <see cref="QueryResult.Versions"/> (negative to skip one); when any document changed
+        /// since, nothing is updated and a <see cref="ChromaErrorCode.ConflictError"/> is thrown</param>
+        public void Update(IEnumerable<ChromaDocument> documents, IReadOnlyList<long>? expectedVersions = null)
         {
             var docs = documents.ToList();
             if (docs.Count == 0)
                 return;
+            if (expectedVersions != null && expectedVersions.Count != docs.Count)
+                throw new ArgumentException("Expected one version per document", nameof(expectedVersions));
 
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
+            var collectionHandle = GetHandleOrThrow();
 
             if (_embeddingFunction != null)
             {
@@ -90,8 +1866,13 @@ namespace ChromaDB.NET
             var idsPtr = MarshalStringArray(ids);
 
             // Marshal embeddings
-            var embeddingDim = docs[0].Embedding.Length;
-            var embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
+            var embeddingDim = docs[0].Embedding != null ? docs[0].Embedding.Length : 0;
+            var embeddingsPtr = IntPtr.Zero;
+
+            if (embeddingDim > 0)
+            {
+                embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
+            }
 
             // Marshal metadata
             var metadataJsons = docs.Select(d => d.Metadata != null
@@ -105,8 +1886,7 @@ namespace ChromaDB.NET
 
             try
             {
-                var result = NativeMethods.chroma_add(
-                    clientHandle,
+                var result = NativeMethods.chroma_update(
                     collectionHandle,
                     idsPtr,
                     (UIntPtr)ids.Length,
@@ -114,6 +1894,10 @@ namespace ChromaDB.NET
                     (UIntPtr)embeddingDim,
                     metadataPtr,
                     textsPtr,
+                    null,
+                    null,
+                    expectedVersions?.ToArray(),
+                    IntPtr.Zero,
                     out var errorPtr);
 
                 ChromaClient.CheckError(result, errorPtr);
@@ -123,116 +1907,278 @@ namespace ChromaDB.NET
                 FreeStringArray(idsPtr, ids.Length);
                 FreeStringArray(metadataPtr, metadataJsons.Length);
                 FreeStringArray(textsPtr, texts.Length);
-                FreeEmbeddings(embeddingsPtr, docs.Count);
+                if (embeddingsPtr != IntPtr.Zero)
+                    FreeEmbeddings(embeddingsPtr, docs.Count);
             }
         }
 
         /// <summary>
-        /// Queries the collection for similar documents
+        /// Queues adds or updates on a write batch, see <see cref="ChromaBatch"/>
         /// </summary>
-        /// <param name="queryEmbedding">Query embedding vector</param>
-        /// <param name="nResults">Number of results to return</param>
+        internal void QueueWrites(ulong batchHandle, IEnumerable<ChromaDocument> documents, bool add)
+        {
+            WriteDocuments(documents, (ulong collectionHandle, IntPtr ids, UIntPtr idsCount, IntPtr embeddings,
+                UIntPtr embeddingDim, IntPtr metadatas, IntPtr texts, out IntPtr errorPtr) => add
+                ? NativeMethods.chroma_batch_add(
+                    batchHandle, collectionHandle, ids, idsCount, embeddings, embeddingDim, metadatas, texts, out errorPtr)
+                : NativeMethods.chroma_batch_update(
+                    batchHandle, collectionHandle, ids, idsCount, embeddings, embeddingDim, metadatas, texts, out errorPtr));
+        }
+
+        /// <summary>
+        /// Subscribes to the changes of this collection (<c>chroma_subscribe</c>), e.g. to invalidate
+        /// a cache or push notifications without polling. Adds, updates, upserts and deletes are
+        /// read from the native write log, whichever handle or process made them, and delivered in
+        /// log order within about 50 ms; <see cref="ChangeEvents.Drop"/> is delivered once if the
+        /// collection is deleted. <see cref="ChangeEvents.Gap"/> is delivered, whichever
+        /// <paramref name="events"/> are passed, when <see cref="Optimize"/> purged log entries
+        /// before they were read, after which cached records should be reloaded. The callback runs
+        /// on a native watcher thread, one change at a time, and must not throw. Dispose the
+        /// returned registration to stop it; the subscription also stops when this collection is
+        /// disposed.
+        /// </summary>
+        public unsafe CallbackRegistration Subscribe(Action<CollectionChange> callback, ChangeEvents events = ChangeEvents.All)
+        {
+            if (callback == null)
+                throw new ArgumentNullException(nameof(callback));
+
+            var collectionHandle = GetHandleOrThrow();
+            // A function pointer instead of a delegate, as for log callbacks
+            delegate* unmanaged[Cdecl]<IntPtr, int, IntPtr, IntPtr, ulong, void> onChange = &OnChange;
+            var state = GCHandle.Alloc(callback);
+            var result = NativeMethods.chroma_subscribe(
+                collectionHandle,
+                (int)events,
+                (IntPtr)onChange,
+                GCHandle.ToIntPtr(state),
+                out var callbackId,
+                out var errorPtr);
+
+            if (result != 0)
+            {
+                state.Free();
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            return new CallbackRegistration(callbackId, state);
+        }
+
+        [UnmanagedCallersOnly(CallConvs = new[] { typeof(CallConvCdecl) })]
+        private static void OnChange(IntPtr userData, int kind, IntPtr collectionId, IntPtr documentId, ulong sequence)
+        {
+            try
+            {
+                var callback = (Action<CollectionChange>)GCHandle.FromIntPtr(userData).Target!;
+                callback(new CollectionChange(
+                    (ChangeEvents)kind,
+                    Marshal.PtrToStringUTF8(collectionId) ?? "",
+                    Marshal.PtrToStringUTF8(documentId),
+                    (long)sequence));
+            }
+            catch
+            {
+                // Exceptions cannot cross into native code; a failing subscriber misses the change
+            }
+        }
+
+        /// <summary>
+        /// Queues documents on the native ingestion queue of the client and returns without
+        /// waiting for them to be written; embeddings missing from documents with text are
+        /// generated with the embedding function first. Background workers write them in batches,
+        /// leaving existing IDs unchanged. Call <see cref="ChromaClient.FlushIngestQueue"/> to wait
+        /// for them and learn about failed writes. Throws a <see cref="ChromaException"/> with
+        /// <see cref="ChromaErrorCode.Busy"/> when the queue is full.
+        /// </summary>
+        public void EnqueueAdd(IEnumerable<ChromaDocument> documents)
+        {
+            WriteDocuments(documents, NativeMethods.chroma_enqueue_add);
+        }
+
+        private delegate int DocumentsWrite(
+            ulong collectionHandle,
+            IntPtr ids,
+            UIntPtr idsCount,
+            IntPtr embeddings,
+            UIntPtr embeddingDim,
+            IntPtr metadatasJson,
+            IntPtr documents,
+            out IntPtr error);
+
+        /// <summary>
+        /// Embeds and marshals documents for a native call taking the record arrays of <c>chroma_add</c>
+        /// </summary>
+        private void WriteDocuments(IEnumerable<ChromaDocument> documents, DocumentsWrite write)
+        {
+            var docs = documents.ToList();
+            if (docs.Count == 0)
+                return;
+
+            var collectionHandle = GetHandleOrThrow();
+
+            if (_embeddingFunction != null)
+            {
+                var toEmbed = docs.Where(d => d.Embedding == null && !string.IsNullOrEmpty(d.Text)).ToList();
+                if (toEmbed.Count > 0)
+                {
+                    var embeddings = _embeddingFunction.GenerateEmbeddings(toEmbed.Select(d => d.Text).ToList());
+                    for (int i = 0; i < toEmbed.Count; i++)
+                        toEmbed[i].Embedding = embeddings[i];
+                }
+            }
+
+            var ids = docs.Select(d => d.Id).ToArray();
+            var idsPtr = MarshalStringArray(ids);
+
+            var embeddingDim = docs.FirstOrDefault(d => d.Embedding != null)?.Embedding.Length ?? 0;
+            var embeddingsPtr = embeddingDim > 0
+                ? MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray())
+                : IntPtr.Zero;
+
+            var metadataJsons = docs.Select(d => d.Metadata != null
+                ? MetadataConverter.SerializeMetadata(d.Metadata)
+                : null).ToArray();
+            var metadataPtr = MarshalStringArray(metadataJsons);
+
+            var texts = docs.Select(d => d.Text).ToArray();
+            var textsPtr = MarshalStringArray(texts);
+
+            try
+            {
+                var result = write(
+                    collectionHandle, idsPtr, (UIntPtr)ids.Length, embeddingsPtr,
+                    (UIntPtr)embeddingDim, metadataPtr, textsPtr, out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, ids.Length);
+                FreeStringArray(metadataPtr, metadataJsons.Length);
+                FreeStringArray(textsPtr, texts.Length);
+                if (embeddingsPtr != IntPtr.Zero)
+                    FreeEmbeddings(embeddingsPtr, docs.Count);
+            }
+        }
+
+        /// <summary>
+        /// Queues a delete on a write batch, see <see cref="ChromaBatch"/>
+        /// </summary>
+        internal void QueueDelete(
+            ulong batchHandle,
+            IEnumerable<string>? ids,
+            Dictionary<string, object>? whereFilter,
+            string? whereDocument)
+        {
+            if (ids == null && whereFilter == null && whereDocument == null)
+                throw new ArgumentException("You must provide at least one of: ids, whereFilter, or whereDocument");
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsArray = ids?.ToArray() ?? Array.Empty<string>();
+            var idsPtr = idsArray.Length > 0 ? MarshalStringArray(idsArray) : IntPtr.Zero;
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            try
+            {
+                var result = NativeMethods.chroma_batch_delete(
+                    batchHandle,
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    whereFilterJson,
+                    whereDocument,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                if (idsPtr != IntPtr.Zero)
+                    FreeStringArray(idsPtr, idsArray.Length);
+            }
+        }
+
+        /// <summary>
+        /// Sets metadata on the documents matching the filters in a single native call, e.g. to
+        /// set <c>reviewed = true</c> where <c>source = "x"</c>. Keys set to null are removed.
+        /// </summary>
+        /// <param name="metadata">Metadata keys to set on each matching document</param>
         /// <param name="whereFilter">Metadata filter</param>
         /// <param name="whereDocument">Document content filter</param>
-        /// <param name="includeMetadatas">Include metadata in results</param>
-        /// <param name="includeDocuments">Include document text in results</param>
-        /// <param name="includeDistances">Include distance scores in results</param>
-        /// <returns>Query results</returns>
-        public QueryResult Query(
-            float[] queryEmbedding,
-            int nResults = 10,
-            Dictionary<string, object> whereFilter = null,
-            string whereDocument = null,
-            bool includeMetadatas = true,
-            bool includeDocuments = true,
-            bool includeDistances = true)
+        /// <param name="ids">Optional IDs to restrict the update to</param>
+        /// <returns>IDs of the updated documents</returns>
+        public List<string> UpdateWhere(
+            Dictionary<string, object> metadata,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            IEnumerable<string>? ids = null)
         {
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
-            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            if (metadata == null)
+                throw new ArgumentNullException(nameof(metadata));
+            if (whereFilter == null && whereDocument == null)
+                throw new ArgumentException("You must provide at least one of: whereFilter or whereDocument");
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsArray = ids?.ToArray();
+            if (idsArray != null && idsArray.Length == 0)
+                return new List<string>();
+
+            var idsPtr = idsArray != null ? MarshalStringArray(idsArray) : IntPtr.Zero;
+
+            // A filter-only update takes a single metadata entry applied to every match
+            var metadataJsons = Enumerable.Repeat(
+                MetadataConverter.SerializeMetadata(metadata),
+                idsArray?.Length ?? 1).ToArray();
+            var metadataPtr = MarshalStringArray(metadataJsons);
 
-            var embeddingPtr = Marshal.AllocHGlobal(queryEmbedding.Length * sizeof(float));
-            Marshal.Copy(queryEmbedding, 0, embeddingPtr, queryEmbedding.Length);
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            var updatedIdsPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
 
             try
             {
-                var result = NativeMethods.chroma_query(
-                    clientHandle,
+                var result = NativeMethods.chroma_update(
                     collectionHandle,
-                    embeddingPtr,
-                    (UIntPtr)queryEmbedding.Length,
-                    (uint)nResults,
+                    idsPtr,
+                    (UIntPtr)(idsArray?.Length ?? 0),
+                    IntPtr.Zero,
+                    UIntPtr.Zero,
+                    metadataPtr,
+                    IntPtr.Zero,
                     whereFilterJson,
                     whereDocument,
-                    false, // Don't include embeddings in response for simplicity
-                    includeMetadatas,
-                    includeDocuments,
-                    includeDistances,
-                    out var queryResultPtr,
+                    null,
+                    updatedIdsPtr,
                     out var errorPtr);
 
                 ChromaClient.CheckError(result, errorPtr);
 
                 try
                 {
-                    return MarshalQueryResult(queryResultPtr);
+                    return MarshalResultSet(updatedIdsPtr);
                 }
                 finally
                 {
-                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                    NativeMethods.chroma_free_result_set(updatedIdsPtr);
                 }
             }
             finally
             {
-                Marshal.FreeHGlobal(embeddingPtr);
+                if (idsPtr != IntPtr.Zero)
+                    FreeStringArray(idsPtr, idsArray!.Length);
+                FreeStringArray(metadataPtr, metadataJsons.Length);
+                Marshal.FreeHGlobal(updatedIdsPtr);
             }
         }
 
         /// <summary>
-        /// Queries the collection using text
-        /// </summary>
-        /// <param name="queryText">Text to search for</param>
-        /// <param name="nResults">Number of results to return</param>
-        /// <param name="whereFilter">Metadata filter</param>
-        /// <param name="whereDocument">Document content filter</param>
-        /// <param name="includeMetadatas">Include metadata in results</param>
-        /// <param name="includeDocuments">Include document text in results</param>
-        /// <param name="includeDistances">Include distance scores in results</param>
-        /// <returns>Query results</returns>
-        public QueryResult Query(
-            string queryText,
-            int nResults = 10,
-            Dictionary<string, object> whereFilter = null,
-            string whereDocument = null,
-            bool includeMetadatas = true,
-            bool includeDocuments = true,
-            bool includeDistances = true)
-        {
-            if (_embeddingFunction == null)
-                throw new ChromaException("Cannot query by text without an embedding function");
-
-            var queryEmbedding = _embeddingFunction.GenerateEmbeddings(new[] { queryText })[0];
-
-            return Query(
-                queryEmbedding,
-                nResults,
-                whereFilter,
-                whereDocument,
-                includeMetadatas,
-                includeDocuments,
-                includeDistances);
-        }
-
-        /// <summary>
-        /// Updates existing documents in the collection
+        /// Upserts documents (insert if not present, update if present)
         /// </summary>
-        /// <param name="documents">Documents to update</param>
-        public void Update(IEnumerable<ChromaDocument> documents)
+        /// <param name="documents">Documents to upsert</param>
+        public void Upsert(IEnumerable<ChromaDocument> documents)
         {
             var docs = documents.ToList();
             if (docs.Count == 0)
                 return;
 
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
+            var collectionHandle = GetHandleOrThrow();
 
             if (_embeddingFunction != null)
             {
@@ -257,13 +2203,8 @@ namespace ChromaDB.NET
             var idsPtr = MarshalStringArray(ids);
 
             // Marshal embeddings
-            var embeddingDim = docs[0].Embedding != null ? docs[0].Embedding.Length : 0;
-            var embeddingsPtr = IntPtr.Zero;
-
-            if (embeddingDim > 0)
-            {
-                embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
-            }
+            var embeddingDim = docs[0].Embedding.Length;
+            var embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
 
             // Marshal metadata
             var metadataJsons = docs.Select(d => d.Metadata != null
@@ -277,8 +2218,7 @@ namespace ChromaDB.NET
 
             try
             {
-                var result = NativeMethods.chroma_update(
-                    clientHandle,
+                var result = NativeMethods.chroma_upsert(
                     collectionHandle,
                     idsPtr,
                     (UIntPtr)ids.Length,
@@ -295,98 +2235,349 @@ namespace ChromaDB.NET
                 FreeStringArray(idsPtr, ids.Length);
                 FreeStringArray(metadataPtr, metadataJsons.Length);
                 FreeStringArray(textsPtr, texts.Length);
-                if (embeddingsPtr != IntPtr.Zero)
-                    FreeEmbeddings(embeddingsPtr, docs.Count);
+                FreeEmbeddings(embeddingsPtr, docs.Count);
             }
         }
 
         /// <summary>
-        /// Upserts documents (insert if not present, update if present)
+        /// Deletes documents from the collection
         /// </summary>
-        /// <param name="documents">Documents to upsert</param>
-        public void Upsert(IEnumerable<ChromaDocument> documents)
+        /// <param name="ids">Optional list of document IDs to delete</param>
+        /// <param name="whereFilter">Optional metadata filter</param>
+        /// <param name="whereDocument">Optional document content filter</param>
+        /// <param name="soft">Keep the documents but hide them from reads until they are restored
+        /// with <see cref="RestoreRecords"/> or removed with <see cref="PurgeDeleted"/></param>
+        /// <param name="expectedVersions">Optional version of each of <paramref name="ids"/>, as read with
+        /// <see cref="QueryResult.Versions"/> (negative to skip one); when any document changed
+        /// since, nothing is deleted and a <see cref="ChromaErrorCode.ConflictError"/> is thrown</param>
+        public void Delete(
+            IEnumerable<string>? ids = null,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            bool soft = false,
+            IReadOnlyList<long>? expectedVersions = null)
         {
-            var docs = documents.ToList();
-            if (docs.Count == 0)
-                return;
+            DeleteCore(ids, whereFilter, whereDocument, returnDeletedIds: false, dryRun: false, soft: soft,
+                expectedVersions: expectedVersions);
+        }
+
+        /// <summary>
+        /// Deletes documents from the collection and returns the IDs that were deleted,
+        /// e.g. for audit logging
+        /// </summary>
+        /// <param name="ids">Optional list of document IDs to delete</param>
+        /// <param name="whereFilter">Optional metadata filter</param>
+        /// <param name="whereDocument">Optional document content filter</param>
+        /// <param name="soft">Keep the documents but hide them from reads</param>
+        /// <returns>IDs of the documents that matched and were deleted</returns>
+        public List<string> DeleteAndReturnIds(
+            IEnumerable<string>? ids = null,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            bool soft = false)
+        {
+            return DeleteCore(ids, whereFilter, whereDocument, returnDeletedIds: true, dryRun: false, soft: soft)!;
+        }
 
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
+        /// <summary>
+        /// Returns the IDs a delete with the same arguments would remove, without deleting
+        /// anything, so a destructive filter can be verified first
+        /// </summary>
+        /// <param name="ids">Optional list of document IDs</param>
+        /// <param name="whereFilter">Optional metadata filter</param>
+        /// <param name="whereDocument">Optional document content filter</param>
+        /// <returns>IDs of the documents that match</returns>
+        public List<string> PreviewDelete(
+            IEnumerable<string>? ids = null,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null)
+        {
+            return DeleteCore(ids, whereFilter, whereDocument, returnDeletedIds: true, dryRun: true, soft: false)!;
+        }
 
-            if (_embeddingFunction != null)
+        /// <summary>
+        /// Deletes a large number of documents by ID in batches run inside the native library.
+        /// A failed batch does not stop the following ones; its IDs are reported in
+        /// <see cref="BatchDeleteResult.FailedIds"/> instead of throwing.
+        /// </summary>
+        /// <param name="ids">IDs of the documents to delete</param>
+        /// <param name="batchSize">IDs per batch, 0 for the native default of 1000</param>
+        /// <param name="onProgress">Called after every batch with the number of IDs processed and the total</param>
+        /// <param name="cancellationToken">Stops the delete after the current batch</param>
+        public BatchDeleteResult DeleteBatched(
+            IEnumerable<string> ids,
+            int batchSize = 0,
+            Action<int, int>? onProgress = null,
+            CancellationToken cancellationToken = default)
+        {
+            var idsArray = ids.ToArray();
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
+            var failedIdsPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            NativeMethods.ProgressCallback callback = (processed, total, _) =>
             {
-                var textsToEmbed = docs.Where(d => d.Embedding == null && !string.IsNullOrEmpty(d.Text))
-                    .Select(d => d.Text)
-                    .ToList();
+                onProgress?.Invoke((int)processed.ToUInt64(), (int)total.ToUInt64());
+                return !cancellationToken.IsCancellationRequested;
+            };
 
-                if (textsToEmbed.Count > 0)
+            try
+            {
+                var result = NativeMethods.chroma_delete_batched(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    (UIntPtr)batchSize,
+                    callback,
+                    IntPtr.Zero,
+                    out var stats,
+                    failedIdsPtr,
+                    out var errorPtr);
+                GC.KeepAlive(callback);
+
+                ChromaErrorInfo? error = null;
+                if (result != 0)
                 {
-                    var embeddings = _embeddingFunction.GenerateEmbeddings(textsToEmbed);
-                    int index = 0;
+                    if (stats.Batches == UIntPtr.Zero)
+                        ChromaClient.CheckError(result, errorPtr);
 
-                    foreach (var doc in docs.Where(d => d.Embedding == null && !string.IsNullOrEmpty(d.Text)))
+                    try
                     {
-                        doc.Embedding = embeddings[index++];
+                        error = ChromaClient.MarshalError(errorPtr);
                     }
+                    finally
+                    {
+                        NativeMethods.chroma_free_error(errorPtr);
+                    }
+                }
+
+                List<string> failedIds;
+                try
+                {
+                    failedIds = MarshalResultSet(failedIdsPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(failedIdsPtr);
                 }
+
+                return new BatchDeleteResult(
+                    (int)stats.Succeeded.ToUInt64(),
+                    failedIds,
+                    (int)stats.Batches.ToUInt64(),
+                    (int)stats.FailedBatches.ToUInt64(),
+                    stats.Cancelled,
+                    error);
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+                Marshal.FreeHGlobal(failedIdsPtr);
             }
+        }
 
-            // Marshal IDs
-            var ids = docs.Select(d => d.Id).ToArray();
-            var idsPtr = MarshalStringArray(ids);
+        /// <summary>
+        /// Starts deleting documents by ID in batches on a native background thread and returns
+        /// at once. Poll the returned operation for progress, or wait for it; it fails with the
+        /// error of the first failed batch. Disposing the operation cancels it if still running.
+        /// </summary>
+        /// <param name="ids">IDs of the documents to delete</param>
+        /// <param name="batchSize">IDs per batch, 0 for the native default of 1000</param>
+        public Operation StartDeleteBatched(IEnumerable<string> ids, int batchSize = 0)
+        {
+            var idsArray = ids.ToArray();
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
 
-            // Marshal embeddings
-            var embeddingDim = docs[0].Embedding.Length;
-            var embeddingsPtr = MarshalEmbeddings(docs.Select(d => d.Embedding).ToArray());
+            try
+            {
+                var result = NativeMethods.chroma_delete_batched_async(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    (UIntPtr)batchSize,
+                    out var operationHandle,
+                    out var errorPtr);
 
-            // Marshal metadata
-            var metadataJsons = docs.Select(d => d.Metadata != null
-                ? MetadataConverter.SerializeMetadata(d.Metadata)
-                : null).ToArray();
-            var metadataPtr = MarshalStringArray(metadataJsons);
+                ChromaClient.CheckError(result, errorPtr);
+                return new Operation(_client, operationHandle);
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+            }
+        }
 
-            // Marshal documents
-            var texts = docs.Select(d => d.Text).ToArray();
-            var textsPtr = MarshalStringArray(texts);
+        private List<string>? DeleteCore(
+            IEnumerable<string>? ids,
+            Dictionary<string, object>? whereFilter,
+            string? whereDocument,
+            bool returnDeletedIds,
+            bool dryRun,
+            bool soft,
+            IReadOnlyList<long>? expectedVersions = null)
+        {
+            if (ids == null && whereFilter == null && whereDocument == null)
+                throw new ArgumentException("You must provide at least one of: ids, whereFilter, or whereDocument");
+
+            var collectionHandle = GetHandleOrThrow();
+            IntPtr idsPtr = IntPtr.Zero;
+            UIntPtr idsCount = UIntPtr.Zero;
+
+            if (ids != null)
+            {
+                var idsList = ids.ToList();
+                if (idsList.Count > 0)
+                {
+                    idsPtr = MarshalStringArray(idsList.ToArray());
+                    idsCount = (UIntPtr)idsList.Count;
+                }
+            }
+
+            if (expectedVersions != null && (ulong)expectedVersions.Count != idsCount.ToUInt64())
+            {
+                if (idsPtr != IntPtr.Zero)
+                    FreeStringArray(idsPtr, (int)idsCount.ToUInt64());
+                throw new ArgumentException("Expected one version per ID", nameof(expectedVersions));
+            }
+
+            // Convert whereFilter to JSON
+            string whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+
+            var deletedIdsPtr = returnDeletedIds
+                ? Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>())
+                : IntPtr.Zero;
 
             try
             {
-                var result = NativeMethods.chroma_upsert(
-                    clientHandle,
+                var result = NativeMethods.chroma_delete(
                     collectionHandle,
                     idsPtr,
-                    (UIntPtr)ids.Length,
-                    embeddingsPtr,
-                    (UIntPtr)embeddingDim,
-                    metadataPtr,
-                    textsPtr,
+                    idsCount,
+                    whereFilterJson,
+                    whereDocument,
+                    dryRun,
+                    soft,
+                    expectedVersions?.ToArray(),
+                    deletedIdsPtr,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+
+                if (deletedIdsPtr == IntPtr.Zero)
+                    return null;
+
+                try
+                {
+                    return MarshalResultSet(deletedIdsPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(deletedIdsPtr);
+                }
+            }
+            finally
+            {
+                if (idsPtr != IntPtr.Zero)
+                {
+                    FreeStringArray(idsPtr, (int)idsCount.ToUInt64());
+                }
+                if (deletedIdsPtr != IntPtr.Zero)
+                {
+                    Marshal.FreeHGlobal(deletedIdsPtr);
+                }
+            }
+        }
+
+        /// <summary>
+        /// Gets the IDs of the documents matching the IDs and filters; documents, metadata and
+        /// embeddings are neither read nor marshaled
+        /// </summary>
+        /// <param name="ids">Optional list of document IDs to look up</param>
+        /// <param name="whereFilter">Optional metadata filter</param>
+        /// <param name="whereDocument">Optional document content filter</param>
+        /// <param name="limit">Maximum number of IDs to return</param>
+        /// <param name="offset">Number of IDs to skip</param>
+        public List<string> GetIds(
+            IEnumerable<string>? ids = null,
+            Dictionary<string, object>? whereFilter = null,
+            string? whereDocument = null,
+            uint limit = 0,
+            uint offset = 0)
+        {
+            var collectionHandle = GetHandleOrThrow();
+            var idsArray = ids?.ToArray();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            var idsPtr = MarshalStringArray(idsArray);
+            var resultSetPtr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaResultSetNative>());
+
+            try
+            {
+                var result = NativeMethods.chroma_get_ids(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)(idsArray?.Length ?? 0),
+                    whereFilterJson,
+                    whereDocument,
+                    limit,
+                    offset,
+                    resultSetPtr,
                     out var errorPtr);
 
                 ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalResultSet(resultSetPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_result_set(resultSetPtr);
+                }
             }
             finally
             {
-                FreeStringArray(idsPtr, ids.Length);
-                FreeStringArray(metadataPtr, metadataJsons.Length);
-                FreeStringArray(textsPtr, texts.Length);
-                FreeEmbeddings(embeddingsPtr, docs.Count);
+                Marshal.FreeHGlobal(resultSetPtr);
+                if (idsPtr != IntPtr.Zero)
+                    FreeStringArray(idsPtr, idsArray!.Length);
             }
         }
 
         /// <summary>
-        /// Deletes documents from the collection
+        /// Gets documents from the collection
         /// </summary>
-        /// <param name="ids">Optional list of document IDs to delete</param>
+        /// <param name="ids">Optional list of document IDs to retrieve</param>
         /// <param name="whereFilter">Optional metadata filter</param>
         /// <param name="whereDocument">Optional document content filter</param>
-        public void Delete(
+        /// <param name="limit">Maximum number of documents to return</param>
+        /// <param name="offset">Number of documents to skip</param>
+        /// <param name="includeEmbeddings">Include embeddings in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeMetadatas">Include metadata in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="includeDocuments">Include document text in results; null for <see cref="DefaultIncludes"/></param>
+        /// <param name="metadataKeys">Optional metadata keys to return; other keys are dropped natively</param>
+        /// <param name="orderBy">Optional sort order, applied before limit and offset</param>
+        /// <param name="countTotal">Fill <see cref="QueryResult.TotalMatchingCount"/>, which may cost an extra read</param>
+        /// <param name="includeVersions">Fill <see cref="QueryResult.Versions"/> with the version token of each record</param>
+        /// <returns>Query results</returns>
+        public QueryResult Get(
             IEnumerable<string>? ids = null,
             Dictionary<string, object>? whereFilter = null,
-            string? whereDocument = null)
+            string? whereDocument = null,
+            uint limit = 0,
+            uint offset = 0,
+            bool? includeEmbeddings = null,
+            bool? includeMetadatas = null,
+            bool? includeDocuments = null,
+            IEnumerable<string>? metadataKeys = null,
+            OrderBy? orderBy = null,
+            bool countTotal = false,
+            bool includeVersions = false)
         {
-            if (ids == null && whereFilter == null && whereDocument == null)
-                throw new ArgumentException("You must provide at least one of: ids, whereFilter, or whereDocument");
+            var collectionHandle = GetHandleOrThrow();
+            var defaults = includeEmbeddings.HasValue && includeMetadatas.HasValue && includeDocuments.HasValue
+                ? Include.Default
+                : DefaultIncludes;
 
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
             IntPtr idsPtr = IntPtr.Zero;
             UIntPtr idsCount = UIntPtr.Zero;
 
@@ -403,18 +2594,42 @@ namespace ChromaDB.NET
             // Convert whereFilter to JSON
             string whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
 
+            var metadataKeysArray = metadataKeys?.ToArray();
+            var metadataKeysPtr = MarshalMetadataKeys(metadataKeysArray);
+            var orderByPtr = MarshalOrderBy(orderBy);
+
             try
             {
-                var result = NativeMethods.chroma_delete(
-                    clientHandle,
+                var result = NativeMethods.chroma_get(
                     collectionHandle,
                     idsPtr,
                     idsCount,
                     whereFilterJson,
                     whereDocument,
+                    limit,
+                    offset,
+                    orderByPtr,
+                    includeEmbeddings ?? defaults.HasFlag(Include.Embeddings),
+                    includeMetadatas ?? defaults.HasFlag(Include.Metadatas),
+                    includeDocuments ?? defaults.HasFlag(Include.Documents),
+                    metadataKeysPtr,
+                    (UIntPtr)(metadataKeysArray?.Length ?? 0),
+                    MetadataFormat,
+                    countTotal,
+                    includeVersions,
+                    out var queryResultPtr,
                     out var errorPtr);
 
                 ChromaClient.CheckError(result, errorPtr);
+
+                try
+                {
+                    return MarshalQueryResult(queryResultPtr);
+                }
+                finally
+                {
+                    NativeMethods.chroma_free_query_result(queryResultPtr);
+                }
             }
             finally
             {
@@ -422,12 +2637,15 @@ namespace ChromaDB.NET
                 {
                     FreeStringArray(idsPtr, (int)idsCount.ToUInt64());
                 }
+                FreeMetadataKeys(metadataKeysPtr, metadataKeysArray);
+                FreeOrderBy(orderByPtr);
             }
         }
 
         /// <summary>
-        /// Gets documents from the collection
+        /// Gets documents from the collection and returns the full response encoded in a single buffer
         /// </summary>
+        /// <param name="format">Encoding of the returned buffer</param>
         /// <param name="ids">Optional list of document IDs to retrieve</param>
         /// <param name="whereFilter">Optional metadata filter</param>
         /// <param name="whereDocument">Optional document content filter</param>
@@ -436,8 +2654,9 @@ namespace ChromaDB.NET
         /// <param name="includeEmbeddings">Include embeddings in results</param>
         /// <param name="includeMetadatas">Include metadata in results</param>
         /// <param name="includeDocuments">Include document text in results</param>
-        /// <returns>Query results</returns>
-        public QueryResult Get(
+        /// <returns>The encoded get response</returns>
+        public byte[] GetSerialized(
+            SerializationFormat format,
             IEnumerable<string>? ids = null,
             Dictionary<string, object>? whereFilter = null,
             string? whereDocument = null,
@@ -447,28 +2666,17 @@ namespace ChromaDB.NET
             bool includeMetadatas = true,
             bool includeDocuments = true)
         {
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
+            var collectionHandle = GetHandleOrThrow();
 
-            IntPtr idsPtr = IntPtr.Zero;
-            UIntPtr idsCount = UIntPtr.Zero;
-
-            if (ids != null)
-            {
-                var idsList = ids.ToList();
-                if (idsList.Count > 0)
-                {
-                    idsPtr = MarshalStringArray(idsList.ToArray());
-                    idsCount = (UIntPtr)idsList.Count;
-                }
-            }
+            var idsArray = ids?.ToArray();
+            var idsPtr = idsArray != null && idsArray.Length > 0 ? MarshalStringArray(idsArray) : IntPtr.Zero;
+            var idsCount = idsPtr != IntPtr.Zero ? (UIntPtr)idsArray!.Length : UIntPtr.Zero;
 
-            // Convert whereFilter to JSON
             string whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
 
             try
             {
-                var result = NativeMethods.chroma_get(
-                    clientHandle,
+                var result = NativeMethods.chroma_get_serialized(
                     collectionHandle,
                     idsPtr,
                     idsCount,
@@ -479,19 +2687,14 @@ namespace ChromaDB.NET
                     includeEmbeddings,
                     includeMetadatas,
                     includeDocuments,
-                    out var queryResultPtr,
+                    (int)format,
+                    out var bufferPtr,
+                    out var length,
                     out var errorPtr);
 
                 ChromaClient.CheckError(result, errorPtr);
 
-                try
-                {
-                    return MarshalQueryResult(queryResultPtr);
-                }
-                finally
-                {
-                    NativeMethods.chroma_free_query_result(queryResultPtr);
-                }
+                return CopyAndFreeBuffer(bufferPtr, length);
             }
             finally
             {
@@ -518,6 +2721,53 @@ namespace ChromaDB.NET
             ).FirstOrDefault();
         }
 
+        /// <summary>
+        /// Gets a single document by ID without marshaling arrays
+        /// </summary>
+        /// <param name="id">Document ID</param>
+        /// <param name="include">Fields to return; <see cref="Include.Default"/> for <see cref="DefaultIncludes"/></param>
+        /// <returns>The document or null if not found</returns>
+        public ChromaDocument? GetOne(string id, Include include = Include.Default)
+        {
+            if (id == null)
+                throw new ArgumentNullException(nameof(id));
+
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_get_one(collectionHandle, id, (uint)include, out var recordPtr, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            if (recordPtr == IntPtr.Zero)
+                return null;
+
+            try
+            {
+                var record = Marshal.PtrToStructure<ChromaRecordNative>(recordPtr);
+
+                float[]? embedding = null;
+                var dimension = (int)record.EmbeddingDim.ToUInt64();
+                if (record.Embedding != IntPtr.Zero && dimension > 0)
+                {
+                    embedding = new float[dimension];
+                    Marshal.Copy(record.Embedding, embedding, 0, dimension);
+                }
+
+                var metadataJson = record.MetadataJson != IntPtr.Zero ? Marshal.PtrToStringAnsi(record.MetadataJson) : null;
+
+                return new ChromaDocument
+                {
+                    Id = Marshal.PtrToStringAnsi(record.Id),
+                    Text = record.Document != IntPtr.Zero ? Marshal.PtrToStringAnsi(record.Document) : null,
+                    Metadata = metadataJson != null ? JsonSerializer.Deserialize<Dictionary<string, object>>(metadataJson) : null,
+                    Embedding = embedding,
+                    Uri = record.Uri != IntPtr.Zero ? Marshal.PtrToStringAnsi(record.Uri) : null
+                };
+            }
+            finally
+            {
+                NativeMethods.chroma_free_record(recordPtr);
+            }
+        }
+
         /// <summary>
         /// Gets documents matching the specified filter
         /// </summary>
@@ -528,13 +2778,12 @@ namespace ChromaDB.NET
         /// <returns>Query results</returns>
         public QueryResult Where(WhereFilter filter, uint limit = 0, uint offset = 0, bool includeEmbeddings = false)
         {
-            var (clientHandle, collectionHandle) = GetHandlesOrThrow();
+            var collectionHandle = GetHandleOrThrow();
             string whereFilterJson = JsonSerializer.Serialize(filter, WhereFilterSerializerOptions);
 
             try
             {
                 var result = NativeMethods.chroma_get(
-                    clientHandle,
                     collectionHandle,
                     IntPtr.Zero, // No specific IDs
                     UIntPtr.Zero,
@@ -542,9 +2791,15 @@ namespace ChromaDB.NET
                     null, // No document filter
                     limit,
                     offset,
+                    IntPtr.Zero, // Backend order
                     includeEmbeddings,
                     true, // Include metadatas
                     true, // Include documents
+                    IntPtr.Zero, // All metadata keys
+                    UIntPtr.Zero,
+                    MetadataFormat,
+                    false, // No total count
+                    false, // No versions
                     out var queryResultPtr,
                     out var errorPtr);
 
@@ -632,12 +2887,47 @@ namespace ChromaDB.NET
         }
 
         /// <summary>
-        /// Updates a single document in the collection
+        /// Updates a single document in the collection; null fields are left unchanged
         /// </summary>
         /// <param name="document">Document to update</param>
         public void Update(ChromaDocument document)
         {
-            Update(new[] { document });
+            if (document == null)
+                throw new ArgumentNullException(nameof(document));
+
+            var collectionHandle = GetHandleOrThrow();
+
+            var embedding = document.Embedding;
+            if (embedding == null && _embeddingFunction != null && !string.IsNullOrEmpty(document.Text))
+                embedding = _embeddingFunction.GenerateEmbeddings(new[] { document.Text })[0];
+
+            var metadataJson = document.Metadata != null ? MetadataConverter.SerializeMetadata(document.Metadata) : null;
+
+            var embeddingPtr = IntPtr.Zero;
+            if (embedding != null)
+            {
+                embeddingPtr = Marshal.AllocHGlobal(embedding.Length * sizeof(float));
+                Marshal.Copy(embedding, 0, embeddingPtr, embedding.Length);
+            }
+
+            try
+            {
+                var result = NativeMethods.chroma_update_one(
+                    collectionHandle,
+                    document.Id,
+                    embeddingPtr,
+                    (UIntPtr)(embedding?.Length ?? 0),
+                    metadataJson,
+                    document.Text,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                if (embeddingPtr != IntPtr.Zero)
+                    Marshal.FreeHGlobal(embeddingPtr);
+            }
         }
 
         /// <summary>
@@ -671,13 +2961,75 @@ namespace ChromaDB.NET
             Upsert(ChromaDocument.Create(id, text, metadata));
         }
 
+        /// <summary>
+        /// Applies one metadata patch to many documents in a single native call, leaving their
+        /// embeddings and documents unchanged
+        /// </summary>
+        /// <param name="ids">IDs of the documents to patch; IDs that do not exist are ignored</param>
+        /// <param name="patch">Metadata keys to set; with <see cref="MetadataMergeMode.Merge"/> keys
+        /// set to null are removed</param>
+        /// <param name="mode">Whether the patch is merged into or replaces the existing metadata</param>
+        public void UpdateMetadata(
+            IEnumerable<string> ids,
+            Dictionary<string, object> patch,
+            MetadataMergeMode mode = MetadataMergeMode.Merge)
+        {
+            if (ids == null)
+                throw new ArgumentNullException(nameof(ids));
+            if (patch == null)
+                throw new ArgumentNullException(nameof(patch));
+
+            var idsArray = ids.ToArray();
+            if (idsArray.Length == 0)
+                return;
+
+            var collectionHandle = GetHandleOrThrow();
+            var idsPtr = MarshalStringArray(idsArray);
+
+            try
+            {
+                var result = NativeMethods.chroma_update_metadata(
+                    collectionHandle,
+                    idsPtr,
+                    (UIntPtr)idsArray.Length,
+                    MetadataConverter.SerializeMetadata(patch),
+                    (int)mode,
+                    out var errorPtr);
+
+                ChromaClient.CheckError(result, errorPtr);
+            }
+            finally
+            {
+                FreeStringArray(idsPtr, idsArray.Length);
+            }
+        }
+
         /// <summary>
         /// Deletes a document by ID
         /// </summary>
         /// <param name="id">Document ID</param>
         public void Delete(string id)
         {
-            Delete(new[] { id });
+            DeleteOne(id);
+        }
+
+        /// <summary>
+        /// Deletes a single document by ID without marshaling arrays
+        /// </summary>
+        /// <param name="id">Document ID</param>
+        /// <param name="soft">Keep the document but hide it from reads until it is restored
+        /// with <see cref="RestoreRecords"/> or removed with <see cref="PurgeDeleted"/></param>
+        /// <returns>Whether the document existed and was deleted</returns>
+        public bool DeleteOne(string id, bool soft = false)
+        {
+            if (id == null)
+                throw new ArgumentNullException(nameof(id));
+
+            var collectionHandle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_delete_one(collectionHandle, id, soft, out var deleted, out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+            return deleted;
         }
 
         /// <summary>
@@ -694,8 +3046,8 @@ namespace ChromaDB.NET
         /// </summary>
         protected virtual void Dispose(bool disposing)
         {
-            var handle = Interlocked.Exchange(ref _handle, IntPtr.Zero);
-            if (handle == IntPtr.Zero)
+            var handle = Interlocked.Exchange(ref _handle, 0UL);
+            if (handle == 0)
                 return;
 
             var result = NativeMethods.chroma_destroy_collection(handle, out var errorPtr);
@@ -724,7 +3076,7 @@ namespace ChromaDB.NET
 
         #region Marshaling Helpers
 
-        private static IntPtr MarshalStringArray(string[] strings)
+        internal static IntPtr MarshalStringArray(string[] strings)
         {
             if (strings == null || strings.Length == 0)
                 return IntPtr.Zero;
@@ -743,7 +3095,7 @@ namespace ChromaDB.NET
             return arrayPtr;
         }
 
-        private static void FreeStringArray(IntPtr arrayPtr, int length)
+        internal static void FreeStringArray(IntPtr arrayPtr, int length)
         {
             if (arrayPtr == IntPtr.Zero)
                 return;
@@ -760,6 +3112,113 @@ namespace ChromaDB.NET
             Marshal.FreeHGlobal(arrayPtr);
         }
 
+        private static IntPtr MarshalMetadataKeys(string[]? keys)
+        {
+            if (keys == null)
+                return IntPtr.Zero;
+
+            // An empty projection still needs a non-null array so no keys are returned
+            if (keys.Length == 0)
+                return Marshal.AllocHGlobal(IntPtr.Size);
+
+            return MarshalStringArray(keys);
+        }
+
+        private static void FreeMetadataKeys(IntPtr arrayPtr, string[]? keys)
+        {
+            if (arrayPtr == IntPtr.Zero)
+                return;
+
+            if (keys!.Length == 0)
+                Marshal.FreeHGlobal(arrayPtr);
+            else
+                FreeStringArray(arrayPtr, keys.Length);
+        }
+
+        private static List<string> MarshalResultSet(IntPtr resultSetPtr)
+        {
+            var resultSet = Marshal.PtrToStructure<ChromaResultSetNative>(resultSetPtr);
+            var ids = new List<string>((int)resultSet.Count);
+            for (int i = 0; i < (int)resultSet.Count; i++)
+                ids.Add(Marshal.PtrToStringAnsi(Marshal.ReadIntPtr(resultSet.Ids, i * IntPtr.Size)));
+            return ids;
+        }
+
+        private static IntPtr MarshalOrderBy(OrderBy? orderBy)
+        {
+            if (orderBy == null)
+                return IntPtr.Zero;
+
+            var native = new ChromaOrderByNative
+            {
+                Field = orderBy.MetadataKey == null ? OrderByFieldId : OrderByFieldMetadata,
+                MetadataKey = orderBy.MetadataKey != null ? Marshal.StringToHGlobalAnsi(orderBy.MetadataKey) : IntPtr.Zero,
+                Descending = orderBy.Descending
+            };
+
+            var ptr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaOrderByNative>());
+            Marshal.StructureToPtr(native, ptr, false);
+            return ptr;
+        }
+
+        private static void FreeOrderBy(IntPtr ptr)
+        {
+            if (ptr == IntPtr.Zero)
+                return;
+
+            var native = Marshal.PtrToStructure<ChromaOrderByNative>(ptr);
+            if (native.MetadataKey != IntPtr.Zero)
+                Marshal.FreeHGlobal(native.MetadataKey);
+            Marshal.FreeHGlobal(ptr);
+        }
+
+        private static IntPtr MarshalQueryOptions(QueryOptions? options, out NativeMethods.RerankCallback? reranker)
+        {
+            reranker = null;
+            if (options == null)
+                return IntPtr.Zero;
+
+            if (options.OverFetch < 0)
+                throw new ArgumentOutOfRangeException(nameof(options), "Over-fetch factor cannot be negative");
+            if (options.MmrFetchK < 0)
+                throw new ArgumentOutOfRangeException(nameof(options), "MMR candidate count cannot be negative");
+            if (options.RerankFetchK < 0)
+                throw new ArgumentOutOfRangeException(nameof(options), "Re-rank candidate count cannot be negative");
+
+            if (options.Reranker != null)
+                reranker = RerankerCallback.ToNative(options.Reranker);
+
+            var native = new ChromaQueryOptionsNative
+            {
+                StructSize = (UIntPtr)Marshal.SizeOf<ChromaQueryOptionsNative>(),
+                MaxDistance = options.MaxDistance ?? 0,
+                DedupeByMetadataKey = options.DedupeByMetadataKey != null ? Marshal.StringToHGlobalAnsi(options.DedupeByMetadataKey) : IntPtr.Zero,
+                OverFetch = (uint)options.OverFetch,
+                MmrFetchK = (uint)options.MmrFetchK,
+                MmrLambda = options.MmrLambda,
+                RerankQuery = options.RerankQuery != null ? Marshal.StringToHGlobalAnsi(options.RerankQuery) : IntPtr.Zero,
+                Reranker = reranker != null ? Marshal.GetFunctionPointerForDelegate(reranker) : IntPtr.Zero,
+                RerankFetchK = (uint)options.RerankFetchK
+            };
+
+            var ptr = Marshal.AllocHGlobal(Marshal.SizeOf<ChromaQueryOptionsNative>());
+            Marshal.StructureToPtr(native, ptr, false);
+            return ptr;
+        }
+
+        private static void FreeQueryOptions(IntPtr ptr)
+        {
+            if (ptr == IntPtr.Zero)
+                return;
+
+            var native = Marshal.PtrToStructure<ChromaQueryOptionsNative>(ptr);
+            if (native.DedupeByMetadataKey != IntPtr.Zero)
+                Marshal.FreeHGlobal(native.DedupeByMetadataKey);
+            if (native.RerankQuery != IntPtr.Zero)
+                Marshal.FreeHGlobal(native.RerankQuery);
+            Marshal.FreeHGlobal(ptr);
+        }
+
         private static IntPtr MarshalEmbeddings(float[][] embeddings)
         {
             if (embeddings == null || embeddings.Length == 0)
@@ -814,15 +3273,168 @@ namespace ChromaDB.NET
             public UIntPtr MetadataCount;
             public IntPtr Documents;
             public UIntPtr DocumentsCount;
+            public IntPtr Scores;
+            public UIntPtr ScoresCount;
+            public IntPtr TextScores;
+            public UIntPtr TextScoresCount;
+            public IntPtr MetadataEntries;
+            public UIntPtr MetadataEntriesCount;
+            public IntPtr Embeddings;
+            public UIntPtr EmbeddingsCount;
+            public UIntPtr EmbeddingDim;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool HasMore;
+            public long TotalMatchingCount;
+            public IntPtr Versions;
+            public UIntPtr VersionsCount;
+        }
+
+        private const int EmbeddingDtypeF16 = 1;
+        private const int EmbeddingDtypeF64 = 2;
+
+        private const int MetadataFormatJson = 0;
+        private const int MetadataFormatTyped = 1;
+
+        private const int OrderByFieldId = 0;
+        private const int OrderByFieldMetadata = 1;
+
+        private const int MergeNone = 0;
+        private const int MergeByDistance = 1;
+
+        private const int MetadataValueString = 0;
+        private const int MetadataValueInt = 1;
+        private const int MetadataValueFloat = 2;
+        private const int MetadataValueBool = 3;
+        private const int MetadataValueJson = 4;
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct ChromaMetadataEntryNative
+        {
+            public UIntPtr RecordIndex;
+            public IntPtr Key;
+            public int ValueType;
+            public IntPtr StrVal;
+            public long IntVal;
+            public double FloatVal;
+            [MarshalAs(UnmanagedType.I1)]
+            public bool BoolVal;
+        }
+
+        private static object MarshalMetadataValue(ChromaMetadataEntryNative entry)
+        {
+            switch (entry.ValueType)
+            {
+                case MetadataValueString:
+                    return Marshal.PtrToStringAnsi(entry.StrVal);
+                case MetadataValueInt:
+                    return entry.IntVal;
+                case MetadataValueFloat:
+                    return entry.FloatVal;
+                case MetadataValueBool:
+                    return entry.BoolVal;
+                case MetadataValueJson:
+                    var json = Marshal.PtrToStringAnsi(entry.StrVal);
+                    return string.IsNullOrEmpty(json) ? null : JsonSerializer.Deserialize<JsonElement>(json);
+                default:
+                    return null;
+            }
+        }
+
+        internal static byte[] CopyAndFreeBuffer(IntPtr bufferPtr, UIntPtr length)
+        {
+            try
+            {
+                var bytes = new byte[(int)length.ToUInt64()];
+                if (bytes.Length > 0)
+                    Marshal.Copy(bufferPtr, bytes, 0, bytes.Length);
+                return bytes;
+            }
+            finally
+            {
+                NativeMethods.chroma_free_buffer(bufferPtr);
+            }
+        }
+
+        /// <summary>
+        /// Runs one embedding query against several collections of the same client concurrently
+        /// </summary>
+        /// <returns>One result per collection, or a single result merged by distance</returns>
+        internal static List<QueryResult> QueryMulti(
+            IReadOnlyList<Collection> collections,
+            float[] queryEmbedding,
+            int nResults,
+            Dictionary<string, object> whereFilter,
+            string whereDocument,
+            bool includeMetadatas,
+            bool includeDocuments,
+            bool mergeByDistance)
+        {
+            if (collections == null || collections.Count == 0)
+                throw new ArgumentException("At least one collection is required", nameof(collections));
+
+            var handles = collections.Select(c => c.GetHandleOrThrow()).ToArray();
+            var whereFilterJson = whereFilter != null ? JsonSerializer.Serialize(whereFilter, WhereFilterSerializerOptions) : null;
+            var resultPtrs = new IntPtr[mergeByDistance ? 1 : handles.Length];
+
+            var result = NativeMethods.chroma_query_multi(
+                handles,
+                (UIntPtr)handles.Length,
+                queryEmbedding,
+                (UIntPtr)queryEmbedding.Length,
+                (uint)nResults,
+                whereFilterJson,
+                whereDocument,
+                includeMetadatas,
+                includeDocuments,
+                mergeByDistance ? MergeByDistance : MergeNone,
+                resultPtrs,
+                out var errorPtr);
+
+            ChromaClient.CheckError(result, errorPtr);
+
+            var results = new List<QueryResult>(resultPtrs.Length);
+            try
+            {
+                foreach (var resultPtr in resultPtrs)
+                    results.Add(MarshalQueryResult(resultPtr));
+            }
+            finally
+            {
+                foreach (var resultPtr in resultPtrs)
+                    NativeMethods.chroma_free_query_result(resultPtr);
+            }
+
+            return results;
         }
 
-        private static QueryResult MarshalQueryResult(IntPtr resultPtr)
+        internal static QueryResult MarshalQueryResult(IntPtr resultPtr)
         {
             if (resultPtr == IntPtr.Zero)
                 return new QueryResult();
 
             var nativeResult = Marshal.PtrToStructure<ChromaQueryResultNative>(resultPtr);
-            var result = new QueryResult();
+            var result = new QueryResult
+            {
+                HasMore = nativeResult.HasMore,
+                TotalMatchingCount = nativeResult.TotalMatchingCount >= 0 ? nativeResult.TotalMatchingCount : null
+            };
+
+            if (nativeResult.Versions != IntPtr.Zero && nativeResult.VersionsCount.ToUInt64() > 0)
+            {
+                var versions = new long[(int)nativeResult.VersionsCount.ToUInt64()];
+                Marshal.Copy(nativeResult.Versions, versions, 0, versions.Length);
+                result.Versions = versions.ToList();
+            }
+
+            // Marshal embeddings as one contiguous block
+            if (nativeResult.Embeddings != IntPtr.Zero && nativeResult.EmbeddingsCount.ToUInt64() > 0)
+            {
+                var dimension = (int)nativeResult.EmbeddingDim.ToUInt64();
+                var buffer = new float[(int)nativeResult.EmbeddingsCount.ToUInt64() * dimension];
+                Marshal.Copy(nativeResult.Embeddings, buffer, 0, buffer.Length);
+                result.EmbeddingBuffer = buffer;
+                result.EmbeddingDimension = dimension;
+            }
 
             // Marshal IDs
             if (nativeResult.Ids != IntPtr.Zero && nativeResult.IdsCount.ToUInt64() > 0)
@@ -854,6 +3466,24 @@ namespace ChromaDB.NET
                 result.Distances.AddRange(distances);
             }
 
+            // Marshal scores
+            if (nativeResult.Scores != IntPtr.Zero && nativeResult.ScoresCount.ToUInt64() > 0)
+            {
+                var count = (int)nativeResult.ScoresCount.ToUInt64();
+                var scores = new float[count];
+                Marshal.Copy(nativeResult.Scores, scores, 0, count);
+                result.Scores.AddRange(scores);
+            }
+
+            // Marshal text scores
+            if (nativeResult.TextScores != IntPtr.Zero && nativeResult.TextScoresCount.ToUInt64() > 0)
+            {
+                var count = (int)nativeResult.TextScoresCount.ToUInt64();
+                var textScores = new float[count];
+                Marshal.Copy(nativeResult.TextScores, textScores, 0, count);
+                result.TextScores.AddRange(textScores);
+            }
+
             // Marshal metadata
             if (nativeResult.MetadataJson != IntPtr.Zero && nativeResult.MetadataCount.ToUInt64() > 0)
             {
@@ -890,6 +3520,28 @@ namespace ChromaDB.NET
                 }
             }
 
+            // Marshal typed metadata entries
+            if (nativeResult.MetadataEntries != IntPtr.Zero && nativeResult.MetadataEntriesCount.ToUInt64() > 0)
+            {
+                var count = (int)nativeResult.MetadataEntriesCount.ToUInt64();
+                var entrySize = Marshal.SizeOf<ChromaMetadataEntryNative>();
+
+                for (int i = 0; i < result.Ids.Count; i++)
+                {
+                    result.Metadatas.Add(new Dictionary<string, object>());
+                }
+
+                for (int i = 0; i < count; i++)
+                {
+                    var entry = Marshal.PtrToStructure<ChromaMetadataEntryNative>(nativeResult.MetadataEntries + i * entrySize);
+                    var recordIndex = (int)entry.RecordIndex.ToUInt64();
+                    if (recordIndex >= result.Metadatas.Count || entry.Key == IntPtr.Zero)
+                        continue;
+
+                    result.Metadatas[recordIndex][Marshal.PtrToStringAnsi(entry.Key)] = MarshalMetadataValue(entry);
+                }
+            }
+
             // Marshal documents
             if (nativeResult.Documents != IntPtr.Zero && nativeResult.DocumentsCount.ToUInt64() > 0)
             {
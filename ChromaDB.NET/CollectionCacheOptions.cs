@@ -0,0 +1,19 @@
+using System;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// In-memory cache of collections and their segments. Without it every operation looks the
+/// collection up in the sysdb; with it, lookups are served from memory until the TTL expires.
+/// </summary>
+public sealed class CollectionCacheOptions
+{
+    /// <summary>Number of collections to cache</summary>
+    public int Capacity { get; init; } = 1000;
+
+    /// <summary>How long a cached collection stays valid</summary>
+    public TimeSpan Ttl { get; init; } = TimeSpan.FromSeconds(60);
+
+    /// <summary>Maximum number of concurrent sysdb lookups</summary>
+    public int MaxParallelism { get; init; } = 32;
+}
@@ -0,0 +1,61 @@
+using System;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Kinds of collection changes a subscription receives, see <see cref="Collection.Subscribe"/>
+/// </summary>
+[Flags]
+public enum ChangeEvents
+{
+    /// <summary>Documents added</summary>
+    Add = 1,
+
+    /// <summary>Documents updated</summary>
+    Update = 2,
+
+    /// <summary>Documents upserted</summary>
+    Upsert = 4,
+
+    /// <summary>Documents deleted</summary>
+    Delete = 8,
+
+    /// <summary>The collection was deleted; delivered once, as the last change</summary>
+    Drop = 16,
+
+    /// <summary>
+    /// Write log entries were purged by <see cref="Collection.Optimize"/> before the subscription
+    /// read them, so changes before <see cref="CollectionChange.Sequence"/> may have been missed;
+    /// delivered whichever events were subscribed to
+    /// </summary>
+    Gap = 32,
+
+    /// <summary>Every kind of change</summary>
+    All = Add | Update | Upsert | Delete | Drop | Gap
+}
+
+/// <summary>
+/// A change to a collection observed through the native write log
+/// </summary>
+public sealed class CollectionChange
+{
+    internal CollectionChange(ChangeEvents kind, string collectionId, string? documentId, long sequence)
+    {
+        Kind = kind;
+        CollectionId = collectionId;
+        DocumentId = documentId;
+        Sequence = sequence;
+    }
+
+    /// <summary>What happened, a single flag of <see cref="ChangeEvents"/></summary>
+    public ChangeEvents Kind { get; }
+
+    /// <summary>ID of the collection that changed</summary>
+    public string CollectionId { get; }
+
+    /// <summary>ID of the document written, null for <see cref="ChangeEvents.Drop"/> and <see cref="ChangeEvents.Gap"/></summary>
+    public string? DocumentId { get; }
+
+    /// <summary>Sequence number of the write log entry, usable with <c>chroma_changes_since</c>; 0 for a drop and the oldest entry left for a gap</summary>
+    public long Sequence { get; }
+}
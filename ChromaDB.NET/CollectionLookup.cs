@@ -0,0 +1,23 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Outcome of looking up one name in <see cref="ChromaClient.GetCollections"/>
+/// </summary>
+public sealed class CollectionLookup
+{
+    internal CollectionLookup(string name, Collection? collection, ChromaErrorInfo? error)
+    {
+        Name = name;
+        Collection = collection;
+        Error = error;
+    }
+
+    /// <summary>Name that was looked up</summary>
+    public string Name { get; }
+
+    /// <summary>The collection, or null if the lookup failed</summary>
+    public Collection? Collection { get; }
+
+    /// <summary>Why the lookup failed, or null if it succeeded</summary>
+    public ChromaErrorInfo? Error { get; }
+}
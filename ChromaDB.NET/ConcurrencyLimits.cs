@@ -0,0 +1,35 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// How many calls of a client the native library runs at once; null leaves a limit unset
+/// </summary>
+public sealed class ConcurrencyLimits
+{
+    /// <summary>Reads (gets, queries, counts, lookups) running at once</summary>
+    public int? MaxReads { get; init; }
+
+    /// <summary>Writes (adds, updates, upserts, deletes, collection and database changes) running at once</summary>
+    public int? MaxWrites { get; init; }
+
+    /// <summary>How long a call over a limit waits for room before failing; zero fails it at once</summary>
+    public TimeSpan QueueTimeout { get; init; } = TimeSpan.Zero;
+
+    internal ChromaConcurrencyLimitsNative ToNative()
+    {
+        if (MaxReads <= 0 || MaxWrites <= 0)
+            throw new ArgumentOutOfRangeException(MaxReads <= 0 ? nameof(MaxReads) : nameof(MaxWrites), "Limits must be positive");
+        if (QueueTimeout < TimeSpan.Zero)
+            throw new ArgumentOutOfRangeException(nameof(QueueTimeout), "The queue timeout cannot be negative");
+
+        return new ChromaConcurrencyLimitsNative
+        {
+            StructSize = (UIntPtr)Marshal.SizeOf<ChromaConcurrencyLimitsNative>(),
+            MaxReads = (uint)(MaxReads ?? 0),
+            MaxWrites = (uint)(MaxWrites ?? 0),
+            QueueTimeoutMs = (uint)Math.Min(QueueTimeout.TotalMilliseconds, uint.MaxValue)
+        };
+    }
+}
@@ -0,0 +1,13 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Whether reads wait for writes still pending in the write log, see <see cref="ChromaClient.SetConsistency"/>
+/// </summary>
+public enum ConsistencyMode
+{
+    /// <summary>Read the segments as they are; writes of this client are visible once they return</summary>
+    Eventual = 0,
+
+    /// <summary>Apply every pending write of the collection, including writes of other clients or processes, before reading</summary>
+    Strong = 1
+}
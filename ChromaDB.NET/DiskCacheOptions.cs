@@ -0,0 +1,17 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Disk tier for the HNSW index cache. Indexes evicted from memory stay cached on disk,
+/// so more collections can be served without reloading them from the persist directory.
+/// </summary>
+public sealed class DiskCacheOptions
+{
+    /// <summary>Directory holding the cached indexes; created if missing</summary>
+    public string Directory { get; init; } = string.Empty;
+
+    /// <summary>Disk space for the cache, in MiB</summary>
+    public int DiskSizeMb { get; init; } = 1024;
+
+    /// <summary>Memory in front of the disk tier, in MiB, or 0 for the native default</summary>
+    public int MemorySizeMb { get; init; }
+}
@@ -0,0 +1,13 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// How hybrid search combines vector and keyword rankings
+/// </summary>
+public enum FusionMethod
+{
+    /// <summary>Reciprocal rank fusion</summary>
+    ReciprocalRank = 0,
+
+    /// <summary>Weighted sum of normalized vector similarity and keyword score</summary>
+    Weighted = 1
+}
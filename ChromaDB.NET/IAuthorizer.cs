@@ -0,0 +1,45 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Decides which principal may run which operation on which tenant, database and collection
+/// </summary>
+public interface IAuthorizer
+{
+    /// <summary>
+    /// Returns whether a backend call may run
+    /// </summary>
+    /// <param name="principalJson">Identity set with <see cref="ChromaClient.SetIdentity"/>, or null</param>
+    /// <param name="tenant">Tenant the call targets</param>
+    /// <param name="database">Database the call targets</param>
+    /// <param name="collection">Collection ID, or name for lookups by name; null for calls on a database</param>
+    /// <param name="operation">Operation, e.g. <c>query</c> or <c>create_collection</c></param>
+    bool Authorize(string? principalJson, string tenant, string database, string? collection, string operation);
+}
+
+internal static class AuthorizerCallback
+{
+    /// <summary>
+    /// Wraps an authorizer in a native callback. Exceptions cannot cross into native code,
+    /// so they deny the call instead.
+    /// </summary>
+    internal static NativeMethods.AuthorizeCallback ToNative(IAuthorizer authorizer) =>
+        (_, principal, tenant, database, collection, operation) =>
+        {
+            try
+            {
+                return authorizer.Authorize(
+                    Marshal.PtrToStringUTF8(principal),
+                    Marshal.PtrToStringUTF8(tenant) ?? string.Empty,
+                    Marshal.PtrToStringUTF8(database) ?? string.Empty,
+                    Marshal.PtrToStringUTF8(collection),
+                    Marshal.PtrToStringUTF8(operation) ?? string.Empty);
+            }
+            catch (Exception)
+            {
+                return false;
+            }
+        };
+}
@@ -0,0 +1,48 @@
+using System;
+using System.Collections.Generic;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Re-scores query candidates, e.g. with a cross-encoder, inside the native query path
+/// </summary>
+public interface IReranker
+{
+    /// <summary>
+    /// Scores each document against the query; higher scores rank first
+    /// </summary>
+    /// <param name="query">Query text</param>
+    /// <param name="documents">Candidate documents, closest first</param>
+    /// <returns>One score per document</returns>
+    float[] Score(string query, IReadOnlyList<string> documents);
+}
+
+internal static class RerankerCallback
+{
+    /// <summary>
+    /// Wraps a re-ranker in a native callback. Exceptions cannot cross into native code,
+    /// so they fail the query instead.
+    /// </summary>
+    internal static NativeMethods.RerankCallback ToNative(IReranker reranker) =>
+        (_, query, documents, count, scoresOut) =>
+        {
+            try
+            {
+                var texts = new string[(int)count.ToUInt64()];
+                for (int i = 0; i < texts.Length; i++)
+                    texts[i] = Marshal.PtrToStringAnsi(Marshal.ReadIntPtr(documents, i * IntPtr.Size)) ?? string.Empty;
+
+                var scores = reranker.Score(Marshal.PtrToStringAnsi(query) ?? string.Empty, texts);
+                if (scores == null || scores.Length != texts.Length)
+                    return false;
+
+                Marshal.Copy(scores, 0, scoresOut, scores.Length);
+                return true;
+            }
+            catch (Exception)
+            {
+                return false;
+            }
+        };
+}
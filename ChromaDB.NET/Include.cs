@@ -0,0 +1,28 @@
+using System;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Fields returned by queries and gets
+/// </summary>
+[Flags]
+public enum Include : uint
+{
+    /// <summary>The default includes of the collection, see <see cref="Collection.DefaultIncludes"/></summary>
+    Default = 0,
+
+    /// <summary>Embedding vectors</summary>
+    Embeddings = 1,
+
+    /// <summary>Record metadata</summary>
+    Metadatas = 1 << 1,
+
+    /// <summary>Document text</summary>
+    Documents = 1 << 2,
+
+    /// <summary>Distances of query hits</summary>
+    Distances = 1 << 3,
+
+    /// <summary>Record URIs</summary>
+    Uris = 1 << 4
+}
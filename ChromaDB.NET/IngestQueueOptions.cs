@@ -0,0 +1,74 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Configuration of the native ingestion queue of a client; null leaves a setting at its default
+/// </summary>
+public sealed class IngestQueueOptions
+{
+    /// <summary>Background threads writing queued documents, 2 by default</summary>
+    public int? Workers { get; init; }
+
+    /// <summary>Most documents written by one add, 1000 by default</summary>
+    public int? MaxBatchSize { get; init; }
+
+    /// <summary>Most documents waiting to be written before enqueueing fails, 100000 by default</summary>
+    public long? MaxPending { get; init; }
+
+    internal ChromaIngestOptionsNative ToNative()
+    {
+        if (Workers <= 0 || MaxBatchSize <= 0 || MaxPending <= 0)
+        {
+            var name = Workers <= 0 ? nameof(Workers) : MaxBatchSize <= 0 ? nameof(MaxBatchSize) : nameof(MaxPending);
+            throw new ArgumentOutOfRangeException(name, "Settings must be positive");
+        }
+
+        return new ChromaIngestOptionsNative
+        {
+            StructSize = (UIntPtr)Marshal.SizeOf<ChromaIngestOptionsNative>(),
+            Workers = (uint)(Workers ?? 0),
+            MaxBatchSize = (uint)(MaxBatchSize ?? 0),
+            MaxPending = (UIntPtr)(ulong)(MaxPending ?? 0)
+        };
+    }
+}
+
+/// <summary>
+/// Counters of the native ingestion queue of a client, see <see cref="ChromaClient.GetIngestQueueStats"/>
+/// </summary>
+public sealed class IngestQueueStats
+{
+    internal IngestQueueStats(ChromaQueueStatsNative native)
+    {
+        Workers = (int)native.Workers;
+        Pending = (long)native.Pending.ToUInt64();
+        InFlight = (long)native.InFlight.ToUInt64();
+        Written = (long)native.Written;
+        Failed = (long)native.Failed;
+        Batches = (long)native.Batches;
+        FailedBatches = (long)native.FailedBatches;
+    }
+
+    /// <summary>Background threads running</summary>
+    public int Workers { get; }
+
+    /// <summary>Documents queued and not yet picked up by a worker</summary>
+    public long Pending { get; }
+
+    /// <summary>Documents being written</summary>
+    public long InFlight { get; }
+
+    /// <summary>Documents written since the client was created</summary>
+    public long Written { get; }
+
+    /// <summary>Documents whose add failed since the client was created</summary>
+    public long Failed { get; }
+
+    /// <summary>Adds sent by the workers</summary>
+    public long Batches { get; }
+
+    /// <summary>Adds that failed</summary>
+    public long FailedBatches { get; }
+}
@@ -0,0 +1,29 @@
+using System;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Kind of advisory lock a client takes on its persist directory
+/// </summary>
+public enum LockMode
+{
+    /// <summary>Only this client may open the directory</summary>
+    Exclusive = 1,
+
+    /// <summary>Any number of shared clients may open the directory, but no exclusive one</summary>
+    SharedRead = 2
+}
+
+/// <summary>
+/// Advisory lock on the persist directory, taken when the client is created so that two
+/// processes cannot write to the same segments. Creation fails with
+/// <see cref="ChromaErrorCode.ConcurrentAccess"/> if the lock is not available within <see cref="Wait"/>.
+/// </summary>
+public sealed class LockOptions
+{
+    /// <summary>Kind of lock to take</summary>
+    public LockMode Mode { get; init; } = LockMode.Exclusive;
+
+    /// <summary>How long to wait for other clients to release the lock</summary>
+    public TimeSpan Wait { get; init; } = TimeSpan.Zero;
+}
@@ -0,0 +1,13 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// How <see cref="Collection.UpdateMetadata"/> applies a metadata patch to each document
+/// </summary>
+public enum MetadataMergeMode
+{
+    /// <summary>Set the keys of the patch, remove keys set to null and keep all other keys</summary>
+    Merge = 0,
+
+    /// <summary>Replace the metadata of each document with the patch</summary>
+    Replace = 1
+}
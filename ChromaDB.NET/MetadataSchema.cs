@@ -0,0 +1,40 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Type a metadata field of a collection schema must have
+/// </summary>
+public enum MetadataFieldType
+{
+    /// <summary>A string value</summary>
+    String,
+
+    /// <summary>An integer value</summary>
+    Int,
+
+    /// <summary>A floating-point value; integers are accepted too</summary>
+    Float,
+
+    /// <summary>A boolean value</summary>
+    Bool
+}
+
+/// <summary>
+/// A declared metadata field of a collection schema, see <see cref="Collection.SetMetadataSchema"/>
+/// </summary>
+public sealed class MetadataField
+{
+    /// <summary>Type the values of the field must have</summary>
+    public MetadataFieldType Type { get; }
+
+    /// <summary>Whether every document must carry the field</summary>
+    public bool Required { get; }
+
+    /// <summary>
+    /// Creates a field declaration
+    /// </summary>
+    public MetadataField(MetadataFieldType type, bool required = false)
+    {
+        Type = type;
+        Required = required;
+    }
+}
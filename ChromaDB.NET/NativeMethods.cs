@@ -13,127 +13,408 @@ internal static class NativeMethods
 {
     private const string DllName = "chroma_csharp";
 
+    /// <summary>ABI version of the native library these declarations match</summary>
+    public const uint AbiVersion = 10;
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern uint chroma_abi_version();
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    [return: MarshalAs(UnmanagedType.I1)]
+    public static extern bool chroma_is_compatible(uint expected);
+
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern void chroma_free_error(IntPtr error);
 
+    // Only exported by native builds with the debug-exports feature
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_debug_panic(out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_error_to_json(IntPtr error, out IntPtr json, out IntPtr jsonError);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_last_error_code();
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern UIntPtr chroma_get_last_error_message(byte[]? buffer, UIntPtr length);
+
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern int chroma_create_client(
         [MarshalAs(UnmanagedType.I1)] bool allowReset,
+        [MarshalAs(UnmanagedType.I1)] bool readOnly,
         IntPtr sqliteConfigPtr,
         UIntPtr hnswCacheSize,
-        [MarshalAs(UnmanagedType.LPStr)] string? persistPath,
-        out IntPtr clientHandle,
+        IntPtr diskCacheConfigPtr,
+        IntPtr collectionCacheConfigPtr,
+        IntPtr lockConfigPtr,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? persistPath,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? distributedConfigJson,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? objectStoreConfigJson,
+        IntPtr encryptionConfigPtr,
+        out ulong clientHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_create_client_from_config(
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? configPath,
+        out ulong clientHandle,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_destroy_client(IntPtr clientHandle, out IntPtr error);
+    public static extern int chroma_destroy_client(ulong clientHandle, out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_heartbeat(IntPtr clientHandle, out ulong result, out IntPtr error);
+    public static extern int chroma_register_log_callback(IntPtr callback, IntPtr userData, int maxLevel, out ulong callbackId, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_unregister_callback(ulong callbackId, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_subscribe(ulong collectionHandle, int eventMask, IntPtr callback, IntPtr userData, out ulong callbackId, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_client_retain(ulong clientHandle, out ulong retainedHandle, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_client_release(ulong clientHandle, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_shutdown_client(ulong clientHandle, uint drainTimeoutMs, out UIntPtr aborted, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_heartbeat(ulong clientHandle, out ulong result, out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern int chroma_create_collection(
-        IntPtr clientHandle,
+        ulong clientHandle,
         [MarshalAs(UnmanagedType.LPStr)] string name,
         [MarshalAs(UnmanagedType.LPStr)] string configJson,
         [MarshalAs(UnmanagedType.LPStr)] string metadataJson,
         [MarshalAs(UnmanagedType.I1)] bool getOrCreate,
         [MarshalAs(UnmanagedType.LPStr)] string tenant,
         [MarshalAs(UnmanagedType.LPStr)] string database,
-        out IntPtr collectionHandle,
+        out ulong collectionHandle,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern int chroma_get_collection(
-        IntPtr clientHandle,
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string database,
+        out ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_collections(
+        ulong clientHandle,
+        IntPtr names,
+        UIntPtr namesCount,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string database,
+        [Out] ulong[] collectionHandles,
+        [Out] IntPtr[] errors,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_collection_by_id(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string id,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string database,
+        out ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_collection_exists(
+        ulong clientHandle,
         [MarshalAs(UnmanagedType.LPStr)] string name,
         [MarshalAs(UnmanagedType.LPStr)] string tenant,
         [MarshalAs(UnmanagedType.LPStr)] string database,
-        out IntPtr collectionHandle,
+        [MarshalAs(UnmanagedType.I1)] out bool exists,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_records_exist(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [Out, MarshalAs(UnmanagedType.LPArray, ArraySubType = UnmanagedType.I1)] bool[] exists,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_copy_collection(
+        ulong clientHandle,
+        ulong sourceHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string targetTenant,
+        [MarshalAs(UnmanagedType.LPStr)] string targetDatabase,
+        [MarshalAs(UnmanagedType.LPStr)] string targetName,
+        [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
+        out ulong targetHandle,
+        out UIntPtr copied,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_generate_test_collection(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        uint count,
+        UIntPtr dimension,
+        ulong seed,
+        out ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_create_alias(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string alias,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_swap_alias(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string alias,
+        out IntPtr previousId,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_resolve_alias(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string alias,
+        [MarshalAs(UnmanagedType.LPStr)] string? tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string? database,
+        out ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete_alias(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string alias,
+        [MarshalAs(UnmanagedType.LPStr)] string? tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string? database,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_collection_readonly(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.I1)] bool readOnly,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_collection_is_readonly(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.I1)] out bool readOnly,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_collection_get_id(ulong collectionHandle, out IntPtr id, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_collection_set_default_includes(ulong collectionHandle, uint includeFlags, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_collection_get_default_includes(ulong collectionHandle, out uint includeFlags, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_one(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string id,
+        uint includeFlags,
+        out IntPtr record,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void chroma_free_record(IntPtr record);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_update_one(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string id,
+        IntPtr embedding,
+        UIntPtr embeddingDim,
+        [MarshalAs(UnmanagedType.LPStr)] string? metadataJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? document,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_update_metadata(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [MarshalAs(UnmanagedType.LPStr)] string patchJson,
+        int mergeMode,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete_one(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string id,
+        [MarshalAs(UnmanagedType.I1)] bool soft,
+        [MarshalAs(UnmanagedType.I1)] out bool deleted,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_destroy_collection(IntPtr collectionHandle, out IntPtr error);
+    public static extern int chroma_destroy_collection(ulong collectionHandle, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_destroy_all(out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_list_handles(
+        int kind,
+        out IntPtr handles,
+        out UIntPtr count,
+        out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern int chroma_add(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
+        ulong collectionHandle,
         IntPtr ids,
         UIntPtr idsCount,
         IntPtr embeddings,
         UIntPtr embeddingDim,
         IntPtr metadatasJson,
         IntPtr documents,
+        int onConflict,
+        out ChromaAddStatsNative stats,
+        IntPtr generatedIdsOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_add_batch(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        ref ChromaEmbeddingBatchNative embeddings,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        int onConflict,
+        out ChromaAddStatsNative stats,
+        IntPtr generatedIdsOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_query_multi(
+        ulong[] collectionHandles,
+        UIntPtr collectionsCount,
+        float[] queryEmbedding,
+        UIntPtr embeddingDim,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
+        [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
+        int mergeStrategy,
+        [Out] IntPtr[] results,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
     public static extern int chroma_query(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
+        ulong collectionHandle,
         IntPtr queryEmbedding,
         UIntPtr embeddingDim,
         uint nResults,
         [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
         [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        IntPtr allowedIds,
+        UIntPtr allowedIdsCount,
         [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
         [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
         [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
         [MarshalAs(UnmanagedType.I1)] bool includeDistances,
+        IntPtr metadataKeys,
+        UIntPtr metadataKeysCount,
+        int metadataFormat,
+        IntPtr options,
         out IntPtr result,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_free_query_result(IntPtr result);
+    public static extern int chroma_query_batch(
+        ulong collectionHandle,
+        ref ChromaEmbeddingBatchNative queryEmbedding,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        IntPtr allowedIds,
+        UIntPtr allowedIdsCount,
+        [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
+        [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
+        [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
+        [MarshalAs(UnmanagedType.I1)] bool includeDistances,
+        IntPtr metadataKeys,
+        UIntPtr metadataKeysCount,
+        int metadataFormat,
+        out IntPtr result,
+        out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_free_string(IntPtr str);
+    public static extern int chroma_search(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string queryText,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        ref ChromaRankOptionsNative rankOptions,
+        out IntPtr result,
+        out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_count(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
-        out uint result,
+    public static extern int chroma_hybrid_search(
+        ulong collectionHandle,
+        IntPtr queryEmbedding,
+        UIntPtr embeddingDim,
+        [MarshalAs(UnmanagedType.LPStr)] string queryText,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        ref ChromaRankOptionsNative rankOptions,
+        ref ChromaFusionOptionsNative fusionOptions,
+        out IntPtr result,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_update(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
+    public static extern int chroma_add_sparse(
+        ulong collectionHandle,
         IntPtr ids,
         UIntPtr idsCount,
-        IntPtr embeddings,
-        UIntPtr embeddingDim,
-        IntPtr metadatasJson,
-        IntPtr documents,
+        [In] ChromaSparseVectorNative[] vectors,
+        [MarshalAs(UnmanagedType.LPStr)] string? sparseKey,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_upsert(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
-        IntPtr ids,
-        UIntPtr idsCount,
-        IntPtr embeddings,
+    public static extern int chroma_query_sparse(
+        ulong collectionHandle,
+        ref ChromaSparseVectorNative query,
+        [MarshalAs(UnmanagedType.LPStr)] string? sparseKey,
+        IntPtr queryEmbedding,
         UIntPtr embeddingDim,
-        IntPtr metadatasJson,
-        IntPtr documents,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        ref ChromaFusionOptionsNative fusionOptions,
+        out IntPtr result,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_delete(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
-        IntPtr ids,
-        UIntPtr idsCount,
+    public static extern int chroma_query_serialized(
+        ulong collectionHandle,
+        IntPtr queryEmbeddings,
+        UIntPtr embeddingDim,
+        UIntPtr queryCount,
+        uint nResults,
         [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
         [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
+        [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
+        [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
+        [MarshalAs(UnmanagedType.I1)] bool includeDistances,
+        int format,
+        out IntPtr buffer,
+        out UIntPtr length,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_get(
-        IntPtr clientHandle,
-        IntPtr collectionHandle,
+    public static extern int chroma_get_serialized(
+        ulong collectionHandle,
         IntPtr ids,
         UIntPtr idsCount,
         [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
@@ -143,28 +424,956 @@ internal static class NativeMethods
         [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
         [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
         [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
-        out IntPtr result,
+        int format,
+        out IntPtr buffer,
+        out UIntPtr length,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_create_database(
-        IntPtr clientHandle,
-        [MarshalAs(UnmanagedType.LPStr)] string name,
-        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+    public static extern void chroma_free_buffer(IntPtr buffer);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_add_arrow(
+        ulong collectionHandle,
+        IntPtr ipcBuffer,
+        UIntPtr ipcLength,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_get_database(
-        IntPtr clientHandle,
-        [MarshalAs(UnmanagedType.LPStr)] string name,
-        [MarshalAs(UnmanagedType.LPStr)] string tenant,
-        out IntPtr idResult,
+    public static extern int chroma_free_query_result(IntPtr result);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_free_string(IntPtr str);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern IntPtr chroma_malloc(UIntPtr size);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void chroma_free(IntPtr ptr);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_count(
+        ulong collectionHandle,
+        out uint result,
         out IntPtr error);
 
     [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-    public static extern int chroma_delete_database(
-        IntPtr clientHandle,
-        [MarshalAs(UnmanagedType.LPStr)] string name,
-        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+    public static extern int chroma_count_where(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        out uint result,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_optimize_collection(
+        ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_preload_collection(
+        ulong collectionHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_collection_ttl(
+        ulong collectionHandle,
+        uint seconds,
+        [MarshalAs(UnmanagedType.LPStr)] string timestampKey,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_purge_expired(
+        ulong collectionHandle,
+        out UIntPtr purged,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_filterable_fields(
+        ulong collectionHandle,
+        IntPtr keys,
+        UIntPtr keysCount,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_filterable_fields(
+        ulong collectionHandle,
+        IntPtr keysOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_metadata_schema(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? schemaJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_metadata_schema(
+        ulong collectionHandle,
+        out IntPtr schemaJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_undeclared_filter_keys(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string whereJson,
+        IntPtr keysOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_query_explain(
+        ulong collectionHandle,
+        float[] queryEmbedding,
+        UIntPtr embeddingDim,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocumentFilter,
+        out IntPtr explainJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_aggregate(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string? groupByKey,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string aggregationsJson,
+        out IntPtr resultJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_distinct_metadata_values(
+        ulong collectionHandle,
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string key,
+        uint limit,
+        IntPtr valuesOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_ids(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocumentFilter,
+        uint limit,
+        uint offset,
+        IntPtr idsOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_query_ids(
+        ulong collectionHandle,
+        float[] queryEmbedding,
+        UIntPtr embeddingDim,
+        uint nResults,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocumentFilter,
+        [MarshalAs(UnmanagedType.I1)] bool includeDistances,
+        out IntPtr result,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_prepare_query(
+        ulong collectionHandle,
+        ref ChromaQueryTemplateNative template,
+        out ulong preparedHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_execute_prepared(
+        ulong preparedHandle,
+        float[] queryEmbedding,
+        UIntPtr embeddingDim,
+        uint nResults,
+        out IntPtr result,
         out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_bench_add(
+        ulong collectionHandle,
+        uint count,
+        UIntPtr dimension,
+        uint batchSize,
+        ulong seed,
+        out IntPtr reportJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_bench_query(
+        ulong collectionHandle,
+        uint queries,
+        UIntPtr dimension,
+        uint nResults,
+        ulong seed,
+        out IntPtr reportJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_destroy_prepared_query(
+        ulong preparedHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_changes_since(
+        ulong collectionHandle,
+        ulong sequence,
+        uint limit,
+        out IntPtr changesJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_restore_records(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        out UIntPtr restored,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_purge_deleted(
+        ulong collectionHandle,
+        uint olderThanSecs,
+        out UIntPtr purged,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_preload_all(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string database,
+        out UIntPtr preloaded,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_update(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr embeddings,
+        UIntPtr embeddingDim,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocumentFilter,
+        long[]? expectedVersions,
+        IntPtr updatedIdsOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_begin(
+        ulong clientHandle,
+        out ulong batchHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_add(
+        ulong batchHandle,
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr embeddings,
+        UIntPtr embeddingDim,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_update(
+        ulong batchHandle,
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr embeddings,
+        UIntPtr embeddingDim,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_delete(
+        ulong batchHandle,
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocumentFilter,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_commit(
+        ulong batchHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_batch_abort(
+        ulong batchHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_upsert(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr embeddings,
+        UIntPtr embeddingDim,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        [MarshalAs(UnmanagedType.I1)] bool dryRun,
+        [MarshalAs(UnmanagedType.I1)] bool soft,
+        long[]? expectedVersions,
+        IntPtr deletedIds,
+        out IntPtr error);
+
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    [return: MarshalAs(UnmanagedType.I1)]
+    public delegate bool ProgressCallback(UIntPtr processed, UIntPtr total, IntPtr userData);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete_batched(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        UIntPtr batchSize,
+        ProgressCallback progressCallback,
+        IntPtr userData,
+        out ChromaBatchStatsNative stats,
+        IntPtr failedIds,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete_batched_async(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        UIntPtr batchSize,
+        out ulong operationHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_operation_status(
+        ulong operationHandle,
+        out ChromaOperationProgressNative progress,
+        out int state,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_operation_wait(
+        ulong operationHandle,
+        uint timeoutMs,
+        out int state,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_operation_cancel(
+        ulong operationHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_destroy_operation(
+        ulong operationHandle,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern void chroma_free_result_set(IntPtr resultSet);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_emit_header(
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string path,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_validate_filter(
+        [MarshalAs(UnmanagedType.LPStr)] string? whereJson,
+        [MarshalAs(UnmanagedType.LPStr)] string? whereDocument,
+        out IntPtr normalizedJson,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_new_uuid(
+        int version,
+        out IntPtr uuid,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_validate_uuid(
+        [MarshalAs(UnmanagedType.LPStr)] string uuid,
+        out IntPtr canonical,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_validate_collection_name(
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_request_id(
+        [MarshalAs(UnmanagedType.LPStr)] string? requestId,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_consistency(
+        ulong clientHandle,
+        int mode,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_default_tenant_database(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string? tenant,
+        [MarshalAs(UnmanagedType.LPStr)] string? database,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_default_tenant_database(
+        ulong clientHandle,
+        out IntPtr tenant,
+        out IntPtr database,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_thread_consistency(
+        int mode,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_encryption_key(
+        byte[]? key,
+        UIntPtr keyLen,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_seal_directory(
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string persistPath,
+        byte[] key,
+        UIntPtr keyLen,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_rotate_encryption_key(
+        ulong clientHandle,
+        byte[] key,
+        UIntPtr keyLen,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_put_blobs(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [In] ChromaBlobNative[] blobs,
+        IntPtr urisOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_uris(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr urisOut,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_blob(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string uri,
+        out IntPtr buffer,
+        out UIntPtr length,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        [MarshalAs(UnmanagedType.LPStr)] string whereFilterJson,
+        [MarshalAs(UnmanagedType.LPStr)] string whereDocumentFilter,
+        uint limit,
+        uint offset,
+        IntPtr orderBy,
+        [MarshalAs(UnmanagedType.I1)] bool includeEmbeddings,
+        [MarshalAs(UnmanagedType.I1)] bool includeMetadatas,
+        [MarshalAs(UnmanagedType.I1)] bool includeDocuments,
+        IntPtr metadataKeys,
+        UIntPtr metadataKeysCount,
+        int metadataFormat,
+        [MarshalAs(UnmanagedType.I1)] bool countTotal,
+        [MarshalAs(UnmanagedType.I1)] bool includeVersions,
+        out IntPtr result,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_retry_policy(
+        ulong clientHandle,
+        ref ChromaRetryPolicyNative policy,
+        out IntPtr error);
+
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    [return: MarshalAs(UnmanagedType.I1)]
+    public delegate bool RerankCallback(IntPtr userData, IntPtr query, IntPtr documents, UIntPtr count, IntPtr scoresOut);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_reranker(
+        ulong clientHandle,
+        RerankCallback? callback,
+        IntPtr userData,
+        out IntPtr error);
+
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    [return: MarshalAs(UnmanagedType.I1)]
+    public delegate bool AuthorizeCallback(IntPtr userData, IntPtr principal, IntPtr tenant, IntPtr database, IntPtr collection, IntPtr operation);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_authorizer(
+        ulong clientHandle,
+        AuthorizeCallback? callback,
+        IntPtr userData,
+        out IntPtr error);
+
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    public delegate void AuditCallback(IntPtr userData, IntPtr entryJson);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_audit_log(ulong clientHandle, ref ChromaAuditLogOptionsNative options, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_audit_log(ulong clientHandle, IntPtr options, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_quotas(ulong clientHandle, [MarshalAs(UnmanagedType.LPStr)] string? tenant, ref ChromaQuotasNative quotas, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_quotas(ulong clientHandle, [MarshalAs(UnmanagedType.LPStr)] string? tenant, IntPtr quotas, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_embedding_cache_get(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string model,
+        IntPtr documents,
+        UIntPtr count,
+        [Out, MarshalAs(UnmanagedType.LPArray, ArraySubType = UnmanagedType.I1)] bool[] found,
+        out IntPtr embeddings,
+        out UIntPtr embeddingDim,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_embedding_cache_put(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string model,
+        IntPtr documents,
+        UIntPtr count,
+        float[] embeddings,
+        UIntPtr embeddingDim,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_embedding_cache_clear(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string? model,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_concurrency_limits(ulong clientHandle, ref ChromaConcurrencyLimitsNative limits, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_configure_ingest_queue(ulong clientHandle, ref ChromaIngestOptionsNative options, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_enqueue_add(
+        ulong collectionHandle,
+        IntPtr ids,
+        UIntPtr idsCount,
+        IntPtr embeddings,
+        UIntPtr embeddingDim,
+        IntPtr metadatasJson,
+        IntPtr documents,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_queue_stats(ulong clientHandle, out ChromaQueueStatsNative stats, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_queue_flush(ulong clientHandle, uint timeoutMs, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_identity(ulong clientHandle, string? principalJson, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_metrics_json(ulong clientHandle, out IntPtr json, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_reset_metrics(ulong clientHandle, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_flush(ulong clientHandle, ulong collectionHandle, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_set_auto_flush_interval(ulong clientHandle, uint intervalMs, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_dump_effective_config(ulong clientHandle, out IntPtr json, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_storage_info(ulong clientHandle, out IntPtr json, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_migration_status(
+        [MarshalAs(UnmanagedType.LPUTF8Str)] string persistPath,
+        IntPtr sqliteConfigPtr,
+        out IntPtr json,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_health_check(ulong clientHandle, out IntPtr reportJson, out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_create_database(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_get_database(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        out IntPtr idResult,
+        out IntPtr error);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+    public static extern int chroma_delete_database(
+        ulong clientHandle,
+        [MarshalAs(UnmanagedType.LPStr)] string name,
+        [MarshalAs(UnmanagedType.LPStr)] string tenant,
+        out IntPtr error);
+}
+
+/// <summary>
+/// Native representation of keyword search ranking options
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaRankOptionsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public float K1;
+    public float B;
+    [MarshalAs(UnmanagedType.I1)]
+    public bool MatchAllTerms;
+    public uint MaxCandidates;
+}
+
+/// <summary>
+/// Native representation of hybrid search fusion options
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaFusionOptionsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public int Method;
+    public float RrfK;
+    public float VectorWeight;
+    public uint CandidateK;
+}
+
+/// <summary>
+/// Native representation of the progress of a background operation
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaOperationProgressNative
+{
+    public UIntPtr Processed;
+    public UIntPtr Total;
+    public ulong ElapsedMs;
+    public long EtaMs;
+}
+
+/// <summary>
+/// Native representation of a list of record IDs
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaResultSetNative
+{
+    public IntPtr Ids;
+    public UIntPtr Count;
+}
+
+/// <summary>
+/// Native representation of a get sort order
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaOrderByNative
+{
+    public int Field;
+    public IntPtr MetadataKey;
+    [MarshalAs(UnmanagedType.I1)]
+    public bool Descending;
+}
+
+/// <summary>
+/// Native representation of the fixed parts of a prepared query
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaQueryTemplateNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public IntPtr WhereFilterJson;
+    public IntPtr WhereDocumentFilter;
+    public uint IncludeFlags;
+    public IntPtr MetadataKeys;
+    public UIntPtr MetadataKeysCount;
+    public int MetadataFormat;
+    public IntPtr Options;
+}
+
+/// <summary>
+/// Native representation of query post-processing options
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaQueryOptionsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public float MaxDistance;
+    public IntPtr DedupeByMetadataKey;
+    public uint OverFetch;
+    public uint MmrFetchK;
+    public float MmrLambda;
+    public IntPtr RerankQuery;
+    public IntPtr Reranker;
+    public IntPtr RerankerUserData;
+    public uint RerankFetchK;
+}
+
+/// <summary>
+/// Native representation of a contiguous block of embeddings
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaEmbeddingBatchNative
+{
+    public IntPtr Data;
+    public int Dtype;
+    public UIntPtr Count;
+    public UIntPtr Dimension;
+}
+
+/// <summary>
+/// Native representation of a client retry policy
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaRetryPolicyNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public uint MaxAttempts;
+    public uint BaseDelayMs;
+    public uint MaxDelayMs;
+    public float Jitter;
+    public uint RetryableClasses;
+}
+
+/// <summary>
+/// Native representation of the concurrency limits of a client
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaConcurrencyLimitsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public uint MaxReads;
+    public uint MaxWrites;
+    public uint QueueTimeoutMs;
+}
+
+/// <summary>
+/// Native representation of the ingestion queue configuration of a client
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaIngestOptionsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public uint Workers;
+    public uint MaxBatchSize;
+    public UIntPtr MaxPending;
+}
+
+/// <summary>
+/// Native representation of the ingestion queue counters of a client
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaQueueStatsNative
+{
+    public uint Workers;
+    public UIntPtr Pending;
+    public UIntPtr InFlight;
+    public ulong Written;
+    public ulong Failed;
+    public ulong Batches;
+    public ulong FailedBatches;
+}
+
+/// <summary>
+/// Native representation of the quotas of a client or tenant
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaQuotasNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public UIntPtr MaxCollections;
+    public UIntPtr MaxRecordsPerCollection;
+    public UIntPtr MaxDimension;
+    public uint MaxWriteOpsPerSecond;
+}
+
+/// <summary>
+/// Native representation of the audit log options of a client
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaAuditLogOptionsNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public IntPtr Path;
+    public IntPtr Callback;
+    public IntPtr UserData;
+}
+
+/// <summary>
+/// Native representation of the outcome of an add
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaAddStatsNative
+{
+    public UIntPtr Inserted;
+    public UIntPtr Skipped;
+    public UIntPtr Overwritten;
+}
+
+/// <summary>
+/// Native representation of the outcome of a batched delete
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaBatchStatsNative
+{
+    public UIntPtr Succeeded;
+    public UIntPtr Failed;
+    public UIntPtr Batches;
+    public UIntPtr FailedBatches;
+    [MarshalAs(UnmanagedType.I1)]
+    public bool Cancelled;
+}
+
+/// <summary>
+/// Native representation of the HNSW cache disk tier
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct DiskCacheConfigNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public IntPtr Dir;
+    public UIntPtr DiskMib;
+    public UIntPtr MemoryMib;
+}
+
+/// <summary>
+/// Native representation of the collection cache settings
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct CollectionCacheConfigNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public UIntPtr Capacity;
+    public uint TtlSecs;
+    public uint PermittedParallelism;
+}
+
+/// <summary>
+/// Native representation of the SQLite settings
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct SqliteConfigNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public IntPtr Url;
+    public int HashType;
+    public int MigrationMode;
+    public int JournalMode;
+    public int Synchronous;
+    public uint BusyTimeoutMs;
+    public uint CacheSizeKib;
+}
+
+/// <summary>
+/// Native representation of the persist directory lock settings
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct LockConfigNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public int Mode;
+    public uint WaitMs;
+}
+
+/// <summary>
+/// Native representation of the persist directory encryption settings
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct EncryptionConfigNative
+{
+    /// <summary>Size of this struct, checked by the native library</summary>
+    public UIntPtr StructSize;
+    public IntPtr Key;
+    public UIntPtr KeyLen;
+}
+
+/// <summary>
+/// Native representation of a sparse vector
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaSparseVectorNative
+{
+    public IntPtr Indices;
+    public IntPtr Values;
+    public UIntPtr Count;
+}
+
+/// <summary>
+/// Native representation of a binary attachment
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaBlobNative
+{
+    public IntPtr Data;
+    public UIntPtr Len;
+}
+
+/// <summary>
+/// Native representation of a single record
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+internal struct ChromaRecordNative
+{
+    public IntPtr Id;
+    public IntPtr Document;
+    public IntPtr MetadataJson;
+    public IntPtr Embedding;
+    public UIntPtr EmbeddingDim;
+    public IntPtr Uri;
 }
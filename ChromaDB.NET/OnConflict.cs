@@ -0,0 +1,45 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// What <see cref="Collection.Add(System.Collections.Generic.IEnumerable{ChromaDocument}, OnConflict)"/>
+/// does with IDs that already exist in the collection
+/// </summary>
+public enum OnConflict
+{
+    /// <summary>Reject the whole add</summary>
+    Fail = 0,
+
+    /// <summary>Add only the new documents and leave existing ones untouched</summary>
+    Ignore = 1,
+
+    /// <summary>Write every document, updating existing ones as an upsert would</summary>
+    Overwrite = 2
+}
+
+/// <summary>
+/// Number of documents inserted, skipped and overwritten by an add
+/// </summary>
+public sealed class AddResult
+{
+    internal AddResult(int inserted, int skipped, int overwritten)
+    {
+        Inserted = inserted;
+        Skipped = skipped;
+        Overwritten = overwritten;
+    }
+
+    /// <summary>
+    /// Documents that did not exist before the add
+    /// </summary>
+    public int Inserted { get; }
+
+    /// <summary>
+    /// Existing documents left untouched by <see cref="OnConflict.Ignore"/>
+    /// </summary>
+    public int Skipped { get; }
+
+    /// <summary>
+    /// Existing documents replaced by <see cref="OnConflict.Overwrite"/>
+    /// </summary>
+    public int Overwritten { get; }
+}
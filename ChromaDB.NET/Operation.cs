@@ -0,0 +1,134 @@
+using System;
+using System.Threading;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// State of a background <see cref="Operation"/>
+/// </summary>
+public enum OperationState
+{
+    Running = 0,
+    Succeeded = 1,
+    Failed = 2,
+    Cancelled = 3
+}
+
+/// <summary>
+/// Progress of a background <see cref="Operation"/>
+/// </summary>
+public sealed class OperationProgress
+{
+    internal OperationProgress(OperationState state, long processed, long total, TimeSpan elapsed, TimeSpan? eta)
+    {
+        State = state;
+        Processed = processed;
+        Total = total;
+        Elapsed = elapsed;
+        Eta = eta;
+    }
+
+    /// <summary>State of the operation</summary>
+    public OperationState State { get; }
+
+    /// <summary>Units of work done so far, e.g. records</summary>
+    public long Processed { get; }
+
+    /// <summary>Units of work in the operation</summary>
+    public long Total { get; }
+
+    /// <summary>Time since the operation started, or its duration once it ended</summary>
+    public TimeSpan Elapsed { get; }
+
+    /// <summary>Estimated time left from the rate so far; null until some work is done and once ended</summary>
+    public TimeSpan? Eta { get; }
+}
+
+/// <summary>
+/// A long operation running on a native background thread, such as one started by
+/// <see cref="Collection.StartDeleteBatched"/>, polled instead of reporting through callbacks.
+/// Disposing it releases the native operation and cancels it if still running.
+/// </summary>
+public sealed class Operation : IDisposable
+{
+    private ulong _handle;
+    private readonly ChromaClient _client;
+
+    internal Operation(ChromaClient client, ulong handle)
+    {
+        _client = client;
+        _handle = handle;
+    }
+
+    /// <summary>
+    /// Current state and progress, read without blocking
+    /// </summary>
+    public OperationProgress Progress
+    {
+        get
+        {
+            var handle = GetHandleOrThrow();
+            var result = NativeMethods.chroma_operation_status(handle, out var progress, out var state, out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+
+            return new OperationProgress(
+                (OperationState)state,
+                (long)progress.Processed.ToUInt64(),
+                (long)progress.Total.ToUInt64(),
+                TimeSpan.FromMilliseconds(progress.ElapsedMs),
+                progress.EtaMs < 0 ? null : TimeSpan.FromMilliseconds(progress.EtaMs));
+        }
+    }
+
+    /// <summary>
+    /// Current state, read without blocking
+    /// </summary>
+    public OperationState State => Progress.State;
+
+    /// <summary>
+    /// Waits for the operation to end and throws its error if it failed
+    /// </summary>
+    /// <param name="timeout">Longest time to wait, null to wait however long it takes</param>
+    /// <returns>State reached; <see cref="OperationState.Running"/> if the timeout elapsed</returns>
+    public OperationState Wait(TimeSpan? timeout = null)
+    {
+        var handle = GetHandleOrThrow();
+        var timeoutMs = timeout.HasValue
+            ? (uint)Math.Min(Math.Max(timeout.Value.TotalMilliseconds, 0), uint.MaxValue - 1)
+            : uint.MaxValue;
+        var result = NativeMethods.chroma_operation_wait(handle, timeoutMs, out var state, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+        return (OperationState)state;
+    }
+
+    /// <summary>
+    /// Asks the operation to stop after its current step; work already done is kept
+    /// </summary>
+    public void Cancel()
+    {
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_operation_cancel(handle, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+    }
+
+    public void Dispose()
+    {
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
+            return;
+
+        var result = NativeMethods.chroma_destroy_operation(handle, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+    }
+
+    private ulong GetHandleOrThrow()
+    {
+        // An operation is unusable once the client it was started through is disposed
+        _client.GetHandleOrThrow();
+
+        var handle = Volatile.Read(ref _handle);
+        if (handle == 0)
+            throw new ObjectDisposedException(nameof(Operation));
+        return handle;
+    }
+}
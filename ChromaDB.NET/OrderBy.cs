@@ -0,0 +1,35 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Sort order for <see cref="Collection.Get"/>. Sorting happens natively before
+/// limit and offset are applied, so every page of a paginated read is consistent.
+/// </summary>
+public sealed class OrderBy
+{
+    private OrderBy(string? metadataKey, bool descending)
+    {
+        MetadataKey = metadataKey;
+        Descending = descending;
+    }
+
+    /// <summary>
+    /// Metadata key to sort by, or null to sort by document ID
+    /// </summary>
+    public string? MetadataKey { get; }
+
+    /// <summary>
+    /// Sort from largest to smallest
+    /// </summary>
+    public bool Descending { get; }
+
+    /// <summary>
+    /// Sorts by document ID
+    /// </summary>
+    public static OrderBy Id(bool descending = false) => new OrderBy(null, descending);
+
+    /// <summary>
+    /// Sorts by a metadata value, then by document ID. Numbers compare numerically and
+    /// documents without the key come last.
+    /// </summary>
+    public static OrderBy Metadata(string key, bool descending = false) => new OrderBy(key, descending);
+}
@@ -0,0 +1,78 @@
+using System;
+using System.Threading;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// A query whose filters, includes, metadata projection and options were parsed and validated
+/// once by <see cref="Collection.PrepareQuery"/>, for services running the same query shape many
+/// times with different embeddings. Disposing it releases the native prepared query.
+/// </summary>
+public sealed class PreparedQuery : IDisposable
+{
+    private ulong _handle;
+    private readonly ChromaClient _client;
+    // The native query calls the re-ranker for as long as it lives
+    private readonly NativeMethods.RerankCallback? _reranker;
+
+    internal PreparedQuery(ChromaClient client, ulong handle, NativeMethods.RerankCallback? reranker)
+    {
+        _client = client;
+        _handle = handle;
+        _reranker = reranker;
+    }
+
+    /// <summary>
+    /// Runs the query for one embedding
+    /// </summary>
+    /// <param name="queryEmbedding">Query embedding vector</param>
+    /// <param name="nResults">Number of results to return</param>
+    /// <returns>Query results, as returned by <c>Collection.Query</c></returns>
+    public QueryResult Execute(float[] queryEmbedding, int nResults = 10)
+    {
+        if (queryEmbedding == null)
+            throw new ArgumentNullException(nameof(queryEmbedding));
+
+        var handle = GetHandleOrThrow();
+        var result = NativeMethods.chroma_execute_prepared(
+            handle,
+            queryEmbedding,
+            (UIntPtr)queryEmbedding.Length,
+            (uint)nResults,
+            out var queryResultPtr,
+            out var errorPtr);
+        GC.KeepAlive(_reranker);
+
+        ChromaClient.CheckError(result, errorPtr);
+
+        try
+        {
+            return Collection.MarshalQueryResult(queryResultPtr);
+        }
+        finally
+        {
+            NativeMethods.chroma_free_query_result(queryResultPtr);
+        }
+    }
+
+    public void Dispose()
+    {
+        var handle = Interlocked.Exchange(ref _handle, 0UL);
+        if (handle == 0)
+            return;
+
+        var result = NativeMethods.chroma_destroy_prepared_query(handle, out var errorPtr);
+        ChromaClient.CheckError(result, errorPtr);
+    }
+
+    private ulong GetHandleOrThrow()
+    {
+        // A prepared query is unusable once the client it was prepared through is disposed
+        _client.GetHandleOrThrow();
+
+        var handle = Volatile.Read(ref _handle);
+        if (handle == 0)
+            throw new ObjectDisposedException(nameof(PreparedQuery));
+        return handle;
+    }
+}
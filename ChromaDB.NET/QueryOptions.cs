@@ -0,0 +1,41 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Post-processing applied natively to the hits of a query before they are marshaled,
+/// so fewer than <c>nResults</c> may be returned.
+/// </summary>
+public sealed class QueryOptions
+{
+    /// <summary>Drops hits farther than this distance, or keeps every hit when null</summary>
+    public float? MaxDistance { get; init; }
+
+    /// <summary>
+    /// Metadata key to deduplicate on, e.g. the source document of a chunk: only the closest
+    /// hit per value is kept, and hits without the key are all kept
+    /// </summary>
+    public string? DedupeByMetadataKey { get; init; }
+
+    /// <summary>Candidates fetched per requested hit when deduplicating, or 0 for the native default of 3</summary>
+    public int OverFetch { get; init; }
+
+    /// <summary>
+    /// Candidates fetched and re-ranked with maximal marginal relevance (MMR), which trades
+    /// relevance for diversity among the returned hits; 0 disables MMR
+    /// </summary>
+    public int MmrFetchK { get; init; }
+
+    /// <summary>MMR trade-off between relevance (1) and diversity (0)</summary>
+    public float MmrLambda { get; init; } = 0.5f;
+
+    /// <summary>
+    /// Query text handed to the re-ranker; candidates are re-ranked only when set, and
+    /// <see cref="QueryResult.Scores"/> then holds the re-ranker scores. Cannot be combined with MMR
+    /// </summary>
+    public string? RerankQuery { get; init; }
+
+    /// <summary>Re-ranker for this query, or null for the one set with <see cref="ChromaClient.SetReranker"/></summary>
+    public IReranker? Reranker { get; init; }
+
+    /// <summary>Candidates fetched and re-ranked, or 0 to re-rank <c>nResults</c> candidates</summary>
+    public int RerankFetchK { get; init; }
+}
@@ -32,6 +32,52 @@ public class QueryResult : IEnumerable<ChromaDocument>
     /// </summary>
     public List<string> Documents { get; set; } = new List<string>();
 
+    /// <summary>
+    /// Relevance scores (higher is more relevant), populated by keyword search
+    /// </summary>
+    public List<float> Scores { get; set; } = new List<float>();
+
+    /// <summary>
+    /// Keyword (BM25) scores, populated by hybrid search; distances are NaN for keyword-only hits
+    /// </summary>
+    public List<float> TextScores { get; set; } = new List<float>();
+
+    /// <summary>
+    /// Embeddings of the results as one row-major block of <see cref="Count"/> rows of
+    /// <see cref="EmbeddingDimension"/> floats; empty unless embeddings were included
+    /// </summary>
+    public float[] EmbeddingBuffer { get; set; } = Array.Empty<float>();
+
+    /// <summary>
+    /// Number of floats per row of <see cref="EmbeddingBuffer"/>
+    /// </summary>
+    public int EmbeddingDimension { get; set; }
+
+    /// <summary>
+    /// Gets the embedding of a result without copying it; NaN-filled when the record has none
+    /// </summary>
+    /// <param name="index">Index of the result</param>
+    public ReadOnlySpan<float> GetEmbedding(int index)
+    {
+        return EmbeddingBuffer.AsSpan(index * EmbeddingDimension, EmbeddingDimension);
+    }
+
+    /// <summary>
+    /// Whether more records match beyond this page of a Get
+    /// </summary>
+    public bool HasMore { get; set; }
+
+    /// <summary>
+    /// Number of records matching a Get across all pages, when it was asked to count them
+    /// </summary>
+    public long? TotalMatchingCount { get; set; }
+
+    /// <summary>
+    /// Version token of each record of a Get, aligned with <see cref="Ids"/>; empty unless
+    /// versions were asked for. Pass them back as expected versions of Update or Delete.
+    /// </summary>
+    public List<long> Versions { get; set; } = new List<long>();
+
     /// <summary>
     /// Gets the number of results
     /// </summary>
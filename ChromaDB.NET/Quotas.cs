@@ -0,0 +1,42 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Limits enforced by the native library on the calls of a client or tenant; null leaves a
+/// limit unset
+/// </summary>
+public sealed class Quotas
+{
+    /// <summary>Collections per database</summary>
+    public int? MaxCollections { get; init; }
+
+    /// <summary>Records per collection, counting upserted records as new</summary>
+    public int? MaxRecordsPerCollection { get; init; }
+
+    /// <summary>Dimension of the embeddings written</summary>
+    public int? MaxDimension { get; init; }
+
+    /// <summary>Adds, updates, upserts, deletes and batch commits per second</summary>
+    public int? MaxWriteOpsPerSecond { get; init; }
+
+    internal ChromaQuotasNative ToNative()
+    {
+        static ulong Limit(int? value, string name) => value switch
+        {
+            null => 0,
+            > 0 => (ulong)value.Value,
+            _ => throw new ArgumentOutOfRangeException(name, "Quotas must be positive")
+        };
+
+        return new ChromaQuotasNative
+        {
+            StructSize = (UIntPtr)Marshal.SizeOf<ChromaQuotasNative>(),
+            MaxCollections = (UIntPtr)Limit(MaxCollections, nameof(MaxCollections)),
+            MaxRecordsPerCollection = (UIntPtr)Limit(MaxRecordsPerCollection, nameof(MaxRecordsPerCollection)),
+            MaxDimension = (UIntPtr)Limit(MaxDimension, nameof(MaxDimension)),
+            MaxWriteOpsPerSecond = (uint)Limit(MaxWriteOpsPerSecond, nameof(MaxWriteOpsPerSecond))
+        };
+    }
+}
@@ -0,0 +1,66 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Classes of transient backend errors that a retry policy may retry
+/// </summary>
+[Flags]
+public enum RetryableErrorClasses
+{
+    /// <summary>Backend temporarily unavailable</summary>
+    Unavailable = 1,
+
+    /// <summary>Backend out of resources, e.g. rate limited</summary>
+    ResourceExhausted = 2,
+
+    /// <summary>Operation aborted, e.g. by a concurrent conflict</summary>
+    Aborted = 4,
+
+    /// <summary>Operation timed out</summary>
+    DeadlineExceeded = 8,
+
+    /// <summary>All of the above</summary>
+    All = Unavailable | ResourceExhausted | Aborted | DeadlineExceeded
+}
+
+/// <summary>
+/// Exponential backoff policy for retrying transient backend errors inside the native library
+/// </summary>
+public sealed class RetryPolicy
+{
+    /// <summary>Total attempts including the first one; 1 disables retries</summary>
+    public int MaxAttempts { get; init; } = 3;
+
+    /// <summary>Delay before the first retry, doubled for every further retry</summary>
+    public TimeSpan BaseDelay { get; init; } = TimeSpan.FromMilliseconds(100);
+
+    /// <summary>Upper bound for a single delay, or null for no bound</summary>
+    public TimeSpan? MaxDelay { get; init; } = TimeSpan.FromSeconds(5);
+
+    /// <summary>Fraction of each delay randomized away, between 0 and 1</summary>
+    public double Jitter { get; init; } = 0.2;
+
+    /// <summary>Error classes to retry</summary>
+    public RetryableErrorClasses RetryableErrors { get; init; } = RetryableErrorClasses.All;
+
+    /// <summary>A policy that never retries</summary>
+    public static RetryPolicy None { get; } = new RetryPolicy { MaxAttempts = 1 };
+
+    internal ChromaRetryPolicyNative ToNative()
+    {
+        if (MaxAttempts < 1)
+            throw new ArgumentOutOfRangeException(nameof(MaxAttempts), "At least one attempt is required");
+
+        return new ChromaRetryPolicyNative
+        {
+            StructSize = (UIntPtr)Marshal.SizeOf<ChromaRetryPolicyNative>(),
+            MaxAttempts = (uint)MaxAttempts,
+            BaseDelayMs = (uint)BaseDelay.TotalMilliseconds,
+            MaxDelayMs = MaxDelay.HasValue ? (uint)Math.Max(1, MaxDelay.Value.TotalMilliseconds) : 0,
+            Jitter = (float)Jitter,
+            RetryableClasses = (uint)RetryableErrors
+        };
+    }
+}
@@ -0,0 +1,16 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// Encoding used for serialized query results
+/// </summary>
+public enum SerializationFormat
+{
+    /// <summary>MessagePack, with fields written as map keys</summary>
+    MessagePack = 0,
+
+    /// <summary>CBOR (RFC 8949)</summary>
+    Cbor = 1,
+
+    /// <summary>Apache Arrow IPC stream with a single record batch, readable with Apache.Arrow's ArrowStreamReader</summary>
+    ArrowIpc = 2
+}
@@ -0,0 +1,30 @@
+namespace ChromaDB.NET;
+
+/// <summary>
+/// A sparse vector, such as a SPLADE or BM25 term weighting, as parallel arrays of
+/// dimension indices and values
+/// </summary>
+public sealed class SparseVector
+{
+    /// <summary>Dimension indices, each at most once</summary>
+    public uint[] Indices { get; }
+
+    /// <summary>Value of each index</summary>
+    public float[] Values { get; }
+
+    /// <summary>
+    /// Creates a sparse vector; indices need not be sorted but must be unique
+    /// </summary>
+    public SparseVector(IReadOnlyList<uint> indices, IReadOnlyList<float> values)
+    {
+        if (indices == null)
+            throw new ArgumentNullException(nameof(indices));
+        if (values == null)
+            throw new ArgumentNullException(nameof(values));
+        if (indices.Count != values.Count)
+            throw new ArgumentException("Sparse vector must have one value per index", nameof(values));
+
+        Indices = indices.ToArray();
+        Values = values.ToArray();
+    }
+}
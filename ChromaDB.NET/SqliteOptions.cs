@@ -0,0 +1,61 @@
+using System;
+
+namespace ChromaDB.NET;
+
+/// <summary>
+/// SQLite journal mode
+/// </summary>
+public enum SqliteJournalMode
+{
+    /// <summary>Keep the journal mode the database already uses</summary>
+    Default = 0,
+
+    /// <summary>Write-ahead log: readers do not block the writer</summary>
+    Wal = 1,
+
+    /// <summary>Rollback journal, deleted after each transaction; safest on network volumes</summary>
+    Delete = 2
+}
+
+/// <summary>
+/// How often SQLite waits for writes to reach the disk
+/// </summary>
+public enum SqliteSynchronous
+{
+    /// <summary>Keep the SQLite default</summary>
+    Default = 0,
+
+    /// <summary>Never wait; fastest, but a power loss may corrupt the database</summary>
+    Off = 1,
+
+    /// <summary>Wait at critical moments only; durable in WAL mode except for the last commits</summary>
+    Normal = 2,
+
+    /// <summary>Wait on every commit</summary>
+    Full = 3
+}
+
+/// <summary>
+/// SQLite tuning knobs, for trading durability against throughput on slow or network disks.
+/// Unset values keep the backend defaults.
+/// </summary>
+public sealed class SqliteOptions
+{
+    /// <summary>Journal mode of the database; stored in the database file</summary>
+    public SqliteJournalMode JournalMode { get; init; } = SqliteJournalMode.Default;
+
+    /// <summary>Synchronous level of every connection</summary>
+    public SqliteSynchronous Synchronous { get; init; } = SqliteSynchronous.Default;
+
+    /// <summary>How long a connection waits for a locked database</summary>
+    public TimeSpan BusyTimeout { get; init; } = TimeSpan.Zero;
+
+    /// <summary>Page cache of each connection, in KiB</summary>
+    public int CacheSizeKb { get; init; }
+
+    /// <summary>
+    /// Only validate the schema instead of applying pending migrations; a database that is not
+    /// fully migrated then fails with <see cref="ChromaErrorCode.MigrationsPending"/>
+    /// </summary>
+    public bool ValidateMigrations { get; init; }
+}
@@ -2,6 +2,7 @@ using System;
 using System.Collections.Generic;
 using System.IO;
 using System.Linq;
+using System.Text.Json;
 using System.Threading;
 using Microsoft.VisualStudio.TestTools.UnitTesting;
 
@@ -314,5 +315,2142 @@ namespace ChromaDB.NET.Tests
 
             Assert.AreEqual<uint>(1, collection.Count());
         }
+
+        [TestMethod]
+        public void Get_WithMetadataKeys_ReturnsOnlyRequestedKeys()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            collection.Add("doc1", "text",
+                new Dictionary<string, object> { ["keep"] = "yes", ["drop"] = "no", ["other"] = 1 });
+
+            var result = collection.Get(ids: new[] { "doc1" }, metadataKeys: new[] { "keep" });
+
+            Assert.AreEqual(1, result.Metadatas.Count);
+            Assert.AreEqual(1, result.Metadatas[0].Count);
+            Assert.IsTrue(result.Metadatas[0].ContainsKey("keep"));
+        }
+
+        [TestMethod]
+        public void Query_WithEmptyMetadataKeys_ReturnsEmptyMetadata()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            collection.Add("doc1", "text", new Dictionary<string, object> { ["k"] = "v" });
+
+            var result = collection.Query(queryText: "text", nResults: 1, metadataKeys: Array.Empty<string>());
+
+            Assert.AreEqual(1, result.Metadatas.Count);
+            Assert.AreEqual(0, result.Metadatas[0].Count);
+        }
+
+        [TestMethod]
+        public void Get_WithTypedMetadata_ReturnsNativeValueTypes()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.TypedMetadata = true;
+
+            collection.Add("doc1", "text",
+                new Dictionary<string, object> { ["name"] = "a", ["count"] = 3, ["ratio"] = 0.5, ["active"] = true });
+
+            var result = collection.Get(ids: new[] { "doc1" });
+
+            Assert.AreEqual(1, result.Metadatas.Count);
+            var metadata = result.Metadatas[0];
+            Assert.AreEqual("a", metadata["name"]);
+            Assert.AreEqual(3L, metadata["count"]);
+            Assert.AreEqual(0.5, metadata["ratio"]);
+            Assert.AreEqual(true, metadata["active"]);
+        }
+
+        [TestMethod]
+        public void Get_WithOrderBy_ReturnsSortedPages()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            foreach (var i in new[] { 7, 2, 9, 0, 5 })
+            {
+                collection.Add($"doc-{i}", $"Document {i}",
+                    new Dictionary<string, object> { ["rank"] = 10 - i });
+            }
+
+            var byIdDesc = collection.Get(orderBy: OrderBy.Id(descending: true));
+            CollectionAssert.AreEqual(new[] { "doc-9", "doc-7", "doc-5", "doc-2", "doc-0" }, byIdDesc.Ids);
+
+            var page1 = collection.Get(limit: 2, orderBy: OrderBy.Metadata("rank"));
+            var page2 = collection.Get(limit: 2, offset: 2, orderBy: OrderBy.Metadata("rank"));
+            CollectionAssert.AreEqual(new[] { "doc-9", "doc-7" }, page1.Ids);
+            CollectionAssert.AreEqual(new[] { "doc-5", "doc-2" }, page2.Ids);
+        }
+
+        [TestMethod]
+        public void DeleteAndReturnIds_ReportsDeletedDocuments()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            collection.Add("a", "First", new Dictionary<string, object> { ["group"] = "old" });
+            collection.Add("b", "Second", new Dictionary<string, object> { ["group"] = "old" });
+            collection.Add("c", "Third", new Dictionary<string, object> { ["group"] = "new" });
+
+            var deleted = collection.DeleteAndReturnIds(whereFilter: new Dictionary<string, object> { ["group"] = "old" });
+
+            CollectionAssert.AreEquivalent(new[] { "a", "b" }, deleted);
+            Assert.AreEqual(1u, collection.Count());
+
+            var deletedById = collection.DeleteAndReturnIds(ids: new[] { "c", "missing" });
+            CollectionAssert.AreEqual(new[] { "c" }, deletedById);
+        }
+
+        [TestMethod]
+        public void PreviewDelete_ReturnsMatchesWithoutDeleting()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            collection.Add("a", "First", new Dictionary<string, object> { ["group"] = "old" });
+            collection.Add("b", "Second", new Dictionary<string, object> { ["group"] = "new" });
+
+            var matches = collection.PreviewDelete(whereFilter: new Dictionary<string, object> { ["group"] = "old" });
+
+            CollectionAssert.AreEqual(new[] { "a" }, matches);
+            Assert.AreEqual(2u, collection.Count());
+        }
+
+        [TestMethod]
+        public void Add_OnConflict_ReportsInsertedSkippedAndOverwritten()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            var first = collection.Add(new[] { ChromaDocument.Create("a", "First"), ChromaDocument.Create("b", "Second") });
+            Assert.AreEqual(2, first.Inserted);
+
+            var batch = new[] { ChromaDocument.Create("b", "Second again"), ChromaDocument.Create("c", "Third") };
+
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.Add(batch));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+            Assert.AreEqual(2u, collection.Count());
+
+            var ignored = collection.Add(batch, OnConflict.Ignore);
+            Assert.AreEqual(1, ignored.Inserted);
+            Assert.AreEqual(1, ignored.Skipped);
+            Assert.AreEqual(3u, collection.Count());
+
+            var overwritten = collection.Add(
+                new[] { ChromaDocument.Create("b", "Second rewritten"), ChromaDocument.Create("d", "Fourth") },
+                OnConflict.Overwrite);
+            Assert.AreEqual(1, overwritten.Inserted);
+            Assert.AreEqual(1, overwritten.Overwritten);
+
+            var b = collection.Get(ids: new[] { "b" }, includeDocuments: true);
+            Assert.AreEqual("Second rewritten", b.Documents[0]);
+        }
+
+        [TestMethod]
+        public void Optimize_KeepsDocumentsQueryable()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            for (int i = 0; i < 20; i++)
+                collection.Add($"doc-{i}", $"Document number {i}");
+
+            collection.Optimize();
+
+            Assert.AreEqual(20u, collection.Count());
+            var results = collection.Query("Document number 3", nResults: 1);
+            Assert.AreEqual(1, results.Ids.Count);
+        }
+
+        [TestMethod]
+        public void Preload_LoadsCollectionsWithEmbeddings()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var filled = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            using var empty = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            filled.Add("doc1", "Preloaded document");
+
+            filled.Preload();
+            empty.Preload();
+
+            Assert.AreEqual(1, client.PreloadAll());
+            Assert.AreEqual(1u, filled.Count());
+        }
+
+        [TestMethod]
+        public void PurgeExpired_DeletesDocumentsPastTheirTtl()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var now = DateTimeOffset.UtcNow.ToUnixTimeSeconds();
+            collection.Add("stale", "Old session", new Dictionary<string, object> { ["created_at"] = now - 7200 });
+            collection.Add("fresh", "New session", new Dictionary<string, object> { ["created_at"] = now });
+            collection.Add("pinned", "Session without timestamp", new Dictionary<string, object> { ["user"] = "admin" });
+
+            Assert.AreEqual(0, collection.PurgeExpired());
+
+            collection.SetTtl(TimeSpan.FromHours(1));
+            Assert.AreEqual(1, collection.PurgeExpired());
+            Assert.AreEqual(2u, collection.Count());
+            Assert.AreEqual(0, collection.Get(ids: new[] { "stale" }).Ids.Count);
+        }
+
+        [TestMethod]
+        public void SoftDelete_HidesRecordsUntilRestoredOrPurged()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("keep", "Document to keep");
+            collection.Add("trash", "Document to trash");
+            collection.Add("restore", "Document to restore");
+
+            collection.Delete(ids: new[] { "trash", "restore" }, soft: true);
+            Assert.AreEqual(1u, collection.Count());
+            Assert.AreEqual(0, collection.Get(ids: new[] { "trash", "restore" }).Ids.Count);
+            var results = collection.Query(queryText: "Document", nResults: 3);
+            CollectionAssert.AreEqual(new[] { "keep" }, results.Ids.ToArray());
+
+            Assert.AreEqual(1, collection.RestoreRecords(new[] { "restore", "keep" }));
+            Assert.AreEqual(2u, collection.Count());
+
+            Assert.AreEqual(0, collection.PurgeDeleted(TimeSpan.FromHours(1)));
+            Assert.AreEqual(1, collection.PurgeDeleted(TimeSpan.Zero));
+            Assert.AreEqual(0, collection.RestoreRecords(new[] { "trash" }));
+            Assert.AreEqual(2u, collection.Count());
+        }
+
+        [TestMethod]
+        public void SoftDelete_HidesRecordsFromOtherOpenHandles()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("keep", "Document to keep");
+            collection.Add("trash", "Document to trash");
+            using var other = client.GetCollectionById(collection.Id, _embeddingFunction);
+
+            collection.Delete(ids: new[] { "trash" }, soft: true);
+            Assert.AreEqual(1u, other.Count());
+            Assert.AreEqual(0, other.Get(ids: new[] { "trash" }).Ids.Count);
+        }
+
+        [TestMethod]
+        public void ChangesSince_ReturnsWritesInOrderAndPages()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            using var other = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("doc1", "First document", new Dictionary<string, object> { ["source"] = "feed" });
+            other.Add("elsewhere", "Document in another collection");
+            collection.Update("doc1", "First document, edited");
+            collection.Delete("doc1");
+
+            using var all = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson());
+            var changes = all.RootElement.GetProperty("changes").EnumerateArray().ToList();
+            CollectionAssert.AreEqual(
+                new[] { "add", "update", "delete" },
+                changes.Select(c => c.GetProperty("operation").GetString()).ToArray());
+            Assert.IsTrue(changes.All(c => c.GetProperty("id").GetString() == "doc1"));
+            Assert.AreEqual("First document", changes[0].GetProperty("document").GetString());
+            Assert.AreEqual("feed", changes[0].GetProperty("metadata").GetProperty("source").GetString());
+            Assert.IsFalse(all.RootElement.GetProperty("truncated").GetBoolean());
+
+            using var first = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson(limit: 1));
+            var next = first.RootElement.GetProperty("next_sequence").GetUInt64();
+            Assert.AreEqual(changes[0].GetProperty("seq").GetUInt64(), next);
+
+            using var rest = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson(next));
+            Assert.AreEqual(2, rest.RootElement.GetProperty("changes").GetArrayLength());
+        }
+
+        [TestMethod]
+        public void ChangesSince_ReportsTruncationOnceTheLogIsPurged()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            for (int i = 0; i < 3; i++)
+                collection.Add($"doc-{i}", $"Document number {i}");
+
+            using var before = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson());
+            Assert.IsFalse(before.RootElement.GetProperty("truncated").GetBoolean());
+            var consumed = before.RootElement.GetProperty("next_sequence").GetUInt64();
+
+            collection.Optimize();
+
+            using var stale = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson());
+            Assert.IsTrue(stale.RootElement.GetProperty("truncated").GetBoolean());
+            using var current = System.Text.Json.JsonDocument.Parse(collection.GetChangesSinceJson(consumed));
+            Assert.IsFalse(current.RootElement.GetProperty("truncated").GetBoolean());
+            Assert.AreEqual(0, current.RootElement.GetProperty("changes").GetArrayLength());
+        }
+
+        [TestMethod]
+        public void CopyTo_CopiesRecordsIntoAnotherDatabase()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            client.CreateDatabase("promoted_db");
+            using var source = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            source.Add("doc1", "First document", new Dictionary<string, object> { ["stage"] = "staging" });
+            source.Add("doc2", "Second document");
+
+            using var copy = source.CopyTo("promoted", database: "promoted_db");
+            Assert.AreEqual(2u, copy.Count());
+            Assert.AreNotEqual(source.Id, copy.Id);
+            var doc = copy.GetById("doc1", includeEmbedding: true);
+            Assert.AreEqual("First document", doc.Text);
+            Assert.AreEqual("staging", doc.Metadata["stage"].ToString());
+            CollectionAssert.AreEqual(source.GetById("doc1", includeEmbedding: true).Embedding, doc.Embedding);
+
+            Assert.ThrowsException<ChromaException>(() => source.CopyTo("promoted", database: "promoted_db"));
+        }
+
+        [TestMethod]
+        public void CopyTo_CopiesReadOnlyCollectionAsWritable()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var source = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            source.Add("doc1", "First document");
+            source.Add("doc2", "Second document");
+            source.Delete(ids: new[] { "doc2" }, soft: true);
+            source.SetReadOnly(true);
+
+            using var copy = source.CopyTo($"frozen-copy-{Guid.NewGuid():N}".Substring(0, 24));
+            Assert.IsFalse(copy.IsReadOnly);
+            Assert.AreEqual(1u, copy.Count());
+            copy.Add("doc3", "Written to the copy");
+            Assert.AreEqual(2u, copy.Count());
+            Assert.IsTrue(source.IsReadOnly);
+        }
+
+        [TestMethod]
+        public void DeleteBatched_DeletesInBatchesAndReportsProgress()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var ids = Enumerable.Range(0, 25).Select(i => $"doc{i}").ToList();
+            foreach (var id in ids)
+                collection.Add(id, $"Document {id}");
+
+            var progress = new List<(int Processed, int Total)>();
+            var result = collection.DeleteBatched(ids.Take(20), batchSize: 8, onProgress: (processed, total) => progress.Add((processed, total)));
+
+            Assert.AreEqual(20, result.Deleted);
+            Assert.AreEqual(3, result.Batches);
+            Assert.AreEqual(0, result.FailedIds.Count);
+            Assert.IsNull(result.Error);
+            CollectionAssert.AreEqual(new[] { (8, 20), (16, 20), (20, 20) }, progress);
+            Assert.AreEqual(5u, collection.Count());
+
+            using var cts = new CancellationTokenSource();
+            var cancelled = collection.DeleteBatched(ids.Skip(20), batchSize: 2, onProgress: (_, _) => cts.Cancel(), cancellationToken: cts.Token);
+            Assert.IsTrue(cancelled.Cancelled);
+            Assert.AreEqual(1, cancelled.Batches);
+            Assert.AreEqual(3u, collection.Count());
+        }
+
+        [TestMethod]
+        public void StartDeleteBatched_ReportsProgressUntilDone()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var ids = Enumerable.Range(0, 25).Select(i => $"doc{i}").ToList();
+            foreach (var id in ids)
+                collection.Add(id, $"Document {id}");
+
+            using var operation = collection.StartDeleteBatched(ids.Take(20), batchSize: 4);
+            Assert.AreEqual(OperationState.Succeeded, operation.Wait(TimeSpan.FromSeconds(30)));
+
+            var progress = operation.Progress;
+            Assert.AreEqual(OperationState.Succeeded, progress.State);
+            Assert.AreEqual(20, progress.Processed);
+            Assert.AreEqual(20, progress.Total);
+            Assert.IsNull(progress.Eta);
+            Assert.AreEqual(5u, collection.Count());
+
+            operation.Dispose();
+            Assert.ThrowsException<ObjectDisposedException>(() => operation.Progress);
+        }
+
+        [TestMethod]
+        public void CountWhere_CountsOnlyMatchingDocuments()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            for (int i = 0; i < 6; i++)
+                collection.Add($"doc{i}", i % 2 == 0 ? $"Even document {i}" : $"Odd document {i}", new Dictionary<string, object> { ["parity"] = i % 2 });
+
+            Assert.AreEqual(3u, collection.Count(new Dictionary<string, object> { ["parity"] = 0 }));
+            Assert.AreEqual(3u, collection.Count(null, "{\"$contains\": \"Odd\"}"));
+            Assert.AreEqual(0u, collection.Count(new Dictionary<string, object> { ["parity"] = 5 }));
+            Assert.AreEqual(6u, collection.Count(null));
+        }
+
+        [TestMethod]
+        public void Exists_ReportsCollectionsAndRecords()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollection("exists_check", _embeddingFunction);
+            collection.Add("doc1", "First document");
+            collection.Add("doc2", "Second document");
+
+            Assert.IsTrue(client.CollectionExists("exists_check"));
+            Assert.IsFalse(client.CollectionExists("missing_collection"));
+
+            CollectionAssert.AreEqual(new[] { true, false, true, true }, collection.Exists(new[] { "doc1", "doc3", "doc2", "doc1" }));
+            Assert.IsFalse(collection.Exists("doc3"));
+            Assert.AreEqual(0, collection.Exists(Array.Empty<string>()).Length);
+        }
+
+        private static float[] UnitVector(double angle) => new[] { (float)Math.Cos(angle), (float)Math.Sin(angle), 0f };
+
+        [TestMethod]
+        public void Query_MaxDistanceDropsFarHits()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 5).Select(i =>
+                new ChromaDocument { Id = $"doc{i}", Text = $"Document number {i}", Embedding = UnitVector(i * 0.3) }));
+
+            var all = collection.Query(UnitVector(0), nResults: 5);
+            Assert.AreEqual(5, all.Count);
+            var threshold = (all.Distances[1] + all.Distances[2]) / 2;
+
+            var near = collection.Query(UnitVector(0), nResults: 5, includeDistances: false,
+                options: new QueryOptions { MaxDistance = threshold });
+            CollectionAssert.AreEqual(new[] { "doc0", "doc1" }, near.Ids);
+            Assert.AreEqual(0, near.Distances.Count);
+
+            var none = collection.Query(UnitVector(0), nResults: 5, options: new QueryOptions());
+            Assert.AreEqual(5, none.Count);
+        }
+
+        [TestMethod]
+        public void Query_DedupeByMetadataKeyKeepsBestHitPerValue()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 9).Select(i => new ChromaDocument
+            {
+                Id = $"chunk{i}",
+                Text = $"Chunk {i} of source {i % 3}",
+                Embedding = UnitVector(i * 0.1),
+                Metadata = new Dictionary<string, object> { ["source"] = $"src{i % 3}" }
+            }));
+
+            // Closest first: chunk4 (src1), chunk5 (src2), chunk3 (src0), chunk6 (src0), ...
+            var deduped = collection.Query(UnitVector(0.42), nResults: 3, includeMetadatas: false,
+                options: new QueryOptions { DedupeByMetadataKey = "source" });
+            CollectionAssert.AreEqual(new[] { "chunk4", "chunk5", "chunk3" }, deduped.Ids);
+            Assert.AreEqual(0, deduped.Metadatas.Count);
+
+            var top = collection.Query(UnitVector(0.42), nResults: 2,
+                options: new QueryOptions { DedupeByMetadataKey = "source" });
+            CollectionAssert.AreEqual(new[] { "chunk4", "chunk5" }, top.Ids);
+        }
+
+        [TestMethod]
+        public void PrepareQuery_ReusesFiltersAcrossEmbeddings()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 6).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"Document number {i}",
+                Embedding = UnitVector(i * 0.3),
+                Metadata = new Dictionary<string, object> { ["even"] = i % 2 == 0 }
+            }));
+
+            using var prepared = collection.PrepareQuery(
+                whereFilter: new Dictionary<string, object> { ["even"] = true },
+                include: Include.Distances);
+
+            var first = prepared.Execute(UnitVector(0), nResults: 2);
+            CollectionAssert.AreEqual(new[] { "doc0", "doc2" }, first.Ids);
+            Assert.AreEqual(2, first.Distances.Count);
+            Assert.AreEqual(0, first.Documents.Count);
+
+            var second = prepared.Execute(UnitVector(1.5), nResults: 1);
+            CollectionAssert.AreEqual(new[] { "doc4" }, second.Ids);
+
+            Assert.ThrowsException<ChromaException>(() => collection.PrepareQuery(
+                whereFilter: new Dictionary<string, object> { ["even"] = new Dictionary<string, object> { ["$bogus"] = 1 } }));
+
+            prepared.Dispose();
+            Assert.ThrowsException<ObjectDisposedException>(() => prepared.Execute(UnitVector(0)));
+        }
+
+        [TestMethod]
+        public void Benchmark_ReportsThroughputAndLatency()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            using (var add = JsonDocument.Parse(collection.BenchmarkAddJson(count: 25, dimension: 8, batchSize: 10, seed: 7)))
+            {
+                Assert.AreEqual(25, add.RootElement.GetProperty("records").GetInt32());
+                Assert.AreEqual(3, add.RootElement.GetProperty("batches").GetInt32());
+                Assert.IsTrue(add.RootElement.GetProperty("latency").GetProperty("max_ms").GetDouble() >= 0);
+            }
+            Assert.AreEqual(25u, collection.Count());
+
+            using (var query = JsonDocument.Parse(collection.BenchmarkQueryJson(queries: 4, dimension: 8, nResults: 5)))
+            {
+                Assert.AreEqual(4, query.RootElement.GetProperty("queries").GetInt32());
+                Assert.AreEqual(20, query.RootElement.GetProperty("hits").GetInt32());
+                Assert.IsTrue(query.RootElement.TryGetProperty("queries_per_second", out _));
+            }
+
+            Assert.ThrowsException<ChromaException>(() => collection.BenchmarkAddJson(count: 1, dimension: 4));
+        }
+
+        [TestMethod]
+        public void GenerateTestCollection_IsReproducible()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var first = client.GenerateTestCollection($"fixture_a_{Guid.NewGuid():N}", count: 50, dimension: 4, seed: 42);
+            using var second = client.GenerateTestCollection($"fixture_b_{Guid.NewGuid():N}", count: 50, dimension: 4, seed: 42);
+            Assert.AreEqual(50u, first.Count());
+
+            var ids = new[] { "doc-0", "doc-49" };
+            var a = first.Get(ids, includeEmbeddings: true, includeMetadatas: true, includeDocuments: true);
+            var b = second.Get(ids, includeEmbeddings: true, includeMetadatas: true, includeDocuments: true);
+            CollectionAssert.AreEqual(a.Documents, b.Documents);
+            CollectionAssert.AreEqual(a.EmbeddingBuffer, b.EmbeddingBuffer);
+            var doc49 = a.Ids.IndexOf("doc-49");
+            Assert.AreEqual(49L, Convert.ToInt64(a.Metadatas[doc49]["index"]));
+
+            var query = first.Query(a.GetEmbedding(a.Ids.IndexOf("doc-0")).ToArray(), nResults: 1);
+            CollectionAssert.AreEqual(new[] { "doc-0" }, query.Ids);
+        }
+
+        [TestMethod]
+        public void Aliases_SwapToRebuiltCollection()
+        {
+            var alias = $"alias_{Guid.NewGuid():N}";
+            string blueId;
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                using var blue = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+                using var green = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+                blueId = blue.Id;
+                blue.CreateAlias(alias);
+
+                var ex = Assert.ThrowsException<ChromaException>(() => green.CreateAlias(alias));
+                Assert.AreEqual(ChromaErrorCode.ConflictError, ex.ErrorInfo.Code);
+
+                using (var resolved = client.GetCollectionByAlias(alias))
+                    Assert.AreEqual(blueId, resolved.Id);
+
+                Assert.AreEqual(blueId, green.SwapAlias(alias));
+                using (var resolved = client.GetCollectionByAlias(alias))
+                    Assert.AreEqual(green.Id, resolved.Id);
+            }
+
+            // Aliases are persisted
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                using (var resolved = client.GetCollectionByAlias(alias))
+                    Assert.AreNotEqual(blueId, resolved.Id);
+
+                client.DeleteAlias(alias);
+                var ex = Assert.ThrowsException<ChromaException>(() => client.GetCollectionByAlias(alias));
+                Assert.AreEqual(ChromaErrorCode.NotFound, ex.ErrorInfo.Code);
+            }
+        }
+
+        [TestMethod]
+        public void SetReadOnly_RefusesWritesAndKeepsReads()
+        {
+            var name = $"frozen_{Guid.NewGuid():N}";
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                using var collection = client.CreateCollection(name, _embeddingFunction);
+                collection.Add(new ChromaDocument { Id = "doc1", Text = "First", Embedding = UnitVector(0) });
+
+                collection.SetReadOnly(true);
+                Assert.IsTrue(collection.IsReadOnly);
+                var ex = Assert.ThrowsException<ChromaException>(() =>
+                    collection.Add(new ChromaDocument { Id = "doc2", Text = "Second", Embedding = UnitVector(1) }));
+                Assert.AreEqual(ChromaErrorCode.CollectionReadOnly, ex.ErrorInfo.Code);
+                Assert.ThrowsException<ChromaException>(() => collection.Delete(new[] { "doc1" }));
+                Assert.AreEqual(1u, collection.Count());
+            }
+
+            // The flag is persisted
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                using var collection = client.GetCollection(name, _embeddingFunction);
+                Assert.IsTrue(collection.IsReadOnly);
+
+                collection.SetReadOnly(false);
+                collection.Add(new ChromaDocument { Id = "doc2", Text = "Second", Embedding = UnitVector(1) });
+                Assert.AreEqual(2u, collection.Count());
+            }
+        }
+
+        [TestMethod]
+        public void EnqueueAdd_WritesInBackgroundUntilFlushed()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            client.ConfigureIngestQueue(new IngestQueueOptions { Workers = 2, MaxBatchSize = 7 });
+
+            for (int i = 0; i < 5; i++)
+            {
+                var documents = Enumerable.Range(i * 10, 10)
+                    .Select(n => new ChromaDocument { Id = $"doc{n}", Text = $"Document {n}" });
+                collection.EnqueueAdd(documents);
+            }
+            client.FlushIngestQueue(TimeSpan.FromSeconds(30));
+
+            var stats = client.GetIngestQueueStats();
+            Assert.AreEqual(0, stats.Pending);
+            Assert.AreEqual(0, stats.InFlight);
+            Assert.AreEqual(50, stats.Written);
+            Assert.AreEqual(0, stats.Failed);
+            Assert.IsTrue(stats.Batches >= 8);
+            Assert.AreEqual(50u, collection.Count());
+
+            client.ConfigureIngestQueue(new IngestQueueOptions { MaxPending = 5 });
+            var tooMany = Enumerable.Range(100, 6).Select(n => new ChromaDocument { Id = $"doc{n}", Text = $"Document {n}" });
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.EnqueueAdd(tooMany));
+            Assert.AreEqual(ChromaErrorCode.Busy, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void Subscribe_ReceivesWritesInLogOrder()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("before", "Written before the subscription");
+
+            var changes = new System.Collections.Concurrent.ConcurrentQueue<CollectionChange>();
+            using (collection.Subscribe(changes.Enqueue, ChangeEvents.Add | ChangeEvents.Delete))
+            {
+                collection.Add("doc1", "First document");
+                collection.Add("doc2", "Second document");
+                collection.Update(new ChromaDocument { Id = "doc1", Text = "First document, revised" });
+                collection.Delete("doc2");
+
+                var deadline = DateTime.UtcNow.AddSeconds(10);
+                while (changes.Count < 3 && DateTime.UtcNow < deadline)
+                    Thread.Sleep(20);
+            }
+
+            var received = changes.ToList();
+            CollectionAssert.AreEqual(
+                new[] { (ChangeEvents.Add, "doc1"), (ChangeEvents.Add, "doc2"), (ChangeEvents.Delete, "doc2") },
+                received.Select(c => (c.Kind, c.DocumentId)).ToArray());
+            Assert.IsTrue(received.All(c => c.CollectionId == collection.Id));
+            Assert.IsTrue(received[0].Sequence < received[1].Sequence && received[1].Sequence < received[2].Sequence);
+        }
+
+        [TestMethod]
+        public void SetDefaultTenantDatabase_AppliesToCallsWithoutDatabase()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            client.CreateDatabase("scoped_db");
+            client.SetDefaultTenantDatabase(null, "scoped_db");
+            Assert.AreEqual(("default_tenant", "scoped_db"), client.GetDefaultTenantDatabase());
+
+            using (client.CreateCollection("scoped_collection", _embeddingFunction)) { }
+            Assert.IsTrue(client.CollectionExists("scoped_collection"));
+            Assert.IsTrue(client.CollectionExists("scoped_collection", "default_tenant", "scoped_db"));
+            Assert.IsFalse(client.CollectionExists("scoped_collection", "default_tenant", "default_database"));
+
+            var ex = Assert.ThrowsException<ChromaException>(() => client.SetDefaultTenantDatabase(null, "missing_db"));
+            Assert.AreEqual(ChromaErrorCode.NotFound, ex.ErrorInfo.Code);
+            Assert.AreEqual(("default_tenant", "scoped_db"), client.GetDefaultTenantDatabase());
+
+            client.SetDefaultTenantDatabase(null, null);
+            Assert.IsFalse(client.CollectionExists("scoped_collection"));
+        }
+
+        [TestMethod]
+        public void ValidateCollectionName_DescribesBrokenRule()
+        {
+            ChromaClient.ValidateCollectionName("my-collection_v1.2");
+
+            var cases = new[]
+            {
+                ("ab", "3 to 512 characters"),
+                ("my collection", "position 2"),
+                ("-leading", "start with"),
+                ("trailing.", "end with"),
+                ("two..periods", "Consecutive periods"),
+                ("192.168.0.1", "IPv4"),
+            };
+            foreach (var (name, reason) in cases)
+            {
+                var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.ValidateCollectionName(name));
+                Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+                StringAssert.Contains(ex.ErrorInfo.Details, reason);
+            }
+        }
+
+        [TestMethod]
+        public void ChromaUuid_GeneratesAndNormalizesIds()
+        {
+            var v4 = ChromaUuid.New();
+            Assert.AreEqual(Guid.Parse(v4).ToString("D"), v4);
+            Assert.AreEqual('4', v4[14]);
+
+            var first = ChromaUuid.New(UuidVersion.V7);
+            Thread.Sleep(2);
+            var second = ChromaUuid.New(UuidVersion.V7);
+            Assert.AreEqual('7', first[14]);
+            Assert.IsTrue(string.CompareOrdinal(first, second) < 0);
+
+            var guid = Guid.NewGuid();
+            Assert.AreEqual(guid.ToString("D"), ChromaUuid.Normalize(guid.ToString("B").ToUpperInvariant()));
+            Assert.AreEqual(guid.ToString("D"), ChromaUuid.Normalize(guid.ToString("N")));
+            Assert.IsTrue(ChromaUuid.IsValid(v4));
+            Assert.IsFalse(ChromaUuid.IsValid("not-a-uuid"));
+
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaUuid.Normalize("1234"));
+            Assert.AreEqual(ChromaErrorCode.InvalidUuid, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void Query_MmrReranksCandidates()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 10).Select(i =>
+                new ChromaDocument { Id = $"doc{i}", Text = $"Document number {i}", Embedding = UnitVector(i * 0.1) }));
+
+            var relevanceOnly = collection.Query(UnitVector(0), nResults: 3,
+                options: new QueryOptions { MmrFetchK = 10, MmrLambda = 1 });
+            CollectionAssert.AreEqual(new[] { "doc0", "doc1", "doc2" }, relevanceOnly.Ids);
+
+            // After the closest hit, MMR favours the candidates least similar to the picks so far
+            var diverse = collection.Query(UnitVector(0), nResults: 3,
+                options: new QueryOptions { MmrFetchK = 10, MmrLambda = 0.3f });
+            CollectionAssert.AreEqual(new[] { "doc0", "doc9", "doc4" }, diverse.Ids);
+
+            Assert.ThrowsException<ChromaException>(() => collection.Query(UnitVector(0),
+                options: new QueryOptions { MmrFetchK = 10, MmrLambda = 2 }));
+        }
+
+        private sealed class TrailingNumberReranker : IReranker
+        {
+            public string? LastQuery { get; private set; }
+
+            public float[] Score(string query, IReadOnlyList<string> documents)
+            {
+                LastQuery = query;
+                return documents.Select(d => float.Parse(d.Split(' ').Last())).ToArray();
+            }
+        }
+
+        [TestMethod]
+        public void Query_RerankerReordersCandidates()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 5).Select(i =>
+                new ChromaDocument { Id = $"doc{i}", Text = $"Document number {i}", Embedding = UnitVector(i * 0.3) }));
+
+            var reranker = new TrailingNumberReranker();
+            var reranked = collection.Query(UnitVector(0), nResults: 2, includeDocuments: false,
+                options: new QueryOptions { RerankQuery = "highest number", Reranker = reranker, RerankFetchK = 5 });
+            Assert.AreEqual("highest number", reranker.LastQuery);
+            CollectionAssert.AreEqual(new[] { "doc4", "doc3" }, reranked.Ids);
+            CollectionAssert.AreEqual(new[] { 4f, 3f }, reranked.Scores);
+            Assert.AreEqual(0, reranked.Documents.Count);
+
+            Assert.ThrowsException<ChromaException>(() => collection.Query(UnitVector(0),
+                options: new QueryOptions { RerankQuery = "highest number" }));
+
+            client.SetReranker(new TrailingNumberReranker());
+            var clientReranked = collection.Query(UnitVector(0), nResults: 3,
+                options: new QueryOptions { RerankQuery = "highest number" });
+            CollectionAssert.AreEqual(new[] { "doc2", "doc1", "doc0" }, clientReranked.Ids);
+        }
+
+        [TestMethod]
+        public void QuerySparse_ScoresByDotProductAndFusesWithDenseQuery()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 4).Select(i =>
+                new ChromaDocument { Id = $"doc{i}", Text = $"Document {i}", Embedding = UnitVector(i * 0.3) }));
+
+            collection.AddSparse(
+                new[] { "doc0", "doc1", "doc2", "missing" },
+                new[]
+                {
+                    new SparseVector(new uint[] { 1 }, new[] { 1f }),
+                    new SparseVector(new uint[] { 2 }, new[] { 1f }),
+                    new SparseVector(new uint[] { 2, 1 }, new[] { 2f, 0.5f }),
+                    new SparseVector(new uint[] { 1 }, new[] { 1f })
+                });
+
+            var query = new SparseVector(new uint[] { 1 }, new[] { 1f });
+            var sparse = collection.QuerySparse(query, nResults: 5);
+            CollectionAssert.AreEqual(new[] { "doc0", "doc2" }, sparse.Ids);
+            CollectionAssert.AreEqual(new[] { 1f, 0.5f }, sparse.Scores);
+
+            var hybrid = collection.QuerySparse(query, nResults: 2, queryEmbedding: UnitVector(0.9));
+            CollectionAssert.AreEqual(new[] { "doc2", "doc0" }, hybrid.Ids);
+            CollectionAssert.AreEqual(new[] { 0.5f, 1f }, hybrid.TextScores);
+
+            Assert.ThrowsException<ChromaException>(() => collection.AddSparse(
+                new[] { "doc0" }, new[] { new SparseVector(new uint[] { 3, 3 }, new[] { 1f, 2f }) }));
+        }
+
+        [TestMethod]
+        public void Blobs_AreStoredWithDocumentsAndReadBack()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var image = new byte[] { 0x89, 0x50, 0x4E, 0x47, 0, 255 };
+            collection.Add(new[]
+            {
+                new ChromaDocument { Id = "with-blob", Text = "A picture", Embedding = UnitVector(0), Blob = image },
+                new ChromaDocument { Id = "without-blob", Text = "No picture", Embedding = UnitVector(1) }
+            });
+
+            CollectionAssert.AreEqual(image, collection.GetBlob("with-blob"));
+            Assert.IsNull(collection.GetBlob("without-blob"));
+            Assert.IsNull(collection.GetBlob("missing"));
+
+            var uris = collection.PutBlobs(new[] { "with-blob", "missing" }, new[] { new byte[] { 1, 2, 3 }, new byte[] { 4 } });
+            StringAssert.StartsWith(uris[0], "chroma-blob://");
+            Assert.AreEqual("", uris[1]);
+            CollectionAssert.AreEqual(new[] { uris[0], "" }, collection.GetUris(new[] { "with-blob", "without-blob" }));
+            CollectionAssert.AreEqual(new byte[] { 1, 2, 3 }, client.GetBlob(uris[0]));
+
+            Assert.ThrowsException<ChromaException>(() => client.GetBlob("file:///etc/passwd"));
+        }
+
+        [TestMethod]
+        public void RequestContext_IsRecordedOnErrors()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+
+            using (ChromaRequestContext.Begin("req-42"))
+            {
+                Assert.AreEqual("req-42", ChromaRequestContext.Current);
+                var ex = Assert.ThrowsException<ChromaException>(() => client.GetCollection("no-such-collection"));
+                Assert.AreEqual("req-42", ex.ErrorInfo.RequestId);
+                StringAssert.Contains(ex.Message, "[request req-42]");
+
+                using var json = System.Text.Json.JsonDocument.Parse(ex.ErrorInfo.Json);
+                Assert.AreEqual("req-42", json.RootElement.GetProperty("request_id").GetString());
+            }
+
+            Assert.IsNull(ChromaRequestContext.Current);
+            var outside = Assert.ThrowsException<ChromaException>(() => client.GetCollection("no-such-collection"));
+            Assert.AreEqual("", outside.ErrorInfo.RequestId);
+        }
+
+        [TestMethod]
+        public void EmitHeader_WritesGeneratedDeclarations()
+        {
+            var path = Path.Combine(_testDir, "chroma_api.h");
+            ChromaClient.EmitHeader(path);
+
+            var header = File.ReadAllText(path);
+            StringAssert.Contains(header, "#define CHROMA_API_H");
+            StringAssert.Contains(header, "chroma_emit_header(");
+            StringAssert.Contains(header, "typedef struct ChromaQueryOptions");
+            Assert.IsFalse(header.Contains("#error"), header);
+        }
+
+        [TestMethod]
+        public void AbiVersion_MatchesBindings()
+        {
+            Assert.AreEqual(NativeMethods.AbiVersion, NativeMethods.chroma_abi_version());
+            Assert.IsTrue(NativeMethods.chroma_is_compatible(NativeMethods.AbiVersion));
+            Assert.IsFalse(NativeMethods.chroma_is_compatible(NativeMethods.AbiVersion + 1));
+            ChromaClient.EnsureCompatibleNativeLibrary();
+        }
+
+        [TestMethod]
+        public void OptionStructs_AreCheckedAgainstTheirStructSize()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            var handle = client.GetHandleOrThrow();
+            var policy = RetryPolicy.None.ToNative();
+
+            // An older caller omitting trailing fields reads them as zero
+            policy.StructSize = (UIntPtr)(UIntPtr.Size + sizeof(uint));
+            var result = NativeMethods.chroma_set_retry_policy(handle, ref policy, out var errorPtr);
+            ChromaClient.CheckError(result, errorPtr);
+
+            policy.StructSize = (UIntPtr)(System.Runtime.InteropServices.Marshal.SizeOf<ChromaRetryPolicyNative>() + sizeof(uint));
+            result = NativeMethods.chroma_set_retry_policy(handle, ref policy, out errorPtr);
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.CheckError(result, errorPtr));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+
+            policy.StructSize = UIntPtr.Zero;
+            result = NativeMethods.chroma_set_retry_policy(handle, ref policy, out errorPtr);
+            Assert.ThrowsException<ChromaException>(() => ChromaClient.CheckError(result, errorPtr));
+        }
+
+        [TestMethod]
+        public void PersistDirectory_WithLongNonAsciiPath_IsReopened()
+        {
+            var persistDir = _testDir;
+            for (int i = 0; i < 6; i++)
+                persistDir = Path.Combine(persistDir, $"données-日本語-{i}-" + new string('x', 40));
+            Assert.IsTrue(persistDir.Length > 260);
+
+            string name = $"long-path-{Guid.NewGuid():N}".Substring(0, 30);
+            using (var client = new ChromaClient(persistDirectory: persistDir))
+            using (var collection = client.CreateCollection(name, _embeddingFunction))
+            {
+                collection.Add(new ChromaDocument { Id = "doc1", Text = "stored deep", Embedding = UnitVector(0) });
+            }
+
+            Assert.IsTrue(File.Exists(Path.Combine(persistDir, "chroma.sqlite3")));
+            using (var client = new ChromaClient(persistDirectory: persistDir))
+            using (var collection = client.GetCollection(name, _embeddingFunction))
+            {
+                Assert.AreEqual<uint>(1, collection.Count());
+            }
+        }
+
+        [TestMethod]
+        public void PersistDirectory_WindowsPathForms_OpenTheSameDatabase()
+        {
+            if (!OperatingSystem.IsWindows())
+                Assert.Inconclusive("Windows path forms are only meaningful on Windows");
+
+            string name = $"win-path-{Guid.NewGuid():N}".Substring(0, 30);
+            using (var client = new ChromaClient(persistDirectory: _testDir.Replace('\\', '/')))
+            using (var collection = client.CreateCollection(name, _embeddingFunction))
+            {
+                collection.Add(new ChromaDocument { Id = "doc1", Text = "forward slashes", Embedding = UnitVector(0) });
+            }
+
+            using (var client = new ChromaClient(persistDirectory: @"\\?\" + Path.GetFullPath(_testDir)))
+            using (var collection = client.GetCollection(name, _embeddingFunction))
+            {
+                Assert.AreEqual<uint>(1, collection.Count());
+            }
+
+            var relative = Path.GetRelativePath(Environment.CurrentDirectory, _testDir);
+            using (var client = new ChromaClient(persistDirectory: Path.Combine(relative, ".", "sub", "..")))
+            using (var collection = client.GetCollection(name, _embeddingFunction))
+            {
+                Assert.AreEqual<uint>(1, collection.Count());
+            }
+        }
+
+        [TestMethod]
+        public void DefaultIncludes_ApplyWhenIncludeFlagsAreLeftNull()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "first", Embedding = UnitVector(0) });
+
+            Assert.AreEqual(Include.Metadatas | Include.Documents | Include.Distances, collection.DefaultIncludes);
+
+            collection.DefaultIncludes = Include.Metadatas;
+            Assert.AreEqual(Include.Metadatas, collection.DefaultIncludes);
+
+            var got = collection.Get(ids: new[] { "doc1" });
+            Assert.AreEqual(0, got.Documents.Count);
+            var queried = collection.Query(UnitVector(0), nResults: 1);
+            Assert.AreEqual(0, queried.Documents.Count);
+            Assert.AreEqual(0, queried.Distances.Count);
+
+            var explicitDocuments = collection.Get(ids: new[] { "doc1" }, includeDocuments: true);
+            Assert.AreEqual("first", explicitDocuments.Documents[0]);
+
+            collection.DefaultIncludes = Include.Default;
+            Assert.AreEqual(1, collection.Query(UnitVector(0), nResults: 1).Distances.Count);
+
+            Assert.ThrowsException<ChromaException>(() => collection.DefaultIncludes = (Include)0x100);
+        }
+
+        [TestMethod]
+        public void GetOne_ReturnsRequestedFieldsOrNull()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument
+            {
+                Id = "doc1",
+                Text = "single record",
+                Embedding = UnitVector(0),
+                Metadata = new Dictionary<string, object> { ["kind"] = "note" }
+            });
+
+            var full = collection.GetOne("doc1", Include.Embeddings | Include.Metadatas | Include.Documents);
+            Assert.IsNotNull(full);
+            Assert.AreEqual("doc1", full.Id);
+            Assert.AreEqual("single record", full.Text);
+            Assert.AreEqual("note", full.Metadata["kind"].ToString());
+            CollectionAssert.AreEqual(UnitVector(0), full.Embedding);
+
+            var defaults = collection.GetOne("doc1");
+            Assert.IsNotNull(defaults);
+            Assert.AreEqual("single record", defaults.Text);
+            Assert.IsNull(defaults.Embedding);
+
+            var idOnly = collection.GetOne("doc1", Include.Uris);
+            Assert.IsNotNull(idOnly);
+            Assert.IsNull(idOnly.Text);
+            Assert.IsNull(idOnly.Metadata);
+
+            Assert.IsNull(collection.GetOne("missing"));
+        }
+
+        [TestMethod]
+        public void UpdateOneAndDeleteOne_TouchASingleDocument()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument
+            {
+                Id = "doc1",
+                Text = "before",
+                Embedding = UnitVector(0),
+                Metadata = new Dictionary<string, object> { ["kind"] = "note" }
+            });
+
+            collection.Update(new ChromaDocument
+            {
+                Id = "doc1",
+                Embedding = UnitVector(1),
+                Metadata = new Dictionary<string, object> { ["kind"] = "memo" }
+            });
+
+            var updated = collection.GetOne("doc1", Include.Embeddings | Include.Metadatas | Include.Documents);
+            Assert.IsNotNull(updated);
+            Assert.AreEqual("before", updated.Text);
+            Assert.AreEqual("memo", updated.Metadata["kind"].ToString());
+            CollectionAssert.AreEqual(UnitVector(1), updated.Embedding);
+
+            Assert.IsFalse(collection.DeleteOne("missing"));
+            Assert.IsTrue(collection.DeleteOne("doc1"));
+            Assert.IsNull(collection.GetOne("doc1"));
+            Assert.AreEqual(0u, collection.Count());
+        }
+
+        [TestMethod]
+        public void UpdateMetadata_AppliesOnePatchToManyDocuments()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new[]
+            {
+                new ChromaDocument
+                {
+                    Id = "doc1",
+                    Text = "first",
+                    Embedding = UnitVector(0),
+                    Metadata = new Dictionary<string, object> { ["kind"] = "note", ["draft"] = true }
+                },
+                new ChromaDocument
+                {
+                    Id = "doc2",
+                    Text = "second",
+                    Embedding = UnitVector(1),
+                    Metadata = new Dictionary<string, object> { ["kind"] = "memo", ["draft"] = true }
+                }
+            });
+
+            collection.UpdateMetadata(
+                new[] { "doc1", "doc2", "missing" },
+                new Dictionary<string, object> { ["reviewed"] = true, ["draft"] = null });
+
+            foreach (var id in new[] { "doc1", "doc2" })
+            {
+                var merged = collection.GetOne(id, Include.Metadatas | Include.Documents);
+                Assert.IsNotNull(merged);
+                Assert.IsTrue(merged.Metadata.ContainsKey("kind"));
+                Assert.IsTrue(merged.Metadata.ContainsKey("reviewed"));
+                Assert.IsFalse(merged.Metadata.ContainsKey("draft"));
+            }
+
+            collection.UpdateMetadata(
+                new[] { "doc1" },
+                new Dictionary<string, object> { ["archived"] = true },
+                MetadataMergeMode.Replace);
+
+            var replaced = collection.GetOne("doc1", Include.Metadatas | Include.Documents);
+            Assert.IsNotNull(replaced);
+            Assert.AreEqual("first", replaced.Text);
+            CollectionAssert.AreEquivalent(new[] { "archived" }, replaced.Metadata.Keys.ToArray());
+
+            Assert.ThrowsException<ChromaException>(() => collection.UpdateMetadata(
+                new[] { "doc1" },
+                new Dictionary<string, object> { ["a"] = 1 },
+                (MetadataMergeMode)5));
+
+            var reserved = Assert.ThrowsException<ChromaException>(() => collection.UpdateMetadata(
+                new[] { "doc1" },
+                new Dictionary<string, object> { ["soft_delete:deleted"] = true },
+                MetadataMergeMode.Replace));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, reserved.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void FilterableFields_AreStoredAndCheckedAgainstFilters()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var filter = new Dictionary<string, object>
+            {
+                ["$and"] = new object[]
+                {
+                    new Dictionary<string, object> { ["kind"] = "note" },
+                    new Dictionary<string, object> { ["year"] = new Dictionary<string, object> { ["$gt"] = 2020 } }
+                }
+            };
+
+            Assert.AreEqual(0, collection.GetFilterableFields().Count);
+            Assert.AreEqual(0, collection.GetUndeclaredFilterKeys(filter).Count);
+
+            collection.SetFilterableFields(new[] { "kind", "author" });
+            CollectionAssert.AreEqual(new[] { "author", "kind" }, collection.GetFilterableFields());
+            CollectionAssert.AreEqual(new[] { "year" }, collection.GetUndeclaredFilterKeys(filter));
+
+            // Filters on undeclared keys still run
+            collection.Add(new ChromaDocument
+            {
+                Id = "doc1",
+                Text = "note",
+                Embedding = UnitVector(0),
+                Metadata = new Dictionary<string, object> { ["kind"] = "note", ["year"] = 2024 }
+            });
+            Assert.AreEqual(1, collection.Get(whereFilter: filter).Ids.Count);
+
+            Assert.ThrowsException<ChromaException>(() => collection.SetFilterableFields(new[] { "$bad" }));
+
+            collection.SetFilterableFields(Array.Empty<string>());
+            Assert.AreEqual(0, collection.GetFilterableFields().Count);
+        }
+
+        [TestMethod]
+        public void ValidateFilter_NormalizesFiltersAndLocatesErrors()
+        {
+            var normalized = ChromaClient.ValidateFilter(
+                "{\"kind\": \"note\", \"year\": {\"$gt\": 2020}}",
+                "{\"$contains\": \"chroma\"}");
+
+            using (var json = JsonDocument.Parse(normalized))
+            {
+                var and = json.RootElement.GetProperty("where").GetProperty("$and");
+                Assert.AreEqual(2, and.GetArrayLength());
+                Assert.AreEqual("note", and[0].GetProperty("kind").GetProperty("$eq").GetString());
+                Assert.AreEqual(2020, and[1].GetProperty("year").GetProperty("$gt").GetInt32());
+                Assert.AreEqual("chroma", json.RootElement.GetProperty("where_document").GetProperty("$contains").GetString());
+            }
+
+            Assert.AreEqual("{}", ChromaClient.ValidateFilter(null));
+
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.ValidateFilter(
+                "{\"$and\": [{\"kind\": \"note\"}, {\"year\": {\"$bogus\": 1}}]}"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+            StringAssert.Contains(ex.ErrorInfo.Details, "where.$and[1].year");
+
+            var syntax = Assert.ThrowsException<ChromaException>(() => ChromaClient.ValidateFilter("{\"kind\": "));
+            StringAssert.Contains(syntax.ErrorInfo.Details, "At where");
+        }
+
+        [TestMethod]
+        public void ExplainQueryJson_ReportsCountsAndStages()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 4).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i),
+                Metadata = new Dictionary<string, object> { ["even"] = i % 2 == 0 }
+            }));
+
+            var json = collection.ExplainQueryJson(
+                UnitVector(0),
+                nResults: 3,
+                whereFilter: new Dictionary<string, object> { ["even"] = true });
+
+            using var report = JsonDocument.Parse(json);
+            var root = report.RootElement;
+            Assert.AreEqual(4, root.GetProperty("records").GetInt32());
+            Assert.IsTrue(root.GetProperty("filtered").GetBoolean());
+            Assert.AreEqual(2, root.GetProperty("candidates").GetInt32());
+            Assert.AreEqual(2, root.GetProperty("hits").GetInt32());
+            Assert.AreEqual(JsonValueKind.Null, root.GetProperty("cache_hits").ValueKind);
+            Assert.IsTrue(root.GetProperty("segments").GetArrayLength() > 0);
+
+            var stages = root.GetProperty("stages").EnumerateArray()
+                .Select(s => s.GetProperty("name").GetString())
+                .ToArray();
+            CollectionAssert.AreEqual(new[] { "segments", "count", "filter", "knn" }, stages);
+        }
+
+        [TestMethod]
+        public void Flush_PersistsWritesAndRunsOnAnInterval()
+        {
+            var collectionName = "flush_" + Guid.NewGuid().ToString("N");
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                using var collection = client.CreateCollection(collectionName, _embeddingFunction);
+                collection.Add(new ChromaDocument { Id = "doc1", Text = "flushed", Embedding = UnitVector(0) });
+
+                collection.Flush();
+                client.Flush();
+
+                client.SetAutoFlushInterval(TimeSpan.FromMilliseconds(50));
+                var deadline = DateTime.UtcNow.AddSeconds(10);
+                int flushes;
+                do
+                {
+                    Thread.Sleep(50);
+                    using var metrics = JsonDocument.Parse(client.GetMetricsJson());
+                    flushes = metrics.RootElement.GetProperty("operations").GetProperty("flush").GetProperty("calls").GetInt32();
+                }
+                while (flushes < 4 && DateTime.UtcNow < deadline);
+                client.SetAutoFlushInterval(TimeSpan.Zero);
+
+                Assert.IsTrue(flushes >= 4, $"Expected automatic flushes, saw {flushes} flushes");
+                Assert.ThrowsException<ArgumentOutOfRangeException>(() => client.SetAutoFlushInterval(TimeSpan.FromSeconds(-1)));
+            }
+
+            using (var reopened = new ChromaClient(persistDirectory: _testDir))
+            {
+                using var collection = reopened.GetCollection(collectionName, _embeddingFunction);
+                Assert.AreEqual("flushed", collection.GetOne("doc1")?.Text);
+            }
+        }
+
+        [TestMethod]
+        public void Shutdown_FlushesAndRejectsFurtherOperations()
+        {
+            var collectionName = "shutdown_" + Guid.NewGuid().ToString("N");
+            var client = new ChromaClient(persistDirectory: _testDir);
+            var collection = client.CreateCollection(collectionName, _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "drained", Embedding = UnitVector(0) });
+
+            Assert.AreEqual(0, client.Shutdown(TimeSpan.FromSeconds(5)));
+
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.Count());
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+            Assert.ThrowsException<ObjectDisposedException>(() => client.Heartbeat());
+            Assert.ThrowsException<ObjectDisposedException>(() => client.Shutdown(TimeSpan.Zero));
+
+            using var reopened = new ChromaClient(persistDirectory: _testDir);
+            using var reopenedCollection = reopened.GetCollection(collectionName, _embeddingFunction);
+            Assert.AreEqual("drained", reopenedCollection.GetOne("doc1")?.Text);
+        }
+
+        [TestMethod]
+        public void Retain_KeepsTheClientAliveUntilEveryReferenceIsDisposed()
+        {
+            var client = new ChromaClient(persistDirectory: _testDir);
+            using var shared = client.Retain();
+            client.Dispose();
+
+            Assert.ThrowsException<ObjectDisposedException>(() => client.Heartbeat());
+            Assert.IsTrue(shared.Heartbeat() > 0);
+
+            using var collection = shared.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "shared", Embedding = UnitVector(0) });
+            Assert.AreEqual(1u, collection.Count());
+        }
+
+        [TestMethod]
+        public void RegisterLogCallback_StopsForwardingOnceDisposed()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.SetFilterableFields(new[] { "kind" });
+            var filter = new Dictionary<string, object> { ["year"] = 2024 };
+
+            var records = new List<(ChromaLogLevel Level, string Message)>();
+            var registration = ChromaClient.RegisterLogCallback(
+                (level, target, message) =>
+                {
+                    lock (records)
+                        records.Add((level, message));
+                },
+                ChromaLogLevel.Warn);
+
+            collection.Get(whereFilter: filter);
+            registration.Dispose();
+            registration.Dispose();
+
+            int forwarded;
+            lock (records)
+            {
+                forwarded = records.Count;
+                Assert.IsTrue(records.Any(r => r.Level == ChromaLogLevel.Warn && r.Message.Contains("not declared filterable")));
+                Assert.IsTrue(records.All(r => r.Level <= ChromaLogLevel.Warn));
+            }
+
+            collection.Get(whereFilter: filter);
+            lock (records)
+                Assert.AreEqual(forwarded, records.Count);
+        }
+
+        [TestMethod]
+        public void FromConfig_ReadsClientOptionsFromAFile()
+        {
+            var persistDirectory = Path.Combine(_testDir, "from-config");
+            Directory.CreateDirectory(persistDirectory);
+            var configPath = Path.Combine(_testDir, "chroma.yaml");
+            File.WriteAllText(configPath,
+                $"persist_path: \"{persistDirectory.Replace("\\", "\\\\")}\"\n" +
+                "hnsw_cache_size: 42\n" +
+                "sqlite:\n" +
+                "  journal_mode: wal\n");
+
+            using (var client = ChromaClient.FromConfig(configPath))
+            using (var config = JsonDocument.Parse(client.GetEffectiveConfigJson()))
+            {
+                var root = config.RootElement;
+                Assert.AreEqual(42, root.GetProperty("hnsw_cache").GetProperty("capacity").GetInt32());
+                StringAssert.EndsWith(root.GetProperty("persist_path").GetString(), "from-config");
+            }
+
+            File.WriteAllText(configPath, "hnsw_cache_sise: 42\n");
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.FromConfig(configPath));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+
+            ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.FromConfig(Path.Combine(_testDir, "missing.toml")));
+            Assert.AreEqual(ChromaErrorCode.NotFound, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void Get_ReportsHasMoreAndTotalMatchingCount()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+
+            for (int i = 0; i < 5; i++)
+            {
+                collection.Add($"doc-{i:D2}", $"Document {i}",
+                    new Dictionary<string, object> { ["i"] = i });
+            }
+
+            var first = collection.Get(limit: 2, countTotal: true);
+            Assert.AreEqual(2, first.Ids.Count);
+            Assert.IsTrue(first.HasMore);
+            Assert.AreEqual(5L, first.TotalMatchingCount);
+
+            var last = collection.Get(limit: 2, offset: 4, countTotal: true);
+            Assert.AreEqual(1, last.Ids.Count);
+            Assert.IsFalse(last.HasMore);
+            Assert.AreEqual(5L, last.TotalMatchingCount);
+
+            var sorted = collection.Get(limit: 2, offset: 2, orderBy: OrderBy.Id());
+            CollectionAssert.AreEqual(new[] { "doc-02", "doc-03" }, sorted.Ids);
+            Assert.IsTrue(sorted.HasMore);
+            Assert.IsNull(sorted.TotalMatchingCount);
+        }
+
+        [TestMethod]
+        public void GetIdsAndQueryIds_ReturnOnlyIds()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 4).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i),
+                Metadata = new Dictionary<string, object> { ["even"] = i % 2 == 0 }
+            }));
+
+            var even = collection.GetIds(whereFilter: new Dictionary<string, object> { ["even"] = true });
+            CollectionAssert.AreEquivalent(new[] { "doc0", "doc2" }, even);
+            Assert.AreEqual(2, collection.GetIds(limit: 2).Count);
+            CollectionAssert.AreEquivalent(new[] { "doc1" }, collection.GetIds(ids: new[] { "doc1", "missing" }));
+
+            var hits = collection.QueryIds(UnitVector(1), nResults: 2, includeDistances: true);
+            Assert.AreEqual("doc1", hits.Ids[0]);
+            Assert.AreEqual(2, hits.Distances.Count);
+            Assert.AreEqual(0, hits.Documents.Count);
+            Assert.AreEqual(0, hits.Metadatas.Count);
+
+            hits = collection.QueryIds(UnitVector(1), nResults: 2);
+            Assert.AreEqual(2, hits.Ids.Count);
+            Assert.AreEqual(0, hits.Distances.Count);
+        }
+
+        [TestMethod]
+        public void Get_ReturnsEmbeddingsAsOneContiguousBuffer()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 3).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i)
+            }));
+
+            var results = collection.Get(includeEmbeddings: true, includeMetadatas: false, includeDocuments: false,
+                orderBy: OrderBy.Id());
+            Assert.AreEqual(UnitVector(0).Length, results.EmbeddingDimension);
+            Assert.AreEqual(3 * results.EmbeddingDimension, results.EmbeddingBuffer.Length);
+            for (int i = 0; i < 3; i++)
+                CollectionAssert.AreEqual(UnitVector(i), results.GetEmbedding(i).ToArray());
+
+            var withoutEmbeddings = collection.Get(includeEmbeddings: false, includeMetadatas: false, includeDocuments: false);
+            Assert.AreEqual(0, withoutEmbeddings.EmbeddingBuffer.Length);
+        }
+
+        [TestMethod]
+        public void Add_GeneratesIdsWhenNoneAreGiven()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            var docs = Enumerable.Range(0, 3)
+                .Select(i => new ChromaDocument { Text = $"document {i}", Embedding = UnitVector(i) })
+                .ToList();
+
+            var added = collection.Add(docs);
+
+            Assert.AreEqual(3, added.Inserted);
+            Assert.IsTrue(docs.All(d => Guid.TryParse(d.Id, out _)));
+            Assert.AreEqual(3, docs.Select(d => d.Id).Distinct().Count());
+            CollectionAssert.AreEquivalent(docs.Select(d => d.Id).ToList(), collection.GetIds());
+
+            var mixed = new[]
+            {
+                new ChromaDocument { Id = "named", Text = "named", Embedding = UnitVector(0) },
+                new ChromaDocument { Text = "unnamed", Embedding = UnitVector(1) }
+            };
+            Assert.ThrowsException<ArgumentException>(() => collection.Add(mixed));
+        }
+
+        [TestMethod]
+        public void SetMetadataSchema_RejectsMistypedAndMissingFields()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.SetMetadataSchema(new Dictionary<string, MetadataField>
+            {
+                ["year"] = new MetadataField(MetadataFieldType.Int, required: true),
+                ["score"] = new MetadataField(MetadataFieldType.Float)
+            });
+
+            var schema = collection.GetMetadataSchema();
+            Assert.IsNotNull(schema);
+            Assert.AreEqual(MetadataFieldType.Int, schema["year"].Type);
+            Assert.IsTrue(schema["year"].Required);
+            Assert.IsFalse(schema["score"].Required);
+
+            collection.Add("ok", "valid", new Dictionary<string, object> { ["year"] = 2024, ["score"] = 3 });
+
+            var ex = Assert.ThrowsException<ChromaException>(() =>
+                collection.Add("typed", "wrong type", new Dictionary<string, object> { ["year"] = "2024" }));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+            StringAssert.Contains(ex.ErrorInfo.Details, "metadatas[0].year: expected int, got string");
+
+            ex = Assert.ThrowsException<ChromaException>(() =>
+                collection.Add("missing", "no year", new Dictionary<string, object> { ["score"] = 1.5 }));
+            StringAssert.Contains(ex.ErrorInfo.Details, "metadatas[0].year: required field is missing");
+
+            collection.SetMetadataSchema(null);
+            Assert.IsNull(collection.GetMetadataSchema());
+            collection.Add("free", "no schema", new Dictionary<string, object> { ["year"] = "2024" });
+        }
+
+        [TestMethod]
+        public void AggregateJson_CountsMinMaxAndDistinctPerGroup()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 5).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i),
+                Metadata = new Dictionary<string, object>
+                {
+                    ["kind"] = i % 2 == 0 ? "even" : "odd",
+                    ["year"] = 2020 + i,
+                    ["author"] = i < 3 ? "ann" : "bob"
+                }
+            }));
+
+            var json = collection.AggregateJson(
+                "[{\"op\": \"count\"}, {\"op\": \"min\", \"key\": \"year\"}, {\"op\": \"max\", \"key\": \"year\"}, {\"op\": \"distinct\", \"key\": \"author\"}]",
+                groupByKey: "kind");
+
+            using var report = JsonDocument.Parse(json);
+            var groups = report.RootElement.GetProperty("groups").EnumerateArray().ToArray();
+            Assert.AreEqual(2, groups.Length);
+            Assert.AreEqual("even", groups[0].GetProperty("group").GetString());
+            var even = groups[0].GetProperty("values");
+            Assert.AreEqual(3, even.GetProperty("count").GetInt32());
+            Assert.AreEqual(2020, even.GetProperty("min:year").GetInt32());
+            Assert.AreEqual(2024, even.GetProperty("max:year").GetInt32());
+            CollectionAssert.AreEqual(
+                new[] { "ann", "bob" },
+                even.GetProperty("distinct:author").EnumerateArray().Select(a => a.GetString()).ToArray());
+            Assert.AreEqual(2, groups[1].GetProperty("values").GetProperty("count").GetInt32());
+
+            json = collection.AggregateJson(
+                "[{\"op\": \"count\"}]",
+                whereFilter: new Dictionary<string, object> { ["author"] = "bob" });
+            using var filtered = JsonDocument.Parse(json);
+            var single = filtered.RootElement.GetProperty("groups")[0];
+            Assert.AreEqual(JsonValueKind.Null, single.GetProperty("group").ValueKind);
+            Assert.AreEqual(2, single.GetProperty("values").GetProperty("count").GetInt32());
+
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.AggregateJson("[{\"op\": \"max\"}]"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void GetDistinctMetadataValues_ListsSortedUniqueValues()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 6).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i),
+                Metadata = new Dictionary<string, object>
+                {
+                    ["author"] = new[] { "carol", "ann", "bob" }[i % 3],
+                    ["year"] = 2020 + i % 2
+                }
+            }));
+
+            CollectionAssert.AreEqual(
+                new[] { "ann", "bob", "carol" },
+                collection.GetDistinctMetadataValues("author"));
+            CollectionAssert.AreEqual(new[] { "ann", "bob" }, collection.GetDistinctMetadataValues("author", limit: 2));
+            CollectionAssert.AreEqual(new[] { "2020", "2021" }, collection.GetDistinctMetadataValues("year"));
+            Assert.AreEqual(0, collection.GetDistinctMetadataValues("missing").Count);
+        }
+
+        [TestMethod]
+        public void UpdateWhere_UpdatesOnlyMatchingDocuments()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(Enumerable.Range(0, 4).Select(i => new ChromaDocument
+            {
+                Id = $"doc{i}",
+                Text = $"document {i}",
+                Embedding = UnitVector(i),
+                Metadata = new Dictionary<string, object> { ["source"] = i < 3 ? "x" : "y" }
+            }));
+
+            var updated = collection.UpdateWhere(
+                new Dictionary<string, object> { ["reviewed"] = true },
+                whereFilter: new Dictionary<string, object> { ["source"] = "x" });
+            CollectionAssert.AreEquivalent(new[] { "doc0", "doc1", "doc2" }, updated);
+
+            var reviewed = collection.GetIds(whereFilter: new Dictionary<string, object> { ["reviewed"] = true });
+            CollectionAssert.AreEquivalent(new[] { "doc0", "doc1", "doc2" }, reviewed);
+
+            updated = collection.UpdateWhere(
+                new Dictionary<string, object> { ["flagged"] = true },
+                whereFilter: new Dictionary<string, object> { ["source"] = "x" },
+                ids: new[] { "doc2", "doc3" });
+            CollectionAssert.AreEqual(new[] { "doc2" }, updated);
+            CollectionAssert.AreEqual(
+                new[] { "doc2" },
+                collection.GetIds(whereFilter: new Dictionary<string, object> { ["flagged"] = true }));
+
+            Assert.AreEqual(0, collection.UpdateWhere(
+                new Dictionary<string, object> { ["reviewed"] = false },
+                whereFilter: new Dictionary<string, object> { ["source"] = "z" }).Count);
+        }
+
+        [TestMethod]
+        public void BeginBatch_AppliesQueuedWritesOnCommitOnly()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "stale", Text = "old chunk", Embedding = UnitVector(3), Metadata = new Dictionary<string, object> { ["parent"] = "doc" } });
+
+            using (var batch = client.BeginBatch())
+            {
+                batch.Add(collection, new[]
+                {
+                    new ChromaDocument { Id = "doc", Text = "document", Embedding = UnitVector(0) },
+                    new ChromaDocument { Id = "doc#1", Text = "chunk 1", Embedding = UnitVector(1), Metadata = new Dictionary<string, object> { ["parent"] = "doc" } }
+                });
+                batch.Delete(collection, whereFilter: new Dictionary<string, object> { ["parent"] = "doc" });
+                batch.Update(collection, new[] { new ChromaDocument { Id = "doc", Metadata = new Dictionary<string, object> { ["chunks"] = 1 } } });
+
+                Assert.AreEqual<uint>(1, collection.Count());
+                batch.Commit();
+            }
+
+            CollectionAssert.AreEquivalent(new[] { "doc", "doc#1" }, collection.GetIds());
+            CollectionAssert.AreEqual(
+                new[] { "doc" },
+                collection.GetIds(whereFilter: new Dictionary<string, object> { ["chunks"] = 1 }));
+
+            using (var aborted = client.BeginBatch())
+            {
+                aborted.Delete(collection, ids: new[] { "doc" });
+            }
+            Assert.AreEqual<uint>(2, collection.Count());
+
+            using var other = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            using var mixed = client.BeginBatch();
+            mixed.Delete(collection, ids: new[] { "doc" });
+            var ex = Assert.ThrowsException<ChromaException>(() => mixed.Delete(other, ids: new[] { "doc" }));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void BatchCommit_IsReadBackAfterReopeningTheClient()
+        {
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            using (var collection = client.CreateCollection("batched", _embeddingFunction))
+            using (var batch = client.BeginBatch())
+            {
+                batch.Add(collection, new[]
+                {
+                    new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) },
+                    new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) }
+                });
+                batch.Update(collection, new[] { new ChromaDocument { Id = "b", Text = "Second, revised" } });
+                batch.Commit();
+            }
+
+            using var reopened = new ChromaClient(persistDirectory: _testDir);
+            using var read = reopened.GetCollection("batched", _embeddingFunction);
+            var result = read.Get(ids: new[] { "a", "b" });
+            CollectionAssert.AreEquivalent(new[] { "a", "b" }, result.Ids.ToList());
+            Assert.AreEqual("Second, revised", result.Documents[result.Ids.IndexOf("b")]);
+            Assert.AreEqual("a", read.Query(UnitVector(0), nResults: 1).Ids[0]);
+        }
+
+        [TestMethod]
+        public void Versions_RejectStaleUpdatesAndDeletes()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new[]
+            {
+                new ChromaDocument { Id = "a", Text = "first", Embedding = UnitVector(0) },
+                new ChromaDocument { Id = "b", Text = "second", Embedding = UnitVector(1) }
+            });
+
+            var read = collection.Get(ids: new[] { "a", "b" }, includeVersions: true);
+            Assert.AreEqual(2, read.Versions.Count);
+            Assert.IsTrue(read.Versions.All(v => v > 0));
+            var versionA = read.Versions[read.Ids.IndexOf("a")];
+            var versionB = read.Versions[read.Ids.IndexOf("b")];
+
+            collection.Update(
+                new[] { new ChromaDocument { Id = "a", Metadata = new Dictionary<string, object> { ["editor"] = "one" } } },
+                new[] { versionA });
+            var updated = collection.Get(ids: new[] { "a" }, includeVersions: true);
+            Assert.IsTrue(updated.Versions[0] > versionA);
+
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.Update(
+                new[] { new ChromaDocument { Id = "a", Metadata = new Dictionary<string, object> { ["editor"] = "two" } } },
+                new[] { versionA }));
+            Assert.AreEqual(ChromaErrorCode.ConflictError, ex.ErrorInfo.Code);
+            Assert.AreEqual("one", collection.GetOne("a")!.Metadata!["editor"].ToString());
+
+            ex = Assert.ThrowsException<ChromaException>(() => collection.Delete(ids: new[] { "a", "b" }, expectedVersions: new[] { versionA, versionB }));
+            Assert.AreEqual(ChromaErrorCode.ConflictError, ex.ErrorInfo.Code);
+            Assert.AreEqual<uint>(2, collection.Count());
+
+            collection.Delete(ids: new[] { "a", "b" }, expectedVersions: new[] { -1, versionB });
+            Assert.AreEqual<uint>(0, collection.Count());
+        }
+
+        [TestMethod]
+        public void Versions_LetOnlyOneOfTwoConcurrentWritersThrough()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new[] { new ChromaDocument { Id = "a", Text = "shared", Embedding = UnitVector(0) } });
+
+            for (int round = 0; round < 20; round++)
+            {
+                var version = collection.Get(ids: new[] { "a" }, includeVersions: true).Versions[0];
+                using var start = new Barrier(2);
+                var outcomes = new ChromaErrorCode?[2];
+                var writers = Enumerable.Range(0, 2).Select(writer => new Thread(() =>
+                {
+                    start.SignalAndWait();
+                    try
+                    {
+                        collection.Update(
+                            new[] { new ChromaDocument { Id = "a", Metadata = new Dictionary<string, object> { ["writer"] = writer } } },
+                            new[] { version });
+                    }
+                    catch (ChromaException ex)
+                    {
+                        outcomes[writer] = ex.ErrorInfo.Code;
+                    }
+                })).ToList();
+                writers.ForEach(t => t.Start());
+                writers.ForEach(t => t.Join());
+
+                Assert.AreEqual(1, outcomes.Count(o => o == null), $"Round {round}");
+                Assert.AreEqual(1, outcomes.Count(o => o == ChromaErrorCode.ConflictError), $"Round {round}");
+            }
+        }
+
+        [TestMethod]
+        public void SetConsistency_StrongReadsApplyTheLogFirst()
+        {
+            static int ReadBarriers(ChromaClient client)
+            {
+                using var metrics = JsonDocument.Parse(client.GetMetricsJson());
+                return metrics.RootElement.GetProperty("operations").TryGetProperty("read_barrier", out var stats)
+                    ? stats.GetProperty("calls").GetInt32()
+                    : 0;
+            }
+
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            using (var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction))
+            {
+                collection.Add(new ChromaDocument { Id = "a", Text = "first", Embedding = UnitVector(0) });
+                Assert.AreEqual<uint>(1, collection.Count());
+                Assert.AreEqual(0, ReadBarriers(client));
+
+                using (ChromaConsistency.Begin(ConsistencyMode.Strong))
+                {
+                    Assert.AreEqual(ConsistencyMode.Strong, ChromaConsistency.Current);
+                    Assert.AreEqual<uint>(1, collection.Count());
+                }
+                Assert.IsNull(ChromaConsistency.Current);
+                Assert.AreEqual(1, ReadBarriers(client));
+                collection.Count();
+                Assert.AreEqual(1, ReadBarriers(client));
+
+                client.SetConsistency(ConsistencyMode.Strong);
+                collection.Add(new ChromaDocument { Id = "b", Text = "second", Embedding = UnitVector(1) });
+                var beforeGet = ReadBarriers(client);
+                CollectionAssert.AreEquivalent(new[] { "a", "b" }, collection.Get().Ids);
+                Assert.AreEqual(beforeGet + 1, ReadBarriers(client));
+
+                client.SetConsistency(ConsistencyMode.Eventual);
+                collection.Get();
+                Assert.AreEqual(beforeGet + 1, ReadBarriers(client));
+            }
+
+            using var replica = new ChromaClient(_testDir, readOnly: true);
+            var ex = Assert.ThrowsException<ChromaException>(() => replica.SetConsistency(ConsistencyMode.Strong));
+            Assert.AreEqual(ChromaErrorCode.ReadOnly, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void DistributedConfig_IsValidatedBeforeConnecting()
+        {
+            var withStorage = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(persistDirectory: _testDir, distributedConfigJson: "{}"));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, withStorage.ErrorInfo.Code);
+
+            var malformed = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(distributedConfigJson: "not json"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, malformed.ErrorInfo.Code);
+
+            var incomplete = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(distributedConfigJson: "{\"sysdb\": {}}"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, incomplete.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void ObjectStoreConfig_NeedsAPersistDirectoryAndValidConfig()
+        {
+            var withoutDirectory = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(objectStoreConfigJson: "{\"storage\": {}}"));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, withoutDirectory.ErrorInfo.Code);
+
+            var malformed = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(persistDirectory: _testDir, objectStoreConfigJson: "not json"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, malformed.ErrorInfo.Code);
+
+            var unknownKey = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(
+                    persistDirectory: _testDir,
+                    objectStoreConfigJson: "{\"bucket\": \"chroma\"}"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, unknownKey.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void ObjectStore_RestoresTheLastUploadAndDropsStaleObjects()
+        {
+            var store = Path.Combine(_testDir, "store");
+            var config = JsonSerializer.Serialize(new
+            {
+                storage = new { local = new { root = store } },
+                prefix = "service"
+            });
+
+            using (var client = new ChromaClient(persistDirectory: Path.Combine(_testDir, "first"), objectStoreConfigJson: config))
+            {
+                using var collection = client.CreateCollection("mirrored", _embeddingFunction);
+                collection.Add(new[] { new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) } });
+                client.Flush();
+                collection.Add(new[] { new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) } });
+                client.Flush();
+            }
+
+            var prefix = Path.Combine(store, "service");
+            using var manifest = JsonDocument.Parse(File.ReadAllText(Path.Combine(prefix, "manifest.json")));
+            var listed = manifest.RootElement.GetProperty("files").EnumerateObject()
+                .Select(f => f.Value.GetProperty("key").GetString())
+                .ToList();
+            Assert.IsFalse(listed.Any(key => key!.EndsWith("-wal")));
+            var stored = Directory.GetFiles(prefix, "*", SearchOption.AllDirectories)
+                .Select(f => Path.GetRelativePath(store, f).Replace(Path.DirectorySeparatorChar, '/'))
+                .Where(key => key != "service/manifest.json");
+            CollectionAssert.AreEquivalent(listed, stored.ToList());
+
+            using var restored = new ChromaClient(persistDirectory: Path.Combine(_testDir, "second"), objectStoreConfigJson: config);
+            using var copy = restored.GetCollection("mirrored", _embeddingFunction);
+            CollectionAssert.AreEquivalent(new[] { "a", "b" }, copy.GetIds());
+        }
+
+        [TestMethod]
+        public void EncryptionKey_SealsTheDirectoryAndRotates()
+        {
+            var key = Enumerable.Range(0, 32).Select(i => (byte)i).ToArray();
+            var rotated = Enumerable.Range(0, 32).Select(i => (byte)(255 - i)).ToArray();
+
+            using (var client = new ChromaClient(persistDirectory: _testDir, encryptionKey: key))
+            using (var collection = client.CreateCollection("sealed", _embeddingFunction))
+            {
+                collection.Add(new[] { new ChromaDocument { Id = "a", Text = "Sealed", Embedding = UnitVector(0) } });
+                client.Flush();
+            }
+
+            Assert.IsTrue(File.Exists(Path.Combine(_testDir, "chroma.sqlite3.sealed")));
+            Assert.IsFalse(File.Exists(Path.Combine(_testDir, "chroma.sqlite3")));
+
+            var missingKey = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(persistDirectory: _testDir));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, missingKey.ErrorInfo.Code);
+            var wrongKey = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(persistDirectory: _testDir, encryptionKey: rotated));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, wrongKey.ErrorInfo.Code);
+
+            using (var client = new ChromaClient(persistDirectory: _testDir, encryptionKey: key))
+            {
+                using (var collection = client.GetCollection("sealed", _embeddingFunction))
+                    Assert.AreEqual<uint>(1, collection.Count());
+                client.RotateEncryptionKey(rotated);
+            }
+
+            using (var client = new ChromaClient(persistDirectory: _testDir, encryptionKey: rotated))
+            using (var collection = client.GetCollection("sealed", _embeddingFunction))
+                Assert.AreEqual<uint>(1, collection.Count());
+        }
+
+        [TestMethod]
+        public void SealDirectory_SealsPlaintextLeftoversWithoutOpeningAClient()
+        {
+            var key = Enumerable.Range(0, 32).Select(i => (byte)i).ToArray();
+            // A client that was never released leaves the same plaintext files as one without a key
+            using (var client = new ChromaClient(persistDirectory: _testDir, lockOptions: new LockOptions { Mode = LockMode.Exclusive }))
+            using (var collection = client.CreateCollection("leftover", _embeddingFunction))
+            {
+                collection.Add(new[] { new ChromaDocument { Id = "a", Text = "Leftover", Embedding = UnitVector(0) } });
+                client.Flush();
+
+                var open = Assert.ThrowsException<ChromaException>(() => ChromaClient.SealDirectory(_testDir, key));
+                Assert.AreEqual(ChromaErrorCode.ConcurrentAccess, open.ErrorInfo.Code);
+            }
+
+            ChromaClient.SealDirectory(_testDir, key);
+
+            Assert.IsTrue(File.Exists(Path.Combine(_testDir, "chroma.sqlite3.sealed")));
+            var plaintext = Directory.GetFiles(_testDir, "*", SearchOption.AllDirectories)
+                .Where(f => !f.EndsWith(".sealed") && Path.GetFileName(f) is not ("chroma.lock" or "chroma.sqlite3-shm"));
+            Assert.AreEqual(0, plaintext.Count(), string.Join(", ", plaintext));
+
+            // Sealing a sealed directory does nothing
+            ChromaClient.SealDirectory(_testDir, key);
+
+            using (var client = new ChromaClient(persistDirectory: _testDir, encryptionKey: key))
+            using (var collection = client.GetCollection("leftover", _embeddingFunction))
+                Assert.AreEqual<uint>(1, collection.Count());
+        }
+
+        [TestMethod]
+        public void EncryptionKey_LeavesTheDirectorySealedWhenAFileFailsToDecrypt()
+        {
+            var key = Enumerable.Range(0, 32).Select(i => (byte)i).ToArray();
+            using (var client = new ChromaClient(persistDirectory: _testDir, encryptionKey: key))
+            using (var collection = client.CreateCollection("sealed", _embeddingFunction))
+            {
+                collection.Add(new[] { new ChromaDocument { Id = "a", Text = "Sealed", Embedding = UnitVector(0) } });
+                client.Flush();
+            }
+
+            var sealedFiles = Directory.GetFiles(_testDir, "*.sealed", SearchOption.AllDirectories);
+            Assert.IsTrue(sealedFiles.Length > 1);
+            var damaged = sealedFiles.OrderBy(f => f, StringComparer.Ordinal).Last();
+            var bytes = File.ReadAllBytes(damaged);
+            bytes[^1] ^= 0xFF;
+            File.WriteAllBytes(damaged, bytes);
+
+            var ex = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(persistDirectory: _testDir, encryptionKey: key));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+            var plaintext = Directory.GetFiles(_testDir, "*", SearchOption.AllDirectories)
+                .Where(f => !f.EndsWith(".sealed") && Path.GetFileName(f) is not ("chroma.lock" or "chroma.sqlite3-shm"));
+            Assert.AreEqual(0, plaintext.Count(), string.Join(", ", plaintext));
+
+            var mirrored = Assert.ThrowsException<ChromaException>(() => new ChromaClient(
+                persistDirectory: Path.Combine(_testDir, "mirrored"),
+                objectStoreConfigJson: "{\"storage\": {\"local\": {\"root\": \"store\"}}}",
+                encryptionKey: key));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, mirrored.ErrorInfo.Code);
+        }
+
+        private sealed class ReadOnlyRoleAuthorizer : IAuthorizer
+        {
+            public List<(string? Principal, string Operation)> Calls { get; } = new();
+
+            public bool Authorize(string? principalJson, string tenant, string database, string? collection, string operation)
+            {
+                Calls.Add((principalJson, operation));
+                return principalJson == null || !principalJson.Contains("reader") || operation == "query";
+            }
+        }
+
+        [TestMethod]
+        public void Authorizer_DeniesCallsByIdentityOfTheHandle()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollection("guarded", _embeddingFunction);
+            collection.Add(new[] { new ChromaDocument { Id = "a", Text = "Guarded", Embedding = UnitVector(0) } });
+
+            var authorizer = new ReadOnlyRoleAuthorizer();
+            client.SetAuthorizer(authorizer);
+            using var reader = client.Retain();
+            reader.SetIdentity("{\"user\": \"alice\", \"roles\": [\"reader\"]}");
+
+            using var readerCollection = reader.GetCollection("guarded", _embeddingFunction);
+            Assert.AreEqual(1, readerCollection.Query(UnitVector(0), nResults: 1).Ids.Count);
+            var denied = Assert.ThrowsException<ChromaException>(() => readerCollection.Add(
+                new[] { new ChromaDocument { Id = "b", Text = "Denied", Embedding = UnitVector(1) } }));
+            Assert.AreEqual(ChromaErrorCode.PermissionDenied, denied.ErrorInfo.Code);
+            Assert.IsTrue(authorizer.Calls.Any(c => c.Principal?.Contains("alice") == true && c.Operation == "query"));
+            var alias = Assert.ThrowsException<ChromaException>(() => readerCollection.CreateAlias("guarded-alias"));
+            Assert.AreEqual(ChromaErrorCode.PermissionDenied, alias.ErrorInfo.Code);
+
+            // The owner's handle has no identity and keeps full access
+            collection.Add(new[] { new ChromaDocument { Id = "b", Text = "Allowed", Embedding = UnitVector(1) } });
+
+            var invalid = Assert.ThrowsException<ChromaException>(() => reader.SetIdentity("not json"));
+            Assert.AreEqual(ChromaErrorCode.ValidationError, invalid.ErrorInfo.Code);
+
+            client.SetAuthorizer(null);
+            readerCollection.Add(new[] { new ChromaDocument { Id = "c", Text = "Open", Embedding = UnitVector(2) } });
+        }
+
+        [TestMethod]
+        public void AuditLog_RecordsMutationsToFileAndCallback()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            var entries = new List<string>();
+            client.SetAuditLog("audit.jsonl", entries.Add);
+
+            using var collection = client.CreateCollection("audited", _embeddingFunction);
+            collection.Add(new[]
+            {
+                new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) },
+                new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) }
+            });
+            collection.Query(UnitVector(0), nResults: 1);
+            collection.Delete(new[] { "a" });
+
+            var lines = File.ReadAllLines(Path.Combine(_testDir, "audit.jsonl"));
+            CollectionAssert.AreEqual(entries, lines);
+            var operations = lines.Select(l => JsonDocument.Parse(l).RootElement).ToList();
+            CollectionAssert.AreEqual(new[] { "create_collection", "add", "delete" },
+                operations.Select(e => e.GetProperty("operation").GetString()).ToArray());
+            Assert.AreEqual(2, operations[1].GetProperty("records").GetInt32());
+            Assert.AreEqual(1, operations[2].GetProperty("records").GetInt32());
+            Assert.AreEqual(JsonValueKind.Null, operations[1].GetProperty("principal").ValueKind);
+
+            client.SetAuditLog(null);
+            collection.Delete(new[] { "b" });
+            Assert.AreEqual(3, File.ReadAllLines(Path.Combine(_testDir, "audit.jsonl")).Length);
+
+            using var memory = new ChromaClient();
+            var relative = Assert.ThrowsException<ChromaException>(() => memory.SetAuditLog("audit.jsonl"));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, relative.ErrorInfo.Code);
+        }
+
+        private sealed class NoDeleteAuthorizer : IAuthorizer
+        {
+            public bool Authorize(string? principalJson, string tenant, string database, string? collection, string operation)
+            {
+                return operation != "delete";
+            }
+        }
+
+        [TestMethod]
+        public void BatchCommit_IsAuthorizedAndAuditedPerWrite()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new[] { new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) } });
+
+            client.SetAuthorizer(new NoDeleteAuthorizer());
+            using (var denied = client.BeginBatch())
+            {
+                denied.Add(collection, new[] { new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) } });
+                denied.Delete(collection, ids: new[] { "a" });
+                var ex = Assert.ThrowsException<ChromaException>(() => denied.Commit());
+                Assert.AreEqual(ChromaErrorCode.PermissionDenied, ex.ErrorInfo.Code);
+            }
+            CollectionAssert.AreEqual(new[] { "a" }, collection.GetIds());
+            client.SetAuthorizer(null);
+
+            var entries = new List<string>();
+            client.SetAuditLog(null, entries.Add);
+            using (var batch = client.BeginBatch())
+            {
+                batch.Add(collection, new[]
+                {
+                    new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) },
+                    new ChromaDocument { Id = "c", Text = "Third", Embedding = UnitVector(2) }
+                });
+                batch.Delete(collection, ids: new[] { "a" });
+                batch.Commit();
+            }
+            client.SetAuditLog(null);
+
+            var logged = entries.Select(l => JsonDocument.Parse(l).RootElement).ToList();
+            CollectionAssert.AreEqual(new[] { "add", "delete" },
+                logged.Select(e => e.GetProperty("operation").GetString()).ToArray());
+            Assert.AreEqual(2, logged[0].GetProperty("records").GetInt32());
+            Assert.AreEqual(1, logged[1].GetProperty("records").GetInt32());
+        }
+
+        [TestMethod]
+        public void Quotas_RejectCallsOverTheLimitsWithQuotaExceeded()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            client.SetQuotas(new Quotas { MaxCollections = 1, MaxRecordsPerCollection = 2 });
+
+            using var collection = client.CreateCollection("limited", _embeddingFunction);
+            using (client.GetOrCreateCollection("limited", _embeddingFunction)) { }
+            var tooManyCollections = Assert.ThrowsException<ChromaException>(
+                () => client.CreateCollection("second", _embeddingFunction));
+            Assert.AreEqual(ChromaErrorCode.QuotaExceeded, tooManyCollections.ErrorInfo.Code);
+
+            collection.Add(new[]
+            {
+                new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) },
+                new ChromaDocument { Id = "b", Text = "Second", Embedding = UnitVector(1) }
+            });
+            var tooManyRecords = Assert.ThrowsException<ChromaException>(() => collection.Add(
+                new[] { new ChromaDocument { Id = "c", Text = "Third", Embedding = UnitVector(2) } }));
+            Assert.AreEqual(ChromaErrorCode.QuotaExceeded, tooManyRecords.ErrorInfo.Code);
+
+            collection.Delete(new[] { "b" });
+            client.SetQuotas(new Quotas { MaxDimension = 2 });
+            var tooWide = Assert.ThrowsException<ChromaException>(() => collection.Add(
+                new[] { new ChromaDocument { Id = "c", Text = "Wide", Embedding = UnitVector(2) } }));
+            Assert.AreEqual(ChromaErrorCode.QuotaExceeded, tooWide.ErrorInfo.Code);
+
+            // A tenant's own quotas replace the client's
+            client.SetQuotas(new Quotas { MaxWriteOpsPerSecond = 1 }, tenant: "default_tenant");
+            collection.Delete(new[] { "a" });
+            var tooFast = Assert.ThrowsException<ChromaException>(() => collection.Delete(new[] { "a" }));
+            Assert.AreEqual(ChromaErrorCode.QuotaExceeded, tooFast.ErrorInfo.Code);
+
+            client.SetQuotas(null, tenant: "default_tenant");
+            client.SetQuotas(null);
+            collection.Delete(new[] { "a" });
+        }
+
+        [TestMethod]
+        public void ConcurrencyLimits_TurnAwayCallsOverTheLimitWithBusy()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollection("limited", _embeddingFunction);
+            client.SetConcurrencyLimits(new ConcurrencyLimits { MaxWrites = 1 });
+
+            // The audit callback runs while the write still holds its slot
+            using var entered = new ManualResetEventSlim();
+            using var release = new ManualResetEventSlim();
+            client.SetAuditLog(null, entry =>
+            {
+                if (entry.Contains("\"add\""))
+                {
+                    entered.Set();
+                    release.Wait(TimeSpan.FromSeconds(10));
+                }
+            });
+
+            var first = new Thread(() => collection.Add(
+                new[] { new ChromaDocument { Id = "a", Text = "First", Embedding = UnitVector(0) } }));
+            first.Start();
+            Assert.IsTrue(entered.Wait(TimeSpan.FromSeconds(10)));
+
+            var busy = Assert.ThrowsException<ChromaException>(() => collection.Delete(new[] { "a" }));
+            Assert.AreEqual(ChromaErrorCode.Busy, busy.ErrorInfo.Code);
+            // Reads have a limit of their own
+            collection.Query(UnitVector(0), nResults: 1);
+
+            release.Set();
+            first.Join();
+            client.SetConcurrencyLimits(null);
+            collection.Delete(new[] { "a" });
+        }
+
+        private sealed class CountingEmbeddingFunction : IEmbeddingFunction
+        {
+            private readonly TestEmbeddingFunction _inner = new TestEmbeddingFunction();
+
+            public int Embedded { get; private set; }
+
+            public object Configuration => _inner.Configuration;
+
+            public float[][] GenerateEmbeddings(IEnumerable<string> documents)
+            {
+                var texts = documents.ToList();
+                Embedded += texts.Count;
+                return _inner.GenerateEmbeddings(texts);
+            }
+        }
+
+        [TestMethod]
+        public void EmbeddingCache_ReusesEmbeddingsOfUnchangedDocuments()
+        {
+            var counting = new CountingEmbeddingFunction();
+            float[][] first;
+            using (var client = new ChromaClient(persistDirectory: _testDir))
+            {
+                var cached = client.WithEmbeddingCache(counting, "test-model");
+                first = cached.GenerateEmbeddings(new[] { "alpha", "beta" });
+                Assert.AreEqual(2, counting.Embedded);
+            }
+
+            // The cache survives the client
+            using var reopened = new ChromaClient(persistDirectory: _testDir);
+            var again = reopened.WithEmbeddingCache(counting, "test-model")
+                .GenerateEmbeddings(new[] { "beta", "gamma", "alpha" });
+            Assert.AreEqual(3, counting.Embedded);
+            CollectionAssert.AreEqual(first[1], again[0]);
+            CollectionAssert.AreEqual(first[0], again[2]);
+
+            // Another model does not share the cached embeddings
+            reopened.WithEmbeddingCache(counting, "other-model").GenerateEmbeddings(new[] { "alpha" });
+            Assert.AreEqual(4, counting.Embedded);
+
+            reopened.ClearEmbeddingCache("test-model");
+            reopened.WithEmbeddingCache(counting, "test-model").GenerateEmbeddings(new[] { "alpha" });
+            Assert.AreEqual(5, counting.Embedded);
+        }
     }
 }
@@ -198,5 +198,336 @@ namespace ChromaDB.NET.Tests
             Assert.IsTrue(results.Ids.Contains("doc2"));
             Assert.IsFalse(results.Ids.Contains("doc3"));
         }
+
+        [TestMethod]
+        public void SetRetryPolicy_OperationsStillSucceed()
+        {
+            using var client = new ChromaClient();
+            client.SetRetryPolicy(new RetryPolicy { MaxAttempts = 4, BaseDelay = TimeSpan.FromMilliseconds(10) });
+
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "retried" });
+
+            Assert.AreEqual(1, collection.Count());
+
+            client.SetRetryPolicy(RetryPolicy.None);
+            Assert.AreEqual(1, collection.Count());
+        }
+
+        [TestMethod]
+        public void SetRetryPolicy_InvalidJitter_Throws()
+        {
+            using var client = new ChromaClient();
+
+            var ex = Assert.ThrowsException<ChromaException>(() => client.SetRetryPolicy(new RetryPolicy { Jitter = 1.5 }));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void Metrics_TrackOperationsAndReset()
+        {
+            using var client = new ChromaClient();
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "first" });
+            collection.Add(new ChromaDocument { Id = "doc2", Text = "second" });
+            collection.Count();
+
+            using (var metrics = System.Text.Json.JsonDocument.Parse(client.GetMetricsJson()))
+            {
+                var root = metrics.RootElement;
+                var add = root.GetProperty("operations").GetProperty("add");
+                Assert.AreEqual(2, add.GetProperty("calls").GetInt32());
+                Assert.AreEqual(0, add.GetProperty("errors").GetInt32());
+                Assert.IsTrue(add.GetProperty("p95_ms").GetDouble() >= add.GetProperty("p50_ms").GetDouble());
+                Assert.AreEqual(1, root.GetProperty("operations").GetProperty("count").GetProperty("calls").GetInt32());
+                Assert.AreEqual(2, root.GetProperty("records_added").GetInt32());
+            }
+
+            client.ResetMetrics();
+
+            using (var metrics = System.Text.Json.JsonDocument.Parse(client.GetMetricsJson()))
+            {
+                var root = metrics.RootElement;
+                Assert.AreEqual(0, root.GetProperty("operations").EnumerateObject().Count());
+                Assert.AreEqual(0, root.GetProperty("records_added").GetInt32());
+            }
+        }
+
+        [TestMethod]
+        public void EffectiveConfig_ReportsResolvedSettings()
+        {
+            var persistDirectory = Path.Combine(Path.GetTempPath(), "chroma-config-" + Guid.NewGuid().ToString("N"));
+            Directory.CreateDirectory(persistDirectory);
+            try
+            {
+                using (var client = new ChromaClient(persistDirectory, hnswCacheSize: 250))
+                using (var config = System.Text.Json.JsonDocument.Parse(client.GetEffectiveConfigJson()))
+                {
+                    var root = config.RootElement;
+                    Assert.AreEqual(persistDirectory, root.GetProperty("persist_path").GetString());
+                    Assert.AreEqual(250, root.GetProperty("hnsw_cache").GetProperty("capacity").GetInt32());
+                    StringAssert.EndsWith(root.GetProperty("sqlite").GetProperty("url").GetString(), "chroma.sqlite3");
+                }
+            }
+            finally
+            {
+                Directory.Delete(persistDirectory, true);
+            }
+        }
+
+        [TestMethod]
+        public void DiskCache_ConfiguresHybridHnswCache()
+        {
+            var cacheDirectory = Path.Combine(Path.GetTempPath(), "chroma-cache-" + Guid.NewGuid().ToString("N"));
+            try
+            {
+                var diskCache = new DiskCacheOptions { Directory = cacheDirectory, DiskSizeMb = 64, MemorySizeMb = 16 };
+                using var client = new ChromaClient(hnswCacheSize: 10, diskCache: diskCache);
+                using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+                collection.Add("doc1", "Cached document");
+                Assert.AreEqual(1u, collection.Count());
+
+                using var config = System.Text.Json.JsonDocument.Parse(client.GetEffectiveConfigJson());
+                var hnswCache = config.RootElement.GetProperty("hnsw_cache");
+                Assert.AreEqual("disk", hnswCache.GetProperty("type").GetString());
+                Assert.AreEqual(64, hnswCache.GetProperty("disk_mib").GetInt32());
+                Assert.IsTrue(Directory.Exists(cacheDirectory));
+            }
+            finally
+            {
+                if (Directory.Exists(cacheDirectory))
+                    Directory.Delete(cacheDirectory, true);
+            }
+        }
+
+        [TestMethod]
+        public void CollectionCache_IsReportedInEffectiveConfig()
+        {
+            var collectionCache = new CollectionCacheOptions { Capacity = 100, Ttl = TimeSpan.FromSeconds(5), MaxParallelism = 4 };
+            using var client = new ChromaClient(collectionCache: collectionCache);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("doc1", "Cached lookup");
+            Assert.AreEqual(1u, collection.Count());
+
+            using var config = System.Text.Json.JsonDocument.Parse(client.GetEffectiveConfigJson());
+            var cache = config.RootElement.GetProperty("collection_cache");
+            Assert.AreEqual("memory", cache.GetProperty("type").GetString());
+            Assert.AreEqual(100, cache.GetProperty("capacity").GetInt32());
+            Assert.AreEqual(5, cache.GetProperty("ttl_secs").GetInt32());
+            Assert.AreEqual(4, cache.GetProperty("permitted_parallelism").GetInt32());
+        }
+
+        [TestMethod]
+        public void StorageInfo_ReportsPersistDirectoryUsage()
+        {
+            var persistDirectory = Path.Combine(Path.GetTempPath(), "chroma-storage-" + Guid.NewGuid().ToString("N"));
+            Directory.CreateDirectory(persistDirectory);
+            try
+            {
+                using (var client = new ChromaClient(persistDirectory))
+                {
+                    using (var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction))
+                        collection.Add("doc1", "Stored document");
+
+                    using var info = System.Text.Json.JsonDocument.Parse(client.GetStorageInfoJson());
+                    var root = info.RootElement;
+                    Assert.AreEqual(persistDirectory, root.GetProperty("persist_path").GetString());
+                    Assert.IsTrue(root.GetProperty("sqlite_bytes").GetInt64() > 0);
+                    Assert.IsTrue(root.GetProperty("total_bytes").GetInt64() >= root.GetProperty("sqlite_bytes").GetInt64());
+                }
+            }
+            finally
+            {
+                Directory.Delete(persistDirectory, true);
+            }
+        }
+
+        [TestMethod]
+        public void GetCollectionById_ReattachesToSameCollection()
+        {
+            using var client = new ChromaClient();
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add(new ChromaDocument { Id = "doc1", Text = "first" });
+
+            using var reattached = client.GetCollectionById(collection.Id, _embeddingFunction);
+            Assert.AreEqual(collection.Id, reattached.Id);
+            Assert.AreEqual(1u, reattached.Count());
+
+            var invalid = Assert.ThrowsException<ChromaException>(() => client.GetCollectionById("not-a-uuid"));
+            Assert.AreEqual(ChromaErrorCode.InvalidUuid, invalid.ErrorInfo.Code);
+
+            var missing = Assert.ThrowsException<ChromaException>(() => client.GetCollectionById(Guid.NewGuid().ToString()));
+            Assert.AreEqual(ChromaErrorCode.NotFound, missing.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void GetCollections_ReturnsPerNameResults()
+        {
+            using var client = new ChromaClient();
+            var suffix = Guid.NewGuid().ToString("N").Substring(0, 8);
+            using var first = client.CreateCollection($"multi-a-{suffix}", _embeddingFunction);
+            using var second = client.CreateCollection($"multi-b-{suffix}", _embeddingFunction);
+            first.Add("doc1", "In the first collection");
+
+            var lookups = client.GetCollections(new[] { $"multi-a-{suffix}", $"missing-{suffix}", $"multi-b-{suffix}" }, _embeddingFunction);
+
+            Assert.AreEqual(3, lookups.Count);
+            using var firstLookup = lookups[0].Collection;
+            using var secondLookup = lookups[2].Collection;
+            Assert.AreEqual(first.Id, firstLookup.Id);
+            Assert.AreEqual(1u, firstLookup.Count());
+            Assert.AreEqual(second.Id, secondLookup.Id);
+            Assert.IsNull(lookups[1].Collection);
+            Assert.AreEqual(ChromaErrorCode.NotFound, lookups[1].Error.Code);
+        }
+
+        [TestMethod]
+        public void ReadOnlyClient_ReadsButRejectsMutations()
+        {
+            var name = $"readonly-{Guid.NewGuid():N}".Substring(0, 20);
+            using (var writer = new ChromaClient(_testDir))
+            using (var collection = writer.CreateCollection(name, _embeddingFunction))
+                collection.Add("doc1", "Written before the replica opened");
+
+            using var client = new ChromaClient(_testDir, readOnly: true);
+            using var replica = client.GetCollection(name, _embeddingFunction);
+            Assert.AreEqual(1u, replica.Count());
+            Assert.AreEqual(1, replica.Query("replica", nResults: 1).Ids.Count);
+
+            var addError = Assert.ThrowsException<ChromaException>(() => replica.Add("doc2", "Rejected"));
+            Assert.AreEqual(ChromaErrorCode.ReadOnly, addError.ErrorInfo.Code);
+            var createError = Assert.ThrowsException<ChromaException>(() => client.CreateCollection(name + "-new", _embeddingFunction));
+            Assert.AreEqual(ChromaErrorCode.ReadOnly, createError.ErrorInfo.Code);
+            Assert.AreEqual(1u, replica.Count());
+        }
+
+        [TestMethod]
+        public void ReadOnlyClient_LeavesPersistDirectoryUnchanged()
+        {
+            var name = $"readonly-{Guid.NewGuid():N}".Substring(0, 20);
+            using (var writer = new ChromaClient(_testDir))
+            using (var collection = writer.CreateCollection(name, _embeddingFunction))
+            {
+                collection.Add("doc1", "Written before the replica opened");
+                collection.Add("doc2", "Also written before the replica opened");
+            }
+
+            // The lock file and SQLite's shared-memory index change on every open; data files must not
+            Dictionary<string, string> Snapshot() => Directory
+                .EnumerateFiles(_testDir, "*", SearchOption.AllDirectories)
+                .Where(path => !path.EndsWith("chroma.lock") && !path.EndsWith("-shm") && new FileInfo(path).Length > 0)
+                .ToDictionary(
+                    path => Path.GetRelativePath(_testDir, path),
+                    path => Convert.ToHexString(System.Security.Cryptography.SHA256.HashData(File.ReadAllBytes(path))));
+            var before = Snapshot();
+
+            using (var client = new ChromaClient(_testDir, readOnly: true))
+            using (var replica = client.GetCollection(name, _embeddingFunction))
+            {
+                Assert.AreEqual(2u, replica.Count());
+                Assert.AreEqual(2, replica.Query("replica", nResults: 2).Ids.Count);
+                Assert.AreEqual(1, replica.Get(ids: new[] { "doc1" }).Ids.Count);
+            }
+
+            CollectionAssert.AreEquivalent(before.ToList(), Snapshot().ToList());
+        }
+
+        [TestMethod]
+        public void ReadOnlyClient_RequiresExistingDatabase()
+        {
+            var error = Assert.ThrowsException<ChromaException>(() => new ChromaClient(_testDir, readOnly: true));
+            Assert.AreEqual(ChromaErrorCode.NotFound, error.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void ExclusiveLock_RejectsSecondClientUntilReleased()
+        {
+            var lockOptions = new LockOptions { Mode = LockMode.Exclusive };
+            using (var first = new ChromaClient(_testDir, lockOptions: lockOptions))
+            {
+                var error = Assert.ThrowsException<ChromaException>(
+                    () => new ChromaClient(_testDir, lockOptions: new LockOptions { Mode = LockMode.SharedRead, Wait = TimeSpan.FromMilliseconds(50) }));
+                Assert.AreEqual(ChromaErrorCode.ConcurrentAccess, error.ErrorInfo.Code);
+            }
+
+            using var second = new ChromaClient(_testDir, lockOptions: lockOptions);
+            second.Heartbeat();
+        }
+
+        [TestMethod]
+        public void SharedReadLock_AllowsSeveralClients()
+        {
+            var lockOptions = new LockOptions { Mode = LockMode.SharedRead };
+            using var first = new ChromaClient(_testDir, lockOptions: lockOptions);
+            using var second = new ChromaClient(_testDir, lockOptions: lockOptions);
+
+            var error = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(_testDir, lockOptions: new LockOptions { Mode = LockMode.Exclusive }));
+            Assert.AreEqual(ChromaErrorCode.ConcurrentAccess, error.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void SqliteOptions_AreAppliedAndReported()
+        {
+            var sqlite = new SqliteOptions
+            {
+                JournalMode = SqliteJournalMode.Wal,
+                Synchronous = SqliteSynchronous.Normal,
+                BusyTimeout = TimeSpan.FromSeconds(5),
+                CacheSizeKb = 8192
+            };
+            using var client = new ChromaClient(_testDir, sqlite: sqlite);
+            using var collection = client.CreateCollectionWithUniqueName(embeddingFunction: _embeddingFunction);
+            collection.Add("doc1", "Written through a tuned connection");
+            Assert.AreEqual(1u, collection.Count());
+
+            using var config = System.Text.Json.JsonDocument.Parse(client.GetEffectiveConfigJson());
+            var pragmas = config.RootElement.GetProperty("sqlite").GetProperty("pragmas");
+            Assert.AreEqual("WAL", pragmas.GetProperty("journal_mode").GetString());
+            Assert.AreEqual("NORMAL", pragmas.GetProperty("synchronous").GetString());
+            Assert.AreEqual(5000, pragmas.GetProperty("busy_timeout_ms").GetInt32());
+            Assert.AreEqual(8192, pragmas.GetProperty("cache_size_kib").GetInt32());
+        }
+
+        [TestMethod]
+        public void HealthReport_PassesForWritablePersistDirectory()
+        {
+            using var client = new ChromaClient(_testDir);
+
+            using var report = System.Text.Json.JsonDocument.Parse(client.GetHealthReportJson());
+            var root = report.RootElement;
+            Assert.AreNotEqual("fail", root.GetProperty("status").GetString());
+            var checks = root.GetProperty("checks").EnumerateArray()
+                .ToDictionary(c => c.GetProperty("name").GetString(), c => c.GetProperty("status").GetString());
+            Assert.AreEqual("pass", checks["sqlite"]);
+            Assert.AreEqual("pass", checks["persist_path"]);
+            Assert.IsTrue(checks.ContainsKey("cache"));
+            Assert.IsTrue(checks.ContainsKey("executor"));
+        }
+
+        [TestMethod]
+        public void MigrationStatus_ReportsMigratedDatabase()
+        {
+            using (var missing = System.Text.Json.JsonDocument.Parse(ChromaClient.GetMigrationStatusJson(_testDir)))
+            {
+                Assert.IsFalse(missing.RootElement.GetProperty("exists").GetBoolean());
+                Assert.IsFalse(missing.RootElement.GetProperty("up_to_date").GetBoolean());
+            }
+
+            var error = Assert.ThrowsException<ChromaException>(
+                () => new ChromaClient(_testDir, sqlite: new SqliteOptions { ValidateMigrations = true }));
+            Assert.AreEqual(ChromaErrorCode.MigrationsPending, error.ErrorInfo.Code);
+
+            using (new ChromaClient(_testDir))
+            {
+            }
+
+            using var status = System.Text.Json.JsonDocument.Parse(ChromaClient.GetMigrationStatusJson(_testDir));
+            Assert.IsTrue(status.RootElement.GetProperty("up_to_date").GetBoolean());
+            Assert.IsTrue(status.RootElement.GetProperty("applied_count").GetInt32() > 0);
+
+            using var validated = new ChromaClient(_testDir, sqlite: new SqliteOptions { ValidateMigrations = true });
+            validated.Heartbeat();
+        }
     }
 }
\ No newline at end of file
@@ -183,5 +183,36 @@ namespace ChromaDB.NET.Tests
                     Assert.Fail($"Task {i} threw: {errors[i]}");
             }
         }
+
+        [TestMethod]
+        public void DestroyedHandle_IsRejectedByNativeLibrary()
+        {
+            var client = new ChromaClient(persistDirectory: _testDir);
+            var staleHandle = client.GetHandleOrThrow();
+            client.Dispose();
+
+            var result = NativeMethods.chroma_heartbeat(staleHandle, out _, out var errorPtr);
+
+            Assert.AreEqual((int)ChromaErrorCode.InvalidArgument, result);
+            NativeMethods.chroma_free_error(errorPtr);
+
+            result = NativeMethods.chroma_destroy_client(staleHandle, out errorPtr);
+
+            Assert.AreEqual((int)ChromaErrorCode.InvalidArgument, result);
+            NativeMethods.chroma_free_error(errorPtr);
+        }
+
+        [TestMethod]
+        public void ListHandles_IncludesLiveClientUntilDisposed()
+        {
+            var client = new ChromaClient(persistDirectory: _testDir);
+            var handle = client.GetHandleOrThrow();
+
+            CollectionAssert.Contains(ChromaClient.ListHandles(0), handle);
+
+            client.Dispose();
+
+            CollectionAssert.DoesNotContain(ChromaClient.ListHandles(0), handle);
+        }
     }
 }
@@ -118,6 +118,169 @@ namespace ChromaDB.NET.Tests
             Assert.IsTrue(results.Distances.Count > 0, "Expected distances to be returned");
         }
 
+        [TestMethod]
+        public void Collection_QueryWithAllowedIds_RestrictsCandidates()
+        {
+            // Arrange
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f }));
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc2", new float[] { 0.0f, 1.0f, 0.0f }));
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc3", new float[] { 0.0f, 0.0f, 1.0f }));
+
+            // Act - the closest document is excluded from the allowed set
+            var results = collection.Query(
+                queryEmbedding: new float[] { 1.0f, 0.0f, 0.0f },
+                nResults: 3,
+                allowedIds: new[] { "doc2", "doc3" });
+
+            // Assert
+            Assert.AreEqual(2, results.Ids.Count);
+            CollectionAssert.DoesNotContain(results.Ids, "doc1");
+        }
+
+        [TestMethod]
+        public void Collection_QueryWithEmptyAllowedIds_ReturnsNoResults()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f }));
+
+            var results = collection.Query(new float[] { 1.0f, 0.0f, 0.0f }, allowedIds: Array.Empty<string>());
+
+            Assert.AreEqual(0, results.Count);
+        }
+
+        [TestMethod]
+        [DataRow(SerializationFormat.MessagePack, 0x80, 0x8F)]
+        [DataRow(SerializationFormat.Cbor, 0xA0, 0xBF)]
+        public void Collection_QuerySerialized_ReturnsEncodedMap(SerializationFormat format, int minHeader, int maxHeader)
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f }));
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc2", new float[] { 0.0f, 1.0f, 0.0f }));
+
+            var bytes = collection.QuerySerialized(
+                new[] { new float[] { 1.0f, 0.0f, 0.0f }, new float[] { 0.0f, 1.0f, 0.0f } },
+                nResults: 1,
+                format: format);
+
+            // The response is a single top-level map
+            Assert.IsTrue(bytes.Length > 0);
+            Assert.IsTrue(bytes[0] >= minHeader && bytes[0] <= maxHeader, $"Unexpected header byte 0x{bytes[0]:X2}");
+        }
+
+        [TestMethod]
+        public void Collection_QuerySerialized_MismatchedDimensions_ThrowsException()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            Assert.ThrowsException<ArgumentException>(() => collection.QuerySerialized(
+                new[] { new float[] { 1.0f, 0.0f }, new float[] { 1.0f } }));
+        }
+
+        [TestMethod]
+        public void Collection_GetSerialized_ArrowIpc_ReturnsStream()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f }));
+
+            var bytes = collection.GetSerialized(SerializationFormat.ArrowIpc, includeEmbeddings: true);
+
+            // Arrow IPC streams open with the 0xFFFFFFFF continuation marker
+            Assert.IsTrue(bytes.Length > 8);
+            CollectionAssert.AreEqual(new byte[] { 0xFF, 0xFF, 0xFF, 0xFF }, bytes.Take(4).ToArray());
+        }
+
+        [TestMethod]
+        public void Collection_AddArrow_RoundTripsArrowGetOutput()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var source = client.CreateCollectionWithUniqueName();
+            using var target = client.CreateCollectionWithUniqueName();
+
+            var doc1 = ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f });
+            doc1.Text = "first";
+            doc1.Metadata = new Dictionary<string, object> { ["rank"] = 1 };
+            source.Add(doc1);
+            source.Add(ChromaDocument.CreateWithEmbedding("doc2", new float[] { 0.0f, 1.0f, 0.0f }));
+
+            var stream = source.GetSerialized(SerializationFormat.ArrowIpc, includeEmbeddings: true);
+            target.AddArrow(stream);
+
+            Assert.AreEqual(2u, target.Count());
+            var copied = target.Get(ids: new[] { "doc1" });
+            Assert.AreEqual("first", copied.Documents[0]);
+            Assert.IsTrue(copied.Metadatas[0].ContainsKey("rank"));
+        }
+
+        [TestMethod]
+        public void Collection_AddArrow_InvalidStream_ThrowsException()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            Assert.ThrowsException<ChromaException>(() => collection.AddArrow(new byte[] { 1, 2, 3, 4 }));
+        }
+
+        [TestMethod]
+        public void Collection_AddEmbeddings_Half_QueryWithDouble_FindsDocument()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            var embeddings = new Half[]
+            {
+                (Half)1.0f, (Half)0.0f, (Half)0.0f,
+                (Half)0.0f, (Half)1.0f, (Half)0.0f
+            };
+            collection.AddEmbeddings(new[] { "doc1", "doc2" }, embeddings, dimension: 3,
+                documents: new[] { "first", "second" });
+
+            var results = collection.Query(new double[] { 0.0, 1.0, 0.0 }, nResults: 1);
+
+            Assert.AreEqual(2u, collection.Count());
+            Assert.AreEqual("doc2", results.Ids[0]);
+            Assert.AreEqual("second", results.Documents[0]);
+        }
+
+        [TestMethod]
+        public void Collection_AddEmbeddings_WithoutIds_ReturnsGeneratedIds()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            var embeddings = new double[]
+            {
+                1.0, 0.0, 0.0,
+                0.0, 1.0, 0.0
+            };
+            var ids = collection.AddEmbeddings(null, embeddings, dimension: 3,
+                documents: new[] { "first", "second" });
+
+            Assert.AreEqual(2, ids.Count);
+            Assert.AreNotEqual(ids[0], ids[1]);
+            Assert.IsTrue(ids.All(id => Guid.TryParse(id, out _)));
+
+            var results = collection.Query(new double[] { 0.0, 1.0, 0.0 }, nResults: 1);
+            Assert.AreEqual(ids[1], results.Ids[0]);
+            Assert.AreEqual("second", results.Documents[0]);
+        }
+
+        [TestMethod]
+        public void Collection_AddEmbeddings_WrongLength_ThrowsException()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+
+            Assert.ThrowsException<ArgumentException>(() =>
+                collection.AddEmbeddings(new[] { "doc1", "doc2" }, new double[] { 1.0, 0.0, 0.0 }, dimension: 3));
+        }
+
         [TestMethod]
         public void EmbeddingFunction_GeneratesConsistentEmbeddings()
         {
@@ -209,6 +372,45 @@ namespace ChromaDB.NET.Tests
                 new Dictionary<string, object>()
             ));
         }
+
+        [TestMethod]
+        public void Collection_WrongEmbeddingDimension_ThrowsDimensionMismatch()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = client.CreateCollectionWithUniqueName();
+            collection.Add(ChromaDocument.CreateWithEmbedding("doc1", new float[] { 1.0f, 0.0f, 0.0f, 0.0f }));
+
+            var queryError = Assert.ThrowsException<ChromaException>(
+                () => collection.Query(queryEmbedding: new float[] { 1.0f, 0.0f }));
+            Assert.AreEqual(ChromaErrorCode.DimensionMismatch, queryError.ErrorInfo.Code);
+            StringAssert.Contains(queryError.ErrorInfo.Details, "Expected 4 dimensions, got 2");
+
+            var addError = Assert.ThrowsException<ChromaException>(
+                () => collection.Add(ChromaDocument.CreateWithEmbedding("doc2", new float[] { 1.0f, 0.0f, 0.0f })));
+            Assert.AreEqual(ChromaErrorCode.DimensionMismatch, addError.ErrorInfo.Code);
+            Assert.AreEqual(1u, collection.Count());
+        }
+
+        [TestMethod]
+        public void Client_QueryCollections_QueriesEachCollectionAndMerges()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var first = client.CreateCollectionWithUniqueName();
+            using var second = client.CreateCollectionWithUniqueName();
+            first.Add(ChromaDocument.CreateWithEmbedding("a1", new float[] { 1.0f, 0.0f, 0.0f }));
+            first.Add(ChromaDocument.CreateWithEmbedding("a2", new float[] { 0.0f, 0.0f, 1.0f }));
+            second.Add(ChromaDocument.CreateWithEmbedding("b1", new float[] { 0.9f, 0.1f, 0.0f }));
+            var queryEmbedding = new float[] { 1.0f, 0.0f, 0.0f };
+
+            var perCollection = client.QueryCollections(new[] { first, second }, queryEmbedding, nResults: 1);
+            Assert.AreEqual(2, perCollection.Count);
+            CollectionAssert.AreEqual(new[] { "a1" }, perCollection[0].Ids);
+            CollectionAssert.AreEqual(new[] { "b1" }, perCollection[1].Ids);
+
+            var merged = client.QueryCollectionsMerged(new[] { first, second }, queryEmbedding, nResults: 2);
+            CollectionAssert.AreEqual(new[] { "a1", "b1" }, merged.Ids);
+            Assert.IsTrue(merged.Distances[0] <= merged.Distances[1]);
+        }
     }
 
     /// <summary>
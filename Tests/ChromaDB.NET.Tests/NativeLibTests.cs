@@ -176,5 +176,99 @@ namespace ChromaDB.NET.Tests
                 Console.WriteLine($"Stack trace: {ex.StackTrace}");
             }
         }
+
+        [TestMethod]
+        public void NativeAllocator_MallocAndFree_RoundTrips()
+        {
+            var ptr = NativeMethods.chroma_malloc((UIntPtr)sizeof(long));
+            Assert.AreNotEqual(IntPtr.Zero, ptr);
+
+            Marshal.WriteInt64(ptr, 42);
+            Assert.AreEqual(42, Marshal.ReadInt64(ptr));
+
+            NativeMethods.chroma_free(ptr);
+            NativeMethods.chroma_free(IntPtr.Zero);
+            Assert.AreEqual(IntPtr.Zero, NativeMethods.chroma_malloc(UIntPtr.Zero));
+        }
+
+        // chroma_debug_panic is only exported by native builds with the debug-exports feature
+        private static int DebugPanic(out IntPtr errorPtr)
+        {
+            try
+            {
+                return NativeMethods.chroma_debug_panic(out errorPtr);
+            }
+            catch (EntryPointNotFoundException)
+            {
+                Assert.Inconclusive("The native library was built without the debug-exports feature");
+                throw;
+            }
+        }
+
+        [TestMethod]
+        public void NativePanic_IsReportedAsInternalError()
+        {
+            var result = DebugPanic(out var errorPtr);
+
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.CheckError(result, errorPtr));
+            Assert.AreEqual(ChromaErrorCode.InternalError, ex.ErrorInfo.Code);
+            Assert.AreEqual("chroma_debug_panic", ex.ErrorInfo.Source);
+            StringAssert.Contains(ex.ErrorInfo.Details, "Forced panic");
+
+            // The host process survives and the library stays usable
+            var ptr = NativeMethods.chroma_malloc((UIntPtr)1);
+            Assert.AreNotEqual(IntPtr.Zero, ptr);
+            NativeMethods.chroma_free(ptr);
+        }
+
+        [TestMethod]
+        public void LastError_TracksMostRecentCallOnThread()
+        {
+            DebugPanic(out var errorPtr);
+            NativeMethods.chroma_free_error(errorPtr);
+
+            var (code, message) = ChromaClient.GetLastError();
+            Assert.AreEqual(ChromaErrorCode.InternalError, code);
+            StringAssert.Contains(message, "chroma_debug_panic");
+            StringAssert.Contains(message, "Forced panic");
+
+            var result = NativeMethods.chroma_list_handles(0, out var handles, out _, out errorPtr);
+            Assert.AreEqual(0, result);
+            NativeMethods.chroma_free(handles);
+
+            (code, message) = ChromaClient.GetLastError();
+            Assert.AreEqual(ChromaErrorCode.Success, code);
+            Assert.AreEqual(string.Empty, message);
+        }
+
+        [TestMethod]
+        public void SuccessfulCall_DoesNotAllocateError()
+        {
+            using var client = new ChromaClient();
+
+            for (int i = 0; i < 100; i++)
+            {
+                var result = NativeMethods.chroma_heartbeat(client.GetHandleOrThrow(), out _, out var errorPtr);
+                Assert.AreEqual(0, result);
+                Assert.AreEqual(IntPtr.Zero, errorPtr);
+            }
+        }
+
+        [TestMethod]
+        public void ErrorJson_ContainsStructuredFields()
+        {
+            var result = DebugPanic(out var errorPtr);
+            var ex = Assert.ThrowsException<ChromaException>(() => ChromaClient.CheckError(result, errorPtr));
+
+            using var json = System.Text.Json.JsonDocument.Parse(ex.ErrorInfo.Json);
+            var root = json.RootElement;
+            Assert.AreEqual((int)ChromaErrorCode.InternalError, root.GetProperty("code").GetInt32());
+            Assert.AreEqual("InternalError", root.GetProperty("name").GetString());
+            Assert.AreEqual("chroma_debug_panic", root.GetProperty("source").GetString());
+            StringAssert.Contains(root.GetProperty("details").GetString(), "Forced panic");
+            Assert.IsFalse(root.GetProperty("retryable").GetBoolean());
+            Assert.AreEqual(System.Text.Json.JsonValueKind.Array, root.GetProperty("chain").ValueKind);
+            Assert.IsFalse(ex.ErrorInfo.Retryable);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,116 @@
+using System;
+using System.Collections.Generic;
+using System.IO;
+using System.Linq;
+using System.Threading;
+using Microsoft.VisualStudio.TestTools.UnitTesting;
+
+namespace ChromaDB.NET.Tests
+{
+    [TestClass]
+    public class SearchTests
+    {
+        private string _testDir = string.Empty;
+        private TestEmbeddingFunction _embeddingFunction = new TestEmbeddingFunction();
+
+        [TestInitialize]
+        public void Initialize()
+        {
+            _testDir = Path.Combine(Path.GetTempPath(), "chromadb-dotnet-tests", Guid.NewGuid().ToString());
+            Directory.CreateDirectory(_testDir);
+            _embeddingFunction = new TestEmbeddingFunction();
+        }
+
+        [TestCleanup]
+        public void Cleanup()
+        {
+            try
+            {
+                if (Directory.Exists(_testDir))
+                {
+                    Thread.Sleep(100);
+                    Directory.Delete(_testDir, true);
+                }
+            }
+            catch (Exception ex)
+            {
+                Console.WriteLine($"Error cleaning up test directory '{_testDir}': {ex.Message}");
+            }
+        }
+
+        private Collection CreateSearchCollection(ChromaClient client)
+        {
+            var collection = client.CreateCollectionWithUniqueName("search", _embeddingFunction);
+            collection.Add(new[]
+            {
+                ChromaDocument.Create("doc1", "the quick brown fox jumps over the lazy dog", new Dictionary<string, object> { ["source"] = "a" }),
+                ChromaDocument.Create("doc2", "a fox and another fox met a fox", new Dictionary<string, object> { ["source"] = "b" }),
+                ChromaDocument.Create("doc3", "nothing relevant in this sentence", new Dictionary<string, object> { ["source"] = "a" })
+            });
+            return collection;
+        }
+
+        [TestMethod]
+        public void KeywordSearch_RanksByTermFrequency()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = CreateSearchCollection(client);
+
+            var results = collection.KeywordSearch("fox", nResults: 5);
+
+            Assert.AreEqual(2, results.Count);
+            Assert.AreEqual("doc2", results.Ids[0]);
+            Assert.AreEqual(results.Ids.Count, results.Scores.Count);
+            Assert.IsTrue(results.Scores[0] >= results.Scores[1]);
+            Assert.IsFalse(results.Ids.Contains("doc3"));
+        }
+
+        [TestMethod]
+        public void KeywordSearch_WithWhereFilter_RestrictsCandidates()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = CreateSearchCollection(client);
+
+            var results = collection.KeywordSearch("fox", whereFilter: new Dictionary<string, object> { ["source"] = "a" });
+
+            Assert.AreEqual(1, results.Count);
+            Assert.AreEqual("doc1", results.Ids[0]);
+        }
+
+        [TestMethod]
+        public void KeywordSearch_NoSearchableTerms_ThrowsInvalidArgument()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = CreateSearchCollection(client);
+
+            var ex = Assert.ThrowsException<ChromaException>(() => collection.KeywordSearch("  !! "));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+        }
+
+        [TestMethod]
+        public void HybridSearch_ReturnsFusedScoresAndBothColumns()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = CreateSearchCollection(client);
+
+            var results = collection.HybridSearch("fox", nResults: 3);
+
+            Assert.IsTrue(results.Count > 0);
+            Assert.AreEqual(results.Ids.Count, results.Scores.Count);
+            Assert.AreEqual(results.Ids.Count, results.Distances.Count);
+            Assert.AreEqual(results.Ids.Count, results.TextScores.Count);
+            CollectionAssert.AreEqual(results.Scores.OrderByDescending(s => s).ToList(), results.Scores);
+        }
+
+        [TestMethod]
+        public void HybridSearch_WeightedFusion_InvalidWeight_ThrowsInvalidArgument()
+        {
+            using var client = new ChromaClient(persistDirectory: _testDir);
+            using var collection = CreateSearchCollection(client);
+
+            var ex = Assert.ThrowsException<ChromaException>(() =>
+                collection.HybridSearch("fox", fusion: FusionMethod.Weighted, vectorWeight: 1.5f));
+            Assert.AreEqual(ChromaErrorCode.InvalidArgument, ex.ErrorInfo.Code);
+        }
+    }
+}
@@ -16,6 +16,11 @@ if ($IsWindows -or $env:OS -match "Windows") {
     cargo build --release
     Copy-Item "$ScriptDir\target\release\chroma_csharp.dll" -Destination "$OutputDir\win-x64\native\" -Force
 
+    # Refresh the checked-in C header from the one cbindgen generated for this build
+    $Header = Get-ChildItem "$ScriptDir\target\release\build\chromadb-dotnet-bindings-*\out\chroma_api.h" |
+        Sort-Object LastWriteTime -Descending | Select-Object -First 1
+    Copy-Item $Header.FullName -Destination "$ScriptDir\chroma_api.h" -Force
+
     $targets = rustup target list --installed
 
     # Cross-compile for Linux (if target installed)
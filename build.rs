@@ -0,0 +1,52 @@
+// Generates the C header of the exported API with cbindgen
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=chroma_api.h");
+    println!("cargo:rerun-if-env-changed=CHROMA_REQUIRE_FRESH_HEADER");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    let header = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR")).join("chroma_api.h");
+
+    let generated = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .map_err(|e| e.to_string())
+        .and_then(|config| {
+            cbindgen::generate_with_config(&crate_dir, config).map_err(|e| e.to_string())
+        });
+
+    // A header that fails to parse must not break the library build itself; the
+    // placeholder fails loudly where the header is consumed instead
+    match generated {
+        Ok(bindings) => {
+            bindings.write_to_file(&header);
+            check_committed_header(&crate_dir.join("chroma_api.h"), &header);
+        }
+        Err(e) => {
+            println!("cargo:warning=Failed to generate chroma_api.h: {}", e);
+            let message = e.replace('"', "'").replace('\n', " ");
+            fs::write(&header, format!("#error \"chroma_api.h generation failed: {}\"\n", message))
+                .expect("write placeholder header");
+        }
+    }
+}
+
+/// Reports when the checked-in `chroma_api.h` differs from the header just generated, and
+/// fails the build when `CHROMA_REQUIRE_FRESH_HEADER` is set, e.g. in CI
+fn check_committed_header(committed: &Path, generated: &Path) {
+    if fs::read(committed).ok() == fs::read(generated).ok() {
+        return;
+    }
+
+    let message = format!(
+        "chroma_api.h is stale; copy {} over it (the build scripts do)",
+        generated.display()
+    );
+    if env::var_os("CHROMA_REQUIRE_FRESH_HEADER").is_some() {
+        panic!("{}", message);
+    }
+    println!("cargo:warning={}", message);
+}
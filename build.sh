@@ -15,6 +15,10 @@ echo "Building for Linux x64..."
 cargo build --release
 cp "$SCRIPT_DIR/target/release/libchroma_csharp.so" "$OUTPUT_DIR/linux-x64/native/"
 
+# Refresh the checked-in C header from the one cbindgen generated for this build
+HEADER="$(ls -t "$SCRIPT_DIR"/target/release/build/chromadb-dotnet-bindings-*/out/chroma_api.h | head -n 1)"
+cp "$HEADER" "$SCRIPT_DIR/chroma_api.h"
+
 # Cross-compile for Windows (requires appropriate target)
 if rustup target list --installed | grep -q "x86_64-pc-windows-msvc"; then
     echo "Building for Windows x64..."
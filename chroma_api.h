@@ -1,142 +1,2313 @@
 #ifndef CHROMA_API_H
 #define CHROMA_API_H
 
+// Generated by cbindgen from the chroma_csharp sources; do not edit by hand.
+
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 
-#ifdef __cplusplus
-extern "C" {
-#endif
+// Version of the C ABI, raised whenever an exported signature or the layout of a
+// `#[repr(C)]` type changes, so bindings built against another version can be detected.
+//
+// Fields appended to a size-prefixed input struct do not change the ABI version.
+#define CHROMA_ABI_VERSION 10
+
+// Levels passed to and accepted by log callbacks, from least to most verbose
+#define CHROMA_LOG_ERROR 1
+
+#define CHROMA_LOG_WARN 2
+
+#define CHROMA_LOG_INFO 3
+
+#define CHROMA_LOG_DEBUG 4
+
+#define CHROMA_LOG_TRACE 5
+
+// Events passed to change callbacks, combined into the mask of `chroma_subscribe`
+#define CHROMA_EVENT_ADD 1
+
+#define CHROMA_EVENT_UPDATE 2
+
+#define CHROMA_EVENT_UPSERT 4
+
+#define CHROMA_EVENT_DELETE 8
+
+#define CHROMA_EVENT_DROP 16
+
+#define CHROMA_EVENT_GAP 32
+
+#define CHROMA_EVENT_ALL 63
+
+// Reads see the segments as they are, without waiting for logged writes to be applied
+#define CHROMA_CONSISTENCY_EVENTUAL 0
+
+// Reads first apply every logged write of the collection, wherever it came from
+#define CHROMA_CONSISTENCY_STRONG 1
+
+// Clears the mode set on the calling thread, falling back to the client's mode
+#define CHROMA_CONSISTENCY_CLIENT_DEFAULT -1
+
+// Length of an AES-256 key in bytes
+#define CHROMA_ENCRYPTION_KEY_LEN 32
+
+// Modes accepted in `LockConfigFFI::mode`
+#define CHROMA_LOCK_NONE 0
+
+#define CHROMA_LOCK_EXCLUSIVE 1
+
+#define CHROMA_LOCK_SHARED_READ 2
+
+// States reported by `chroma_operation_status` and `chroma_operation_wait`
+#define CHROMA_OPERATION_RUNNING 0
+
+#define CHROMA_OPERATION_SUCCEEDED 1
+
+#define CHROMA_OPERATION_FAILED 2
+
+#define CHROMA_OPERATION_CANCELLED 3
+
+
+
+// Error classes accepted in `ChromaRetryPolicy::retryable_classes`
+#define CHROMA_RETRY_UNAVAILABLE 1
+
+#define CHROMA_RETRY_RESOURCE_EXHAUSTED (1 << 1)
+
+#define CHROMA_RETRY_ABORTED (1 << 2)
+
+#define CHROMA_RETRY_DEADLINE_EXCEEDED (1 << 3)
+
+// Classes retried when a policy leaves `retryable_classes` at 0
+#define CHROMA_RETRY_DEFAULT_CLASSES (((CHROMA_RETRY_UNAVAILABLE | CHROMA_RETRY_RESOURCE_EXHAUSTED) | CHROMA_RETRY_ABORTED) | CHROMA_RETRY_DEADLINE_EXCEEDED)
+
+// Journal modes accepted in `SqliteConfigFFI::journal_mode`
+#define CHROMA_JOURNAL_MODE_DEFAULT 0
+
+#define CHROMA_JOURNAL_MODE_WAL 1
+
+#define CHROMA_JOURNAL_MODE_DELETE 2
+
+// Levels accepted in `SqliteConfigFFI::synchronous`
+#define CHROMA_SYNCHRONOUS_DEFAULT 0
+
+#define CHROMA_SYNCHRONOUS_OFF 1
+
+#define CHROMA_SYNCHRONOUS_NORMAL 2
+
+#define CHROMA_SYNCHRONOUS_FULL 3
+
+// Modes accepted in the `on_conflict` argument of `chroma_add`
+#define CHROMA_ON_CONFLICT_FAIL 0
+
+#define CHROMA_ON_CONFLICT_IGNORE 1
+
+#define CHROMA_ON_CONFLICT_OVERWRITE 2
+
+// Include flags accepted by calls taking `include_flags`
+#define CHROMA_INCLUDE_EMBEDDINGS 1
+
+#define CHROMA_INCLUDE_METADATAS (1 << 1)
+
+#define CHROMA_INCLUDE_DOCUMENTS (1 << 2)
+
+#define CHROMA_INCLUDE_DISTANCES (1 << 3)
+
+#define CHROMA_INCLUDE_URIS (1 << 4)
+
+// Passed as `include_flags` to use the default includes of the collection handle
+#define CHROMA_INCLUDE_DEFAULTS 0
+
+// Fields accepted in `ChromaOrderBy::field`
+#define CHROMA_ORDER_BY_ID 0
+
+#define CHROMA_ORDER_BY_METADATA 1
+
+// Modes accepted in the `merge_mode` argument of `chroma_update_metadata`
+#define CHROMA_METADATA_MERGE 0
+
+#define CHROMA_METADATA_REPLACE 1
+
+// Handle kinds accepted by `chroma_list_handles`
+#define CHROMA_HANDLE_CLIENT 0
+
+#define CHROMA_HANDLE_COLLECTION 1
+
+#define CHROMA_HANDLE_BATCH 2
+
+#define CHROMA_HANDLE_PREPARED_QUERY 3
+
+#define CHROMA_HANDLE_OPERATION 4
+
+// Reciprocal rank fusion
+#define CHROMA_FUSION_RRF 0
+
+// Weighted sum of normalized vector similarity and text score
+#define CHROMA_FUSION_WEIGHTED 1
+
+// Default RRF rank constant
+#define DEFAULT_RRF_K 60.0
+
+// Merge strategies accepted by `chroma_query_multi`
+#define CHROMA_MERGE_NONE 0
+
+#define CHROMA_MERGE_BY_DISTANCE 1
+
+// Default BM25 term frequency saturation
+#define DEFAULT_BM25_K1 1.2
+
+// Default BM25 document length normalization
+#define DEFAULT_BM25_B 0.75
+
+// Results encoded as MessagePack, with struct fields written as map keys
+#define CHROMA_SERIALIZATION_MSGPACK 0
+
+// Results encoded as CBOR
+#define CHROMA_SERIALIZATION_CBOR 1
+
+// Results encoded as an Apache Arrow IPC stream holding a single record batch
+#define CHROMA_SERIALIZATION_ARROW_IPC 2
+
+// Metadata returned as one JSON object string per record
+#define CHROMA_METADATA_FORMAT_JSON 0
+
+// Metadata returned as a flat array of typed `ChromaMetadataEntry` values
+#define CHROMA_METADATA_FORMAT_TYPED 1
+
+// Value types of a `ChromaMetadataEntry`
+#define CHROMA_METADATA_VALUE_STRING 0
+
+#define CHROMA_METADATA_VALUE_INT 1
+
+#define CHROMA_METADATA_VALUE_FLOAT 2
+
+#define CHROMA_METADATA_VALUE_BOOL 3
+
+// Values without a scalar representation, serialized as JSON in `str_val`
+#define CHROMA_METADATA_VALUE_JSON 4
+
+// Element types accepted in a `ChromaEmbeddingBatch`
+#define CHROMA_DTYPE_F32 0
+
+#define CHROMA_DTYPE_F16 1
+
+#define CHROMA_DTYPE_F64 2
+
+// Versions of the UUIDs generated by `chroma_new_uuid`
+#define CHROMA_UUID_V4 4
+
+#define CHROMA_UUID_V7 7
 
-// Error codes
 typedef enum ChromaErrorCode {
-    CHROMA_SUCCESS = 0,
-    CHROMA_INVALID_ARGUMENT = 1,
-    CHROMA_INTERNAL_ERROR = 2,
-    CHROMA_MEMORY_ERROR = 3,
-    CHROMA_NOT_FOUND = 4,
-    CHROMA_VALIDATION_ERROR = 5,
-    CHROMA_INVALID_UUID = 6,
-    CHROMA_NOT_IMPLEMENTED = 7
+  CHROMA_ERROR_CODE_SUCCESS = 0,
+  CHROMA_ERROR_CODE_INVALID_ARGUMENT = 1,
+  CHROMA_ERROR_CODE_INTERNAL_ERROR = 2,
+  CHROMA_ERROR_CODE_MEMORY_ERROR = 3,
+  CHROMA_ERROR_CODE_NOT_FOUND = 4,
+  CHROMA_ERROR_CODE_VALIDATION_ERROR = 5,
+  CHROMA_ERROR_CODE_INVALID_UUID = 6,
+  CHROMA_ERROR_CODE_NOT_IMPLEMENTED = 7,
+  CHROMA_ERROR_CODE_DIMENSION_MISMATCH = 8,
+  CHROMA_ERROR_CODE_READ_ONLY = 9,
+  CHROMA_ERROR_CODE_CONCURRENT_ACCESS = 10,
+  CHROMA_ERROR_CODE_MIGRATIONS_PENDING = 11,
+  CHROMA_ERROR_CODE_CONFLICT_ERROR = 12,
+  CHROMA_ERROR_CODE_PERMISSION_DENIED = 13,
+  CHROMA_ERROR_CODE_QUOTA_EXCEEDED = 14,
+  CHROMA_ERROR_CODE_BUSY = 15,
+  CHROMA_ERROR_CODE_COLLECTION_READ_ONLY = 16,
 } ChromaErrorCode;
 
-// Opaque handle types
-typedef struct ChromaClient ChromaClient;
-typedef struct ChromaCollection ChromaCollection;
+typedef struct Option_ChromaAuditFn Option_ChromaAuditFn;
+
+typedef struct Option_ChromaAuthorizeFn Option_ChromaAuthorizeFn;
+
+typedef struct Option_ChromaChangeFn Option_ChromaChangeFn;
+
+typedef struct Option_ChromaLogFn Option_ChromaLogFn;
 
-// SQLite configuration
+typedef struct Option_ChromaRerankFn Option_ChromaRerankFn;
+
+// Error details returned through `error_out`.
+//
+// Only allocated on failure: every function writes NULL to `error_out` on success, so
+// callers need not free anything after a successful call. Owned by the library:
+// release with `chroma_free_error`, never with `chroma_free`.
+typedef struct ChromaError {
+  enum ChromaErrorCode code;
+  char *message;
+  char *source;
+  char *details;
+  // Whether retrying the same call may succeed (e.g. the backend was unavailable)
+  bool retryable;
+  // Messages of the backend error and its causes, outermost first
+  char **chain;
+  size_t chain_count;
+  // Request ID set with `chroma_set_request_id` when the error occurred, or null
+  char *request_id;
+} ChromaError;
+
+// Identifies a registered callback; 0 never does
+typedef uint64_t ChromaCallbackId;
+
+// A `ChromaLogFn`, or null for none
+typedef struct Option_ChromaLogFn ChromaLogCallback;
+
+// Opaque handle to a client, collection, write batch, prepared query or operation
+typedef uint64_t ChromaHandle;
+
+// A `ChromaChangeFn`, or null for none
+typedef struct Option_ChromaChangeFn ChromaChangeCallback;
+
+// SQLite settings of the client.
+//
+// Zero in any of the tuning fields keeps the backend default.
 typedef struct SqliteConfigFFI {
-    const char* url;
-    int hash_type;
-    int migration_mode;
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Ignored; the database lives in the persist directory. May be null
+  const char *url;
+  int hash_type;
+  int migration_mode;
+  // One of the `CHROMA_JOURNAL_MODE_*` modes
+  int journal_mode;
+  // One of the `CHROMA_SYNCHRONOUS_*` levels
+  int synchronous;
+  // How long a connection waits for a locked database, in milliseconds
+  unsigned int busy_timeout_ms;
+  // Page cache of each connection, in KiB
+  unsigned int cache_size_kib;
 } SqliteConfigFFI;
 
-// Query result structure
-typedef struct ChromaQueryResult {
-    char** ids;
-    size_t ids_count;
-    float* distances;
-    size_t distances_count;
-    char** metadata_json;
-    size_t metadata_count;
-    char** documents;
-    size_t documents_count;
-} ChromaQueryResult;
+// Disk tier of the HNSW index cache; indexes evicted from memory are kept on disk
+typedef struct DiskCacheConfigFFI {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Directory holding the cached indexes, created if missing
+  const char *dir;
+  // Disk space for the cache, in MiB
+  size_t disk_mib;
+  // Memory in front of the disk tier, in MiB (0 = Foyer default)
+  size_t memory_mib;
+} DiskCacheConfigFFI;
+
+// Cache of collections and their segments, which saves a sysdb lookup per operation
+typedef struct CollectionCacheConfigFFI {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Number of collections cached in memory (0 = no cache)
+  size_t capacity;
+  // Seconds a cached collection stays valid (0 = 60)
+  unsigned int ttl_secs;
+  // Maximum concurrent sysdb lookups (0 = 32)
+  unsigned int permitted_parallelism;
+} CollectionCacheConfigFFI;
+
+// Advisory lock taken on the persist directory when the client is created
+typedef struct LockConfigFFI {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // One of the `CHROMA_LOCK_*` modes
+  int mode;
+  // How long to wait for other clients to release the lock, in milliseconds
+  unsigned int wait_ms;
+} LockConfigFFI;
+
+// Encryption at rest of the persist directory, see `chroma_create_client`
+typedef struct EncryptionConfigFFI {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // AES-256 key the persist directory is sealed with
+  const uint8_t *key;
+  // Length of `key` in bytes, `CHROMA_ENCRYPTION_KEY_LEN`
+  size_t key_len;
+} EncryptionConfigFFI;
+
+// A `ChromaAuditFn`, or null for none
+typedef struct Option_ChromaAuditFn ChromaAuditCallback;
+
+// Where the audit log of a client is written
+typedef struct ChromaAuditLogOptions {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // JSONL file entries are appended to, relative to the persist directory unless
+  // absolute; null for none
+  const char *path;
+  // Callback receiving each entry; null for none
+  ChromaAuditCallback callback;
+  // Passed back to `callback`
+  void *user_data;
+} ChromaAuditLogOptions;
+
+// A `ChromaAuthorizeFn`, or null for none
+typedef struct Option_ChromaAuthorizeFn ChromaAuthorizeCallback;
+
+// How many calls may run at once through a client; 0 leaves a limit unset
+typedef struct ChromaConcurrencyLimits {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Reads (gets, queries, counts, lookups) running at once
+  unsigned int max_reads;
+  // Writes (adds, updates, upserts, deletes, collection and database changes) running at once
+  unsigned int max_writes;
+  // How long a call over a limit waits for another to finish before failing with `Busy`;
+  // 0 fails it at once
+  unsigned int queue_timeout_ms;
+} ChromaConcurrencyLimits;
+
+// Configuration of the ingestion queue of a client; 0 leaves a field at its default
+typedef struct ChromaIngestOptions {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Background threads writing queued records, 2 by default
+  unsigned int workers;
+  // Most records written by one add request, 1000 by default
+  unsigned int max_batch_size;
+  // Most records queued and not yet written before enqueueing fails with `Busy`, 100000
+  // by default
+  size_t max_pending;
+} ChromaIngestOptions;
+
+// Counters of the ingestion queue of a client, filled in by `chroma_queue_stats` in a
+// caller-owned struct
+typedef struct ChromaQueueStats {
+  // Background threads running
+  unsigned int workers;
+  // Records queued and not yet picked up by a worker
+  size_t pending;
+  // Records being written by the workers
+  size_t in_flight;
+  // Records written since the client was created
+  uint64_t written;
+  // Records whose add request failed since the client was created
+  uint64_t failed;
+  // Add requests sent by the workers
+  uint64_t batches;
+  // Add requests that failed
+  uint64_t failed_batches;
+} ChromaQueueStats;
 
-// Result set information
+// Progress of an operation, filled in by `chroma_operation_status` in a caller-owned struct
+typedef struct ChromaOperationProgress {
+  // Units of work done so far, e.g. records
+  size_t processed;
+  // Units of work in the operation
+  size_t total;
+  // Time since the operation started, or its duration once it ended
+  uint64_t elapsed_ms;
+  // Estimated time left from the rate so far; -1 until some work is done and once ended
+  int64_t eta_ms;
+} ChromaOperationProgress;
+
+// Limits enforced on the calls of a client; 0 leaves a limit unset
+typedef struct ChromaQuotas {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Collections per database
+  size_t max_collections;
+  // Records per collection
+  size_t max_records_per_collection;
+  // Dimension of the embeddings written
+  size_t max_dimension;
+  // Adds, updates, upserts, deletes and batch commits per second
+  unsigned int max_write_ops_per_sec;
+} ChromaQuotas;
+
+// Retry behavior for backend calls made through a client
+typedef struct ChromaRetryPolicy {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Total attempts including the first one; 0 or 1 disables retries
+  unsigned int max_attempts;
+  // Delay before the first retry, doubled for every further retry
+  unsigned int base_delay_ms;
+  // Upper bound for a single delay (0 = unbounded)
+  unsigned int max_delay_ms;
+  // Fraction of each delay randomized away, between 0 and 1
+  float jitter;
+  // Bitmask of `CHROMA_RETRY_*` classes to retry (0 = default classes)
+  unsigned int retryable_classes;
+} ChromaRetryPolicy;
+
+// List of record IDs, filled in by the library in a caller-owned struct
 typedef struct ChromaResultSet {
-    char** ids;
-    size_t count;
+  char **ids;
+  size_t count;
 } ChromaResultSet;
 
-// Memory management functions
-int chroma_free_string(char* str);
-int chroma_free_string_array(char** array, size_t count);
-int chroma_free_query_result(ChromaQueryResult* result);
-
-// Client management
-int chroma_create_client(
-    int allow_reset,
-    const SqliteConfigFFI* sqlite_config,
-    size_t hnsw_cache_size,
-    const char* persist_path,
-    ChromaClient** client_handle
-);
-
-int chroma_destroy_client(ChromaClient* client_handle);
-int chroma_heartbeat(ChromaClient* client_handle, uint64_t* result);
-
-// Database management
-int chroma_create_database(
-    ChromaClient* client_handle,
-    const char* name,
-    const char* tenant
-);
-
-int chroma_get_database(
-    ChromaClient* client_handle,
-    const char* name,
-    const char* tenant,
-    char** id_result
-);
-
-int chroma_delete_database(
-    ChromaClient* client_handle,
-    const char* name,
-    const char* tenant
-);
-
-// Collection management
-int chroma_create_collection(
-    ChromaClient* client_handle,
-    const char* name,
-    const char* config_json,
-    const char* metadata_json,
-    int get_or_create,
-    const char* tenant,
-    const char* database,
-    ChromaCollection** collection_handle
-);
-
-int chroma_get_collection(
-    ChromaClient* client_handle,
-    const char* name,
-    const char* tenant,
-    const char* database,
-    ChromaCollection** collection_handle
-);
-
-int chroma_destroy_collection(ChromaCollection* collection_handle);
-
-// Document management
-int chroma_add(
-    ChromaClient* client_handle,
-    const ChromaCollection* collection_handle,
-    const char** ids,
-    size_t ids_count,
-    const float** embeddings,
-    size_t embedding_dim,
-    const char** metadatas_json,
-    const char** documents
-);
-
-int chroma_query(
-    ChromaClient* client_handle,
-    const ChromaCollection* collection_handle,
-    const float* query_embedding,
-    size_t embedding_dim,
-    unsigned int n_results,
-    const char* where_filter_json,
-    const char* where_document_filter,
-    int include_embeddings,
-    int include_metadatas,
-    int include_documents,
-    int include_distances,
-    ChromaQueryResult** result
-);
+// A contiguous, row-major block of `count` embeddings of `dimension` elements each
+typedef struct ChromaEmbeddingBatch {
+  const void *data;
+  // One of the `CHROMA_DTYPE_*` constants
+  int dtype;
+  size_t count;
+  size_t dimension;
+} ChromaEmbeddingBatch;
+
+// Outcome of `chroma_add`, filled in by the library in a caller-owned struct
+typedef struct ChromaAddStats {
+  // Records that did not exist before the add
+  size_t inserted;
+  // Existing records left untouched (`CHROMA_ON_CONFLICT_IGNORE`)
+  size_t skipped;
+  // Existing records replaced (`CHROMA_ON_CONFLICT_OVERWRITE`)
+  size_t overwritten;
+} ChromaAddStats;
+
+// A single metadata key/value of a result record
+typedef struct ChromaMetadataEntry {
+  // Index of the record in the result's ID list
+  size_t record_index;
+  char *key;
+  // One of the `CHROMA_METADATA_VALUE_*` constants
+  int value_type;
+  char *str_val;
+  int64_t int_val;
+  double float_val;
+  bool bool_val;
+} ChromaMetadataEntry;
+
+// Result of a get, query or search call.
+//
+// Owned by the library: release with `chroma_free_query_result`, which frees
+// every array and string it points to.
+typedef struct ChromaQueryResult {
+  char **ids;
+  size_t ids_count;
+  float *distances;
+  size_t distances_count;
+  char **metadata_json;
+  size_t metadata_count;
+  char **documents;
+  size_t documents_count;
+  float *scores;
+  size_t scores_count;
+  float *text_scores;
+  size_t text_scores_count;
+  struct ChromaMetadataEntry *metadata_entries;
+  size_t metadata_entries_count;
+  // Embeddings as one row-major block of `embeddings_count` rows of `embedding_dim`
+  // floats, row `i` belonging to `ids[i]`; rows of records without an embedding are NaN
+  float *embeddings;
+  size_t embeddings_count;
+  size_t embedding_dim;
+  // Whether `chroma_get` found more matching records beyond the returned page
+  bool has_more;
+  // Number of records matching a `chroma_get` across all pages, or -1 when it was not
+  // asked to count them
+  int64_t total_matching_count;
+  // Version token of each record returned by `chroma_get`, `versions[i]` belonging to
+  // `ids[i]`; null unless versions were asked for
+  int64_t *versions;
+  size_t versions_count;
+} ChromaQueryResult;
+
+// A binary attachment passed by the caller
+typedef struct ChromaBlob {
+  // Blob bytes; null with `len` 0 to leave the record's attachment unchanged
+  const uint8_t *data;
+  // Number of bytes in `data`
+  size_t len;
+} ChromaBlob;
+
+// Called after every batch with the number of IDs processed so far, the total number of
+// IDs and the caller's `user_data`. Returning false cancels the remaining batches.
+typedef bool (*ChromaProgressCallback)(size_t processed, size_t total, void *user_data);
+
+// Outcome of `chroma_delete_batched`, filled in by the library in a caller-owned struct
+typedef struct ChromaBatchStats {
+  // IDs in batches the backend accepted
+  size_t succeeded;
+  // IDs in batches that failed
+  size_t failed;
+  // Batches sent to the backend
+  size_t batches;
+  // Batches that failed
+  size_t failed_batches;
+  // Whether the progress callback cancelled the remaining batches
+  bool cancelled;
+} ChromaBatchStats;
+
+// Sort order for `chroma_get`
+typedef struct ChromaOrderBy {
+  // `CHROMA_ORDER_BY_ID` or `CHROMA_ORDER_BY_METADATA`
+  int field;
+  // Metadata key to sort by when `field` is `CHROMA_ORDER_BY_METADATA`
+  const char *metadata_key;
+  // Sort from largest to smallest instead of smallest to largest
+  bool descending;
+} ChromaOrderBy;
+
+// A `ChromaRerankFn`, or null for none
+typedef struct Option_ChromaRerankFn ChromaRerankCallback;
+
+// Options for `chroma_query`; pass null to return the backend's hits unchanged
+typedef struct ChromaQueryOptions {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Drops hits farther than this distance; 0 or less keeps every hit
+  float max_distance;
+  // Metadata key to deduplicate on: only the closest hit per value of the key is kept,
+  // hits without the key are all kept. Null to keep duplicates
+  const char *dedupe_by_metadata_key;
+  // Candidates fetched per requested hit when deduplicating; 0 for the default of 3
+  unsigned int over_fetch;
+  // Candidates fetched and re-ranked with maximal marginal relevance (MMR); 0 disables MMR
+  unsigned int mmr_fetch_k;
+  // MMR trade-off between relevance (1) and diversity (0), from 0 to 1
+  float mmr_lambda;
+  // Query text handed to the re-ranker; candidates are re-ranked only when not null.
+  // Cannot be combined with MMR
+  const char *rerank_query;
+  // Re-ranker for this query; null for the one set with `chroma_set_reranker`
+  ChromaRerankCallback reranker;
+  // Passed back to `reranker`
+  void *reranker_user_data;
+  // Candidates fetched and re-ranked; 0 to re-rank `n_results` candidates
+  unsigned int rerank_fetch_k;
+} ChromaQueryOptions;
+
+// Parts of a `chroma_query` call that stay the same from one execution to the next
+typedef struct ChromaQueryTemplate {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Metadata filter as JSON; null for none
+  const char *where_filter_json;
+  // Document filter as JSON; null for none
+  const char *where_document_filter;
+  // `CHROMA_INCLUDE_*` flags of the fields to return, or `CHROMA_INCLUDE_DEFAULTS` for
+  // the default includes of the collection handle when the query is prepared. URIs are
+  // not returned by queries
+  unsigned int include_flags;
+  // Metadata keys to return; null for every key
+  const char *const *metadata_keys;
+  size_t metadata_keys_count;
+  // `CHROMA_METADATA_FORMAT_*` of the returned metadata
+  int metadata_format;
+  // Post-processing of the hits, as passed to `chroma_query`; null for none
+  const struct ChromaQueryOptions *options;
+} ChromaQueryTemplate;
+
+// A single record returned by `chroma_get_one`.
+//
+// Owned by the library: release with `chroma_free_record`. Fields that were not included
+// or are not set on the record are null, with `embedding_dim` 0 when there is no embedding.
+typedef struct ChromaRecord {
+  char *id;
+  char *document;
+  // Metadata serialized as a JSON object
+  char *metadata_json;
+  float *embedding;
+  size_t embedding_dim;
+  char *uri;
+} ChromaRecord;
+
+// Ranking options for keyword search
+typedef struct ChromaRankOptions {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // BM25 term frequency saturation (k1); values <= 0 use the default
+  float k1;
+  // BM25 length normalization (b); values outside [0, 1] use the default
+  float b;
+  // Require every query term to appear in a document instead of any term
+  bool match_all_terms;
+  // Maximum number of candidates read from the full-text index (0 = unlimited)
+  unsigned int max_candidates;
+} ChromaRankOptions;
+
+// Options controlling how vector and keyword results are fused
+typedef struct ChromaFusionOptions {
+  // Size of this struct as compiled by the caller, see `SizedInput`
+  size_t struct_size;
+  // Fusion method: 0 = reciprocal rank fusion, 1 = weighted
+  int method;
+  // RRF rank constant; values <= 0 use the default of 60
+  float rrf_k;
+  // Weight of the vector similarity in weighted fusion, in [0, 1]
+  float vector_weight;
+  // Candidates fetched from each retriever before fusion (0 = 2 * n_results)
+  unsigned int candidate_k;
+} ChromaFusionOptions;
+
+// A sparse vector as parallel arrays of dimension indices and values
+typedef struct ChromaSparseVector {
+  // Dimension indices, each at most once
+  const uint32_t *indices;
+  // Value of each index
+  const float *values;
+  // Number of indices and of values
+  size_t count;
+} ChromaSparseVector;
+
+// Handle value that never refers to a live object
+#define CHROMA_INVALID_HANDLE 0
 
 #ifdef __cplusplus
-}
+extern "C" {
+#endif // __cplusplus
+
+// Writes the C header declaring every exported function, struct, enum and constant of
+// this build of the library to `path`, replacing any existing file.
+//
+// The header is generated from the Rust sources at build time, so it always matches the
+// library it comes from and can drive code generation of bindings.
+int chroma_emit_header(const char *path_ptr, struct ChromaError **error_out);
+
+// Returns the ABI version of this build of the library, `CHROMA_ABI_VERSION`
+unsigned int chroma_abi_version(void);
+
+// Checks whether bindings built against ABI version `expected` can use this library.
+//
+// Versions are not backward compatible: any difference means signatures or struct layouts
+// may differ, so callers should refuse to make further calls rather than risk corrupting
+// memory.
+bool chroma_is_compatible(unsigned int expected);
+
+// Unregisters a callback registered with a `chroma_register_*_callback` function.
+//
+// Returns once no invocation of the callback is running; it is never invoked afterwards,
+// so `user_data` may be released when this returns. Called from within the callback
+// itself, it returns at once and the running invocation is the last one.
+int chroma_unregister_callback(ChromaCallbackId callback_id, struct ChromaError **error_out);
+
+// Registers a callback receiving the log records of the library and the Chroma backend up
+// to `max_level` (`CHROMA_LOG_ERROR` .. `CHROMA_LOG_TRACE`), and writes its ID to
+// `*callback_id`.
+//
+// The callback runs on the thread that logged, possibly several threads at once, and
+// until `chroma_unregister_callback` returns for the ID; after that it is never called
+// again. Records logged from within the callback are dropped. The first registration
+// installs the library as the process-wide tracing subscriber and fails with
+// `InternalError` if another one was installed before.
+int chroma_register_log_callback(ChromaLogCallback callback,
+                                 void *user_data,
+                                 int max_level,
+                                 ChromaCallbackId *callback_id,
+                                 struct ChromaError **error_out);
+
+// Subscribes a callback to the changes of a collection and writes its ID to
+// `*callback_id`; `chroma_unregister_callback` ends the subscription.
+//
+// `event_mask` combines the `CHROMA_EVENT_*` events to receive. Adds, updates, upserts and
+// deletes are read from the write log of the client, so they are observed whichever handle,
+// batch or process wrote them, in log order, within about 50 ms; writes made before the
+// subscription are not reported. `CHROMA_EVENT_DROP` is delivered once when the collection
+// is deleted, such as with its database or by another process sharing the persist
+// directory, after which the subscription stops reading the log.
+//
+// `chroma_optimize_collection` purges compacted entries from the log, also while
+// subscriptions read it. When entries were purged before the subscription read them,
+// `CHROMA_EVENT_GAP` is delivered whatever `event_mask` holds, with the sequence number of
+// the oldest entry left: changes before it may have been missed, so a subscriber caching
+// records has to resynchronize from `chroma_get`. Delivery then resumes from that entry.
+//
+// The callback runs on a watcher thread of the subscription, one change at a time, and
+// until `chroma_unregister_callback` returns for the ID; after that it is never called
+// again. The subscription also stops once the collection handle is destroyed. Fails with
+// `InternalError` on clients without a local write log.
+int chroma_subscribe(ChromaHandle collection_handle,
+                     int event_mask,
+                     ChromaChangeCallback callback,
+                     void *user_data,
+                     ChromaCallbackId *callback_id,
+                     struct ChromaError **error_out);
+
+// Creates a new ChromaDB client
+//
+// The HNSW index cache keeps up to `hnsw_cache_size` indexes in memory. When
+// `disk_cache_config_ptr` is not null the cache becomes a hybrid memory/disk cache,
+// so more indexes can stay cached than fit in RAM.
+//
+// Collections and their segments are looked up in the sysdb on every operation unless
+// `collection_cache_config_ptr` enables an in-memory collection cache.
+//
+// With `read_only` set, every call that would write (adds, updates, deletes, collection and
+// database creation, optimization) fails with `ReadOnly`. Migrations are then validated
+// instead of applied, the SQLite database must already exist in `persist_path`, and its
+// connections are opened read-only, so the client never writes to a volume shared with a
+// writer.
+//
+// When `lock_config_ptr` is not null and `persist_path` is set, an advisory lock on
+// `chroma.lock` in the persist directory is taken before anything else is opened:
+// `CHROMA_LOCK_EXCLUSIVE` for a single writer, or `CHROMA_LOCK_SHARED_READ` for clients
+// that may run alongside each other but never alongside an exclusive one. If the lock
+// is still held by another client after `wait_ms`, creation fails with `ConcurrentAccess`.
+// The lock is released once the client and every collection opened from it are destroyed.
+//
+// When `distributed_config_json` is not null the client connects to a distributed Chroma
+// deployment instead of embedding a single node. It holds a JSON object with the `sysdb`,
+// `log` and `executor` sections of the Chroma frontend configuration, which must use the
+// gRPC sysdb and log and the distributed executor (see `DistributedConfig`). Such clients
+// have no local storage: `persist_path`, `sqlite_config_ptr` and `lock_config_ptr` must be
+// null, and functions working on the local SQLite database or segments (the write log,
+// flushes, batches, migrations) fail.
+//
+// When `object_store_config_json` is not null the persist directory is mirrored to an object
+// store, so a client on a machine without durable disk keeps its collections. It holds a JSON
+// object with a `storage` section in the format of the Chroma storage configuration (such as
+// `s3` or `object_store`) and an optional key `prefix`. `persist_path` is then required and
+// serves as the working copy: if it has no database yet, every object under the prefix is
+// downloaded into it before the client opens it, and `chroma_flush` (including automatic and
+// shutdown flushes) uploads the files that changed since their last upload. Writes that were
+// not flushed are lost with the machine.
+//
+// When `encryption_config_ptr` is not null, or a key was set with `chroma_set_encryption_key`,
+// the persist directory is sealed with AES-256-GCM while no client has it open: every file is
+// sealed once the client is released and decrypted when a client opens the directory again
+// with the same key. This is not transparent encryption at rest. The SQLite database and
+// segments are written by engines that cannot encrypt pages, so every file is plaintext on
+// disk for as long as a client has the directory open, and stays plaintext after a crash,
+// kill or abort until a client opens the directory with the key, which seals the leftovers
+// before anything else, or `chroma_seal_directory` is called. Opening an encrypted directory fails
+// with `InvalidArgument` without the key or with a wrong one, and with `ReadOnly` for
+// read-only clients; encryption cannot be combined with `CHROMA_LOCK_SHARED_READ` or
+// `object_store_config_json`. The files are decrypted into a directory of their own and
+// moved into place only once all of them are, so a failure or crash while opening never
+// leaves the directory half decrypted. Rotate the key with `chroma_rotate_encryption_key`.
+int chroma_create_client(bool allow_reset,
+                         bool read_only,
+                         const struct SqliteConfigFFI *sqlite_config_ptr,
+                         size_t hnsw_cache_size,
+                         const struct DiskCacheConfigFFI *disk_cache_config_ptr,
+                         const struct CollectionCacheConfigFFI *collection_cache_config_ptr,
+                         const struct LockConfigFFI *lock_config_ptr,
+                         const char *persist_path_ptr,
+                         const char *distributed_config_json,
+                         const char *object_store_config_json,
+                         const struct EncryptionConfigFFI *encryption_config_ptr,
+                         ChromaHandle *client_handle,
+                         struct ChromaError **error_out);
+
+// Destroys a ChromaDB client
+//
+// Collections opened from the client stay usable until they are destroyed themselves.
+// Use `chroma_shutdown_client` to let running operations finish and flush pending writes.
+int chroma_destroy_client(ChromaHandle client_handle, struct ChromaError **error_out);
+
+// Issues another handle to the same client, for a component that shares the client with
+// others and releases it on its own schedule.
+//
+// Each handle is released with `chroma_client_release` (or `chroma_destroy_client`); the
+// client and its runtime stay alive until every handle and every collection opened from it
+// are released. Handles are independent, so releasing one twice fails instead of dropping
+// a reference held by another component.
+int chroma_client_retain(ChromaHandle client_handle,
+                         ChromaHandle *retained_handle,
+                         struct ChromaError **error_out);
+
+// Releases one handle to a client, as `chroma_destroy_client` does.
+//
+// The client itself is dropped with its last handle or collection, see
+// `chroma_client_retain`.
+int chroma_client_release(ChromaHandle client_handle, struct ChromaError **error_out);
+
+// Returns a heartbeat (current time) from the client
+int chroma_heartbeat(ChromaHandle client_handle, uint64_t *result, struct ChromaError **error_out);
+
+// Creates a new database in ChromaDB
+int chroma_create_database(ChromaHandle client_handle,
+                           const char *name_ptr,
+                           const char *tenant_ptr,
+                           struct ChromaError **error_out);
+
+// Gets a database from ChromaDB
+int chroma_get_database(ChromaHandle client_handle,
+                        const char *name_ptr,
+                        const char *tenant_ptr,
+                        char **id_result,
+                        struct ChromaError **error_out);
+
+// Deletes a database from ChromaDB
+int chroma_delete_database(ChromaHandle client_handle,
+                           const char *name_ptr,
+                           const char *tenant_ptr,
+                           struct ChromaError **error_out);
+
+// Sets where a client records the mutations made through it (adds, updates, upserts,
+// deletes, including each write of `chroma_batch_commit`, and collection and database
+// creation, changes and removal), or turns the audit log off when `options` is null.
+//
+// Each successful mutation produces one JSON entry with its UTC `timestamp`, `operation`,
+// the `principal` set with `chroma_set_identity` (null when none), `tenant`, `database`,
+// `collection` and `records`, the number of records written (null when not known, e.g. for
+// deletes by filter). Entries are appended to the file at `path` and passed to `callback`
+// on the calling thread. A relative `path` needs a client with a persist directory.
+//
+// Replacing the log or turning it off returns once no call runs the previous callback,
+// which is never called again, so its `user_data` may be released then; otherwise it must
+// stay valid until the client is destroyed.
+int chroma_set_audit_log(ChromaHandle client_handle,
+                         const struct ChromaAuditLogOptions *options,
+                         struct ChromaError **error_out);
+
+// Sets the callback authorizing every call a client makes into the Chroma frontend, such
+// as collection management, reads and writes, including calls on collections opened from it.
+//
+// The callback runs on the calling thread before each call and its retries; a denied call
+// fails with `PermissionDenied` without reaching the backend. Writes made to the local SQLite
+// database directly are checked too: each write of `chroma_batch_commit` as `add`, `update`,
+// `upsert` or `delete`, and alias changes as `create_alias`, `swap_alias` or `delete_alias`.
+// Functions only reading it directly (change feed, write log, record versions, storage
+// reports) are not covered.
+//
+// A null `callback` removes the authorizer. Replacing or removing it returns once no call
+// runs the previous callback, which is never called again, so its `user_data` may be
+// released then; otherwise it must stay valid until the client is destroyed.
+int chroma_set_authorizer(ChromaHandle client_handle,
+                          ChromaAuthorizeCallback callback,
+                          void *user_data,
+                          struct ChromaError **error_out);
+
+// Sets the identity the authorizer sees for calls made through one client handle and the
+// collections opened through it afterwards, as a JSON document of the host's choosing
+// (e.g. `{"user": "alice", "roles": ["reader"]}`).
+//
+// Identities belong to handles, not clients: a multi-user server issues a handle per
+// principal with `chroma_client_retain` and sets its identity once. Collections keep the
+// identity of the handle they were opened through. A null `principal_json` clears it;
+// invalid JSON fails with `ValidationError`.
+int chroma_set_identity(ChromaHandle client_handle,
+                        const char *principal_json,
+                        struct ChromaError **error_out);
+
+// Limits how many reads and how many writes run at once through a client and the
+// collections opened from it, protecting the SQLite database and segments from more
+// threads than they can serve; a null `limits` removes the limits.
+//
+// Calls into the Chroma frontend and batch commits are counted. A call over a limit waits
+// up to `queue_timeout_ms` for another to finish, then fails with `Busy` without reaching
+// the backend. Calls already running or waiting are not interrupted by a change of limits.
+int chroma_set_concurrency_limits(ChromaHandle client_handle,
+                                  const struct ChromaConcurrencyLimits *limits,
+                                  struct ChromaError **error_out);
+
+// Returns the configuration a client was created with, resolved to the values
+// actually in effect, as JSON.
+//
+// Passwords, secrets, tokens, keys and URL credentials are replaced with `***`.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_dump_effective_config(ChromaHandle client_handle,
+                                 char **out_json,
+                                 struct ChromaError **error_out);
+
+// Creates a client from a YAML (`.yaml`, `.yml`) or TOML (`.toml`) configuration file
+// instead of arguments, so the native layer can be tuned without recompiling the host.
+//
+// The file holds the options of `chroma_create_client`: `allow_reset`, `read_only`,
+// `persist_path`, `hnsw_cache_size`, `disk_cache` (`dir`, `disk_mib`, `memory_mib`),
+// `collection_cache` (`capacity`, `ttl_secs`, `permitted_parallelism`), `lock` (`mode` of
+// `none`, `exclusive` or `shared_read`, `wait_ms`) and `sqlite` (`hash_type`,
+// `migration_mode`, `journal_mode`, `synchronous`, `busy_timeout_ms`, `cache_size_kib`),
+// with enum values written in lowercase. Options left out keep the defaults of
+// `chroma_create_client`. A `distributed` section (`sysdb`, `log`, `executor`) connects the
+// client to a distributed deployment, see `distributed_config_json`, and an `object_store`
+// section (`storage`, `prefix`) mirrors the persist directory, see `object_store_config_json`.
+// Keys do not belong in configuration files: an encrypted persist directory is opened with
+// the key set by `chroma_set_encryption_key`.
+//
+// When `config_path` is null the file named by `CHROMA_CONFIG_PATH` is read, if set.
+// `CHROMA_*` environment variables override the file, with `__` separating nested keys,
+// e.g. `CHROMA_PERSIST_PATH` or `CHROMA_SQLITE__JOURNAL_MODE=wal`. Unknown keys in the file
+// fail with `ValidationError`.
+int chroma_create_client_from_config(const char *config_path,
+                                     ChromaHandle *client_handle,
+                                     struct ChromaError **error_out);
+
+// Sets the read consistency of a client: `CHROMA_CONSISTENCY_EVENTUAL` (the default) or
+// `CHROMA_CONSISTENCY_STRONG`.
+//
+// Writes made through a client are applied to the segments before they return, so its own
+// reads see them either way. Strong reads additionally apply every write still pending in
+// the log of the collection before reading, such as writes of another client or process
+// sharing the persist directory, at the cost of a compactor round trip per read.
+// Strong consistency fails with `ReadOnly` on read-only clients, which cannot apply the log.
+int chroma_set_consistency(ChromaHandle client_handle, int mode, struct ChromaError **error_out);
+
+// Overrides the read consistency of the calls made on the calling thread, whatever client
+// they go through, until it is replaced or cleared with `CHROMA_CONSISTENCY_CLIENT_DEFAULT`.
+//
+// Lets a single call opt into strong reads without changing the mode of a shared client.
+// Strong reads through a read-only client fail with `ReadOnly`.
+int chroma_set_thread_consistency(int mode, struct ChromaError **error_out);
+
+// Sets the tenant and database that calls of this client use when they pass null for
+// them, such as `chroma_create_collection`, `chroma_get_collection` or
+// `chroma_create_database`, instead of `default_tenant` and `default_database`.
+//
+// A null `tenant` or `database` restores the built-in default. The database must exist in
+// the tenant, or the call fails with `NotFound` and the defaults are left unchanged. The
+// defaults are shared by every handle of the client, including those issued by
+// `chroma_client_retain`; collections already open keep the tenant and database they were
+// opened in.
+int chroma_set_default_tenant_database(ChromaHandle client_handle,
+                                       const char *tenant_ptr,
+                                       const char *database_ptr,
+                                       struct ChromaError **error_out);
+
+// Reports the tenant and database calls of this client use when they pass none. Both
+// strings are allocated with `chroma_malloc`; release them with `chroma_free_string`.
+int chroma_get_default_tenant_database(ChromaHandle client_handle,
+                                       char **tenant_out,
+                                       char **database_out,
+                                       struct ChromaError **error_out);
+
+// Looks up the cached embeddings of `count` documents computed by `model`.
+//
+// `found_out` points to `count` flags, set to whether each document was cached. The
+// embeddings of the cached documents are returned row-major in one block of
+// `count * *embedding_dim_out` floats allocated with `chroma_malloc`, with NaN rows for the
+// documents not cached; release it with `chroma_free`. When no document is cached the block
+// is null and the dimension 0.
+//
+// Embeddings are keyed by a SHA-256 hash of the model name and the document text, so the
+// model name should change whenever the model does, e.g. `text-embedding-3-small@1536`.
+int chroma_embedding_cache_get(ChromaHandle client_handle,
+                               const char *model,
+                               const char *const *documents,
+                               size_t count,
+                               bool *found_out,
+                               float **embeddings_out,
+                               size_t *embedding_dim_out,
+                               struct ChromaError **error_out);
+
+// Caches the embeddings `model` computed for `count` documents, so re-ingesting unchanged
+// documents does not compute them again.
+//
+// `embeddings` holds `count * embedding_dim` floats, one row per document. The cache is
+// stored in the `embedding_cache` table of the client's SQLite database and survives
+// restarts; documents already cached are overwritten. Fails with `ReadOnly` on a read-only
+// client.
+int chroma_embedding_cache_put(ChromaHandle client_handle,
+                               const char *model,
+                               const char *const *documents,
+                               size_t count,
+                               const float *embeddings,
+                               size_t embedding_dim,
+                               struct ChromaError **error_out);
+
+// Removes the cached embeddings of `model`, or of every model when `model` is null
+int chroma_embedding_cache_clear(ChromaHandle client_handle,
+                                 const char *model,
+                                 struct ChromaError **error_out);
+
+// Sets the AES-256 key used by clients created afterwards without an encryption config,
+// for hosts that fetch the key from a secret store once at startup.
+//
+// `key` must point to `CHROMA_ENCRYPTION_KEY_LEN` (32) bytes; pass null to clear the key.
+// Clients already created keep the key they were created with.
+int chroma_set_encryption_key(const uint8_t *key, size_t key_len, struct ChromaError **error_out);
+
+// Seals a persist directory that a client with encryption left plaintext because it was
+// not released, such as when the process crashed, was killed or aborted on a panic.
+//
+// Files of an encrypted directory are plaintext from the moment a client opens it until it
+// is released; a client that never is leaves them so until the next client opens the
+// directory with the key, which seals them first. Hosts call this at startup or from a
+// supervisor to close that window without opening a client. `key` must be the
+// `CHROMA_ENCRYPTION_KEY_LEN` (32) byte key the directory is sealed with; files already
+// sealed with another key fail the call with `InvalidArgument` and nothing is sealed.
+//
+// Takes the exclusive directory lock without waiting, so it fails with `ConcurrentAccess`
+// while a client holds the lock; a client created with `CHROMA_LOCK_NONE` is not detected
+// and must not have the directory open. Fails with `NotFound` if the directory does not
+// exist. Sealing a directory that is already sealed does nothing.
+int chroma_seal_directory(const char *persist_path,
+                          const uint8_t *key,
+                          size_t key_len,
+                          struct ChromaError **error_out);
+
+// Replaces the key the persist directory of an encrypted client is sealed with when the
+// client is released.
+//
+// Rotating the key of a directory takes opening it with the current key, calling this
+// with the new one and releasing the client; the directory is then readable with the new
+// key only. Fails with `InvalidArgument` for clients without encryption.
+int chroma_rotate_encryption_key(ChromaHandle client_handle,
+                                 const uint8_t *key,
+                                 size_t key_len,
+                                 struct ChromaError **error_out);
+
+// Forces pending writes to disk and returns once they are durable.
+//
+// Pass a collection handle of the client to flush only that collection, or
+// `CHROMA_INVALID_HANDLE` (0) to flush every collection of the client. The pending write
+// log is applied to the local segments and the SQLite WAL is checkpointed into the
+// database file. Clients mirroring their persist directory to an object store then upload
+// the files that changed. Unlike `chroma_optimize_collection`, the log is not purged.
+// Fails with `ReadOnly` on read-only clients.
+int chroma_flush(ChromaHandle client_handle,
+                 ChromaHandle collection_handle,
+                 struct ChromaError **error_out);
+
+// Flushes every collection of a client in the background each `interval_ms` milliseconds,
+// as `chroma_flush` with no collection would.
+//
+// Pass 0 to stop automatic flushing. Setting a new interval replaces the previous one.
+// Flushing stops once the client and every collection opened from it are released;
+// failures are counted under `flush` in the client metrics. Fails with `ReadOnly` on
+// read-only clients.
+int chroma_set_auto_flush_interval(ChromaHandle client_handle,
+                                   unsigned int interval_ms,
+                                   struct ChromaError **error_out);
+
+// Checks that a client can serve requests and reports the result as JSON.
+//
+// The report holds an overall `status` (`pass`, `warn` or `fail`, the worst of all
+// checks) and a `checks` array with the `name`, `status`, `detail` and `duration_ms`
+// of the `sqlite`, `persist_path`, `cache` and `executor` checks. Failing checks do not
+// fail the call; only invalid arguments do.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_health_check(ChromaHandle client_handle,
+                        char **out_report_json,
+                        struct ChromaError **error_out);
+
+// Configures the ingestion queue of a client; a null `options` restores the defaults.
+//
+// The queue starts its workers on the first `chroma_enqueue_add`. Lowering the number of
+// workers stops the extra ones once their current batch is written; raising it starts new
+// ones as soon as records are queued.
+int chroma_configure_ingest_queue(ChromaHandle client_handle,
+                                  const struct ChromaIngestOptions *options,
+                                  struct ChromaError **error_out);
+
+// Queues records to add and returns without waiting for them to be written. The arguments
+// match `chroma_add`, except that every record needs an embedding and IDs are not generated.
+//
+// Records are checked as `chroma_add` checks them before being queued. Background workers
+// of the client then write them in batches, merging consecutive calls for the same
+// collection, with the client's retry policy; existing IDs are left unchanged, as with
+// `CHROMA_ON_CONFLICT_IGNORE`. Write failures are counted by `chroma_queue_stats` and
+// returned by the next `chroma_queue_flush`. Fails with `Busy` when the queue already holds
+// `max_pending` records.
+int chroma_enqueue_add(ChromaHandle collection_handle,
+                       const char *const *ids,
+                       size_t ids_count,
+                       const float *const *embeddings,
+                       size_t embedding_dim,
+                       const char *const *metadatas_json,
+                       const char *const *documents,
+                       struct ChromaError **error_out);
+
+// Reports the counters of the ingestion queue of a client without blocking
+int chroma_queue_stats(ChromaHandle client_handle,
+                       struct ChromaQueueStats *stats_out,
+                       struct ChromaError **error_out);
+
+// Waits up to `timeout_ms` for every record queued with `chroma_enqueue_add` to be written:
+// 0 only checks, `CHROMA_WAIT_FOREVER` waits without limit. Fails with `Busy` if records
+// are still queued when the timeout elapses.
+//
+// Once the queue drained, returns the error of the first batch that failed since the last
+// flush, with the number of failed batches and records, so each failure is reported once.
+int chroma_queue_flush(ChromaHandle client_handle,
+                       unsigned int timeout_ms,
+                       struct ChromaError **error_out);
+
+// Returns the metrics of a client as JSON.
+//
+// The object maps each backend operation (`add`, `query`, ...) to its `calls`, `errors`,
+// `mean_ms`, `p50_ms`, `p95_ms` and `max_ms`, and holds the total `records_added`.
+// Percentiles cover the most recent 1024 calls of each operation. The string is
+// allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_get_metrics_json(ChromaHandle client_handle,
+                            char **out_str,
+                            struct ChromaError **error_out);
+
+// Clears all metrics of a client
+int chroma_reset_metrics(ChromaHandle client_handle, struct ChromaError **error_out);
+
+// Reports whether the SQLite database in a persist directory is fully migrated, as JSON.
+//
+// Works without a client, so deployments can gate startup on it. `sqlite_config_ptr`
+// may be null; only its `hash_type` is used. The report holds the database path,
+// whether it `exists`, whether it is `up_to_date`, the `applied` migrations (`dir`,
+// `version`, `filename`) and the backend's `validation_error` when migrations are pending.
+// The database is opened read-only. The string is allocated with `chroma_malloc`;
+// release it with `chroma_free_string`.
+int chroma_get_migration_status(const char *persist_path_ptr,
+                                const struct SqliteConfigFFI *sqlite_config_ptr,
+                                char **out_json,
+                                struct ChromaError **error_out);
+
+// Reports the state of an operation (`CHROMA_OPERATION_*`) and its progress without
+// blocking, for hosts polling instead of taking progress callbacks.
+//
+// `progress_out` and `state_out` may each be null. The ETA extrapolates the rate so far. An
+// operation that failed reports `CHROMA_OPERATION_FAILED` here; its error is returned by
+// `chroma_operation_wait`.
+int chroma_operation_status(ChromaHandle operation_handle,
+                            struct ChromaOperationProgress *progress_out,
+                            int *state_out,
+                            struct ChromaError **error_out);
+
+// Waits up to `timeout_ms` for an operation to end: 0 polls, `CHROMA_WAIT_FOREVER` waits
+// without limit. `state_out`, when not null, receives the state reached.
+//
+// Returns success while the operation is still running or once it succeeded or was
+// cancelled; once it failed, returns the operation's error, each time it is waited for.
+int chroma_operation_wait(ChromaHandle operation_handle,
+                          unsigned int timeout_ms,
+                          int *state_out,
+                          struct ChromaError **error_out);
+
+// Asks an operation to stop. It stops at its next step, e.g. after the batch in progress,
+// and ends as `CHROMA_OPERATION_CANCELLED`; work already done is kept. Does nothing once
+// the operation ended.
+int chroma_operation_cancel(ChromaHandle operation_handle, struct ChromaError **error_out);
+
+// Releases an operation handle. A running operation is cancelled and ends in the
+// background.
+int chroma_destroy_operation(ChromaHandle operation_handle, struct ChromaError **error_out);
+
+// Sets the quotas of a client, or of one tenant when `tenant` is not null; a null `quotas`
+// removes them.
+//
+// Calls over a quota fail with `QuotaExceeded` before reaching the backend. A tenant's own
+// quotas replace the client's for that tenant and get their own write allowance; tenants
+// without their own share the client's. `max_collections` counts the collections of the
+// database a collection is created in, and `max_records_per_collection` the records of the
+// collection before an add, upsert or batch commit plus the records it writes, upserted
+// records included. `max_write_ops_per_sec` allows bursts of up to one second's worth.
+int chroma_set_quotas(ChromaHandle client_handle,
+                      const char *tenant_ptr,
+                      const struct ChromaQuotas *quotas,
+                      struct ChromaError **error_out);
+
+// Sets the retry policy used for backend calls made through a client.
+//
+// A null `policy` restores the default of a single attempt without retries.
+int chroma_set_retry_policy(ChromaHandle client_handle,
+                            const struct ChromaRetryPolicy *policy,
+                            struct ChromaError **error_out);
+
+// Shuts a client down gracefully, unlike `chroma_destroy_client` which releases it at once.
+//
+// New operations are rejected: every handle of the client, including those issued by
+// `chroma_client_retain`, is destroyed immediately and collections opened from the client
+// fail with `InvalidArgument`. Backend calls already running get up to `drain_timeout_ms`
+// milliseconds to finish; those still running afterwards are aborted and fail in their own
+// thread. Records queued with `chroma_enqueue_add` get the same time to be written first;
+// those still queued afterwards are discarded. Pending writes are then flushed as
+// `chroma_flush` would, unless the client is read-only, and the client and its collection
+// handles are released.
+//
+// `*aborted_out` receives the number of aborted calls, also when the final flush fails.
+int chroma_shutdown_client(ChromaHandle client_handle,
+                           unsigned int drain_timeout_ms,
+                           size_t *aborted_out,
+                           struct ChromaError **error_out);
+
+// Reports the disk usage of a client's persist directory as JSON.
+//
+// Usage is broken down into the SQLite database, its WAL and shared-memory files,
+// and one entry per segment directory (named after the segment UUID). Clients without
+// a persist directory report `null` as the path and zero bytes.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_get_storage_info(ChromaHandle client_handle,
+                            char **out_json,
+                            struct ChromaError **error_out);
+
+// Aggregates the metadata of the records matching a where filter, without returning them.
+//
+// `agg_ops_json` is a JSON array of aggregations such as `{"op": "count"}`,
+// `{"op": "min", "key": "year"}`, `{"op": "max", "key": "year"}` or
+// `{"op": "distinct", "key": "author"}`; `count` with a key counts the records carrying it.
+// Min, max and distinct order values as `chroma_get` sorting does. With a non-null
+// `group_by_key` the records are grouped by their value of that key, records without it
+// forming a group of their own.
+//
+// `*out_json` receives `{"groups": [{"group": value, "values": {"count": 3, "max:year":
+// 2024, ...}}]}` with one group whose `group` is null when not grouping; release it with
+// `chroma_free_string`. The metadata of every matching record is read, so narrow large
+// collections with `where_filter`.
+int chroma_aggregate(ChromaHandle collection_handle,
+                     const char *where_filter_json,
+                     const char *group_by_key,
+                     const char *agg_ops_json,
+                     char **out_json,
+                     struct ChromaError **error_out);
+
+// Lists the distinct values of a metadata key across a collection, e.g. to fill filter
+// dropdowns without reading the records.
+//
+// Values are ordered as `chroma_get` sorting orders them, and `limit` 0 returns them all.
+// Strings are listed as is and other values as JSON, e.g. `2024` or `true`. `values_out`
+// receives them and is released with `chroma_free_result_set`. Soft-deleted records are
+// skipped.
+int chroma_distinct_metadata_values(ChromaHandle collection_handle,
+                                    const char *key,
+                                    unsigned int limit,
+                                    struct ChromaResultSet *values_out,
+                                    struct ChromaError **error_out);
+
+// Creates `alias` naming the collection in its tenant and database, so applications can
+// open it with `chroma_resolve_alias` under a stable name while it is rebuilt elsewhere.
+//
+// Aliases are stored in the client's SQLite database and survive restarts. Fails with
+// `ConflictError` if the alias exists; repoint it with `chroma_swap_alias`. An alias is not
+// removed with its collection, resolving it then fails with `NotFound`.
+int chroma_create_alias(ChromaHandle collection_handle,
+                        const char *alias_ptr,
+                        struct ChromaError **error_out);
+
+// Atomically repoints the existing `alias` in the collection's tenant and database to the
+// collection, e.g. once a rebuilt collection is ready: every later `chroma_resolve_alias`
+// opens the new collection, handles opened before keep using the old one.
+//
+// When `previous_id_out` is not null it receives the ID of the collection the alias named
+// before, released with `chroma_free_string`, e.g. to delete it. Fails with `NotFound` if
+// the alias does not exist.
+int chroma_swap_alias(ChromaHandle collection_handle,
+                      const char *alias_ptr,
+                      char **previous_id_out,
+                      struct ChromaError **error_out);
+
+// Opens the collection `alias` names in `tenant` and `database`, which default to the
+// client's default tenant and database when null.
+//
+// On success `collection_handle_out` receives a handle released with
+// `chroma_destroy_collection`. Fails with `NotFound` if the alias does not exist or its
+// collection was deleted.
+int chroma_resolve_alias(ChromaHandle client_handle,
+                         const char *alias_ptr,
+                         const char *tenant_ptr,
+                         const char *database_ptr,
+                         ChromaHandle *collection_handle_out,
+                         struct ChromaError **error_out);
+
+// Removes `alias` from `tenant` and `database`, which default to the client's default
+// tenant and database when null. The collection it names is left untouched. Fails with
+// `NotFound` if the alias does not exist.
+int chroma_delete_alias(ChromaHandle client_handle,
+                        const char *alias_ptr,
+                        const char *tenant_ptr,
+                        const char *database_ptr,
+                        struct ChromaError **error_out);
+
+// Adds documents whose embeddings are given as an f32, f16 or f64 batch.
+//
+// The batch must hold exactly `ids_count` embeddings. All other arguments
+// behave as in `chroma_add`, including `generated_ids_out`, which receives
+// the IDs generated for null `ids`.
+int chroma_add_batch(ChromaHandle collection_handle,
+                     const char *const *ids,
+                     size_t ids_count,
+                     const struct ChromaEmbeddingBatch *embeddings,
+                     const char *const *metadatas_json,
+                     const char *const *documents,
+                     int on_conflict,
+                     struct ChromaAddStats *stats_out,
+                     struct ChromaResultSet *generated_ids_out,
+                     struct ChromaError **error_out);
+
+// Queries a collection with a single embedding given as an f32, f16 or f64 batch.
+//
+// The batch must hold exactly one embedding. All other arguments behave as
+// in `chroma_query`.
+int chroma_query_batch(ChromaHandle collection_handle,
+                       const struct ChromaEmbeddingBatch *query_embedding,
+                       unsigned int n_results,
+                       const char *where_filter_json,
+                       const char *where_document_filter,
+                       const char *const *allowed_ids,
+                       size_t allowed_ids_count,
+                       bool include_embeddings,
+                       bool include_metadatas,
+                       bool include_documents,
+                       bool include_distances,
+                       const char *const *metadata_keys,
+                       size_t metadata_keys_count,
+                       int metadata_format,
+                       struct ChromaQueryResult **result,
+                       struct ChromaError **error_out);
+
+// Benchmarks adds: generates `count` records with random embeddings of `dimension`
+// components from `seed` inside the library and adds them in batches of `batch_size`
+// (0 for 100), timing each backend call.
+//
+// Nothing crosses the FFI boundary per record, so comparing the report to adds made from
+// the host separates marshaling cost from backend cost. The records are really added, with
+// IDs `bench-<run>-<n>`; run it on a scratch collection. The report is a JSON object with
+// `records`, `batches`, `total_ms`, `generate_ms`, `backend_ms`, `records_per_second` and
+// the per-batch `latency` (`min_ms`, `mean_ms`, `p50_ms`, `p95_ms`, `p99_ms`, `max_ms`),
+// allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_bench_add(ChromaHandle collection_handle,
+                     unsigned int count,
+                     size_t dimension,
+                     unsigned int batch_size,
+                     uint64_t seed,
+                     char **report_json_out,
+                     struct ChromaError **error_out);
+
+// Benchmarks queries: runs `queries` queries for `n_results` hits with random query
+// embeddings of `dimension` components generated from `seed` inside the library, reading
+// metadatas, documents and distances as `chroma_query` does by default.
+//
+// The report is a JSON object with `queries`, `hits`, `total_ms`, `backend_ms`,
+// `queries_per_second` and the per-query `latency`, as for `chroma_bench_add`; release it
+// with `chroma_free_string`. Nothing is written to the collection.
+int chroma_bench_query(ChromaHandle collection_handle,
+                       unsigned int queries,
+                       size_t dimension,
+                       unsigned int n_results,
+                       uint64_t seed,
+                       char **report_json_out,
+                       struct ChromaError **error_out);
+
+// Stores binary attachments for existing records, one per ID.
+//
+// Each blob is written to a file under `<persist path>/blobs/<collection id>/` and its
+// `chroma-blob://` URI is recorded as the record's URI, replacing the record's previous
+// blob, whose file is removed. IDs that do not exist are skipped. Requires a client with a
+// persist path. Records deleted later keep their files until the collection's blob
+// directory is removed.
+//
+// `uris_out`, when not null, receives one URI per ID, empty for skipped IDs and blobs;
+// release it with `chroma_free_result_set`.
+int chroma_put_blobs(ChromaHandle collection_handle,
+                     const char *const *ids,
+                     size_t ids_count,
+                     const struct ChromaBlob *blobs,
+                     struct ChromaResultSet *uris_out,
+                     struct ChromaError **error_out);
+
+// Reads the URIs of records, such as those recorded by `chroma_put_blobs`.
+//
+// `uris_out` receives one URI per ID, empty for missing records and records without a
+// URI; release it with `chroma_free_result_set`.
+int chroma_get_uris(ChromaHandle collection_handle,
+                    const char *const *ids,
+                    size_t ids_count,
+                    struct ChromaResultSet *uris_out,
+                    struct ChromaError **error_out);
+
+// Reads a blob stored by `chroma_put_blobs` into a buffer owned by the caller, released
+// with `chroma_free_buffer`
+int chroma_get_blob(ChromaHandle client_handle,
+                    const char *uri_ptr,
+                    uint8_t **out_buffer,
+                    size_t *out_len,
+                    struct ChromaError **error_out);
+
+// Deletes records by ID in batches of `batch_size` (0 for the default of 1000).
+//
+// Batches run one after the other; a failed batch does not stop the following ones.
+// `progress_cb`, when not null, is called on the calling thread after every batch and
+// may cancel the rest. `stats_out`, when not null, receives the counts, and
+// `failed_ids_out`, when not null, the IDs of the failed batches; release them with
+// `chroma_free_result_set`.
+//
+// If any batch failed the call returns the error of the first failed batch, with the
+// number of failures in its details; the outputs are filled in either way.
+int chroma_delete_batched(ChromaHandle collection_handle,
+                          const char *const *ids,
+                          size_t ids_count,
+                          size_t batch_size,
+                          ChromaProgressCallback progress_cb,
+                          void *user_data,
+                          struct ChromaBatchStats *stats_out,
+                          struct ChromaResultSet *failed_ids_out,
+                          struct ChromaError **error_out);
+
+// Starts deleting records by ID in batches of `batch_size` (0 for the default of 1000) on a
+// background thread and returns at once, for hosts that cannot take progress callbacks.
+//
+// `operation_out` receives an operation handle whose progress counts IDs: poll it with
+// `chroma_operation_status`, wait for it with `chroma_operation_wait`, stop it between
+// batches with `chroma_operation_cancel` and release it with `chroma_destroy_operation`.
+// As with `chroma_delete_batched`, a failed batch does not stop the following ones; the
+// operation then fails with the error of the first failed batch.
+int chroma_delete_batched_async(ChromaHandle collection_handle,
+                                const char *const *ids,
+                                size_t ids_count,
+                                size_t batch_size,
+                                ChromaHandle *operation_out,
+                                struct ChromaError **error_out);
+
+// Reads the writes made to a collection after `sequence`, oldest first, as JSON.
+//
+// Each entry of `changes` holds the `seq` number, the `operation` (`add`, `update`,
+// `upsert` or `delete`), the record `id`, the `created_at` timestamp (UTC) and, when the
+// write carried them, the `document`, `metadata` and `embedding`. Pass the returned
+// `next_sequence` back to read the following page; start from 0. `limit` caps the number
+// of entries, 0 means no limit. Once a page is not full, `next_sequence` moves past the
+// writes of other collections as well.
+//
+// The feed reads the local write log, which loses entries once they are compacted and
+// purged. `truncated` is set when entries after `sequence` may already be gone, including
+// when the purge left the log empty, in which case a consumer has to resynchronize from
+// `chroma_get`. Sequence numbers are shared by
+// all collections of the client, so they are increasing but not contiguous.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_changes_since(ChromaHandle collection_handle,
+                         uint64_t sequence,
+                         unsigned int limit,
+                         char **out_json,
+                         struct ChromaError **error_out);
+
+// Copies a collection, its configuration, metadata and records, into a new collection.
+//
+// The target is created as `target_name` in `target_tenant` and `target_database`, which
+// default to the client's default tenant and database when null and may be the source's
+// own.
+// Records are read and written in pages inside the library, so nothing passes through the
+// caller, embeddings included. Records are written with the checks of `chroma_add`, which
+// needs an embedding per record, so `include_embeddings` must be true; false fails with
+// `InvalidArgument` and creates nothing.
+//
+// Soft-deleted records are not copied, and the target is writable even when the source
+// was made read-only with `chroma_set_collection_readonly`.
+//
+// On success `target_handle_out` receives a handle to the new collection, released with
+// `chroma_destroy_collection`, and `copied_out`, when not null, the number of records
+// copied. If copying fails the partial target is deleted. The source must have been
+// opened from `client_handle` and should not be written to during the copy.
+int chroma_copy_collection(ChromaHandle client_handle,
+                           ChromaHandle source_handle,
+                           const char *target_tenant_ptr,
+                           const char *target_database_ptr,
+                           const char *target_name_ptr,
+                           bool include_embeddings,
+                           ChromaHandle *target_handle_out,
+                           size_t *copied_out,
+                           struct ChromaError **error_out);
+
+// Checks whether a collection exists, without opening a handle to it.
+//
+// `tenant_ptr` and `database_ptr` default to the client's default tenant and database when null.
+// A missing collection is not an error: `exists_out` is set to false.
+int chroma_collection_exists(ChromaHandle client_handle,
+                             const char *name_ptr,
+                             const char *tenant_ptr,
+                             const char *database_ptr,
+                             bool *exists_out,
+                             struct ChromaError **error_out);
+
+// Checks which of `ids` exist in a collection.
+//
+// `exists_out` must have room for `ids_count` flags; flag `i` is set to whether `ids[i]`
+// exists. Soft-deleted records count as missing. Only IDs are read from the backend.
+int chroma_records_exist(ChromaHandle collection_handle,
+                         const char *const *ids,
+                         size_t ids_count,
+                         bool *exists_out,
+                         struct ChromaError **error_out);
+
+// Runs a query and reports how it was executed as JSON, to diagnose slow queries.
+//
+// The arguments match `chroma_query` for a single query embedding. The query is executed
+// but its hits are not returned. The report holds the `segments` of the collection (`id`,
+// `type`, `scope`), the number of `log_entries` of the collection still in the write log,
+// the visible `records`, the `candidates` left by the filters (all records without filters),
+// the number of `hits`, the `hnsw_cache` configuration and the `name` and `duration_ms` of
+// each stage (`segments`, `count`, `filter` when filtered, `knn`) with their `total_ms`.
+// `cache_hits` is always null: the index cache keeps no statistics the library can read.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_query_explain(ChromaHandle collection_handle,
+                         const float *query_embedding,
+                         size_t embedding_dim,
+                         unsigned int n_results,
+                         const char *where_filter_json,
+                         const char *where_document_filter,
+                         char **out_json,
+                         struct ChromaError **error_out);
+
+// Declares the metadata keys that where filters of a collection use.
+//
+// The declaration is stored in the collection metadata, so it survives restarts and is
+// shared by every client, and is available to backends that index only declared keys.
+// Filtered `chroma_get` and `chroma_query` calls log a warning for keys outside it. Passing
+// no keys removes the declaration, after which every key is accepted without warning.
+int chroma_set_filterable_fields(ChromaHandle collection_handle,
+                                 const char *const *keys,
+                                 size_t keys_count,
+                                 struct ChromaError **error_out);
+
+// Reads the filterable keys declared for a collection, sorted, into `keys_out`.
+//
+// The set is empty when no keys are declared. Release it with `chroma_free_result_set`.
+int chroma_get_filterable_fields(ChromaHandle collection_handle,
+                                 struct ChromaResultSet *keys_out,
+                                 struct ChromaError **error_out);
+
+// Lists the metadata keys of a where filter that are not declared filterable, sorted,
+// into `keys_out`.
+//
+// The set is empty when every key is declared or the collection declares no keys. Use it
+// to check user-supplied filters before running them. Release the set with
+// `chroma_free_result_set`.
+int chroma_undeclared_filter_keys(ChromaHandle collection_handle,
+                                  const char *where_json,
+                                  struct ChromaResultSet *keys_out,
+                                  struct ChromaError **error_out);
+
+// Creates the collection `name` in the client's default tenant and database and fills it
+// with `count` reproducible records generated inside the library from `seed`, so tests and
+// demos get realistic data without marshaling it.
+//
+// Record `n` has the ID `doc-<n>`, an embedding of `dimension` components uniform in
+// [-1, 1), a document of eight words from a fixed vocabulary and the metadata `index` (`n`),
+// `category` (one of `news`, `sports`, `science`, `finance` and `travel`), `score` (a float
+// in [0, 1)) and `flag` (a bool). The same seed, count and dimension always produce the same
+// records. Fails if the collection exists; if filling it fails it is deleted.
+//
+// On success `collection_handle_out` receives a handle to the new collection, released with
+// `chroma_destroy_collection`.
+int chroma_generate_test_collection(ChromaHandle client_handle,
+                                    const char *name_ptr,
+                                    unsigned int count,
+                                    size_t dimension,
+                                    uint64_t seed,
+                                    ChromaHandle *collection_handle_out,
+                                    struct ChromaError **error_out);
+
+// Gets the IDs of the records matching `ids` and the filters, without reading or
+// marshaling their documents, metadata or embeddings.
+//
+// The arguments match `chroma_get` without sorting; `limit` 0 returns every match.
+// `ids_out` receives the IDs and is released with `chroma_free_result_set`. Soft-deleted
+// records are skipped.
+int chroma_get_ids(ChromaHandle collection_handle,
+                   const char *const *ids,
+                   size_t ids_count,
+                   const char *where_filter_json,
+                   const char *where_document_filter,
+                   unsigned int limit,
+                   unsigned int offset,
+                   struct ChromaResultSet *ids_out,
+                   struct ChromaError **error_out);
+
+// Queries a collection for the IDs of the nearest records, and their distances when
+// `include_distances` is set, without reading or marshaling their contents.
+//
+// The arguments match `chroma_query` for a single query embedding. Only `ids` and
+// `distances` of the result are filled; release it with `chroma_free_query_result`.
+int chroma_query_ids(ChromaHandle collection_handle,
+                     const float *query_embedding,
+                     size_t embedding_dim,
+                     unsigned int n_results,
+                     const char *where_filter_json,
+                     const char *where_document_filter,
+                     bool include_distances,
+                     struct ChromaQueryResult **result,
+                     struct ChromaError **error_out);
+
+// Sets the include flags used by calls on this collection handle that are passed
+// `CHROMA_INCLUDE_DEFAULTS`.
+//
+// `include_flags` is a combination of `CHROMA_INCLUDE_*` flags; `CHROMA_INCLUDE_DEFAULTS`
+// restores the initial defaults of metadatas, documents and distances. The setting
+// belongs to the handle: other handles of the same collection keep their own defaults.
+int chroma_collection_set_default_includes(ChromaHandle collection_handle,
+                                           unsigned int include_flags,
+                                           struct ChromaError **error_out);
+
+// Reads the default include flags of a collection handle
+int chroma_collection_get_default_includes(ChromaHandle collection_handle,
+                                           unsigned int *include_flags_out,
+                                           struct ChromaError **error_out);
+
+// Compacts the write-ahead log of a collection into its local segments and
+// purges the compacted entries.
+//
+// Writes are normally compacted as they arrive; calling this during quiet hours
+// makes sure no backlog is left to be applied by the next query.
+int chroma_optimize_collection(ChromaHandle collection_handle, struct ChromaError **error_out);
+
+// Loads the vector index of a collection into the cache, so the first query after
+// startup does not pay for reading it from disk
+int chroma_preload_collection(ChromaHandle collection_handle, struct ChromaError **error_out);
+
+// Preloads every collection of a database, as `chroma_preload_collection` does.
+//
+// A null `tenant` or `database` selects the default one. When `preloaded_out` is not null it
+// receives the number of collections whose index was loaded; empty collections are skipped.
+int chroma_preload_all(ChromaHandle client_handle,
+                       const char *tenant_ptr,
+                       const char *database_ptr,
+                       size_t *preloaded_out,
+                       struct ChromaError **error_out);
+
+// Creates a new collection in ChromaDB
+int chroma_create_collection(ChromaHandle client_handle,
+                             const char *name_ptr,
+                             const char *config_json_ptr,
+                             const char *metadata_json_ptr,
+                             bool get_or_create,
+                             const char *tenant_ptr,
+                             const char *database_ptr,
+                             ChromaHandle *collection_handle,
+                             struct ChromaError **error_out);
+
+// Gets a collection from ChromaDB
+int chroma_get_collection(ChromaHandle client_handle,
+                          const char *name_ptr,
+                          const char *tenant_ptr,
+                          const char *database_ptr,
+                          ChromaHandle *collection_handle,
+                          struct ChromaError **error_out);
+
+// Gets several collections by name with one call, resolving them concurrently
+//
+// `out_handles` must have room for `names_count` handles. Names that cannot be
+// resolved get `CHROMA_INVALID_HANDLE`; when `out_errors` is not null it must have
+// room for `names_count` errors and receives an error for each of them (null for
+// names that resolved). The call itself succeeds even if some names failed.
+int chroma_get_collections(ChromaHandle client_handle,
+                           const char *const *names,
+                           size_t names_count,
+                           const char *tenant_ptr,
+                           const char *database_ptr,
+                           ChromaHandle *out_handles,
+                           struct ChromaError **out_errors,
+                           struct ChromaError **error_out);
+
+// Gets a collection from ChromaDB by its UUID
+//
+// Lets applications that persist collection IDs reattach to a collection without
+// storing its name. Fails with `NotFound` if the collection belongs to another
+// tenant or database.
+int chroma_get_collection_by_id(ChromaHandle client_handle,
+                                const char *id_ptr,
+                                const char *tenant_ptr,
+                                const char *database_ptr,
+                                ChromaHandle *collection_handle,
+                                struct ChromaError **error_out);
+
+// Checks a collection name locally against the rules the backend applies on create: 3 to
+// 512 characters from `[a-zA-Z0-9._-]`, starting and ending with a letter or digit, without
+// two consecutive periods, and not an IPv4 address.
+//
+// Returns success for a valid name. Otherwise fails with `ValidationError`, and the error
+// details describe the first rule broken, such as the position of a disallowed character,
+// so a UI can explain it before attempting a create.
+int chroma_validate_collection_name(const char *name_ptr, struct ChromaError **error_out);
+
+// Adds documents to a collection
+//
+// `on_conflict` decides what happens to IDs that already exist: `CHROMA_ON_CONFLICT_FAIL`
+// rejects the whole add, `CHROMA_ON_CONFLICT_IGNORE` adds only the new records and
+// `CHROMA_ON_CONFLICT_OVERWRITE` writes every record as `chroma_upsert` would. When
+// `stats_out` is not null it receives how many records were inserted, skipped and overwritten.
+//
+// Pass null `ids` to have `ids_count` UUIDv7 IDs generated, which sort by creation time;
+// when `generated_ids_out` is not null it receives them in record order and is released
+// with `chroma_free_result_set`. It is left empty when IDs are passed in.
+int chroma_add(ChromaHandle collection_handle,
+               const char *const *ids,
+               size_t ids_count,
+               const float *const *embeddings,
+               size_t embedding_dim,
+               const char *const *metadatas_json,
+               const char *const *documents,
+               int on_conflict,
+               struct ChromaAddStats *stats_out,
+               struct ChromaResultSet *generated_ids_out,
+               struct ChromaError **error_out);
+
+// Counts the number of documents in a collection
+int chroma_count(ChromaHandle collection_handle,
+                 unsigned int *result,
+                 struct ChromaError **error_out);
+
+// Counts the documents of a collection that match metadata and document filters.
+//
+// Only the IDs of matching records are read inside the library; nothing else is loaded or
+// returned. With both filters null this is `chroma_count`.
+int chroma_count_where(ChromaHandle collection_handle,
+                       const char *where_filter_json,
+                       const char *where_document_filter,
+                       unsigned int *result,
+                       struct ChromaError **error_out);
+
+// Updates documents in a collection
+//
+// With `where_filter_json` or `where_document_filter` set the update is conditional: only
+// the records matching the filters are updated and the entries of other IDs are skipped.
+// `ids` may then be null to update every matching record, in which case the embedding,
+// metadata and document arrays hold a single entry applied to each of them and `ids_count`
+// is ignored. Soft-deleted records never match.
+//
+// `expected_versions`, when not null, holds one version token from `chroma_get` per ID
+// (a negative entry skips that record) and requires `ids`. If any record is at another
+// version nothing is updated and the call fails with `ConflictError`, whose details list
+// the mismatched IDs. The check and the write hold the collection's write lock, so no other
+// add, update, upsert, delete or batch through the client lands in between.
+//
+// When `updated_ids_out` is not null it receives the IDs the update was applied to;
+// release them with `chroma_free_result_set`.
+int chroma_update(ChromaHandle collection_handle,
+                  const char *const *ids,
+                  size_t ids_count,
+                  const float *const *embeddings,
+                  size_t embedding_dim,
+                  const char *const *metadatas_json,
+                  const char *const *documents,
+                  const char *where_filter_json,
+                  const char *where_document_filter,
+                  const int64_t *expected_versions,
+                  struct ChromaResultSet *updated_ids_out,
+                  struct ChromaError **error_out);
+
+// Upserts documents in a collection (adds if not exists, updates if exists)
+int chroma_upsert(ChromaHandle collection_handle,
+                  const char *const *ids,
+                  size_t ids_count,
+                  const float *const *embeddings,
+                  size_t embedding_dim,
+                  const char *const *metadatas_json,
+                  const char *const *documents,
+                  struct ChromaError **error_out);
+
+// Deletes documents from a collection
+//
+// When `deleted_ids_out` is not null it receives the IDs of the records that matched the
+// IDs and filters right before the delete ran; release them with `chroma_free_result_set`.
+// With `dry_run` set nothing is deleted and `deleted_ids_out`, which is then required,
+// receives the IDs that would have been deleted.
+//
+// With `soft` set the records are kept but flagged as deleted: reads skip them until they
+// are restored with `chroma_restore_records` or removed by `chroma_purge_deleted`.
+// Records that are already soft-deleted do not match a soft delete.
+//
+// `expected_versions`, when not null, holds one version token from `chroma_get` per ID
+// (a negative entry skips that record) and requires `ids`. If any record is at another
+// version nothing is deleted and the call fails with `ConflictError`. As with
+// `chroma_update`, no other write to the collection lands between the check and the delete.
+int chroma_delete(ChromaHandle collection_handle,
+                  const char *const *ids,
+                  size_t ids_count,
+                  const char *where_filter_json,
+                  const char *where_document_filter,
+                  bool dry_run,
+                  bool soft,
+                  const int64_t *expected_versions,
+                  struct ChromaResultSet *deleted_ids_out,
+                  struct ChromaError **error_out);
+
+// Gets documents from a collection
+//
+// The result's `has_more` tells whether records beyond the returned page match. When
+// `count_total` is set, `total_matching_count` receives the number of matching records
+// across all pages, which may take an extra read of their IDs; otherwise it is -1.
+//
+// With `include_versions` set, `versions` receives the version token of each returned
+// record, to pass back as the expected version of `chroma_update` or `chroma_delete`.
+int chroma_get(ChromaHandle collection_handle,
+               const char *const *ids,
+               size_t ids_count,
+               const char *where_filter_json,
+               const char *where_document_filter,
+               unsigned int limit,
+               unsigned int offset,
+               const struct ChromaOrderBy *order_by,
+               bool include_embeddings,
+               bool include_metadatas,
+               bool include_documents,
+               const char *const *metadata_keys,
+               size_t metadata_keys_count,
+               int metadata_format,
+               bool count_total,
+               bool include_versions,
+               struct ChromaQueryResult **result,
+               struct ChromaError **error_out);
+
+// Queries a collection for similar documents
+//
+// `options`, when not null, post-processes the hits before they are marshaled, so fewer
+// than `n_results` may be returned. When the hits are re-ranked, `scores` holds the
+// re-ranker score of each hit.
+int chroma_query(ChromaHandle collection_handle,
+                 const float *query_embeddings,
+                 size_t embedding_dim,
+                 unsigned int n_results,
+                 const char *where_filter_json,
+                 const char *where_document_filter,
+                 const char *const *allowed_ids,
+                 size_t allowed_ids_count,
+                 bool include_embeddings,
+                 bool include_metadatas,
+                 bool include_documents,
+                 bool include_distances,
+                 const char *const *metadata_keys,
+                 size_t metadata_keys_count,
+                 int metadata_format,
+                 const struct ChromaQueryOptions *options,
+                 struct ChromaQueryResult **result,
+                 struct ChromaError **error_out);
+
+// Applies one metadata patch to many records in a single update.
+//
+// `patch_json` is a JSON object of metadata keys. With `CHROMA_METADATA_MERGE` its keys
+// are set on every record, keys set to null are removed and other keys are kept. With
+// `CHROMA_METADATA_REPLACE` the metadata of every record becomes the patch, so keys not
+// in the patch are removed. Embeddings and documents are left unchanged and IDs that do
+// not exist are ignored. The soft deletion keys cannot be patched and fail the call with
+// `InvalidArgument`.
+int chroma_update_metadata(ChromaHandle collection_handle,
+                           const char *const *ids,
+                           size_t ids_count,
+                           const char *patch_json,
+                           int merge_mode,
+                           struct ChromaError **error_out);
+
+// Prepares a query on a collection for repeated execution with
+// `chroma_execute_prepared`.
+//
+// The filters, include flags, metadata projection and options of `template` are parsed and
+// validated once, failing here with the errors `chroma_query` would report. Strings and
+// options passed in `template` are copied and may be released once the call returns; a
+// re-ranker callback passed in them must stay valid for as long as the prepared query,
+// while one set with `chroma_set_reranker` is looked up on each execution. The prepared query
+// keeps the collection alive and is released with `chroma_destroy_prepared_query`.
+int chroma_prepare_query(ChromaHandle collection_handle,
+                         const struct ChromaQueryTemplate *template_,
+                         ChromaHandle *prepared_out,
+                         struct ChromaError **error_out);
+
+// Executes a prepared query for one query embedding.
+//
+// Only the embedding and `n_results` are read; everything else was validated by
+// `chroma_prepare_query`. The result matches that of `chroma_query` and is released with
+// `chroma_free_query_result`.
+int chroma_execute_prepared(ChromaHandle prepared_handle,
+                            const float *query_embedding,
+                            size_t embedding_dim,
+                            unsigned int n_results,
+                            struct ChromaQueryResult **result,
+                            struct ChromaError **error_out);
+
+// Releases a prepared query
+int chroma_destroy_prepared_query(ChromaHandle prepared_handle, struct ChromaError **error_out);
+
+// Makes the records of a collection read-only, or writable again, e.g. to freeze a
+// production collection while it is migrated: reads keep working, and adds, updates,
+// upserts, deletes and the other calls writing records fail with `CollectionReadOnly`.
+//
+// The flag is stored in the collection metadata under `read_only:enabled`, so it survives
+// restarts. It applies at once to every handle of the collection opened from the same
+// client; handles opened by other clients or processes see it once reopened. Changing the
+// collection's configuration, such as its TTL or schema, is still allowed.
+int chroma_set_collection_readonly(ChromaHandle collection_handle,
+                                   bool read_only,
+                                   struct ChromaError **error_out);
+
+// Reports whether the records of a collection are read-only, as set with
+// `chroma_set_collection_readonly`
+int chroma_collection_is_readonly(ChromaHandle collection_handle,
+                                  bool *read_only_out,
+                                  struct ChromaError **error_out);
+
+// Sets the re-ranker used by queries of a client that ask for re-ranking without passing
+// their own callback in `ChromaQueryOptions`.
+//
+// The callback runs on the thread calling `chroma_query`. A null `callback` removes the
+// re-ranker. Replacing or removing it returns once no query runs the previous callback,
+// which is never called again, so its `user_data` may be released then; otherwise it must
+// stay valid until the client is destroyed.
+int chroma_set_reranker(ChromaHandle client_handle,
+                        ChromaRerankCallback callback,
+                        void *user_data,
+                        struct ChromaError **error_out);
+
+// Attaches a metadata schema to a collection, or removes it when `schema_json` is null.
+//
+// The schema is a JSON object mapping metadata keys to `{"type": ..., "required": ...}`,
+// where `type` is `string`, `int`, `float` or `bool` (floats also accept ints) and
+// `required` defaults to false. Keys it does not declare are not checked. Adds, updates,
+// upserts, metadata patches and Arrow adds are then validated against it, failing with
+// `ValidationError` whose details list each violation as `metadatas[index].key: reason`.
+// Upserts must carry every required field, since they may insert records. The schema is
+// stored in the collection metadata; records written before it was attached are not
+// checked.
+int chroma_set_metadata_schema(ChromaHandle collection_handle,
+                               const char *schema_json,
+                               struct ChromaError **error_out);
+
+// Reads the metadata schema of a collection as JSON into `*schema_json_out`, or null when
+// it has none. Release the string with `chroma_free_string`.
+int chroma_get_metadata_schema(ChromaHandle collection_handle,
+                               char **schema_json_out,
+                               struct ChromaError **error_out);
+
+// Reads a single record by ID.
+//
+// `include_flags` combines `CHROMA_INCLUDE_*` flags, or is `CHROMA_INCLUDE_DEFAULTS` for
+// the default includes of the handle; distances do not apply and are ignored. When the
+// record does not exist, `*out_record` is set to null and the call succeeds. Otherwise
+// release the record with `chroma_free_record`.
+int chroma_get_one(ChromaHandle collection_handle,
+                   const char *id,
+                   unsigned int include_flags,
+                   struct ChromaRecord **out_record,
+                   struct ChromaError **error_out);
+
+// Updates a single record.
+//
+// Null `embedding`, `metadata_json` or `document` leave that field unchanged, as in
+// `chroma_update`; metadata keys set to null in `metadata_json` are removed. Updating a
+// record that does not exist has no effect. Errors are reported as by `chroma_update`.
+int chroma_update_one(ChromaHandle collection_handle,
+                      const char *id,
+                      const float *embedding,
+                      size_t embedding_dim,
+                      const char *metadata_json,
+                      const char *document,
+                      struct ChromaError **error_out);
+
+// Deletes a single record, or flags it as deleted with `soft` set (see `chroma_delete`).
+//
+// `deleted_out`, when not null, receives whether the record existed and was deleted.
+int chroma_delete_one(ChromaHandle collection_handle,
+                      const char *id,
+                      bool soft,
+                      bool *deleted_out,
+                      struct ChromaError **error_out);
+
+// Restores soft-deleted records, making them visible to reads again.
+//
+// IDs that do not exist or are not soft-deleted are ignored. When `restored_out` is not
+// null it receives the number of records restored.
+int chroma_restore_records(ChromaHandle collection_handle,
+                           const char *const *ids,
+                           size_t ids_count,
+                           size_t *restored_out,
+                           struct ChromaError **error_out);
+
+// Permanently deletes records that were soft-deleted at least `older_than_secs` seconds ago.
+//
+// Pass 0 to purge every soft-deleted record. When `purged_out` is not null it receives the
+// number of records deleted.
+int chroma_purge_deleted(ChromaHandle collection_handle,
+                         unsigned int older_than_secs,
+                         size_t *purged_out,
+                         struct ChromaError **error_out);
+
+// Begins a write batch on a client.
+//
+// Adds, updates and deletes queued on the batch with `chroma_batch_add`,
+// `chroma_batch_update` and `chroma_batch_delete` are checked when queued but not applied
+// until `chroma_batch_commit`, which writes them all to the write log in one SQLite
+// transaction: either every write is applied or none is. All writes of a batch target one
+// collection, set by the first one. `chroma_batch_abort` discards the batch instead.
+int chroma_batch_begin(ChromaHandle client_handle,
+                       ChromaHandle *batch_out,
+                       struct ChromaError **error_out);
+
+// Queues records to add on a batch. The arguments match `chroma_add`, except that every
+// record needs an embedding; adds of existing IDs are ignored when the batch is committed.
+int chroma_batch_add(ChromaHandle batch_handle,
+                     ChromaHandle collection_handle,
+                     const char *const *ids,
+                     size_t ids_count,
+                     const float *const *embeddings,
+                     size_t embedding_dim,
+                     const char *const *metadatas_json,
+                     const char *const *documents,
+                     struct ChromaError **error_out);
+
+// Queues record updates on a batch. The arguments match `chroma_update` without filters;
+// updates of IDs that do not exist are ignored when the batch is committed.
+int chroma_batch_update(ChromaHandle batch_handle,
+                        ChromaHandle collection_handle,
+                        const char *const *ids,
+                        size_t ids_count,
+                        const float *const *embeddings,
+                        size_t embedding_dim,
+                        const char *const *metadatas_json,
+                        const char *const *documents,
+                        struct ChromaError **error_out);
+
+// Queues a delete on a batch. The IDs and filters match `chroma_delete`; filters are
+// evaluated when the batch is committed, against the records committed before it.
+int chroma_batch_delete(ChromaHandle batch_handle,
+                        ChromaHandle collection_handle,
+                        const char *const *ids,
+                        size_t ids_count,
+                        const char *where_filter_json,
+                        const char *where_document_filter,
+                        struct ChromaError **error_out);
+
+// Applies the writes queued on a batch atomically and closes it.
+//
+// The writes go to the write log in one SQLite transaction, in the order they were queued,
+// and are then applied to the collection's segments. If writing the log fails nothing is
+// applied. Each queued write is checked by the authorizer set with `chroma_set_authorizer`
+// as the matching `add`, `update` or `delete` call, and nothing is applied if any is
+// denied; once committed, each is recorded in the audit log set with `chroma_set_audit_log`.
+// Fails with `NotFound`, applying nothing, if the collection was deleted after the writes
+// were queued. The batch handle is released whether or not the commit succeeds.
+int chroma_batch_commit(ChromaHandle batch_handle, struct ChromaError **error_out);
+
+// Discards the writes queued on a batch and closes it
+int chroma_batch_abort(ChromaHandle batch_handle, struct ChromaError **error_out);
+
+// Sets how long records of a collection live.
+//
+// Records expire `seconds` after the Unix time (in whole seconds, stored as an integer)
+// found under `timestamp_key` in their metadata; records without that key never expire.
+// The policy is stored in the collection metadata, so it survives restarts and is shared
+// by every client. Passing 0 seconds removes it. Expired records are removed by
+// `chroma_purge_expired`.
+int chroma_set_collection_ttl(ChromaHandle collection_handle,
+                              unsigned int seconds,
+                              const char *timestamp_key,
+                              struct ChromaError **error_out);
+
+// Deletes the records of a collection whose time to live has passed.
+//
+// Does nothing on collections without a TTL. When `purged_out` is not null it receives
+// the number of records deleted.
+int chroma_purge_expired(ChromaHandle collection_handle,
+                         size_t *purged_out,
+                         struct ChromaError **error_out);
+
+int chroma_destroy_collection(ChromaHandle collection_handle, struct ChromaError **error_out);
+
+// Returns the UUID of a collection as a string.
+//
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_collection_get_id(ChromaHandle collection_handle,
+                             char **out_id,
+                             struct ChromaError **error_out);
+
+// Checks a metadata filter and a document filter without running them.
+//
+// Either filter may be null. On success `*out_normalized_json` receives a JSON object with
+// the `where` and `where_document` filters that were given, with shorthand equality written
+// as `$eq` and multi-key objects as `$and`. On failure the error details name the path of
+// the rejected part, such as `where.$and[1].year`, and `*out_normalized_json` is null.
+// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_validate_filter(const char *where_json,
+                           const char *where_document,
+                           char **out_normalized_json,
+                           struct ChromaError **error_out);
+
+// Returns the error code of the last function called on this thread that takes
+// `error_out`, or `Success` if it succeeded.
+//
+// Those functions record their outcome here whether or not `error_out` is null, so
+// callers may pass a null `error_out` and query the last error instead.
+int chroma_get_last_error_code(void);
+
+// Copies the message of the last error on this thread into `buf` as a NUL-terminated
+// string, truncated to fit `len` bytes.
+//
+// Returns the buffer size needed for the full message including the terminator, or 0
+// when the last call succeeded. Pass a null `buf` to query the size only.
+size_t chroma_get_last_error_message(char *buf, size_t len);
+
+#if defined(CHROMA_DEBUG_EXPORTS)
+// Panics inside the FFI boundary so bindings can verify panics surface as
+// `InternalError` instead of aborting the process. Only exported by builds with the
+// `debug-exports` feature.
+int chroma_debug_panic(struct ChromaError **error_out);
 #endif
 
-#endif // CHROMA_API_H
\ No newline at end of file
+// Frees an error returned through `error_out`; NULL (the success value) is ignored
+void chroma_free_error(struct ChromaError *error);
+
+// Converts an error into a JSON object for exception mappers and log aggregators.
+//
+// The object has the fields `code`, `name`, `message`, `source`, `details`, `retryable`,
+// `chain` and `request_id`. The string is allocated with `chroma_malloc`; release it with
+// `chroma_free_string`.
+int chroma_error_to_json(const struct ChromaError *error,
+                         char **out_str,
+                         struct ChromaError **error_out);
+
+// Sets the request or correlation ID of the calls made on the calling thread, until it is
+// replaced or cleared with a null `request_id`.
+//
+// The ID is recorded on every error those calls return (`ChromaError::request_id`, the
+// `request_id` field of `chroma_error_to_json` and the last error message) and on the
+// `chroma_ffi` tracing span wrapping their backend calls, so a failure can be traced back
+// to the request that caused it.
+int chroma_set_request_id(const char *request_id, struct ChromaError **error_out);
+
+// Destroys every live client and collection, e.g. when the host unloads the library.
+//
+// Handles issued before the call become invalid and open write batches are aborted.
+int chroma_destroy_all(struct ChromaError **error_out);
+
+// Lists the live handles of one kind for diagnostics.
+//
+// The array is allocated with `chroma_malloc` and must be released with `chroma_free`.
+// An empty registry yields a null array and a zero count.
+int chroma_list_handles(int kind,
+                        ChromaHandle **handles_out,
+                        size_t *count_out,
+                        struct ChromaError **error_out);
+
+// Searches a collection by keywords using the full-text index, ranked with BM25
+int chroma_search(ChromaHandle collection_handle,
+                  const char *query_text,
+                  unsigned int n_results,
+                  const char *where_filter_json,
+                  const struct ChromaRankOptions *rank_options,
+                  struct ChromaQueryResult **result,
+                  struct ChromaError **error_out);
+
+// Runs a vector KNN query and a keyword search, fusing their results
+int chroma_hybrid_search(ChromaHandle collection_handle,
+                         const float *query_embedding,
+                         size_t embedding_dim,
+                         const char *query_text,
+                         unsigned int n_results,
+                         const char *where_filter_json,
+                         const struct ChromaRankOptions *rank_options,
+                         const struct ChromaFusionOptions *fusion_options,
+                         struct ChromaQueryResult **result,
+                         struct ChromaError **error_out);
+
+// Runs the same embedding query against several collections concurrently.
+//
+// All collections must have been opened from the same client. With `CHROMA_MERGE_NONE`,
+// `results_out` must have room for `collections_count` results, one per collection in the
+// given order. With `CHROMA_MERGE_BY_DISTANCE` it receives a single result holding the
+// `n_results` closest matches over all collections. Distances are always included; release
+// every result with `chroma_free_query_result`.
+int chroma_query_multi(const ChromaHandle *collection_handles,
+                       size_t collections_count,
+                       const float *query_embedding,
+                       size_t embedding_dim,
+                       unsigned int n_results,
+                       const char *where_filter_json,
+                       const char *where_document_filter,
+                       bool include_metadatas,
+                       bool include_documents,
+                       int merge_strategy,
+                       struct ChromaQueryResult **results_out,
+                       struct ChromaError **error_out);
+
+// Attaches sparse vectors to existing records, one per ID.
+//
+// Each vector is stored in the record's metadata under `sparse_key`, or `sparse_embedding`
+// when null, next to the record's dense embedding; IDs that do not exist are ignored.
+// Indices need not be sorted but must be unique.
+int chroma_add_sparse(ChromaHandle collection_handle,
+                      const char *const *ids,
+                      size_t ids_count,
+                      const struct ChromaSparseVector *vectors,
+                      const char *sparse_key_ptr,
+                      struct ChromaError **error_out);
+
+// Searches a collection by a sparse vector, optionally fused with a dense vector query.
+//
+// Records are scored by the dot product of their sparse vector under `sparse_key` (or
+// `sparse_embedding` when null) with `query`; records without one, or with a score of 0,
+// do not match. The scan reads every record passing `where_filter_json`, so narrow large
+// collections with a filter.
+//
+// Without `query_embedding` the best `n_results` sparse matches are returned with their
+// scores in `scores`. With it, a dense query runs as well and both rankings are fused as
+// in `chroma_hybrid_search`: `scores` holds the fused score, `distances` the dense distance
+// and `text_scores` the sparse score.
+int chroma_query_sparse(ChromaHandle collection_handle,
+                        const struct ChromaSparseVector *query,
+                        const char *sparse_key_ptr,
+                        const float *query_embedding,
+                        size_t embedding_dim,
+                        unsigned int n_results,
+                        const char *where_filter_json,
+                        const struct ChromaFusionOptions *fusion_options,
+                        struct ChromaQueryResult **result,
+                        struct ChromaError **error_out);
+
+// Queries a collection and returns the whole response encoded in a single buffer.
+//
+// `query_embeddings` holds `query_count` embeddings of `embedding_dim` floats each.
+// The buffer must be released with `chroma_free_buffer`.
+int chroma_query_serialized(ChromaHandle collection_handle,
+                            const float *query_embeddings,
+                            size_t embedding_dim,
+                            size_t query_count,
+                            unsigned int n_results,
+                            const char *where_filter_json,
+                            const char *where_document_filter,
+                            bool include_embeddings,
+                            bool include_metadatas,
+                            bool include_documents,
+                            bool include_distances,
+                            int format,
+                            uint8_t **out_buffer,
+                            size_t *out_len,
+                            struct ChromaError **error_out);
+
+// Gets records from a collection and returns the whole response encoded in a single buffer.
+//
+// The buffer must be released with `chroma_free_buffer`.
+int chroma_get_serialized(ChromaHandle collection_handle,
+                          const char *const *ids,
+                          size_t ids_count,
+                          const char *where_filter_json,
+                          const char *where_document_filter,
+                          unsigned int limit,
+                          unsigned int offset,
+                          bool include_embeddings,
+                          bool include_metadatas,
+                          bool include_documents,
+                          int format,
+                          uint8_t **out_buffer,
+                          size_t *out_len,
+                          struct ChromaError **error_out);
+
+// Adds records decoded from an Arrow IPC stream.
+//
+// The stream must contain an `id` Utf8 column and an `embedding` column of
+// `FixedSizeList<Float32>` or `List<Float32>`. Optional `document` (Utf8) and
+// `metadata` (struct, or Utf8 JSON objects) columns are read when present.
+int chroma_add_arrow(ChromaHandle collection_handle,
+                     const uint8_t *ipc_buffer,
+                     size_t ipc_len,
+                     struct ChromaError **error_out);
+
+// Frees a buffer returned by a serialized result function
+void chroma_free_buffer(uint8_t *buffer);
+
+// Frees the IDs held by a result set and resets it to empty; the struct itself is not freed
+void chroma_free_result_set(struct ChromaResultSet *result_set);
+
+void chroma_free_query_result(struct ChromaQueryResult *result);
+
+// Frees a record returned by `chroma_get_one` and every string and array it points to
+void chroma_free_record(struct ChromaRecord *record);
+
+// Allocates memory with the library's allocator; release it with `chroma_free`
+void *chroma_malloc(size_t size);
+
+// Frees memory allocated by the library, including strings, arrays and buffers
+void chroma_free(void *ptr);
+
+// Frees memory allocated for C strings
+void chroma_free_string(char *s);
+
+// Frees memory allocated for C string arrays
+void chroma_free_string_array(char **array, size_t count);
+
+// Generates a UUID and writes it to `*out_str` in the hyphenated lowercase form the library
+// uses for collection and database IDs, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+//
+// `version` is `CHROMA_UUID_V4` for a random UUID, or `CHROMA_UUID_V7` for one that starts
+// with a millisecond timestamp, so IDs generated in order also sort in order; 0 selects
+// `CHROMA_UUID_V4`. The string is allocated with `chroma_malloc`; release it with
+// `chroma_free_string`.
+int chroma_new_uuid(int version, char **out_str, struct ChromaError **error_out);
+
+// Checks that `uuid_str` is a UUID the library accepts as an ID. Besides the hyphenated form,
+// the simple (32 hex digits), braced and `urn:uuid:` forms are accepted, in any case.
+//
+// Fails with `InvalidUuid`, whose details give the parse error, for anything else. On
+// success `*canonical_out`, when not null, receives the hyphenated lowercase form the
+// library stores and returns, which IDs should be compared in; it is allocated with
+// `chroma_malloc`; release it with `chroma_free_string`.
+int chroma_validate_uuid(const char *uuid_str,
+                         char **canonical_out,
+                         struct ChromaError **error_out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHROMA_API_H */
@@ -0,0 +1,138 @@
+// Description of the C ABI exported by the library
+use libc::{c_char, c_int, c_uint, size_t};
+use std::fs;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+
+use crate::error::{
+    ffi_boundary, ffi_boundary_or, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::utils::c_str_to_string;
+
+/// Version of the C ABI, raised whenever an exported signature or the layout of a
+/// `#[repr(C)]` type changes, so bindings built against another version can be detected.
+///
+/// Fields appended to a size-prefixed input struct do not change the ABI version.
+pub const CHROMA_ABI_VERSION: c_uint = 10;
+
+/// Input structs that begin with a `struct_size` field, set by the caller to the size of
+/// the struct it was compiled against.
+///
+/// New fields are only ever appended to these structs, and a zero in a new field must
+/// keep the behavior from before the field existed, so callers built against an older
+/// layout keep working.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]`, have a `size_t` as first field and be valid when
+/// every byte is zero.
+pub(crate) unsafe trait SizedInput: Sized {}
+
+/// Reads a size-prefixed input struct passed by the caller; null reads as `None`.
+///
+/// Fields beyond `struct_size` are missing from the caller's layout and read as zero. A
+/// `struct_size` larger than this library's layout means the caller sets fields this
+/// library does not know, which is rejected rather than silently ignored.
+///
+/// # Safety
+///
+/// A non-null `ptr` must point to at least `struct_size` readable bytes.
+pub(crate) unsafe fn read_sized<T: SizedInput>(ptr: *const T) -> Result<Option<T>, FfiError> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+
+    let size = ptr::read_unaligned(ptr as *const size_t);
+    let min = mem::size_of::<size_t>();
+    let max = mem::size_of::<T>();
+    if size < min || size > max {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid struct size",
+            Some(format!(
+                "{} has struct_size {}, expected {} to {} bytes",
+                std::any::type_name::<T>().rsplit("::").next().unwrap_or_default(),
+                size,
+                min,
+                max
+            )),
+        ));
+    }
+
+    let mut value = MaybeUninit::<T>::zeroed();
+    ptr::copy_nonoverlapping(ptr as *const u8, value.as_mut_ptr() as *mut u8, size);
+    Ok(Some(value.assume_init()))
+}
+
+/// C header generated by cbindgen from these sources when the library was built
+const HEADER: &str = include_str!(concat!(env!("OUT_DIR"), "/chroma_api.h"));
+
+/// Writes the C header declaring every exported function, struct, enum and constant of
+/// this build of the library to `path`, replacing any existing file.
+///
+/// The header is generated from the Rust sources at build time, so it always matches the
+/// library it comes from and can drive code generation of bindings.
+#[no_mangle]
+pub extern "C" fn chroma_emit_header(
+    path_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_emit_header", || {
+        let func_name = "chroma_emit_header";
+
+        if path_ptr.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Header path pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let path = match unsafe { c_str_to_string(path_ptr) } {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid header path",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        if let Err(e) = fs::write(&path, HEADER) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InternalError,
+                "Failed to write header",
+                func_name,
+                Some(&format!("{}: {}", path, e)),
+            );
+            return ChromaErrorCode::InternalError as c_int;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Returns the ABI version of this build of the library, `CHROMA_ABI_VERSION`
+#[no_mangle]
+pub extern "C" fn chroma_abi_version() -> c_uint {
+    ffi_boundary_or("chroma_abi_version", 0, || CHROMA_ABI_VERSION)
+}
+
+/// Checks whether bindings built against ABI version `expected` can use this library.
+///
+/// Versions are not backward compatible: any difference means signatures or struct layouts
+/// may differ, so callers should refuse to make further calls rather than risk corrupting
+/// memory.
+#[no_mangle]
+pub extern "C" fn chroma_is_compatible(expected: c_uint) -> bool {
+    ffi_boundary_or("chroma_is_compatible", false, || expected == CHROMA_ABI_VERSION)
+}
@@ -0,0 +1,234 @@
+// Log records of the library and the Chroma backend forwarded to host callbacks
+use libc::{c_char, c_int, c_void};
+use std::cell::Cell;
+use std::ffi::CString;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::OnceLock;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::{Interest, Subscriber};
+use tracing::{Event, Level, Metadata};
+
+use crate::callbacks::{register_callback, registered_callbacks, Callback, ChromaCallbackId};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+
+/// Levels passed to and accepted by log callbacks, from least to most verbose
+pub const CHROMA_LOG_ERROR: c_int = 1;
+pub const CHROMA_LOG_WARN: c_int = 2;
+pub const CHROMA_LOG_INFO: c_int = 3;
+pub const CHROMA_LOG_DEBUG: c_int = 4;
+pub const CHROMA_LOG_TRACE: c_int = 5;
+
+/// Receives one log record: its `CHROMA_LOG_*` level, the module that emitted it and the
+/// message followed by its fields as ` key=value`. Both strings are UTF-8 and only valid
+/// during the call.
+pub type ChromaLogFn = extern "C" fn(
+    user_data: *mut c_void,
+    level: c_int,
+    target: *const c_char,
+    message: *const c_char,
+);
+
+/// A `ChromaLogFn`, or null for none
+pub type ChromaLogCallback = Option<ChromaLogFn>;
+
+/// A log callback, the caller's `user_data` and the most verbose level it receives
+pub(crate) struct LogCallback {
+    callback: ChromaLogFn,
+    user_data: *mut c_void,
+    max_level: c_int,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// thread that logged; keeping it valid until unregistration is up to the caller
+unsafe impl Send for LogCallback {}
+unsafe impl Sync for LogCallback {}
+
+impl Callback {
+    fn as_log(&self) -> Option<&LogCallback> {
+        match self {
+            Callback::Log(log) => Some(log),
+            _ => None,
+        }
+    }
+}
+
+/// Most verbose level any registered log callback receives, 0 when none is registered
+static MAX_LEVEL: AtomicI32 = AtomicI32::new(0);
+
+/// Whether the callback subscriber became the process-wide tracing subscriber
+static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+thread_local! {
+    /// Set while log callbacks run, so records the host causes from within them are dropped
+    /// instead of recursing
+    static IN_LOG_CALLBACK: Cell<bool> = const { Cell::new(false) };
+}
+
+fn level_number(level: &Level) -> c_int {
+    match *level {
+        Level::ERROR => CHROMA_LOG_ERROR,
+        Level::WARN => CHROMA_LOG_WARN,
+        Level::INFO => CHROMA_LOG_INFO,
+        Level::DEBUG => CHROMA_LOG_DEBUG,
+        Level::TRACE => CHROMA_LOG_TRACE,
+    }
+}
+
+/// Recomputes the most verbose level to forward after a registration change
+pub(crate) fn refresh_log_level() {
+    let max_level = registered_callbacks()
+        .iter()
+        .filter_map(|registration| registration.callback.as_log().map(|log| log.max_level))
+        .max()
+        .unwrap_or(0);
+    MAX_LEVEL.store(max_level, Ordering::SeqCst);
+}
+
+/// Collects the message and fields of an event into one line
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Tracing subscriber forwarding events to the registered log callbacks. Spans are not
+/// tracked; only events are forwarded.
+struct CallbackSubscriber;
+
+impl Subscriber for CallbackSubscriber {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // Callbacks come and go, so every callsite is checked again with `enabled`
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        level_number(metadata.level()) <= MAX_LEVEL.load(Ordering::Relaxed)
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if IN_LOG_CALLBACK.with(Cell::get) {
+            return;
+        }
+
+        let level = level_number(event.metadata().level());
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let to_c = |s: &str| CString::new(s.replace('\0', "")).unwrap_or_default();
+        let target = to_c(event.metadata().target());
+        let message = to_c(&(visitor.message + &visitor.fields));
+
+        IN_LOG_CALLBACK.with(|flag| flag.set(true));
+        for registration in registered_callbacks() {
+            registration.invoke(|callback| {
+                if let Some(log) = callback.as_log().filter(|log| level <= log.max_level) {
+                    (log.callback)(log.user_data, level, target.as_ptr(), message.as_ptr());
+                }
+            });
+        }
+        IN_LOG_CALLBACK.with(|flag| flag.set(false));
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// Registers a callback receiving the log records of the library and the Chroma backend up
+/// to `max_level` (`CHROMA_LOG_ERROR` .. `CHROMA_LOG_TRACE`), and writes its ID to
+/// `*callback_id`.
+///
+/// The callback runs on the thread that logged, possibly several threads at once, and
+/// until `chroma_unregister_callback` returns for the ID; after that it is never called
+/// again. Records logged from within the callback are dropped. The first registration
+/// installs the library as the process-wide tracing subscriber and fails with
+/// `InternalError` if another one was installed before.
+#[no_mangle]
+pub extern "C" fn chroma_register_log_callback(
+    callback: ChromaLogCallback,
+    user_data: *mut c_void,
+    max_level: c_int,
+    callback_id: *mut ChromaCallbackId,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_register_log_callback", || {
+        let func_name = "chroma_register_log_callback";
+
+        let callback = match callback {
+            Some(callback) if !callback_id.is_null() => callback,
+            _ => {
+                let message = if callback.is_none() {
+                    "Log callback is null"
+                } else {
+                    "Callback ID output pointer is null"
+                };
+
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    message,
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        if !(CHROMA_LOG_ERROR..=CHROMA_LOG_TRACE).contains(&max_level) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown log level",
+                func_name,
+                Some(&format!("Got {}, expected 1 (error) to 5 (trace)", max_level)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let installed = *INSTALLED
+            .get_or_init(|| tracing::subscriber::set_global_default(CallbackSubscriber).is_ok());
+        if !installed {
+            set_error(
+                error_out,
+                ChromaErrorCode::InternalError,
+                "Another tracing subscriber is installed in this process",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InternalError as c_int;
+        }
+
+        let id = register_callback(Callback::Log(LogCallback {
+            callback,
+            user_data,
+            max_level,
+        }));
+        refresh_log_level();
+
+        unsafe {
+            *callback_id = id;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
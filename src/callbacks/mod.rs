@@ -0,0 +1,198 @@
+// Registry of callbacks into the host, such as log sinks, change subscriptions, the
+// re-ranker, authorizer and audit log of a client and the progress callback of a bulk delete
+//
+// Callbacks are plain `extern "C"` function pointers with a `user_data` pointer, so hosts
+// can pass `[UnmanagedCallersOnly]` functions under .NET NativeAOT instead of delegates the
+// GC may collect. Every registration gets an ID; `chroma_unregister_callback` returns only
+// once no invocation of the callback is running, and the callback is never invoked again
+// afterwards, so the host may free `user_data` as soon as the call returns.
+use libc::c_int;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::client::{AuditCallback, Authorizer};
+use crate::collection::{ProgressCallback, Reranker};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+
+mod log;
+mod watch;
+
+pub use log::*;
+pub use watch::*;
+
+/// Identifies a registered callback; 0 never does
+pub type ChromaCallbackId = u64;
+
+/// A registered callback of any kind
+pub(crate) enum Callback {
+    Log(LogCallback),
+    Change(ChangeCallback),
+    Rerank(Reranker),
+    Progress(ProgressCallback),
+    Authorize(Authorizer),
+    Audit(AuditCallback),
+}
+
+/// A callback together with the gate that keeps it from running after unregistration
+pub(crate) struct Registration {
+    id: ChromaCallbackId,
+    callback: Callback,
+    active: AtomicBool,
+    /// Held shared by every invocation and exclusively by `chroma_unregister_callback`
+    gate: RwLock<()>,
+}
+
+thread_local! {
+    /// Registrations whose callback is running on this thread
+    static INVOKING: RefCell<Vec<ChromaCallbackId>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Registration {
+    /// Runs `invoke` with the callback unless it was unregistered
+    pub(crate) fn invoke(&self, invoke: impl FnOnce(&Callback)) {
+        let _gate = self.gate.read().unwrap_or_else(|e| e.into_inner());
+        if !self.active.load(Ordering::SeqCst) {
+            return;
+        }
+
+        INVOKING.with(|invoking| invoking.borrow_mut().push(self.id));
+        invoke(&self.callback);
+        INVOKING.with(|invoking| invoking.borrow_mut().pop());
+    }
+
+    /// Stops further invocations and waits for the running ones to finish, unless called
+    /// from within the callback itself, which then finishes on its own
+    fn deactivate(&self) {
+        self.active.store(false, Ordering::SeqCst);
+        let reentrant = INVOKING.with(|invoking| invoking.borrow().contains(&self.id));
+        if !reentrant {
+            drop(self.gate.write().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CALLBACKS: LazyLock<RwLock<BTreeMap<ChromaCallbackId, Arc<Registration>>>> =
+    LazyLock::new(|| RwLock::new(BTreeMap::new()));
+
+/// Registers a callback and returns its ID
+pub(crate) fn register_callback(callback: Callback) -> ChromaCallbackId {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let registration = Arc::new(Registration {
+        id,
+        callback,
+        active: AtomicBool::new(true),
+        gate: RwLock::new(()),
+    });
+    CALLBACKS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, registration);
+    id
+}
+
+/// Registrations currently active, in registration order
+pub(crate) fn registered_callbacks() -> Vec<Arc<Registration>> {
+    let callbacks = CALLBACKS.read().unwrap_or_else(|e| e.into_inner());
+    callbacks.values().cloned().collect()
+}
+
+/// Registration of callback `id`, unless it was unregistered
+pub(crate) fn find_callback(id: ChromaCallbackId) -> Option<Arc<Registration>> {
+    let callbacks = CALLBACKS.read().unwrap_or_else(|e| e.into_inner());
+    callbacks.get(&id).cloned()
+}
+
+/// Unregisters callback `id` as `chroma_unregister_callback` does, returning false if it
+/// is not registered
+pub(crate) fn unregister_callback(id: ChromaCallbackId) -> bool {
+    let registration = CALLBACKS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&id);
+    let Some(registration) = registration else {
+        return false;
+    };
+
+    registration.deactivate();
+    on_unregistered(&registration.callback);
+    true
+}
+
+/// The one callback of a kind a client holds, such as its re-ranker; it is unregistered when
+/// replaced and when the client is released
+#[derive(Default)]
+pub(crate) struct CallbackSlot(RwLock<Option<ChromaCallbackId>>);
+
+impl CallbackSlot {
+    /// Registers `callback` in place of the current one, and returns once no invocation of
+    /// the one replaced is running
+    pub(crate) fn replace(&self, callback: Option<Callback>) {
+        let id = callback.map(register_callback);
+        let previous = {
+            let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *current, id)
+        };
+        if let Some(previous) = previous {
+            unregister_callback(previous);
+        }
+    }
+
+    /// Registration of the current callback, if any
+    pub(crate) fn registration(&self) -> Option<Arc<Registration>> {
+        let id = (*self.0.read().unwrap_or_else(|e| e.into_inner()))?;
+        find_callback(id)
+    }
+}
+
+impl Drop for CallbackSlot {
+    fn drop(&mut self) {
+        let current = self.0.get_mut().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(id) = current {
+            unregister_callback(id);
+        }
+    }
+}
+
+/// Unregisters a callback registered with a `chroma_register_*_callback` function.
+///
+/// Returns once no invocation of the callback is running; it is never invoked afterwards,
+/// so `user_data` may be released when this returns. Called from within the callback
+/// itself, it returns at once and the running invocation is the last one.
+#[no_mangle]
+pub extern "C" fn chroma_unregister_callback(
+    callback_id: ChromaCallbackId,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_unregister_callback", || {
+        let func_name = "chroma_unregister_callback";
+
+        if !unregister_callback(callback_id) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Callback ID is invalid or already unregistered",
+                func_name,
+                Some(&format!("Callback ID: {}", callback_id)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Updates the state derived from the registered callbacks of one kind
+fn on_unregistered(callback: &Callback) {
+    match callback {
+        Callback::Log(_) => refresh_log_level(),
+        Callback::Change(_)
+        | Callback::Rerank(_)
+        | Callback::Progress(_)
+        | Callback::Authorize(_)
+        | Callback::Audit(_) => {}
+    }
+}
@@ -0,0 +1,355 @@
+// Subscriptions to the changes of a collection, observed through the write log
+use libc::{c_char, c_int, c_void};
+use sqlx::Row;
+use std::ffi::CString;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+
+use crate::callbacks::{
+    find_callback, register_callback, unregister_callback, Callback, ChromaCallbackId,
+};
+use crate::collection::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Events passed to change callbacks, combined into the mask of `chroma_subscribe`
+pub const CHROMA_EVENT_ADD: c_int = 1;
+pub const CHROMA_EVENT_UPDATE: c_int = 2;
+pub const CHROMA_EVENT_UPSERT: c_int = 4;
+pub const CHROMA_EVENT_DELETE: c_int = 8;
+pub const CHROMA_EVENT_DROP: c_int = 16;
+pub const CHROMA_EVENT_GAP: c_int = 32;
+pub const CHROMA_EVENT_ALL: c_int = 63;
+
+/// How often subscriptions read the write log for new entries
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Log entries read per query while catching up
+const WATCH_PAGE_SIZE: i64 = 1000;
+
+/// Receives one change: its `CHROMA_EVENT_*` event, the ID of the collection, the ID of the
+/// record, null for `CHROMA_EVENT_DROP` and `CHROMA_EVENT_GAP`, and the sequence number of the
+/// log entry, 0 for `CHROMA_EVENT_DROP` and the oldest entry left for `CHROMA_EVENT_GAP`. Both
+/// strings are UTF-8 and only valid during the call.
+pub type ChromaChangeFn = extern "C" fn(
+    user_data: *mut c_void,
+    event: c_int,
+    collection_id: *const c_char,
+    record_id: *const c_char,
+    sequence: u64,
+);
+
+/// A `ChromaChangeFn`, or null for none
+pub type ChromaChangeCallback = Option<ChromaChangeFn>;
+
+/// A change callback, the caller's `user_data` and the events it receives
+pub(crate) struct ChangeCallback {
+    callback: ChromaChangeFn,
+    user_data: *mut c_void,
+    event_mask: c_int,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// watcher thread; keeping it valid until unregistration is up to the caller
+unsafe impl Send for ChangeCallback {}
+unsafe impl Sync for ChangeCallback {}
+
+impl Callback {
+    fn as_change(&self) -> Option<&ChangeCallback> {
+        match self {
+            Callback::Change(change) => Some(change),
+            _ => None,
+        }
+    }
+}
+
+/// Event of a write log operation code
+fn event_of(operation: i64) -> c_int {
+    match operation {
+        0 => CHROMA_EVENT_ADD,
+        1 => CHROMA_EVENT_UPDATE,
+        2 => CHROMA_EVENT_UPSERT,
+        _ => CHROMA_EVENT_DELETE,
+    }
+}
+
+/// Entries of the write log of a collection after `sequence`, the bounds of the whole log
+/// and whether the collection was dropped, read from one snapshot of the log
+struct LogPoll {
+    entries: Vec<(i64, c_int, String)>,
+    /// Sequence number of the oldest entry left in the log, `None` when it is empty
+    oldest: Option<i64>,
+    /// Sequence number of the newest entry of the log, 0 when it is empty
+    newest: i64,
+    dropped: bool,
+}
+
+/// Sequence number of the newest entry of the write log, shared by every collection
+fn last_sequence(collection: &ChromaCollection) -> Result<i64, FfiError> {
+    let client = &collection.client;
+    let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+        return Err(FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Write log is not available",
+            Some("The SQLite database is not registered".to_string()),
+        ));
+    };
+
+    let query = sqlx::query_scalar("SELECT COALESCE(MAX(seq_id), 0) FROM embeddings_queue");
+    client
+        .runtime
+        .block_on(query.fetch_one(sqlite_db.get_conn()))
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to read the write log",
+                Some(e.to_string()),
+            )
+        })
+}
+
+/// Reads up to a page of the entries of a collection's write log after `sequence`
+fn poll_log(collection: &ChromaCollection, sequence: i64) -> Result<LogPoll, sqlx::Error> {
+    let client = &collection.client;
+    let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+        return Ok(LogPoll {
+            entries: Vec::new(),
+            oldest: None,
+            newest: 0,
+            dropped: false,
+        });
+    };
+
+    let topic = collection.id.to_string();
+    client.runtime.block_on(async {
+        // One read transaction, so that the bounds match the entries read
+        let mut snapshot = sqlite_db.get_conn().begin().await?;
+        let (oldest, newest): (Option<i64>, i64) =
+            sqlx::query_as("SELECT MIN(seq_id), COALESCE(MAX(seq_id), 0) FROM embeddings_queue")
+                .fetch_one(&mut *snapshot)
+                .await?;
+        let rows = sqlx::query(
+            "SELECT seq_id, operation, id FROM embeddings_queue \
+             WHERE seq_id > ? AND (topic = ? OR topic LIKE '%/' || ?) \
+             ORDER BY seq_id LIMIT ?",
+        )
+        .bind(sequence)
+        .bind(&topic)
+        .bind(&topic)
+        .bind(WATCH_PAGE_SIZE)
+        .fetch_all(&mut *snapshot)
+        .await?;
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM collections WHERE id = ?")
+            .bind(&topic)
+            .fetch_one(&mut *snapshot)
+            .await?;
+        snapshot.commit().await?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let seq: i64 = row.try_get(0)?;
+            let operation: i64 = row.try_get(1)?;
+            let id: String = row.try_get(2)?;
+            entries.push((seq, event_of(operation), id));
+        }
+        Ok(LogPoll {
+            entries,
+            oldest,
+            newest,
+            dropped: exists == 0,
+        })
+    })
+}
+
+/// Reads the write log of a collection and forwards its new entries to callback `id` until
+/// the callback is unregistered, the collection handle is released or the collection dropped
+fn run_watch(id: ChromaCallbackId, collection: Weak<ChromaCollection>, mut sequence: i64) {
+    loop {
+        thread::sleep(WATCH_POLL_INTERVAL);
+        let Some(registration) = find_callback(id) else {
+            return;
+        };
+        let Some(collection) = collection.upgrade() else {
+            return;
+        };
+        let collection_id = CString::new(collection.id.to_string()).unwrap_or_default();
+
+        loop {
+            // Read errors are transient, such as a locked database; the next tick tries again
+            let Ok(poll) = poll_log(&collection, sequence) else {
+                break;
+            };
+            let full_page = poll.entries.len() as i64 == WATCH_PAGE_SIZE;
+
+            // `chroma_optimize_collection` purged entries after `sequence` before they were
+            // read; some of them may have been changes of this collection
+            if let Some(oldest) = poll.oldest.filter(|&oldest| sequence.saturating_add(1) < oldest)
+            {
+                registration.invoke(|callback| {
+                    if let Some(change) = callback.as_change() {
+                        (change.callback)(
+                            change.user_data,
+                            CHROMA_EVENT_GAP,
+                            collection_id.as_ptr(),
+                            std::ptr::null(),
+                            oldest as u64,
+                        );
+                    }
+                });
+                sequence = oldest - 1;
+            }
+
+            for (seq, event, record_id) in poll.entries {
+                sequence = seq;
+                let record_id = CString::new(record_id).unwrap_or_default();
+                registration.invoke(|callback| {
+                    if let Some(change) = callback.as_change().filter(|c| c.event_mask & event != 0)
+                    {
+                        (change.callback)(
+                            change.user_data,
+                            event,
+                            collection_id.as_ptr(),
+                            record_id.as_ptr(),
+                            seq as u64,
+                        );
+                    }
+                });
+            }
+
+            if poll.dropped {
+                registration.invoke(|callback| {
+                    if let Some(change) =
+                        callback.as_change().filter(|c| c.event_mask & CHROMA_EVENT_DROP != 0)
+                    {
+                        (change.callback)(
+                            change.user_data,
+                            CHROMA_EVENT_DROP,
+                            collection_id.as_ptr(),
+                            std::ptr::null(),
+                            0,
+                        );
+                    }
+                });
+                return;
+            }
+            if !full_page {
+                // Entries of other collections up to `newest` were read past as well, so that
+                // purging them is not mistaken for a gap
+                sequence = sequence.max(poll.newest);
+                break;
+            }
+        }
+    }
+}
+
+/// Subscribes a callback to the changes of a collection and writes its ID to
+/// `*callback_id`; `chroma_unregister_callback` ends the subscription.
+///
+/// `event_mask` combines the `CHROMA_EVENT_*` events to receive. Adds, updates, upserts and
+/// deletes are read from the write log of the client, so they are observed whichever handle,
+/// batch or process wrote them, in log order, within about 50 ms; writes made before the
+/// subscription are not reported. `CHROMA_EVENT_DROP` is delivered once when the collection
+/// is deleted, such as with its database or by another process sharing the persist
+/// directory, after which the subscription stops reading the log.
+///
+/// `chroma_optimize_collection` purges compacted entries from the log, also while
+/// subscriptions read it. When entries were purged before the subscription read them,
+/// `CHROMA_EVENT_GAP` is delivered whatever `event_mask` holds, with the sequence number of
+/// the oldest entry left: changes before it may have been missed, so a subscriber caching
+/// records has to resynchronize from `chroma_get`. Delivery then resumes from that entry.
+///
+/// The callback runs on a watcher thread of the subscription, one change at a time, and
+/// until `chroma_unregister_callback` returns for the ID; after that it is never called
+/// again. The subscription also stops once the collection handle is destroyed. Fails with
+/// `InternalError` on clients without a local write log.
+#[no_mangle]
+pub extern "C" fn chroma_subscribe(
+    collection_handle: ChromaHandle,
+    event_mask: c_int,
+    callback: ChromaChangeCallback,
+    user_data: *mut c_void,
+    callback_id: *mut ChromaCallbackId,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_subscribe", || {
+        let func_name = "chroma_subscribe";
+
+        let callback = match callback {
+            Some(callback)
+                if collection_handle != CHROMA_INVALID_HANDLE && !callback_id.is_null() =>
+            {
+                callback
+            }
+            _ => {
+                let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                    "Collection handle is null"
+                } else if callback.is_none() {
+                    "Change callback is null"
+                } else {
+                    "Callback ID output pointer is null"
+                };
+
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    message,
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        if event_mask & !CHROMA_EVENT_ALL != 0 || event_mask == 0 {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown event mask",
+                func_name,
+                Some(&format!(
+                    "Got {}, expected a combination of CHROMA_EVENT_* flags (1 to 63)",
+                    event_mask
+                )),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let sequence = match last_sequence(&collection) {
+            Ok(sequence) => sequence,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let id = register_callback(Callback::Change(ChangeCallback {
+            callback,
+            user_data,
+            event_mask,
+        }));
+        let watched = Arc::downgrade(&collection);
+        let started = thread::Builder::new()
+            .name("chroma-watch".to_string())
+            .spawn(move || run_watch(id, watched, sequence));
+        if let Err(e) = started {
+            unregister_callback(id);
+            set_error(
+                error_out,
+                ChromaErrorCode::InternalError,
+                "Failed to start the watcher thread",
+                func_name,
+                Some(&e.to_string()),
+            );
+            return ChromaErrorCode::InternalError as c_int;
+        }
+
+        unsafe {
+            *callback_id = id;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,248 @@
+// Append-only audit log of the mutations made through a client
+use libc::{c_char, c_int, c_void, size_t};
+use serde_json::{json, Value};
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::abi::{read_sized, SizedInput};
+use crate::callbacks::{Callback, CallbackSlot};
+use crate::client::{AccessScope, ChromaClient};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Operations recorded in the audit log; reads are not audited
+const AUDITED_OPERATIONS: &[&str] = &[
+    "add",
+    "update",
+    "upsert",
+    "delete",
+    "create_collection",
+    "update_collection",
+    "delete_collection",
+    "create_database",
+    "delete_database",
+];
+
+/// Receives one audit entry, a single-line JSON object only valid during the call
+pub type ChromaAuditFn = extern "C" fn(user_data: *mut c_void, entry_json: *const c_char);
+
+/// A `ChromaAuditFn`, or null for none
+pub type ChromaAuditCallback = Option<ChromaAuditFn>;
+
+/// Where the audit log of a client is written
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ChromaAuditLogOptions {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// JSONL file entries are appended to, relative to the persist directory unless
+    /// absolute; null for none
+    pub path: *const c_char,
+    /// Callback receiving each entry; null for none
+    pub callback: ChromaAuditCallback,
+    /// Passed back to `callback`
+    pub user_data: *mut c_void,
+}
+
+unsafe impl SizedInput for ChromaAuditLogOptions {}
+
+/// An audit callback and the caller's `user_data`
+pub(crate) struct AuditCallback {
+    callback: ChromaAuditFn,
+    user_data: *mut c_void,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// thread making the call; keeping it valid is up to the caller
+unsafe impl Send for AuditCallback {}
+unsafe impl Sync for AuditCallback {}
+
+impl Callback {
+    fn as_audit(&self) -> Option<&AuditCallback> {
+        match self {
+            Callback::Audit(audit) => Some(audit),
+            _ => None,
+        }
+    }
+}
+
+/// Destinations of a client's audit entries
+pub(crate) struct AuditLog {
+    file: Option<Mutex<File>>,
+    callback: CallbackSlot,
+}
+
+impl AuditLog {
+    fn write(&self, entry: &Value) {
+        let line = entry.to_string();
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            // The mutation already happened, so a failed write cannot fail the call
+            if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+                tracing::warn!(error = %e, "Failed to append to the audit log");
+            }
+        }
+        let Some(registration) = self.callback.registration() else {
+            return;
+        };
+        if let Ok(line) = CString::new(line) {
+            registration.invoke(|callback| {
+                if let Some(audit) = callback.as_audit() {
+                    (audit.callback)(audit.user_data, line.as_ptr());
+                }
+            });
+        }
+    }
+}
+
+impl ChromaClient {
+    /// Sets the audit log, and returns once no call runs the callback of the one replaced
+    fn replace_audit_log(&self, audit_log: Option<AuditLog>) {
+        let previous = std::mem::replace(
+            &mut *self.audit_log.write().unwrap_or_else(|e| e.into_inner()),
+            audit_log.map(Arc::new),
+        );
+        // Calls still holding the previous log skip its callback once it is unregistered
+        if let Some(previous) = previous {
+            previous.callback.replace(None);
+        }
+    }
+
+    /// Records a successful call in the audit log set with `chroma_set_audit_log`, if it
+    /// is a mutation
+    pub(crate) fn audit(&self, operation: &str, scope: &AccessScope) {
+        if !AUDITED_OPERATIONS.contains(&operation) {
+            return;
+        }
+        let audit_log = self.audit_log.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(audit_log) = audit_log else {
+            return;
+        };
+
+        let principal = scope
+            .principal
+            .as_deref()
+            .map_or(Value::Null, |p| serde_json::from_str(p).unwrap_or(Value::Null));
+        audit_log.write(&json!({
+            "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "operation": operation,
+            "principal": principal,
+            "tenant": scope.tenant,
+            "database": scope.database,
+            "collection": scope.collection,
+            "records": scope.records,
+        }));
+    }
+}
+
+/// Sets where a client records the mutations made through it (adds, updates, upserts,
+/// deletes, including each write of `chroma_batch_commit`, and collection and database
+/// creation, changes and removal), or turns the audit log off when `options` is null.
+///
+/// Each successful mutation produces one JSON entry with its UTC `timestamp`, `operation`,
+/// the `principal` set with `chroma_set_identity` (null when none), `tenant`, `database`,
+/// `collection` and `records`, the number of records written (null when not known, e.g. for
+/// deletes by filter). Entries are appended to the file at `path` and passed to `callback`
+/// on the calling thread. A relative `path` needs a client with a persist directory.
+///
+/// Replacing the log or turning it off returns once no call runs the previous callback,
+/// which is never called again, so its `user_data` may be released then; otherwise it must
+/// stay valid until the client is destroyed.
+#[no_mangle]
+pub extern "C" fn chroma_set_audit_log(
+    client_handle: ChromaHandle,
+    options: *const ChromaAuditLogOptions,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_audit_log", || {
+        let func_name = "chroma_set_audit_log";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let options = match unsafe { read_sized(options) } {
+            Ok(options) => options,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let Some(options) = options else {
+            client.replace_audit_log(None);
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        };
+
+        let file = if options.path.is_null() {
+            None
+        } else {
+            let path = match unsafe { c_str_to_string(options.path) } {
+                Ok(path) => path,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid audit log path",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            };
+            let path = match (Path::new(&path).is_absolute(), &client.persist_path) {
+                (true, _) => Path::new(&path).to_path_buf(),
+                (false, Some(persist_path)) => Path::new(persist_path).join(&path),
+                (false, None) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "A relative audit log path needs a persist directory",
+                        func_name,
+                        Some(&path),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            };
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => Some(Mutex::new(file)),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InternalError,
+                        "Failed to open the audit log",
+                        func_name,
+                        Some(&format!("{}: {}", path.display(), e)),
+                    );
+                    return ChromaErrorCode::InternalError as c_int;
+                }
+            }
+        };
+
+        let callback = CallbackSlot::default();
+        callback.replace(options.callback.map(|callback| {
+            Callback::Audit(AuditCallback {
+                callback,
+                user_data: options.user_data,
+            })
+        }));
+        client.replace_audit_log(Some(AuditLog { file, callback }));
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,296 @@
+// Authorization of backend calls by a host callback, per caller identity
+use libc::{c_char, c_int, c_void};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ptr;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::callbacks::Callback;
+use crate::client::ChromaClient;
+use crate::collection::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Decides whether a call may run: the identity set with `chroma_set_identity` on the client
+/// handle it went through (null when none was set), the tenant, database and collection it
+/// targets (collection null for calls on a database) and the operation, such as `query` or
+/// `create_collection`. All strings are UTF-8 and only valid during the call. Returns true
+/// to allow the call.
+pub type ChromaAuthorizeFn = extern "C" fn(
+    user_data: *mut c_void,
+    principal_json: *const c_char,
+    tenant: *const c_char,
+    database: *const c_char,
+    collection: *const c_char,
+    operation: *const c_char,
+) -> bool;
+
+/// A `ChromaAuthorizeFn`, or null for none
+pub type ChromaAuthorizeCallback = Option<ChromaAuthorizeFn>;
+
+/// An authorization callback and the caller's `user_data`
+pub(crate) struct Authorizer {
+    callback: ChromaAuthorizeFn,
+    user_data: *mut c_void,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// thread making the call; keeping it valid is up to the caller
+unsafe impl Send for Authorizer {}
+unsafe impl Sync for Authorizer {}
+
+impl Callback {
+    fn as_authorize(&self) -> Option<&Authorizer> {
+        match self {
+            Callback::Authorize(authorizer) => Some(authorizer),
+            _ => None,
+        }
+    }
+}
+
+/// Identities set with `chroma_set_identity`, by client handle
+static IDENTITIES: LazyLock<RwLock<HashMap<ChromaHandle, Arc<str>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Identity set on a client handle, inherited by the collections opened through it
+pub(crate) fn identity_of(client_handle: ChromaHandle) -> Option<Arc<str>> {
+    let identities = IDENTITIES.read().unwrap_or_else(|e| e.into_inner());
+    identities.get(&client_handle).cloned()
+}
+
+/// Drops the identity of a released client handle
+pub(crate) fn forget_identity(client_handle: ChromaHandle) {
+    let mut identities = IDENTITIES.write().unwrap_or_else(|e| e.into_inner());
+    identities.remove(&client_handle);
+}
+
+/// Who makes a backend call and what it targets, as passed to the authorizer
+#[derive(Clone)]
+pub(crate) struct AccessScope {
+    pub(crate) principal: Option<Arc<str>>,
+    pub(crate) tenant: String,
+    pub(crate) database: String,
+    /// Collection ID, or its name for calls that look the collection up by name
+    pub(crate) collection: Option<String>,
+    /// Number of records a write touches, when known, for the audit log and quotas
+    pub(crate) records: Option<usize>,
+    /// Dimension of the embeddings a write carries, when it carries any, for quotas
+    pub(crate) dimension: Option<usize>,
+}
+
+impl AccessScope {
+    /// Scope of a call made through a client handle
+    pub(crate) fn new(
+        client_handle: ChromaHandle,
+        tenant: &str,
+        database: &str,
+        collection: Option<&str>,
+    ) -> Self {
+        AccessScope {
+            principal: identity_of(client_handle),
+            tenant: tenant.to_string(),
+            database: database.to_string(),
+            collection: collection.map(str::to_string),
+            records: None,
+            dimension: None,
+        }
+    }
+
+    /// Same scope for a write of `records` records, `None` when not known
+    pub(crate) fn with_records(self, records: Option<usize>) -> Self {
+        AccessScope {
+            records,
+            ..self
+        }
+    }
+
+    /// Same scope for a write carrying embeddings of `dimension` dimensions, `None` for none
+    pub(crate) fn with_dimension(self, dimension: Option<usize>) -> Self {
+        AccessScope { dimension, ..self }
+    }
+}
+
+impl ChromaCollection {
+    /// Scope of a call on this collection, made with the identity it was opened with
+    pub(crate) fn scope(&self) -> AccessScope {
+        AccessScope {
+            principal: self.principal.clone(),
+            tenant: self.tenant.to_string(),
+            database: self.database.to_string(),
+            collection: Some(self.id.to_string()),
+            records: None,
+            dimension: None,
+        }
+    }
+}
+
+impl ChromaClient {
+    /// Asks the authorizer set with `chroma_set_authorizer` whether a call may run; the
+    /// error describes a denied call
+    pub(crate) fn authorize(&self, operation: &str, scope: &AccessScope) -> Result<(), String> {
+        let Some(registration) = self.authorizer.registration() else {
+            return Ok(());
+        };
+
+        let target = format!(
+            "{} on {}/{}{}",
+            operation,
+            scope.tenant,
+            scope.database,
+            scope.collection.as_deref().map(|c| format!("/{}", c)).unwrap_or_default()
+        );
+        // Strings with a NUL byte cannot be passed, so such calls are denied
+        let to_c = |s: &str| CString::new(s).map_err(|_| format!("Denied {}", target));
+        let principal = scope.principal.as_deref().map(to_c).transpose()?;
+        let tenant = to_c(&scope.tenant)?;
+        let database = to_c(&scope.database)?;
+        let collection = scope.collection.as_deref().map(to_c).transpose()?;
+        let operation = to_c(operation)?;
+
+        // An authorizer removed since it was looked up no longer applies
+        let mut allowed = true;
+        registration.invoke(|callback| {
+            if let Some(authorizer) = callback.as_authorize() {
+                allowed = (authorizer.callback)(
+                    authorizer.user_data,
+                    principal.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                    tenant.as_ptr(),
+                    database.as_ptr(),
+                    collection.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                    operation.as_ptr(),
+                );
+            }
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!("Denied {}", target))
+        }
+    }
+}
+
+/// Sets the callback authorizing every call a client makes into the Chroma frontend, such
+/// as collection management, reads and writes, including calls on collections opened from it.
+///
+/// The callback runs on the calling thread before each call and its retries; a denied call
+/// fails with `PermissionDenied` without reaching the backend. Writes made to the local SQLite
+/// database directly are checked too: each write of `chroma_batch_commit` as `add`, `update`,
+/// `upsert` or `delete`, and alias changes as `create_alias`, `swap_alias` or `delete_alias`.
+/// Functions only reading it directly (change feed, write log, record versions, storage
+/// reports) are not covered.
+///
+/// A null `callback` removes the authorizer. Replacing or removing it returns once no call
+/// runs the previous callback, which is never called again, so its `user_data` may be
+/// released then; otherwise it must stay valid until the client is destroyed.
+#[no_mangle]
+pub extern "C" fn chroma_set_authorizer(
+    client_handle: ChromaHandle,
+    callback: ChromaAuthorizeCallback,
+    user_data: *mut c_void,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_authorizer", || {
+        let func_name = "chroma_set_authorizer";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        client.authorizer.replace(callback.map(|callback| {
+            Callback::Authorize(Authorizer {
+                callback,
+                user_data,
+            })
+        }));
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Sets the identity the authorizer sees for calls made through one client handle and the
+/// collections opened through it afterwards, as a JSON document of the host's choosing
+/// (e.g. `{"user": "alice", "roles": ["reader"]}`).
+///
+/// Identities belong to handles, not clients: a multi-user server issues a handle per
+/// principal with `chroma_client_retain` and sets its identity once. Collections keep the
+/// identity of the handle they were opened through. A null `principal_json` clears it;
+/// invalid JSON fails with `ValidationError`.
+#[no_mangle]
+pub extern "C" fn chroma_set_identity(
+    client_handle: ChromaHandle,
+    principal_json: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_identity", || {
+        let func_name = "chroma_set_identity";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if let Err(e) = client_from_handle(client_handle) {
+            return e.report(error_out, func_name);
+        }
+
+        let principal = if principal_json.is_null() {
+            None
+        } else {
+            let json = match unsafe { c_str_to_string(principal_json) } {
+                Ok(json) => json,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid identity string",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            };
+            match serde_json::from_str::<serde_json::Value>(&json) {
+                Ok(value) => Some(Arc::<str>::from(value.to_string())),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::ValidationError,
+                        "Identity is not valid JSON",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            }
+        };
+
+        let mut identities = IDENTITIES.write().unwrap_or_else(|e| e.into_inner());
+        match principal {
+            Some(principal) => identities.insert(client_handle, principal),
+            None => identities.remove(&client_handle),
+        };
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,163 @@
+// Limits on the number of calls running concurrently through a client
+use libc::{c_int, c_uint, size_t};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::abi::{read_sized, SizedInput};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Operations counted against `max_writes`; every other call counts as a read
+const WRITE_OPERATIONS: &[&str] = &[
+    "add",
+    "update",
+    "upsert",
+    "delete",
+    "batch_commit",
+    "create_collection",
+    "update_collection",
+    "delete_collection",
+    "create_database",
+    "delete_database",
+];
+
+/// How many calls may run at once through a client; 0 leaves a limit unset
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChromaConcurrencyLimits {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Reads (gets, queries, counts, lookups) running at once
+    pub max_reads: c_uint,
+    /// Writes (adds, updates, upserts, deletes, collection and database changes) running at once
+    pub max_writes: c_uint,
+    /// How long a call over a limit waits for another to finish before failing with `Busy`;
+    /// 0 fails it at once
+    pub queue_timeout_ms: c_uint,
+}
+
+unsafe impl SizedInput for ChromaConcurrencyLimits {}
+
+#[derive(Default)]
+struct LimiterState {
+    limits: ChromaConcurrencyLimits,
+    reads: u32,
+    writes: u32,
+}
+
+/// Counting semaphores for the reads and writes of a client. Callers are blocking host
+/// threads, so waiting parks the calling thread rather than a runtime task.
+#[derive(Default)]
+pub(crate) struct ConcurrencyLimiter {
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+/// A running call, counted against its limit until dropped
+pub(crate) struct Permit<'a> {
+    limiter: &'a ConcurrencyLimiter,
+    write: bool,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state.lock().unwrap_or_else(|e| e.into_inner());
+        if self.write {
+            state.writes -= 1;
+        } else {
+            state.reads -= 1;
+        }
+        drop(state);
+        self.limiter.released.notify_all();
+    }
+}
+
+impl ConcurrencyLimiter {
+    /// Waits for room to run `operation`; the error describes a call that found none in time
+    pub(crate) fn acquire(&self, operation: &str) -> Result<Permit<'_>, String> {
+        let write = WRITE_OPERATIONS.contains(&operation);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let deadline =
+            Instant::now() + Duration::from_millis(u64::from(state.limits.queue_timeout_ms));
+        loop {
+            // Limits are read on every pass, so a raised limit lets waiting calls through
+            let (running, max) = if write {
+                (state.writes, state.limits.max_writes)
+            } else {
+                (state.reads, state.limits.max_reads)
+            };
+            if max == 0 || running < max {
+                break;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(format!(
+                    "{} {} already running, the limit of the client",
+                    running,
+                    if write { "writes" } else { "reads" }
+                ));
+            }
+            state = self
+                .released
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+
+        if write {
+            state.writes += 1;
+        } else {
+            state.reads += 1;
+        }
+        Ok(Permit {
+            limiter: self,
+            write,
+        })
+    }
+}
+
+/// Limits how many reads and how many writes run at once through a client and the
+/// collections opened from it, protecting the SQLite database and segments from more
+/// threads than they can serve; a null `limits` removes the limits.
+///
+/// Calls into the Chroma frontend and batch commits are counted. A call over a limit waits
+/// up to `queue_timeout_ms` for another to finish, then fails with `Busy` without reaching
+/// the backend. Calls already running or waiting are not interrupted by a change of limits.
+#[no_mangle]
+pub extern "C" fn chroma_set_concurrency_limits(
+    client_handle: ChromaHandle,
+    limits: *const ChromaConcurrencyLimits,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_concurrency_limits", || {
+        let func_name = "chroma_set_concurrency_limits";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let limits = match unsafe { read_sized(limits) } {
+            Ok(limits) => limits.unwrap_or_default(),
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let limiter = &client.concurrency;
+        limiter.state.lock().unwrap_or_else(|e| e.into_inner()).limits = limits;
+        limiter.released.notify_all();
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,106 @@
+// Effective configuration of a client, dumped on demand
+use libc::{c_char, c_int};
+use serde_json::Value;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// Placeholder written in place of sensitive values
+const REDACTED: &str = "***";
+
+/// Key fragments whose values are never returned by the config dump
+const SENSITIVE_KEY_PARTS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "credential",
+    "api_key",
+    "access_key",
+    "encryption_key",
+];
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+/// Strips `user:password@` credentials from URL-like strings
+fn redact_url_credentials(value: &str) -> Option<String> {
+    let scheme_end = value.find("://")? + 3;
+    let at = scheme_end + value[scheme_end..].find('@')?;
+    if value[scheme_end..at].contains('/') {
+        return None;
+    }
+    Some(format!("{}{}{}", &value[..scheme_end], REDACTED, &value[at..]))
+}
+
+/// Replaces sensitive values in a configuration tree
+pub(crate) fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if is_sensitive_key(key) && !entry.is_null() {
+                    *entry = Value::String(REDACTED.to_string());
+                } else {
+                    redact(entry);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        Value::String(s) => {
+            if let Some(redacted) = redact_url_credentials(s) {
+                *s = redacted;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the configuration a client was created with, resolved to the values
+/// actually in effect, as JSON.
+///
+/// Passwords, secrets, tokens, keys and URL credentials are replaced with `***`.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_dump_effective_config(
+    client_handle: ChromaHandle,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_dump_effective_config", || {
+        let func_name = "chroma_dump_effective_config";
+
+        if client_handle == CHROMA_INVALID_HANDLE || out_json.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let mut config = client.effective_config.clone();
+        redact(&mut config);
+
+        unsafe {
+            *out_json = string_to_c_str(config.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
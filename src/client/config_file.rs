@@ -0,0 +1,391 @@
+// Client creation from a configuration file and CHROMA_* environment variables
+use figment::providers::{Env, Format, Toml, Yaml};
+use figment::Figment;
+use libc::{c_char, c_int};
+use serde::Deserialize;
+use serde_json::Value;
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+
+use crate::client::{
+    chroma_create_client, CHROMA_JOURNAL_MODE_DEFAULT, CHROMA_JOURNAL_MODE_DELETE,
+    CHROMA_JOURNAL_MODE_WAL, CHROMA_LOCK_EXCLUSIVE, CHROMA_LOCK_NONE, CHROMA_LOCK_SHARED_READ,
+    CHROMA_SYNCHRONOUS_DEFAULT, CHROMA_SYNCHRONOUS_FULL, CHROMA_SYNCHRONOUS_NORMAL,
+    CHROMA_SYNCHRONOUS_OFF,
+};
+use crate::error::{ffi_boundary, set_error, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::ChromaHandle;
+use crate::types::{CollectionCacheConfigFFI, DiskCacheConfigFFI, LockConfigFFI, SqliteConfigFFI};
+use crate::utils::c_str_to_string;
+
+/// Environment variable naming the configuration file when no path is passed
+const CONFIG_PATH_VAR: &str = "CHROMA_CONFIG_PATH";
+
+/// Top-level options that `CHROMA_*` environment variables may set; other `CHROMA_*`
+/// variables belong to other tools and are ignored
+const ENV_OPTIONS: &[&str] = &[
+    "allow_reset",
+    "read_only",
+    "persist_path",
+    "hnsw_cache_size",
+    "disk_cache",
+    "collection_cache",
+    "lock",
+    "sqlite",
+    "distributed",
+    "object_store",
+];
+
+/// Every option of `chroma_create_client`, as read from a configuration file
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ClientOptions {
+    allow_reset: bool,
+    read_only: bool,
+    persist_path: Option<String>,
+    hnsw_cache_size: usize,
+    disk_cache: Option<DiskCacheOptions>,
+    collection_cache: Option<CollectionCacheOptions>,
+    lock: Option<LockOptions>,
+    sqlite: Option<SqliteOptions>,
+    /// Passed through as `distributed_config_json`, validated by `chroma_create_client`
+    distributed: Option<Value>,
+    /// Passed through as `object_store_config_json`, validated by `chroma_create_client`
+    object_store: Option<Value>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            allow_reset: false,
+            read_only: false,
+            persist_path: None,
+            hnsw_cache_size: 1000,
+            disk_cache: None,
+            collection_cache: None,
+            lock: None,
+            sqlite: None,
+            distributed: None,
+            object_store: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DiskCacheOptions {
+    dir: String,
+    disk_mib: usize,
+    #[serde(default)]
+    memory_mib: usize,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CollectionCacheOptions {
+    capacity: usize,
+    ttl_secs: u32,
+    permitted_parallelism: u32,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LockOptions {
+    mode: LockModeOption,
+    wait_ms: u32,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum LockModeOption {
+    #[default]
+    None,
+    Exclusive,
+    SharedRead,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct SqliteOptions {
+    hash_type: HashTypeOption,
+    migration_mode: MigrationModeOption,
+    journal_mode: JournalModeOption,
+    synchronous: SynchronousOption,
+    busy_timeout_ms: u32,
+    cache_size_kib: u32,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum HashTypeOption {
+    #[default]
+    Sha256,
+    Md5,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum MigrationModeOption {
+    #[default]
+    Apply,
+    Validate,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum JournalModeOption {
+    #[default]
+    Default,
+    Wal,
+    Delete,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum SynchronousOption {
+    #[default]
+    Default,
+    Off,
+    Normal,
+    Full,
+}
+
+impl SqliteOptions {
+    fn to_ffi(&self) -> SqliteConfigFFI {
+        SqliteConfigFFI {
+            struct_size: std::mem::size_of::<SqliteConfigFFI>(),
+            url: ptr::null(),
+            hash_type: match self.hash_type {
+                HashTypeOption::Sha256 => 0,
+                HashTypeOption::Md5 => 1,
+            },
+            migration_mode: match self.migration_mode {
+                MigrationModeOption::Apply => 0,
+                MigrationModeOption::Validate => 1,
+            },
+            journal_mode: match self.journal_mode {
+                JournalModeOption::Default => CHROMA_JOURNAL_MODE_DEFAULT,
+                JournalModeOption::Wal => CHROMA_JOURNAL_MODE_WAL,
+                JournalModeOption::Delete => CHROMA_JOURNAL_MODE_DELETE,
+            },
+            synchronous: match self.synchronous {
+                SynchronousOption::Default => CHROMA_SYNCHRONOUS_DEFAULT,
+                SynchronousOption::Off => CHROMA_SYNCHRONOUS_OFF,
+                SynchronousOption::Normal => CHROMA_SYNCHRONOUS_NORMAL,
+                SynchronousOption::Full => CHROMA_SYNCHRONOUS_FULL,
+            },
+            busy_timeout_ms: self.busy_timeout_ms,
+            cache_size_kib: self.cache_size_kib,
+        }
+    }
+}
+
+impl LockOptions {
+    fn to_ffi(&self) -> LockConfigFFI {
+        LockConfigFFI {
+            struct_size: std::mem::size_of::<LockConfigFFI>(),
+            mode: match self.mode {
+                LockModeOption::None => CHROMA_LOCK_NONE,
+                LockModeOption::Exclusive => CHROMA_LOCK_EXCLUSIVE,
+                LockModeOption::SharedRead => CHROMA_LOCK_SHARED_READ,
+            },
+            wait_ms: self.wait_ms,
+        }
+    }
+}
+
+impl CollectionCacheOptions {
+    fn to_ffi(&self) -> CollectionCacheConfigFFI {
+        CollectionCacheConfigFFI {
+            struct_size: std::mem::size_of::<CollectionCacheConfigFFI>(),
+            capacity: self.capacity,
+            ttl_secs: self.ttl_secs,
+            permitted_parallelism: self.permitted_parallelism,
+        }
+    }
+}
+
+fn invalid_config(e: impl ToString) -> FfiError {
+    FfiError::new(
+        ChromaErrorCode::ValidationError,
+        "Invalid client configuration",
+        Some(e.to_string()),
+    )
+}
+
+/// Reads the options from `path`, or the file named by `CHROMA_CONFIG_PATH`, overridden by
+/// `CHROMA_*` environment variables
+fn load_options(path: Option<String>) -> Result<ClientOptions, FfiError> {
+    let path = path.or_else(|| std::env::var(CONFIG_PATH_VAR).ok());
+
+    let mut figment = Figment::new();
+    if let Some(path) = path {
+        if !Path::new(&path).is_file() {
+            return Err(FfiError::new(
+                ChromaErrorCode::NotFound,
+                "Configuration file not found",
+                Some(path),
+            ));
+        }
+        let extension = Path::new(&path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        figment = match extension.as_deref() {
+            Some("yaml" | "yml") => figment.merge(Yaml::file(&path)),
+            Some("toml") => figment.merge(Toml::file(&path)),
+            _ => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Unknown configuration file format",
+                    Some(format!("{}: expected a .yaml, .yml or .toml file", path)),
+                ))
+            }
+        };
+    }
+
+    // CHROMA_SQLITE__JOURNAL_MODE=wal sets `sqlite.journal_mode`
+    let env = Env::prefixed("CHROMA_")
+        .filter(|key| {
+            let key = key.as_str().to_ascii_lowercase();
+            ENV_OPTIONS
+                .iter()
+                .any(|option| key == *option || key.starts_with(&format!("{}__", option)))
+        })
+        .split("__");
+    figment.merge(env).extract().map_err(invalid_config)
+}
+
+/// Creates a client from a YAML (`.yaml`, `.yml`) or TOML (`.toml`) configuration file
+/// instead of arguments, so the native layer can be tuned without recompiling the host.
+///
+/// The file holds the options of `chroma_create_client`: `allow_reset`, `read_only`,
+/// `persist_path`, `hnsw_cache_size`, `disk_cache` (`dir`, `disk_mib`, `memory_mib`),
+/// `collection_cache` (`capacity`, `ttl_secs`, `permitted_parallelism`), `lock` (`mode` of
+/// `none`, `exclusive` or `shared_read`, `wait_ms`) and `sqlite` (`hash_type`,
+/// `migration_mode`, `journal_mode`, `synchronous`, `busy_timeout_ms`, `cache_size_kib`),
+/// with enum values written in lowercase. Options left out keep the defaults of
+/// `chroma_create_client`. A `distributed` section (`sysdb`, `log`, `executor`) connects the
+/// client to a distributed deployment, see `distributed_config_json`, and an `object_store`
+/// section (`storage`, `prefix`) mirrors the persist directory, see `object_store_config_json`.
+/// Keys do not belong in configuration files: an encrypted persist directory is opened with
+/// the key set by `chroma_set_encryption_key`.
+///
+/// When `config_path` is null the file named by `CHROMA_CONFIG_PATH` is read, if set.
+/// `CHROMA_*` environment variables override the file, with `__` separating nested keys,
+/// e.g. `CHROMA_PERSIST_PATH` or `CHROMA_SQLITE__JOURNAL_MODE=wal`. Unknown keys in the file
+/// fail with `ValidationError`.
+#[no_mangle]
+pub extern "C" fn chroma_create_client_from_config(
+    config_path: *const c_char,
+    client_handle: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_create_client_from_config", || {
+        let func_name = "chroma_create_client_from_config";
+
+        if client_handle.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let path = if config_path.is_null() {
+            None
+        } else {
+            match unsafe { c_str_to_string(config_path) } {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid configuration file path",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let options = match load_options(path) {
+            Ok(options) => options,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let to_c = |s: &str| {
+            CString::new(s).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Configuration path contains a NUL byte",
+                    Some(e.to_string()),
+                )
+            })
+        };
+        let persist_path = match options.persist_path.as_deref().map(to_c).transpose() {
+            Ok(persist_path) => persist_path,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let disk_cache_dir = match options.disk_cache.as_ref().map(|c| to_c(&c.dir)).transpose()
+        {
+            Ok(dir) => dir,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let distributed = match options
+            .distributed
+            .as_ref()
+            .map(|c| to_c(&c.to_string()))
+            .transpose()
+        {
+            Ok(distributed) => distributed,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let object_store = match options
+            .object_store
+            .as_ref()
+            .map(|c| to_c(&c.to_string()))
+            .transpose()
+        {
+            Ok(object_store) => object_store,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        // The structs only borrow the strings above, which outlive the call
+        let sqlite = options.sqlite.as_ref().map(SqliteOptions::to_ffi);
+        let disk_cache = options.disk_cache.as_ref().zip(disk_cache_dir.as_ref()).map(
+            |(disk_cache, dir)| DiskCacheConfigFFI {
+                struct_size: std::mem::size_of::<DiskCacheConfigFFI>(),
+                dir: dir.as_ptr(),
+                disk_mib: disk_cache.disk_mib,
+                memory_mib: disk_cache.memory_mib,
+            },
+        );
+        let collection_cache = options
+            .collection_cache
+            .as_ref()
+            .map(CollectionCacheOptions::to_ffi);
+        let lock = options.lock.as_ref().map(LockOptions::to_ffi);
+
+        chroma_create_client(
+            options.allow_reset,
+            options.read_only,
+            sqlite.as_ref().map_or(ptr::null(), |c| c as *const _),
+            options.hnsw_cache_size,
+            disk_cache.as_ref().map_or(ptr::null(), |c| c as *const _),
+            collection_cache.as_ref().map_or(ptr::null(), |c| c as *const _),
+            lock.as_ref().map_or(ptr::null(), |c| c as *const _),
+            persist_path.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+            distributed.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+            object_store.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+            ptr::null(),
+            client_handle,
+            error_out,
+        )
+    })
+}
@@ -0,0 +1,153 @@
+// Read consistency with respect to the write log
+use chroma_log::local_compaction_manager::BackfillMessage;
+use chroma_types::CollectionUuid;
+use libc::c_int;
+use std::cell::Cell;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Reads see the segments as they are, without waiting for logged writes to be applied
+pub const CHROMA_CONSISTENCY_EVENTUAL: c_int = 0;
+/// Reads first apply every logged write of the collection, wherever it came from
+pub const CHROMA_CONSISTENCY_STRONG: c_int = 1;
+/// Clears the mode set on the calling thread, falling back to the client's mode
+pub const CHROMA_CONSISTENCY_CLIENT_DEFAULT: c_int = -1;
+
+thread_local! {
+    static THREAD_CONSISTENCY: Cell<c_int> = const { Cell::new(CHROMA_CONSISTENCY_CLIENT_DEFAULT) };
+}
+
+fn is_consistency_mode(mode: c_int) -> bool {
+    mode == CHROMA_CONSISTENCY_EVENTUAL || mode == CHROMA_CONSISTENCY_STRONG
+}
+
+impl ChromaClient {
+    /// Consistency of the reads made on the calling thread
+    fn read_consistency(&self) -> c_int {
+        match THREAD_CONSISTENCY.with(Cell::get) {
+            CHROMA_CONSISTENCY_CLIENT_DEFAULT => self.consistency.load(Ordering::Relaxed),
+            mode => mode,
+        }
+    }
+
+    /// Applies the pending write log of a collection to its segments before a strong read;
+    /// does nothing for eventual reads
+    pub(crate) fn read_barrier(&self, collection_id: CollectionUuid) -> Result<(), FfiError> {
+        if self.read_consistency() != CHROMA_CONSISTENCY_STRONG {
+            return Ok(());
+        }
+        // Applying the log writes the segments
+        self.check_writable()?;
+        let Some(compaction_manager) = self.compaction_manager.as_ref() else {
+            return Ok(());
+        };
+
+        let started = Instant::now();
+        let result = self.runtime.block_on(async {
+            compaction_manager
+                .request(BackfillMessage { collection_id }, None)
+                .await
+                .map_err(|e| {
+                    FfiError::backend(
+                        ChromaErrorCode::InternalError,
+                        "Failed to reach compactor",
+                        &e,
+                    )
+                })?
+                .map_err(|e| {
+                    FfiError::backend(
+                        ChromaErrorCode::InternalError,
+                        "Failed to apply the write log",
+                        &e,
+                    )
+                })
+        });
+
+        self.metrics.record("read_barrier", started.elapsed(), result.is_ok());
+        result
+    }
+}
+
+/// Sets the read consistency of a client: `CHROMA_CONSISTENCY_EVENTUAL` (the default) or
+/// `CHROMA_CONSISTENCY_STRONG`.
+///
+/// Writes made through a client are applied to the segments before they return, so its own
+/// reads see them either way. Strong reads additionally apply every write still pending in
+/// the log of the collection before reading, such as writes of another client or process
+/// sharing the persist directory, at the cost of a compactor round trip per read.
+/// Strong consistency fails with `ReadOnly` on read-only clients, which cannot apply the log.
+#[no_mangle]
+pub extern "C" fn chroma_set_consistency(
+    client_handle: ChromaHandle,
+    mode: c_int,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_consistency", || {
+        let func_name = "chroma_set_consistency";
+
+        if client_handle == CHROMA_INVALID_HANDLE || !is_consistency_mode(mode) {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Unknown consistency mode"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if mode == CHROMA_CONSISTENCY_STRONG {
+            if let Err(e) = client.check_writable() {
+                return e.report(error_out, func_name);
+            }
+        }
+        client.consistency.store(mode, Ordering::Relaxed);
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Overrides the read consistency of the calls made on the calling thread, whatever client
+/// they go through, until it is replaced or cleared with `CHROMA_CONSISTENCY_CLIENT_DEFAULT`.
+///
+/// Lets a single call opt into strong reads without changing the mode of a shared client.
+/// Strong reads through a read-only client fail with `ReadOnly`.
+#[no_mangle]
+pub extern "C" fn chroma_set_thread_consistency(
+    mode: c_int,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_thread_consistency", || {
+        let func_name = "chroma_set_thread_consistency";
+
+        if !is_consistency_mode(mode) && mode != CHROMA_CONSISTENCY_CLIENT_DEFAULT {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown consistency mode",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+        THREAD_CONSISTENCY.with(|current| current.set(mode));
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
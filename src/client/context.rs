@@ -0,0 +1,181 @@
+// Default tenant and database of a client, used by calls that pass none
+use chroma_types::{DatabaseName, GetDatabaseRequest};
+use libc::{c_char, c_int};
+
+use crate::client::{AccessScope, ChromaClient};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_str_to_string, string_to_c_str, DEFAULT_DATABASE, DEFAULT_TENANT};
+
+/// Tenant and database a client uses when a call passes null for them
+pub(crate) struct DefaultContext {
+    tenant: String,
+    database: String,
+}
+
+impl Default for DefaultContext {
+    fn default() -> Self {
+        DefaultContext {
+            tenant: DEFAULT_TENANT.to_string(),
+            database: DEFAULT_DATABASE.to_string(),
+        }
+    }
+}
+
+impl ChromaClient {
+    /// Tenant of calls that pass none, set with `chroma_set_default_tenant_database`
+    pub(crate) fn default_tenant(&self) -> String {
+        let context = self.default_context.read().unwrap_or_else(|e| e.into_inner());
+        context.tenant.clone()
+    }
+
+    /// Tenant and database of calls that pass none, read together so that a concurrent
+    /// `chroma_set_default_tenant_database` cannot pair the tenant of one with the database
+    /// of the other
+    pub(crate) fn default_tenant_database(&self) -> (String, String) {
+        let context = self.default_context.read().unwrap_or_else(|e| e.into_inner());
+        (context.tenant.clone(), context.database.clone())
+    }
+
+    /// Checks that `database` exists in `tenant`, so a mistyped default fails when it is set
+    /// rather than on every later call
+    fn check_database_exists(
+        &self,
+        client_handle: ChromaHandle,
+        tenant: &str,
+        database: &str,
+    ) -> Result<(), FfiError> {
+        let scope = AccessScope::new(client_handle, tenant, database, None);
+        let Some(database_name) = DatabaseName::new(database.to_string()) else {
+            return Err(FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid database name (must be at least 3 characters)",
+                None,
+            ));
+        };
+        let request = GetDatabaseRequest::try_new(tenant.to_string(), database_name).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create get database request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        self.block_on_with_retry("get_database", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get_database(request).await }
+        })
+        .map(|_| ())
+        .map_err(|e| FfiError::backend(ChromaErrorCode::NotFound, "Database not found", &e))
+    }
+}
+
+/// Sets the tenant and database that calls of this client use when they pass null for
+/// them, such as `chroma_create_collection`, `chroma_get_collection` or
+/// `chroma_create_database`, instead of `default_tenant` and `default_database`.
+///
+/// A null `tenant` or `database` restores the built-in default. The database must exist in
+/// the tenant, or the call fails with `NotFound` and the defaults are left unchanged. The
+/// defaults are shared by every handle of the client, including those issued by
+/// `chroma_client_retain`; collections already open keep the tenant and database they were
+/// opened in.
+#[no_mangle]
+pub extern "C" fn chroma_set_default_tenant_database(
+    client_handle: ChromaHandle,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_default_tenant_database", || {
+        let func_name = "chroma_set_default_tenant_database";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let parse = |ptr: *const c_char, default: &str, what: &'static str| {
+            if ptr.is_null() {
+                return Ok(default.to_string());
+            }
+            unsafe { c_str_to_string(ptr) }.map_err(|e| {
+                FfiError::new(ChromaErrorCode::InvalidArgument, what, Some(e.to_string()))
+            })
+        };
+        let context = (
+            parse(tenant_ptr, DEFAULT_TENANT, "Invalid tenant name"),
+            parse(database_ptr, DEFAULT_DATABASE, "Invalid database name"),
+        );
+        let (tenant, database) = match context {
+            (Ok(tenant), Ok(database)) => (tenant, database),
+            (Err(e), _) | (_, Err(e)) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = client.check_database_exists(client_handle, &tenant, &database) {
+            return e.report(error_out, func_name);
+        }
+
+        *client.default_context.write().unwrap_or_else(|e| e.into_inner()) =
+            DefaultContext { tenant, database };
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Reports the tenant and database calls of this client use when they pass none. Both
+/// strings are allocated with `chroma_malloc`; release them with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_get_default_tenant_database(
+    client_handle: ChromaHandle,
+    tenant_out: *mut *mut c_char,
+    database_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_default_tenant_database", || {
+        let func_name = "chroma_get_default_tenant_database";
+
+        if client_handle == CHROMA_INVALID_HANDLE || tenant_out.is_null() || database_out.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if tenant_out.is_null() {
+                "Tenant output pointer is null"
+            } else {
+                "Database output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let context = client.default_context.read().unwrap_or_else(|e| e.into_inner());
+        unsafe {
+            *tenant_out = string_to_c_str(context.tenant.clone());
+            *database_out = string_to_c_str(context.database.clone());
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,71 @@
+// Backend configuration of clients connected to a distributed Chroma deployment
+use chroma_frontend::executor::config::ExecutorConfig;
+use chroma_log::config::LogConfig;
+use chroma_sysdb::SysDbConfig;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Sysdb, log and executor of a distributed deployment, in the format of the `sysdb`, `log`
+/// and `executor` sections of the Chroma frontend configuration
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DistributedSections {
+    sysdb: SysDbConfig,
+    log: LogConfig,
+    executor: ExecutorConfig,
+}
+
+/// Parsed distributed configuration, with the JSON it came from for the effective config
+pub(crate) struct DistributedConfig {
+    pub(crate) sysdb: SysDbConfig,
+    pub(crate) log: LogConfig,
+    pub(crate) executor: ExecutorConfig,
+    pub(crate) json: Value,
+}
+
+impl DistributedConfig {
+    /// Parses the configuration, which must use the gRPC sysdb and log and the distributed
+    /// executor; local sections belong to embedded clients
+    pub(crate) fn from_json(json: &str) -> Result<Self, FfiError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid distributed configuration JSON",
+                Some(e.to_string()),
+            )
+        })?;
+        let sections = DistributedSections::deserialize(&value).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid distributed configuration",
+                Some(e.to_string()),
+            )
+        })?;
+
+        let local_section = match (&sections.sysdb, &sections.log, &sections.executor) {
+            (SysDbConfig::Sqlite(_), _, _) => Some("sysdb"),
+            (_, LogConfig::Sqlite(_), _) => Some("log"),
+            (_, _, ExecutorConfig::Local(_)) => Some("executor"),
+            _ => None,
+        };
+        if let Some(section) = local_section {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Distributed configuration uses a local backend",
+                Some(format!(
+                    "The {} section must point at a service of the deployment",
+                    section
+                )),
+            ));
+        }
+
+        Ok(DistributedConfig {
+            sysdb: sections.sysdb,
+            log: sections.log,
+            executor: sections.executor,
+            json: value,
+        })
+    }
+}
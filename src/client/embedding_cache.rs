@@ -0,0 +1,417 @@
+// Persistent cache of embeddings computed by the host, keyed by a hash of model and document
+use chroma_sqlite::db::SqliteDb;
+use libc::{c_char, c_float, c_int, size_t};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::ptr;
+
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{
+    c_array_to_vec_f32, c_array_to_vec_string, c_str_to_string, vec_f32_to_c_array,
+};
+
+/// Table of the SQLite database holding the cache, next to the sysdb and write log tables
+const CACHE_TABLE: &str = "embedding_cache";
+
+/// Number of keys looked up per query, well below the SQLite bound-parameter limit
+const CACHE_LOOKUP_CHUNK: usize = 400;
+
+/// Key of the cached embedding of `document` computed by `model`
+fn cache_key(model: &str, document: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    // The separator keeps ("ab", "c") and ("a", "bc") apart
+    hasher.update([0]);
+    hasher.update(document.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn cache_error(message: &'static str) -> impl Fn(sqlx::Error) -> FfiError {
+    move |e| FfiError::new(ChromaErrorCode::InternalError, message, Some(e.to_string()))
+}
+
+impl ChromaClient {
+    fn cache_db(&self) -> Result<&SqliteDb, FfiError> {
+        self.sqlite_db.as_ref().ok_or_else(|| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Embedding cache is not available",
+                Some("The SQLite database is not registered".to_string()),
+            )
+        })
+    }
+
+    /// Whether the cache table exists; it is created by the first put
+    fn cache_table_exists(&self, sqlite_db: &SqliteDb) -> Result<bool, sqlx::Error> {
+        let row = self.runtime.block_on(
+            sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(CACHE_TABLE)
+                .fetch_optional(sqlite_db.get_conn()),
+        )?;
+        Ok(row.is_some())
+    }
+
+    /// Reads the cached embeddings of `documents`, `None` for those not cached
+    fn cached_embeddings(
+        &self,
+        model: &str,
+        documents: &[String],
+    ) -> Result<Vec<Option<Vec<f32>>>, FfiError> {
+        let sqlite_db = self.cache_db()?;
+        let read_error = cache_error("Failed to read the embedding cache");
+
+        if !self.cache_table_exists(sqlite_db).map_err(&read_error)? {
+            return Ok(vec![None; documents.len()]);
+        }
+
+        let keys: Vec<String> = documents.iter().map(|d| cache_key(model, d)).collect();
+        let mut cached = HashMap::with_capacity(keys.len());
+        for chunk in keys.chunks(CACHE_LOOKUP_CHUNK) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql =
+                format!("SELECT key, embedding FROM {CACHE_TABLE} WHERE key IN ({placeholders})");
+            let rows = self
+                .runtime
+                .block_on(async {
+                    let mut query = sqlx::query(&sql);
+                    for key in chunk {
+                        query = query.bind(key);
+                    }
+                    query.fetch_all(sqlite_db.get_conn()).await
+                })
+                .map_err(&read_error)?;
+
+            for row in rows {
+                let key: String = row.try_get(0).map_err(&read_error)?;
+                let bytes: Vec<u8> = row.try_get(1).map_err(&read_error)?;
+                let embedding: Vec<f32> = bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                cached.insert(key, embedding);
+            }
+        }
+
+        Ok(keys.iter().map(|key| cached.get(key).cloned()).collect())
+    }
+
+    /// Stores the embeddings of `documents`, replacing those already cached
+    fn cache_embeddings(
+        &self,
+        model: &str,
+        documents: &[String],
+        embeddings: &[f32],
+        dimension: usize,
+    ) -> Result<(), FfiError> {
+        self.check_writable()?;
+        let sqlite_db = self.cache_db()?;
+        let write_error = cache_error("Failed to write the embedding cache");
+
+        self.runtime
+            .block_on(async {
+                let mut transaction = sqlite_db.get_conn().begin().await?;
+                sqlx::query(&format!(
+                    "CREATE TABLE IF NOT EXISTS {CACHE_TABLE} ( \
+                     key TEXT PRIMARY KEY, \
+                     model TEXT NOT NULL, \
+                     embedding BLOB NOT NULL)"
+                ))
+                .execute(&mut *transaction)
+                .await?;
+
+                let sql = format!(
+                    "INSERT OR REPLACE INTO {CACHE_TABLE} (key, model, embedding) VALUES (?, ?, ?)"
+                );
+                for (document, embedding) in documents.iter().zip(embeddings.chunks(dimension)) {
+                    let bytes: Vec<u8> = embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+                    sqlx::query(&sql)
+                        .bind(cache_key(model, document))
+                        .bind(model)
+                        .bind(bytes)
+                        .execute(&mut *transaction)
+                        .await?;
+                }
+                transaction.commit().await
+            })
+            .map_err(write_error)
+    }
+
+    /// Removes the cached embeddings of `model`, or of every model
+    fn clear_embedding_cache(&self, model: Option<&str>) -> Result<(), FfiError> {
+        self.check_writable()?;
+        let sqlite_db = self.cache_db()?;
+        let clear_error = cache_error("Failed to clear the embedding cache");
+        if !self.cache_table_exists(sqlite_db).map_err(&clear_error)? {
+            return Ok(());
+        }
+
+        let sql = match model {
+            Some(_) => format!("DELETE FROM {CACHE_TABLE} WHERE model = ?"),
+            None => format!("DELETE FROM {CACHE_TABLE}"),
+        };
+        let mut query = sqlx::query(&sql);
+        if let Some(model) = model {
+            query = query.bind(model);
+        }
+        self.runtime
+            .block_on(query.execute(sqlite_db.get_conn()))
+            .map(drop)
+            .map_err(clear_error)
+    }
+}
+
+/// Converts the model name and documents of a cache call
+unsafe fn cache_arguments(
+    model: *const c_char,
+    documents: *const *const c_char,
+    count: size_t,
+) -> Result<(String, Vec<String>), FfiError> {
+    let model = c_str_to_string(model).map_err(|e| {
+        FfiError::new(ChromaErrorCode::InvalidArgument, "Invalid model name", Some(e.to_string()))
+    })?;
+    let documents = c_array_to_vec_string(documents, count).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Failed to convert documents array",
+            Some(e.to_string()),
+        )
+    })?;
+    Ok((model, documents))
+}
+
+/// Looks up the cached embeddings of `count` documents computed by `model`.
+///
+/// `found_out` points to `count` flags, set to whether each document was cached. The
+/// embeddings of the cached documents are returned row-major in one block of
+/// `count * *embedding_dim_out` floats allocated with `chroma_malloc`, with NaN rows for the
+/// documents not cached; release it with `chroma_free`. When no document is cached the block
+/// is null and the dimension 0.
+///
+/// Embeddings are keyed by a SHA-256 hash of the model name and the document text, so the
+/// model name should change whenever the model does, e.g. `text-embedding-3-small@1536`.
+#[no_mangle]
+pub extern "C" fn chroma_embedding_cache_get(
+    client_handle: ChromaHandle,
+    model: *const c_char,
+    documents: *const *const c_char,
+    count: size_t,
+    found_out: *mut bool,
+    embeddings_out: *mut *mut c_float,
+    embedding_dim_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_embedding_cache_get", || {
+        let func_name = "chroma_embedding_cache_get";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || model.is_null()
+            || (documents.is_null() && count > 0)
+            || (found_out.is_null() && count > 0)
+            || embeddings_out.is_null()
+            || embedding_dim_out.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if model.is_null() {
+                "Model name pointer is null"
+            } else if documents.is_null() && count > 0 {
+                "Documents pointer is null"
+            } else if found_out.is_null() && count > 0 {
+                "Found flags pointer is null"
+            } else if embeddings_out.is_null() {
+                "Embeddings output pointer is null"
+            } else {
+                "Embedding dimension output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *embeddings_out = ptr::null_mut();
+            *embedding_dim_out = 0;
+        }
+
+        let (model, documents) = match unsafe { cache_arguments(model, documents, count) } {
+            Ok(arguments) => arguments,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let cached = match client.cached_embeddings(&model, &documents) {
+            Ok(cached) => cached,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let dimension = cached.iter().flatten().map(Vec::len).max().unwrap_or(0);
+        let mut values = Vec::with_capacity(cached.len() * dimension);
+        for (i, embedding) in cached.iter().enumerate() {
+            // A row of another dimension was cached under a reused model name; treat it as a miss
+            let hit = embedding.as_ref().filter(|e| e.len() == dimension && dimension > 0);
+            unsafe {
+                *found_out.add(i) = hit.is_some();
+            }
+            match hit {
+                Some(embedding) => values.extend_from_slice(embedding),
+                None => values.resize(values.len() + dimension, f32::NAN),
+            }
+        }
+
+        if dimension > 0 {
+            let (array, _) = vec_f32_to_c_array(values);
+            if array.is_null() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::MemoryError,
+                    "Failed to allocate embeddings",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::MemoryError as c_int;
+            }
+            unsafe {
+                *embeddings_out = array;
+                *embedding_dim_out = dimension;
+            }
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Caches the embeddings `model` computed for `count` documents, so re-ingesting unchanged
+/// documents does not compute them again.
+///
+/// `embeddings` holds `count * embedding_dim` floats, one row per document. The cache is
+/// stored in the `embedding_cache` table of the client's SQLite database and survives
+/// restarts; documents already cached are overwritten. Fails with `ReadOnly` on a read-only
+/// client.
+#[no_mangle]
+pub extern "C" fn chroma_embedding_cache_put(
+    client_handle: ChromaHandle,
+    model: *const c_char,
+    documents: *const *const c_char,
+    count: size_t,
+    embeddings: *const c_float,
+    embedding_dim: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_embedding_cache_put", || {
+        let func_name = "chroma_embedding_cache_put";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || model.is_null()
+            || (count > 0 && (documents.is_null() || embeddings.is_null() || embedding_dim == 0))
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if model.is_null() {
+                "Model name pointer is null"
+            } else if documents.is_null() {
+                "Documents pointer is null"
+            } else if embeddings.is_null() {
+                "Embeddings pointer is null"
+            } else {
+                "Embedding dimension is zero"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let (model, documents) = match unsafe { cache_arguments(model, documents, count) } {
+            Ok(arguments) => arguments,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let embeddings = unsafe { c_array_to_vec_f32(embeddings, count * embedding_dim) };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if count > 0 {
+            if let Err(e) = client.cache_embeddings(&model, &documents, &embeddings, embedding_dim)
+            {
+                return e.report(error_out, func_name);
+            }
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Removes the cached embeddings of `model`, or of every model when `model` is null
+#[no_mangle]
+pub extern "C" fn chroma_embedding_cache_clear(
+    client_handle: ChromaHandle,
+    model: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_embedding_cache_clear", || {
+        let func_name = "chroma_embedding_cache_clear";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let model = if model.is_null() {
+            None
+        } else {
+            match unsafe { c_str_to_string(model) } {
+                Ok(model) => Some(model),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid model name",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if let Err(e) = client.clear_embedding_cache(model.as_deref()) {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,609 @@
+// Encryption of a client's persist directory while no client has it open
+//
+// The SQLite database and HNSW segments are written by engines that cannot encrypt pages, so
+// the files are sealed when a client releases the directory and decrypted when one opens it.
+// They are plaintext in between, and stay so after a crash until the directory is sealed
+// again by `chroma_seal_directory` or the next client that opens it.
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use chroma_sqlite::db::SqliteDb;
+use libc::{c_char, c_int, size_t};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::client::{acquire_lock, LockMode};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Length of an AES-256 key in bytes
+pub const CHROMA_ENCRYPTION_KEY_LEN: size_t = 32;
+
+/// Suffix of an encrypted file, next to where its plaintext lives while a client has the
+/// directory open
+const SEALED_SUFFIX: &str = ".sealed";
+
+/// Suffix of a file being encrypted or decrypted, removed if the process dies midway
+const PARTIAL_SUFFIX: &str = ".sealing";
+
+/// Directory below the persist directory that sealed files are decrypted into, discarded if
+/// the process dies before every file is decrypted
+const UNSEALING_DIR: &str = ".unsealing";
+
+/// Name the decryption directory takes once every file is decrypted; its files are then
+/// moved into place, by the next client if the process dies midway
+const UNSEALED_DIR: &str = ".unsealed";
+
+/// Files of the persist directory that are never encrypted: the advisory lock and the SQLite
+/// shared-memory index, which hold no records
+const UNSEALED_FILES: &[&str] = &["chroma.lock", "chroma.sqlite3-shm"];
+
+/// Identifies the format of a sealed file
+const SEALED_MAGIC: &[u8; 8] = b"CHRSEAL1";
+
+/// Plaintext bytes per AES-GCM chunk, so large segment files never sit in memory whole
+const SEALED_CHUNK: usize = 1 << 20;
+
+/// Size of the AES-GCM authentication tag appended to each chunk
+const TAG_LEN: usize = 16;
+
+/// Key used by clients created without an encryption config, see `chroma_set_encryption_key`
+static DEFAULT_KEY: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+
+/// Reads a caller-supplied AES-256 key
+///
+/// # Safety
+///
+/// A non-null `key` must point to `key_len` readable bytes.
+pub(crate) unsafe fn read_key(key: *const u8, key_len: size_t) -> Result<[u8; 32], FfiError> {
+    if key.is_null() || key_len != CHROMA_ENCRYPTION_KEY_LEN {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid encryption key",
+            Some(format!(
+                "Expected a {}-byte AES-256 key",
+                CHROMA_ENCRYPTION_KEY_LEN
+            )),
+        ));
+    }
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(std::slice::from_raw_parts(key, key_len));
+    Ok(bytes)
+}
+
+/// Key set with `chroma_set_encryption_key`, if any
+pub(crate) fn default_key() -> Option<[u8; 32]> {
+    *DEFAULT_KEY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn io_error(message: &'static str, path: &Path, e: io::Error) -> FfiError {
+    FfiError::new(
+        ChromaErrorCode::InternalError,
+        message,
+        Some(format!("{}: {}", path.display(), e)),
+    )
+}
+
+/// Every file below a directory, walked depth first, leaving out the decryption directories
+fn walk_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let name = path.file_name().unwrap_or_default();
+            if name != UNSEALING_DIR && name != UNSEALED_DIR {
+                walk_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn has_suffix(path: &Path, suffix: &str) -> bool {
+    path.to_string_lossy().ends_with(suffix)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn without_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let name = path.to_string_lossy();
+    PathBuf::from(&name[..name.len() - suffix.len()])
+}
+
+/// Nonce of a chunk: the random prefix of the file followed by the chunk index
+fn chunk_nonce(prefix: &[u8], index: u32) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..8].copy_from_slice(prefix);
+    nonce[8..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+/// Reads up to `buf.len()` bytes, stopping early only at the end of the input
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypts `source` into `target`.
+///
+/// The header holds the format, a random nonce prefix and the plaintext length, and is
+/// authenticated with every chunk, so truncated, reordered or swapped chunks fail to decrypt.
+fn encrypt_file(cipher: &Aes256Gcm, source: &Path, target: &Path) -> io::Result<()> {
+    let length = fs::metadata(source)?.len();
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let mut header = Vec::with_capacity(24);
+    header.extend_from_slice(SEALED_MAGIC);
+    header.extend_from_slice(&nonce[..8]);
+    header.extend_from_slice(&length.to_le_bytes());
+
+    let mut reader = BufReader::new(File::open(source)?);
+    let mut writer = BufWriter::new(File::create(target)?);
+    writer.write_all(&header)?;
+
+    let mut chunk = vec![0u8; SEALED_CHUNK];
+    let mut index = 0u32;
+    loop {
+        let read = read_full(&mut reader, &mut chunk)?;
+        if read == 0 && index > 0 {
+            break;
+        }
+        let nonce = chunk_nonce(&nonce[..8], index);
+        let payload = Payload { msg: &chunk[..read], aad: &header };
+        let sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| io::Error::other("Encryption failed"))?;
+        writer.write_all(&sealed)?;
+        index += 1;
+        if read < SEALED_CHUNK {
+            break;
+        }
+    }
+
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+}
+
+/// Decrypts `source` into `target`; fails with `InvalidData` for a wrong key or a
+/// damaged file
+fn decrypt_file(cipher: &Aes256Gcm, source: &Path, target: &Path) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(source)?);
+    let mut header = [0u8; 24];
+    reader.read_exact(&mut header)?;
+    if !header.starts_with(SEALED_MAGIC) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a sealed file"));
+    }
+    let length = u64::from_le_bytes(header[16..].try_into().unwrap_or_default());
+
+    let mut writer = BufWriter::new(File::create(target)?);
+    let mut chunk = vec![0u8; SEALED_CHUNK + TAG_LEN];
+    let mut written = 0u64;
+    let mut index = 0u32;
+    loop {
+        let read = read_full(&mut reader, &mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        let nonce = chunk_nonce(&header[8..16], index);
+        let payload = Payload { msg: &chunk[..read], aad: &header };
+        let plain = cipher.decrypt(Nonce::from_slice(&nonce), payload).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "Wrong key or damaged file")
+        })?;
+        writer.write_all(&plain)?;
+        written += plain.len() as u64;
+        index += 1;
+    }
+    if written != length {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Sealed file is truncated"));
+    }
+
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()
+}
+
+/// Checks that `key` opens `sealed` by decrypting its first chunk; fails with `InvalidData`
+/// for a wrong key or a damaged file
+fn check_key(cipher: &Aes256Gcm, sealed: &Path) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(sealed)?);
+    let mut header = [0u8; 24];
+    reader.read_exact(&mut header)?;
+    if !header.starts_with(SEALED_MAGIC) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a sealed file"));
+    }
+    let mut chunk = vec![0u8; SEALED_CHUNK + TAG_LEN];
+    let read = read_full(&mut reader, &mut chunk)?;
+    let nonce = chunk_nonce(&header[8..16], 0);
+    let payload = Payload { msg: &chunk[..read], aad: &header };
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), payload)
+        .map(|_| ())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Wrong key or damaged file"))
+}
+
+/// Whether a file is left plaintext by `seal_directory`: sealed and partial files, the lock
+/// and the SQLite shared-memory index
+fn stays_unsealed(root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+    has_suffix(path, SEALED_SUFFIX)
+        || has_suffix(path, PARTIAL_SUFFIX)
+        || UNSEALED_FILES.contains(&relative.as_ref())
+}
+
+/// Whether a persist directory holds encrypted files, or files decrypted by a client that
+/// died before moving them into place
+pub(crate) fn is_sealed(persist_path: &str) -> bool {
+    let root = Path::new(persist_path);
+    let mut files = Vec::new();
+    root.join(UNSEALED_DIR).is_dir()
+        || walk_files(root, &mut files).is_ok()
+            && files.iter().any(|path| has_suffix(path, SEALED_SUFFIX))
+}
+
+/// Decrypts every sealed file of a persist directory in place.
+///
+/// The files are decrypted into a directory of their own first and only moved into place
+/// once all of them are, so the directory is either left sealed or unsealed as a whole. Fails
+/// with `InvalidArgument`, leaving the directory untouched, when the key does not match the
+/// one the directory was sealed with.
+pub(crate) fn unseal_directory(persist_path: &str, key: &[u8; 32]) -> Result<(), FfiError> {
+    let root = Path::new(persist_path);
+    let staging = root.join(UNSEALING_DIR);
+    let unsealed = root.join(UNSEALED_DIR);
+
+    // Files decrypted by a client that died while moving them into place are moved now
+    if unsealed.is_dir() {
+        return finish_unseal(root, &unsealed);
+    }
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| io_error("Failed to remove a partial decryption", &staging, e))?;
+    }
+
+    let cipher = Aes256Gcm::new(key.into());
+    let mut files = Vec::new();
+    if let Err(e) = walk_files(root, &mut files) {
+        return match e.kind() {
+            io::ErrorKind::NotFound => Ok(()),
+            _ => Err(io_error("Failed to list the persist directory", root, e)),
+        };
+    }
+
+    for path in &files {
+        if has_suffix(path, PARTIAL_SUFFIX) {
+            fs::remove_file(path)
+                .map_err(|e| io_error("Failed to remove a partial file", path, e))?;
+        }
+    }
+    for sealed in files.iter().filter(|path| has_suffix(path, SEALED_SUFFIX)) {
+        let plain = without_suffix(sealed, SEALED_SUFFIX);
+        let target = staging.join(plain.strip_prefix(root).unwrap_or(&plain));
+        let decrypted = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| decrypt_file(&cipher, sealed, &target));
+        if let Err(e) = decrypted {
+            let _ = fs::remove_dir_all(&staging);
+            if e.kind() == io::ErrorKind::InvalidData {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to decrypt the persist directory",
+                    Some(format!("{}: {}", sealed.display(), e)),
+                ));
+            }
+            return Err(io_error("Failed to decrypt the persist directory", sealed, e));
+        }
+    }
+
+    fs::rename(&staging, &unsealed)
+        .map_err(|e| io_error("Failed to complete the decryption", &staging, e))?;
+    finish_unseal(root, &unsealed)
+}
+
+/// Moves the files of a complete decryption into place, then removes the sealed files and
+/// the decryption directory. Safe to run again after a crash.
+fn finish_unseal(root: &Path, unsealed: &Path) -> Result<(), FfiError> {
+    let mut decrypted = Vec::new();
+    walk_files(unsealed, &mut decrypted)
+        .map_err(|e| io_error("Failed to list the decrypted files", unsealed, e))?;
+    for path in &decrypted {
+        let plain = root.join(path.strip_prefix(unsealed).unwrap_or(path));
+        fs::rename(path, &plain)
+            .map_err(|e| io_error("Failed to move a decrypted file into place", &plain, e))?;
+    }
+
+    let mut files = Vec::new();
+    walk_files(root, &mut files)
+        .map_err(|e| io_error("Failed to list the persist directory", root, e))?;
+    for sealed in files.iter().filter(|path| has_suffix(path, SEALED_SUFFIX)) {
+        fs::remove_file(sealed).map_err(|e| io_error("Failed to remove a sealed file", sealed, e))?;
+    }
+    fs::remove_dir_all(unsealed)
+        .map_err(|e| io_error("Failed to remove the decryption directory", unsealed, e))
+}
+
+/// Encrypts every file of a persist directory in place, replacing each with its sealed copy
+fn seal_directory(persist_path: &str, key: &[u8; 32]) -> Result<(), FfiError> {
+    let root = Path::new(persist_path);
+    let cipher = Aes256Gcm::new(key.into());
+    let mut files = Vec::new();
+    walk_files(root, &mut files)
+        .map_err(|e| io_error("Failed to list the persist directory", root, e))?;
+
+    for plain in files.iter().filter(|path| !stays_unsealed(root, path)) {
+        let sealed = with_suffix(plain, SEALED_SUFFIX);
+        let partial = with_suffix(plain, PARTIAL_SUFFIX);
+        if let Err(e) = encrypt_file(&cipher, plain, &partial) {
+            let _ = fs::remove_file(&partial);
+            return Err(io_error("Failed to encrypt the persist directory", plain, e));
+        }
+        fs::rename(&partial, &sealed)
+            .and_then(|_| fs::remove_file(plain))
+            .map_err(|e| io_error("Failed to replace a plaintext file", plain, e))?;
+    }
+    Ok(())
+}
+
+/// Seals the plaintext files a client that did not release the directory left behind, such as
+/// after a crash, and returns whether there were any.
+///
+/// A decryption the client did not finish is completed first, so every file is in one state
+/// or the other. Fails with `InvalidArgument`, sealing nothing, when files of the directory
+/// are already sealed with another key. The caller holds the directory lock.
+pub(crate) fn reseal_leftovers(persist_path: &str, key: &[u8; 32]) -> Result<bool, FfiError> {
+    let root = Path::new(persist_path);
+    let staging = root.join(UNSEALING_DIR);
+    let unsealed = root.join(UNSEALED_DIR);
+    if unsealed.is_dir() {
+        finish_unseal(root, &unsealed)?;
+    }
+    // A partial decryption holds plaintext copies of files that are still sealed
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| io_error("Failed to remove a partial decryption", &staging, e))?;
+    }
+
+    let mut files = Vec::new();
+    if let Err(e) = walk_files(root, &mut files) {
+        return match e.kind() {
+            io::ErrorKind::NotFound => Ok(false),
+            _ => Err(io_error("Failed to list the persist directory", root, e)),
+        };
+    }
+    if files.iter().all(|path| stays_unsealed(root, path)) {
+        return Ok(false);
+    }
+
+    let cipher = Aes256Gcm::new(key.into());
+    if let Some(sealed) = files.iter().find(|path| has_suffix(path, SEALED_SUFFIX)) {
+        if let Err(e) = check_key(&cipher, sealed) {
+            if e.kind() == io::ErrorKind::InvalidData {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to decrypt the persist directory",
+                    Some(format!("{}: {}", sealed.display(), e)),
+                ));
+            }
+            return Err(io_error("Failed to decrypt the persist directory", sealed, e));
+        }
+    }
+    tracing::warn!(
+        persist_path = %persist_path,
+        "Sealing plaintext files left in the persist directory"
+    );
+    seal_directory(persist_path, key)?;
+    Ok(true)
+}
+
+/// Encryption of a client's persist directory, which is sealed again once the client is
+/// dropped and the backend has closed its files, or once a failed client creation unwinds.
+/// Nothing is sealed while the client is open, nor if the process dies without dropping it.
+pub(crate) struct DirectoryEncryption {
+    persist_path: String,
+    /// Key the directory is sealed with on close, replaced by `chroma_rotate_encryption_key`
+    key: Mutex<[u8; 32]>,
+    /// Closed before sealing, so SQLite checkpoints its WAL into the database first
+    sqlite_db: Option<SqliteDb>,
+}
+
+impl DirectoryEncryption {
+    pub(crate) fn new(persist_path: String, key: [u8; 32]) -> Self {
+        DirectoryEncryption {
+            persist_path,
+            key: Mutex::new(key),
+            sqlite_db: None,
+        }
+    }
+
+    /// Registers the database the backend opened, to be closed before sealing
+    pub(crate) fn close_before_sealing(&mut self, sqlite_db: Option<SqliteDb>) {
+        self.sqlite_db = sqlite_db;
+    }
+}
+
+impl Drop for DirectoryEncryption {
+    fn drop(&mut self) {
+        if let Some(sqlite_db) = self.sqlite_db.take() {
+            // The client's runtime is gone by now
+            if let Ok(runtime) = tokio::runtime::Builder::new_current_thread().build() {
+                runtime.block_on(sqlite_db.get_conn().close());
+            }
+        }
+
+        let key = *self.key.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = seal_directory(&self.persist_path, &key) {
+            tracing::error!(
+                persist_path = %self.persist_path,
+                error = ?e,
+                "Failed to encrypt the persist directory"
+            );
+        }
+    }
+}
+
+/// Sets the AES-256 key used by clients created afterwards without an encryption config,
+/// for hosts that fetch the key from a secret store once at startup.
+///
+/// `key` must point to `CHROMA_ENCRYPTION_KEY_LEN` (32) bytes; pass null to clear the key.
+/// Clients already created keep the key they were created with.
+#[no_mangle]
+pub extern "C" fn chroma_set_encryption_key(
+    key: *const u8,
+    key_len: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_encryption_key", || {
+        let func_name = "chroma_set_encryption_key";
+
+        let key = if key.is_null() {
+            None
+        } else {
+            match unsafe { read_key(key, key_len) } {
+                Ok(key) => Some(key),
+                Err(e) => return e.report(error_out, func_name),
+            }
+        };
+        *DEFAULT_KEY.lock().unwrap_or_else(|e| e.into_inner()) = key;
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Seals a persist directory that a client with encryption left plaintext because it was
+/// not released, such as when the process crashed, was killed or aborted on a panic.
+///
+/// Files of an encrypted directory are plaintext from the moment a client opens it until it
+/// is released; a client that never is leaves them so until the next client opens the
+/// directory with the key, which seals them first. Hosts call this at startup or from a
+/// supervisor to close that window without opening a client. `key` must be the
+/// `CHROMA_ENCRYPTION_KEY_LEN` (32) byte key the directory is sealed with; files already
+/// sealed with another key fail the call with `InvalidArgument` and nothing is sealed.
+///
+/// Takes the exclusive directory lock without waiting, so it fails with `ConcurrentAccess`
+/// while a client holds the lock; a client created with `CHROMA_LOCK_NONE` is not detected
+/// and must not have the directory open. Fails with `NotFound` if the directory does not
+/// exist. Sealing a directory that is already sealed does nothing.
+#[no_mangle]
+pub extern "C" fn chroma_seal_directory(
+    persist_path: *const c_char,
+    key: *const u8,
+    key_len: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_seal_directory", || {
+        let func_name = "chroma_seal_directory";
+
+        if persist_path.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Persist path pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let persist_path = match unsafe { c_str_to_string(persist_path) } {
+            Ok(path) => path,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid persist path",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+        let key = match unsafe { read_key(key, key_len) } {
+            Ok(key) => key,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if !Path::new(&persist_path).is_dir() {
+            set_error(
+                error_out,
+                ChromaErrorCode::NotFound,
+                "Persist directory not found",
+                func_name,
+                Some(&persist_path),
+            );
+            return ChromaErrorCode::NotFound as c_int;
+        }
+
+        let _lock = match acquire_lock(&persist_path, LockMode::Exclusive, Duration::ZERO, false) {
+            Ok(lock) => lock,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = reseal_leftovers(&persist_path, &key) {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Replaces the key the persist directory of an encrypted client is sealed with when the
+/// client is released.
+///
+/// Rotating the key of a directory takes opening it with the current key, calling this
+/// with the new one and releasing the client; the directory is then readable with the new
+/// key only. Fails with `InvalidArgument` for clients without encryption.
+#[no_mangle]
+pub extern "C" fn chroma_rotate_encryption_key(
+    client_handle: ChromaHandle,
+    key: *const u8,
+    key_len: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_rotate_encryption_key", || {
+        let func_name = "chroma_rotate_encryption_key";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let new_key = match unsafe { read_key(key, key_len) } {
+            Ok(key) => key,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let Some(encryption) = client.encryption.as_ref() else {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client is not encrypted",
+                func_name,
+                Some("The client was created without an encryption key"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        };
+        *encryption.key.lock().unwrap_or_else(|e| e.into_inner()) = new_key;
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,237 @@
+// Explicit and periodic flushing of pending writes to disk
+use chroma_log::local_compaction_manager::BackfillMessage;
+use chroma_types::CollectionUuid;
+use libc::{c_int, c_uint};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+
+impl ChromaClient {
+    /// IDs of every collection in the local sysdb, across tenants and databases
+    fn local_collection_ids(&self) -> Result<Vec<CollectionUuid>, FfiError> {
+        let Some(sqlite_db) = self.sqlite_db.as_ref() else {
+            return Ok(Vec::new());
+        };
+
+        let query = sqlx::query_scalar("SELECT id FROM collections");
+        let ids: Vec<String> = self
+            .runtime
+            .block_on(query.fetch_all(sqlite_db.get_conn()))
+            .map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Failed to list collections",
+                    Some(e.to_string()),
+                )
+            })?;
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| id.parse().ok().map(CollectionUuid))
+            .collect())
+    }
+
+    /// Applies the pending write log of the given collections, or of every collection, to
+    /// their segments, checkpoints the SQLite WAL into the database file and uploads the
+    /// changed files to the object store the client mirrors, if any
+    pub(crate) fn flush(&self, collection_id: Option<CollectionUuid>) -> Result<(), FfiError> {
+        self.check_writable()?;
+
+        let collection_ids = match collection_id {
+            Some(collection_id) => vec![collection_id],
+            None => self.local_collection_ids()?,
+        };
+
+        let started = Instant::now();
+        let result = self.runtime.block_on(async {
+            if let Some(compaction_manager) = self.compaction_manager.as_ref() {
+                for collection_id in collection_ids {
+                    compaction_manager
+                        .request(BackfillMessage { collection_id }, None)
+                        .await
+                        .map_err(|e| {
+                            FfiError::backend(
+                                ChromaErrorCode::InternalError,
+                                "Failed to reach compactor",
+                                &e,
+                            )
+                        })?
+                        .map_err(|e| {
+                            FfiError::backend(
+                                ChromaErrorCode::InternalError,
+                                "Failed to flush log",
+                                &e,
+                            )
+                        })?;
+                }
+            }
+
+            if let Some(sqlite_db) = self.sqlite_db.as_ref() {
+                sqlx::query("PRAGMA wal_checkpoint(FULL)")
+                    .execute(sqlite_db.get_conn())
+                    .await
+                    .map_err(|e| {
+                        FfiError::new(
+                            ChromaErrorCode::InternalError,
+                            "Failed to checkpoint the SQLite WAL",
+                            Some(e.to_string()),
+                        )
+                    })?;
+            }
+            Ok(())
+        });
+
+        self.metrics.record("flush", started.elapsed(), result.is_ok());
+        result?;
+        self.sync_object_store()
+    }
+}
+
+/// Flushes every collection of a client each `interval` until the client is released or
+/// the sender is dropped
+fn run_auto_flush(client: Weak<ChromaClient>, interval: Duration, stop: mpsc::Receiver<()>) {
+    while let Err(RecvTimeoutError::Timeout) = stop.recv_timeout(interval) {
+        let Some(client) = client.upgrade() else {
+            return;
+        };
+        // Failures are recorded in the client metrics; the next tick tries again
+        let _ = client.flush(None);
+    }
+}
+
+/// Forces pending writes to disk and returns once they are durable.
+///
+/// Pass a collection handle of the client to flush only that collection, or
+/// `CHROMA_INVALID_HANDLE` (0) to flush every collection of the client. The pending write
+/// log is applied to the local segments and the SQLite WAL is checkpointed into the
+/// database file. Clients mirroring their persist directory to an object store then upload
+/// the files that changed. Unlike `chroma_optimize_collection`, the log is not purged.
+/// Fails with `ReadOnly` on read-only clients.
+#[no_mangle]
+pub extern "C" fn chroma_flush(
+    client_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_flush", || {
+        let func_name = "chroma_flush";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let collection_id = if collection_handle == CHROMA_INVALID_HANDLE {
+            None
+        } else {
+            let collection = match collection_from_handle(collection_handle) {
+                Ok(collection) => collection,
+                Err(e) => return e.report(error_out, func_name),
+            };
+            if !Arc::ptr_eq(&collection.client, &client) {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Collection belongs to another client",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+            Some(collection.id)
+        };
+
+        match client.flush(collection_id) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Flushes every collection of a client in the background each `interval_ms` milliseconds,
+/// as `chroma_flush` with no collection would.
+///
+/// Pass 0 to stop automatic flushing. Setting a new interval replaces the previous one.
+/// Flushing stops once the client and every collection opened from it are released;
+/// failures are counted under `flush` in the client metrics. Fails with `ReadOnly` on
+/// read-only clients.
+#[no_mangle]
+pub extern "C" fn chroma_set_auto_flush_interval(
+    client_handle: ChromaHandle,
+    interval_ms: c_uint,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_auto_flush_interval", || {
+        let func_name = "chroma_set_auto_flush_interval";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let mut auto_flush = match client.auto_flush.lock() {
+            Ok(auto_flush) => auto_flush,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // Dropping the sender stops the thread flushing at the previous interval
+        *auto_flush = None;
+
+        if interval_ms > 0 {
+            let (sender, receiver) = mpsc::channel();
+            let weak = Arc::downgrade(&client);
+            let interval = Duration::from_millis(interval_ms.into());
+            let spawned = thread::Builder::new()
+                .name("chroma-auto-flush".to_string())
+                .spawn(move || run_auto_flush(weak, interval, receiver));
+            if let Err(e) = spawned {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InternalError,
+                    "Failed to start the auto-flush thread",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InternalError as c_int;
+            }
+            *auto_flush = Some(sender);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,170 @@
+// Readiness report of a client, for health probes
+use libc::{c_char, c_int};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use uuid::Uuid;
+
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// Outcome of a single check, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum HealthStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl HealthStatus {
+    fn name(self) -> &'static str {
+        match self {
+            HealthStatus::Pass => "pass",
+            HealthStatus::Warn => "warn",
+            HealthStatus::Fail => "fail",
+        }
+    }
+}
+
+type CheckOutcome = (HealthStatus, String);
+
+/// Runs a trivial query on the SQLite pool shared by the sysdb and the log
+fn check_sqlite(client: &ChromaClient) -> CheckOutcome {
+    let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+        return (HealthStatus::Fail, "SQLite database is not registered".to_string());
+    };
+    match client.runtime.block_on(sqlx::query("SELECT 1").execute(sqlite_db.get_conn())) {
+        Ok(_) => (HealthStatus::Pass, "SELECT 1 succeeded".to_string()),
+        Err(e) => (HealthStatus::Fail, e.to_string()),
+    }
+}
+
+/// Writes and removes a probe file in the persist directory
+fn check_persist_path(client: &ChromaClient) -> CheckOutcome {
+    let Some(persist_path) = client.persist_path.as_deref() else {
+        return (HealthStatus::Pass, "In-memory client, nothing is persisted".to_string());
+    };
+    if !Path::new(persist_path).is_dir() {
+        return (HealthStatus::Fail, format!("{} is not a directory", persist_path));
+    }
+    if client.read_only {
+        return (HealthStatus::Pass, format!("{} exists; read-only client", persist_path));
+    }
+
+    let probe = Path::new(persist_path).join(format!(".chroma-health-{}", Uuid::new_v4()));
+    match fs::write(&probe, b"ok").and_then(|_| fs::remove_file(&probe)) {
+        Ok(()) => (HealthStatus::Pass, format!("{} is writable", persist_path)),
+        Err(e) => (HealthStatus::Fail, format!("{} is not writable: {}", persist_path, e)),
+    }
+}
+
+/// Checks the HNSW index cache configuration the client was created with
+fn check_cache(client: &ChromaClient) -> CheckOutcome {
+    let hnsw_cache = &client.effective_config["hnsw_cache"];
+    let cache_type = hnsw_cache["type"].as_str().unwrap_or_default();
+    if cache_type == "disk" {
+        let dir = hnsw_cache["dir"].as_str().unwrap_or_default();
+        if !Path::new(dir).is_dir() {
+            return (HealthStatus::Fail, format!("Disk cache directory {} is missing", dir));
+        }
+    }
+
+    let capacity = hnsw_cache["capacity"].as_u64().unwrap_or_default();
+    if capacity == 0 {
+        return (
+            HealthStatus::Warn,
+            "HNSW cache capacity is 0; every query loads its index from disk".to_string(),
+        );
+    }
+    (HealthStatus::Pass, format!("{} HNSW cache for {} indexes", cache_type, capacity))
+}
+
+/// Checks that the runtime driving the local executor accepts work and that writes
+/// are being compacted
+fn check_executor(client: &ChromaClient) -> CheckOutcome {
+    if let Err(e) = client.runtime.block_on(async { tokio::spawn(async {}).await }) {
+        return (HealthStatus::Fail, format!("Runtime does not accept tasks: {}", e));
+    }
+    if client.compaction_manager.is_none() {
+        return (
+            HealthStatus::Warn,
+            "Local executor ready, but no compaction manager is running".to_string(),
+        );
+    }
+    (HealthStatus::Pass, "Local executor ready".to_string())
+}
+
+fn health_report(client: &ChromaClient) -> Value {
+    let checks: [(&str, fn(&ChromaClient) -> CheckOutcome); 4] = [
+        ("sqlite", check_sqlite),
+        ("persist_path", check_persist_path),
+        ("cache", check_cache),
+        ("executor", check_executor),
+    ];
+
+    let mut overall = HealthStatus::Pass;
+    let mut results = Vec::with_capacity(checks.len());
+    for (name, check) in checks {
+        let started = Instant::now();
+        let (status, detail) = check(client);
+        overall = overall.max(status);
+        results.push(json!({
+            "name": name,
+            "status": status.name(),
+            "detail": detail,
+            "duration_ms": started.elapsed().as_secs_f64() * 1000.0,
+        }));
+    }
+
+    json!({ "status": overall.name(), "checks": results })
+}
+
+/// Checks that a client can serve requests and reports the result as JSON.
+///
+/// The report holds an overall `status` (`pass`, `warn` or `fail`, the worst of all
+/// checks) and a `checks` array with the `name`, `status`, `detail` and `duration_ms`
+/// of the `sqlite`, `persist_path`, `cache` and `executor` checks. Failing checks do not
+/// fail the call; only invalid arguments do.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_health_check(
+    client_handle: ChromaHandle,
+    out_report_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_health_check", || {
+        let func_name = "chroma_health_check";
+
+        if client_handle == CHROMA_INVALID_HANDLE || out_report_json.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_report_json = string_to_c_str(health_report(&client).to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
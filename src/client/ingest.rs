@@ -0,0 +1,647 @@
+// Native ingestion queue, decoupling the latency of the caller from that of the writes
+use chroma_types::{AddCollectionRecordsRequest, Metadata};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::abi::{read_sized, SizedInput};
+use crate::client::CHROMA_WAIT_FOREVER;
+use crate::collection::{read_records, ChromaCollection, RecordWrites};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+
+/// Workers draining the queue unless configured otherwise
+const DEFAULT_INGEST_WORKERS: c_uint = 2;
+/// Records written per add request unless configured otherwise
+const DEFAULT_INGEST_BATCH_SIZE: c_uint = 1000;
+/// Records the queue holds before `chroma_enqueue_add` fails, unless configured otherwise
+const DEFAULT_INGEST_MAX_PENDING: size_t = 100_000;
+
+/// Configuration of the ingestion queue of a client; 0 leaves a field at its default
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChromaIngestOptions {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Background threads writing queued records, 2 by default
+    pub workers: c_uint,
+    /// Most records written by one add request, 1000 by default
+    pub max_batch_size: c_uint,
+    /// Most records queued and not yet written before enqueueing fails with `Busy`, 100000
+    /// by default
+    pub max_pending: size_t,
+}
+
+unsafe impl SizedInput for ChromaIngestOptions {}
+
+impl ChromaIngestOptions {
+    fn workers(&self) -> usize {
+        if self.workers > 0 {
+            self.workers as usize
+        } else {
+            DEFAULT_INGEST_WORKERS as usize
+        }
+    }
+
+    fn max_batch_size(&self) -> usize {
+        if self.max_batch_size > 0 {
+            self.max_batch_size as usize
+        } else {
+            DEFAULT_INGEST_BATCH_SIZE as usize
+        }
+    }
+
+    fn max_pending(&self) -> usize {
+        if self.max_pending > 0 {
+            self.max_pending
+        } else {
+            DEFAULT_INGEST_MAX_PENDING
+        }
+    }
+}
+
+/// Counters of the ingestion queue of a client, filled in by `chroma_queue_stats` in a
+/// caller-owned struct
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChromaQueueStats {
+    /// Background threads running
+    pub workers: c_uint,
+    /// Records queued and not yet picked up by a worker
+    pub pending: size_t,
+    /// Records being written by the workers
+    pub in_flight: size_t,
+    /// Records written since the client was created
+    pub written: u64,
+    /// Records whose add request failed since the client was created
+    pub failed: u64,
+    /// Add requests sent by the workers
+    pub batches: u64,
+    /// Add requests that failed
+    pub failed_batches: u64,
+}
+
+/// Records of one `chroma_enqueue_add` call, or part of them
+struct IngestJob {
+    collection: Arc<ChromaCollection>,
+    records: RecordWrites<Metadata>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    options: ChromaIngestOptions,
+    jobs: VecDeque<IngestJob>,
+    stats: ChromaQueueStats,
+    /// Error of the first batch that failed since the last `chroma_queue_flush`
+    first_error: Option<FfiError>,
+    /// Failed records and batches since the last `chroma_queue_flush`
+    unreported: (u64, u64),
+    stopped: bool,
+}
+
+#[derive(Default)]
+struct QueueShared {
+    state: Mutex<QueueState>,
+    /// Notified when records are queued, the workers are reconfigured or the queue stops
+    queued: Condvar,
+    /// Notified when a worker finishes a batch
+    written: Condvar,
+}
+
+impl QueueShared {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Waits up to `timeout` for every queued record to be written, and returns whether
+    /// the queue drained
+    fn wait_drained(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.lock();
+        while state.stats.pending > 0 || state.stats.in_flight > 0 {
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.written
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => self.written.wait(state).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+        true
+    }
+}
+
+/// Ingestion queue of a client, filled by `chroma_enqueue_add` and drained by background
+/// workers. The workers stop when the queue is dropped with the client.
+#[derive(Default)]
+pub(crate) struct IngestQueue(Arc<QueueShared>);
+
+impl Drop for IngestQueue {
+    fn drop(&mut self) {
+        self.0.lock().stopped = true;
+        self.0.queued.notify_all();
+    }
+}
+
+impl IngestQueue {
+    /// Starts workers until as many run as configured
+    fn spawn_workers(&self, state: &mut QueueState) -> Result<(), FfiError> {
+        while (state.stats.workers as usize) < state.options.workers() {
+            let shared = self.0.clone();
+            thread::Builder::new()
+                .name("chroma-ingest".to_string())
+                .spawn(move || run_worker(shared))
+                .map_err(|e| {
+                    FfiError::new(
+                        ChromaErrorCode::InternalError,
+                        "Failed to start an ingestion worker",
+                        Some(e.to_string()),
+                    )
+                })?;
+            state.stats.workers += 1;
+        }
+        Ok(())
+    }
+
+    fn configure(&self, options: ChromaIngestOptions) -> Result<(), FfiError> {
+        let mut state = self.0.lock();
+        state.options = options;
+        // Workers over the new count exit once they finish their batch
+        self.0.queued.notify_all();
+        if state.stats.pending > 0 {
+            self.spawn_workers(&mut state)?;
+        }
+        Ok(())
+    }
+
+    fn enqueue(
+        &self,
+        collection: Arc<ChromaCollection>,
+        records: RecordWrites<Metadata>,
+    ) -> Result<(), FfiError> {
+        let mut state = self.0.lock();
+        if state.stopped {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Client is shutting down",
+                Some("The ingestion queue was closed by chroma_shutdown_client".to_string()),
+            ));
+        }
+        let count = records.ids.len();
+        let max_pending = state.options.max_pending();
+        if state.stats.pending + state.stats.in_flight + count > max_pending {
+            return Err(FfiError::new(
+                ChromaErrorCode::Busy,
+                "Ingestion queue is full",
+                Some(format!(
+                    "{} records are waiting to be written and the queue holds at most {}",
+                    state.stats.pending + state.stats.in_flight,
+                    max_pending
+                )),
+            ));
+        }
+        self.spawn_workers(&mut state)?;
+
+        let RecordWrites {
+            ids,
+            embeddings,
+            metadatas,
+            documents,
+        } = records;
+        let batch_size = state.options.max_batch_size();
+        let mut embeddings = embeddings.into_iter();
+        let mut metadatas = metadatas.into_iter();
+        let mut documents = documents.into_iter();
+        for chunk in ids.chunks(batch_size) {
+            state.jobs.push_back(IngestJob {
+                collection: collection.clone(),
+                records: RecordWrites {
+                    ids: chunk.to_vec(),
+                    embeddings: embeddings.by_ref().take(chunk.len()).collect(),
+                    metadatas: metadatas.by_ref().take(chunk.len()).collect(),
+                    documents: documents.by_ref().take(chunk.len()).collect(),
+                },
+            });
+        }
+        state.stats.pending += count;
+        self.0.queued.notify_all();
+        Ok(())
+    }
+
+    fn stats(&self) -> ChromaQueueStats {
+        self.0.lock().stats
+    }
+
+    /// Waits up to `timeout` for the queue to drain, then reports the batches that failed
+    /// since the last flush
+    fn flush(&self, timeout: Option<Duration>) -> Result<(), FfiError> {
+        if !self.0.wait_drained(timeout) {
+            let state = self.0.lock();
+            return Err(FfiError::new(
+                ChromaErrorCode::Busy,
+                "Ingestion queue did not drain in time",
+                Some(format!(
+                    "{} records are still waiting to be written",
+                    state.stats.pending + state.stats.in_flight
+                )),
+            ));
+        }
+
+        let mut state = self.0.lock();
+        let (failed, failed_batches) = std::mem::take(&mut state.unreported);
+        match state.first_error.take() {
+            Some(mut error) => {
+                let first = match &error.details {
+                    Some(details) => format!("{}: {}", error.message, details),
+                    None => error.message.to_string(),
+                };
+                error.message = "Some queued records were not written";
+                error.details = Some(format!(
+                    "{} batches ({} records) failed; first error: {}",
+                    failed_batches, failed, first
+                ));
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Stops accepting records, waits up to `timeout` for the queued ones to be written and
+    /// discards the rest, returning how many were discarded
+    pub(crate) fn close(&self, timeout: Duration) -> usize {
+        self.0.wait_drained(Some(timeout));
+        let mut state = self.0.lock();
+        let discarded = state.stats.pending;
+        state.jobs.clear();
+        state.stats.pending = 0;
+        state.stopped = true;
+        self.0.queued.notify_all();
+        discarded
+    }
+}
+
+/// Takes the next jobs off the queue, merging those of the same collection up to the batch
+/// size into one add
+fn next_batch(state: &mut QueueState) -> Option<IngestJob> {
+    let mut batch = state.jobs.pop_front()?;
+    let batch_size = state.options.max_batch_size();
+    while let Some(next) = state.jobs.front() {
+        if !Arc::ptr_eq(&next.collection, &batch.collection)
+            || batch.records.ids.len() + next.records.ids.len() > batch_size
+        {
+            break;
+        }
+        let Some(next) = state.jobs.pop_front() else {
+            break;
+        };
+        batch.records.ids.extend(next.records.ids);
+        batch.records.embeddings.extend(next.records.embeddings);
+        batch.records.metadatas.extend(next.records.metadatas);
+        batch.records.documents.extend(next.records.documents);
+    }
+    Some(batch)
+}
+
+/// Writes one batch of queued records, retried by the client's retry policy
+fn write_batch(job: IngestJob) -> Result<(), FfiError> {
+    let IngestJob {
+        collection,
+        records,
+    } = job;
+    let count = records.ids.len();
+    let dimension = records.embeddings.iter().flatten().next().map(Vec::len);
+    let request = AddCollectionRecordsRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        records.ids,
+        records.embeddings.into_iter().flatten().collect(),
+        Some(records.documents),
+        None, // uris
+        Some(records.metadatas),
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create add request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    let client = &collection.client;
+    client
+        .block_on_with_retry(
+            "add",
+            collection
+                .scope()
+                .with_records(Some(count))
+                .with_dimension(dimension),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.add(request).await }
+            },
+        )
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to add documents", &e)
+        })?;
+    client.metrics.add_records(count);
+    Ok(())
+}
+
+/// Writes queued batches until the queue stops or the worker is no longer needed
+fn run_worker(shared: Arc<QueueShared>) {
+    loop {
+        let job = {
+            let mut state = shared.lock();
+            loop {
+                if state.stopped || state.stats.workers as usize > state.options.workers() {
+                    state.stats.workers -= 1;
+                    return;
+                }
+                if let Some(job) = next_batch(&mut state) {
+                    let count = job.records.ids.len();
+                    state.stats.pending -= count;
+                    state.stats.in_flight += count;
+                    break job;
+                }
+                state = shared.queued.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+        };
+
+        let count = job.records.ids.len();
+        // The job may hold the last reference to the client, so it is dropped before locking
+        let result = write_batch(job);
+
+        let mut state = shared.lock();
+        state.stats.in_flight -= count;
+        state.stats.batches += 1;
+        match result {
+            Ok(()) => state.stats.written += count as u64,
+            Err(e) => {
+                state.stats.failed += count as u64;
+                state.stats.failed_batches += 1;
+                state.unreported.0 += count as u64;
+                state.unreported.1 += 1;
+                state.first_error.get_or_insert(e);
+            }
+        }
+        shared.written.notify_all();
+    }
+}
+
+/// Configures the ingestion queue of a client; a null `options` restores the defaults.
+///
+/// The queue starts its workers on the first `chroma_enqueue_add`. Lowering the number of
+/// workers stops the extra ones once their current batch is written; raising it starts new
+/// ones as soon as records are queued.
+#[no_mangle]
+pub extern "C" fn chroma_configure_ingest_queue(
+    client_handle: ChromaHandle,
+    options: *const ChromaIngestOptions,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_configure_ingest_queue", || {
+        let func_name = "chroma_configure_ingest_queue";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let options = match unsafe { read_sized(options) } {
+            Ok(options) => options.unwrap_or_default(),
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match client.ingest.configure(options) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Queues records to add and returns without waiting for them to be written. The arguments
+/// match `chroma_add`, except that every record needs an embedding and IDs are not generated.
+///
+/// Records are checked as `chroma_add` checks them before being queued. Background workers
+/// of the client then write them in batches, merging consecutive calls for the same
+/// collection, with the client's retry policy; existing IDs are left unchanged, as with
+/// `CHROMA_ON_CONFLICT_IGNORE`. Write failures are counted by `chroma_queue_stats` and
+/// returned by the next `chroma_queue_flush`. Fails with `Busy` when the queue already holds
+/// `max_pending` records.
+#[no_mangle]
+pub extern "C" fn chroma_enqueue_add(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const *const c_float,
+    embedding_dim: size_t,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_enqueue_add", || {
+        let func_name = "chroma_enqueue_add";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || ids.is_null()
+            || ids_count == 0
+            || embeddings.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs pointer is null"
+            } else if ids_count == 0 {
+                "IDs count is zero"
+            } else {
+                "Embeddings pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let records = match unsafe {
+            read_records::<Metadata>(
+                ids,
+                ids_count,
+                embeddings,
+                embedding_dim,
+                metadatas_json,
+                documents,
+            )
+        } {
+            Ok(records) => records,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Some(index) = records.embeddings.iter().position(Option::is_none) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Embedding pointer is null",
+                func_name,
+                Some(&format!("Null embedding at index {}", index)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+        if let Err(e) =
+            collection.validate_new_metadatas(ids_count, Some(records.metadatas.as_slice()))
+        {
+            return e.report(error_out, func_name);
+        }
+        // Built only to run the checks the backend applies to adds
+        if let Err(e) = AddCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            records.ids.clone(),
+            records.embeddings.iter().flatten().cloned().collect(),
+            Some(records.documents.clone()),
+            None,
+            Some(records.metadatas.clone()),
+        ) {
+            set_error(
+                error_out,
+                ChromaErrorCode::ValidationError,
+                "Failed to create add request",
+                func_name,
+                Some(&format!("Validation error: {:?}", e)),
+            );
+            return ChromaErrorCode::ValidationError as c_int;
+        }
+
+        let client = collection.client.clone();
+        match client.ingest.enqueue(collection, records) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Reports the counters of the ingestion queue of a client without blocking
+#[no_mangle]
+pub extern "C" fn chroma_queue_stats(
+    client_handle: ChromaHandle,
+    stats_out: *mut ChromaQueueStats,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_queue_stats", || {
+        let func_name = "chroma_queue_stats";
+
+        if client_handle == CHROMA_INVALID_HANDLE || stats_out.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Stats output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *stats_out = client.ingest.stats();
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Waits up to `timeout_ms` for every record queued with `chroma_enqueue_add` to be written:
+/// 0 only checks, `CHROMA_WAIT_FOREVER` waits without limit. Fails with `Busy` if records
+/// are still queued when the timeout elapses.
+///
+/// Once the queue drained, returns the error of the first batch that failed since the last
+/// flush, with the number of failed batches and records, so each failure is reported once.
+#[no_mangle]
+pub extern "C" fn chroma_queue_flush(
+    client_handle: ChromaHandle,
+    timeout_ms: c_uint,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_queue_flush", || {
+        let func_name = "chroma_queue_flush";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let timeout = (timeout_ms != CHROMA_WAIT_FOREVER)
+            .then(|| Duration::from_millis(timeout_ms.into()));
+        match client.ingest.flush(timeout) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,129 @@
+// Advisory lock guarding a persist directory against concurrent clients
+use libc::c_int;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Modes accepted in `LockConfigFFI::mode`
+pub const CHROMA_LOCK_NONE: c_int = 0;
+pub const CHROMA_LOCK_EXCLUSIVE: c_int = 1;
+pub const CHROMA_LOCK_SHARED_READ: c_int = 2;
+
+/// File name of the lock file inside the persist directory
+const LOCK_FILE: &str = "chroma.lock";
+
+/// Delay between two attempts while waiting for the lock
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Parsed form of `LockConfigFFI::mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockMode {
+    None,
+    Exclusive,
+    SharedRead,
+}
+
+impl LockMode {
+    pub(crate) fn from_ffi(mode: c_int) -> Result<Self, FfiError> {
+        match mode {
+            CHROMA_LOCK_NONE => Ok(LockMode::None),
+            CHROMA_LOCK_EXCLUSIVE => Ok(LockMode::Exclusive),
+            CHROMA_LOCK_SHARED_READ => Ok(LockMode::SharedRead),
+            mode => Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Unknown lock mode",
+                Some(format!("Got {}, expected 0 (none), 1 (exclusive) or 2 (shared read)", mode)),
+            )),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            LockMode::None => "none",
+            LockMode::Exclusive => "exclusive",
+            LockMode::SharedRead => "shared_read",
+        }
+    }
+}
+
+fn try_lock(file: &File, mode: LockMode) -> Result<(), TryLockError> {
+    match mode {
+        LockMode::SharedRead => file.try_lock_shared(),
+        _ => file.try_lock(),
+    }
+}
+
+/// Takes the advisory lock of a persist directory, waiting up to `wait` for other
+/// clients to release it.
+///
+/// The lock is held for as long as the returned file stays open. Read-only clients
+/// never create the lock file, so they need a writer to have opened the directory first.
+pub(crate) fn acquire_lock(
+    persist_path: &str,
+    mode: LockMode,
+    wait: Duration,
+    read_only: bool,
+) -> Result<Option<File>, FfiError> {
+    if mode == LockMode::None {
+        return Ok(None);
+    }
+
+    let path = Path::new(persist_path).join(LOCK_FILE);
+    let opened = if read_only {
+        OpenOptions::new().read(true).open(&path)
+    } else {
+        fs::create_dir_all(persist_path).and_then(|_| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+        })
+    };
+    let file = opened.map_err(|e| {
+        let code = if e.kind() == io::ErrorKind::NotFound {
+            ChromaErrorCode::NotFound
+        } else {
+            ChromaErrorCode::InternalError
+        };
+        FfiError::new(
+            code,
+            "Failed to open lock file",
+            Some(format!("{}: {}", path.display(), e)),
+        )
+    })?;
+
+    let deadline = Instant::now() + wait;
+    loop {
+        match try_lock(&file, mode) {
+            Ok(()) => return Ok(Some(file)),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(LOCK_POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::ConcurrentAccess,
+                    "Persist directory is locked by another client",
+                    Some(format!(
+                        "Could not take the {} lock on {} within {} ms",
+                        mode.name(),
+                        path.display(),
+                        wait.as_millis()
+                    )),
+                ));
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Failed to lock persist directory",
+                    Some(format!("{}: {}", path.display(), e)),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Per-client operation metrics
+use libc::{c_char, c_int};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// Number of most recent latencies kept per operation for percentiles
+const LATENCY_WINDOW: usize = 1024;
+
+#[derive(Default)]
+struct OperationStats {
+    calls: u64,
+    errors: u64,
+    total_micros: u64,
+    recent_micros: VecDeque<u64>,
+}
+
+impl OperationStats {
+    fn percentile_ms(sorted: &[u64], percentile: f64) -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let rank = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+        sorted[rank] as f64 / 1000.0
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut sorted: Vec<u64> = self.recent_micros.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let mean_ms = if self.calls == 0 {
+            0.0
+        } else {
+            self.total_micros as f64 / self.calls as f64 / 1000.0
+        };
+
+        serde_json::json!({
+            "calls": self.calls,
+            "errors": self.errors,
+            "mean_ms": mean_ms,
+            "p50_ms": Self::percentile_ms(&sorted, 0.50),
+            "p95_ms": Self::percentile_ms(&sorted, 0.95),
+            "max_ms": sorted.last().map_or(0.0, |m| *m as f64 / 1000.0),
+        })
+    }
+}
+
+/// Call counts, error counts and latencies of the backend operations made through a client
+#[derive(Default)]
+pub(crate) struct Metrics {
+    operations: Mutex<BTreeMap<&'static str, OperationStats>>,
+    records_added: AtomicU64,
+}
+
+impl Metrics {
+    /// Records one backend operation, including any retries it needed
+    pub(crate) fn record(&self, operation: &'static str, elapsed: Duration, succeeded: bool) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let mut operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = operations.entry(operation).or_default();
+
+        stats.calls += 1;
+        if !succeeded {
+            stats.errors += 1;
+        }
+        stats.total_micros = stats.total_micros.saturating_add(micros);
+        if stats.recent_micros.len() == LATENCY_WINDOW {
+            stats.recent_micros.pop_front();
+        }
+        stats.recent_micros.push_back(micros);
+    }
+
+    /// Counts records successfully added
+    pub(crate) fn add_records(&self, count: usize) {
+        self.records_added.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let operations = self.operations.lock().unwrap_or_else(|e| e.into_inner());
+        let operations: serde_json::Map<String, serde_json::Value> = operations
+            .iter()
+            .map(|(name, stats)| (name.to_string(), stats.to_json()))
+            .collect();
+
+        serde_json::json!({
+            "operations": operations,
+            "records_added": self.records_added.load(Ordering::Relaxed),
+        })
+    }
+
+    fn reset(&self) {
+        self.operations.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.records_added.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Returns the metrics of a client as JSON.
+///
+/// The object maps each backend operation (`add`, `query`, ...) to its `calls`, `errors`,
+/// `mean_ms`, `p50_ms`, `p95_ms` and `max_ms`, and holds the total `records_added`.
+/// Percentiles cover the most recent 1024 calls of each operation. The string is
+/// allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_get_metrics_json(
+    client_handle: ChromaHandle,
+    out_str: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_metrics_json", || {
+        let func_name = "chroma_get_metrics_json";
+
+        if client_handle == CHROMA_INVALID_HANDLE || out_str.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Output string pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_str = string_to_c_str(client.metrics.to_json().to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Clears all metrics of a client
+#[no_mangle]
+pub extern "C" fn chroma_reset_metrics(
+    client_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_reset_metrics", || {
+        let func_name = "chroma_reset_metrics";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        client.metrics.reset();
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,219 @@
+// Status of the SQLite schema migrations of a persist directory
+use chroma_config::{registry::Registry, Configurable};
+use chroma_sqlite::config::{MigrationHash, MigrationMode, SqliteDBConfig};
+use chroma_sqlite::db::SqliteDb;
+use libc::{c_char, c_int};
+use serde_json::{json, Value};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, SqliteConnection};
+use std::path::Path;
+
+use crate::abi::read_sized;
+use crate::client::normalize_path;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::types::SqliteConfigFFI;
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+/// File name of the SQLite database inside the persist directory
+const SQLITE_FILE: &str = "chroma.sqlite3";
+
+pub(crate) fn hash_from_ffi(hash_type: c_int) -> Result<MigrationHash, FfiError> {
+    match hash_type {
+        0 => Ok(MigrationHash::SHA256),
+        1 => Ok(MigrationHash::MD5),
+        invalid => Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid hash type",
+            Some(format!("Got {}, expected 0 (SHA256) or 1 (MD5)", invalid)),
+        )),
+    }
+}
+
+/// Migrations recorded in a database, and whether the backend accepts it as up to date
+pub(crate) struct MigrationStatus {
+    database: String,
+    exists: bool,
+    /// `(dir, version, filename)` of every applied migration
+    applied: Vec<(String, i64, String)>,
+    validation_error: Option<String>,
+}
+
+impl MigrationStatus {
+    pub(crate) fn up_to_date(&self) -> bool {
+        self.exists && self.validation_error.is_none()
+    }
+
+    /// Why the database is not up to date, for error details
+    pub(crate) fn pending_details(&self) -> String {
+        match &self.validation_error {
+            Some(error) => format!(
+                "{} has {} applied migrations; validation failed: {}",
+                self.database,
+                self.applied.len(),
+                error
+            ),
+            None => format!("{} does not exist; no migrations are applied", self.database),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        let applied: Vec<Value> = self
+            .applied
+            .iter()
+            .map(|(dir, version, filename)| {
+                json!({ "dir": dir, "version": version, "filename": filename })
+            })
+            .collect();
+        json!({
+            "database": self.database,
+            "exists": self.exists,
+            "up_to_date": self.up_to_date(),
+            "applied_count": applied.len(),
+            "applied": applied,
+            "validation_error": self.validation_error,
+        })
+    }
+}
+
+/// Reads the applied migrations of the database in `persist_path` and validates them
+/// against the migrations the backend ships, without writing to the database
+pub(crate) async fn migration_status(
+    persist_path: &str,
+    hash_type: MigrationHash,
+) -> Result<MigrationStatus, FfiError> {
+    let database = Path::new(persist_path).join(SQLITE_FILE);
+    let mut status = MigrationStatus {
+        database: database.to_string_lossy().into_owned(),
+        exists: database.is_file(),
+        applied: Vec::new(),
+        validation_error: None,
+    };
+    if !status.exists {
+        return Ok(status);
+    }
+
+    let read_error = |e: sqlx::Error| {
+        FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Failed to read applied migrations",
+            Some(e.to_string()),
+        )
+    };
+    let options = SqliteConnectOptions::new().filename(&database).read_only(true);
+    let mut connection = SqliteConnection::connect_with(&options).await.map_err(read_error)?;
+    let has_table: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'migrations'",
+    )
+    .fetch_optional(&mut connection)
+    .await
+    .map_err(read_error)?;
+    if has_table.is_some() {
+        status.applied =
+            sqlx::query_as("SELECT dir, version, filename FROM migrations ORDER BY dir, version")
+                .fetch_all(&mut connection)
+                .await
+                .map_err(read_error)?;
+    }
+    connection.close().await.map_err(read_error)?;
+
+    let config = SqliteDBConfig {
+        url: Some(status.database.clone()),
+        hash_type,
+        migration_mode: MigrationMode::Validate,
+    };
+    if let Err(e) = SqliteDb::try_from_config(&config, &Registry::new()).await {
+        status.validation_error = Some(e.to_string());
+    }
+    Ok(status)
+}
+
+/// Reports whether the SQLite database in a persist directory is fully migrated, as JSON.
+///
+/// Works without a client, so deployments can gate startup on it. `sqlite_config_ptr`
+/// may be null; only its `hash_type` is used. The report holds the database path,
+/// whether it `exists`, whether it is `up_to_date`, the `applied` migrations (`dir`,
+/// `version`, `filename`) and the backend's `validation_error` when migrations are pending.
+/// The database is opened read-only. The string is allocated with `chroma_malloc`;
+/// release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_get_migration_status(
+    persist_path_ptr: *const c_char,
+    sqlite_config_ptr: *const SqliteConfigFFI,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_migration_status", || {
+        let func_name = "chroma_get_migration_status";
+
+        if persist_path_ptr.is_null() || out_json.is_null() {
+            let message = if persist_path_ptr.is_null() {
+                "Persistence path pointer is null"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let persist_path = unsafe {
+            match c_str_to_string(persist_path_ptr) {
+                Ok(s) => match normalize_path(&s) {
+                    Ok(path) => path,
+                    Err(e) => return e.report(error_out, func_name),
+                },
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid persistence path",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let hash_type = match unsafe { read_sized(sqlite_config_ptr) } {
+            Ok(Some(sqlite_config)) => match hash_from_ffi(sqlite_config.hash_type) {
+                Ok(hash_type) => hash_type,
+                Err(e) => return e.report(error_out, func_name),
+            },
+            Ok(None) => MigrationHash::SHA256,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InternalError,
+                    "Failed to create Tokio runtime",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InternalError as c_int;
+            }
+        };
+
+        let status = match runtime.block_on(migration_status(&persist_path, hash_type)) {
+            Ok(status) => status,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_json = string_to_c_str(status.to_json().to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
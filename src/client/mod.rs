@@ -1,4 +1,56 @@
 // Client module for ChromaDB C# bindings
+mod audit;
+mod authorization;
+mod concurrency;
+mod config;
+mod config_file;
+mod consistency;
+mod context;
+mod distributed;
+mod embedding_cache;
+mod encryption;
+mod flush;
+mod health;
+mod ingest;
+mod lock;
+mod metrics;
+mod migrations;
+mod object_store;
+mod operation;
+mod paths;
+mod quota;
+mod retry;
+mod shutdown;
+mod sqlite;
+mod storage;
+mod write_locks;
+
+pub use audit::*;
+pub use authorization::*;
+pub use concurrency::*;
+pub use config::*;
+pub use config_file::*;
+pub use consistency::*;
+pub use context::*;
+pub use distributed::*;
+pub use embedding_cache::*;
+pub use encryption::*;
+pub use flush::*;
+pub use health::*;
+pub use ingest::*;
+pub use lock::*;
+pub use metrics::*;
+pub use migrations::*;
+pub use object_store::*;
+pub use operation::*;
+pub use paths::*;
+pub use quota::*;
+pub use retry::*;
+pub use shutdown::*;
+pub use sqlite::*;
+pub use storage::*;
+pub use write_locks::*;
+
 use chroma_cache::FoyerCacheConfig;
 use chroma_config::{registry::Registry, Configurable};
 use chroma_frontend::{
@@ -9,675 +61,1363 @@ use chroma_frontend::{
     Frontend, FrontendConfig,
 };
 use chroma_log::config::{LogConfig, SqliteLogConfig};
+use chroma_log::local_compaction_manager::LocalCompactionManager;
 use chroma_segment::local_segment_manager::LocalSegmentManagerConfig;
 use chroma_sqlite::config::{MigrationHash, MigrationMode, SqliteDBConfig};
+use chroma_sqlite::db::SqliteDb;
 use chroma_sysdb::{SqliteSysDbConfig, SysDbConfig};
-use chroma_system::System;
+use chroma_system::{ComponentHandle, System};
 use chroma_types::{
-    CreateDatabaseRequest, DatabaseName, DeleteDatabaseRequest, GetDatabaseRequest, KnnIndex,
+    CollectionUuid, CreateDatabaseRequest, DatabaseName, DeleteDatabaseRequest, GetDatabaseRequest,
+    KnnIndex,
 };
 use libc::{c_char, c_int, size_t};
-use std::time::SystemTime;
+use serde_json::Value;
+use std::fs::File;
+use std::sync::atomic::AtomicI32;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
 use tokio::runtime::Runtime;
 
-use crate::error::{set_error, set_success, ChromaErrorCode, ChromaError};
-use crate::types::SqliteConfigFFI;
-use crate::utils::{c_str_to_string, string_to_c_str, DEFAULT_TENANT};
+use crate::abi::read_sized;
+use crate::callbacks::CallbackSlot;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaErrorCode, ChromaError, FfiError,
+};
+use crate::handles::{
+    client_from_handle, insert_client, remove_client, retain_client, ChromaHandle,
+    CHROMA_INVALID_HANDLE,
+};
+use crate::types::{
+    CollectionCacheConfigFFI, DiskCacheConfigFFI, EncryptionConfigFFI, LockConfigFFI,
+    SqliteConfigFFI,
+};
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+/// Tenant and topic namespace of the local SQLite write log
+const LOG_TENANT: &str = "default";
+const LOG_TOPIC_NAMESPACE: &str = "default";
+
+/// Topic the local write log files the entries of a collection under
+pub(crate) fn log_topic(collection_id: &CollectionUuid) -> String {
+    format!("persistent://{}/{}/{}", LOG_TENANT, LOG_TOPIC_NAMESPACE, collection_id)
+}
 
 /// Client handle for ChromaDB
 #[repr(C)]
 pub struct ChromaClient {
     pub(crate) runtime: Runtime,
     pub(crate) frontend: Frontend,
+    /// Compactor of the local segments, registered by the frontend
+    pub(crate) compaction_manager: Option<ComponentHandle<LocalCompactionManager>>,
+    /// SQLite database backing the sysdb and log, registered by the frontend
+    pub(crate) sqlite_db: Option<SqliteDb>,
+    pub(crate) retry_policy: RwLock<ChromaRetryPolicy>,
+    /// Read consistency set with `chroma_set_consistency`
+    pub(crate) consistency: AtomicI32,
+    /// Tenant and database of calls that pass none, see `chroma_set_default_tenant_database`
+    pub(crate) default_context: RwLock<DefaultContext>,
+    pub(crate) metrics: Metrics,
+    /// Directory holding the SQLite database and segments, if the client persists data
+    pub(crate) persist_path: Option<String>,
+    /// Object store the persist directory is mirrored to, see `object_store_config_json`
+    pub(crate) object_store: Option<ObjectStoreMirror>,
+    /// Configuration the client was created with, unredacted
+    pub(crate) effective_config: serde_json::Value,
+    /// Whether mutations are rejected with `ReadOnly`
+    pub(crate) read_only: bool,
+    /// Re-ranker for queries that ask for re-ranking without passing their own
+    pub(crate) reranker: CallbackSlot,
+    /// Callback authorizing backend calls, set with `chroma_set_authorizer`
+    pub(crate) authorizer: CallbackSlot,
+    /// Destinations of the audit log, set with `chroma_set_audit_log`
+    pub(crate) audit_log: RwLock<Option<Arc<AuditLog>>>,
+    /// Quotas set with `chroma_set_quotas`
+    pub(crate) quotas: Quotas,
+    /// Limits on concurrent calls, set with `chroma_set_concurrency_limits`
+    pub(crate) concurrency: ConcurrencyLimiter,
+    /// Locks serializing the record writes to each collection
+    pub(crate) write_locks: WriteLocks,
+    /// Seals the persist directory when dropped, after the backend and before the lock
+    pub(crate) encryption: Option<DirectoryEncryption>,
+    /// Advisory lock on the persist directory, released when the client is dropped
+    pub(crate) _lock_file: Option<File>,
+    /// Stops the auto-flush thread when dropped, see `chroma_set_auto_flush_interval`
+    pub(crate) auto_flush: Mutex<Option<Sender<()>>>,
+    /// Records queued with `chroma_enqueue_add`; its workers stop when it is dropped
+    pub(crate) ingest: IngestQueue,
+    /// Backend calls in flight and shutdown state, see `chroma_shutdown_client`
+    pub(crate) lifecycle: Lifecycle,
+}
+
+impl ChromaClient {
+    /// Fails with `ReadOnly` when the client was created in read-only mode
+    pub(crate) fn check_writable(&self) -> Result<(), FfiError> {
+        if self.read_only {
+            return Err(FfiError::new(
+                ChromaErrorCode::ReadOnly,
+                "Client is read-only",
+                Some("The client was created with read_only set".to_string()),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Creates a new ChromaDB client
+///
+/// The HNSW index cache keeps up to `hnsw_cache_size` indexes in memory. When
+/// `disk_cache_config_ptr` is not null the cache becomes a hybrid memory/disk cache,
+/// so more indexes can stay cached than fit in RAM.
+///
+/// Collections and their segments are looked up in the sysdb on every operation unless
+/// `collection_cache_config_ptr` enables an in-memory collection cache.
+///
+/// With `read_only` set, every call that would write (adds, updates, deletes, collection and
+/// database creation, optimization) fails with `ReadOnly`. Migrations are then validated
+/// instead of applied, the SQLite database must already exist in `persist_path`, and its
+/// connections are opened read-only, so the client never writes to a volume shared with a
+/// writer.
+///
+/// When `lock_config_ptr` is not null and `persist_path` is set, an advisory lock on
+/// `chroma.lock` in the persist directory is taken before anything else is opened:
+/// `CHROMA_LOCK_EXCLUSIVE` for a single writer, or `CHROMA_LOCK_SHARED_READ` for clients
+/// that may run alongside each other but never alongside an exclusive one. If the lock
+/// is still held by another client after `wait_ms`, creation fails with `ConcurrentAccess`.
+/// The lock is released once the client and every collection opened from it are destroyed.
+///
+/// When `distributed_config_json` is not null the client connects to a distributed Chroma
+/// deployment instead of embedding a single node. It holds a JSON object with the `sysdb`,
+/// `log` and `executor` sections of the Chroma frontend configuration, which must use the
+/// gRPC sysdb and log and the distributed executor (see `DistributedConfig`). Such clients
+/// have no local storage: `persist_path`, `sqlite_config_ptr` and `lock_config_ptr` must be
+/// null, and functions working on the local SQLite database or segments (the write log,
+/// flushes, batches, migrations) fail.
+///
+/// When `object_store_config_json` is not null the persist directory is mirrored to an object
+/// store, so a client on a machine without durable disk keeps its collections. It holds a JSON
+/// object with a `storage` section in the format of the Chroma storage configuration (such as
+/// `s3` or `object_store`) and an optional key `prefix`. `persist_path` is then required and
+/// serves as the working copy: if it has no database yet, every object under the prefix is
+/// downloaded into it before the client opens it, and `chroma_flush` (including automatic and
+/// shutdown flushes) uploads the files that changed since their last upload. Writes that were
+/// not flushed are lost with the machine.
+///
+/// When `encryption_config_ptr` is not null, or a key was set with `chroma_set_encryption_key`,
+/// the persist directory is sealed with AES-256-GCM while no client has it open: every file is
+/// sealed once the client is released and decrypted when a client opens the directory again
+/// with the same key. This is not transparent encryption at rest. The SQLite database and
+/// segments are written by engines that cannot encrypt pages, so every file is plaintext on
+/// disk for as long as a client has the directory open, and stays plaintext after a crash,
+/// kill or abort until a client opens the directory with the key, which seals the leftovers
+/// before anything else, or `chroma_seal_directory` is called. Opening an encrypted directory fails
+/// with `InvalidArgument` without the key or with a wrong one, and with `ReadOnly` for
+/// read-only clients; encryption cannot be combined with `CHROMA_LOCK_SHARED_READ` or
+/// `object_store_config_json`. The files are decrypted into a directory of their own and
+/// moved into place only once all of them are, so a failure or crash while opening never
+/// leaves the directory half decrypted. Rotate the key with `chroma_rotate_encryption_key`.
 #[no_mangle]
 pub extern "C" fn chroma_create_client(
     allow_reset: bool,
+    read_only: bool,
     sqlite_config_ptr: *const SqliteConfigFFI,
     hnsw_cache_size: size_t,
+    disk_cache_config_ptr: *const DiskCacheConfigFFI,
+    collection_cache_config_ptr: *const CollectionCacheConfigFFI,
+    lock_config_ptr: *const LockConfigFFI,
     persist_path_ptr: *const c_char,
-    client_handle: *mut *mut ChromaClient,
+    distributed_config_json: *const c_char,
+    object_store_config_json: *const c_char,
+    encryption_config_ptr: *const EncryptionConfigFFI,
+    client_handle: *mut ChromaHandle,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    // Function name for error reporting
-    let func_name = "chroma_create_client";
-
-    // Check arguments
-    if client_handle.is_null() {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Client handle pointer is null",
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+    ffi_boundary(error_out, "chroma_create_client", || {
+        // Function name for error reporting
+        let func_name = "chroma_create_client";
 
-    // Parse SQLite configuration
-    let mut sqlite_db_config = if !sqlite_config_ptr.is_null() {
-        unsafe {
-            let sqlite_config = &*sqlite_config_ptr;
+        // Check arguments
+        if client_handle.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-            let _url = match c_str_to_string(sqlite_config.url) {
-                Ok(s) => s,
+        // Read the size-prefixed configuration structs
+        let configs = unsafe {
+            read_sized(sqlite_config_ptr).and_then(|sqlite| {
+                Ok((
+                    sqlite,
+                    read_sized(disk_cache_config_ptr)?,
+                    read_sized(collection_cache_config_ptr)?,
+                    read_sized(lock_config_ptr)?,
+                    read_sized(encryption_config_ptr)?,
+                ))
+            })
+        };
+        let (
+            sqlite_config,
+            disk_cache_config,
+            collection_cache_config,
+            lock_config,
+            encryption_config,
+        ) = match configs {
+            Ok(configs) => configs,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if !distributed_config_json.is_null()
+            && (!persist_path_ptr.is_null() || sqlite_config.is_some() || lock_config.is_some())
+        {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Distributed clients have no local storage",
+                func_name,
+                Some("persist_path, sqlite_config_ptr and lock_config_ptr must be null"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        // Parse the distributed deployment configuration
+        let distributed = if distributed_config_json.is_null() {
+            None
+        } else {
+            let json = match unsafe { c_str_to_string(distributed_config_json) } {
+                Ok(json) => json,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid SQLite URL",
+                        "Invalid distributed configuration string",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             };
+            match DistributedConfig::from_json(&json) {
+                Ok(distributed) => Some(distributed),
+                Err(e) => return e.report(error_out, func_name),
+            }
+        };
 
-            let hash_type = match sqlite_config.hash_type {
-                0 => MigrationHash::SHA256,
-                1 => MigrationHash::MD5,
-                invalid => {
+        // Parse the object store mirroring the persist directory
+        let object_store = if object_store_config_json.is_null() {
+            None
+        } else {
+            if persist_path_ptr.is_null() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Object store mirroring needs a persist directory",
+                    func_name,
+                    Some("persist_path is the working copy of the mirrored files"),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+            let json = match unsafe { c_str_to_string(object_store_config_json) } {
+                Ok(json) => json,
+                Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid hash type",
+                        "Invalid object store configuration string",
                         func_name,
-                        Some(&format!("Got {}, expected 0 (SHA256) or 1 (MD5)", invalid)),
+                        Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             };
+            match ObjectStoreOptions::from_json(&json) {
+                Ok(object_store) => Some(object_store),
+                Err(e) => return e.report(error_out, func_name),
+            }
+        };
 
-            let migration_mode = match sqlite_config.migration_mode {
-                0 => MigrationMode::Apply,
-                1 => MigrationMode::Validate,
-                invalid => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Invalid migration mode",
-                        func_name,
-                        Some(&format!(
-                            "Got {}, expected 0 (Apply) or 1 (Validate)",
-                            invalid
-                        )),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+        // Parse SQLite configuration
+        let mut sqlite_pragmas = SqlitePragmas::default();
+        let mut sqlite_db_config = if let Some(sqlite_config) = &sqlite_config {
+            unsafe {
+                if !sqlite_config.url.is_null() {
+                    if let Err(e) = c_str_to_string(sqlite_config.url) {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid SQLite URL",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
-            };
 
+                sqlite_pragmas = match SqlitePragmas::from_ffi(sqlite_config) {
+                    Ok(pragmas) => pragmas,
+                    Err(e) => return e.report(error_out, func_name),
+                };
+
+                let hash_type = match hash_from_ffi(sqlite_config.hash_type) {
+                    Ok(hash_type) => hash_type,
+                    Err(e) => return e.report(error_out, func_name),
+                };
+
+                let migration_mode = match sqlite_config.migration_mode {
+                    0 => MigrationMode::Apply,
+                    1 => MigrationMode::Validate,
+                    invalid => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid migration mode",
+                            func_name,
+                            Some(&format!(
+                                "Got {}, expected 0 (Apply) or 1 (Validate)",
+                                invalid
+                            )),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                };
+
+                SqliteDBConfig {
+                    url: None,
+                    hash_type,
+                    migration_mode,
+                }
+            }
+        } else {
+            // Default SQLite configuration
             SqliteDBConfig {
                 url: None,
-                hash_type,
-                migration_mode,
+                hash_type: MigrationHash::SHA256,
+                migration_mode: MigrationMode::Apply,
             }
-        }
-    } else {
-        // Default SQLite configuration
-        SqliteDBConfig {
-            url: None,
-            hash_type: MigrationHash::SHA256,
-            migration_mode: MigrationMode::Apply,
-        }
-    };
+        };
 
-    // Parse persistence path
-    let persist_path = if !persist_path_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(persist_path_ptr) {
-                Ok(s) => Some(s),
-                Err(e) => {
+        // Parse persistence path
+        let persist_path = if !persist_path_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(persist_path_ptr) {
+                    Ok(s) => match normalize_path(&s) {
+                        Ok(path) => Some(path),
+                        Err(e) => return e.report(error_out, func_name),
+                    },
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid persistence path",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        // Parse disk cache configuration
+        let disk_cache = match &disk_cache_config {
+            Some(disk_cache_config) => {
+                let dir = if disk_cache_config.dir.is_null() {
+                    None
+                } else {
+                    unsafe { c_str_to_string(disk_cache_config.dir) }.ok()
+                };
+                let Some(dir) = dir.and_then(|dir| normalize_path(&dir).ok()) else {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid persistence path",
+                        "Invalid disk cache directory",
                         func_name,
-                        Some(&e.to_string()),
+                        None,
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                };
+                if disk_cache_config.disk_mib == 0 {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Disk cache size is zero",
+                        func_name,
+                        None,
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
+                if let Err(e) = std::fs::create_dir_all(&dir) {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InternalError,
+                        "Failed to create disk cache directory",
+                        func_name,
+                        Some(&format!("{}: {}", dir, e)),
+                    );
+                    return ChromaErrorCode::InternalError as c_int;
+                }
+                Some((dir, disk_cache_config.disk_mib, disk_cache_config.memory_mib))
             }
-        }
-    } else {
-        None
-    };
+            None => None,
+        };
 
-    // Adjust SQLite URL if persist_path is provided
-    if let Some(persist_dir) = &persist_path {
-        sqlite_db_config.url = Some(format!("{}/chroma.sqlite3", persist_dir));
-    }
+        // Parse lock configuration
+        let (lock_mode, lock_wait_ms) = match &lock_config {
+            Some(lock_config) => match LockMode::from_ffi(lock_config.mode) {
+                Ok(mode) => (mode, lock_config.wait_ms),
+                Err(e) => return e.report(error_out, func_name),
+            },
+            None => (LockMode::None, 0),
+        };
 
-    // Create runtime and frontend
-    let runtime = match Runtime::new() {
-        Ok(rt) => rt,
-        Err(e) => {
+        // Resolve the encryption key; clients without a persist directory have nothing at rest
+        let encryption_key = match &encryption_config {
+            Some(_) if persist_path.is_none() => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Encryption needs a persist directory",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+            Some(encryption_config) => {
+                match unsafe { read_key(encryption_config.key, encryption_config.key_len) } {
+                    Ok(key) => Some(key),
+                    Err(e) => return e.report(error_out, func_name),
+                }
+            }
+            None => persist_path.as_ref().and_then(|_| default_key()),
+        };
+        if encryption_key.is_some() && !read_only && lock_mode == LockMode::SharedRead {
             set_error(
                 error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to create Tokio runtime",
+                ChromaErrorCode::InvalidArgument,
+                "Encrypted persist directories cannot be shared",
                 func_name,
-                Some(&e.to_string()),
+                Some("The directory is sealed when a client releases it; use an exclusive lock"),
             );
-            return ChromaErrorCode::InternalError as c_int;
-        }
-    };
-
-    let _guard = runtime.enter();
-    let system = System::new();
-    let registry = Registry::new();
-
-    // Configure cache
-    let cache_config = FoyerCacheConfig {
-        capacity: hnsw_cache_size,
-        ..Default::default()
-    };
-    let cache_config = chroma_cache::CacheConfig::Memory(cache_config);
-
-    // Configure segment manager
-    let segment_manager_config = LocalSegmentManagerConfig {
-        hnsw_index_pool_cache_config: cache_config,
-        persist_path,
-    };
-
-    // Configure sysdb
-    let sysdb_config = SysDbConfig::Sqlite(SqliteSysDbConfig {
-        log_topic_namespace: "default".to_string(),
-        log_tenant: "default".to_string(),
-    });
-
-    // Configure log
-    let log_config = LogConfig::Sqlite(SqliteLogConfig {
-        tenant_id: "default".to_string(),
-        topic_namespace: "default".to_string(),
-    });
-
-    // Configure collection cache
-    let collection_cache_config = CollectionsWithSegmentsProviderConfig {
-        cache_invalidation_retry_policy: CacheInvalidationRetryConfig::new(0, 0),
-        permitted_parallelism: 32,
-        cache: chroma_cache::CacheConfig::Nop,
-        cache_ttl_secs: 60,
-    };
-
-    // Configure executor
-    let executor_config = ExecutorConfig::Local(LocalExecutorConfig {});
-
-    // Default KNN index
-    let knn_index = KnnIndex::Hnsw;
-
-    // Build frontend config
-    let frontend_config = FrontendConfig {
-        allow_reset,
-        segment_manager: Some(segment_manager_config),
-        sqlitedb: Some(sqlite_db_config),
-        sysdb: sysdb_config,
-        mcmr_sysdb: None,
-        collections_with_segments_provider: collection_cache_config,
-        log: log_config,
-        executor: executor_config,
-        default_knn_index: knn_index,
-        tenants_to_migrate_immediately: vec![],
-        tenants_to_migrate_immediately_threshold: None,
-        enable_schema: false,
-        min_records_for_invocation: 0,
-        tenants_with_quantization_enabled: vec![],
-        tenants_with_maxscore_enabled: vec![],
-        enable_log_scouting: false,
-    };
-
-    // Create frontend
-    let frontend = match runtime
-        .block_on(async { Frontend::try_from_config(&(frontend_config, system), &registry).await })
-    {
-        Ok(frontend) => frontend,
-        Err(e) => {
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        // Mirrored files would leave the machine as the plaintext the backend works on
+        if encryption_key.is_some() && object_store.is_some() {
             set_error(
                 error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to create Chroma frontend",
+                ChromaErrorCode::InvalidArgument,
+                "Encrypted persist directories cannot be mirrored to an object store",
                 func_name,
-                Some(&e.to_string()),
+                Some("Rely on the object store's own encryption instead"),
             );
-            return ChromaErrorCode::InternalError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Create client handle
-    let client = Box::new(ChromaClient { runtime, frontend });
-    unsafe {
-        *client_handle = Box::into_raw(client);
-    }
-
-    // Create success result
-    set_success(error_out);
-
-    ChromaErrorCode::Success as c_int
-}
-
-/// Destroys a ChromaDB client
-#[no_mangle]
-pub extern "C" fn chroma_destroy_client(
-    client_handle: *mut ChromaClient,
-    error_out: *mut *mut ChromaError,
-) -> c_int {
-    let func_name = "chroma_destroy_client";
-
-    if client_handle.is_null() {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Client handle pointer is null",
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        let sealed = persist_path.as_deref().is_some_and(is_sealed);
+        if sealed && (read_only || encryption_key.is_none()) {
+            let (code, message, details) = if read_only {
+                (
+                    ChromaErrorCode::ReadOnly,
+                    "Read-only client cannot decrypt the persist directory",
+                    None,
+                )
+            } else {
+                (
+                    ChromaErrorCode::InvalidArgument,
+                    "Persist directory is encrypted",
+                    Some("Pass the key it was sealed with"),
+                )
+            };
+            set_error(error_out, code, message, func_name, details);
+            return code as c_int;
+        }
 
-    unsafe {
-        let _ = Box::from_raw(client_handle);
-    }
+        // Adjust SQLite URL if persist_path is provided
+        if let Some(persist_dir) = &persist_path {
+            let database = std::path::Path::new(persist_dir).join("chroma.sqlite3");
+            sqlite_db_config.url = Some(database.to_string_lossy().into_owned());
+        }
 
-    // Return success
-    set_success(error_out);
+        // Read-only clients must not create the database or apply migrations to it; a
+        // mirrored database is looked for once restored
+        if read_only {
+            if let (Some(url), None) = (&sqlite_db_config.url, &object_store) {
+                if !std::path::Path::new(url).is_file() {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::NotFound,
+                        "Read-only client needs an existing database",
+                        func_name,
+                        Some(&format!("{} does not exist", url)),
+                    );
+                    return ChromaErrorCode::NotFound as c_int;
+                }
+            }
+            if sqlite_pragmas.writes_database() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ReadOnly,
+                    "Read-only client cannot change the journal mode",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::ReadOnly as c_int;
+            }
+            sqlite_db_config.migration_mode = MigrationMode::Validate;
+        }
 
-    ChromaErrorCode::Success as c_int
-}
+        // Lock the persist directory before the backend opens anything in it
+        let lock_file = match &persist_path {
+            Some(persist_dir) => {
+                let wait = Duration::from_millis(lock_wait_ms.into());
+                match acquire_lock(persist_dir, lock_mode, wait, read_only) {
+                    Ok(lock_file) => lock_file,
+                    Err(e) => return e.report(error_out, func_name),
+                }
+            }
+            None => None,
+        };
 
-/// Returns a heartbeat (current time) from the client
-#[no_mangle]
-pub extern "C" fn chroma_heartbeat(
-    client_handle: *mut ChromaClient,
-    result: *mut u64,
-    error_out: *mut *mut ChromaError,
-) -> c_int {
-    let func_name = "chroma_heartbeat";
+        // Seal what a client that was never released left plaintext, then decrypt the
+        // directory; it is sealed again when the guard drops, including when creation fails
+        // below. Read-only clients never write the directory.
+        let mut encryption = None;
+        if let (Some(persist_dir), Some(key), false) = (&persist_path, encryption_key, read_only) {
+            if let Err(e) = reseal_leftovers(persist_dir, &key) {
+                return e.report(error_out, func_name);
+            }
+            if is_sealed(persist_dir) {
+                if let Err(e) = unseal_directory(persist_dir, &key) {
+                    return e.report(error_out, func_name);
+                }
+            }
+            encryption = Some(DirectoryEncryption::new(persist_dir.clone(), key));
+        }
 
-    if client_handle.is_null() || result.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else {
-            "Result pointer is null"
+        // Create runtime and frontend
+        let runtime = match Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InternalError,
+                    "Failed to create Tokio runtime",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InternalError as c_int;
+            }
         };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        let _guard = runtime.enter();
+        let registry = Registry::new();
+
+        // Restore a persist directory without a database from its object store
+        let object_store_json = object_store.as_ref().map(|options| options.json.clone());
+        let object_store = match (&object_store, &persist_path, &sqlite_db_config.url) {
+            (Some(options), Some(persist_dir), Some(url)) => {
+                let opened = runtime.block_on(async {
+                    let mirror = ObjectStoreMirror::open(options, persist_dir, &registry).await?;
+                    if !std::path::Path::new(url).is_file() {
+                        mirror.restore().await?;
+                    }
+                    Ok::<_, FfiError>(mirror)
+                });
+                match opened {
+                    Ok(mirror) => Some(mirror),
+                    Err(e) => return e.report(error_out, func_name),
+                }
+            }
+            _ => None,
+        };
+        if let (true, Some(_), Some(url)) = (read_only, &object_store, &sqlite_db_config.url) {
+            if !std::path::Path::new(url).is_file() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::NotFound,
+                    "Read-only client needs an existing database",
+                    func_name,
+                    Some(&format!("{} was not found locally or in the object store", url)),
+                );
+                return ChromaErrorCode::NotFound as c_int;
+            }
+        }
 
-    let duration_since_epoch = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-        Ok(duration) => duration,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to get system time",
-                func_name,
-                Some(&e.to_string()),
-            );
-            return ChromaErrorCode::InternalError as c_int;
+        // Validate migrations up front, so pending ones surface as MigrationsPending
+        if let (Some(persist_dir), MigrationMode::Validate) =
+            (&persist_path, &sqlite_db_config.migration_mode)
+        {
+            let status = match runtime
+                .block_on(migration_status(persist_dir, sqlite_db_config.hash_type.clone()))
+            {
+                Ok(status) => status,
+                Err(e) => return e.report(error_out, func_name),
+            };
+            if !status.up_to_date() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::MigrationsPending,
+                    "Database migrations are pending",
+                    func_name,
+                    Some(&status.pending_details()),
+                );
+                return ChromaErrorCode::MigrationsPending as c_int;
+            }
         }
-    };
 
-    unsafe {
-        *result = duration_since_epoch.as_nanos() as u64;
-    }
+        let system = System::new();
 
-    // Return success
-    set_success(error_out);
+        // Configure cache
+        let mut cache_config = FoyerCacheConfig {
+            capacity: hnsw_cache_size,
+            ..Default::default()
+        };
+        let cache_config = match disk_cache {
+            Some((dir, disk_mib, memory_mib)) => {
+                cache_config.dir = Some(dir);
+                cache_config.disk = disk_mib;
+                if memory_mib > 0 {
+                    cache_config.mem = memory_mib;
+                }
+                chroma_cache::CacheConfig::Disk(cache_config)
+            }
+            None => chroma_cache::CacheConfig::Memory(cache_config),
+        };
+        let hnsw_cache_json = match &cache_config {
+            chroma_cache::CacheConfig::Disk(c) => serde_json::json!({
+                "type": "disk",
+                "capacity": c.capacity,
+                "dir": c.dir,
+                "disk_mib": c.disk,
+                "memory_mib": c.mem,
+            }),
+            _ => serde_json::json!({ "type": "memory", "capacity": hnsw_cache_size }),
+        };
 
-    ChromaErrorCode::Success as c_int
-}
+        // Configure segment manager
+        let segment_manager_config = LocalSegmentManagerConfig {
+            hnsw_index_pool_cache_config: cache_config,
+            persist_path: persist_path.clone(),
+        };
 
-/// Creates a new database in ChromaDB
-#[no_mangle]
-pub extern "C" fn chroma_create_database(
-    client_handle: *mut ChromaClient,
-    name_ptr: *const c_char,
-    tenant_ptr: *const c_char,
-    error_out: *mut *mut ChromaError,
-) -> c_int {
-    let func_name = "chroma_create_database";
+        // Configure sysdb and log; a distributed deployment brings its own, and the executor
+        let (sysdb_config, log_config, executor_config, distributed_json) = match distributed {
+            Some(distributed) => (
+                distributed.sysdb,
+                distributed.log,
+                distributed.executor,
+                Some(distributed.json),
+            ),
+            None => (
+                SysDbConfig::Sqlite(SqliteSysDbConfig {
+                    log_topic_namespace: LOG_TOPIC_NAMESPACE.to_string(),
+                    log_tenant: LOG_TENANT.to_string(),
+                }),
+                LogConfig::Sqlite(SqliteLogConfig {
+                    tenant_id: LOG_TENANT.to_string(),
+                    topic_namespace: LOG_TOPIC_NAMESPACE.to_string(),
+                }),
+                ExecutorConfig::Local(LocalExecutorConfig {}),
+                None,
+            ),
+        };
 
-    // Check arguments
-    if client_handle.is_null() || name_ptr.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else {
-            "Database name pointer is null"
+        // Configure collection cache
+        let collection_cache = collection_cache_config.as_ref();
+        let collection_cache_capacity = collection_cache.map_or(0, |c| c.capacity);
+        let collection_cache_config = CollectionsWithSegmentsProviderConfig {
+            cache_invalidation_retry_policy: CacheInvalidationRetryConfig::new(0, 0),
+            permitted_parallelism: collection_cache
+                .map(|c| c.permitted_parallelism)
+                .filter(|&p| p > 0)
+                .unwrap_or(32),
+            cache: if collection_cache_capacity > 0 {
+                chroma_cache::CacheConfig::Memory(FoyerCacheConfig {
+                    capacity: collection_cache_capacity,
+                    ..Default::default()
+                })
+            } else {
+                chroma_cache::CacheConfig::Nop
+            },
+            cache_ttl_secs: collection_cache
+                .map(|c| c.ttl_secs)
+                .filter(|&ttl| ttl > 0)
+                .unwrap_or(60),
         };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        // Default KNN index
+        let knn_index = KnnIndex::Hnsw;
+
+        // Snapshot of the resolved configuration for chroma_dump_effective_config
+        let mut effective_config = serde_json::json!({
+            "allow_reset": allow_reset,
+            "read_only": read_only,
+            "lock": { "mode": lock_mode.name(), "wait_ms": lock_wait_ms },
+            "persist_path": segment_manager_config.persist_path,
+            "sqlite": {
+                "url": sqlite_db_config.url,
+                "hash_type": format!("{:?}", sqlite_db_config.hash_type),
+                "migration_mode": format!("{:?}", sqlite_db_config.migration_mode),
+                "pragmas": sqlite_pragmas.to_json(),
+            },
+            "hnsw_cache": hnsw_cache_json,
+            "sysdb": {
+                "type": "sqlite",
+                "log_tenant": LOG_TENANT,
+                "log_topic_namespace": LOG_TOPIC_NAMESPACE,
+            },
+            "log": {
+                "type": "sqlite",
+                "tenant_id": LOG_TENANT,
+                "topic_namespace": LOG_TOPIC_NAMESPACE,
+            },
+            "collection_cache": {
+                "type": if collection_cache_capacity > 0 { "memory" } else { "nop" },
+                "capacity": collection_cache_capacity,
+                "ttl_secs": collection_cache_config.cache_ttl_secs,
+                "permitted_parallelism": collection_cache_config.permitted_parallelism,
+            },
+            "executor": { "type": "local" },
+            "object_store": object_store_json,
+            "encryption": { "enabled": encryption.is_some() },
+            "default_knn_index": "hnsw",
+            "enable_schema": false,
+        });
+        let distributed = distributed_json.is_some();
+        if let Some(Value::Object(sections)) = distributed_json {
+            effective_config["sqlite"] = Value::Null;
+            for (section, config) in sections {
+                effective_config[section] = config;
+            }
+        }
+
+        // Build frontend config; a distributed client has no local storage
+        let frontend_config = FrontendConfig {
+            allow_reset,
+            segment_manager: (!distributed).then_some(segment_manager_config),
+            sqlitedb: (!distributed).then_some(sqlite_db_config),
+            sysdb: sysdb_config,
+            mcmr_sysdb: None,
+            collections_with_segments_provider: collection_cache_config,
+            log: log_config,
+            executor: executor_config,
+            default_knn_index: knn_index,
+            tenants_to_migrate_immediately: vec![],
+            tenants_to_migrate_immediately_threshold: None,
+            enable_schema: false,
+            min_records_for_invocation: 0,
+            tenants_with_quantization_enabled: vec![],
+            tenants_with_maxscore_enabled: vec![],
+            enable_log_scouting: false,
+        };
 
-    // Parse name
-    let name = unsafe {
-        match c_str_to_string(name_ptr) {
-            Ok(s) => s,
+        // Create frontend
+        let frontend = match runtime.block_on(async {
+            Frontend::try_from_config(&(frontend_config, system), &registry).await
+        }) {
+            Ok(frontend) => frontend,
             Err(e) => {
                 set_error(
                     error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Invalid database name",
+                    ChromaErrorCode::InternalError,
+                    "Failed to create Chroma frontend",
                     func_name,
                     Some(&e.to_string()),
                 );
-                return ChromaErrorCode::InvalidArgument as c_int;
+                return ChromaErrorCode::InternalError as c_int;
             }
+        };
+
+        // Tune the SQLite connections the frontend opened
+        let sqlite_db = registry.get::<SqliteDb>().ok();
+        if let (true, Some(sqlite_db)) = (read_only, sqlite_db.as_ref()) {
+            if let Err(e) = runtime.block_on(reopen_read_only(sqlite_db)) {
+                return e.report(error_out, func_name);
+            }
+        }
+        if sqlite_pragmas != SqlitePragmas::default() {
+            let Some(sqlite_db) = sqlite_db.as_ref() else {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InternalError,
+                    "Failed to find the SQLite database",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::InternalError as c_int;
+            };
+            if let Err(e) = runtime.block_on(sqlite_pragmas.apply(sqlite_db)) {
+                return e.report(error_out, func_name);
+            }
+        }
+
+        let compaction_manager = registry.get::<ComponentHandle<LocalCompactionManager>>().ok();
+        if let Some(encryption) = encryption.as_mut() {
+            encryption.close_before_sealing(sqlite_db.clone());
         }
-    };
 
-    // Parse tenant name
-    let tenant = if !tenant_ptr.is_null() {
+        // Create client handle
+        let handle = insert_client(ChromaClient {
+            runtime,
+            frontend,
+            compaction_manager,
+            sqlite_db,
+            retry_policy: RwLock::new(ChromaRetryPolicy::default()),
+            consistency: AtomicI32::new(CHROMA_CONSISTENCY_EVENTUAL),
+            default_context: RwLock::new(DefaultContext::default()),
+            metrics: Metrics::default(),
+            persist_path,
+            object_store,
+            effective_config,
+            read_only,
+            reranker: CallbackSlot::default(),
+            authorizer: CallbackSlot::default(),
+            audit_log: RwLock::new(None),
+            quotas: Quotas::default(),
+            concurrency: ConcurrencyLimiter::default(),
+            write_locks: WriteLocks::default(),
+            encryption,
+            _lock_file: lock_file,
+            auto_flush: Mutex::new(None),
+            ingest: IngestQueue::default(),
+            lifecycle: Lifecycle::new(),
+        });
         unsafe {
-            match c_str_to_string(tenant_ptr) {
-                Ok(s) => s,
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Invalid tenant name",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
-                }
-            }
+            *client_handle = handle;
         }
-    } else {
-        DEFAULT_TENANT.to_string()
-    };
 
-    // Get client reference
-    let client = unsafe { &*client_handle };
+        // Create success result
+        set_success(error_out);
 
-    let db_name = match DatabaseName::new(name) {
-        Some(n) => n,
-        None => {
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Destroys a ChromaDB client
+///
+/// Collections opened from the client stay usable until they are destroyed themselves.
+/// Use `chroma_shutdown_client` to let running operations finish and flush pending writes.
+#[no_mangle]
+pub extern "C" fn chroma_destroy_client(
+    client_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_destroy_client", || {
+        let func_name = "chroma_destroy_client";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid database name (must be at least 3 characters)",
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
                 func_name,
                 None,
             );
-            return ChromaErrorCode::ValidationError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Create database request
-    let request = match CreateDatabaseRequest::try_new(tenant, db_name) {
-        Ok(req) => req,
-        Err(e) => {
+        if let Err(e) = remove_client(client_handle) {
+            return e.report(error_out, func_name);
+        }
+
+        // Return success
+        set_success(error_out);
+
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Issues another handle to the same client, for a component that shares the client with
+/// others and releases it on its own schedule.
+///
+/// Each handle is released with `chroma_client_release` (or `chroma_destroy_client`); the
+/// client and its runtime stay alive until every handle and every collection opened from it
+/// are released. Handles are independent, so releasing one twice fails instead of dropping
+/// a reference held by another component.
+#[no_mangle]
+pub extern "C" fn chroma_client_retain(
+    client_handle: ChromaHandle,
+    retained_handle: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_client_retain", || {
+        let func_name = "chroma_client_retain";
+
+        if client_handle == CHROMA_INVALID_HANDLE || retained_handle.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Retained handle output pointer is null"
+            };
+
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create database request",
+                ChromaErrorCode::InvalidArgument,
+                message,
                 func_name,
-                Some(&format!("Validation error: {:?}", e)),
+                None,
             );
-            return ChromaErrorCode::ValidationError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Execute request
-    let mut frontend = client.frontend.clone();
+        unsafe {
+            *retained_handle = CHROMA_INVALID_HANDLE;
+        }
 
-    match client
-        .runtime
-        .block_on(async { frontend.create_database(request).await })
-    {
-        Ok(_) => {
-            // Return success
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
+        match retain_client(client_handle) {
+            Ok(handle) => {
+                unsafe {
+                    *retained_handle = handle;
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
         }
-        Err(e) => {
+    })
+}
+
+/// Releases one handle to a client, as `chroma_destroy_client` does.
+///
+/// The client itself is dropped with its last handle or collection, see
+/// `chroma_client_retain`.
+#[no_mangle]
+pub extern "C" fn chroma_client_release(
+    client_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_client_release", || {
+        let func_name = "chroma_client_release";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
             set_error(
                 error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to create database",
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
                 func_name,
-                Some(&format!("Error: {:?}", e)),
+                None,
             );
-            ChromaErrorCode::InternalError as c_int
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    }
+
+        match remove_client(client_handle) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
 }
 
-/// Gets a database from ChromaDB
+/// Returns a heartbeat (current time) from the client
 #[no_mangle]
-pub extern "C" fn chroma_get_database(
-    client_handle: *mut ChromaClient,
-    name_ptr: *const c_char,
-    tenant_ptr: *const c_char,
-    id_result: *mut *mut c_char,
+pub extern "C" fn chroma_heartbeat(
+    client_handle: ChromaHandle,
+    result: *mut u64,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_get_database";
+    ffi_boundary(error_out, "chroma_heartbeat", || {
+        let func_name = "chroma_heartbeat";
+
+        if client_handle == CHROMA_INVALID_HANDLE || result.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Result pointer is null"
+            };
 
-    if client_handle.is_null() || name_ptr.is_null() || id_result.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if name_ptr.is_null() {
-            "Database name pointer is null"
-        } else {
-            "ID result pointer is null"
-        };
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        if let Err(e) = client_from_handle(client_handle) {
+            return e.report(error_out, func_name);
+        }
 
-    let name = unsafe {
-        match c_str_to_string(name_ptr) {
-            Ok(s) => s,
+        let duration_since_epoch = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(duration) => duration,
             Err(e) => {
                 set_error(
                     error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Invalid database name",
+                    ChromaErrorCode::InternalError,
+                    "Failed to get system time",
                     func_name,
                     Some(&e.to_string()),
                 );
-                return ChromaErrorCode::InvalidArgument as c_int;
+                return ChromaErrorCode::InternalError as c_int;
             }
-        }
-    };
+        };
 
-    let tenant = if !tenant_ptr.is_null() {
         unsafe {
-            match c_str_to_string(tenant_ptr) {
+            *result = duration_since_epoch.as_nanos() as u64;
+        }
+
+        // Return success
+        set_success(error_out);
+
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Creates a new database in ChromaDB
+#[no_mangle]
+pub extern "C" fn chroma_create_database(
+    client_handle: ChromaHandle,
+    name_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_create_database", || {
+        let func_name = "chroma_create_database";
+
+        // Check arguments
+        if client_handle == CHROMA_INVALID_HANDLE || name_ptr.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Database name pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        // Parse name
+        let name = unsafe {
+            match c_str_to_string(name_ptr) {
                 Ok(s) => s,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid tenant name",
+                        "Invalid database name",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-    } else {
-        DEFAULT_TENANT.to_string()
-    };
+        };
 
-    let client = unsafe { &*client_handle };
+        // Get client reference
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
 
-    let db_name = match DatabaseName::new(name) {
-        Some(n) => n,
-        None => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid database name (must be at least 3 characters)",
-                func_name,
-                None,
-            );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
+        // Parse tenant name
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            client.default_tenant()
+        };
 
-    let request = match GetDatabaseRequest::try_new(tenant, db_name) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create get database request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
         }
-    };
 
-    let mut frontend = client.frontend.clone();
+        let scope = AccessScope::new(client_handle, &tenant, &name, None);
+        let db_name = match DatabaseName::new(name) {
+            Some(n) => n,
+            None => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid database name (must be at least 3 characters)",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
 
-    match client
-        .runtime
-        .block_on(async { frontend.get_database(request).await })
-    {
-        Ok(database) => {
-            unsafe {
-                *id_result = string_to_c_str(database.id.to_string());
+        // Create database request
+        let request = match CreateDatabaseRequest::try_new(tenant, db_name) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create database request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Execute request
+        match client.block_on_with_retry("create_database", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.create_database(request).await }
+        }) {
+            Ok(_) => {
+                // Return success
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to create database", &e)
+                    .report(error_out, func_name)
             }
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
-        }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::NotFound,
-                "Database not found",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::NotFound as c_int
         }
-    }
+    })
 }
 
-/// Deletes a database from ChromaDB
+/// Gets a database from ChromaDB
 #[no_mangle]
-pub extern "C" fn chroma_delete_database(
-    client_handle: *mut ChromaClient,
+pub extern "C" fn chroma_get_database(
+    client_handle: ChromaHandle,
     name_ptr: *const c_char,
     tenant_ptr: *const c_char,
+    id_result: *mut *mut c_char,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_delete_database";
+    ffi_boundary(error_out, "chroma_get_database", || {
+        let func_name = "chroma_get_database";
+
+        if client_handle == CHROMA_INVALID_HANDLE || name_ptr.is_null() || id_result.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if name_ptr.is_null() {
+                "Database name pointer is null"
+            } else {
+                "ID result pointer is null"
+            };
 
-    if client_handle.is_null() || name_ptr.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let name = unsafe {
+            match c_str_to_string(name_ptr) {
+                Ok(s) => s,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid database name",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
         } else {
-            "Database name pointer is null"
+            client.default_tenant()
         };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        let scope = AccessScope::new(client_handle, &tenant, &name, None);
+        let db_name = match DatabaseName::new(name) {
+            Some(n) => n,
+            None => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid database name (must be at least 3 characters)",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
 
-    let name = unsafe {
-        match c_str_to_string(name_ptr) {
-            Ok(s) => s,
+        let request = match GetDatabaseRequest::try_new(tenant, db_name) {
+            Ok(req) => req,
             Err(e) => {
                 set_error(
                     error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Invalid database name",
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get database request",
                     func_name,
-                    Some(&e.to_string()),
+                    Some(&format!("Validation error: {:?}", e)),
                 );
-                return ChromaErrorCode::InvalidArgument as c_int;
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match client.block_on_with_retry("get_database", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get_database(request).await }
+        }) {
+            Ok(database) => {
+                unsafe {
+                    *id_result = string_to_c_str(database.id.to_string());
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::NotFound, "Database not found", &e)
+                    .report(error_out, func_name)
             }
         }
-    };
+    })
+}
 
-    let tenant = if !tenant_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(tenant_ptr) {
+/// Deletes a database from ChromaDB
+#[no_mangle]
+pub extern "C" fn chroma_delete_database(
+    client_handle: ChromaHandle,
+    name_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete_database", || {
+        let func_name = "chroma_delete_database";
+
+        if client_handle == CHROMA_INVALID_HANDLE || name_ptr.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Database name pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let name = unsafe {
+            match c_str_to_string(name_ptr) {
                 Ok(s) => s,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid tenant name",
+                        "Invalid database name",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-    } else {
-        DEFAULT_TENANT.to_string()
-    };
+        };
 
-    let client = unsafe { &*client_handle };
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
 
-    let request = match DeleteDatabaseRequest::try_new(tenant, name) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create delete database request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            client.default_tenant()
+        };
+
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
         }
-    };
 
-    let mut frontend = client.frontend.clone();
+        let scope = AccessScope::new(client_handle, &tenant, &name, None);
+        let request = match DeleteDatabaseRequest::try_new(tenant, name) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create delete database request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
 
-    match client
-        .runtime
-        .block_on(async { frontend.delete_database(request).await })
-    {
-        Ok(_) => {
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
-        }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to delete database",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
+        match client.block_on_with_retry("delete_database", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.delete_database(request).await }
+        }) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to delete database", &e)
+                    .report(error_out, func_name)
+            }
         }
-    }
+    })
 }
@@ -0,0 +1,435 @@
+// Mirroring of a client's persist directory to an object store
+use chroma_config::{registry::Registry, Configurable};
+use chroma_storage::config::StorageConfig;
+use chroma_sqlite::db::SqliteDb;
+use chroma_storage::{DeleteOptions, GetOptions, PutOptions, Storage, StorageError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Instant, SystemTime};
+use tokio::sync::Mutex;
+
+use crate::client::ChromaClient;
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// SQLite database of the persist directory, uploaded from a snapshot rather than as is
+const DATABASE_FILE: &str = "chroma.sqlite3";
+
+/// Write-ahead log of the database; never uploaded, as the snapshot of the database includes
+/// it, but a change to it means the database changed
+const WAL_FILE: &str = "chroma.sqlite3-wal";
+
+/// Files of the persist directory that are never mirrored: the advisory lock and the SQLite
+/// shared-memory index, which only make sense on the machine that created them
+const UNMIRRORED_FILES: &[&str] = &["chroma.lock", "chroma.sqlite3-shm"];
+
+/// Key of the manifest below the prefix, written last by every upload
+const MANIFEST_KEY: &str = "manifest.json";
+
+/// Object store and key prefix of a mirrored persist directory, in the format of the
+/// `storage` section of the Chroma worker configuration
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectStoreSections {
+    storage: StorageConfig,
+    #[serde(default)]
+    prefix: String,
+}
+
+/// Parsed object store configuration, with the JSON it came from for the effective config
+pub(crate) struct ObjectStoreOptions {
+    pub(crate) storage: StorageConfig,
+    pub(crate) prefix: String,
+    pub(crate) json: Value,
+}
+
+impl ObjectStoreOptions {
+    pub(crate) fn from_json(json: &str) -> Result<Self, FfiError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid object store configuration JSON",
+                Some(e.to_string()),
+            )
+        })?;
+        let sections = ObjectStoreSections::deserialize(&value).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid object store configuration",
+                Some(e.to_string()),
+            )
+        })?;
+
+        Ok(ObjectStoreOptions {
+            storage: sections.storage,
+            prefix: sections.prefix.trim_matches('/').to_string(),
+            json: value,
+        })
+    }
+}
+
+/// Object holding one file of the persist directory as of an upload
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    key: String,
+    size: u64,
+}
+
+/// Files of the latest complete upload and the objects holding them. Each upload puts the
+/// files that changed under a prefix of its own generation and then replaces the manifest,
+/// so a restore never mixes files of two uploads.
+#[derive(Default, Serialize, Deserialize)]
+struct Manifest {
+    generation: u64,
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+/// What the mirror knows of the store: the manifest, once read, and the size and
+/// modification time of each local file as last uploaded, keyed by relative path
+#[derive(Default)]
+struct MirrorState {
+    manifest: Option<Manifest>,
+    stamps: HashMap<String, (u64, SystemTime)>,
+}
+
+/// Copy of a persist directory kept in an object store
+pub(crate) struct ObjectStoreMirror {
+    storage: Storage,
+    prefix: String,
+    persist_path: PathBuf,
+    state: Mutex<MirrorState>,
+}
+
+fn storage_error(message: &'static str, key: &str, e: impl std::fmt::Display) -> FfiError {
+    FfiError::new(
+        ChromaErrorCode::InternalError,
+        message,
+        Some(format!("{}: {}", key, e)),
+    )
+}
+
+/// Size and modification time of a file, which tell whether it changed since its upload
+fn file_stamp(path: &Path) -> std::io::Result<(u64, SystemTime)> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.len(), metadata.modified()?))
+}
+
+/// Relative paths of every file below a directory, with `/` separators
+fn list_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if !UNMIRRORED_FILES.contains(&relative.as_str()) {
+                files.push(relative);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl ObjectStoreMirror {
+    /// Connects to the object store; nothing is read or written yet
+    pub(crate) async fn open(
+        options: &ObjectStoreOptions,
+        persist_path: &str,
+        registry: &Registry,
+    ) -> Result<Self, FfiError> {
+        let storage = Storage::try_from_config(&options.storage, registry)
+            .await
+            .map_err(|e| {
+                FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to connect to the object store",
+                    &*e,
+                )
+            })?;
+
+        Ok(ObjectStoreMirror {
+            storage,
+            prefix: options.prefix.clone(),
+            persist_path: PathBuf::from(persist_path),
+            state: Mutex::new(MirrorState::default()),
+        })
+    }
+
+    fn key(&self, relative: &str) -> String {
+        if self.prefix.is_empty() {
+            relative.to_string()
+        } else {
+            format!("{}/{}", self.prefix, relative)
+        }
+    }
+
+    fn list_prefix(&self) -> String {
+        if self.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.prefix)
+        }
+    }
+
+    /// Reads the manifest of the last upload; an empty one when nothing was uploaded yet
+    async fn read_manifest(&self) -> Result<Manifest, FfiError> {
+        let key = self.key(MANIFEST_KEY);
+        let bytes = match self.storage.get(&key, GetOptions::default()).await {
+            Ok(bytes) => bytes,
+            Err(StorageError::NotFound { .. }) => return Ok(Manifest::default()),
+            Err(e) => {
+                return Err(storage_error(
+                    "Failed to download from the object store",
+                    &key,
+                    e,
+                ))
+            }
+        };
+        serde_json::from_slice(&bytes).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid object store manifest",
+                Some(format!("{}: {}", key, e)),
+            )
+        })
+    }
+
+    /// Downloads the files of the last upload into the persist directory and returns how
+    /// many were restored.
+    ///
+    /// Only called when the directory has no database yet, so a local copy is never
+    /// overwritten by an older one from the store.
+    pub(crate) async fn restore(&self) -> Result<usize, FfiError> {
+        let mut state = self.state.lock().await;
+        let manifest = self.read_manifest().await?;
+
+        let mut restored = 0;
+        for (relative, entry) in &manifest.files {
+            // Paths come from the store, so they must not escape the persist directory
+            let relative_path = Path::new(relative);
+            if relative.is_empty()
+                || !relative_path
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+            {
+                continue;
+            }
+
+            let bytes = self
+                .storage
+                .get(&entry.key, GetOptions::default())
+                .await
+                .map_err(|e| {
+                    storage_error("Failed to download from the object store", &entry.key, e)
+                })?;
+            let path = self.persist_path.join(relative_path);
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, bytes.as_slice()))
+                .and_then(|_| file_stamp(&path));
+            let stamp = written.map_err(|e| {
+                storage_error("Failed to write a restored file", &path.to_string_lossy(), e)
+            })?;
+
+            state.stamps.insert(relative.clone(), stamp);
+            restored += 1;
+        }
+        state.manifest = Some(manifest);
+        Ok(restored)
+    }
+
+    /// Uploads the files of the persist directory that changed since their last upload and
+    /// returns how many were uploaded.
+    ///
+    /// The database is uploaded from a snapshot taken with `VACUUM INTO`, which includes the
+    /// write-ahead log and is consistent even while writes go on, and before the segment
+    /// files, so the segments restored are never older than the database that refers to
+    /// them. The new manifest is written once every file is in the store; objects it no
+    /// longer refers to are deleted afterwards.
+    pub(crate) async fn upload(&self, sqlite_db: &SqliteDb) -> Result<usize, FfiError> {
+        // Held across the uploads, so concurrent flushes do not upload the same files
+        let mut state = self.state.lock().await;
+        let mut manifest = match state.manifest.take() {
+            Some(manifest) => manifest,
+            None => self.read_manifest().await?,
+        };
+        let result = self
+            .upload_generation(&mut state, &mut manifest, sqlite_db)
+            .await;
+        // After a failure the manifest is read again, as the store still holds the old one
+        if result.is_ok() {
+            state.manifest = Some(manifest);
+        }
+        result
+    }
+
+    /// Uploads the changed files under the prefix of the next generation and writes its
+    /// manifest
+    async fn upload_generation(
+        &self,
+        state: &mut MirrorState,
+        manifest: &mut Manifest,
+        sqlite_db: &SqliteDb,
+    ) -> Result<usize, FfiError> {
+        let generation = manifest.generation + 1;
+        let mut files = Vec::new();
+        list_files(&self.persist_path, &self.persist_path, &mut files).map_err(|e| {
+            storage_error(
+                "Failed to list the persist directory",
+                &self.persist_path.to_string_lossy(),
+                e,
+            )
+        })?;
+
+        let mut stamps = HashMap::new();
+        for relative in &files {
+            // Files removed while listing are left out
+            if let Ok(stamp) = file_stamp(&self.persist_path.join(relative)) {
+                stamps.insert(relative.clone(), stamp);
+            }
+        }
+        let changed = |relative: &str| stamps.get(relative) != state.stamps.get(relative);
+        let mut pending: Vec<&String> = stamps
+            .keys()
+            .filter(|relative| relative.as_str() != DATABASE_FILE && relative.as_str() != WAL_FILE)
+            .filter(|relative| changed(relative))
+            .collect();
+        pending.sort();
+        let upload_database =
+            stamps.contains_key(DATABASE_FILE) && (changed(DATABASE_FILE) || changed(WAL_FILE));
+        let removed = manifest.files.keys().any(|relative| !stamps.contains_key(relative));
+        if pending.is_empty() && !upload_database && !removed {
+            return Ok(0);
+        }
+
+        let generation_key = |relative: &str| self.key(&format!("{}/{}", generation, relative));
+        let mut count = 0;
+        if upload_database {
+            let snapshot = std::env::temp_dir()
+                .join(format!("chroma-snapshot-{}.sqlite3", uuid::Uuid::new_v4()));
+            let key = generation_key(DATABASE_FILE);
+            let uploaded = self.upload_snapshot(sqlite_db, &snapshot, &key).await;
+            let _ = fs::remove_file(&snapshot);
+            manifest.files.insert(DATABASE_FILE.to_string(), uploaded?);
+            count += 1;
+        }
+        for relative in pending {
+            let path = self.persist_path.join(relative);
+            let key = generation_key(relative);
+            self.storage
+                .put_file(&key, &path.to_string_lossy(), PutOptions::default())
+                .await
+                .map_err(|e| storage_error("Failed to upload to the object store", &key, e))?;
+            let size = stamps.get(relative).map_or(0, |(size, _)| *size);
+            manifest.files.insert(relative.clone(), ManifestEntry { key, size });
+            count += 1;
+        }
+        manifest.files.retain(|relative, _| stamps.contains_key(relative));
+        manifest.generation = generation;
+
+        let key = self.key(MANIFEST_KEY);
+        let bytes = serde_json::to_vec(&*manifest).map_err(|e| {
+            storage_error("Failed to write the object store manifest", &key, e)
+        })?;
+        self.storage
+            .put_bytes(&key, bytes, PutOptions::default())
+            .await
+            .map_err(|e| storage_error("Failed to upload to the object store", &key, e))?;
+        state.stamps = stamps;
+
+        self.remove_stale(manifest).await;
+        Ok(count)
+    }
+
+    /// Copies a consistent snapshot of the database to `snapshot` and uploads it to `key`
+    async fn upload_snapshot(
+        &self,
+        sqlite_db: &SqliteDb,
+        snapshot: &Path,
+        key: &str,
+    ) -> Result<ManifestEntry, FfiError> {
+        let snapshot_path = snapshot.to_string_lossy();
+        sqlx::query("VACUUM INTO ?")
+            .bind(snapshot_path.as_ref())
+            .execute(sqlite_db.get_conn())
+            .await
+            .map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Failed to snapshot the SQLite database",
+                    Some(e.to_string()),
+                )
+            })?;
+        let size = fs::metadata(snapshot)
+            .map_err(|e| storage_error("Failed to read the database snapshot", &snapshot_path, e))?
+            .len();
+        self.storage
+            .put_file(key, &snapshot_path, PutOptions::default())
+            .await
+            .map_err(|e| storage_error("Failed to upload to the object store", key, e))?;
+        Ok(ManifestEntry {
+            key: key.to_string(),
+            size,
+        })
+    }
+
+    /// Deletes the objects under the prefix that `manifest` does not refer to: files of
+    /// earlier uploads, and of uploads that failed before their manifest was written. The
+    /// upload already succeeded, so failures are only logged.
+    async fn remove_stale(&self, manifest: &Manifest) {
+        let list_prefix = self.list_prefix();
+        let keys = match self
+            .storage
+            .list_prefix(&list_prefix, GetOptions::default())
+            .await
+        {
+            Ok(keys) => keys,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to list the object store for stale objects");
+                return;
+            }
+        };
+
+        let manifest_key = self.key(MANIFEST_KEY);
+        let live: HashSet<&str> = manifest.files.values().map(|e| e.key.as_str()).collect();
+        for key in keys {
+            if key == manifest_key || live.contains(key.as_str()) {
+                continue;
+            }
+            if let Err(e) = self.storage.delete(&key, DeleteOptions::default()).await {
+                tracing::warn!(error = %e, key = %key, "Failed to delete a stale object");
+            }
+        }
+    }
+}
+
+impl ChromaClient {
+    /// Uploads the changed files of the persist directory to the object store, if the client
+    /// mirrors one
+    pub(crate) fn sync_object_store(&self) -> Result<(), FfiError> {
+        let Some(mirror) = self.object_store.as_ref() else {
+            return Ok(());
+        };
+
+        let Some(sqlite_db) = self.sqlite_db.as_ref() else {
+            return Err(FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to upload to the object store",
+                Some("The SQLite database is not registered".to_string()),
+            ));
+        };
+
+        let started = Instant::now();
+        let result = self.runtime.block_on(mirror.upload(sqlite_db)).map(|_| ());
+        self.metrics
+            .record("object_store_upload", started.elapsed(), result.is_ok());
+        result
+    }
+}
@@ -0,0 +1,320 @@
+// Long operations run on a background thread and polled through an operation handle
+use libc::{c_int, c_uint, size_t};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    insert_operation, operation_from_handle, remove_operation, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+
+/// States reported by `chroma_operation_status` and `chroma_operation_wait`
+pub const CHROMA_OPERATION_RUNNING: c_int = 0;
+pub const CHROMA_OPERATION_SUCCEEDED: c_int = 1;
+pub const CHROMA_OPERATION_FAILED: c_int = 2;
+pub const CHROMA_OPERATION_CANCELLED: c_int = 3;
+
+/// Timeout of `chroma_operation_wait` waiting until the operation ends, however long it takes
+pub const CHROMA_WAIT_FOREVER: c_uint = c_uint::MAX;
+
+/// Progress of an operation, filled in by `chroma_operation_status` in a caller-owned struct
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChromaOperationProgress {
+    /// Units of work done so far, e.g. records
+    pub processed: size_t,
+    /// Units of work in the operation
+    pub total: size_t,
+    /// Time since the operation started, or its duration once it ended
+    pub elapsed_ms: u64,
+    /// Estimated time left from the rate so far; -1 until some work is done and once ended
+    pub eta_ms: i64,
+}
+
+struct OperationState {
+    state: c_int,
+    processed: usize,
+    ended: Option<Duration>,
+    error: Option<FfiError>,
+}
+
+/// A long operation started by a `*_async` function, shared by its handle and the thread
+/// running it
+pub struct ChromaOperation {
+    pub(crate) client: Arc<ChromaClient>,
+    total: usize,
+    started: Instant,
+    cancelled: AtomicBool,
+    state: Mutex<OperationState>,
+    ended: Condvar,
+}
+
+impl ChromaOperation {
+    fn lock(&self) -> std::sync::MutexGuard<'_, OperationState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records `units` more units of work done
+    pub(crate) fn advance(&self, units: usize) {
+        self.lock().processed += units;
+    }
+
+    /// Asks the operation to stop after its current step
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `chroma_operation_cancel` asked the operation to stop; the work checks it
+    /// between steps
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn finish(&self, result: Result<(), FfiError>) {
+        let mut state = self.lock();
+        state.state = match &result {
+            Ok(()) if self.is_cancelled() => CHROMA_OPERATION_CANCELLED,
+            Ok(()) => CHROMA_OPERATION_SUCCEEDED,
+            Err(_) => CHROMA_OPERATION_FAILED,
+        };
+        state.error = result.err();
+        state.ended = Some(self.started.elapsed());
+        self.ended.notify_all();
+    }
+
+    fn progress(&self) -> (c_int, ChromaOperationProgress) {
+        let state = self.lock();
+        let elapsed = state.ended.unwrap_or_else(|| self.started.elapsed());
+        let eta_ms = match state.state {
+            CHROMA_OPERATION_RUNNING if state.processed > 0 => {
+                let left = self.total.saturating_sub(state.processed);
+                (elapsed.as_secs_f64() * 1000.0 * left as f64 / state.processed as f64) as i64
+            }
+            _ => -1,
+        };
+        let progress = ChromaOperationProgress {
+            processed: state.processed,
+            total: self.total,
+            elapsed_ms: elapsed.as_millis() as u64,
+            eta_ms,
+        };
+        (state.state, progress)
+    }
+
+    /// Waits up to `timeout` for the operation to end and returns its state and error
+    fn wait(&self, timeout: Option<Duration>) -> (c_int, Option<FfiError>) {
+        let state = self.lock();
+        let is_running = |state: &mut OperationState| state.state == CHROMA_OPERATION_RUNNING;
+        let state = match timeout {
+            Some(timeout) => {
+                self.ended
+                    .wait_timeout_while(state, timeout, is_running)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            }
+            None => self
+                .ended
+                .wait_while(state, is_running)
+                .unwrap_or_else(|e| e.into_inner()),
+        };
+        (state.state, state.error.clone())
+    }
+}
+
+/// Starts `work` on a background thread and returns the handle of the operation, which
+/// reports `total` units of work as the work advances it
+pub(crate) fn start_operation(
+    client: Arc<ChromaClient>,
+    name: &str,
+    total: usize,
+    work: impl FnOnce(&ChromaOperation) -> Result<(), FfiError> + Send + 'static,
+) -> Result<ChromaHandle, FfiError> {
+    let operation = Arc::new(ChromaOperation {
+        client,
+        total,
+        started: Instant::now(),
+        cancelled: AtomicBool::new(false),
+        state: Mutex::new(OperationState {
+            state: CHROMA_OPERATION_RUNNING,
+            processed: 0,
+            ended: None,
+            error: None,
+        }),
+        ended: Condvar::new(),
+    });
+
+    let running = operation.clone();
+    thread::Builder::new()
+        .name(format!("chroma-{}", name))
+        .spawn(move || {
+            let result = work(&running);
+            running.finish(result);
+        })
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to start the operation thread",
+                Some(e.to_string()),
+            )
+        })?;
+    Ok(insert_operation(operation))
+}
+
+/// Reports the state of an operation (`CHROMA_OPERATION_*`) and its progress without
+/// blocking, for hosts polling instead of taking progress callbacks.
+///
+/// `progress_out` and `state_out` may each be null. The ETA extrapolates the rate so far. An
+/// operation that failed reports `CHROMA_OPERATION_FAILED` here; its error is returned by
+/// `chroma_operation_wait`.
+#[no_mangle]
+pub extern "C" fn chroma_operation_status(
+    operation_handle: ChromaHandle,
+    progress_out: *mut ChromaOperationProgress,
+    state_out: *mut c_int,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_operation_status", || {
+        let func_name = "chroma_operation_status";
+
+        if operation_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Operation handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let operation = match operation_from_handle(operation_handle) {
+            Ok(operation) => operation,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (state, progress) = operation.progress();
+        if let Some(progress_out) = unsafe { progress_out.as_mut() } {
+            *progress_out = progress;
+        }
+        if let Some(state_out) = unsafe { state_out.as_mut() } {
+            *state_out = state;
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Waits up to `timeout_ms` for an operation to end: 0 polls, `CHROMA_WAIT_FOREVER` waits
+/// without limit. `state_out`, when not null, receives the state reached.
+///
+/// Returns success while the operation is still running or once it succeeded or was
+/// cancelled; once it failed, returns the operation's error, each time it is waited for.
+#[no_mangle]
+pub extern "C" fn chroma_operation_wait(
+    operation_handle: ChromaHandle,
+    timeout_ms: c_uint,
+    state_out: *mut c_int,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_operation_wait", || {
+        let func_name = "chroma_operation_wait";
+
+        if operation_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Operation handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let operation = match operation_from_handle(operation_handle) {
+            Ok(operation) => operation,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let timeout = (timeout_ms != CHROMA_WAIT_FOREVER)
+            .then(|| Duration::from_millis(timeout_ms.into()));
+        let (state, error) = operation.wait(timeout);
+        if let Some(state_out) = unsafe { state_out.as_mut() } {
+            *state_out = state;
+        }
+        match error {
+            Some(e) => e.report(error_out, func_name),
+            None => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+        }
+    })
+}
+
+/// Asks an operation to stop. It stops at its next step, e.g. after the batch in progress,
+/// and ends as `CHROMA_OPERATION_CANCELLED`; work already done is kept. Does nothing once
+/// the operation ended.
+#[no_mangle]
+pub extern "C" fn chroma_operation_cancel(
+    operation_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_operation_cancel", || {
+        let func_name = "chroma_operation_cancel";
+
+        if operation_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Operation handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        match operation_from_handle(operation_handle) {
+            Ok(operation) => {
+                operation.cancel();
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Releases an operation handle. A running operation is cancelled and ends in the
+/// background.
+#[no_mangle]
+pub extern "C" fn chroma_destroy_operation(
+    operation_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_destroy_operation", || {
+        let func_name = "chroma_destroy_operation";
+
+        if operation_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Operation handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        match remove_operation(operation_handle) {
+            Ok(operation) => {
+                operation.cancel();
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,57 @@
+// Normalization of the filesystem paths passed to a client
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Prefix of Windows verbatim paths, which bypass `MAX_PATH` and path parsing
+#[cfg(windows)]
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// Normalizes a directory path passed by the caller before it is used by the backend.
+///
+/// On Windows the path is made absolute with `.` and `..` resolved and separators turned
+/// into backslashes, then converted to a verbatim path: `C:\data` becomes `\\?\C:\data` and
+/// the UNC share `\\server\share\data` becomes `\\?\UNC\server\share\data`. Verbatim paths
+/// are not limited to `MAX_PATH` characters, so deep persist directories work without the
+/// long path policy enabled. Paths that already are verbatim or device paths are kept.
+///
+/// Elsewhere the path is returned unchanged.
+pub(crate) fn normalize_path(path: &str) -> Result<String, FfiError> {
+    if path.is_empty() {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Path is empty",
+            None,
+        ));
+    }
+
+    #[cfg(windows)]
+    {
+        windows_verbatim_path(path)
+    }
+    #[cfg(not(windows))]
+    {
+        Ok(path.to_string())
+    }
+}
+
+#[cfg(windows)]
+fn windows_verbatim_path(path: &str) -> Result<String, FfiError> {
+    if path.starts_with(VERBATIM_PREFIX) || path.starts_with(r"\\.\") {
+        return Ok(path.to_string());
+    }
+
+    // Resolves the path against the current directory the way Windows APIs would,
+    // including `/` separators and `.` and `..` components, which verbatim paths reject
+    let absolute = std::path::absolute(path).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid path",
+            Some(format!("{}: {}", path, e)),
+        )
+    })?;
+    let absolute = absolute.to_string_lossy();
+
+    Ok(match absolute.strip_prefix(r"\\") {
+        Some(unc) => format!(r"{}UNC\{}", VERBATIM_PREFIX, unc),
+        None => format!("{}{}", VERBATIM_PREFIX, absolute),
+    })
+}
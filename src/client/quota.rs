@@ -0,0 +1,264 @@
+// Per-client and per-tenant quotas on collections, records, dimensions and write rate
+use chroma_types::{CollectionUuid, CountRequest, ListCollectionsRequest, ReadLevel};
+use libc::{c_char, c_int, c_uint, size_t};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+use crate::abi::{read_sized, SizedInput};
+use crate::client::{call_with_retry, AccessScope, ChromaClient};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Operations counted against `max_write_ops_per_sec`
+const WRITE_OPERATIONS: &[&str] = &["add", "update", "upsert", "delete", "batch_commit"];
+
+/// Operations that may grow a collection past `max_records_per_collection`
+const GROWING_OPERATIONS: &[&str] = &["add", "upsert", "batch_commit"];
+
+/// Limits enforced on the calls of a client; 0 leaves a limit unset
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChromaQuotas {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Collections per database
+    pub max_collections: size_t,
+    /// Records per collection
+    pub max_records_per_collection: size_t,
+    /// Dimension of the embeddings written
+    pub max_dimension: size_t,
+    /// Adds, updates, upserts, deletes and batch commits per second
+    pub max_write_ops_per_sec: c_uint,
+}
+
+unsafe impl SizedInput for ChromaQuotas {}
+
+/// Write rate allowance of a client or tenant, refilled continuously up to one second's worth
+struct TokenBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn take(&mut self, per_sec: c_uint) -> bool {
+        let now = Instant::now();
+        let capacity = f64::from(per_sec);
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity).min(capacity);
+        self.refilled = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Quotas set with `chroma_set_quotas` and the write rate consumed against them
+#[derive(Default)]
+pub(crate) struct Quotas {
+    /// Quotas of the whole client, for tenants without their own
+    client: RwLock<Option<ChromaQuotas>>,
+    tenants: RwLock<HashMap<String, ChromaQuotas>>,
+    /// Write allowance by tenant, or under `None` for the whole client
+    buckets: Mutex<HashMap<Option<String>, TokenBucket>>,
+}
+
+impl Quotas {
+    /// Quotas applying to a tenant, and the tenant whose write allowance they draw from
+    /// (`None` for the client's)
+    fn for_tenant(&self, tenant: &str) -> Option<(ChromaQuotas, Option<String>)> {
+        let tenants = self.tenants.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(quotas) = tenants.get(tenant) {
+            return Some((*quotas, Some(tenant.to_string())));
+        }
+        let client = self.client.read().unwrap_or_else(|e| e.into_inner());
+        client.map(|quotas| (quotas, None))
+    }
+}
+
+impl ChromaClient {
+    /// Checks a call against the quotas of its tenant; the error describes the exceeded one
+    pub(crate) fn check_quota(&self, operation: &str, scope: &AccessScope) -> Result<(), String> {
+        let Some((quotas, bucket)) = self.quotas.for_tenant(&scope.tenant) else {
+            return Ok(());
+        };
+
+        let max = quotas.max_dimension;
+        if let Some(dimension) = scope.dimension.filter(|&d| max > 0 && d > max) {
+            return Err(format!(
+                "Embeddings of {} dimensions exceed the quota of {} for tenant {}",
+                dimension, max, scope.tenant
+            ));
+        }
+
+        let max = quotas.max_collections;
+        if max > 0 && operation == "create_collection" {
+            if let Some(names) = self.collection_names(scope) {
+                let exists = scope.collection.as_ref().is_some_and(|name| names.contains(name));
+                if !exists && names.len() >= max {
+                    return Err(format!(
+                        "Database {} already has the {} collections its quota allows",
+                        scope.database, max
+                    ));
+                }
+            }
+        }
+
+        let max = quotas.max_records_per_collection;
+        if max > 0 && GROWING_OPERATIONS.contains(&operation) {
+            // Upserts count as new records, so a collection at its quota cannot be upserted
+            // into even when every record already exists
+            let records = scope.records.unwrap_or(0);
+            if let Some(count) = self.count_records(scope) {
+                if count + records > max {
+                    return Err(format!(
+                        "Collection of {} records cannot take {} more under the quota of {}",
+                        count, records, max
+                    ));
+                }
+            }
+        }
+
+        // Checked last, so calls rejected by another quota do not use up the allowance
+        let max = quotas.max_write_ops_per_sec;
+        if max > 0 && WRITE_OPERATIONS.contains(&operation) {
+            let mut buckets = self.quotas.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let bucket = buckets.entry(bucket).or_insert_with(|| TokenBucket {
+                tokens: f64::from(max),
+                refilled: Instant::now(),
+            });
+            if !bucket.take(max) {
+                return Err(format!(
+                    "Write rate exceeds the quota of {} per second for tenant {}",
+                    max, scope.tenant
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records in the collection a call targets. Counting errors leave the check to the call
+    /// itself, which fails the same way.
+    fn count_records(&self, scope: &AccessScope) -> Option<usize> {
+        let id = scope.collection.as_deref()?;
+        let collection_id = CollectionUuid(uuid::Uuid::parse_str(id).ok()?);
+        let request = CountRequest::try_new(
+            scope.tenant.clone(),
+            scope.database.clone(),
+            collection_id,
+            ReadLevel::default(),
+        )
+        .ok()?;
+        let count = self.runtime.block_on(call_with_retry(
+            self.retry_policy(),
+            &self.frontend,
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.count(request).await }
+            },
+        ));
+        count.ok().map(|count| count as usize)
+    }
+
+    /// Names of the collections in the database a call targets
+    fn collection_names(&self, scope: &AccessScope) -> Option<Vec<String>> {
+        let request =
+            ListCollectionsRequest::try_new(scope.tenant.clone(), scope.database.clone(), None, 0)
+                .ok()?;
+        let collections = self.runtime.block_on(call_with_retry(
+            self.retry_policy(),
+            &self.frontend,
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.list_collections(request).await }
+            },
+        ));
+        collections
+            .ok()
+            .map(|collections| collections.into_iter().map(|c| c.name).collect())
+    }
+}
+
+/// Sets the quotas of a client, or of one tenant when `tenant` is not null; a null `quotas`
+/// removes them.
+///
+/// Calls over a quota fail with `QuotaExceeded` before reaching the backend. A tenant's own
+/// quotas replace the client's for that tenant and get their own write allowance; tenants
+/// without their own share the client's. `max_collections` counts the collections of the
+/// database a collection is created in, and `max_records_per_collection` the records of the
+/// collection before an add, upsert or batch commit plus the records it writes, upserted
+/// records included. `max_write_ops_per_sec` allows bursts of up to one second's worth.
+#[no_mangle]
+pub extern "C" fn chroma_set_quotas(
+    client_handle: ChromaHandle,
+    tenant_ptr: *const c_char,
+    quotas: *const ChromaQuotas,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_quotas", || {
+        let func_name = "chroma_set_quotas";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let tenant = if tenant_ptr.is_null() {
+            None
+        } else {
+            match unsafe { c_str_to_string(tenant_ptr) } {
+                Ok(tenant) => Some(tenant),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid tenant name",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let quotas = match unsafe { read_sized(quotas) } {
+            Ok(quotas) => quotas,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match &tenant {
+            Some(tenant) => {
+                let mut tenants = client.quotas.tenants.write().unwrap_or_else(|e| e.into_inner());
+                match quotas {
+                    Some(quotas) => tenants.insert(tenant.clone(), quotas),
+                    None => tenants.remove(tenant),
+                };
+            }
+            None => {
+                *client.quotas.client.write().unwrap_or_else(|e| e.into_inner()) = quotas;
+            }
+        }
+        // New limits start with a full allowance
+        let mut buckets = client.quotas.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.remove(&tenant);
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
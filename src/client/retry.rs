@@ -0,0 +1,327 @@
+// Retry policy for transient backend errors
+use chroma_error::{ChromaError as BackendError, ErrorCodes};
+use libc::{c_float, c_int, c_uint, size_t};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+use chroma_frontend::Frontend;
+use tracing::Instrument;
+
+use crate::abi::{read_sized, SizedInput};
+use crate::client::{AccessScope, ChromaClient};
+use crate::error::{
+    ffi_boundary, request_id, set_error, set_success, ChromaError, ChromaErrorCode,
+};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Error classes accepted in `ChromaRetryPolicy::retryable_classes`
+pub const CHROMA_RETRY_UNAVAILABLE: c_uint = 1;
+pub const CHROMA_RETRY_RESOURCE_EXHAUSTED: c_uint = 1 << 1;
+pub const CHROMA_RETRY_ABORTED: c_uint = 1 << 2;
+pub const CHROMA_RETRY_DEADLINE_EXCEEDED: c_uint = 1 << 3;
+
+/// Classes retried when a policy leaves `retryable_classes` at 0
+pub const CHROMA_RETRY_DEFAULT_CLASSES: c_uint = CHROMA_RETRY_UNAVAILABLE
+    | CHROMA_RETRY_RESOURCE_EXHAUSTED
+    | CHROMA_RETRY_ABORTED
+    | CHROMA_RETRY_DEADLINE_EXCEEDED;
+
+/// Retry behavior for backend calls made through a client
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ChromaRetryPolicy {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Total attempts including the first one; 0 or 1 disables retries
+    pub max_attempts: c_uint,
+    /// Delay before the first retry, doubled for every further retry
+    pub base_delay_ms: c_uint,
+    /// Upper bound for a single delay (0 = unbounded)
+    pub max_delay_ms: c_uint,
+    /// Fraction of each delay randomized away, between 0 and 1
+    pub jitter: c_float,
+    /// Bitmask of `CHROMA_RETRY_*` classes to retry (0 = default classes)
+    pub retryable_classes: c_uint,
+}
+
+unsafe impl SizedInput for ChromaRetryPolicy {}
+
+impl Default for ChromaRetryPolicy {
+    fn default() -> Self {
+        ChromaRetryPolicy {
+            struct_size: std::mem::size_of::<ChromaRetryPolicy>(),
+            max_attempts: 1,
+            base_delay_ms: 0,
+            max_delay_ms: 0,
+            jitter: 0.0,
+            retryable_classes: 0,
+        }
+    }
+}
+
+impl ChromaRetryPolicy {
+    fn retries(&self, code: ErrorCodes) -> bool {
+        let class = match code {
+            ErrorCodes::Unavailable => CHROMA_RETRY_UNAVAILABLE,
+            ErrorCodes::ResourceExhausted => CHROMA_RETRY_RESOURCE_EXHAUSTED,
+            ErrorCodes::Aborted => CHROMA_RETRY_ABORTED,
+            ErrorCodes::DeadlineExceeded => CHROMA_RETRY_DEADLINE_EXCEEDED,
+            _ => return false,
+        };
+        let classes = if self.retryable_classes == 0 {
+            CHROMA_RETRY_DEFAULT_CLASSES
+        } else {
+            self.retryable_classes
+        };
+        classes & class != 0
+    }
+
+    /// Delay before retry number `retry` (starting at 1): exponential backoff with jitter
+    fn delay(&self, retry: u32) -> Duration {
+        let exponential = u64::from(self.base_delay_ms).saturating_mul(1 << (retry - 1).min(20));
+        let capped = if self.max_delay_ms > 0 {
+            exponential.min(u64::from(self.max_delay_ms))
+        } else {
+            exponential
+        };
+
+        let jitter = self.jitter.clamp(0.0, 1.0) as f64;
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        Duration::from_millis((capped as f64 * (1.0 - jitter * random)) as u64)
+    }
+}
+
+/// A backend error together with the number of attempts made before giving up
+pub(crate) struct Attempted<E> {
+    /// Error of the last attempt, or `None` when the call was aborted by
+    /// `chroma_shutdown_client` or rejected before reaching the backend
+    pub(crate) error: Option<E>,
+    pub(crate) attempts: u32,
+    /// Code and reason of a call denied by the authorizer or a quota or turned away by the
+    /// concurrency limits, which then never reached the backend
+    pub(crate) rejected: Option<(ErrorCodes, String)>,
+}
+
+impl<E> Attempted<E> {
+    /// A call aborted by a client shutdown
+    fn aborted() -> Self {
+        Attempted {
+            error: None,
+            attempts: 0,
+            rejected: None,
+        }
+    }
+
+    /// A call denied by the authorizer (`PermissionDenied`) or a quota (`ResourceExhausted`),
+    /// or turned away by the concurrency limits (`Unavailable`)
+    fn rejected(code: ErrorCodes, reason: String) -> Self {
+        Attempted {
+            error: None,
+            attempts: 0,
+            rejected: Some((code, reason)),
+        }
+    }
+}
+
+const ABORTED_MESSAGE: &str = "Operation aborted by client shutdown";
+
+impl<E: fmt::Debug> fmt::Debug for Attempted<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.error, &self.rejected) {
+            (None, Some((_, reason))) => f.write_str(reason),
+            (None, None) => f.write_str(ABORTED_MESSAGE),
+            (Some(error), _) if self.attempts > 1 => {
+                write!(f, "{:?} (failed after {} attempts)", error, self.attempts)
+            }
+            (Some(error), _) => write!(f, "{:?}", error),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Attempted<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.error, &self.rejected) {
+            (Some(error), _) => fmt::Display::fmt(error, f),
+            (None, Some((_, reason))) => f.write_str(reason),
+            (None, None) => f.write_str(ABORTED_MESSAGE),
+        }
+    }
+}
+
+impl<E: BackendError> std::error::Error for Attempted<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.as_ref().and_then(|error| error.source())
+    }
+}
+
+impl<E: BackendError> BackendError for Attempted<E> {
+    fn code(&self) -> ErrorCodes {
+        match (&self.error, &self.rejected) {
+            (Some(error), _) => error.code(),
+            (None, Some((code, _))) => *code,
+            (None, None) => ErrorCodes::Aborted,
+        }
+    }
+}
+
+/// Runs a backend call, retrying transient errors according to `policy`
+pub(crate) async fn call_with_retry<T, E, F, Fut>(
+    policy: ChromaRetryPolicy,
+    frontend: &Frontend,
+    mut call: F,
+) -> Result<T, Attempted<E>>
+where
+    F: FnMut(Frontend) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: BackendError,
+{
+    let mut attempts = 1;
+    loop {
+        match call(frontend.clone()).await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempts < policy.max_attempts && policy.retries(error.code()) => {
+                tokio::time::sleep(policy.delay(attempts)).await;
+                attempts += 1;
+            }
+            Err(error) => {
+                return Err(Attempted {
+                    error: Some(error),
+                    attempts,
+                    rejected: None,
+                })
+            }
+        }
+    }
+}
+
+impl ChromaClient {
+    /// Retry policy currently applied to backend calls
+    pub(crate) fn retry_policy(&self) -> ChromaRetryPolicy {
+        *self.retry_policy.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs a backend call on the client runtime, retrying transient errors
+    /// according to the client's retry policy, and records it in the client metrics.
+    ///
+    /// The call counts as in flight for `chroma_shutdown_client` and fails as aborted when
+    /// the shutdown gives up waiting for it. The authorizer set with `chroma_set_authorizer`
+    /// checks `scope` first; a denied call fails with a `PermissionDenied` backend error, and a
+    /// call over a quota set with `chroma_set_quotas` with a `ResourceExhausted` one. The call
+    /// then waits for room under the limits set with `chroma_set_concurrency_limits`, failing
+    /// with an `Unavailable` backend error when none frees up in time. Adds, updates, upserts
+    /// and deletes hold the write lock of their collection while they run.
+    /// Successful mutations are recorded in the audit log set with `chroma_set_audit_log`.
+    pub(crate) fn block_on_with_retry<T, E, F, Fut>(
+        &self,
+        operation: &'static str,
+        scope: AccessScope,
+        call: F,
+    ) -> Result<T, Attempted<E>>
+    where
+        F: FnMut(Frontend) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: BackendError,
+    {
+        let Some(_in_flight) = self.lifecycle.enter() else {
+            return Err(Attempted::aborted());
+        };
+        let mut abort = self.lifecycle.abort_signal();
+        let rejected = match self.authorize(operation, &scope) {
+            Err(reason) => Some((ErrorCodes::PermissionDenied, reason)),
+            Ok(()) => self
+                .check_quota(operation, &scope)
+                .err()
+                .map(|reason| (ErrorCodes::ResourceExhausted, reason)),
+        };
+        // Taken before the permit, which a thread holding the lock may still be waiting for
+        let _write = match rejected {
+            Some(_) => None,
+            None => self.write_locks.lock_for(operation, &scope),
+        };
+        let permit = match rejected {
+            Some(rejected) => Err(rejected),
+            None => self
+                .concurrency
+                .acquire(operation)
+                .map_err(|reason| (ErrorCodes::Unavailable, reason)),
+        };
+        let _permit = match permit {
+            Ok(permit) => permit,
+            Err((code, reason)) => {
+                self.metrics.record(operation, Duration::ZERO, false);
+                return Err(Attempted::rejected(code, reason));
+            }
+        };
+
+        let policy = self.retry_policy();
+        let request_id = request_id();
+        let span = tracing::info_span!("chroma_ffi", operation, request_id = request_id.as_deref());
+
+        let started = Instant::now();
+        let result = self.runtime.block_on(async {
+            tokio::select! {
+                result = call_with_retry(policy, &self.frontend, call).instrument(span) => result,
+                _ = abort.wait_for(|aborted| *aborted) => Err(Attempted::aborted()),
+            }
+        });
+
+        self.metrics.record(operation, started.elapsed(), result.is_ok());
+        if result.is_ok() {
+            self.audit(operation, &scope);
+        }
+        result
+    }
+}
+
+/// Sets the retry policy used for backend calls made through a client.
+///
+/// A null `policy` restores the default of a single attempt without retries.
+#[no_mangle]
+pub extern "C" fn chroma_set_retry_policy(
+    client_handle: ChromaHandle,
+    policy: *const ChromaRetryPolicy,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_retry_policy", || {
+        let func_name = "chroma_set_retry_policy";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let policy = match unsafe { read_sized(policy) } {
+            Ok(policy) => policy.unwrap_or_default(),
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if !(0.0..=1.0).contains(&policy.jitter) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Retry jitter must be between 0 and 1",
+                func_name,
+                Some(&format!("Jitter: {}", policy.jitter)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        *client.retry_policy.write().unwrap_or_else(|e| e.into_inner()) = policy;
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
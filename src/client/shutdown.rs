@@ -0,0 +1,170 @@
+// Graceful shutdown of a client, draining the backend calls it is running
+use libc::{c_int, c_uint, size_t};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::handles::{
+    client_from_handle, remove_client_batches, remove_client_collections, remove_client_handles,
+    remove_client_operations, remove_client_prepared_queries, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+
+/// How often a draining shutdown checks whether the in-flight calls have finished
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Tracks the backend calls a client is running, so a shutdown can wait for them
+pub(crate) struct Lifecycle {
+    /// Set once a shutdown starts; collection lookups fail from then on
+    shutting_down: AtomicBool,
+    in_flight: AtomicUsize,
+    /// Set to true when the shutdown gives up waiting, aborting the remaining calls
+    abort: watch::Sender<bool>,
+}
+
+/// Marks one backend call as in flight until dropped
+pub(crate) struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Lifecycle {
+    pub(crate) fn new() -> Self {
+        Lifecycle {
+            shutting_down: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            abort: watch::channel(false).0,
+        }
+    }
+
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Registers a backend call, or returns `None` when calls are being aborted
+    pub(crate) fn enter(&self) -> Option<InFlight<'_>> {
+        if *self.abort.borrow() {
+            return None;
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        Some(InFlight(&self.in_flight))
+    }
+
+    /// Receiver whose value turns true when the in-flight calls are aborted
+    pub(crate) fn abort_signal(&self) -> watch::Receiver<bool> {
+        self.abort.subscribe()
+    }
+
+    /// Waits until no call is in flight or `deadline` passes, and returns how many were left
+    fn wait_idle(&self, deadline: Option<Instant>) -> usize {
+        loop {
+            let in_flight = self.in_flight.load(Ordering::SeqCst);
+            if in_flight == 0 || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return in_flight;
+            }
+            thread::sleep(DRAIN_POLL_INTERVAL);
+        }
+    }
+
+    /// Stops accepting operations, waits up to `drain_timeout` for the calls in flight and
+    /// aborts the rest, returning how many were aborted
+    fn drain(&self, drain_timeout: Duration) -> usize {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let aborted = self.wait_idle(Some(Instant::now() + drain_timeout));
+        if aborted > 0 {
+            self.abort.send_replace(true);
+            // Aborted calls return as soon as their future is dropped
+            self.wait_idle(None);
+        }
+        aborted
+    }
+}
+
+/// Shuts a client down gracefully, unlike `chroma_destroy_client` which releases it at once.
+///
+/// New operations are rejected: every handle of the client, including those issued by
+/// `chroma_client_retain`, is destroyed immediately and collections opened from the client
+/// fail with `InvalidArgument`. Backend calls already running get up to `drain_timeout_ms`
+/// milliseconds to finish; those still running afterwards are aborted and fail in their own
+/// thread. Records queued with `chroma_enqueue_add` get the same time to be written first;
+/// those still queued afterwards are discarded. Pending writes are then flushed as
+/// `chroma_flush` would, unless the client is read-only, and the client and its collection
+/// handles are released.
+///
+/// `*aborted_out` receives the number of aborted calls, also when the final flush fails.
+#[no_mangle]
+pub extern "C" fn chroma_shutdown_client(
+    client_handle: ChromaHandle,
+    drain_timeout_ms: c_uint,
+    aborted_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_shutdown_client", || {
+        let func_name = "chroma_shutdown_client";
+
+        if client_handle == CHROMA_INVALID_HANDLE || aborted_out.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Aborted count output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *aborted_out = 0;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        drop(remove_client_handles(&client));
+        // Operations running in the background stop after their current step
+        for operation in remove_client_operations(&client) {
+            operation.cancel();
+        }
+
+        let drain_timeout = Duration::from_millis(drain_timeout_ms.into());
+        client.ingest.close(drain_timeout);
+        let aborted = client.lifecycle.drain(drain_timeout);
+        unsafe {
+            *aborted_out = aborted;
+        }
+
+        // Stop automatic flushing before the final flush
+        *client.auto_flush.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let flushed = if client.read_only {
+            Ok(())
+        } else {
+            client.flush(None)
+        };
+
+        // The runtime is dropped with the last reference, once the collections are gone
+        drop(remove_client_batches(&client));
+        drop(remove_client_prepared_queries(&client));
+        drop(remove_client_collections(&client));
+        drop(client);
+
+        match flushed {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,164 @@
+// SQLite pragmas applied to the backend database of a client
+use chroma_sqlite::db::SqliteDb;
+use libc::c_int;
+use serde_json::{json, Value};
+use sqlx::Connection;
+
+use crate::error::{ChromaErrorCode, FfiError};
+use crate::types::SqliteConfigFFI;
+
+/// Journal modes accepted in `SqliteConfigFFI::journal_mode`
+pub const CHROMA_JOURNAL_MODE_DEFAULT: c_int = 0;
+pub const CHROMA_JOURNAL_MODE_WAL: c_int = 1;
+pub const CHROMA_JOURNAL_MODE_DELETE: c_int = 2;
+
+/// Levels accepted in `SqliteConfigFFI::synchronous`
+pub const CHROMA_SYNCHRONOUS_DEFAULT: c_int = 0;
+pub const CHROMA_SYNCHRONOUS_OFF: c_int = 1;
+pub const CHROMA_SYNCHRONOUS_NORMAL: c_int = 2;
+pub const CHROMA_SYNCHRONOUS_FULL: c_int = 3;
+
+/// Pragmas requested through `SqliteConfigFFI`; `None` keeps the backend default
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SqlitePragmas {
+    journal_mode: Option<&'static str>,
+    synchronous: Option<&'static str>,
+    busy_timeout_ms: Option<u32>,
+    cache_size_kib: Option<u32>,
+}
+
+impl SqlitePragmas {
+    pub(crate) fn from_ffi(config: &SqliteConfigFFI) -> Result<Self, FfiError> {
+        let journal_mode = match config.journal_mode {
+            CHROMA_JOURNAL_MODE_DEFAULT => None,
+            CHROMA_JOURNAL_MODE_WAL => Some("WAL"),
+            CHROMA_JOURNAL_MODE_DELETE => Some("DELETE"),
+            mode => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid journal mode",
+                    Some(format!("Got {}, expected 0 (default), 1 (WAL) or 2 (DELETE)", mode)),
+                ))
+            }
+        };
+        let synchronous = match config.synchronous {
+            CHROMA_SYNCHRONOUS_DEFAULT => None,
+            CHROMA_SYNCHRONOUS_OFF => Some("OFF"),
+            CHROMA_SYNCHRONOUS_NORMAL => Some("NORMAL"),
+            CHROMA_SYNCHRONOUS_FULL => Some("FULL"),
+            level => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid synchronous level",
+                    Some(format!(
+                        "Got {}, expected 0 (default), 1 (OFF), 2 (NORMAL) or 3 (FULL)",
+                        level
+                    )),
+                ))
+            }
+        };
+
+        Ok(SqlitePragmas {
+            journal_mode,
+            synchronous,
+            busy_timeout_ms: Some(config.busy_timeout_ms).filter(|&ms| ms > 0),
+            cache_size_kib: Some(config.cache_size_kib).filter(|&kib| kib > 0),
+        })
+    }
+
+    /// Whether applying the pragmas writes to the database file
+    pub(crate) fn writes_database(&self) -> bool {
+        self.journal_mode.is_some()
+    }
+
+    pub(crate) fn to_json(self) -> Value {
+        json!({
+            "journal_mode": self.journal_mode,
+            "synchronous": self.synchronous,
+            "busy_timeout_ms": self.busy_timeout_ms,
+            "cache_size_kib": self.cache_size_kib,
+        })
+    }
+
+    /// Pragmas that only affect the connection they run on
+    fn connection_statements(&self) -> Vec<String> {
+        let mut statements = Vec::new();
+        if let Some(synchronous) = self.synchronous {
+            statements.push(format!("PRAGMA synchronous = {}", synchronous));
+        }
+        if let Some(busy_timeout_ms) = self.busy_timeout_ms {
+            statements.push(format!("PRAGMA busy_timeout = {}", busy_timeout_ms));
+        }
+        if let Some(cache_size_kib) = self.cache_size_kib {
+            // Negative sizes are in KiB rather than pages
+            statements.push(format!("PRAGMA cache_size = -{}", cache_size_kib));
+        }
+        statements
+    }
+
+    /// Applies the pragmas to the connection pool of the backend database.
+    ///
+    /// The journal mode is stored in the database file, so setting it once covers every
+    /// connection. The other pragmas are per connection: every connection the pool may
+    /// hold is opened and configured before the client is handed out.
+    pub(crate) async fn apply(&self, db: &SqliteDb) -> Result<(), FfiError> {
+        let pool = db.get_conn();
+        let pragma_error = |e: sqlx::Error| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to apply SQLite pragma",
+                Some(e.to_string()),
+            )
+        };
+
+        if let Some(journal_mode) = self.journal_mode {
+            sqlx::query(&format!("PRAGMA journal_mode = {}", journal_mode))
+                .execute(pool)
+                .await
+                .map_err(pragma_error)?;
+        }
+
+        let statements = self.connection_statements();
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        // Connections are held until all are configured, so none is handed out twice
+        let mut connections = Vec::new();
+        for _ in 0..pool.options().get_max_connections() {
+            let mut connection = pool.acquire().await.map_err(pragma_error)?;
+            for statement in &statements {
+                sqlx::query(statement)
+                    .execute(&mut *connection)
+                    .await
+                    .map_err(pragma_error)?;
+            }
+            connections.push(connection);
+        }
+        Ok(())
+    }
+}
+
+/// Makes every connection of the backend database read-only, so SQLite itself rejects any
+/// write a read-only client would still attempt.
+///
+/// The backend opens its pool read-write; the connections it already opened are closed and
+/// the pool reopens them with `SQLITE_OPEN_READONLY`.
+pub(crate) async fn reopen_read_only(db: &SqliteDb) -> Result<(), FfiError> {
+    let pool = db.get_conn();
+    let reopen_error = |e: sqlx::Error| {
+        FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Failed to reopen the SQLite database read-only",
+            Some(e.to_string()),
+        )
+    };
+
+    let options = (*pool.connect_options()).clone().read_only(true);
+    pool.set_connect_options(options);
+    for _ in 0..pool.num_idle() {
+        let connection = pool.acquire().await.map_err(reopen_error)?;
+        connection.detach().close().await.map_err(reopen_error)?;
+    }
+    Ok(())
+}
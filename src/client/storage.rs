@@ -0,0 +1,143 @@
+// Disk usage of a client's persist directory
+use libc::{c_char, c_int};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// File name of the SQLite database inside the persist directory
+const SQLITE_FILE: &str = "chroma.sqlite3";
+
+/// Total size in bytes of a file, or of every file below a directory; symlinks are not
+/// followed and files removed while walking count as empty
+fn disk_usage(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += disk_usage(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+/// Breaks the disk usage of a persist directory down into SQLite, WAL and segment directories
+fn storage_info(persist_path: &str) -> io::Result<Value> {
+    let mut sqlite_bytes = 0;
+    let mut wal_bytes = 0;
+    let mut other_bytes = 0;
+    let mut segments = Vec::new();
+
+    for entry in fs::read_dir(persist_path)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let bytes = disk_usage(&entry.path())?;
+
+        if name == SQLITE_FILE {
+            sqlite_bytes += bytes;
+        } else if name == format!("{}-wal", SQLITE_FILE) || name == format!("{}-shm", SQLITE_FILE) {
+            wal_bytes += bytes;
+        } else if entry.path().is_dir() && Uuid::parse_str(&name).is_ok() {
+            segments.push((name, bytes));
+        } else {
+            other_bytes += bytes;
+        }
+    }
+
+    segments.sort();
+    let segment_bytes: u64 = segments.iter().map(|(_, bytes)| bytes).sum();
+    let segments: Vec<Value> = segments
+        .into_iter()
+        .map(|(id, bytes)| json!({ "segment_id": id, "bytes": bytes }))
+        .collect();
+
+    Ok(json!({
+        "persist_path": persist_path,
+        "total_bytes": sqlite_bytes + wal_bytes + segment_bytes + other_bytes,
+        "sqlite_bytes": sqlite_bytes,
+        "wal_bytes": wal_bytes,
+        "segment_bytes": segment_bytes,
+        "other_bytes": other_bytes,
+        "segments": segments,
+    }))
+}
+
+/// Reports the disk usage of a client's persist directory as JSON.
+///
+/// Usage is broken down into the SQLite database, its WAL and shared-memory files,
+/// and one entry per segment directory (named after the segment UUID). Clients without
+/// a persist directory report `null` as the path and zero bytes.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_get_storage_info(
+    client_handle: ChromaHandle,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_storage_info", || {
+        let func_name = "chroma_get_storage_info";
+
+        if client_handle == CHROMA_INVALID_HANDLE || out_json.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let info = match client.persist_path.as_deref() {
+            Some(persist_path) => match storage_info(persist_path) {
+                Ok(info) => info,
+                Err(e) => {
+                    return FfiError::new(
+                        ChromaErrorCode::InternalError,
+                        "Failed to read persist directory",
+                        Some(format!("{}: {}", persist_path, e)),
+                    )
+                    .report(error_out, func_name);
+                }
+            },
+            None => json!({
+                "persist_path": null,
+                "total_bytes": 0,
+                "sqlite_bytes": 0,
+                "wal_bytes": 0,
+                "segment_bytes": 0,
+                "other_bytes": 0,
+                "segments": [],
+            }),
+        };
+
+        unsafe {
+            *out_json = string_to_c_str(info.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,71 @@
+// Per-collection locks serializing the record writes made through a client
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+
+use crate::client::AccessScope;
+
+/// Operations that write records and take the lock of their collection
+const RECORD_WRITES: &[&str] = &["add", "update", "upsert", "delete"];
+
+/// Collections being written and the thread writing each, so that a check of record
+/// versions and the write it guards run without another write to the collection in between
+#[derive(Default)]
+pub(crate) struct WriteLocks {
+    held: Mutex<HashMap<String, ThreadId>>,
+    released: Condvar,
+}
+
+/// The write lock of one collection, held until dropped
+pub(crate) struct WriteGuard<'a> {
+    locks: &'a WriteLocks,
+    /// `None` when the thread already held the lock further up the call
+    collection: Option<String>,
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        let Some(collection) = self.collection.take() else {
+            return;
+        };
+        let mut held = self.locks.held.lock().unwrap_or_else(|e| e.into_inner());
+        held.remove(&collection);
+        drop(held);
+        self.locks.released.notify_all();
+    }
+}
+
+impl WriteLocks {
+    /// Waits until no other thread writes to `collection` and holds its lock until the guard
+    /// is dropped. A thread that already holds it gets it again, for a write made while it
+    /// checks versions.
+    pub(crate) fn lock(&self, collection: &str) -> WriteGuard<'_> {
+        let current = thread::current().id();
+        let mut held = self.held.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match held.get(collection) {
+                Some(holder) if *holder == current => {
+                    return WriteGuard {
+                        locks: self,
+                        collection: None,
+                    }
+                }
+                Some(_) => held = self.released.wait(held).unwrap_or_else(|e| e.into_inner()),
+                None => break,
+            }
+        }
+        held.insert(collection.to_string(), current);
+        WriteGuard {
+            locks: self,
+            collection: Some(collection.to_string()),
+        }
+    }
+
+    /// Lock of the collection a backend call writes records to, if it does
+    pub(crate) fn lock_for(&self, operation: &str, scope: &AccessScope) -> Option<WriteGuard<'_>> {
+        if !RECORD_WRITES.contains(&operation) {
+            return None;
+        }
+        scope.collection.as_deref().map(|collection| self.lock(collection))
+    }
+}
@@ -0,0 +1,378 @@
+// Aggregations over the metadata of a collection's records
+use chroma_types::{GetRequest, IncludeList, Metadata, MetadataValue, Where};
+use libc::{c_char, c_int, c_uint};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ptr;
+
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::ordering::compare_values;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::ChromaResultSet;
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AggregateKind {
+    Count,
+    Min,
+    Max,
+    Distinct,
+}
+
+/// One aggregation of `chroma_aggregate`, e.g. `{"op": "max", "key": "year"}`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AggregateOp {
+    op: AggregateKind,
+    /// Metadata key aggregated; `count` without a key counts records
+    key: Option<String>,
+}
+
+impl AggregateOp {
+    /// Name of the result in each group, `count` or `op:key`
+    fn name(&self) -> String {
+        let op = match self.op {
+            AggregateKind::Count => "count",
+            AggregateKind::Min => "min",
+            AggregateKind::Max => "max",
+            AggregateKind::Distinct => "distinct",
+        };
+        match &self.key {
+            Some(key) => format!("{}:{}", op, key),
+            None => op.to_string(),
+        }
+    }
+
+    /// Applies the aggregation to the metadata of one group of records
+    fn apply(&self, metadatas: &[Option<&Metadata>]) -> Value {
+        let Some(key) = self.key.as_deref() else {
+            return json!(metadatas.len());
+        };
+        let values: Vec<&MetadataValue> = metadatas
+            .iter()
+            .filter_map(|metadata| metadata.and_then(|m| m.get(key)))
+            .collect();
+
+        let extreme = match self.op {
+            AggregateKind::Count => return json!(values.len()),
+            AggregateKind::Min => values.into_iter().min_by(|a, b| compare_values(a, b)),
+            AggregateKind::Max => values.into_iter().max_by(|a, b| compare_values(a, b)),
+            AggregateKind::Distinct => {
+                return distinct(values).into_iter().map(to_json).collect();
+            }
+        };
+        extreme.map_or(Value::Null, to_json)
+    }
+}
+
+/// Sorts values in `chroma_get` order and drops duplicates
+fn distinct(mut values: Vec<&MetadataValue>) -> Vec<&MetadataValue> {
+    values.sort_by(|a, b| compare_values(a, b));
+    values.dedup_by(|a, b| compare_values(a, b) == Ordering::Equal);
+    values
+}
+
+fn to_json(value: &MetadataValue) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Text of a value as listed by `chroma_distinct_metadata_values`
+fn value_text(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::Str(s) => s.clone(),
+        other => to_json(other).to_string(),
+    }
+}
+
+fn parse_ops(json: &str) -> Result<Vec<AggregateOp>, FfiError> {
+    let ops: Vec<AggregateOp> = serde_json::from_str(json).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid aggregation JSON",
+            Some(e.to_string()),
+        )
+    })?;
+    if let Some(op) = ops
+        .iter()
+        .find(|op| op.key.is_none() && op.op != AggregateKind::Count)
+    {
+        return Err(FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid aggregation JSON",
+            Some(format!("{} needs a key", op.name())),
+        ));
+    }
+    Ok(ops)
+}
+
+/// Reads the metadata of every visible record matching `where_filter`
+fn matching_metadatas(
+    collection: &ChromaCollection,
+    where_filter: Option<Where>,
+) -> Result<Vec<Option<Metadata>>, FfiError> {
+    let include_list = IncludeList::try_from(vec!["metadatas".to_string()]).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        None,
+        collection.visible(where_filter),
+        None,
+        0,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    collection
+        .client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map(|response| response.metadatas.unwrap_or_default())
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to read records", &e)
+        })
+}
+
+/// Groups records by their value of `group_by_key` and runs `ops` on each group
+fn aggregate(
+    metadatas: &[Option<Metadata>],
+    group_by_key: Option<&str>,
+    ops: &[AggregateOp],
+) -> Value {
+    // Groups are keyed by the JSON of their value, which tells ints and floats apart
+    let mut groups: HashMap<String, (Option<&MetadataValue>, Vec<Option<&Metadata>>)> =
+        HashMap::new();
+    for metadata in metadatas {
+        let value = group_by_key.and_then(|key| metadata.as_ref()?.get(key));
+        let group_key = value.map(to_json).unwrap_or(Value::Null).to_string();
+        groups
+            .entry(group_key)
+            .or_insert_with(|| (value, Vec::new()))
+            .1
+            .push(metadata.as_ref());
+    }
+
+    let mut groups: Vec<_> = groups.into_values().collect();
+    // Records without the key come first, then the groups in value order
+    groups.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => compare_values(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    });
+
+    let groups: Vec<Value> = groups
+        .into_iter()
+        .map(|(value, members)| {
+            let values: Map<String, Value> =
+                ops.iter().map(|op| (op.name(), op.apply(&members))).collect();
+            json!({
+                "group": value.map_or(Value::Null, to_json),
+                "values": values,
+            })
+        })
+        .collect();
+    json!({ "groups": groups })
+}
+
+/// Aggregates the metadata of the records matching a where filter, without returning them.
+///
+/// `agg_ops_json` is a JSON array of aggregations such as `{"op": "count"}`,
+/// `{"op": "min", "key": "year"}`, `{"op": "max", "key": "year"}` or
+/// `{"op": "distinct", "key": "author"}`; `count` with a key counts the records carrying it.
+/// Min, max and distinct order values as `chroma_get` sorting does. With a non-null
+/// `group_by_key` the records are grouped by their value of that key, records without it
+/// forming a group of their own.
+///
+/// `*out_json` receives `{"groups": [{"group": value, "values": {"count": 3, "max:year":
+/// 2024, ...}}]}` with one group whose `group` is null when not grouping; release it with
+/// `chroma_free_string`. The metadata of every matching record is read, so narrow large
+/// collections with `where_filter`.
+#[no_mangle]
+pub extern "C" fn chroma_aggregate(
+    collection_handle: ChromaHandle,
+    where_filter_json: *const c_char,
+    group_by_key: *const c_char,
+    agg_ops_json: *const c_char,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_aggregate", || {
+        let func_name = "chroma_aggregate";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || agg_ops_json.is_null()
+            || out_json.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if agg_ops_json.is_null() {
+                "Aggregation JSON pointer is null"
+            } else {
+                "Output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *out_json = ptr::null_mut();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let strings = unsafe {
+            (
+                c_str_to_string(agg_ops_json),
+                (!group_by_key.is_null()).then(|| c_str_to_string(group_by_key)).transpose(),
+            )
+        };
+        let (ops_json, group_by_key) = match strings {
+            (Ok(ops_json), Ok(group_by_key)) => (ops_json, group_by_key),
+            (Err(e), _) | (_, Err(e)) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert aggregation string",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let ops = match parse_ops(&ops_json) {
+            Ok(ops) => ops,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let where_filter = match unsafe { parse_where_ptrs(where_filter_json, ptr::null()) } {
+            Ok(where_filter) => where_filter,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let metadatas = match matching_metadatas(&collection, where_filter) {
+            Ok(metadatas) => metadatas,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let report = aggregate(&metadatas, group_by_key.as_deref(), &ops);
+        unsafe {
+            *out_json = string_to_c_str(report.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Lists the distinct values of a metadata key across a collection, e.g. to fill filter
+/// dropdowns without reading the records.
+///
+/// Values are ordered as `chroma_get` sorting orders them, and `limit` 0 returns them all.
+/// Strings are listed as is and other values as JSON, e.g. `2024` or `true`. `values_out`
+/// receives them and is released with `chroma_free_result_set`. Soft-deleted records are
+/// skipped.
+#[no_mangle]
+pub extern "C" fn chroma_distinct_metadata_values(
+    collection_handle: ChromaHandle,
+    key: *const c_char,
+    limit: c_uint,
+    values_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_distinct_metadata_values", || {
+        let func_name = "chroma_distinct_metadata_values";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || key.is_null() || values_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if key.is_null() {
+                "Key pointer is null"
+            } else {
+                "Values output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *values_out = ChromaResultSet::empty();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let key = match unsafe { c_str_to_string(key) } {
+            Ok(key) => key,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert key string",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let metadatas = match matching_metadatas(&collection, None) {
+            Ok(metadatas) => metadatas,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let mut values = distinct(
+            metadatas
+                .iter()
+                .filter_map(|metadata| metadata.as_ref()?.get(&key))
+                .collect(),
+        );
+        if limit > 0 {
+            values.truncate(limit as usize);
+        }
+
+        unsafe {
+            *values_out = ChromaResultSet::from_ids(values.into_iter().map(value_text).collect());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,486 @@
+// Persisted aliases naming collections, for swapping a rebuilt collection in atomically
+use chroma_sqlite::db::SqliteDb;
+use chroma_types::CollectionUuid;
+use libc::{c_char, c_int};
+use std::ptr;
+
+use crate::client::{identity_of, AccessScope, ChromaClient};
+use crate::collection::includes::BUILTIN_DEFAULT_INCLUDES;
+use crate::collection::read_only::read_only_enabled;
+use crate::collection::soft_delete::soft_delete_enabled;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, insert_collection, ChromaHandle,
+    CHROMA_INVALID_HANDLE,
+};
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+/// Table of the SQLite database holding the aliases, next to the sysdb tables
+const ALIAS_TABLE: &str = "collection_aliases";
+
+fn alias_error(message: &'static str) -> impl Fn(sqlx::Error) -> FfiError {
+    move |e| FfiError::new(ChromaErrorCode::InternalError, message, Some(e.to_string()))
+}
+
+fn alias_not_found(alias: &str, tenant: &str, database: &str) -> FfiError {
+    FfiError::new(
+        ChromaErrorCode::NotFound,
+        "Alias not found",
+        Some(format!(
+            "No alias '{}' in tenant '{}' and database '{}'",
+            alias, tenant, database
+        )),
+    )
+}
+
+/// Asks the authorizer whether an alias may be changed, as the backend calls do
+fn authorize_alias(
+    client: &ChromaClient,
+    operation: &str,
+    scope: &AccessScope,
+) -> Result<(), FfiError> {
+    client.authorize(operation, scope).map_err(|reason| {
+        FfiError::new(ChromaErrorCode::PermissionDenied, "Permission denied", Some(reason))
+    })
+}
+
+/// Parses a required alias name
+fn parse_alias(alias_ptr: *const c_char) -> Result<String, FfiError> {
+    let alias = unsafe { c_str_to_string(alias_ptr) }.map_err(|e| {
+        FfiError::new(ChromaErrorCode::InvalidArgument, "Invalid alias", Some(e.to_string()))
+    })?;
+    if alias.is_empty() {
+        return Err(FfiError::new(ChromaErrorCode::InvalidArgument, "Alias is empty", None));
+    }
+    Ok(alias)
+}
+
+/// Parses an optional tenant or database name, `default` when null
+fn parse_or_default(
+    ptr: *const c_char,
+    default: &str,
+    what: &'static str,
+) -> Result<String, FfiError> {
+    if ptr.is_null() {
+        return Ok(default.to_string());
+    }
+    unsafe { c_str_to_string(ptr) }
+        .map_err(|e| FfiError::new(ChromaErrorCode::InvalidArgument, what, Some(e.to_string())))
+}
+
+impl ChromaClient {
+    fn alias_db(&self) -> Result<&SqliteDb, FfiError> {
+        self.sqlite_db.as_ref().ok_or_else(|| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Aliases are not available",
+                Some("The SQLite database is not registered".to_string()),
+            )
+        })
+    }
+
+    /// ID of the collection `alias` names in `tenant` and `database`, if any
+    fn alias_target(
+        &self,
+        tenant: &str,
+        database: &str,
+        alias: &str,
+    ) -> Result<Option<String>, FfiError> {
+        let sqlite_db = self.alias_db()?;
+        self.runtime
+            .block_on(async {
+                // The table is created by the first alias
+                let exists =
+                    sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")
+                        .bind(ALIAS_TABLE)
+                        .fetch_optional(sqlite_db.get_conn())
+                        .await?;
+                if exists.is_none() {
+                    return Ok(None);
+                }
+                sqlx::query_scalar::<_, String>(&format!(
+                    "SELECT collection_id FROM {ALIAS_TABLE} \
+                     WHERE tenant = ? AND database = ? AND alias = ?"
+                ))
+                .bind(tenant)
+                .bind(database)
+                .bind(alias)
+                .fetch_optional(sqlite_db.get_conn())
+                .await
+            })
+            .map_err(alias_error("Failed to read aliases"))
+    }
+
+    /// Points `alias` at `collection` in its tenant and database. Unless `replace`, fails with
+    /// `ConflictError` if the alias exists; with it, fails with `NotFound` if it does not and
+    /// returns the ID it named before. The check and the write happen in one transaction.
+    fn write_alias(
+        &self,
+        collection: &ChromaCollection,
+        alias: &str,
+        replace: bool,
+    ) -> Result<Option<String>, FfiError> {
+        self.check_writable()?;
+        let operation = if replace { "swap_alias" } else { "create_alias" };
+        authorize_alias(self, operation, &collection.scope())?;
+        let sqlite_db = self.alias_db()?;
+        let (tenant, database) = (&*collection.tenant, &*collection.database);
+
+        let previous = self
+            .runtime
+            .block_on(async {
+                let mut transaction = sqlite_db.get_conn().begin().await?;
+                sqlx::query(&format!(
+                    "CREATE TABLE IF NOT EXISTS {ALIAS_TABLE} ( \
+                     tenant TEXT NOT NULL, \
+                     database TEXT NOT NULL, \
+                     alias TEXT NOT NULL, \
+                     collection_id TEXT NOT NULL, \
+                     PRIMARY KEY (tenant, database, alias))"
+                ))
+                .execute(&mut *transaction)
+                .await?;
+
+                let previous: Option<String> = sqlx::query_scalar(&format!(
+                    "SELECT collection_id FROM {ALIAS_TABLE} \
+                     WHERE tenant = ? AND database = ? AND alias = ?"
+                ))
+                .bind(tenant)
+                .bind(database)
+                .bind(alias)
+                .fetch_optional(&mut *transaction)
+                .await?;
+                if previous.is_some() != replace {
+                    return Ok(Err(previous));
+                }
+
+                sqlx::query(&format!(
+                    "INSERT OR REPLACE INTO {ALIAS_TABLE} \
+                     (tenant, database, alias, collection_id) VALUES (?, ?, ?, ?)"
+                ))
+                .bind(tenant)
+                .bind(database)
+                .bind(alias)
+                .bind(collection.id.to_string())
+                .execute(&mut *transaction)
+                .await?;
+                transaction.commit().await?;
+                Ok::<_, sqlx::Error>(Ok(previous))
+            })
+            .map_err(alias_error("Failed to write aliases"))?;
+
+        match previous {
+            Ok(previous) => Ok(previous),
+            Err(Some(existing)) => Err(FfiError::new(
+                ChromaErrorCode::ConflictError,
+                "Alias already exists",
+                Some(format!("Alias '{}' names collection {}", alias, existing)),
+            )),
+            Err(None) => Err(alias_not_found(alias, tenant, database)),
+        }
+    }
+
+    /// Removes `alias` from the tenant and database of `scope`; returns whether it existed
+    fn remove_alias(&self, scope: &AccessScope, alias: &str) -> Result<bool, FfiError> {
+        self.check_writable()?;
+        authorize_alias(self, "delete_alias", scope)?;
+        let (tenant, database) = (scope.tenant.as_str(), scope.database.as_str());
+        if self.alias_target(tenant, database, alias)?.is_none() {
+            return Ok(false);
+        }
+
+        let sqlite_db = self.alias_db()?;
+        let result = self
+            .runtime
+            .block_on(
+                sqlx::query(&format!(
+                    "DELETE FROM {ALIAS_TABLE} WHERE tenant = ? AND database = ? AND alias = ?"
+                ))
+                .bind(tenant)
+                .bind(database)
+                .bind(alias)
+                .execute(sqlite_db.get_conn()),
+            )
+            .map_err(alias_error("Failed to write aliases"))?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Creates `alias` naming the collection in its tenant and database, so applications can
+/// open it with `chroma_resolve_alias` under a stable name while it is rebuilt elsewhere.
+///
+/// Aliases are stored in the client's SQLite database and survive restarts. Fails with
+/// `ConflictError` if the alias exists; repoint it with `chroma_swap_alias`. An alias is not
+/// removed with its collection, resolving it then fails with `NotFound`.
+#[no_mangle]
+pub extern "C" fn chroma_create_alias(
+    collection_handle: ChromaHandle,
+    alias_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_create_alias", || {
+        let func_name = "chroma_create_alias";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || alias_ptr.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Alias pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let alias = match parse_alias(alias_ptr) {
+            Ok(alias) => alias,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match collection.client.write_alias(&collection, &alias, false) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Atomically repoints the existing `alias` in the collection's tenant and database to the
+/// collection, e.g. once a rebuilt collection is ready: every later `chroma_resolve_alias`
+/// opens the new collection, handles opened before keep using the old one.
+///
+/// When `previous_id_out` is not null it receives the ID of the collection the alias named
+/// before, released with `chroma_free_string`, e.g. to delete it. Fails with `NotFound` if
+/// the alias does not exist.
+#[no_mangle]
+pub extern "C" fn chroma_swap_alias(
+    collection_handle: ChromaHandle,
+    alias_ptr: *const c_char,
+    previous_id_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_swap_alias", || {
+        let func_name = "chroma_swap_alias";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || alias_ptr.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Alias pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let alias = match parse_alias(alias_ptr) {
+            Ok(alias) => alias,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match collection.client.write_alias(&collection, &alias, true) {
+            Ok(previous) => {
+                if let Some(out) = unsafe { previous_id_out.as_mut() } {
+                    *out = previous.map_or(ptr::null_mut(), string_to_c_str);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Opens the collection `alias` names in `tenant` and `database`, which default to the
+/// client's default tenant and database when null.
+///
+/// On success `collection_handle_out` receives a handle released with
+/// `chroma_destroy_collection`. Fails with `NotFound` if the alias does not exist or its
+/// collection was deleted.
+#[no_mangle]
+pub extern "C" fn chroma_resolve_alias(
+    client_handle: ChromaHandle,
+    alias_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    collection_handle_out: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_resolve_alias", || {
+        let func_name = "chroma_resolve_alias";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || alias_ptr.is_null()
+            || collection_handle_out.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if alias_ptr.is_null() {
+                "Alias pointer is null"
+            } else {
+                "Collection handle output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let target = (
+            parse_alias(alias_ptr),
+            parse_or_default(tenant_ptr, &default_tenant, "Invalid tenant name"),
+            parse_or_default(database_ptr, &default_database, "Invalid database name"),
+        );
+        let (alias, tenant, database) = match target {
+            (Ok(alias), Ok(tenant), Ok(database)) => (alias, tenant, database),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return e.report(error_out, func_name)
+            }
+        };
+
+        let id = match client.alias_target(&tenant, &database, &alias) {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                return alias_not_found(&alias, &tenant, &database).report(error_out, func_name)
+            }
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let collection_id = match uuid::Uuid::parse_str(&id) {
+            Ok(id) => CollectionUuid(id),
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidUuid,
+                    "Invalid collection UUID",
+                    func_name,
+                    Some(&format!("UUID parse error: {}", e)),
+                );
+                return ChromaErrorCode::InvalidUuid as c_int;
+            }
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&id));
+        match client.block_on_with_retry("get_collection", scope, |mut frontend| async move {
+            frontend.get_cached_collection(collection_id).await
+        }) {
+            Ok(collection) => {
+                let handle = insert_collection(ChromaCollection {
+                    client: client.clone(),
+                    id: collection.collection_id,
+                    tenant: tenant.into(),
+                    database: database.into(),
+                    dimension: collection.dimension.map_or(0, |d| d as usize).into(),
+                    soft_delete: soft_delete_enabled(collection.metadata.as_ref()).into(),
+                    read_only: read_only_enabled(collection.metadata.as_ref()).into(),
+                    default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+                    principal: identity_of(client_handle),
+                });
+
+                unsafe {
+                    *collection_handle_out = handle;
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::NotFound, "Aliased collection not found", &e)
+                    .report(error_out, func_name)
+            }
+        }
+    })
+}
+
+/// Removes `alias` from `tenant` and `database`, which default to the client's default
+/// tenant and database when null. The collection it names is left untouched. Fails with
+/// `NotFound` if the alias does not exist.
+#[no_mangle]
+pub extern "C" fn chroma_delete_alias(
+    client_handle: ChromaHandle,
+    alias_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete_alias", || {
+        let func_name = "chroma_delete_alias";
+
+        if client_handle == CHROMA_INVALID_HANDLE || alias_ptr.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Alias pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let target = (
+            parse_alias(alias_ptr),
+            parse_or_default(tenant_ptr, &default_tenant, "Invalid tenant name"),
+            parse_or_default(database_ptr, &default_database, "Invalid database name"),
+        );
+        let (alias, tenant, database) = match target {
+            (Ok(alias), Ok(tenant), Ok(database)) => (alias, tenant, database),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return e.report(error_out, func_name)
+            }
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, None);
+        match client.remove_alias(&scope, &alias) {
+            Ok(true) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Ok(false) => alias_not_found(&alias, &tenant, &database).report(error_out, func_name),
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,151 @@
+// Typed embedding batch entry points for ChromaDB C# bindings
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+
+use crate::collection::conflict::ChromaAddStats;
+use crate::collection::operations::{chroma_add, chroma_query};
+use crate::error::{ffi_boundary, set_error, ChromaError, ChromaErrorCode};
+use crate::handles::ChromaHandle;
+use crate::types::{
+    embedding_batch_to_vecs, ChromaEmbeddingBatch, ChromaQueryResult, ChromaResultSet,
+};
+
+/// Adds documents whose embeddings are given as an f32, f16 or f64 batch.
+///
+/// The batch must hold exactly `ids_count` embeddings. All other arguments
+/// behave as in `chroma_add`, including `generated_ids_out`, which receives
+/// the IDs generated for null `ids`.
+#[no_mangle]
+pub extern "C" fn chroma_add_batch(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const ChromaEmbeddingBatch,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+    on_conflict: c_int,
+    stats_out: *mut ChromaAddStats,
+    generated_ids_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_add_batch", || {
+        let func_name = "chroma_add_batch";
+
+        let Some(batch) = (unsafe { embeddings.as_ref() }) else {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Embedding batch pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        };
+
+        if batch.count != ids_count {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Embedding count does not match IDs count",
+                func_name,
+                Some(&format!("Got {} embeddings for {} IDs", batch.count, ids_count)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let rows = match unsafe { embedding_batch_to_vecs(batch) } {
+            Ok(rows) => rows,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let row_ptrs: Vec<*const c_float> = rows.iter().map(|row| row.as_ptr()).collect();
+
+        chroma_add(
+            collection_handle,
+            ids,
+            ids_count,
+            row_ptrs.as_ptr(),
+            batch.dimension,
+            metadatas_json,
+            documents,
+            on_conflict,
+            stats_out,
+            generated_ids_out,
+            error_out,
+        )
+    })
+}
+
+/// Queries a collection with a single embedding given as an f32, f16 or f64 batch.
+///
+/// The batch must hold exactly one embedding. All other arguments behave as
+/// in `chroma_query`.
+#[no_mangle]
+pub extern "C" fn chroma_query_batch(
+    collection_handle: ChromaHandle,
+    query_embedding: *const ChromaEmbeddingBatch,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    allowed_ids: *const *const c_char,
+    allowed_ids_count: size_t,
+    include_embeddings: bool,
+    include_metadatas: bool,
+    include_documents: bool,
+    include_distances: bool,
+    metadata_keys: *const *const c_char,
+    metadata_keys_count: size_t,
+    metadata_format: c_int,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_batch", || {
+        let func_name = "chroma_query_batch";
+
+        let Some(batch) = (unsafe { query_embedding.as_ref() }) else {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Query embedding batch pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        };
+
+        if batch.count != 1 {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Query embedding batch must hold exactly one embedding",
+                func_name,
+                Some(&format!("Got {} embeddings", batch.count)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let rows = match unsafe { embedding_batch_to_vecs(batch) } {
+            Ok(rows) => rows,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        chroma_query(
+            collection_handle,
+            rows[0].as_ptr(),
+            batch.dimension,
+            n_results,
+            where_filter_json,
+            where_document_filter,
+            allowed_ids,
+            allowed_ids_count,
+            include_embeddings,
+            include_metadatas,
+            include_documents,
+            include_distances,
+            metadata_keys,
+            metadata_keys_count,
+            metadata_format,
+            std::ptr::null(),
+            result,
+            error_out,
+        )
+    })
+}
@@ -0,0 +1,345 @@
+// Micro-benchmarks run inside the library on synthetic data, without FFI marshaling
+use chroma_types::{AddCollectionRecordsRequest, IncludeList, QueryRequest};
+use libc::{c_char, c_int, c_uint, size_t};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// Records added per call when the caller passes a batch size of 0
+const DEFAULT_BENCH_BATCH_SIZE: u32 = 100;
+
+/// SplitMix64: a small, fast generator whose output depends only on the seed, so the same
+/// seed yields the same data on every platform and version of the library
+pub(crate) struct SeededRng(u64);
+
+impl SeededRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        SeededRng(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A vector of `dimension` components uniform in [-1, 1)
+    pub(crate) fn vector(&mut self, dimension: usize) -> Vec<f32> {
+        (0..dimension)
+            .map(|_| (self.next_f64() * 2.0 - 1.0) as f32)
+            .collect()
+    }
+}
+
+/// Summarizes call latencies as the min, mean, percentiles and max in milliseconds
+fn latency_json(latencies: &mut [Duration]) -> Value {
+    latencies.sort_unstable();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let percentile = |p: f64| {
+        if latencies.is_empty() {
+            return 0.0;
+        }
+        ms(latencies[((latencies.len() - 1) as f64 * p).round() as usize])
+    };
+    let total: Duration = latencies.iter().sum();
+    let mean = if latencies.is_empty() {
+        0.0
+    } else {
+        ms(total) / latencies.len() as f64
+    };
+
+    json!({
+        "min_ms": latencies.first().map_or(0.0, |d| ms(*d)),
+        "mean_ms": mean,
+        "p50_ms": percentile(0.50),
+        "p95_ms": percentile(0.95),
+        "p99_ms": percentile(0.99),
+        "max_ms": latencies.last().map_or(0.0, |d| ms(*d)),
+    })
+}
+
+/// Per-second rate of `count` operations taking `elapsed` in total
+fn per_second(count: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    count as f64 / elapsed.as_secs_f64()
+}
+
+/// Adds `count` synthetic records in batches, timing each backend call
+fn bench_add(
+    collection: &ChromaCollection,
+    count: u32,
+    dimension: usize,
+    batch_size: u32,
+    seed: u64,
+) -> Result<Value, FfiError> {
+    let client = &collection.client;
+    collection.check_writable()?;
+    collection.check_dimension(dimension)?;
+
+    // IDs are unique per run so repeated runs on one collection keep adding records
+    let run = Uuid::now_v7().simple().to_string();
+    let mut rng = SeededRng::new(seed);
+    let mut latencies = Vec::new();
+    let mut generate = Duration::ZERO;
+    let started = Instant::now();
+
+    let mut next = 0u32;
+    while next < count {
+        let size = batch_size.min(count - next);
+        let generating = Instant::now();
+        let ids: Vec<String> = (next..next + size)
+            .map(|i| format!("bench-{}-{}", run, i))
+            .collect();
+        let embeddings: Vec<Vec<f32>> = (0..size).map(|_| rng.vector(dimension)).collect();
+        let request = AddCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            ids,
+            embeddings,
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create add request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+        generate += generating.elapsed();
+
+        let call = Instant::now();
+        client
+            .block_on_with_retry(
+                "add",
+                collection
+                    .scope()
+                    .with_records(Some(size as usize))
+                    .with_dimension(Some(dimension)),
+                |mut frontend| {
+                    let request = request.clone();
+                    async move { frontend.add(request).await }
+                },
+            )
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to add documents", &e)
+            })?;
+        latencies.push(call.elapsed());
+        collection.record_dimension(dimension);
+        client.metrics.add_records(size as usize);
+        next += size;
+    }
+
+    let total = started.elapsed();
+    let backend: Duration = latencies.iter().sum();
+    Ok(json!({
+        "operation": "add",
+        "records": count,
+        "dimension": dimension,
+        "batch_size": batch_size,
+        "batches": latencies.len(),
+        "total_ms": total.as_secs_f64() * 1000.0,
+        "generate_ms": generate.as_secs_f64() * 1000.0,
+        "backend_ms": backend.as_secs_f64() * 1000.0,
+        "records_per_second": per_second(count as usize, backend),
+        "latency": latency_json(&mut latencies),
+    }))
+}
+
+/// Runs `queries` synthetic nearest-neighbor queries, timing each backend call
+fn bench_query(
+    collection: &ChromaCollection,
+    queries: u32,
+    dimension: usize,
+    n_results: u32,
+    seed: u64,
+) -> Result<Value, FfiError> {
+    let client = &collection.client;
+    collection.check_dimension(dimension)?;
+
+    let include = ["metadatas", "documents", "distances"].map(String::from).to_vec();
+    let include_list = IncludeList::try_from(include).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    client.read_barrier(collection.id)?;
+    let mut rng = SeededRng::new(seed);
+    let mut latencies = Vec::with_capacity(queries as usize);
+    let mut hits = 0;
+    let started = Instant::now();
+    for _ in 0..queries {
+        let request = QueryRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            None,
+            collection.visible(None),
+            vec![rng.vector(dimension)],
+            n_results,
+            include_list.clone(),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create query request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        let call = Instant::now();
+        let response = client
+            .block_on_with_retry("query", collection.scope(), |mut frontend| {
+                let request = request.clone();
+                async move { frontend.query(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+            })?;
+        latencies.push(call.elapsed());
+        hits += response.ids.first().map_or(0, Vec::len);
+    }
+
+    let total = started.elapsed();
+    let backend: Duration = latencies.iter().sum();
+    Ok(json!({
+        "operation": "query",
+        "queries": queries,
+        "dimension": dimension,
+        "n_results": n_results,
+        "hits": hits,
+        "total_ms": total.as_secs_f64() * 1000.0,
+        "backend_ms": backend.as_secs_f64() * 1000.0,
+        "queries_per_second": per_second(queries as usize, backend),
+        "latency": latency_json(&mut latencies),
+    }))
+}
+
+/// Checks the arguments shared by the benchmarks
+fn check_bench_arguments(
+    collection_handle: ChromaHandle,
+    dimension: size_t,
+    report_json_out: *mut *mut c_char,
+) -> Result<(), FfiError> {
+    let message = if collection_handle == CHROMA_INVALID_HANDLE {
+        "Collection handle is null"
+    } else if dimension == 0 {
+        "Embedding dimension is zero"
+    } else if report_json_out.is_null() {
+        "Report output pointer is null"
+    } else {
+        return Ok(());
+    };
+    Err(FfiError::new(ChromaErrorCode::InvalidArgument, message, None))
+}
+
+/// Benchmarks adds: generates `count` records with random embeddings of `dimension`
+/// components from `seed` inside the library and adds them in batches of `batch_size`
+/// (0 for 100), timing each backend call.
+///
+/// Nothing crosses the FFI boundary per record, so comparing the report to adds made from
+/// the host separates marshaling cost from backend cost. The records are really added, with
+/// IDs `bench-<run>-<n>`; run it on a scratch collection. The report is a JSON object with
+/// `records`, `batches`, `total_ms`, `generate_ms`, `backend_ms`, `records_per_second` and
+/// the per-batch `latency` (`min_ms`, `mean_ms`, `p50_ms`, `p95_ms`, `p99_ms`, `max_ms`),
+/// allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_bench_add(
+    collection_handle: ChromaHandle,
+    count: c_uint,
+    dimension: size_t,
+    batch_size: c_uint,
+    seed: u64,
+    report_json_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_bench_add", || {
+        let func_name = "chroma_bench_add";
+
+        if let Err(e) = check_bench_arguments(collection_handle, dimension, report_json_out) {
+            return e.report(error_out, func_name);
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let batch_size = if batch_size == 0 {
+            DEFAULT_BENCH_BATCH_SIZE
+        } else {
+            batch_size
+        };
+        match bench_add(&collection, count, dimension, batch_size, seed) {
+            Ok(report) => {
+                unsafe {
+                    *report_json_out = string_to_c_str(report.to_string());
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Benchmarks queries: runs `queries` queries for `n_results` hits with random query
+/// embeddings of `dimension` components generated from `seed` inside the library, reading
+/// metadatas, documents and distances as `chroma_query` does by default.
+///
+/// The report is a JSON object with `queries`, `hits`, `total_ms`, `backend_ms`,
+/// `queries_per_second` and the per-query `latency`, as for `chroma_bench_add`; release it
+/// with `chroma_free_string`. Nothing is written to the collection.
+#[no_mangle]
+pub extern "C" fn chroma_bench_query(
+    collection_handle: ChromaHandle,
+    queries: c_uint,
+    dimension: size_t,
+    n_results: c_uint,
+    seed: u64,
+    report_json_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_bench_query", || {
+        let func_name = "chroma_bench_query";
+
+        if let Err(e) = check_bench_arguments(collection_handle, dimension, report_json_out) {
+            return e.report(error_out, func_name);
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match bench_query(&collection, queries, dimension, n_results, seed) {
+            Ok(report) => {
+                unsafe {
+                    *report_json_out = string_to_c_str(report.to_string());
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,495 @@
+// Binary attachments stored as files under the persist path and referenced by record URIs
+use chroma_types::{GetRequest, IncludeList, UpdateCollectionRecordsRequest};
+use libc::{c_char, c_int, size_t};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::client::ChromaClient;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::serialization::write_buffer;
+use crate::types::ChromaResultSet;
+use crate::utils::{c_array_to_vec_string, c_str_to_string};
+
+/// Scheme of the URIs recorded for stored blobs
+const BLOB_URI_PREFIX: &str = "chroma-blob://";
+/// Directory under the persist path holding one directory of blobs per collection
+const BLOB_DIR: &str = "blobs";
+
+/// A binary attachment passed by the caller
+#[repr(C)]
+pub struct ChromaBlob {
+    /// Blob bytes; null with `len` 0 to leave the record's attachment unchanged
+    pub data: *const u8,
+    /// Number of bytes in `data`
+    pub len: size_t,
+}
+
+fn blob_root(client: &ChromaClient) -> Result<PathBuf, FfiError> {
+    match client.persist_path.as_deref() {
+        Some(persist_path) => Ok(PathBuf::from(persist_path).join(BLOB_DIR)),
+        None => Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Blob storage requires a client with a persist path",
+            None,
+        )),
+    }
+}
+
+/// Maps a blob URI to its file, accepting only URIs made by `chroma_put_blobs` so a URI
+/// cannot point outside the blob directory
+fn blob_path(client: &ChromaClient, uri: &str) -> Result<PathBuf, FfiError> {
+    let parsed = uri
+        .strip_prefix(BLOB_URI_PREFIX)
+        .and_then(|rest| rest.split_once('/'))
+        .and_then(|(collection, blob)| {
+            Some((Uuid::parse_str(collection).ok()?, Uuid::parse_str(blob).ok()?))
+        });
+    let Some((collection, blob)) = parsed else {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Not a blob URI",
+            Some(format!("Expected {}<collection>/<blob>, got {}", BLOB_URI_PREFIX, uri)),
+        ));
+    };
+
+    Ok(blob_root(client)?
+        .join(collection.to_string())
+        .join(blob.to_string()))
+}
+
+/// Reads the URIs of the given records that exist
+fn record_uris(
+    collection: &ChromaCollection,
+    ids: Vec<String>,
+) -> Result<HashMap<String, Option<String>>, FfiError> {
+    let include_list = IncludeList::try_from(vec!["uris".to_string()]).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        Some(ids),
+        None,
+        None,
+        0,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    let response = collection
+        .client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to read record URIs", &e)
+        })?;
+
+    let mut uris = response.uris.unwrap_or_default().into_iter();
+    Ok(response
+        .ids
+        .into_iter()
+        .map(|id| (id, uris.next().flatten()))
+        .collect())
+}
+
+/// Stores binary attachments for existing records, one per ID.
+///
+/// Each blob is written to a file under `<persist path>/blobs/<collection id>/` and its
+/// `chroma-blob://` URI is recorded as the record's URI, replacing the record's previous
+/// blob, whose file is removed. IDs that do not exist are skipped. Requires a client with a
+/// persist path. Records deleted later keep their files until the collection's blob
+/// directory is removed.
+///
+/// `uris_out`, when not null, receives one URI per ID, empty for skipped IDs and blobs;
+/// release it with `chroma_free_result_set`.
+#[no_mangle]
+pub extern "C" fn chroma_put_blobs(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    blobs: *const ChromaBlob,
+    uris_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_put_blobs", || {
+        let func_name = "chroma_put_blobs";
+
+        if let Some(uris_out) = unsafe { uris_out.as_mut() } {
+            *uris_out = ChromaResultSet::empty();
+        }
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || (ids_count > 0 && (ids.is_null() || blobs.is_null()))
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs array is null"
+            } else {
+                "Blobs array is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+        let directory = match blob_root(&collection.client) {
+            Ok(root) => root.join(collection.id.to_string()),
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if ids_count == 0 {
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
+
+        let ids_vec = match unsafe { c_array_to_vec_string(ids, ids_count) } {
+            Ok(v) => v,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert IDs array",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let blobs = unsafe { std::slice::from_raw_parts(blobs, ids_count) };
+        if let Some(i) = blobs.iter().position(|b| b.data.is_null() && b.len > 0) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Blob data pointer is null",
+                func_name,
+                Some(&format!("Null blob at index {}", i)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let previous = match record_uris(&collection, ids_vec.clone()) {
+            Ok(uris) => uris,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if let Err(e) = fs::create_dir_all(&directory) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InternalError,
+                "Failed to create blob directory",
+                func_name,
+                Some(&format!("{}: {}", directory.display(), e)),
+            );
+            return ChromaErrorCode::InternalError as c_int;
+        }
+
+        // Write every file before touching the records, and remove them if anything fails
+        let mut written: Vec<PathBuf> = Vec::new();
+        let mut update_ids = Vec::new();
+        let mut update_uris = Vec::new();
+        let mut uris = Vec::with_capacity(ids_count);
+        for (id, blob) in ids_vec.iter().zip(blobs) {
+            if blob.data.is_null() || !previous.contains_key(id) {
+                uris.push(String::new());
+                continue;
+            }
+
+            let name = Uuid::new_v4();
+            let path = directory.join(name.to_string());
+            let bytes = unsafe { std::slice::from_raw_parts(blob.data, blob.len) };
+            if let Err(e) = fs::write(&path, bytes) {
+                for path in &written {
+                    let _ = fs::remove_file(path);
+                }
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InternalError,
+                    "Failed to write blob",
+                    func_name,
+                    Some(&format!("{}: {}", path.display(), e)),
+                );
+                return ChromaErrorCode::InternalError as c_int;
+            }
+            written.push(path);
+
+            let uri = format!("{}{}/{}", BLOB_URI_PREFIX, collection.id, name);
+            update_ids.push(id.clone());
+            update_uris.push(Some(uri.clone()));
+            uris.push(uri);
+        }
+
+        if !update_ids.is_empty() {
+            let result = UpdateCollectionRecordsRequest::try_new(
+                collection.tenant.to_string(),
+                collection.database.to_string(),
+                collection.id,
+                update_ids.clone(),
+                None,
+                None,
+                Some(update_uris),
+                None,
+            )
+            .map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update request",
+                    Some(format!("Validation error: {:?}", e)),
+                )
+            })
+            .and_then(|request| {
+                collection
+                    .client
+                    .block_on_with_retry(
+                        "update",
+                        collection.scope().with_records(Some(request.ids.len())),
+                        |mut frontend| {
+                            let request = request.clone();
+                            async move { frontend.update(request).await }
+                        },
+                    )
+                    .map_err(|e| {
+                        FfiError::backend(
+                            ChromaErrorCode::InternalError,
+                            "Failed to record blob URIs",
+                            &e,
+                        )
+                    })
+            });
+
+            if let Err(e) = result {
+                for path in &written {
+                    let _ = fs::remove_file(path);
+                }
+                return e.report(error_out, func_name);
+            }
+        }
+
+        // Replaced blobs are no longer referenced; failing to remove one only wastes space
+        for id in &update_ids {
+            let previous = previous.get(id).and_then(Option::as_deref);
+            if let Some(path) = previous.and_then(|u| blob_path(&collection.client, u).ok()) {
+                let _ = fs::remove_file(path);
+            }
+        }
+
+        if let Some(uris_out) = unsafe { uris_out.as_mut() } {
+            *uris_out = ChromaResultSet::from_ids(uris);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Reads the URIs of records, such as those recorded by `chroma_put_blobs`.
+///
+/// `uris_out` receives one URI per ID, empty for missing records and records without a
+/// URI; release it with `chroma_free_result_set`.
+#[no_mangle]
+pub extern "C" fn chroma_get_uris(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    uris_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_uris", || {
+        let func_name = "chroma_get_uris";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || uris_out.is_null()
+            || (ids.is_null() && ids_count > 0)
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if uris_out.is_null() {
+                "URIs output pointer is null"
+            } else {
+                "IDs array is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *uris_out = ChromaResultSet::empty();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if ids_count == 0 {
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
+
+        let ids_vec = match unsafe { c_array_to_vec_string(ids, ids_count) } {
+            Ok(v) => v,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert IDs array",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        // The backend rejects duplicate IDs, which callers may legitimately pass here
+        let mut unique = ids_vec.clone();
+        unique.sort();
+        unique.dedup();
+        let found = match record_uris(&collection, unique) {
+            Ok(uris) => uris,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let uris = ids_vec
+            .iter()
+            .map(|id| found.get(id).cloned().flatten().unwrap_or_default())
+            .collect();
+        unsafe {
+            *uris_out = ChromaResultSet::from_ids(uris);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Reads a blob stored by `chroma_put_blobs` into a buffer owned by the caller, released
+/// with `chroma_free_buffer`
+#[no_mangle]
+pub extern "C" fn chroma_get_blob(
+    client_handle: ChromaHandle,
+    uri_ptr: *const c_char,
+    out_buffer: *mut *mut u8,
+    out_len: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_blob", || {
+        let func_name = "chroma_get_blob";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || uri_ptr.is_null()
+            || out_buffer.is_null()
+            || out_len.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if uri_ptr.is_null() {
+                "URI pointer is null"
+            } else if out_buffer.is_null() {
+                "Output buffer pointer is null"
+            } else {
+                "Output length pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let uri = match unsafe { c_str_to_string(uri_ptr) } {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid URI",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let path = match blob_path(&client, &uri) {
+            Ok(path) => path,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let code = if e.kind() == std::io::ErrorKind::NotFound {
+                    ChromaErrorCode::NotFound
+                } else {
+                    ChromaErrorCode::InternalError
+                };
+                set_error(
+                    error_out,
+                    code,
+                    "Failed to read blob",
+                    func_name,
+                    Some(&format!("{}: {}", uri, e)),
+                );
+                return code as c_int;
+            }
+        };
+
+        if let Err(e) = unsafe { write_buffer(bytes, out_buffer, out_len) } {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,360 @@
+// Chunked bulk deletes with progress reporting
+use chroma_types::DeleteCollectionRecordsRequest;
+use libc::{c_char, c_int, c_void, size_t};
+
+use crate::callbacks::{
+    find_callback, register_callback, unregister_callback, Callback, Registration,
+};
+use crate::client::start_operation;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::ChromaResultSet;
+use crate::utils::c_array_to_vec_string;
+
+/// Batch size used by `chroma_delete_batched` when none is given
+const DEFAULT_DELETE_BATCH_SIZE: usize = 1000;
+
+/// Called after every batch with the number of IDs processed so far, the total number of
+/// IDs and the caller's `user_data`. Returning false cancels the remaining batches.
+pub type ChromaProgressCallback =
+    Option<extern "C" fn(processed: size_t, total: size_t, user_data: *mut c_void) -> bool>;
+
+/// A progress callback and the caller's `user_data`, registered for the length of one call
+pub(crate) struct ProgressCallback {
+    callback: extern "C" fn(processed: size_t, total: size_t, user_data: *mut c_void) -> bool,
+    user_data: *mut c_void,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// calling thread; keeping it valid for the call is up to the caller
+unsafe impl Send for ProgressCallback {}
+unsafe impl Sync for ProgressCallback {}
+
+impl Callback {
+    fn as_progress(&self) -> Option<&ProgressCallback> {
+        match self {
+            Callback::Progress(progress) => Some(progress),
+            _ => None,
+        }
+    }
+}
+
+/// Reports `processed` of `total` to the registered progress callback; true to go on
+fn report_progress(registration: Option<&Registration>, processed: usize, total: usize) -> bool {
+    let mut go_on = true;
+    if let Some(registration) = registration {
+        registration.invoke(|callback| {
+            if let Some(progress) = callback.as_progress() {
+                go_on = (progress.callback)(processed, total, progress.user_data);
+            }
+        });
+    }
+    go_on
+}
+
+/// Outcome of `chroma_delete_batched`, filled in by the library in a caller-owned struct
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChromaBatchStats {
+    /// IDs in batches the backend accepted
+    pub succeeded: size_t,
+    /// IDs in batches that failed
+    pub failed: size_t,
+    /// Batches sent to the backend
+    pub batches: size_t,
+    /// Batches that failed
+    pub failed_batches: size_t,
+    /// Whether the progress callback cancelled the remaining batches
+    pub cancelled: bool,
+}
+
+fn delete_batch(collection: &ChromaCollection, ids: &[String]) -> Result<(), FfiError> {
+    let request = DeleteCollectionRecordsRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        Some(ids.to_vec()),
+        None,
+        None,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create delete request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection
+        .client
+        .block_on_with_retry(
+            "delete",
+            collection.scope().with_records(Some(ids.len())),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.delete(request, String::new()).await }
+            },
+        )
+        .map(|_| ())
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to delete documents", &e)
+        })
+}
+
+/// Deletes `ids` in batches of `batch_size`, calling `progress` with the number of IDs
+/// processed after every batch; it returns false to cancel the remaining batches. Returns
+/// the counts, the IDs of the failed batches and the error of the first one
+fn delete_in_batches(
+    collection: &ChromaCollection,
+    ids: &[String],
+    batch_size: usize,
+    mut progress: impl FnMut(usize) -> bool,
+) -> (ChromaBatchStats, Vec<String>, Option<FfiError>) {
+    let mut stats = ChromaBatchStats::default();
+    let mut failed_ids = Vec::new();
+    let mut first_error = None;
+    for batch in ids.chunks(batch_size) {
+        stats.batches += 1;
+        match delete_batch(collection, batch) {
+            Ok(()) => stats.succeeded += batch.len(),
+            Err(e) => {
+                stats.failed += batch.len();
+                stats.failed_batches += 1;
+                failed_ids.extend_from_slice(batch);
+                first_error.get_or_insert(e);
+            }
+        }
+
+        let processed = stats.succeeded + stats.failed;
+        if !progress(processed) && processed < ids.len() {
+            stats.cancelled = true;
+            break;
+        }
+    }
+    (stats, failed_ids, first_error)
+}
+
+/// Error reported when some batches failed, wrapping the error of the first one
+fn batches_failed(mut error: FfiError, stats: &ChromaBatchStats) -> FfiError {
+    let first = match &error.details {
+        Some(details) => format!("{}: {}", error.message, details),
+        None => error.message.to_string(),
+    };
+    error.message = "Some delete batches failed";
+    error.details = Some(format!(
+        "{} of {} batches ({} IDs) failed; first error: {}",
+        stats.failed_batches, stats.batches, stats.failed, first
+    ));
+    error
+}
+
+/// Deletes records by ID in batches of `batch_size` (0 for the default of 1000).
+///
+/// Batches run one after the other; a failed batch does not stop the following ones.
+/// `progress_cb`, when not null, is called on the calling thread after every batch and
+/// may cancel the rest. `stats_out`, when not null, receives the counts, and
+/// `failed_ids_out`, when not null, the IDs of the failed batches; release them with
+/// `chroma_free_result_set`.
+///
+/// If any batch failed the call returns the error of the first failed batch, with the
+/// number of failures in its details; the outputs are filled in either way.
+#[no_mangle]
+pub extern "C" fn chroma_delete_batched(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    batch_size: size_t,
+    progress_cb: ChromaProgressCallback,
+    user_data: *mut c_void,
+    stats_out: *mut ChromaBatchStats,
+    failed_ids_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete_batched", || {
+        let func_name = "chroma_delete_batched";
+
+        if let Some(stats) = unsafe { stats_out.as_mut() } {
+            *stats = ChromaBatchStats::default();
+        }
+        if let Some(failed_ids) = unsafe { failed_ids_out.as_mut() } {
+            *failed_ids = ChromaResultSet::empty();
+        }
+
+        if collection_handle == CHROMA_INVALID_HANDLE || (ids.is_null() && ids_count > 0) {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "IDs array is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let ids_vec = if ids_count > 0 {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => v,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let batch_size = if batch_size > 0 {
+            batch_size
+        } else {
+            DEFAULT_DELETE_BATCH_SIZE
+        };
+        let progress_id = progress_cb.map(|callback| {
+            register_callback(Callback::Progress(ProgressCallback {
+                callback,
+                user_data,
+            }))
+        });
+        let registration = progress_id.and_then(find_callback);
+        let (stats, failed_ids, first_error) =
+            delete_in_batches(&collection, &ids_vec, batch_size, |processed| {
+                report_progress(registration.as_deref(), processed, ids_vec.len())
+            });
+        if let Some(id) = progress_id {
+            unregister_callback(id);
+        }
+
+        if let Some(stats_out) = unsafe { stats_out.as_mut() } {
+            *stats_out = stats;
+        }
+        if let Some(failed_ids_out) = unsafe { failed_ids_out.as_mut() } {
+            *failed_ids_out = ChromaResultSet::from_ids(failed_ids);
+        }
+
+        match first_error {
+            None => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Some(error) => batches_failed(error, &stats).report(error_out, func_name),
+        }
+    })
+}
+
+/// Starts deleting records by ID in batches of `batch_size` (0 for the default of 1000) on a
+/// background thread and returns at once, for hosts that cannot take progress callbacks.
+///
+/// `operation_out` receives an operation handle whose progress counts IDs: poll it with
+/// `chroma_operation_status`, wait for it with `chroma_operation_wait`, stop it between
+/// batches with `chroma_operation_cancel` and release it with `chroma_destroy_operation`.
+/// As with `chroma_delete_batched`, a failed batch does not stop the following ones; the
+/// operation then fails with the error of the first failed batch.
+#[no_mangle]
+pub extern "C" fn chroma_delete_batched_async(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    batch_size: size_t,
+    operation_out: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete_batched_async", || {
+        let func_name = "chroma_delete_batched_async";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || (ids.is_null() && ids_count > 0)
+            || operation_out.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() && ids_count > 0 {
+                "IDs array is null"
+            } else {
+                "Operation handle output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let ids_vec = if ids_count > 0 {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => v,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let batch_size = if batch_size > 0 {
+            batch_size
+        } else {
+            DEFAULT_DELETE_BATCH_SIZE
+        };
+        let client = collection.client.clone();
+        let started = start_operation(client, "delete-batched", ids_vec.len(), move |operation| {
+            let mut reported = 0;
+            let (stats, _, first_error) =
+                delete_in_batches(&collection, &ids_vec, batch_size, |processed| {
+                    operation.advance(processed - reported);
+                    reported = processed;
+                    !operation.is_cancelled()
+                });
+            first_error.map_or(Ok(()), |error| Err(batches_failed(error, &stats)))
+        });
+
+        match started {
+            Ok(handle) => {
+                unsafe {
+                    *operation_out = handle;
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
@@ -0,0 +1,209 @@
+// Change feed over the write log of a collection
+use libc::{c_char, c_int, c_uint};
+use serde_json::{json, Map, Value};
+use sqlx::Row;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::string_to_c_str;
+
+/// Metadata key under which the log stores the document of a record
+pub(crate) const DOCUMENT_KEY: &str = "chroma:document";
+
+/// Name of an operation code of the write log
+fn operation_name(code: i64) -> &'static str {
+    match code {
+        0 => "add",
+        1 => "update",
+        2 => "upsert",
+        3 => "delete",
+        _ => "unknown",
+    }
+}
+
+/// Decodes an embedding stored in the write log
+fn decode_vector(vector: Option<Vec<u8>>, encoding: Option<&str>) -> Value {
+    match (vector, encoding) {
+        (Some(bytes), Some("FLOAT32")) => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<f32>>()
+            .into(),
+        _ => Value::Null,
+    }
+}
+
+/// Splits the metadata of a log entry into the document and the remaining metadata
+fn split_metadata(metadata: Option<&str>) -> (Value, Value) {
+    let Some(Value::Object(mut metadata)) = metadata.and_then(|m| serde_json::from_str(m).ok())
+    else {
+        return (Value::Null, Value::Null);
+    };
+    let document = metadata.remove(DOCUMENT_KEY).unwrap_or(Value::Null);
+    let metadata = if metadata.is_empty() {
+        Value::Null
+    } else {
+        Value::Object(metadata)
+    };
+    (document, metadata)
+}
+
+/// Reads the log entries of a collection with a sequence number above `sequence`
+fn changes_since(
+    collection: &ChromaCollection,
+    sequence: u64,
+    limit: u32,
+) -> Result<Value, FfiError> {
+    let client = &collection.client;
+    let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+        return Err(FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Write log is not available",
+            Some("The SQLite database is not registered".to_string()),
+        ));
+    };
+    let read_error = |e: sqlx::Error| {
+        FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Failed to read the write log",
+            Some(e.to_string()),
+        )
+    };
+
+    let topic = collection.id.to_string();
+    let after = i64::try_from(sequence).unwrap_or(i64::MAX);
+    // SQLite reads a negative limit as no limit
+    let limit = if limit > 0 { i64::from(limit) } else { -1 };
+    let (rows, oldest, newest, compacted) = client.runtime.block_on(async {
+        // One read transaction, so that the bounds match the entries read
+        let mut snapshot = sqlite_db.get_conn().begin().await?;
+        let (oldest, newest): (Option<i64>, i64) =
+            sqlx::query_as("SELECT MIN(seq_id), COALESCE(MAX(seq_id), 0) FROM embeddings_queue")
+                .fetch_one(&mut *snapshot)
+                .await?;
+        // Compaction offset the log was purged up to, for when no entry is left to show it
+        let compacted: Option<i64> = sqlx::query_scalar("SELECT MAX(seq_id) FROM max_seq_id")
+            .fetch_one(&mut *snapshot)
+            .await?;
+        let rows = sqlx::query(
+            "SELECT seq_id, operation, id, CAST(created_at AS TEXT), vector, encoding, metadata \
+             FROM embeddings_queue \
+             WHERE seq_id > ? AND (topic = ? OR topic LIKE '%/' || ?) \
+             ORDER BY seq_id LIMIT ?",
+        )
+        .bind(after)
+        .bind(&topic)
+        .bind(&topic)
+        .bind(limit)
+        .fetch_all(&mut *snapshot)
+        .await?;
+        snapshot.commit().await?;
+        Ok::<_, sqlx::Error>((rows, oldest, newest, compacted))
+    })
+    .map_err(read_error)?;
+
+    let full_page = limit > 0 && rows.len() as i64 == limit;
+    let mut next_sequence = sequence;
+    let mut changes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let seq: i64 = row.try_get(0).map_err(read_error)?;
+        let operation: i64 = row.try_get(1).map_err(read_error)?;
+        let id: String = row.try_get(2).map_err(read_error)?;
+        let created_at: Option<String> = row.try_get(3).map_err(read_error)?;
+        let vector: Option<Vec<u8>> = row.try_get(4).map_err(read_error)?;
+        let encoding: Option<String> = row.try_get(5).map_err(read_error)?;
+        let metadata: Option<String> = row.try_get(6).map_err(read_error)?;
+
+        let (document, metadata) = split_metadata(metadata.as_deref());
+        let mut change = Map::new();
+        change.insert("seq".to_string(), seq.into());
+        change.insert("operation".to_string(), operation_name(operation).into());
+        change.insert("id".to_string(), id.into());
+        change.insert("created_at".to_string(), created_at.into());
+        change.insert("document".to_string(), document);
+        change.insert("metadata".to_string(), metadata);
+        change.insert("embedding".to_string(), decode_vector(vector, encoding.as_deref()));
+        changes.push(Value::Object(change));
+        next_sequence = seq as u64;
+    }
+
+    if !full_page {
+        // Entries of other collections up to `newest` were read past as well, so that purging
+        // them is not mistaken for a gap
+        next_sequence = next_sequence.max(newest as u64);
+    }
+
+    // Entries up to the compaction offset are purged from the log by `chroma_optimize_collection`,
+    // which may leave it empty
+    let purged_before = oldest.or(compacted.map(|offset| offset.saturating_add(1)));
+    let truncated = purged_before.is_some_and(|oldest| after.saturating_add(1) < oldest);
+    Ok(json!({
+        "changes": changes,
+        "next_sequence": next_sequence,
+        "oldest_sequence": purged_before,
+        "truncated": truncated,
+    }))
+}
+
+/// Reads the writes made to a collection after `sequence`, oldest first, as JSON.
+///
+/// Each entry of `changes` holds the `seq` number, the `operation` (`add`, `update`,
+/// `upsert` or `delete`), the record `id`, the `created_at` timestamp (UTC) and, when the
+/// write carried them, the `document`, `metadata` and `embedding`. Pass the returned
+/// `next_sequence` back to read the following page; start from 0. `limit` caps the number
+/// of entries, 0 means no limit. Once a page is not full, `next_sequence` moves past the
+/// writes of other collections as well.
+///
+/// The feed reads the local write log, which loses entries once they are compacted and
+/// purged. `truncated` is set when entries after `sequence` may already be gone, including
+/// when the purge left the log empty, in which case a consumer has to resynchronize from
+/// `chroma_get`. Sequence numbers are shared by
+/// all collections of the client, so they are increasing but not contiguous.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_changes_since(
+    collection_handle: ChromaHandle,
+    sequence: u64,
+    limit: c_uint,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_changes_since", || {
+        let func_name = "chroma_changes_since";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || out_json.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let changes = match changes_since(&collection, sequence, limit) {
+            Ok(changes) => changes,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_json = string_to_c_str(changes.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,83 @@
+// Conflict handling for adds of records that already exist
+use chroma_types::{Metadata, UpdateMetadata};
+use libc::{c_int, size_t};
+use std::collections::HashSet;
+
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Modes accepted in the `on_conflict` argument of `chroma_add`
+pub const CHROMA_ON_CONFLICT_FAIL: c_int = 0;
+pub const CHROMA_ON_CONFLICT_IGNORE: c_int = 1;
+pub const CHROMA_ON_CONFLICT_OVERWRITE: c_int = 2;
+
+/// Number of IDs listed in the error reported for conflicting adds
+const MAX_REPORTED_CONFLICTS: usize = 10;
+
+/// Outcome of `chroma_add`, filled in by the library in a caller-owned struct
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ChromaAddStats {
+    /// Records that did not exist before the add
+    pub inserted: size_t,
+    /// Existing records left untouched (`CHROMA_ON_CONFLICT_IGNORE`)
+    pub skipped: size_t,
+    /// Existing records replaced (`CHROMA_ON_CONFLICT_OVERWRITE`)
+    pub overwritten: size_t,
+}
+
+/// Parsed form of the `on_conflict` argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OnConflict {
+    Fail,
+    Ignore,
+    Overwrite,
+}
+
+impl OnConflict {
+    pub(crate) fn from_ffi(on_conflict: c_int) -> Result<Self, FfiError> {
+        match on_conflict {
+            CHROMA_ON_CONFLICT_FAIL => Ok(OnConflict::Fail),
+            CHROMA_ON_CONFLICT_IGNORE => Ok(OnConflict::Ignore),
+            CHROMA_ON_CONFLICT_OVERWRITE => Ok(OnConflict::Overwrite),
+            mode => Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Unknown conflict mode",
+                Some(format!("Got {}, expected 0 (fail), 1 (ignore) or 2 (overwrite)", mode)),
+            )),
+        }
+    }
+}
+
+/// Error reported when `CHROMA_ON_CONFLICT_FAIL` finds existing records
+pub(crate) fn conflict_error(existing: &HashSet<String>) -> FfiError {
+    let mut ids: Vec<&str> = existing.iter().map(String::as_str).collect();
+    ids.sort_unstable();
+    let shown = ids[..ids.len().min(MAX_REPORTED_CONFLICTS)].join(", ");
+    let details = if ids.len() > MAX_REPORTED_CONFLICTS {
+        format!("{} existing IDs, including: {}", ids.len(), shown)
+    } else {
+        format!("Existing IDs: {}", shown)
+    };
+    FfiError::new(
+        ChromaErrorCode::ValidationError,
+        "Records with these IDs already exist",
+        Some(details),
+    )
+}
+
+/// Keeps the entries of `values` whose flag in `keep` is set
+pub(crate) fn retain_flagged<T>(values: Vec<T>, keep: &[bool]) -> Vec<T> {
+    values
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(value, &keep)| keep.then_some(value))
+        .collect()
+}
+
+/// Converts add metadata into the form taken by upserts
+pub(crate) fn to_update_metadata(metadata: Metadata) -> UpdateMetadata {
+    metadata
+        .into_iter()
+        .map(|(key, value)| (key, value.into()))
+        .collect()
+}
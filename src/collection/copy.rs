@@ -0,0 +1,359 @@
+// Copying a collection and its records within a client
+use chroma_types::{
+    AddCollectionRecordsRequest, Collection, CreateCollectionRequest, DatabaseName,
+    DeleteCollectionRequest, GetRequest, IncludeList, Metadata,
+};
+use libc::{c_char, c_int, size_t};
+use std::sync::Arc;
+
+use crate::client::{identity_of, AccessScope, ChromaClient};
+use crate::collection::includes::BUILTIN_DEFAULT_INCLUDES;
+use crate::collection::read_only::{read_only_enabled, READ_ONLY_KEY};
+use crate::collection::soft_delete::{soft_delete_enabled, SOFT_DELETE_ENABLED_KEY};
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, insert_collection, ChromaHandle,
+    CHROMA_INVALID_HANDLE,
+};
+use crate::utils::c_str_to_string;
+
+/// Number of records read from the source and written to the target per request
+const COPY_PAGE_SIZE: u32 = 1000;
+
+/// Metadata of the copy of a collection: the state flags of the source are left out, so a
+/// copy of a read-only collection is writable, and soft deletion starts over since
+/// soft-deleted records are not copied
+fn copy_metadata(metadata: Option<&Metadata>) -> Option<Metadata> {
+    let mut metadata = metadata?.clone();
+    metadata.remove(READ_ONLY_KEY);
+    metadata.remove(SOFT_DELETE_ENABLED_KEY);
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+/// Copies every record of `source` visible to reads into `target`, page by page, and
+/// returns the count
+fn copy_records(
+    source: &ChromaCollection,
+    target: &Collection,
+    target_scope: &AccessScope,
+) -> Result<usize, FfiError> {
+    let include = vec![
+        "documents".to_string(),
+        "metadatas".to_string(),
+        "uris".to_string(),
+        "embeddings".to_string(),
+    ];
+    let include_list = IncludeList::try_from(include).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let client = &source.client;
+    client.read_barrier(source.id)?;
+    let mut copied = 0;
+    loop {
+        let get_request = GetRequest::try_new(
+            source.tenant.to_string(),
+            source.database.to_string(),
+            source.id,
+            None,
+            source.visible(None),
+            Some(COPY_PAGE_SIZE),
+            copied as u32,
+            include_list.clone(),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create get request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+        let page = client
+            .block_on_with_retry("get", source.scope(), |mut frontend| {
+                let request = get_request.clone();
+                async move { frontend.get(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to read source records",
+                    &e,
+                )
+            })?;
+
+        let count = page.ids.len();
+        if count == 0 {
+            break;
+        }
+
+        let add_request = AddCollectionRecordsRequest::try_new(
+            target.tenant.clone(),
+            target.database.clone(),
+            target.collection_id,
+            page.ids,
+            page.embeddings.unwrap_or_default(),
+            page.documents,
+            page.uris,
+            page.metadatas,
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create add request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+        client
+            .block_on_with_retry(
+                "add",
+                target_scope.clone().with_records(Some(add_request.ids.len())),
+                |mut frontend| {
+                    let request = add_request.clone();
+                    async move { frontend.add(request).await }
+                },
+            )
+            .map_err(|e| {
+                FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to write target records",
+                    &e,
+                )
+            })?;
+
+        copied += count;
+        if count < COPY_PAGE_SIZE as usize {
+            break;
+        }
+    }
+    Ok(copied)
+}
+
+/// Removes a partially copied target collection; the copy error is reported instead
+pub(crate) fn drop_target(client: &ChromaClient, target: &Collection, target_scope: AccessScope) {
+    let Ok(request) = DeleteCollectionRequest::try_new(
+        target.tenant.clone(),
+        target.database.clone(),
+        target.name.clone(),
+    ) else {
+        return;
+    };
+    let _ = client.block_on_with_retry("delete_collection", target_scope, |mut frontend| {
+        let request = request.clone();
+        async move { frontend.delete_collection(request).await }
+    });
+}
+
+/// Copies a collection, its configuration, metadata and records, into a new collection.
+///
+/// The target is created as `target_name` in `target_tenant` and `target_database`, which
+/// default to the client's default tenant and database when null and may be the source's
+/// own.
+/// Records are read and written in pages inside the library, so nothing passes through the
+/// caller, embeddings included. Records are written with the checks of `chroma_add`, which
+/// needs an embedding per record, so `include_embeddings` must be true; false fails with
+/// `InvalidArgument` and creates nothing.
+///
+/// Soft-deleted records are not copied, and the target is writable even when the source
+/// was made read-only with `chroma_set_collection_readonly`.
+///
+/// On success `target_handle_out` receives a handle to the new collection, released with
+/// `chroma_destroy_collection`, and `copied_out`, when not null, the number of records
+/// copied. If copying fails the partial target is deleted. The source must have been
+/// opened from `client_handle` and should not be written to during the copy.
+#[no_mangle]
+pub extern "C" fn chroma_copy_collection(
+    client_handle: ChromaHandle,
+    source_handle: ChromaHandle,
+    target_tenant_ptr: *const c_char,
+    target_database_ptr: *const c_char,
+    target_name_ptr: *const c_char,
+    include_embeddings: bool,
+    target_handle_out: *mut ChromaHandle,
+    copied_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_copy_collection", || {
+        let func_name = "chroma_copy_collection";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || source_handle == CHROMA_INVALID_HANDLE
+            || target_name_ptr.is_null()
+            || target_handle_out.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if source_handle == CHROMA_INVALID_HANDLE {
+                "Source collection handle is null"
+            } else if target_name_ptr.is_null() {
+                "Target name pointer is null"
+            } else {
+                "Target handle output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+        if !include_embeddings {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Copying without embeddings is not supported",
+                func_name,
+                Some("Every record written to the target needs an embedding"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+        let source = match collection_from_handle(source_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if !Arc::ptr_eq(&client, &source.client) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Source collection belongs to another client",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let parse = |ptr: *const c_char, default: &str, what: &'static str| {
+            if ptr.is_null() {
+                return Ok(default.to_string());
+            }
+            unsafe { c_str_to_string(ptr) }.map_err(|e| {
+                FfiError::new(ChromaErrorCode::InvalidArgument, what, Some(e.to_string()))
+            })
+        };
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let target = (
+            parse(target_tenant_ptr, &default_tenant, "Invalid tenant name"),
+            parse(target_database_ptr, &default_database, "Invalid database name"),
+            parse(target_name_ptr, "", "Invalid target name"),
+        );
+        let (tenant, database, name) = match target {
+            (Ok(tenant), Ok(database), Ok(name)) => (tenant, database, name),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return e.report(error_out, func_name)
+            }
+        };
+
+        let Some(database_name) = DatabaseName::new(database.clone()) else {
+            set_error(
+                error_out,
+                ChromaErrorCode::ValidationError,
+                "Invalid database name (must be at least 3 characters)",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::ValidationError as c_int;
+        };
+
+        let source_id = source.id;
+        let stored = match client.block_on_with_retry(
+            "get_collection",
+            source.scope(),
+            |mut frontend| async move { frontend.get_cached_collection(source_id).await },
+        ) {
+            Ok(stored) => stored,
+            Err(e) => {
+                return FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e)
+                    .report(error_out, func_name)
+            }
+        };
+
+        let target_scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+        let request = match CreateCollectionRequest::try_new(
+            tenant.clone(),
+            database_name,
+            name,
+            copy_metadata(stored.metadata.as_ref()),
+            Some(stored.config.clone()),
+            None, // schema
+            false,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+        let target = match client.block_on_with_retry(
+            "create_collection",
+            target_scope.clone(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.create_collection(request).await }
+            },
+        ) {
+            Ok(target) => target,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to create target collection",
+                    &e,
+                )
+                .report(error_out, func_name)
+            }
+        };
+
+        let target_id = target.collection_id.to_string();
+        let records_scope = AccessScope::new(client_handle, &tenant, &database, Some(&target_id));
+        let copied = match copy_records(&source, &target, &records_scope) {
+            Ok(copied) => copied,
+            Err(e) => {
+                drop_target(&client, &target, target_scope);
+                return e.report(error_out, func_name);
+            }
+        };
+
+        let dimension = stored.dimension.map_or(0, |d| d as usize);
+        let handle = insert_collection(ChromaCollection {
+            client: client.clone(),
+            id: target.collection_id,
+            tenant: tenant.into(),
+            database: database.into(),
+            dimension: dimension.into(),
+            soft_delete: soft_delete_enabled(target.metadata.as_ref()).into(),
+            read_only: read_only_enabled(target.metadata.as_ref()).into(),
+            default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+            principal: identity_of(client_handle),
+        });
+
+        unsafe {
+            *target_handle_out = handle;
+        }
+        if let Some(copied_out) = unsafe { copied_out.as_mut() } {
+            *copied_out = copied;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,207 @@
+// Existence checks for collections and records
+use chroma_error::{ChromaError as _, ErrorCodes};
+use chroma_types::{DatabaseName, GetCollectionRequest};
+use libc::{c_char, c_int, size_t};
+use std::collections::HashSet;
+
+use crate::client::AccessScope;
+use crate::collection::operations::matching_ids;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::utils::{c_array_to_vec_string, c_str_to_string};
+
+/// Reads an optional C string, falling back to `default` when it is null
+fn c_str_or(ptr: *const c_char, default: &str, what: &'static str) -> Result<String, FfiError> {
+    if ptr.is_null() {
+        return Ok(default.to_string());
+    }
+    unsafe { c_str_to_string(ptr) }
+        .map_err(|e| FfiError::new(ChromaErrorCode::InvalidArgument, what, Some(e.to_string())))
+}
+
+/// Checks whether a collection exists, without opening a handle to it.
+///
+/// `tenant_ptr` and `database_ptr` default to the client's default tenant and database when null.
+/// A missing collection is not an error: `exists_out` is set to false.
+#[no_mangle]
+pub extern "C" fn chroma_collection_exists(
+    client_handle: ChromaHandle,
+    name_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    exists_out: *mut bool,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_collection_exists", || {
+        let func_name = "chroma_collection_exists";
+
+        if client_handle == CHROMA_INVALID_HANDLE || name_ptr.is_null() || exists_out.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if name_ptr.is_null() {
+                "Collection name pointer is null"
+            } else {
+                "Exists output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let names = (
+            c_str_or(name_ptr, "", "Invalid collection name"),
+            c_str_or(tenant_ptr, &default_tenant, "Invalid tenant name"),
+            c_str_or(database_ptr, &default_database, "Invalid database name"),
+        );
+        let (name, tenant, database) = match names {
+            (Ok(name), Ok(tenant), Ok(database)) => (name, tenant, database),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return e.report(error_out, func_name)
+            }
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+        let Some(database_name) = DatabaseName::new(database) else {
+            set_error(
+                error_out,
+                ChromaErrorCode::ValidationError,
+                "Invalid database name (must be at least 3 characters)",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::ValidationError as c_int;
+        };
+
+        let request = match GetCollectionRequest::try_new(tenant, database_name, name) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let exists = match client.block_on_with_retry("get_collection", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get_collection(request).await }
+        }) {
+            Ok(_) => true,
+            Err(e) if e.code() == ErrorCodes::NotFound => false,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to look up collection",
+                    &e,
+                )
+                .report(error_out, func_name)
+            }
+        };
+
+        unsafe {
+            *exists_out = exists;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Checks which of `ids` exist in a collection.
+///
+/// `exists_out` must have room for `ids_count` flags; flag `i` is set to whether `ids[i]`
+/// exists. Soft-deleted records count as missing. Only IDs are read from the backend.
+#[no_mangle]
+pub extern "C" fn chroma_records_exist(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    exists_out: *mut bool,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_records_exist", || {
+        let func_name = "chroma_records_exist";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || (ids_count > 0 && (ids.is_null() || exists_out.is_null()))
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs array is null"
+            } else {
+                "Exists output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if ids_count == 0 {
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
+
+        let ids_vec = match unsafe { c_array_to_vec_string(ids, ids_count) } {
+            Ok(v) => v,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert IDs array",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        // The backend rejects duplicate IDs, which callers may legitimately pass here
+        let unique: HashSet<String> = ids_vec.iter().cloned().collect();
+        let found: HashSet<String> = match matching_ids(
+            &collection,
+            Some(unique.into_iter().collect()),
+            collection.visible(None),
+        ) {
+            Ok(found) => found.into_iter().collect(),
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let flags = unsafe { std::slice::from_raw_parts_mut(exists_out, ids_count) };
+        for (flag, id) in flags.iter_mut().zip(&ids_vec) {
+            *flag = found.contains(id);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
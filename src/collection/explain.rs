@@ -0,0 +1,277 @@
+// Diagnostics of how a query is executed
+use chroma_types::{CountRequest, IncludeList, QueryRequest, ReadLevel};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use serde_json::{json, Value};
+use sqlx::Row;
+use std::ptr;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::operations::matching_ids;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_array_to_vec_f32, string_to_c_str};
+
+/// Timings of the stages of an explained query, in execution order
+#[derive(Default)]
+struct Stages(Vec<Value>);
+
+impl Stages {
+    /// Runs one stage and records how long it took
+    fn run<T>(
+        &mut self,
+        name: &str,
+        stage: impl FnOnce() -> Result<T, FfiError>,
+    ) -> Result<T, FfiError> {
+        let started = Instant::now();
+        let result = stage();
+        self.0.push(json!({
+            "name": name,
+            "duration_ms": started.elapsed().as_secs_f64() * 1000.0,
+        }));
+        result
+    }
+}
+
+/// Segments of the collection and the number of its entries still in the write log, read
+/// from the sysdb tables shared with the local segment manager
+fn storage_layout(collection: &ChromaCollection) -> Result<(Vec<Value>, i64), FfiError> {
+    let client = &collection.client;
+    let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+        return Ok((Vec::new(), 0));
+    };
+    let read_error = |e: sqlx::Error| {
+        FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Failed to read the collection segments",
+            Some(e.to_string()),
+        )
+    };
+
+    let topic = collection.id.to_string();
+    let (rows, log_entries) = client
+        .runtime
+        .block_on(async {
+            let rows = sqlx::query(
+                "SELECT id, type, scope FROM segments WHERE collection = ? ORDER BY scope",
+            )
+            .bind(&topic)
+            .fetch_all(sqlite_db.get_conn())
+            .await?;
+            let log_entries: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM embeddings_queue WHERE topic = ? OR topic LIKE '%/' || ?",
+            )
+            .bind(&topic)
+            .bind(&topic)
+            .fetch_one(sqlite_db.get_conn())
+            .await?;
+            Ok::<_, sqlx::Error>((rows, log_entries))
+        })
+        .map_err(read_error)?;
+
+    let mut segments = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row.try_get(0).map_err(read_error)?;
+        let segment_type: String = row.try_get(1).map_err(read_error)?;
+        let scope: String = row.try_get(2).map_err(read_error)?;
+        segments.push(json!({ "id": id, "type": segment_type, "scope": scope }));
+    }
+    Ok((segments, log_entries))
+}
+
+/// Number of visible records in the collection
+fn record_count(collection: &ChromaCollection) -> Result<usize, FfiError> {
+    // Soft-deleted records are still stored, so they cannot be counted by the backend
+    if collection.soft_delete.load(Ordering::Relaxed) {
+        return matching_ids(collection, None, collection.visible(None)).map(|ids| ids.len());
+    }
+
+    let request = CountRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        ReadLevel::default(),
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create count request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    collection
+        .client
+        .block_on_with_retry("count", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.count(request).await }
+        })
+        .map(|count| count as usize)
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to count documents", &e)
+        })
+}
+
+/// Runs a query with its filters and reports how it was executed
+unsafe fn explain_query(
+    collection: &ChromaCollection,
+    query_embedding: Vec<f32>,
+    n_results: u32,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+) -> Result<Value, FfiError> {
+    let where_filter = parse_where_ptrs(where_filter_json, where_document_filter)?;
+    let filtered = where_filter.is_some();
+    let mut stages = Stages::default();
+
+    let (segments, log_entries) = stages.run("segments", || storage_layout(collection))?;
+    let records = stages.run("count", || record_count(collection))?;
+    let candidates = if filtered {
+        let where_filter = collection.visible(where_filter.clone());
+        stages.run("filter", || matching_ids(collection, None, where_filter))?.len()
+    } else {
+        records
+    };
+
+    let include_list = IncludeList::try_from(vec!["distances".to_string()]).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+    let request = QueryRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        None,
+        collection.visible(where_filter),
+        vec![query_embedding],
+        n_results,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create query request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    let response = stages.run("knn", || {
+        collection
+            .client
+            .block_on_with_retry("query", collection.scope(), |mut frontend| {
+                let request = request.clone();
+                async move { frontend.query(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+            })
+    })?;
+    let hits = response.ids.first().map_or(0, Vec::len);
+
+    let total_ms: f64 = stages.0.iter().filter_map(|s| s["duration_ms"].as_f64()).sum();
+    Ok(json!({
+        "collection_id": collection.id.to_string(),
+        "n_results": n_results,
+        "segments": segments,
+        "log_entries": log_entries,
+        "records": records,
+        "filtered": filtered,
+        "candidates": candidates,
+        "hits": hits,
+        "hnsw_cache": collection.client.effective_config["hnsw_cache"].clone(),
+        // The HNSW cache belongs to Chroma's local segment manager, which keeps no statistics
+        "cache_hits": Value::Null,
+        "stages": stages.0,
+        "total_ms": total_ms,
+    }))
+}
+
+/// Runs a query and reports how it was executed as JSON, to diagnose slow queries.
+///
+/// The arguments match `chroma_query` for a single query embedding. The query is executed
+/// but its hits are not returned. The report holds the `segments` of the collection (`id`,
+/// `type`, `scope`), the number of `log_entries` of the collection still in the write log,
+/// the visible `records`, the `candidates` left by the filters (all records without filters),
+/// the number of `hits`, the `hnsw_cache` configuration and the `name` and `duration_ms` of
+/// each stage (`segments`, `count`, `filter` when filtered, `knn`) with their `total_ms`.
+/// `cache_hits` is always null: the index cache keeps no statistics the library can read.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_query_explain(
+    collection_handle: ChromaHandle,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    out_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_explain", || {
+        let func_name = "chroma_query_explain";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || query_embedding.is_null()
+            || embedding_dim == 0
+            || out_json.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_embedding.is_null() {
+                "Query embedding pointer is null"
+            } else if embedding_dim == 0 {
+                "Embedding dimension is zero"
+            } else {
+                "Output JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *out_json = ptr::null_mut();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let query_embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+        let report = match unsafe {
+            explain_query(
+                &collection,
+                query_embedding,
+                n_results,
+                where_filter_json,
+                where_document_filter,
+            )
+        } {
+            Ok(report) => report,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_json = string_to_c_str(report.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,341 @@
+// Declaration of the metadata keys a collection is filtered on
+use chroma_types::{
+    CollectionMetadataUpdate, MetadataValue, UpdateCollectionRequest, UpdateMetadata,
+    UpdateMetadataValue,
+};
+use libc::{c_char, c_int, size_t};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::ChromaResultSet;
+use crate::utils::{c_array_to_vec_string, c_str_to_string};
+
+/// Collection metadata key holding the declared filterable keys as a JSON array
+const FILTERABLE_KEYS_KEY: &str = "filterable:keys";
+
+/// Collects the metadata keys compared by a where filter, skipping `$and`/`$or` operators
+fn collect_filter_keys(filter: &Value, keys: &mut BTreeSet<String>) {
+    let Value::Object(object) = filter else {
+        return;
+    };
+    for (key, value) in object {
+        if key.starts_with('$') {
+            if let Value::Array(children) = value {
+                for child in children {
+                    collect_filter_keys(child, keys);
+                }
+            }
+        } else {
+            keys.insert(key.clone());
+        }
+    }
+}
+
+impl ChromaCollection {
+    /// Declared filterable keys, or `None` when the collection declares none
+    pub(crate) fn filterable_keys(&self) -> Result<Option<Vec<String>>, FfiError> {
+        let collection_id = self.id;
+        let stored = self
+            .client
+            .block_on_with_retry("get_collection", self.scope(), |mut frontend| async move {
+                frontend.get_cached_collection(collection_id).await
+            })
+            .map_err(|e| FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e))?;
+
+        match stored.metadata.as_ref().and_then(|m| m.get(FILTERABLE_KEYS_KEY)) {
+            Some(MetadataValue::Str(keys)) => serde_json::from_str(keys).map(Some).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Invalid filterable keys in collection metadata",
+                    Some(e.to_string()),
+                )
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Metadata keys of `where_json` that are not declared filterable; empty when the
+    /// collection declares no keys
+    pub(crate) fn undeclared_filter_keys(
+        &self,
+        where_json: &str,
+    ) -> Result<Vec<String>, FfiError> {
+        let filter: Value = serde_json::from_str(where_json).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid where filter JSON",
+                Some(e.to_string()),
+            )
+        })?;
+        let Some(declared) = self.filterable_keys()? else {
+            return Ok(Vec::new());
+        };
+
+        let mut keys = BTreeSet::new();
+        collect_filter_keys(&filter, &mut keys);
+        Ok(keys.into_iter().filter(|key| !declared.contains(key)).collect())
+    }
+
+    /// Logs a warning when a read filters on keys that are not declared filterable
+    pub(crate) fn warn_undeclared_filter_keys(&self, where_json: &str) {
+        if let Ok(keys) = self.undeclared_filter_keys(where_json) {
+            if !keys.is_empty() {
+                tracing::warn!(
+                    collection = %self.id,
+                    keys = ?keys,
+                    "Where filter uses metadata keys that are not declared filterable"
+                );
+            }
+        }
+    }
+}
+
+/// Declares the metadata keys that where filters of a collection use.
+///
+/// The declaration is stored in the collection metadata, so it survives restarts and is
+/// shared by every client, and is available to backends that index only declared keys.
+/// Filtered `chroma_get` and `chroma_query` calls log a warning for keys outside it. Passing
+/// no keys removes the declaration, after which every key is accepted without warning.
+#[no_mangle]
+pub extern "C" fn chroma_set_filterable_fields(
+    collection_handle: ChromaHandle,
+    keys: *const *const c_char,
+    keys_count: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_filterable_fields", || {
+        let func_name = "chroma_set_filterable_fields";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || (keys_count > 0 && keys.is_null()) {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Keys pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let keys_vec = if keys_count > 0 {
+            match unsafe { c_array_to_vec_string(keys, keys_count) } {
+                Ok(v) => v,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert keys array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        if let Some(key) = keys_vec.iter().find(|k| k.is_empty() || k.starts_with('$')) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Invalid filterable key",
+                func_name,
+                Some(&format!("Key {:?} is empty or starts with '$'", key)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let mut update = UpdateMetadata::new();
+        let value = if keys_vec.is_empty() {
+            UpdateMetadataValue::None
+        } else {
+            let keys: BTreeSet<String> = keys_vec.into_iter().collect();
+            UpdateMetadataValue::Str(Value::from_iter(keys).to_string())
+        };
+        update.insert(FILTERABLE_KEYS_KEY.to_string(), value);
+
+        let request = match UpdateCollectionRequest::try_new(
+            collection.id,
+            None,
+            Some(CollectionMetadataUpdate::UpdateMetadata(update)),
+            None,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match collection.client.block_on_with_retry(
+            "update_collection",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update_collection(request).await }
+            },
+        ) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to update collection",
+                &e,
+            )
+            .report(error_out, func_name),
+        }
+    })
+}
+
+/// Reads the filterable keys declared for a collection, sorted, into `keys_out`.
+///
+/// The set is empty when no keys are declared. Release it with `chroma_free_result_set`.
+#[no_mangle]
+pub extern "C" fn chroma_get_filterable_fields(
+    collection_handle: ChromaHandle,
+    keys_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_filterable_fields", || {
+        let func_name = "chroma_get_filterable_fields";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || keys_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Keys output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *keys_out = ChromaResultSet::empty();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let keys = match collection.filterable_keys() {
+            Ok(keys) => keys.unwrap_or_default(),
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *keys_out = ChromaResultSet::from_ids(keys);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Lists the metadata keys of a where filter that are not declared filterable, sorted,
+/// into `keys_out`.
+///
+/// The set is empty when every key is declared or the collection declares no keys. Use it
+/// to check user-supplied filters before running them. Release the set with
+/// `chroma_free_result_set`.
+#[no_mangle]
+pub extern "C" fn chroma_undeclared_filter_keys(
+    collection_handle: ChromaHandle,
+    where_json: *const c_char,
+    keys_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_undeclared_filter_keys", || {
+        let func_name = "chroma_undeclared_filter_keys";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || where_json.is_null()
+            || keys_out.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if where_json.is_null() {
+                "Where filter pointer is null"
+            } else {
+                "Keys output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *keys_out = ChromaResultSet::empty();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let where_json = match unsafe { c_str_to_string(where_json) } {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert where filter string",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let keys = match collection.undeclared_filter_keys(&where_json) {
+            Ok(keys) => keys,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *keys_out = ChromaResultSet::from_ids(keys);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,64 @@
+// Shared argument parsing for collection operations
+use chroma_types::{RawWhereFields, Where};
+use libc::c_char;
+
+use crate::error::{ChromaErrorCode, FfiError};
+use crate::utils::c_str_to_string;
+
+/// Converts an optional C string, treating a null pointer as absent
+pub(crate) unsafe fn optional_c_str(
+    s: *const c_char,
+    message: &'static str,
+) -> Result<Option<String>, FfiError> {
+    if s.is_null() {
+        return Ok(None);
+    }
+
+    c_str_to_string(s).map(Some).map_err(|e| {
+        FfiError::new(ChromaErrorCode::InvalidArgument, message, Some(e.to_string()))
+    })
+}
+
+/// Parses metadata and document filters into a single where clause
+pub(crate) fn parse_where(
+    where_json: Option<&str>,
+    where_document: Option<&str>,
+) -> Result<Option<Where>, FfiError> {
+    // Only attempt to parse where filters if they're actually provided
+    if where_json.is_none() && where_document.is_none() {
+        return Ok(None);
+    }
+
+    let raw = RawWhereFields::from_json_str(where_json, where_document).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create where filters",
+            Some(format!("Filter creation error: {:?}", e)),
+        )
+    })?;
+
+    raw.parse().map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to parse where filters",
+            Some(format!("Filter validation error: {:?}", e)),
+        )
+    })
+}
+
+/// Parses metadata and document filters passed as C strings
+pub(crate) unsafe fn parse_where_ptrs(
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+) -> Result<Option<Where>, FfiError> {
+    let where_json = optional_c_str(
+        where_filter_json,
+        "Failed to convert where filter JSON string",
+    )?;
+    let where_document = optional_c_str(
+        where_document_filter,
+        "Failed to convert document filter string",
+    )?;
+
+    parse_where(where_json.as_deref(), where_document.as_deref())
+}
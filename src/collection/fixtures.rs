@@ -0,0 +1,253 @@
+// Seeded test collections generated inside the library
+use chroma_types::{
+    AddCollectionRecordsRequest, CreateCollectionRequest, DatabaseName, Metadata, MetadataValue,
+};
+use libc::{c_char, c_int, c_uint, size_t};
+
+use crate::client::{identity_of, AccessScope};
+use crate::collection::bench::SeededRng;
+use crate::collection::copy::drop_target;
+use crate::collection::includes::BUILTIN_DEFAULT_INCLUDES;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, insert_collection, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Number of generated records written per add request
+const FIXTURE_PAGE_SIZE: u32 = 1000;
+
+/// Values of the `category` metadata key of generated records
+const FIXTURE_CATEGORIES: [&str; 5] = ["news", "sports", "science", "finance", "travel"];
+
+/// Words generated documents are made of, so `$contains` filters have something to match
+const FIXTURE_WORDS: [&str; 16] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet",
+    "kilo", "lima", "mike", "november", "oscar", "papa",
+];
+
+/// Generates the document and metadata of record `index`
+fn fixture_record(rng: &mut SeededRng, index: u32) -> (String, Metadata) {
+    let words: Vec<&str> = (0..8)
+        .map(|_| FIXTURE_WORDS[(rng.next_u64() % FIXTURE_WORDS.len() as u64) as usize])
+        .collect();
+    let category =
+        FIXTURE_CATEGORIES[(rng.next_u64() % FIXTURE_CATEGORIES.len() as u64) as usize];
+
+    let mut metadata = Metadata::new();
+    metadata.insert("index".to_string(), MetadataValue::Int(index as i64));
+    metadata.insert("category".to_string(), MetadataValue::Str(category.to_string()));
+    metadata.insert("score".to_string(), MetadataValue::Float(rng.next_f64()));
+    metadata.insert("flag".to_string(), MetadataValue::Bool(rng.next_u64() % 2 == 0));
+    (format!("Document {}: {}", index, words.join(" ")), metadata)
+}
+
+/// Adds `count` records generated from `seed` to `collection`, page by page
+fn fill_collection(
+    collection: &ChromaCollection,
+    count: u32,
+    dimension: usize,
+    seed: u64,
+) -> Result<(), FfiError> {
+    let client = &collection.client;
+    let mut rng = SeededRng::new(seed);
+    let mut next = 0u32;
+    while next < count {
+        let size = FIXTURE_PAGE_SIZE.min(count - next);
+        let mut ids = Vec::with_capacity(size as usize);
+        let mut embeddings = Vec::with_capacity(size as usize);
+        let mut documents = Vec::with_capacity(size as usize);
+        let mut metadatas = Vec::with_capacity(size as usize);
+        for index in next..next + size {
+            ids.push(format!("doc-{}", index));
+            embeddings.push(rng.vector(dimension));
+            let (document, metadata) = fixture_record(&mut rng, index);
+            documents.push(Some(document));
+            metadatas.push(Some(metadata));
+        }
+
+        let request = AddCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            ids,
+            embeddings,
+            Some(documents),
+            None, // uris
+            Some(metadatas),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create add request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+        client
+            .block_on_with_retry(
+                "add",
+                collection
+                    .scope()
+                    .with_records(Some(size as usize))
+                    .with_dimension(Some(dimension)),
+                |mut frontend| {
+                    let request = request.clone();
+                    async move { frontend.add(request).await }
+                },
+            )
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to add documents", &e)
+            })?;
+        client.metrics.add_records(size as usize);
+        next += size;
+    }
+    Ok(())
+}
+
+/// Creates the collection `name` in the client's default tenant and database and fills it
+/// with `count` reproducible records generated inside the library from `seed`, so tests and
+/// demos get realistic data without marshaling it.
+///
+/// Record `n` has the ID `doc-<n>`, an embedding of `dimension` components uniform in
+/// [-1, 1), a document of eight words from a fixed vocabulary and the metadata `index` (`n`),
+/// `category` (one of `news`, `sports`, `science`, `finance` and `travel`), `score` (a float
+/// in [0, 1)) and `flag` (a bool). The same seed, count and dimension always produce the same
+/// records. Fails if the collection exists; if filling it fails it is deleted.
+///
+/// On success `collection_handle_out` receives a handle to the new collection, released with
+/// `chroma_destroy_collection`.
+#[no_mangle]
+pub extern "C" fn chroma_generate_test_collection(
+    client_handle: ChromaHandle,
+    name_ptr: *const c_char,
+    count: c_uint,
+    dimension: size_t,
+    seed: u64,
+    collection_handle_out: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_generate_test_collection", || {
+        let func_name = "chroma_generate_test_collection";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || name_ptr.is_null()
+            || dimension == 0
+            || collection_handle_out.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if name_ptr.is_null() {
+                "Collection name pointer is null"
+            } else if dimension == 0 {
+                "Embedding dimension is zero"
+            } else {
+                "Collection handle output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let name = match unsafe { c_str_to_string(name_ptr) } {
+            Ok(name) => name,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid collection name",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let (tenant, database) = client.default_tenant_database();
+        let Some(database_name) = DatabaseName::new(database.clone()) else {
+            set_error(
+                error_out,
+                ChromaErrorCode::ValidationError,
+                "Invalid database name (must be at least 3 characters)",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::ValidationError as c_int;
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+        let request = match CreateCollectionRequest::try_new(
+            tenant.clone(),
+            database_name,
+            name,
+            None,
+            None,
+            None, // schema
+            false,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+        let created = match client.block_on_with_retry(
+            "create_collection",
+            scope.clone(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.create_collection(request).await }
+            },
+        ) {
+            Ok(created) => created,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to create collection",
+                    &e,
+                )
+                .report(error_out, func_name)
+            }
+        };
+
+        let collection = ChromaCollection {
+            client: client.clone(),
+            id: created.collection_id,
+            tenant: tenant.into(),
+            database: database.into(),
+            dimension: dimension.into(),
+            soft_delete: false.into(),
+            read_only: false.into(),
+            default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+            principal: identity_of(client_handle),
+        };
+        if let Err(e) = fill_collection(&collection, count, dimension, seed) {
+            drop_target(&client, &created, scope);
+            return e.report(error_out, func_name);
+        }
+
+        unsafe {
+            *collection_handle_out = insert_collection(collection);
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,254 @@
+// Reads returning only record IDs, for existence checks and candidate generation
+use chroma_types::{IncludeList, QueryRequest};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::operations::matching_ids_page;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::{ChromaQueryResult, ChromaResultSet};
+use crate::utils::{
+    c_array_to_vec_f32, c_array_to_vec_string, vec_f32_to_c_array, vec_string_to_c_array,
+};
+
+/// Runs a query that reads nothing but the IDs of the hits and, optionally, their distances
+unsafe fn query_ids(
+    collection: &ChromaCollection,
+    query_embedding: Vec<f32>,
+    n_results: u32,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    include_distances: bool,
+) -> Result<(Vec<String>, Option<Vec<f32>>), FfiError> {
+    let where_filter = parse_where_ptrs(where_filter_json, where_document_filter)?;
+
+    let include = if include_distances {
+        vec!["distances".to_string()]
+    } else {
+        Vec::new()
+    };
+    let include_list = IncludeList::try_from(include).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+    let request = QueryRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        None,
+        collection.visible(where_filter),
+        vec![query_embedding],
+        n_results,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create query request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    let response = collection
+        .client
+        .block_on_with_retry("query", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.query(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+        })?;
+
+    let ids = response.ids.into_iter().next().unwrap_or_default();
+    let distances = response
+        .distances
+        .and_then(|distances| distances.into_iter().next())
+        .map(|distances| distances.iter().map(|d| d.unwrap_or(0.0)).collect());
+    Ok((ids, distances))
+}
+
+/// Gets the IDs of the records matching `ids` and the filters, without reading or
+/// marshaling their documents, metadata or embeddings.
+///
+/// The arguments match `chroma_get` without sorting; `limit` 0 returns every match.
+/// `ids_out` receives the IDs and is released with `chroma_free_result_set`. Soft-deleted
+/// records are skipped.
+#[no_mangle]
+pub extern "C" fn chroma_get_ids(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    limit: c_uint,
+    offset: c_uint,
+    ids_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_ids", || {
+        let func_name = "chroma_get_ids";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || ids_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "IDs output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *ids_out = ChromaResultSet::empty();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let ids_vec = if !ids.is_null() && ids_count > 0 {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            None
+        };
+
+        let where_filter = unsafe { parse_where_ptrs(where_filter_json, where_document_filter) };
+        let where_filter = match where_filter {
+            Ok(where_filter) => where_filter,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let limit = if limit > 0 { Some(limit) } else { None };
+        match matching_ids_page(
+            &collection,
+            ids_vec,
+            collection.visible(where_filter),
+            limit,
+            offset,
+        ) {
+            Ok(found) => {
+                unsafe {
+                    *ids_out = ChromaResultSet::from_ids(found);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Queries a collection for the IDs of the nearest records, and their distances when
+/// `include_distances` is set, without reading or marshaling their contents.
+///
+/// The arguments match `chroma_query` for a single query embedding. Only `ids` and
+/// `distances` of the result are filled; release it with `chroma_free_query_result`.
+#[no_mangle]
+pub extern "C" fn chroma_query_ids(
+    collection_handle: ChromaHandle,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    include_distances: bool,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_ids", || {
+        let func_name = "chroma_query_ids";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || query_embedding.is_null()
+            || embedding_dim == 0
+            || result.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_embedding.is_null() {
+                "Query embedding pointer is null"
+            } else if embedding_dim == 0 {
+                "Embedding dimension is zero"
+            } else {
+                "Result pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let query_embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+        let hits = unsafe {
+            query_ids(
+                &collection,
+                query_embedding,
+                n_results,
+                where_filter_json,
+                where_document_filter,
+                include_distances,
+            )
+        };
+        let (ids, distances) = match hits {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let mut query_result = Box::new(ChromaQueryResult::empty());
+        if !ids.is_empty() {
+            let (array, count) = vec_string_to_c_array(ids);
+            query_result.ids = array;
+            query_result.ids_count = count;
+        }
+        if let Some(distances) = distances.filter(|distances| !distances.is_empty()) {
+            let (array, count) = vec_f32_to_c_array(distances);
+            query_result.distances = array;
+            query_result.distances_count = count;
+        }
+
+        unsafe {
+            *result = Box::into_raw(query_result);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
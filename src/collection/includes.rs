@@ -0,0 +1,141 @@
+// Default include flags of a collection handle
+use libc::{c_int, c_uint};
+use std::sync::atomic::Ordering;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Include flags accepted by calls taking `include_flags`
+pub const CHROMA_INCLUDE_EMBEDDINGS: c_uint = 1;
+pub const CHROMA_INCLUDE_METADATAS: c_uint = 1 << 1;
+pub const CHROMA_INCLUDE_DOCUMENTS: c_uint = 1 << 2;
+pub const CHROMA_INCLUDE_DISTANCES: c_uint = 1 << 3;
+pub const CHROMA_INCLUDE_URIS: c_uint = 1 << 4;
+/// Passed as `include_flags` to use the default includes of the collection handle
+pub const CHROMA_INCLUDE_DEFAULTS: c_uint = 0;
+
+/// Every valid include flag
+const CHROMA_INCLUDE_ALL: c_uint = CHROMA_INCLUDE_EMBEDDINGS
+    | CHROMA_INCLUDE_METADATAS
+    | CHROMA_INCLUDE_DOCUMENTS
+    | CHROMA_INCLUDE_DISTANCES
+    | CHROMA_INCLUDE_URIS;
+
+/// Default includes of a new collection handle, matching Chroma's defaults
+pub(crate) const BUILTIN_DEFAULT_INCLUDES: c_uint =
+    CHROMA_INCLUDE_METADATAS | CHROMA_INCLUDE_DOCUMENTS | CHROMA_INCLUDE_DISTANCES;
+
+/// Rejects include flags with unknown bits
+pub(crate) fn check_include_flags(include_flags: c_uint) -> Result<(), FfiError> {
+    if include_flags & !CHROMA_INCLUDE_ALL != 0 {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid include flags",
+            Some(format!(
+                "Got {:#x}, valid flags are {:#x}",
+                include_flags, CHROMA_INCLUDE_ALL
+            )),
+        ));
+    }
+    Ok(())
+}
+
+impl ChromaCollection {
+    /// Resolves `CHROMA_INCLUDE_DEFAULTS` to the default includes of the handle
+    pub(crate) fn resolve_includes(&self, include_flags: c_uint) -> c_uint {
+        if include_flags == CHROMA_INCLUDE_DEFAULTS {
+            self.default_includes.load(Ordering::Relaxed)
+        } else {
+            include_flags
+        }
+    }
+}
+
+/// Sets the include flags used by calls on this collection handle that are passed
+/// `CHROMA_INCLUDE_DEFAULTS`.
+///
+/// `include_flags` is a combination of `CHROMA_INCLUDE_*` flags; `CHROMA_INCLUDE_DEFAULTS`
+/// restores the initial defaults of metadatas, documents and distances. The setting
+/// belongs to the handle: other handles of the same collection keep their own defaults.
+#[no_mangle]
+pub extern "C" fn chroma_collection_set_default_includes(
+    collection_handle: ChromaHandle,
+    include_flags: c_uint,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_collection_set_default_includes", || {
+        let func_name = "chroma_collection_set_default_includes";
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if let Err(e) = check_include_flags(include_flags) {
+            return e.report(error_out, func_name);
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let include_flags = if include_flags == CHROMA_INCLUDE_DEFAULTS {
+            BUILTIN_DEFAULT_INCLUDES
+        } else {
+            include_flags
+        };
+        collection.default_includes.store(include_flags, Ordering::Relaxed);
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Reads the default include flags of a collection handle
+#[no_mangle]
+pub extern "C" fn chroma_collection_get_default_includes(
+    collection_handle: ChromaHandle,
+    include_flags_out: *mut c_uint,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_collection_get_default_includes", || {
+        let func_name = "chroma_collection_get_default_includes";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || include_flags_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Include flags pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *include_flags_out = collection.default_includes.load(Ordering::Relaxed);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,321 @@
+// Maintenance operations on the local segments of a collection
+use chroma_log::local_compaction_manager::{BackfillMessage, PurgeLogsMessage};
+use chroma_types::{CollectionUuid, IncludeList, ListCollectionsRequest, QueryRequest};
+use libc::{c_char, c_int, size_t};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use crate::client::{AccessScope, ChromaClient};
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    client_from_handle, collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::utils::c_str_to_string;
+
+/// Applies the pending log of a collection to its segments, then purges the applied entries
+fn optimize(collection: &ChromaCollection) -> Result<(), FfiError> {
+    let client = &collection.client;
+    let Some(compaction_manager) = client.compaction_manager.as_ref() else {
+        return Err(FfiError::new(
+            ChromaErrorCode::NotImplemented,
+            "Client has no local compaction manager",
+            None,
+        ));
+    };
+    let collection_id = collection.id;
+
+    let started = Instant::now();
+    let result = client.runtime.block_on(async {
+        compaction_manager
+            .request(BackfillMessage { collection_id }, None)
+            .await
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to reach compactor", &e)
+            })?
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to compact log", &e)
+            })?;
+
+        compaction_manager
+            .request(PurgeLogsMessage { collection_id }, None)
+            .await
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to reach compactor", &e)
+            })?
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to purge log", &e)
+            })
+    });
+
+    client.metrics.record("optimize", started.elapsed(), result.is_ok());
+    result
+}
+
+/// Compacts the write-ahead log of a collection into its local segments and
+/// purges the compacted entries.
+///
+/// Writes are normally compacted as they arrive; calling this during quiet hours
+/// makes sure no backlog is left to be applied by the next query.
+#[no_mangle]
+pub extern "C" fn chroma_optimize_collection(
+    collection_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_optimize_collection", || {
+        let func_name = "chroma_optimize_collection";
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        match optimize(&collection) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Loads the vector index of a collection into the client cache by running a
+/// single-result query that returns nothing but IDs.
+///
+/// Collections that never received embeddings (`dimension` 0) have no index to load.
+fn preload(
+    client: &ChromaClient,
+    scope: AccessScope,
+    tenant: &str,
+    database: &str,
+    collection_id: CollectionUuid,
+    dimension: usize,
+) -> Result<(), FfiError> {
+    if dimension == 0 {
+        return Ok(());
+    }
+
+    let include_list = IncludeList::try_from(Vec::<String>::new()).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = QueryRequest::try_new(
+        tenant.to_string(),
+        database.to_string(),
+        collection_id,
+        None,
+        None,
+        vec![vec![0.0; dimension]],
+        1,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create preload query",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    client
+        .block_on_with_retry("preload", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.query(request).await }
+        })
+        .map(|_| ())
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to preload collection", &e)
+        })
+}
+
+/// Loads the vector index of a collection into the cache, so the first query after
+/// startup does not pay for reading it from disk
+#[no_mangle]
+pub extern "C" fn chroma_preload_collection(
+    collection_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_preload_collection", || {
+        let func_name = "chroma_preload_collection";
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match preload(
+            &collection.client,
+            collection.scope(),
+            &collection.tenant,
+            &collection.database,
+            collection.id,
+            collection.dimension.load(Ordering::Relaxed),
+        ) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Preloads every collection of a database, as `chroma_preload_collection` does.
+///
+/// A null `tenant` or `database` selects the default one. When `preloaded_out` is not null it
+/// receives the number of collections whose index was loaded; empty collections are skipped.
+#[no_mangle]
+pub extern "C" fn chroma_preload_all(
+    client_handle: ChromaHandle,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    preloaded_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_preload_all", || {
+        let func_name = "chroma_preload_all";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            default_tenant
+        };
+
+        let database = if !database_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(database_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid database name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            default_database
+        };
+
+        let request =
+            match ListCollectionsRequest::try_new(tenant.clone(), database.clone(), None, 0) {
+                Ok(request) => request,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::ValidationError,
+                        "Failed to create list collections request",
+                        func_name,
+                        Some(&format!("Validation error: {:?}", e)),
+                    );
+                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, None);
+        let collections = match client.block_on_with_retry(
+            "list_collections",
+            scope,
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.list_collections(request).await }
+            },
+        ) {
+            Ok(collections) => collections,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to list collections",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+        };
+
+        let mut preloaded = 0;
+        for collection in collections {
+            let dimension = collection.dimension.map_or(0, |d| d as usize);
+            let collection_id = collection.collection_id;
+            let id = collection_id.to_string();
+            let scope = AccessScope::new(client_handle, &tenant, &database, Some(&id));
+            if let Err(e) = preload(&client, scope, &tenant, &database, collection_id, dimension) {
+                return e.report(error_out, func_name);
+            }
+            if dimension > 0 {
+                preloaded += 1;
+            }
+        }
+
+        if let Some(preloaded_out) = unsafe { preloaded_out.as_mut() } {
+            *preloaded_out = preloaded;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
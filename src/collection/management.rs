@@ -1,425 +1,837 @@
 // Collection management functions for ChromaDB C# bindings
 use chroma_types::{
-    CollectionConfiguration, CreateCollectionRequest, DatabaseName, GetCollectionRequest,
-    InternalCollectionConfiguration, Metadata,
+    Collection, CollectionConfiguration, CollectionUuid, CreateCollectionRequest, DatabaseName,
+    GetCollectionRequest, InternalCollectionConfiguration, Metadata,
 };
-use libc::{c_char, c_int};
-
-use crate::client::ChromaClient;
+use libc::{c_char, c_int, size_t};
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+use crate::client::{call_with_retry, identity_of, AccessScope};
+use crate::collection::includes::BUILTIN_DEFAULT_INCLUDES;
+use crate::collection::read_only::read_only_enabled;
+use crate::collection::soft_delete::soft_delete_enabled;
 use crate::collection::types::ChromaCollection;
-use crate::error::{set_error, set_success, ChromaError, ChromaErrorCode};
-use crate::utils::{c_str_to_string, DEFAULT_DATABASE, DEFAULT_TENANT};
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{client_from_handle, insert_collection, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_array_to_vec_string, c_str_to_string};
 
 /// Creates a new collection in ChromaDB
 #[no_mangle]
 pub extern "C" fn chroma_create_collection(
-    client_handle: *mut ChromaClient,
+    client_handle: ChromaHandle,
     name_ptr: *const c_char,
     config_json_ptr: *const c_char,
     metadata_json_ptr: *const c_char,
     get_or_create: bool,
     tenant_ptr: *const c_char,
     database_ptr: *const c_char,
-    collection_handle: *mut *mut ChromaCollection,
+    collection_handle: *mut ChromaHandle,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_create_collection";
-
-    // Check required arguments
-    if client_handle.is_null() || name_ptr.is_null() || collection_handle.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if name_ptr.is_null() {
-            "Collection name pointer is null"
-        } else {
-            "Collection handle output pointer is null"
-        };
+    ffi_boundary(error_out, "chroma_create_collection", || {
+        let func_name = "chroma_create_collection";
+
+        // Check required arguments
+        if client_handle == CHROMA_INVALID_HANDLE
+            || name_ptr.is_null()
+            || collection_handle.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if name_ptr.is_null() {
+                "Collection name pointer is null"
+            } else {
+                "Collection handle output pointer is null"
+            };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
-
-    // Parse collection name
-    let name = unsafe {
-        match c_str_to_string(name_ptr) {
-            Ok(s) => s,
-            Err(e) => {
-                set_error(
-                    error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Invalid collection name",
-                    func_name,
-                    Some(&e.to_string()),
-                );
-                return ChromaErrorCode::InvalidArgument as c_int;
-            }
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Parse tenant name
-    let tenant = if !tenant_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(tenant_ptr) {
+        // Parse collection name
+        let name = unsafe {
+            match c_str_to_string(name_ptr) {
                 Ok(s) => s,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid tenant name",
+                        "Invalid collection name",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
+        };
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        // Parse tenant name
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            default_tenant
+        };
+
+        // Parse database name
+        let database = if !database_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(database_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid database name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            default_database
+        };
+
+        let database_name = match DatabaseName::new(database.clone()) {
+            Some(n) => n,
+            None => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid database name (must be at least 3 characters)",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Parse configuration JSON if provided
+        let configuration_json = if !config_json_ptr.is_null() {
+            unsafe {
+                let config_json_str = match c_str_to_string(config_json_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid configuration JSON",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                };
+
+                match serde_json::from_str::<CollectionConfiguration>(&config_json_str) {
+                    Ok(config) => Some(config),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::ValidationError,
+                            "Failed to parse configuration JSON",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::ValidationError as c_int;
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        // Parse metadata JSON if provided
+        let metadata = if !metadata_json_ptr.is_null() {
+            unsafe {
+                let metadata_json_str = match c_str_to_string(metadata_json_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid metadata JSON",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                };
+
+                match serde_json::from_str::<Metadata>(&metadata_json_str) {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::ValidationError,
+                            "Failed to parse metadata JSON",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::ValidationError as c_int;
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
         }
-    } else {
-        DEFAULT_TENANT.to_string()
-    };
-
-    // Parse database name
-    let database = if !database_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(database_ptr) {
-                Ok(s) => s,
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Invalid database name",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+
+        // Convert configuration to internal format
+        let configuration = match configuration_json {
+            Some(c) => {
+                match InternalCollectionConfiguration::try_from_config(
+                    c,
+                    client.frontend.get_default_knn_index(),
+                    None,
+                    ) {
+                    Ok(config) => Some(config),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::ValidationError,
+                            "Invalid collection configuration",
+                            func_name,
+                            Some(&format!("Configuration validation error: {:?}", e)),
+                        );
+                        return ChromaErrorCode::ValidationError as c_int;
+                    }
                 }
             }
+            None => None,
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+
+        // Create the collection request
+        let request = match CreateCollectionRequest::try_new(
+            tenant.clone(),
+            database_name,
+            name,
+            metadata,
+            configuration,
+            None, // schema
+            get_or_create,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Execute the request
+        match client.block_on_with_retry("create_collection", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.create_collection(request).await }
+        }) {
+            Ok(collection) => {
+                // Register the collection wrapper
+                let handle = insert_collection(ChromaCollection {
+                    client: client.clone(),
+                    id: collection.collection_id,
+                    tenant: tenant.into(),
+                    database: database.into(),
+                    dimension: collection.dimension.map_or(0, |d| d as usize).into(),
+                    soft_delete: soft_delete_enabled(collection.metadata.as_ref()).into(),
+                    read_only: read_only_enabled(collection.metadata.as_ref()).into(),
+                    default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+                    principal: identity_of(client_handle),
+                });
+
+                // Set the output handle
+                unsafe {
+                    *collection_handle = handle;
+                }
+
+                // Return success
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to create collection", &e)
+                    .report(error_out, func_name)
+            }
         }
-    } else {
-        DEFAULT_DATABASE.to_string()
-    };
+    })
+}
+
+/// Gets a collection from ChromaDB
+#[no_mangle]
+pub extern "C" fn chroma_get_collection(
+    client_handle: ChromaHandle,
+    name_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    collection_handle: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_collection", || {
+        let func_name = "chroma_get_collection";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || name_ptr.is_null()
+            || collection_handle.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if name_ptr.is_null() {
+                "Collection name pointer is null"
+            } else {
+                "Collection handle output pointer is null"
+            };
 
-    let database_name = match DatabaseName::new(database.clone()) {
-        Some(n) => n,
-        None => {
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid database name (must be at least 3 characters)",
+                ChromaErrorCode::InvalidArgument,
+                message,
                 func_name,
                 None,
             );
-            return ChromaErrorCode::ValidationError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Parse configuration JSON if provided
-    let configuration_json = if !config_json_ptr.is_null() {
-        unsafe {
-            let config_json_str = match c_str_to_string(config_json_ptr) {
+        let name = unsafe {
+            match c_str_to_string(name_ptr) {
                 Ok(s) => s,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid configuration JSON",
+                        "Invalid collection name",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
-            };
+            }
+        };
 
-            match serde_json::from_str::<CollectionConfiguration>(&config_json_str) {
-                Ok(config) => Some(config),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Failed to parse configuration JSON",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::ValidationError as c_int;
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
             }
-        }
-    } else {
-        None
-    };
-
-    // Parse metadata JSON if provided
-    let metadata = if !metadata_json_ptr.is_null() {
-        unsafe {
-            let metadata_json_str = match c_str_to_string(metadata_json_ptr) {
-                Ok(s) => s,
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Invalid metadata JSON",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+        } else {
+            default_tenant
+        };
+
+        let database = if !database_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(database_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid database name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
-            };
+            }
+        } else {
+            default_database
+        };
 
-            match serde_json::from_str::<Metadata>(&metadata_json_str) {
-                Ok(metadata) => Some(metadata),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Failed to parse metadata JSON",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::ValidationError as c_int;
+        let database_name = match DatabaseName::new(database.clone()) {
+            Some(n) => n,
+            None => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid database name (must be at least 3 characters)",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+        let request = match GetCollectionRequest::try_new(tenant.clone(), database_name, name) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match client.block_on_with_retry("get_collection", scope, |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get_collection(request).await }
+        }) {
+            Ok(collection) => {
+                let handle = insert_collection(ChromaCollection {
+                    client: client.clone(),
+                    id: collection.collection_id,
+                    tenant: tenant.into(),
+                    database: database.into(),
+                    dimension: collection.dimension.map_or(0, |d| d as usize).into(),
+                    soft_delete: soft_delete_enabled(collection.metadata.as_ref()).into(),
+                    read_only: read_only_enabled(collection.metadata.as_ref()).into(),
+                    default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+                    principal: identity_of(client_handle),
+                });
+
+                unsafe {
+                    *collection_handle = handle;
                 }
+
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e)
+                    .report(error_out, func_name)
             }
         }
-    } else {
-        None
-    };
-
-    let client = unsafe { &*client_handle };
-
-    // Convert configuration to internal format
-    let configuration = match configuration_json {
-        Some(c) => {
-            match InternalCollectionConfiguration::try_from_config(
-                c,
-                client.frontend.get_default_knn_index(),
+    })
+}
+
+/// Gets several collections by name with one call, resolving them concurrently
+///
+/// `out_handles` must have room for `names_count` handles. Names that cannot be
+/// resolved get `CHROMA_INVALID_HANDLE`; when `out_errors` is not null it must have
+/// room for `names_count` errors and receives an error for each of them (null for
+/// names that resolved). The call itself succeeds even if some names failed.
+#[no_mangle]
+pub extern "C" fn chroma_get_collections(
+    client_handle: ChromaHandle,
+    names: *const *const c_char,
+    names_count: size_t,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    out_handles: *mut ChromaHandle,
+    out_errors: *mut *mut ChromaError,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_collections", || {
+        let func_name = "chroma_get_collections";
+
+        if client_handle == CHROMA_INVALID_HANDLE || names.is_null() || out_handles.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if names.is_null() {
+                "Collection names pointer is null"
+            } else {
+                "Collection handles output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
                 None,
-                ) {
-                Ok(config) => Some(config),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let names_vec = unsafe {
+            match c_array_to_vec_string(names, names_count) {
+                Ok(v) => v,
                 Err(e) => {
                     set_error(
                         error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Invalid collection configuration",
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert collection names array",
                         func_name,
-                        Some(&format!("Configuration validation error: {:?}", e)),
+                        Some(&e.to_string()),
                     );
-                    return ChromaErrorCode::ValidationError as c_int;
+                    return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-        None => None,
-    };
-
-    // Create the collection request
-    let request = match CreateCollectionRequest::try_new(
-        tenant.clone(),
-        database_name,
-        name,
-        metadata,
-        configuration,
-        None, // schema
-        get_or_create,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create collection request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
-
-    // Execute the request
-    let mut frontend = client.frontend.clone();
-
-    match client
-        .runtime
-        .block_on(async { frontend.create_collection(request).await })
-    {
-        Ok(collection) => {
-            // Create the collection wrapper
-            let collection_wrapper = Box::new(ChromaCollection {
-                id: collection.collection_id.0.to_string(),
-                tenant,
-                database,
-            });
+        };
 
-            // Set the output handle
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let tenant = if !tenant_ptr.is_null() {
             unsafe {
-                *collection_handle = Box::into_raw(collection_wrapper);
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
             }
+        } else {
+            default_tenant
+        };
 
-            // Return success
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
-        }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to create collection",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
-        }
-    }
-}
-
-/// Gets a collection from ChromaDB
-#[no_mangle]
-pub extern "C" fn chroma_get_collection(
-    client_handle: *mut ChromaClient,
-    name_ptr: *const c_char,
-    tenant_ptr: *const c_char,
-    database_ptr: *const c_char,
-    collection_handle: *mut *mut ChromaCollection,
-    error_out: *mut *mut ChromaError,
-) -> c_int {
-    let func_name = "chroma_get_collection";
-
-    if client_handle.is_null() || name_ptr.is_null() || collection_handle.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if name_ptr.is_null() {
-            "Collection name pointer is null"
+        let database = if !database_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(database_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid database name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
         } else {
-            "Collection handle output pointer is null"
+            default_database
         };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
-
-    let name = unsafe {
-        match c_str_to_string(name_ptr) {
-            Ok(s) => s,
-            Err(e) => {
+        let database_name = match DatabaseName::new(database.clone()) {
+            Some(n) => n,
+            None => {
                 set_error(
                     error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Invalid collection name",
+                    ChromaErrorCode::ValidationError,
+                    "Invalid database name (must be at least 3 characters)",
                     func_name,
-                    Some(&e.to_string()),
+                    None,
                 );
-                return ChromaErrorCode::InvalidArgument as c_int;
+                return ChromaErrorCode::ValidationError as c_int;
             }
-        }
-    };
+        };
 
-    let tenant = if !tenant_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(tenant_ptr) {
-                Ok(s) => s,
+        // The lookups run at once but count as one call against the concurrency limits
+        let _permit = match client.concurrency.acquire("get_collections") {
+            Ok(permit) => permit,
+            Err(reason) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::Busy,
+                    "Client is busy",
+                    func_name,
+                    Some(&reason),
+                );
+                return ChromaErrorCode::Busy as c_int;
+            }
+        };
+
+        // Resolve every name on the client runtime at once, keeping the input order
+        let policy = client.retry_policy();
+        let started = Instant::now();
+        let results = client.runtime.block_on(async {
+            let mut tasks = JoinSet::new();
+            for (index, name) in names_vec.into_iter().enumerate() {
+                // Each lookup is authorized on its own, so a denied name fails alone
+                let scope = AccessScope::new(client_handle, &tenant, &database, Some(&name));
+                let denied = client.authorize("get_collection", &scope).err();
+                let request =
+                    GetCollectionRequest::try_new(tenant.clone(), database_name.clone(), name);
+                let frontend = client.frontend.clone();
+                tasks.spawn(async move {
+                    let result = match (denied, request) {
+                        (Some(reason), _) => Err(FfiError::new(
+                            ChromaErrorCode::PermissionDenied,
+                            "Permission denied",
+                            Some(reason),
+                        )),
+                        (None, Ok(request)) => call_with_retry(policy, &frontend, |mut frontend| {
+                            let request = request.clone();
+                            async move { frontend.get_collection(request).await }
+                        })
+                        .await
+                        .map_err(|e| {
+                            FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e)
+                        }),
+                        (None, Err(e)) => Err(FfiError::new(
+                            ChromaErrorCode::ValidationError,
+                            "Failed to create get collection request",
+                            Some(format!("Validation error: {:?}", e)),
+                        )),
+                    };
+                    (index, result)
+                });
+            }
+
+            let mut results: Vec<Option<Result<Collection, FfiError>>> =
+                (0..names_count).map(|_| None).collect();
+            while let Some(joined) = tasks.join_next().await {
+                if let Ok((index, result)) = joined {
+                    results[index] = Some(result);
+                }
+            }
+            results
+        });
+        let failed = results.iter().any(|r| !matches!(r, Some(Ok(_))));
+        client.metrics.record("get_collections", started.elapsed(), !failed);
+
+        for (index, result) in results.into_iter().enumerate() {
+            let result = result.unwrap_or_else(|| {
+                Err(FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Collection lookup task failed",
+                    None,
+                ))
+            });
+
+            let handle = match result {
+                Ok(collection) => {
+                    if !out_errors.is_null() {
+                        unsafe { *out_errors.add(index) = std::ptr::null_mut() };
+                    }
+                    insert_collection(ChromaCollection {
+                        client: client.clone(),
+                        id: collection.collection_id,
+                        tenant: tenant.as_str().into(),
+                        database: database.as_str().into(),
+                        dimension: collection.dimension.map_or(0, |d| d as usize).into(),
+                        soft_delete: soft_delete_enabled(collection.metadata.as_ref()).into(),
+                        read_only: read_only_enabled(collection.metadata.as_ref()).into(),
+                        default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+                        principal: identity_of(client_handle),
+                    })
+                }
                 Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Invalid tenant name",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+                    if !out_errors.is_null() {
+                        e.report(unsafe { out_errors.add(index) }, func_name);
+                    }
+                    CHROMA_INVALID_HANDLE
                 }
+            };
+
+            unsafe {
+                *out_handles.add(index) = handle;
             }
         }
-    } else {
-        DEFAULT_TENANT.to_string()
-    };
 
-    let database = if !database_ptr.is_null() {
-        unsafe {
-            match c_str_to_string(database_ptr) {
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Gets a collection from ChromaDB by its UUID
+///
+/// Lets applications that persist collection IDs reattach to a collection without
+/// storing its name. Fails with `NotFound` if the collection belongs to another
+/// tenant or database.
+#[no_mangle]
+pub extern "C" fn chroma_get_collection_by_id(
+    client_handle: ChromaHandle,
+    id_ptr: *const c_char,
+    tenant_ptr: *const c_char,
+    database_ptr: *const c_char,
+    collection_handle: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_collection_by_id", || {
+        let func_name = "chroma_get_collection_by_id";
+
+        if client_handle == CHROMA_INVALID_HANDLE
+            || id_ptr.is_null()
+            || collection_handle.is_null()
+        {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else if id_ptr.is_null() {
+                "Collection ID pointer is null"
+            } else {
+                "Collection handle output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let id = unsafe {
+            match c_str_to_string(id_ptr) {
                 Ok(s) => s,
                 Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Invalid database name",
+                        "Invalid collection ID",
                         func_name,
                         Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-    } else {
-        DEFAULT_DATABASE.to_string()
-    };
+        };
 
-    let database_name = match DatabaseName::new(database.clone()) {
-        Some(n) => n,
-        None => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid database name (must be at least 3 characters)",
-                func_name,
-                None,
-            );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
+        let collection_id = match uuid::Uuid::parse_str(&id) {
+            Ok(id) => CollectionUuid(id),
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidUuid,
+                    "Invalid collection UUID",
+                    func_name,
+                    Some(&format!("UUID parse error: {}", e)),
+                );
+                return ChromaErrorCode::InvalidUuid as c_int;
+            }
+        };
 
-    let client = unsafe { &*client_handle };
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
 
-    let request = match GetCollectionRequest::try_new(tenant.clone(), database_name, name) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create get collection request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
-
-    let mut frontend = client.frontend.clone();
-
-    match client
-        .runtime
-        .block_on(async { frontend.get_collection(request).await })
-    {
-        Ok(collection) => {
-            let collection_wrapper = Box::new(ChromaCollection {
-                id: collection.collection_id.0.to_string(),
-                tenant,
-                database,
-            });
+        let (default_tenant, default_database) = client.default_tenant_database();
+        let tenant = if !tenant_ptr.is_null() {
+            unsafe {
+                match c_str_to_string(tenant_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid tenant name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            default_tenant
+        };
 
+        let database = if !database_ptr.is_null() {
             unsafe {
-                *collection_handle = Box::into_raw(collection_wrapper);
+                match c_str_to_string(database_ptr) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Invalid database name",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
             }
+        } else {
+            default_database
+        };
 
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
-        }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::NotFound,
-                "Collection not found",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::NotFound as c_int
+        let scope = AccessScope::new(client_handle, &tenant, &database, Some(&id));
+        match client.block_on_with_retry("get_collection", scope, |mut frontend| async move {
+            frontend.get_cached_collection(collection_id).await
+        }) {
+            Ok(collection) if collection.tenant == tenant && collection.database == database => {
+                let handle = insert_collection(ChromaCollection {
+                    client: client.clone(),
+                    id: collection.collection_id,
+                    tenant: tenant.into(),
+                    database: database.into(),
+                    dimension: collection.dimension.map_or(0, |d| d as usize).into(),
+                    soft_delete: soft_delete_enabled(collection.metadata.as_ref()).into(),
+                    read_only: read_only_enabled(collection.metadata.as_ref()).into(),
+                    default_includes: BUILTIN_DEFAULT_INCLUDES.into(),
+                    principal: identity_of(client_handle),
+                });
+
+                unsafe {
+                    *collection_handle = handle;
+                }
+
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Ok(collection) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::NotFound,
+                    "Collection not found",
+                    func_name,
+                    Some(&format!(
+                        "Collection {} belongs to tenant '{}' and database '{}'",
+                        id, collection.tenant, collection.database
+                    )),
+                );
+                ChromaErrorCode::NotFound as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e)
+                    .report(error_out, func_name)
+            }
         }
-    }
+    })
 }
@@ -1,8 +1,68 @@
 // Collection module for ChromaDB C# bindings
+mod aggregate;
+mod aliases;
+mod batch;
+mod bench;
+mod blobs;
+mod bulk;
+mod changes;
+mod conflict;
+mod copy;
+mod exists;
+mod explain;
+mod filterable;
+mod fixtures;
+mod ids_only;
+mod includes;
+pub(crate) mod filters;
+mod maintenance;
 mod management;
+mod naming;
 mod operations;
+mod ordering;
+mod patch;
+mod prepared;
+mod query_options;
+mod read_only;
+mod rerank;
+mod schema;
+mod single;
+mod soft_delete;
+mod transaction;
+mod ttl;
 mod types;
+mod validate;
+mod versions;
 
+pub use aggregate::*;
+pub use aliases::*;
+pub use batch::*;
+pub use bench::*;
+pub use blobs::*;
+pub use bulk::*;
+pub use changes::*;
+pub use conflict::*;
+pub use copy::*;
+pub use exists::*;
+pub use explain::*;
+pub use filterable::*;
+pub use fixtures::*;
+pub use ids_only::*;
+pub use includes::*;
+pub use maintenance::*;
 pub use management::*;
+pub use naming::*;
 pub use operations::*;
+pub use ordering::*;
+pub use patch::*;
+pub use prepared::*;
+pub use query_options::*;
+pub use read_only::*;
+pub use rerank::*;
+pub use schema::*;
+pub use single::*;
+pub use soft_delete::*;
+pub use transaction::*;
+pub use ttl::*;
 pub use types::*;
+pub use validate::*;
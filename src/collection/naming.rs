@@ -0,0 +1,111 @@
+// Local checks of collection names against the backend's naming rules
+use libc::{c_char, c_int};
+use std::net::Ipv4Addr;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::utils::c_str_to_string;
+
+/// Shortest collection name the backend accepts
+const MIN_NAME_LENGTH: usize = 3;
+
+/// Longest collection name the backend accepts
+const MAX_NAME_LENGTH: usize = 512;
+
+/// Checks `name` against the rules the backend applies when a collection is created, and
+/// describes the first rule it breaks
+fn check_collection_name(name: &str) -> Result<(), String> {
+    let length = name.chars().count();
+    if !(MIN_NAME_LENGTH..=MAX_NAME_LENGTH).contains(&length) {
+        return Err(format!(
+            "Expected {} to {} characters, got {}",
+            MIN_NAME_LENGTH, MAX_NAME_LENGTH, length
+        ));
+    }
+
+    if let Some((position, c)) = name
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+    {
+        return Err(format!(
+            "Character {:?} at position {} is not allowed; use only [a-zA-Z0-9._-]",
+            c, position
+        ));
+    }
+
+    let is_alphanumeric = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    if !is_alphanumeric(name.chars().next()) {
+        return Err("Expected the name to start with a letter or digit".to_string());
+    }
+    if !is_alphanumeric(name.chars().last()) {
+        return Err("Expected the name to end with a letter or digit".to_string());
+    }
+
+    if let Some(position) = name.find("..") {
+        return Err(format!(
+            "Consecutive periods at position {} are not allowed",
+            position
+        ));
+    }
+
+    if name.parse::<Ipv4Addr>().is_ok() {
+        return Err("Expected a name that is not an IPv4 address".to_string());
+    }
+
+    Ok(())
+}
+
+/// Checks a collection name locally against the rules the backend applies on create: 3 to
+/// 512 characters from `[a-zA-Z0-9._-]`, starting and ending with a letter or digit, without
+/// two consecutive periods, and not an IPv4 address.
+///
+/// Returns success for a valid name. Otherwise fails with `ValidationError`, and the error
+/// details describe the first rule broken, such as the position of a disallowed character,
+/// so a UI can explain it before attempting a create.
+#[no_mangle]
+pub extern "C" fn chroma_validate_collection_name(
+    name_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_validate_collection_name", || {
+        let func_name = "chroma_validate_collection_name";
+
+        if name_ptr.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection name pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let name = match unsafe { c_str_to_string(name_ptr) } {
+            Ok(name) => name,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid collection name",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        match check_collection_name(&name) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(reason) => FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid collection name",
+                Some(reason),
+            )
+            .report(error_out, func_name),
+        }
+    })
+}
@@ -1,577 +1,988 @@
 // Collection operations for ChromaDB C# bindings
 use chroma_types::{
-    AddCollectionRecordsRequest, CollectionUuid, CountRequest, DeleteCollectionRecordsRequest,
-    GetRequest, IncludeList, Metadata, QueryRequest, RawWhereFields,
+    AddCollectionRecordsRequest, CountRequest, DeleteCollectionRecordsRequest,
+    GetRequest, GetResponse, IncludeList, Metadata, MetadataValue, QueryRequest, QueryResponse,
+    RawWhereFields,
     plan::ReadLevel,
-    UpdateCollectionRecordsRequest, UpdateMetadata, UpsertCollectionRecordsRequest,
+    UpdateCollectionRecordsRequest, UpdateMetadata, UpsertCollectionRecordsRequest, Where,
 };
 use libc::{c_char, c_float, c_int, c_uint, size_t};
-use std::ptr;
-use uuid;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use uuid::Uuid;
 
-use crate::client::ChromaClient;
+use crate::abi::read_sized;
+use crate::collection::conflict::{
+    conflict_error, retain_flagged, to_update_metadata, ChromaAddStats, OnConflict,
+};
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::ordering::{ChromaOrderBy, OrderBy};
+use crate::collection::query_options::{drop_unrequested, ChromaQueryOptions, QueryOptions};
 use crate::collection::types::ChromaCollection;
-use crate::error::{set_error, set_success, ChromaError, ChromaErrorCode};
-use crate::types::ChromaQueryResult;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::{
+    metadata_entries_to_c_array, ChromaQueryResult, ChromaResultSet, CHROMA_METADATA_FORMAT_JSON,
+    CHROMA_METADATA_FORMAT_TYPED,
+};
 use crate::utils::{
     c_array_to_vec_f32, c_array_to_vec_string, c_str_to_string, vec_f32_to_c_array,
-    vec_string_to_c_array,
+    vec_i64_to_c_array, vec_string_to_c_array,
 };
 
+/// Serializes a record's metadata, keeping only `keys` when a projection is given
+fn metadata_to_json(metadata: Option<&Metadata>, keys: Option<&[String]>) -> String {
+    match (metadata, keys) {
+        (Some(metadata), Some(keys)) => {
+            let projected: HashMap<&String, &MetadataValue> = metadata
+                .iter()
+                .filter(|(key, _)| keys.contains(*key))
+                .collect();
+            serde_json::to_string(&projected).unwrap_or_default()
+        }
+        (Some(metadata), None) => serde_json::to_string(metadata).unwrap_or_default(),
+        (None, _) => String::new(),
+    }
+}
+
+/// Reads the IDs of the records matching `ids` and `where_filter`, without their contents
+pub(crate) fn matching_ids(
+    collection: &ChromaCollection,
+    ids: Option<Vec<String>>,
+    where_filter: Option<Where>,
+) -> Result<Vec<String>, FfiError> {
+    matching_ids_page(collection, ids, where_filter, None, 0)
+}
+
+/// Reads one page of the IDs of the records matching `ids` and `where_filter`
+pub(crate) fn matching_ids_page(
+    collection: &ChromaCollection,
+    ids: Option<Vec<String>>,
+    where_filter: Option<Where>,
+    limit: Option<u32>,
+    offset: u32,
+) -> Result<Vec<String>, FfiError> {
+    let include_list = IncludeList::try_from(Vec::<String>::new()).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        ids,
+        where_filter,
+        limit,
+        offset,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    collection
+        .client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map(|response| response.ids)
+        .map_err(|e| {
+            FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to read matching documents",
+                &e,
+            )
+        })
+}
+
+/// Drops the records of a get response past the first `len`
+fn truncate_response(response: &mut GetResponse, len: usize) {
+    response.ids.truncate(len);
+    if let Some(embeddings) = response.embeddings.as_mut() {
+        embeddings.truncate(len);
+    }
+    if let Some(documents) = response.documents.as_mut() {
+        documents.truncate(len);
+    }
+    if let Some(uris) = response.uris.as_mut() {
+        uris.truncate(len);
+    }
+    if let Some(metadatas) = response.metadatas.as_mut() {
+        metadatas.truncate(len);
+    }
+}
+
 /// Adds documents to a collection
+///
+/// `on_conflict` decides what happens to IDs that already exist: `CHROMA_ON_CONFLICT_FAIL`
+/// rejects the whole add, `CHROMA_ON_CONFLICT_IGNORE` adds only the new records and
+/// `CHROMA_ON_CONFLICT_OVERWRITE` writes every record as `chroma_upsert` would. When
+/// `stats_out` is not null it receives how many records were inserted, skipped and overwritten.
+///
+/// Pass null `ids` to have `ids_count` UUIDv7 IDs generated, which sort by creation time;
+/// when `generated_ids_out` is not null it receives them in record order and is released
+/// with `chroma_free_result_set`. It is left empty when IDs are passed in.
 #[no_mangle]
 pub extern "C" fn chroma_add(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+    collection_handle: ChromaHandle,
     ids: *const *const c_char,
     ids_count: size_t,
     embeddings: *const *const c_float,
     embedding_dim: size_t,
     metadatas_json: *const *const c_char,
     documents: *const *const c_char,
+    on_conflict: c_int,
+    stats_out: *mut ChromaAddStats,
+    generated_ids_out: *mut ChromaResultSet,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_add";
-
-    // Check required parameters
-    if client_handle.is_null() || collection_handle.is_null() || ids.is_null() || ids_count == 0 {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
-        } else if ids.is_null() {
-            "IDs pointer is null"
-        } else {
-            "IDs count is zero"
-        };
+    ffi_boundary(error_out, "chroma_add", || {
+        let func_name = "chroma_add";
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        // Check required parameters
+        if collection_handle == CHROMA_INVALID_HANDLE || ids_count == 0 {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "IDs count is zero"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+        let on_conflict = match OnConflict::from_ffi(on_conflict) {
+            Ok(on_conflict) => on_conflict,
+            Err(e) => return e.report(error_out, func_name),
+        };
 
-    // Convert C string array to Rust vector
-    let ids_vec = unsafe {
-        match c_array_to_vec_string(ids, ids_count) {
-            Ok(v) => v,
-            Err(e) => {
-                set_error(
-                    error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Failed to convert IDs array",
-                    func_name,
-                    Some(&e.to_string()),
-                );
-                return ChromaErrorCode::InvalidArgument as c_int;
-            }
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
         }
-    };
+        let client = &collection.client;
 
-    // Convert C embedding array to Rust vector (required for add)
-    let embeddings_vec = if !embeddings.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let embedding_ptr = *embeddings.add(i);
-                if !embedding_ptr.is_null() {
-                    result.push(c_array_to_vec_f32(embedding_ptr, embedding_dim));
-                } else {
+        if let Some(generated_ids_out) = unsafe { generated_ids_out.as_mut() } {
+            *generated_ids_out = ChromaResultSet::empty();
+        }
+
+        // Convert C string array to Rust vector, or generate the IDs
+        let generate_ids = ids.is_null();
+        let ids_vec = if generate_ids {
+            (0..ids_count).map(|_| Uuid::now_v7().to_string()).collect()
+        } else {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => v,
+                Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Embedding pointer is null",
+                        "Failed to convert IDs array",
                         func_name,
-                        Some(&format!("Null embedding at index {}", i)),
+                        Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-        result
-    } else {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Embeddings pointer is null",
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    };
-
-    // Convert metadata JSON strings to Rust vector
-    let metadatas_vec = if !metadatas_json.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let metadata_ptr = *metadatas_json.add(i);
-                if !metadata_ptr.is_null() {
-                    let metadata_str = match c_str_to_string(metadata_ptr) {
-                        Ok(s) => s,
-                        Err(e) => {
-                            set_error(
-                                error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert metadata string",
-                                func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
-                            );
-                            return ChromaErrorCode::InvalidArgument as c_int;
-                        }
-                    };
+        };
+        let generated_ids = generate_ids.then(|| ids_vec.clone());
 
-                    if metadata_str.is_empty() {
-                        result.push(None);
+        // Convert C embedding array to Rust vector (required for add)
+        let embeddings_vec = if !embeddings.is_null() {
+            if let Err(e) = collection.check_dimension(embedding_dim) {
+                return e.report(error_out, func_name);
+            }
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let embedding_ptr = *embeddings.add(i);
+                    if !embedding_ptr.is_null() {
+                        result.push(c_array_to_vec_f32(embedding_ptr, embedding_dim));
                     } else {
-                        match serde_json::from_str::<Metadata>(&metadata_str) {
-                            Ok(metadata) => result.push(Some(metadata)),
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Embedding pointer is null",
+                            func_name,
+                            Some(&format!("Null embedding at index {}", i)),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+            result
+        } else {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Embeddings pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        };
+
+        // Convert metadata JSON strings to Rust vector
+        let metadatas_vec = if !metadatas_json.is_null() {
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let metadata_ptr = *metadatas_json.add(i);
+                    if !metadata_ptr.is_null() {
+                        let metadata_str = match c_str_to_string(metadata_ptr) {
+                            Ok(s) => s,
                             Err(e) => {
                                 set_error(
                                     error_out,
-                                    ChromaErrorCode::ValidationError,
-                                    "Invalid metadata JSON",
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert metadata string",
                                     func_name,
-                                    Some(&format!("Error parsing metadata at index {}: {}", i, e)),
+                                    Some(&format!("Error at index {}: {}", i, e)),
                                 );
-                                return ChromaErrorCode::ValidationError as c_int;
+                                return ChromaErrorCode::InvalidArgument as c_int;
+                            }
+                        };
+
+                        if metadata_str.is_empty() {
+                            result.push(None);
+                        } else {
+                            match serde_json::from_str::<Metadata>(&metadata_str) {
+                                Ok(metadata) => result.push(Some(metadata)),
+                                Err(e) => {
+                                    set_error(
+                                        error_out,
+                                        ChromaErrorCode::ValidationError,
+                                        "Invalid metadata JSON",
+                                        func_name,
+                                        Some(&format!(
+                                            "Error parsing metadata at index {}: {}",
+                                            i, e
+                                        )),
+                                    );
+                                    return ChromaErrorCode::ValidationError as c_int;
+                                }
                             }
                         }
+                    } else {
+                        result.push(None);
                     }
-                } else {
-                    result.push(None);
                 }
             }
-        }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Convert document strings to Rust vector
-    let documents_vec = if !documents.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let document_ptr = *documents.add(i);
-                if !document_ptr.is_null() {
-                    match c_str_to_string(document_ptr) {
-                        Ok(s) => {
-                            if s.is_empty() {
-                                result.push(None);
-                            } else {
-                                result.push(Some(s));
+            Some(result)
+        } else {
+            None
+        };
+
+        // Convert document strings to Rust vector
+        let documents_vec = if !documents.is_null() {
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let document_ptr = *documents.add(i);
+                    if !document_ptr.is_null() {
+                        match c_str_to_string(document_ptr) {
+                            Ok(s) => {
+                                if s.is_empty() {
+                                    result.push(None);
+                                } else {
+                                    result.push(Some(s));
+                                }
+                            }
+                            Err(e) => {
+                                set_error(
+                                    error_out,
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert document string",
+                                    func_name,
+                                    Some(&format!("Error at index {}: {}", i, e)),
+                                );
+                                return ChromaErrorCode::InvalidArgument as c_int;
                             }
                         }
+                    } else {
+                        result.push(None);
+                    }
+                }
+            }
+            Some(result)
+        } else {
+            None
+        };
+
+        let collection_id = collection.id;
+
+        if let Err(e) = collection.validate_new_metadatas(ids_count, metadatas_vec.as_deref()) {
+            return e.report(error_out, func_name);
+        }
+
+        // Look up which IDs already exist; overwrites only need this to report stats, and
+        // generated IDs are new
+        let needs_lookup =
+            !generate_ids && (on_conflict != OnConflict::Overwrite || !stats_out.is_null());
+        // Held until the write is done, so no other add lands between lookup and write
+        let _write = needs_lookup.then(|| client.write_locks.lock(&collection_id.to_string()));
+        let existing: HashSet<String> = if needs_lookup {
+            match matching_ids(&collection, Some(ids_vec.clone()), None) {
+                Ok(ids) => ids.into_iter().collect(),
+                Err(e) => return e.report(error_out, func_name),
+            }
+        } else {
+            HashSet::new()
+        };
+
+        let mut stats = ChromaAddStats::default();
+        let result = match on_conflict {
+            OnConflict::Fail if !existing.is_empty() => {
+                return conflict_error(&existing).report(error_out, func_name);
+            }
+            OnConflict::Fail | OnConflict::Ignore => {
+                let keep: Vec<bool> = ids_vec.iter().map(|id| !existing.contains(id)).collect();
+                stats.inserted = keep.iter().filter(|&&keep| keep).count();
+                stats.skipped = ids_count - stats.inserted;
+                let ids_vec = retain_flagged(ids_vec, &keep);
+                let embeddings_vec = retain_flagged(embeddings_vec, &keep);
+                let documents_vec = documents_vec.map(|v| retain_flagged(v, &keep));
+                let metadatas_vec = metadatas_vec.map(|v| retain_flagged(v, &keep));
+
+                if ids_vec.is_empty() {
+                    Ok(())
+                } else {
+                    let request = match AddCollectionRecordsRequest::try_new(
+                        collection.tenant.to_string(),
+                        collection.database.to_string(),
+                        collection_id,
+                        ids_vec,
+                        embeddings_vec,
+                        documents_vec,
+                        None, // uris
+                        metadatas_vec,
+                    ) {
+                        Ok(req) => req,
                         Err(e) => {
                             set_error(
                                 error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert document string",
+                                ChromaErrorCode::ValidationError,
+                                "Failed to create add request",
                                 func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
+                                Some(&format!("Validation error: {:?}", e)),
                             );
-                            return ChromaErrorCode::InvalidArgument as c_int;
+                            return ChromaErrorCode::ValidationError as c_int;
                         }
+                    };
+
+                    client
+                        .block_on_with_retry(
+                            "add",
+                            collection
+                                .scope()
+                                .with_records(Some(request.ids.len()))
+                                .with_dimension((!embeddings.is_null()).then_some(embedding_dim)),
+                            |mut frontend| {
+                                let request = request.clone();
+                                async move { frontend.add(request).await }
+                            },
+                        )
+                        .map(|_| ())
+                        .map_err(|e| {
+                            FfiError::backend(
+                                ChromaErrorCode::InternalError,
+                                "Failed to add documents",
+                                &e,
+                            )
+                        })
+                }
+            }
+            OnConflict::Overwrite => {
+                stats.overwritten = existing.len();
+                stats.inserted = ids_count - existing.len();
+                let metadatas_vec = metadatas_vec.map(|metadatas| {
+                    metadatas
+                        .into_iter()
+                        .map(|metadata| metadata.map(to_update_metadata))
+                        .collect()
+                });
+
+                let request = match UpsertCollectionRecordsRequest::try_new(
+                    collection.tenant.to_string(),
+                    collection.database.to_string(),
+                    collection_id,
+                    ids_vec,
+                    embeddings_vec,
+                    documents_vec,
+                    None, // uris
+                    metadatas_vec,
+                ) {
+                    Ok(req) => req,
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::ValidationError,
+                            "Failed to create upsert request",
+                            func_name,
+                            Some(&format!("Validation error: {:?}", e)),
+                        );
+                        return ChromaErrorCode::ValidationError as c_int;
                     }
-                } else {
-                    result.push(None);
+                };
+
+                client
+                    .block_on_with_retry(
+                        "upsert",
+                        collection
+                            .scope()
+                            .with_records(Some(request.ids.len()))
+                            .with_dimension((!embeddings.is_null()).then_some(embedding_dim)),
+                        |mut frontend| {
+                            let request = request.clone();
+                            async move { frontend.upsert(request).await }
+                        },
+                    )
+                    .map(|_| ())
+                    .map_err(|e| {
+                        FfiError::backend(
+                            ChromaErrorCode::InternalError,
+                            "Failed to add documents",
+                            &e,
+                        )
+                    })
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                if !embeddings.is_null() {
+                    collection.record_dimension(embedding_dim);
+                }
+                client.metrics.add_records(stats.inserted + stats.overwritten);
+                if let Some(stats_out) = unsafe { stats_out.as_mut() } {
+                    *stats_out = stats;
                 }
+                if let (Some(out), Some(ids)) =
+                    (unsafe { generated_ids_out.as_mut() }, generated_ids)
+                {
+                    *out = ChromaResultSet::from_ids(ids);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
             }
+            Err(e) => e.report(error_out, func_name),
         }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
+    })
+}
+
+/// Counts the number of documents in a collection
+#[no_mangle]
+pub extern "C" fn chroma_count(
+    collection_handle: ChromaHandle,
+    result: *mut c_uint,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_count", || {
+        let func_name = "chroma_count";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || result.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Result pointer is null"
+            };
+
             set_error(
                 error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
+                ChromaErrorCode::InvalidArgument,
+                message,
                 func_name,
-                Some(&format!("UUID parse error: {}", e)),
+                None,
             );
-            return ChromaErrorCode::InvalidUuid as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
-
-    // Create request
-    let request = match AddCollectionRecordsRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ids_vec,
-        embeddings_vec,
-        documents_vec,
-        None, // uris
-        metadatas_vec,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create add request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+
+        let collection_id = collection.id;
+
+        // Soft-deleted records are still stored, so they cannot be counted by the backend
+        if collection.soft_delete.load(Ordering::Relaxed) {
+            return match matching_ids(&collection, None, collection.visible(None)) {
+                Ok(ids) => {
+                    unsafe {
+                        *result = ids.len() as c_uint;
+                    }
+                    set_success(error_out);
+                    ChromaErrorCode::Success as c_int
+                }
+                Err(e) => e.report(error_out, func_name),
+            };
         }
-    };
-
-    // Execute request
-    let mut frontend = client.frontend.clone();
-    match client
-        .runtime
-        .block_on(async { frontend.add(request).await })
-    {
-        Ok(_) => {
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
+
+        // Create count request
+        let request = match CountRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ReadLevel::default(),
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create count request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
         }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to add documents",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
+
+        // Execute request
+        match client.block_on_with_retry("count", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.count(request).await }
+        }) {
+            Ok(count_response) => {
+                unsafe {
+                    *result = count_response;
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to count documents", &e)
+                    .report(error_out, func_name)
+            }
         }
-    }
+    })
 }
 
-/// Counts the number of documents in a collection
+/// Counts the documents of a collection that match metadata and document filters.
+///
+/// Only the IDs of matching records are read inside the library; nothing else is loaded or
+/// returned. With both filters null this is `chroma_count`.
 #[no_mangle]
-pub extern "C" fn chroma_count(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+pub extern "C" fn chroma_count_where(
+    collection_handle: ChromaHandle,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
     result: *mut c_uint,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_count";
+    ffi_boundary(error_out, "chroma_count_where", || {
+        let func_name = "chroma_count_where";
 
-    if client_handle.is_null() || collection_handle.is_null() || result.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
-        } else {
-            "Result pointer is null"
-        };
-
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        if where_filter_json.is_null() && where_document_filter.is_null() {
+            return chroma_count(collection_handle, result, error_out);
+        }
 
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+        if collection_handle == CHROMA_INVALID_HANDLE || result.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Result pointer is null"
+            };
 
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
-                func_name,
-                Some(&format!("UUID parse error: {}", e)),
-            );
-            return ChromaErrorCode::InvalidUuid as c_int;
-        }
-    };
-
-    // Create count request
-    let request = match CountRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ReadLevel::default(),
-    ) {
-        Ok(req) => req,
-        Err(e) => {
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create count request",
+                ChromaErrorCode::InvalidArgument,
+                message,
                 func_name,
-                Some(&format!("Validation error: {:?}", e)),
+                None,
             );
-            return ChromaErrorCode::ValidationError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
-
-    // Execute request
-    let mut frontend = client.frontend.clone();
-    match client
-        .runtime
-        .block_on(async { frontend.count(request).await })
-    {
-        Ok(count_response) => {
-            unsafe {
-                *result = count_response;
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let where_filter =
+            match unsafe { parse_where_ptrs(where_filter_json, where_document_filter) } {
+                Ok(w) => w,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+        match matching_ids(&collection, None, collection.visible(where_filter)) {
+            Ok(ids) => {
+                unsafe {
+                    *result = ids.len() as c_uint;
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
             }
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
-        }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to count documents",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
+            Err(e) => e.report(error_out, func_name),
         }
-    }
+    })
+}
+
+/// Repeats the single entry of a filter-only update for each of `count` records
+fn repeat_entry<T: Clone>(values: Option<Vec<T>>, count: usize) -> Option<Vec<T>> {
+    values.map(|values| vec![values[0].clone(); count])
 }
 
 /// Updates documents in a collection
+///
+/// With `where_filter_json` or `where_document_filter` set the update is conditional: only
+/// the records matching the filters are updated and the entries of other IDs are skipped.
+/// `ids` may then be null to update every matching record, in which case the embedding,
+/// metadata and document arrays hold a single entry applied to each of them and `ids_count`
+/// is ignored. Soft-deleted records never match.
+///
+/// `expected_versions`, when not null, holds one version token from `chroma_get` per ID
+/// (a negative entry skips that record) and requires `ids`. If any record is at another
+/// version nothing is updated and the call fails with `ConflictError`, whose details list
+/// the mismatched IDs. The check and the write hold the collection's write lock, so no other
+/// add, update, upsert, delete or batch through the client lands in between.
+///
+/// When `updated_ids_out` is not null it receives the IDs the update was applied to;
+/// release them with `chroma_free_result_set`.
 #[no_mangle]
 pub extern "C" fn chroma_update(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+    collection_handle: ChromaHandle,
     ids: *const *const c_char,
     ids_count: size_t,
     embeddings: *const *const c_float,
     embedding_dim: size_t,
     metadatas_json: *const *const c_char,
     documents: *const *const c_char,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    expected_versions: *const i64,
+    updated_ids_out: *mut ChromaResultSet,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_update";
-
-    if client_handle.is_null() || collection_handle.is_null() || ids.is_null() || ids_count == 0 {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
-        } else if ids.is_null() {
-            "IDs pointer is null"
-        } else {
-            "IDs count is zero"
-        };
+    ffi_boundary(error_out, "chroma_update", || {
+        let func_name = "chroma_update";
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        if let Some(updated_ids) = unsafe { updated_ids_out.as_mut() } {
+            *updated_ids = ChromaResultSet::empty();
+        }
 
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+        let filtered = !where_filter_json.is_null() || !where_document_filter.is_null();
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || (ids.is_null() && !filtered)
+            || (ids.is_null() && !expected_versions.is_null())
+            || (!ids.is_null() && ids_count == 0)
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() && !filtered {
+                "Either document IDs or filter criteria must be specified"
+            } else if ids.is_null() {
+                "Expected versions require document IDs"
+            } else {
+                "IDs count is zero"
+            };
 
-    // Convert C string array to Rust vector
-    let ids_vec = unsafe {
-        match c_array_to_vec_string(ids, ids_count) {
-            Ok(v) => v,
-            Err(e) => {
-                set_error(
-                    error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Failed to convert IDs array",
-                    func_name,
-                    Some(&e.to_string()),
-                );
-                return ChromaErrorCode::InvalidArgument as c_int;
-            }
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
 
-    // Convert C embedding array to Rust vector
-    let embeddings_vec = if !embeddings.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let embedding_ptr = *embeddings.add(i);
-                if !embedding_ptr.is_null() {
-                    let vec = c_array_to_vec_f32(embedding_ptr, embedding_dim);
-                    result.push(Some(vec));
-                } else {
-                    result.push(None);
-                }
-            }
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
         }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Convert metadata JSON strings to Rust vector
-    let metadatas_vec = if !metadatas_json.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let metadata_ptr = *metadatas_json.add(i);
-                if !metadata_ptr.is_null() {
-                    let metadata_str = match c_str_to_string(metadata_ptr) {
-                        Ok(s) => s,
-                        Err(e) => {
-                            set_error(
-                                error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert metadata string",
-                                func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
-                            );
-                            return ChromaErrorCode::InvalidArgument as c_int;
-                        }
-                    };
+        let client = &collection.client;
 
-                    if metadata_str.is_empty() {
-                        result.push(None);
-                    } else {
-                        match serde_json::from_str::<UpdateMetadata>(&metadata_str) {
-                            Ok(metadata) => result.push(Some(metadata)),
-                            Err(e) => {
-                                set_error(
-                                    error_out,
-                                    ChromaErrorCode::ValidationError,
-                                    "Invalid metadata JSON",
-                                    func_name,
-                                    Some(&format!("Error parsing metadata at index {}: {}", i, e)),
-                                );
-                                return ChromaErrorCode::ValidationError as c_int;
-                            }
-                        }
-                    }
-                } else {
-                    result.push(None);
+        // A filter-only update has a single entry applied to every matching record
+        let entries = if ids.is_null() { 1 } else { ids_count };
+
+        // Convert C string array to Rust vector
+        let ids_vec = if !ids.is_null() {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Convert document strings to Rust vector
-    let documents_vec = if !documents.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let document_ptr = *documents.add(i);
-                if !document_ptr.is_null() {
-                    match c_str_to_string(document_ptr) {
-                        Ok(s) => {
-                            if s.is_empty() {
-                                result.push(None);
-                            } else {
-                                result.push(Some(s));
+        } else {
+            None
+        };
+
+        // Convert C embedding array to Rust vector
+        let embeddings_vec = if !embeddings.is_null() {
+            if let Err(e) = collection.check_dimension(embedding_dim) {
+                return e.report(error_out, func_name);
+            }
+            let mut result = Vec::with_capacity(entries);
+            unsafe {
+                for i in 0..entries {
+                    let embedding_ptr = *embeddings.add(i);
+                    if !embedding_ptr.is_null() {
+                        let vec = c_array_to_vec_f32(embedding_ptr, embedding_dim);
+                        result.push(Some(vec));
+                    } else {
+                        result.push(None);
+                    }
+                }
+            }
+            Some(result)
+        } else {
+            None
+        };
+
+        // Convert metadata JSON strings to Rust vector
+        let metadatas_vec = if !metadatas_json.is_null() {
+            let mut result = Vec::with_capacity(entries);
+            unsafe {
+                for i in 0..entries {
+                    let metadata_ptr = *metadatas_json.add(i);
+                    if !metadata_ptr.is_null() {
+                        let metadata_str = match c_str_to_string(metadata_ptr) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                set_error(
+                                    error_out,
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert metadata string",
+                                    func_name,
+                                    Some(&format!("Error at index {}: {}", i, e)),
+                                );
+                                return ChromaErrorCode::InvalidArgument as c_int;
+                            }
+                        };
+
+                        if metadata_str.is_empty() {
+                            result.push(None);
+                        } else {
+                            match serde_json::from_str::<UpdateMetadata>(&metadata_str) {
+                                Ok(metadata) => result.push(Some(metadata)),
+                                Err(e) => {
+                                    set_error(
+                                        error_out,
+                                        ChromaErrorCode::ValidationError,
+                                        "Invalid metadata JSON",
+                                        func_name,
+                                        Some(&format!(
+                                            "Error parsing metadata at index {}: {}",
+                                            i, e
+                                        )),
+                                    );
+                                    return ChromaErrorCode::ValidationError as c_int;
+                                }
                             }
                         }
-                        Err(e) => {
-                            set_error(
-                                error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert document string",
-                                func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
-                            );
-                            return ChromaErrorCode::InvalidArgument as c_int;
+                    } else {
+                        result.push(None);
+                    }
+                }
+            }
+            Some(result)
+        } else {
+            None
+        };
+
+        // Convert document strings to Rust vector
+        let documents_vec = if !documents.is_null() {
+            let mut result = Vec::with_capacity(entries);
+            unsafe {
+                for i in 0..entries {
+                    let document_ptr = *documents.add(i);
+                    if !document_ptr.is_null() {
+                        match c_str_to_string(document_ptr) {
+                            Ok(s) => {
+                                if s.is_empty() {
+                                    result.push(None);
+                                } else {
+                                    result.push(Some(s));
+                                }
+                            }
+                            Err(e) => {
+                                set_error(
+                                    error_out,
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert document string",
+                                    func_name,
+                                    Some(&format!("Error at index {}: {}", i, e)),
+                                );
+                                return ChromaErrorCode::InvalidArgument as c_int;
+                            }
                         }
+                    } else {
+                        result.push(None);
                     }
-                } else {
-                    result.push(None);
                 }
             }
+            Some(result)
+        } else {
+            None
+        };
+
+        // Held until the write is done, so no other write lands between check and write
+        let _write = (!expected_versions.is_null())
+            .then(|| collection.client.write_locks.lock(&collection.id.to_string()));
+        if let Some(ids_vec) = ids_vec.as_ref().filter(|_| !expected_versions.is_null()) {
+            let expected = unsafe { std::slice::from_raw_parts(expected_versions, ids_count) };
+            if let Err(e) = collection.check_versions(ids_vec, expected) {
+                return e.report(error_out, func_name);
+            }
         }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
-                func_name,
-                Some(&format!("UUID parse error: {}", e)),
-            );
-            return ChromaErrorCode::InvalidUuid as c_int;
-        }
-    };
-
-    // Create update request
-    let request = match UpdateCollectionRecordsRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ids_vec,
-        embeddings_vec,
-        documents_vec,
-        None, // uris
-        metadatas_vec,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create update request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
-
-    // Execute request
-    let mut frontend = client.frontend.clone();
-    match client
-        .runtime
-        .block_on(async { frontend.update(request).await })
-    {
-        Ok(_) => {
+
+        // Narrow the update down to the records matching the filters
+        let (ids_vec, embeddings_vec, metadatas_vec, documents_vec) = if filtered {
+            let where_filter =
+                match unsafe { parse_where_ptrs(where_filter_json, where_document_filter) } {
+                    Ok(w) => w,
+                    Err(e) => return e.report(error_out, func_name),
+                };
+            let matching = match matching_ids(
+                &collection,
+                ids_vec.clone(),
+                collection.visible(where_filter),
+            ) {
+                Ok(matching) => matching,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+            match ids_vec {
+                Some(ids_vec) => {
+                    let matching: HashSet<&String> = matching.iter().collect();
+                    let keep: Vec<bool> = ids_vec.iter().map(|id| matching.contains(id)).collect();
+                    (
+                        retain_flagged(ids_vec, &keep),
+                        embeddings_vec.map(|v| retain_flagged(v, &keep)),
+                        metadatas_vec.map(|v| retain_flagged(v, &keep)),
+                        documents_vec.map(|v| retain_flagged(v, &keep)),
+                    )
+                }
+                None => {
+                    let count = matching.len();
+                    (
+                        matching,
+                        repeat_entry(embeddings_vec, count),
+                        repeat_entry(metadatas_vec, count),
+                        repeat_entry(documents_vec, count),
+                    )
+                }
+            }
+        } else {
+            (
+                ids_vec.unwrap_or_default(),
+                embeddings_vec,
+                metadatas_vec,
+                documents_vec,
+            )
+        };
+
+        if ids_vec.is_empty() {
             set_success(error_out);
-            ChromaErrorCode::Success as c_int
+            return ChromaErrorCode::Success as c_int;
         }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to update documents",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
+
+        let collection_id = collection.id;
+
+        if let Err(e) =
+            collection.validate_metadata_updates(ids_vec.len(), metadatas_vec.as_deref(), false)
+        {
+            return e.report(error_out, func_name);
         }
-    }
+        let updated_ids = (!updated_ids_out.is_null()).then(|| ids_vec.clone());
+
+        // Create update request
+        let request = match UpdateCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ids_vec,
+            embeddings_vec,
+            documents_vec,
+            None, // uris
+            metadatas_vec,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Execute request
+        match client.block_on_with_retry(
+            "update",
+            collection
+                .scope()
+                .with_records(Some(request.ids.len()))
+                .with_dimension((!embeddings.is_null()).then_some(embedding_dim)),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update(request).await }
+            },
+        ) {
+            Ok(_) => {
+                if !embeddings.is_null() {
+                    collection.record_dimension(embedding_dim);
+                }
+                if let Some(updated_ids) = updated_ids {
+                    unsafe {
+                        *updated_ids_out = ChromaResultSet::from_ids(updated_ids);
+                    }
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to update documents", &e)
+                    .report(error_out, func_name)
+            }
+        }
+    })
 }
 
 /// Upserts documents in a collection (adds if not exists, updates if exists)
 #[no_mangle]
 pub extern "C" fn chroma_upsert(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+    collection_handle: ChromaHandle,
     ids: *const *const c_char,
     ids_count: size_t,
     embeddings: *const *const c_float,
@@ -580,690 +991,976 @@ pub extern "C" fn chroma_upsert(
     documents: *const *const c_char,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_upsert";
-
-    if client_handle.is_null() || collection_handle.is_null() || ids.is_null() || ids_count == 0 {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
-        } else if ids.is_null() {
-            "IDs pointer is null"
-        } else {
-            "IDs count is zero"
-        };
+    ffi_boundary(error_out, "chroma_upsert", || {
+        let func_name = "chroma_upsert";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || ids.is_null() || ids_count == 0 {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs pointer is null"
+            } else {
+                "IDs count is zero"
+            };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
-
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-    // Convert C string array to Rust vector
-    let ids_vec = unsafe {
-        match c_array_to_vec_string(ids, ids_count) {
-            Ok(v) => v,
-            Err(e) => {
-                set_error(
-                    error_out,
-                    ChromaErrorCode::InvalidArgument,
-                    "Failed to convert IDs array",
-                    func_name,
-                    Some(&e.to_string()),
-                );
-                return ChromaErrorCode::InvalidArgument as c_int;
-            }
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
         }
-    };
+        let client = &collection.client;
 
-    // Convert C embedding array to Rust vector (required for upsert)
-    let embeddings_vec = if !embeddings.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let embedding_ptr = *embeddings.add(i);
-                if !embedding_ptr.is_null() {
-                    result.push(c_array_to_vec_f32(embedding_ptr, embedding_dim));
-                } else {
+        // Convert C string array to Rust vector
+        let ids_vec = unsafe {
+            match c_array_to_vec_string(ids, ids_count) {
+                Ok(v) => v,
+                Err(e) => {
                     set_error(
                         error_out,
                         ChromaErrorCode::InvalidArgument,
-                        "Embedding pointer is null",
+                        "Failed to convert IDs array",
                         func_name,
-                        Some(&format!("Null embedding at index {}", i)),
+                        Some(&e.to_string()),
                     );
                     return ChromaErrorCode::InvalidArgument as c_int;
                 }
             }
-        }
-        result
-    } else {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Embeddings pointer is null",
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    };
-
-    // Convert metadata JSON strings to Rust vector
-    let metadatas_vec = if !metadatas_json.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let metadata_ptr = *metadatas_json.add(i);
-                if !metadata_ptr.is_null() {
-                    let metadata_str = match c_str_to_string(metadata_ptr) {
-                        Ok(s) => s,
-                        Err(e) => {
-                            set_error(
-                                error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert metadata string",
-                                func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
-                            );
-                            return ChromaErrorCode::InvalidArgument as c_int;
-                        }
-                    };
+        };
 
-                    if metadata_str.is_empty() {
-                        result.push(None);
+        // Convert C embedding array to Rust vector (required for upsert)
+        let embeddings_vec = if !embeddings.is_null() {
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let embedding_ptr = *embeddings.add(i);
+                    if !embedding_ptr.is_null() {
+                        result.push(c_array_to_vec_f32(embedding_ptr, embedding_dim));
                     } else {
-                        match serde_json::from_str::<UpdateMetadata>(&metadata_str) {
-                            Ok(metadata) => result.push(Some(metadata)),
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Embedding pointer is null",
+                            func_name,
+                            Some(&format!("Null embedding at index {}", i)),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+            result
+        } else {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Embeddings pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        };
+
+        // Convert metadata JSON strings to Rust vector
+        let metadatas_vec = if !metadatas_json.is_null() {
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let metadata_ptr = *metadatas_json.add(i);
+                    if !metadata_ptr.is_null() {
+                        let metadata_str = match c_str_to_string(metadata_ptr) {
+                            Ok(s) => s,
                             Err(e) => {
                                 set_error(
                                     error_out,
-                                    ChromaErrorCode::ValidationError,
-                                    "Invalid metadata JSON",
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert metadata string",
                                     func_name,
-                                    Some(&format!("Error parsing metadata at index {}: {}", i, e)),
+                                    Some(&format!("Error at index {}: {}", i, e)),
                                 );
-                                return ChromaErrorCode::ValidationError as c_int;
+                                return ChromaErrorCode::InvalidArgument as c_int;
+                            }
+                        };
+
+                        if metadata_str.is_empty() {
+                            result.push(None);
+                        } else {
+                            match serde_json::from_str::<UpdateMetadata>(&metadata_str) {
+                                Ok(metadata) => result.push(Some(metadata)),
+                                Err(e) => {
+                                    set_error(
+                                        error_out,
+                                        ChromaErrorCode::ValidationError,
+                                        "Invalid metadata JSON",
+                                        func_name,
+                                        Some(&format!(
+                                            "Error parsing metadata at index {}: {}",
+                                            i, e
+                                        )),
+                                    );
+                                    return ChromaErrorCode::ValidationError as c_int;
+                                }
                             }
                         }
+                    } else {
+                        result.push(None);
                     }
-                } else {
-                    result.push(None);
                 }
             }
-        }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Convert document strings to Rust vector
-    let documents_vec = if !documents.is_null() {
-        let mut result = Vec::with_capacity(ids_count);
-        unsafe {
-            for i in 0..ids_count {
-                let document_ptr = *documents.add(i);
-                if !document_ptr.is_null() {
-                    match c_str_to_string(document_ptr) {
-                        Ok(s) => {
-                            if s.is_empty() {
-                                result.push(None);
-                            } else {
-                                result.push(Some(s));
+            Some(result)
+        } else {
+            None
+        };
+
+        // Convert document strings to Rust vector
+        let documents_vec = if !documents.is_null() {
+            let mut result = Vec::with_capacity(ids_count);
+            unsafe {
+                for i in 0..ids_count {
+                    let document_ptr = *documents.add(i);
+                    if !document_ptr.is_null() {
+                        match c_str_to_string(document_ptr) {
+                            Ok(s) => {
+                                if s.is_empty() {
+                                    result.push(None);
+                                } else {
+                                    result.push(Some(s));
+                                }
+                            }
+                            Err(e) => {
+                                set_error(
+                                    error_out,
+                                    ChromaErrorCode::InvalidArgument,
+                                    "Failed to convert document string",
+                                    func_name,
+                                    Some(&format!("Error at index {}: {}", i, e)),
+                                );
+                                return ChromaErrorCode::InvalidArgument as c_int;
                             }
                         }
-                        Err(e) => {
-                            set_error(
-                                error_out,
-                                ChromaErrorCode::InvalidArgument,
-                                "Failed to convert document string",
-                                func_name,
-                                Some(&format!("Error at index {}: {}", i, e)),
-                            );
-                            return ChromaErrorCode::InvalidArgument as c_int;
-                        }
+                    } else {
+                        result.push(None);
                     }
-                } else {
-                    result.push(None);
                 }
             }
+            Some(result)
+        } else {
+            None
+        };
+
+        let collection_id = collection.id;
+
+        if let Err(e) =
+            collection.validate_metadata_updates(ids_count, metadatas_vec.as_deref(), true)
+        {
+            return e.report(error_out, func_name);
+        }
+
+        // Create upsert request
+        let request = match UpsertCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ids_vec,
+            embeddings_vec,
+            documents_vec,
+            None, // uris
+            metadatas_vec,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create upsert request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Execute request
+        match client.block_on_with_retry(
+            "upsert",
+            collection
+                .scope()
+                .with_records(Some(request.ids.len()))
+                .with_dimension((!embeddings.is_null()).then_some(embedding_dim)),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.upsert(request).await }
+            },
+        ) {
+            Ok(_) => {
+                if !embeddings.is_null() {
+                    collection.record_dimension(embedding_dim);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to upsert documents", &e)
+                    .report(error_out, func_name)
+            }
+        }
+    })
+}
+
+/// Deletes documents from a collection
+///
+/// When `deleted_ids_out` is not null it receives the IDs of the records that matched the
+/// IDs and filters right before the delete ran; release them with `chroma_free_result_set`.
+/// With `dry_run` set nothing is deleted and `deleted_ids_out`, which is then required,
+/// receives the IDs that would have been deleted.
+///
+/// With `soft` set the records are kept but flagged as deleted: reads skip them until they
+/// are restored with `chroma_restore_records` or removed by `chroma_purge_deleted`.
+/// Records that are already soft-deleted do not match a soft delete.
+///
+/// `expected_versions`, when not null, holds one version token from `chroma_get` per ID
+/// (a negative entry skips that record) and requires `ids`. If any record is at another
+/// version nothing is deleted and the call fails with `ConflictError`. As with
+/// `chroma_update`, no other write to the collection lands between the check and the delete.
+#[no_mangle]
+pub extern "C" fn chroma_delete(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    dry_run: bool,
+    soft: bool,
+    expected_versions: *const i64,
+    deleted_ids_out: *mut ChromaResultSet,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete", || {
+        let func_name = "chroma_delete";
+
+        if let Some(deleted_ids) = unsafe { deleted_ids_out.as_mut() } {
+            *deleted_ids = ChromaResultSet::empty();
         }
-        Some(result)
-    } else {
-        None
-    };
-
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
             set_error(
                 error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
                 func_name,
-                Some(&format!("UUID parse error: {}", e)),
+                None,
             );
-            return ChromaErrorCode::InvalidUuid as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
-
-    // Create upsert request
-    let request = match UpsertCollectionRecordsRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ids_vec,
-        embeddings_vec,
-        documents_vec,
-        None, // uris
-        metadatas_vec,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
+
+        // If both ids and where filter are null, return an error
+        if ids.is_null() && where_filter_json.is_null() && where_document_filter.is_null() {
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create upsert request",
+                ChromaErrorCode::InvalidArgument,
+                "Either document IDs or filter criteria must be specified",
                 func_name,
-                Some(&format!("Validation error: {:?}", e)),
+                None,
             );
-            return ChromaErrorCode::ValidationError as c_int;
-        }
-    };
-
-    // Execute request
-    let mut frontend = client.frontend.clone();
-    match client
-        .runtime
-        .block_on(async { frontend.upsert(request).await })
-    {
-        Ok(_) => {
-            set_success(error_out);
-            ChromaErrorCode::Success as c_int
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-        Err(e) => {
+
+        if !expected_versions.is_null() && (ids.is_null() || ids_count == 0) {
             set_error(
                 error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to upsert documents",
+                ChromaErrorCode::InvalidArgument,
+                "Expected versions require document IDs",
                 func_name,
-                Some(&format!("Error: {:?}", e)),
+                None,
             );
-            ChromaErrorCode::InternalError as c_int
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    }
-}
 
-/// Deletes documents from a collection
-#[no_mangle]
-pub extern "C" fn chroma_delete(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
-    ids: *const *const c_char,
-    ids_count: size_t,
-    where_filter_json: *const c_char,
-    where_document_filter: *const c_char,
-    error_out: *mut *mut ChromaError,
-) -> c_int {
-    let func_name = "chroma_delete";
+        if dry_run && deleted_ids_out.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Deleted IDs output pointer is null",
+                func_name,
+                Some("A dry run reports the matching IDs through deleted_ids_out"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-    if client_handle.is_null() || collection_handle.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else {
-            "Collection handle pointer is null"
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
         };
-
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
-
-    // If both ids and where filter are null, return an error
-    if ids.is_null() && where_filter_json.is_null() && where_document_filter.is_null() {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Either document IDs or filter criteria must be specified",
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
-
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
-
-    // Convert C string array to Rust vector
-    let ids_vec = if !ids.is_null() && ids_count > 0 {
-        unsafe {
-            match c_array_to_vec_string(ids, ids_count) {
-                Ok(v) => Some(v),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert IDs array",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
-                }
-            }
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
         }
-    } else {
-        None
-    };
-
-    // Parse where filters
-    let where_filter = unsafe {
-        let where_json_str = if !where_filter_json.is_null() {
-            match c_str_to_string(where_filter_json) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert where filter JSON string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+        let client = &collection.client;
+
+        // Convert C string array to Rust vector
+        let ids_vec = if !ids.is_null() && ids_count > 0 {
+            unsafe {
+                match c_array_to_vec_string(ids, ids_count) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert IDs array",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
             }
         } else {
             None
         };
 
-        let where_document = if !where_document_filter.is_null() {
-            match c_str_to_string(where_document_filter) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert document filter string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+        // Parse where filters
+        let where_filter = unsafe {
+            let where_json_str = if !where_filter_json.is_null() {
+                match c_str_to_string(where_filter_json) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert where filter JSON string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
-            }
-        } else {
-            None
-        };
+            } else {
+                None
+            };
 
-        // Only attempt to parse where filters if they're actually provided
-        if where_json_str.is_some() || where_document.is_some() {
-            match RawWhereFields::from_json_str(
-                where_json_str.as_deref(),
-                where_document.as_deref(),
-            ) {
-                Ok(raw) => match raw.parse() {
-                    Ok(parsed) => parsed,
+            let where_document = if !where_document_filter.is_null() {
+                match c_str_to_string(where_document_filter) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert document filter string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            } else {
+                None
+            };
+
+            // Only attempt to parse where filters if they're actually provided
+            if where_json_str.is_some() || where_document.is_some() {
+                match RawWhereFields::from_json_str(
+                    where_json_str.as_deref(),
+                    where_document.as_deref(),
+                ) {
+                    Ok(raw) => match raw.parse() {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            set_error(
+                                error_out,
+                                ChromaErrorCode::ValidationError,
+                                "Failed to parse where filters",
+                                func_name,
+                                Some(&format!("Filter validation error: {:?}", e)),
+                            );
+                            return ChromaErrorCode::ValidationError as c_int;
+                        }
+                    },
                     Err(e) => {
                         set_error(
                             error_out,
                             ChromaErrorCode::ValidationError,
-                            "Failed to parse where filters",
+                            "Failed to create where filters",
                             func_name,
-                            Some(&format!("Filter validation error: {:?}", e)),
+                            Some(&format!("Filter creation error: {:?}", e)),
                         );
                         return ChromaErrorCode::ValidationError as c_int;
                     }
-                },
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Failed to create where filters",
-                        func_name,
-                        Some(&format!("Filter creation error: {:?}", e)),
-                    );
-                    return ChromaErrorCode::ValidationError as c_int;
                 }
+            } else {
+                // No where filter provided, use None as default
+                None
             }
-        } else {
-            // No where filter provided, use None as default
-            None
-        }
-    };
+        };
 
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
-                func_name,
-                Some(&format!("UUID parse error: {}", e)),
-            );
-            return ChromaErrorCode::InvalidUuid as c_int;
+        // Held until the write is done, so no other write lands between check and write
+        let _write = (!expected_versions.is_null())
+            .then(|| collection.client.write_locks.lock(&collection.id.to_string()));
+        if let Some(ids_vec) = ids_vec.as_ref().filter(|_| !expected_versions.is_null()) {
+            let expected = unsafe { std::slice::from_raw_parts(expected_versions, ids_count) };
+            if let Err(e) = collection.check_versions(ids_vec, expected) {
+                return e.report(error_out, func_name);
+            }
         }
-    };
-
-    // Create delete request
-    let request = match DeleteCollectionRecordsRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ids_vec,
-        where_filter,
-        None, // limit
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create delete request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+
+        let collection_id = collection.id;
+
+        if soft && !dry_run {
+            return match collection.soft_delete_records(ids_vec, where_filter) {
+                Ok(ids) => {
+                    if let Some(out) = unsafe { deleted_ids_out.as_mut() } {
+                        *out = ChromaResultSet::from_ids(ids);
+                    }
+                    set_success(error_out);
+                    ChromaErrorCode::Success as c_int
+                }
+                Err(e) => e.report(error_out, func_name),
+            };
         }
-    };
-
-    // Execute request
-    let mut frontend = client.frontend.clone();
-    match client
-        .runtime
-        .block_on(async { frontend.delete(request, String::new()).await })
-    {
-        Ok(_) => {
+        let where_filter = if soft {
+            collection.visible(where_filter)
+        } else {
+            where_filter
+        };
+
+        // Read the matching IDs first so they can be reported after the delete
+        let deleted_ids = if deleted_ids_out.is_null() {
+            None
+        } else {
+            match matching_ids(&collection, ids_vec.clone(), where_filter.clone()) {
+                Ok(ids) => Some(ids),
+                Err(e) => return e.report(error_out, func_name),
+            }
+        };
+
+        if dry_run {
+            if let (Some(out), Some(ids)) = (unsafe { deleted_ids_out.as_mut() }, deleted_ids) {
+                *out = ChromaResultSet::from_ids(ids);
+            }
             set_success(error_out);
-            ChromaErrorCode::Success as c_int
+            return ChromaErrorCode::Success as c_int;
         }
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to delete documents",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            ChromaErrorCode::InternalError as c_int
+
+        // Create delete request
+        let request = match DeleteCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ids_vec,
+            where_filter,
+            None, // limit
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create delete request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Execute request
+        match client.block_on_with_retry(
+            "delete",
+            collection.scope().with_records(request.ids.as_ref().map(Vec::len)),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.delete(request, String::new()).await }
+            },
+        ) {
+            Ok(_) => {
+                if let (Some(out), Some(ids)) = (unsafe { deleted_ids_out.as_mut() }, deleted_ids) {
+                    *out = ChromaResultSet::from_ids(ids);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to delete documents", &e)
+                    .report(error_out, func_name)
+            }
         }
-    }
+    })
 }
 
 /// Gets documents from a collection
+///
+/// The result's `has_more` tells whether records beyond the returned page match. When
+/// `count_total` is set, `total_matching_count` receives the number of matching records
+/// across all pages, which may take an extra read of their IDs; otherwise it is -1.
+///
+/// With `include_versions` set, `versions` receives the version token of each returned
+/// record, to pass back as the expected version of `chroma_update` or `chroma_delete`.
 #[no_mangle]
 pub extern "C" fn chroma_get(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+    collection_handle: ChromaHandle,
     ids: *const *const c_char,
     ids_count: size_t,
     where_filter_json: *const c_char,
     where_document_filter: *const c_char,
     limit: c_uint,
     offset: c_uint,
+    order_by: *const ChromaOrderBy,
     include_embeddings: bool,
     include_metadatas: bool,
     include_documents: bool,
+    metadata_keys: *const *const c_char,
+    metadata_keys_count: size_t,
+    metadata_format: c_int,
+    count_total: bool,
+    include_versions: bool,
     result: *mut *mut ChromaQueryResult,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_get";
+    ffi_boundary(error_out, "chroma_get", || {
+        let func_name = "chroma_get";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || result.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Result pointer is null"
+            };
 
-    if client_handle.is_null() || collection_handle.is_null() || result.is_null() {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+
+        // Convert C string array to Rust vector
+        let ids_vec = if !ids.is_null() && ids_count > 0 {
+            unsafe {
+                match c_array_to_vec_string(ids, ids_count) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert IDs array",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
         } else {
-            "Result pointer is null"
+            None
         };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+        // Parse where filters
+        let where_filter = unsafe {
+            let where_json_str = if !where_filter_json.is_null() {
+                match c_str_to_string(where_filter_json) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert where filter JSON string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            } else {
+                None
+            };
+
+            let where_document = if !where_document_filter.is_null() {
+                match c_str_to_string(where_document_filter) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert document filter string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            } else {
+                None
+            };
 
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+            if let Some(where_json) = where_json_str.as_deref() {
+                collection.warn_undeclared_filter_keys(where_json);
+            }
 
-    // Convert C string array to Rust vector
-    let ids_vec = if !ids.is_null() && ids_count > 0 {
-        unsafe {
-            match c_array_to_vec_string(ids, ids_count) {
-                Ok(v) => Some(v),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert IDs array",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+            // Only attempt to parse where filters if they're actually provided
+            if where_json_str.is_some() || where_document.is_some() {
+                match RawWhereFields::from_json_str(
+                    where_json_str.as_deref(),
+                    where_document.as_deref(),
+                ) {
+                    Ok(raw) => match raw.parse() {
+                        Ok(parsed) => Some(parsed),
+                        Err(e) => {
+                            set_error(
+                                error_out,
+                                ChromaErrorCode::ValidationError,
+                                "Failed to parse where filters",
+                                func_name,
+                                Some(&format!("Filter validation error: {:?}", e)),
+                            );
+                            return ChromaErrorCode::ValidationError as c_int;
+                        }
+                    },
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::ValidationError,
+                            "Failed to create where filters",
+                            func_name,
+                            Some(&format!("Filter creation error: {:?}", e)),
+                        );
+                        return ChromaErrorCode::ValidationError as c_int;
+                    }
                 }
+            } else {
+                // No where filter provided, use None as default
+                None
             }
+        };
+
+        let collection_id = collection.id;
+
+        if metadata_format != CHROMA_METADATA_FORMAT_JSON
+            && metadata_format != CHROMA_METADATA_FORMAT_TYPED
+        {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown metadata format",
+                func_name,
+                Some(&format!("Metadata format: {}", metadata_format)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    } else {
-        None
-    };
-
-    // Parse where filters
-    let where_filter = unsafe {
-        let where_json_str = if !where_filter_json.is_null() {
-            match c_str_to_string(where_filter_json) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert where filter JSON string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+
+        // Parse metadata key projection
+        let metadata_keys_vec = if !metadata_keys.is_null() {
+            unsafe {
+                match c_array_to_vec_string(metadata_keys, metadata_keys_count) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert metadata keys array",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
             }
-        } else {
-            None
+        } else {
+            None
+        };
+
+        // Parse sort order
+        let ordering = match unsafe { order_by.as_ref() } {
+            Some(order_by) => match unsafe { OrderBy::from_ffi(order_by) } {
+                Ok(ordering) => Some(ordering),
+                Err(e) => return e.report(error_out, func_name),
+            },
+            None => None,
+        };
+        let sort_by_metadata = ordering.as_ref().is_some_and(|o| o.metadata_key.is_some());
+
+        // Build include list
+        let mut include = Vec::new();
+        if include_embeddings {
+            include.push("embeddings".to_string());
+        }
+        if include_metadatas || sort_by_metadata {
+            include.push("metadatas".to_string());
+        }
+        if include_documents {
+            include.push("documents".to_string());
+        }
+
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Sorted results are read in full and paged after sorting; otherwise one record past
+        // the page is read to tell whether more match
+        let (request_limit, request_offset) = match ordering {
+            Some(_) => (None, 0),
+            None => (
+                if limit > 0 {
+                    Some(limit.saturating_add(1))
+                } else {
+                    None
+                },
+                offset,
+            ),
+        };
+
+        let visible_filter = collection.visible(where_filter.flatten());
+        // Kept to count the matching records when the page does not tell
+        let count_filter = count_total.then(|| (ids_vec.clone(), visible_filter.clone()));
+
+        // Create get request
+        let request = match GetRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ids_vec,
+            visible_filter,
+            request_limit,
+            request_offset,
+            include_list,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
+        }
+
+        // Execute get
+        let mut get_response = match client.block_on_with_retry(
+            "get",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.get(request).await }
+            },
+        ) {
+            Ok(resp) => resp,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to get documents",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
         };
 
-        let where_document = if !where_document_filter.is_null() {
-            match c_str_to_string(where_document_filter) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert document filter string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
-                }
+        let page_limit = if limit > 0 { Some(limit as usize) } else { None };
+        let mut total_matching = None;
+        let has_more = if let Some(ordering) = &ordering {
+            let matching = get_response.ids.len();
+            total_matching = Some(matching);
+            ordering.apply(&mut get_response, offset as usize, page_limit);
+            if !include_metadatas {
+                get_response.metadatas = None;
             }
+            page_limit.is_some_and(|limit| matching > offset as usize + limit)
         } else {
-            None
+            match page_limit {
+                Some(limit) if get_response.ids.len() > limit => {
+                    truncate_response(&mut get_response, limit);
+                    true
+                }
+                _ => false,
+            }
         };
 
-        // Only attempt to parse where filters if they're actually provided
-        if where_json_str.is_some() || where_document.is_some() {
-            match RawWhereFields::from_json_str(
-                where_json_str.as_deref(),
-                where_document.as_deref(),
-            ) {
-                Ok(raw) => match raw.parse() {
-                    Ok(parsed) => Some(parsed),
-                    Err(e) => {
-                        set_error(
-                            error_out,
-                            ChromaErrorCode::ValidationError,
-                            "Failed to parse where filters",
-                            func_name,
-                            Some(&format!("Filter validation error: {:?}", e)),
-                        );
-                        return ChromaErrorCode::ValidationError as c_int;
-                    }
+        // The last page, unless it is empty past the end, tells the total on its own
+        let returned = get_response.ids.len();
+        if total_matching.is_none() && !has_more && (returned > 0 || offset == 0) {
+            total_matching = Some(offset as usize + returned);
+        }
+        let total_matching_count = match count_filter {
+            Some((ids, where_filter)) => match total_matching {
+                Some(total) => total as i64,
+                None => match matching_ids(&collection, ids, where_filter) {
+                    Ok(ids) => ids.len() as i64,
+                    Err(e) => return e.report(error_out, func_name),
                 },
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Failed to create where filters",
-                        func_name,
-                        Some(&format!("Filter creation error: {:?}", e)),
-                    );
-                    return ChromaErrorCode::ValidationError as c_int;
-                }
+            },
+            None => -1,
+        };
+
+        let versions = if include_versions {
+            match collection.record_versions(&get_response.ids) {
+                Ok(versions) => Some(versions),
+                Err(e) => return e.report(error_out, func_name),
             }
         } else {
-            // No where filter provided, use None as default
             None
+        };
+
+        // Prepare result structure
+        let query_result = Box::new(ChromaQueryResult::empty());
+
+        let query_result_ptr = Box::into_raw(query_result);
+        let query_result = unsafe { &mut *query_result_ptr };
+        query_result.has_more = has_more;
+        query_result.total_matching_count = total_matching_count;
+        if let Some(versions) = versions {
+            let (array, count) = vec_i64_to_c_array(versions);
+            query_result.versions = array;
+            query_result.versions_count = count;
         }
-    };
 
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
-                func_name,
-                Some(&format!("UUID parse error: {}", e)),
-            );
-            return ChromaErrorCode::InvalidUuid as c_int;
+        // Set embeddings if available
+        if let Some(embeddings) = get_response.embeddings.as_ref() {
+            let rows: Vec<Option<&[f32]>> = embeddings.iter().map(|e| Some(e.as_slice())).collect();
+            query_result.set_embeddings(&rows);
         }
-    };
 
-    // Build include list
-    let mut include = Vec::new();
-    if include_embeddings {
-        include.push("embeddings".to_string());
-    }
-    if include_metadatas {
-        include.push("metadatas".to_string());
-    }
-    if include_documents {
-        include.push("documents".to_string());
-    }
+        // Set IDs
+        if !get_response.ids.is_empty() {
+            let (array, count) = vec_string_to_c_array(get_response.ids);
+            query_result.ids = array;
+            query_result.ids_count = count;
+        }
 
-    let include_list = match IncludeList::try_from(include) {
-        Ok(list) => list,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid include list",
-                func_name,
-                Some(&format!("Include list validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+        // Set metadata if available
+        if let Some(metadatas) = get_response.metadatas {
+            if !metadatas.is_empty() {
+                if metadata_format == CHROMA_METADATA_FORMAT_TYPED {
+                    let (array, count) =
+                        metadata_entries_to_c_array(&metadatas, metadata_keys_vec.as_deref());
+                    query_result.metadata_entries = array;
+                    query_result.metadata_entries_count = count;
+                } else {
+                    let metadata_strings: Vec<String> = metadatas
+                        .iter()
+                        .map(|m| metadata_to_json(m.as_ref(), metadata_keys_vec.as_deref()))
+                        .collect();
+
+                    let (array, count) = vec_string_to_c_array(metadata_strings);
+                    query_result.metadata_json = array;
+                    query_result.metadata_count = count;
+                }
+            }
         }
-    };
-
-    // Create get request
-    let request = match GetRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        ids_vec,
-        where_filter.flatten(), // Flatten Option<Option<Where>> to Option<Where>
-        if limit > 0 { Some(limit) } else { None },
-        offset,
-        include_list,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create get request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+
+        // Set documents if available
+        if let Some(documents) = get_response.documents {
+            if !documents.is_empty() {
+                let doc_strings: Vec<String> = documents
+                    .iter()
+                    .map(|d| d.clone().unwrap_or_default())
+                    .collect();
+
+                let (array, count) = vec_string_to_c_array(doc_strings);
+                query_result.documents = array;
+                query_result.documents_count = count;
+            }
         }
-    };
-
-    // Execute get
-    let mut frontend = client.frontend.clone();
-    let get_response = match client
-        .runtime
-        .block_on(async { frontend.get(request).await })
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to get documents",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            return ChromaErrorCode::InternalError as c_int;
+
+        unsafe {
+            *result = query_result_ptr;
         }
-    };
-
-    // Prepare result structure
-    let query_result = Box::new(ChromaQueryResult {
-        ids: ptr::null_mut(),
-        ids_count: 0,
-        distances: ptr::null_mut(),
-        distances_count: 0,
-        metadata_json: ptr::null_mut(),
-        metadata_count: 0,
-        documents: ptr::null_mut(),
-        documents_count: 0,
-    });
-
-    let query_result_ptr = Box::into_raw(query_result);
-    let query_result = unsafe { &mut *query_result_ptr };
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Converts the hits of the first query of a response to a `ChromaQueryResult`, keeping only
+/// `metadata_keys` of each metadata when a projection is given
+pub(crate) fn query_result_from_response(
+    response: QueryResponse,
+    metadata_format: c_int,
+    metadata_keys: Option<&[String]>,
+    rerank_scores: Option<Vec<f32>>,
+) -> Box<ChromaQueryResult> {
+    let mut query_result = Box::new(ChromaQueryResult::empty());
 
     // Set IDs
-    if !get_response.ids.is_empty() {
-        let (array, count) = vec_string_to_c_array(get_response.ids);
+    if !response.ids.is_empty() {
+        let ids = response.ids[0].clone();
+        let (array, count) = vec_string_to_c_array(ids);
         query_result.ids = array;
         query_result.ids_count = count;
     }
 
+    // Set embeddings if available
+    if let Some(embeddings) = response.embeddings.as_ref().and_then(|e| e.first()) {
+        let rows: Vec<Option<&[f32]>> = embeddings.iter().map(|e| e.as_deref()).collect();
+        query_result.set_embeddings(&rows);
+    }
+
+    // Set distances if available
+    if let Some(distances) = response.distances {
+        if !distances.is_empty() && !distances[0].is_empty() {
+            let distance_vec: Vec<f32> = distances[0].iter().map(|d| d.unwrap_or(0.0)).collect();
+
+            let (array, count) = vec_f32_to_c_array(distance_vec);
+            query_result.distances = array;
+            query_result.distances_count = count;
+        }
+    }
+
     // Set metadata if available
-    if let Some(metadatas) = get_response.metadatas {
+    if let Some(metadatas) = response.metadatas {
         if !metadatas.is_empty() {
-            let metadata_strings: Vec<String> = metadatas
-                .iter()
-                .map(|m| match m {
-                    Some(metadata) => serde_json::to_string(metadata).unwrap_or_default(),
-                    None => String::new(),
-                })
-                .collect();
-
-            let (array, count) = vec_string_to_c_array(metadata_strings);
-            query_result.metadata_json = array;
-            query_result.metadata_count = count;
+            if metadata_format == CHROMA_METADATA_FORMAT_TYPED {
+                let (array, count) = metadata_entries_to_c_array(&metadatas[0], metadata_keys);
+                query_result.metadata_entries = array;
+                query_result.metadata_entries_count = count;
+            } else {
+                let metadata_strings: Vec<String> = metadatas[0]
+                    .iter()
+                    .map(|m| metadata_to_json(m.as_ref(), metadata_keys))
+                    .collect();
+
+                let (array, count) = vec_string_to_c_array(metadata_strings);
+                query_result.metadata_json = array;
+                query_result.metadata_count = count;
+            }
         }
     }
 
     // Set documents if available
-    if let Some(documents) = get_response.documents {
+    if let Some(documents) = response.documents {
         if !documents.is_empty() {
-            let doc_strings: Vec<String> = documents
+            let doc_strings: Vec<String> = documents[0]
                 .iter()
                 .map(|d| d.clone().unwrap_or_default())
                 .collect();
@@ -1274,308 +1971,371 @@ pub extern "C" fn chroma_get(
         }
     }
 
-    unsafe {
-        *result = query_result_ptr;
+    // Re-ranker scores, when the hits were re-ranked
+    if let Some(scores) = rerank_scores {
+        let (array, count) = vec_f32_to_c_array(scores);
+        query_result.scores = array;
+        query_result.scores_count = count;
     }
 
-    set_success(error_out);
-    ChromaErrorCode::Success as c_int
+    query_result
 }
 
 /// Queries a collection for similar documents
+///
+/// `options`, when not null, post-processes the hits before they are marshaled, so fewer
+/// than `n_results` may be returned. When the hits are re-ranked, `scores` holds the
+/// re-ranker score of each hit.
 #[no_mangle]
 pub extern "C" fn chroma_query(
-    client_handle: *mut ChromaClient,
-    collection_handle: *const ChromaCollection,
+    collection_handle: ChromaHandle,
     query_embeddings: *const c_float,
     embedding_dim: size_t,
     n_results: c_uint,
     where_filter_json: *const c_char,
     where_document_filter: *const c_char,
+    allowed_ids: *const *const c_char,
+    allowed_ids_count: size_t,
     include_embeddings: bool,
     include_metadatas: bool,
     include_documents: bool,
     include_distances: bool,
+    metadata_keys: *const *const c_char,
+    metadata_keys_count: size_t,
+    metadata_format: c_int,
+    options: *const ChromaQueryOptions,
     result: *mut *mut ChromaQueryResult,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    let func_name = "chroma_query";
-
-    if client_handle.is_null()
-        || collection_handle.is_null()
-        || query_embeddings.is_null()
-        || result.is_null()
-    {
-        let message = if client_handle.is_null() {
-            "Client handle pointer is null"
-        } else if collection_handle.is_null() {
-            "Collection handle pointer is null"
-        } else if query_embeddings.is_null() {
-            "Query embeddings pointer is null"
-        } else {
-            "Result pointer is null"
-        };
+    ffi_boundary(error_out, "chroma_query", || {
+        let func_name = "chroma_query";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || query_embeddings.is_null()
+            || result.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_embeddings.is_null() {
+                "Query embeddings pointer is null"
+            } else {
+                "Result pointer is null"
+            };
 
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            message,
-            func_name,
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
 
-    let client = unsafe { &*client_handle };
-    let collection = unsafe { &*collection_handle };
+        let collection_id = collection.id;
 
-    // Parse collection ID
-    let collection_id = match uuid::Uuid::parse_str(&collection.id) {
-        Ok(id) => CollectionUuid(id),
-        Err(e) => {
+        // Convert C embedding to Rust vector
+        let query_embedding_vec =
+            unsafe { vec![c_array_to_vec_f32(query_embeddings, embedding_dim)] };
+        if query_embedding_vec[0].is_empty() || query_embedding_vec[0].len() != embedding_dim {
             set_error(
                 error_out,
-                ChromaErrorCode::InvalidUuid,
-                "Invalid collection UUID",
+                ChromaErrorCode::InvalidArgument,
+                "Invalid query embedding",
                 func_name,
-                Some(&format!("UUID parse error: {}", e)),
+                Some(&format!(
+                    "Expected dimension {}, got {}",
+                    embedding_dim,
+                    query_embedding_vec[0].len()
+                )),
             );
-            return ChromaErrorCode::InvalidUuid as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
         }
-    };
-
-    // Convert C embedding to Rust vector
-    let query_embedding_vec = unsafe { vec![c_array_to_vec_f32(query_embeddings, embedding_dim)] };
-    if query_embedding_vec[0].is_empty() || query_embedding_vec[0].len() != embedding_dim {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Invalid query embedding",
-            func_name,
-            Some(&format!(
-                "Expected dimension {}, got {}",
-                embedding_dim,
-                query_embedding_vec[0].len()
-            )),
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
 
-    // Parse where filters
-    let where_filter = unsafe {
-        let where_json_str = if !where_filter_json.is_null() {
-            match c_str_to_string(where_filter_json) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert where filter JSON string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+        // Parse where filters
+        let where_filter = unsafe {
+            let where_json_str = if !where_filter_json.is_null() {
+                match c_str_to_string(where_filter_json) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert where filter JSON string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
-            }
-        } else {
-            None
-        };
+            } else {
+                None
+            };
 
-        let where_document = if !where_document_filter.is_null() {
-            match c_str_to_string(where_document_filter) {
-                Ok(s) => Some(s),
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::InvalidArgument,
-                        "Failed to convert document filter string",
-                        func_name,
-                        Some(&e.to_string()),
-                    );
-                    return ChromaErrorCode::InvalidArgument as c_int;
+            let where_document = if !where_document_filter.is_null() {
+                match c_str_to_string(where_document_filter) {
+                    Ok(s) => Some(s),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert document filter string",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
+            } else {
+                None
+            };
+
+            if let Some(where_json) = where_json_str.as_deref() {
+                collection.warn_undeclared_filter_keys(where_json);
             }
-        } else {
-            None
-        };
 
-        // Only attempt to parse where filters if they're actually provided
-        if where_json_str.is_some() || where_document.is_some() {
-            match RawWhereFields::from_json_str(
-                where_json_str.as_deref(),
-                where_document.as_deref(),
-            ) {
-                Ok(raw) => match raw.parse() {
-                    Ok(parsed) => parsed,
+            // Only attempt to parse where filters if they're actually provided
+            if where_json_str.is_some() || where_document.is_some() {
+                match RawWhereFields::from_json_str(
+                    where_json_str.as_deref(),
+                    where_document.as_deref(),
+                ) {
+                    Ok(raw) => match raw.parse() {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            set_error(
+                                error_out,
+                                ChromaErrorCode::ValidationError,
+                                "Failed to parse where filters",
+                                func_name,
+                                Some(&format!("Filter validation error: {:?}", e)),
+                            );
+                            return ChromaErrorCode::ValidationError as c_int;
+                        }
+                    },
                     Err(e) => {
                         set_error(
                             error_out,
                             ChromaErrorCode::ValidationError,
-                            "Failed to parse where filters",
+                            "Failed to create where filters",
                             func_name,
-                            Some(&format!("Filter validation error: {:?}", e)),
+                            Some(&format!("Filter creation error: {:?}", e)),
                         );
                         return ChromaErrorCode::ValidationError as c_int;
                     }
-                },
-                Err(e) => {
-                    set_error(
-                        error_out,
-                        ChromaErrorCode::ValidationError,
-                        "Failed to create where filters",
-                        func_name,
-                        Some(&format!("Filter creation error: {:?}", e)),
-                    );
-                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            } else {
+                // No where filter provided, use default None
+                None
+            }
+        };
+
+        // Restrict the candidate set to the allowed IDs when provided
+        let allowed_ids_vec = if !allowed_ids.is_null() {
+            unsafe {
+                match c_array_to_vec_string(allowed_ids, allowed_ids_count) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert allowed IDs array",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
                 }
             }
         } else {
-            // No where filter provided, use default None
             None
-        }
-    };
+        };
 
-    // Build include list
-    let mut include = Vec::new();
-    if include_embeddings {
-        include.push("embeddings".to_string());
-    }
-    if include_metadatas {
-        include.push("metadatas".to_string());
-    }
-    if include_documents {
-        include.push("documents".to_string());
-    }
-    if include_distances {
-        include.push("distances".to_string());
-    }
+        // An empty allow-list matches nothing, so skip the backend call
+        if allowed_ids_vec.as_ref().is_some_and(|ids| ids.is_empty()) {
+            unsafe {
+                *result = Box::into_raw(Box::new(ChromaQueryResult::empty()));
+            }
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
 
-    let include_list = match IncludeList::try_from(include) {
-        Ok(list) => list,
-        Err(e) => {
+        if metadata_format != CHROMA_METADATA_FORMAT_JSON
+            && metadata_format != CHROMA_METADATA_FORMAT_TYPED
+        {
             set_error(
                 error_out,
-                ChromaErrorCode::ValidationError,
-                "Invalid include list",
+                ChromaErrorCode::InvalidArgument,
+                "Unknown metadata format",
                 func_name,
-                Some(&format!("Include list validation error: {:?}", e)),
+                Some(&format!("Metadata format: {}", metadata_format)),
             );
-            return ChromaErrorCode::ValidationError as c_int;
+            return ChromaErrorCode::InvalidArgument as c_int;
         }
-    };
-
-    // Create query request
-    let request = match QueryRequest::try_new(
-        collection.tenant.clone(),
-        collection.database.clone(),
-        collection_id,
-        None, // ids
-        where_filter,
-        query_embedding_vec,
-        n_results,
-        include_list,
-    ) {
-        Ok(req) => req,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::ValidationError,
-                "Failed to create query request",
-                func_name,
-                Some(&format!("Validation error: {:?}", e)),
-            );
-            return ChromaErrorCode::ValidationError as c_int;
+
+        // Parse metadata key projection
+        let metadata_keys_vec = if !metadata_keys.is_null() {
+            unsafe {
+                match c_array_to_vec_string(metadata_keys, metadata_keys_count) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        set_error(
+                            error_out,
+                            ChromaErrorCode::InvalidArgument,
+                            "Failed to convert metadata keys array",
+                            func_name,
+                            Some(&e.to_string()),
+                        );
+                        return ChromaErrorCode::InvalidArgument as c_int;
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        // Parse post-processing options
+        let options = match unsafe { read_sized(options) } {
+            Ok(options) => options,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let mut options = match options {
+            Some(options) => match unsafe { QueryOptions::from_ffi(&options) } {
+                Ok(options) => Some(options),
+                Err(e) => return e.report(error_out, func_name),
+            },
+            None => None,
+        };
+        if let Some(options) = options.as_mut() {
+            if let Err(e) = options.resolve_reranker(client) {
+                return e.report(error_out, func_name);
+            }
         }
-    };
-
-    // Execute query
-    let mut frontend = client.frontend.clone();
-    let query_response = match client
-        .runtime
-        .block_on(async { frontend.query(request).await })
-    {
-        Ok(resp) => resp,
-        Err(e) => {
-            set_error(
-                error_out,
-                ChromaErrorCode::InternalError,
-                "Failed to execute query",
-                func_name,
-                Some(&format!("Error: {:?}", e)),
-            );
-            return ChromaErrorCode::InternalError as c_int;
+        let needs_distances = options.as_ref().is_some_and(|o| o.needs_distances());
+        let needs_metadatas = options.as_ref().is_some_and(|o| o.needs_metadatas());
+        let needs_embeddings = options.as_ref().is_some_and(|o| o.needs_embeddings());
+        let needs_documents = options.as_ref().is_some_and(|o| o.needs_documents());
+        // MMR compares the candidates to the query, which moves into the request
+        let mmr_query = if needs_embeddings {
+            query_embedding_vec[0].clone()
+        } else {
+            Vec::new()
+        };
+        let fetch_count = options.as_ref().map_or(n_results, |o| o.fetch_count(n_results));
+
+        // Build include list
+        let mut include = Vec::new();
+        if include_embeddings || needs_embeddings {
+            include.push("embeddings".to_string());
+        }
+        if include_metadatas || needs_metadatas {
+            include.push("metadatas".to_string());
+        }
+        if include_documents || needs_documents {
+            include.push("documents".to_string());
+        }
+        if include_distances || needs_distances {
+            include.push("distances".to_string());
         }
-    };
-
-    // Convert query response to C struct
-    let query_result = Box::new(ChromaQueryResult {
-        ids: ptr::null_mut(),
-        ids_count: 0,
-        distances: ptr::null_mut(),
-        distances_count: 0,
-        metadata_json: ptr::null_mut(),
-        metadata_count: 0,
-        documents: ptr::null_mut(),
-        documents_count: 0,
-    });
-
-    let query_result_ptr = Box::into_raw(query_result);
-    let query_result = unsafe { &mut *query_result_ptr };
 
-    // Set IDs
-    if !query_response.ids.is_empty() {
-        let ids = query_response.ids[0].clone();
-        let (array, count) = vec_string_to_c_array(ids);
-        query_result.ids = array;
-        query_result.ids_count = count;
-    }
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
 
-    // Set distances if available
-    if let Some(distances) = query_response.distances {
-        if !distances.is_empty() && !distances[0].is_empty() {
-            let distance_vec: Vec<f32> = distances[0].iter().map(|d| d.unwrap_or(0.0)).collect();
+        // Create query request
+        let request = match QueryRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            allowed_ids_vec,
+            collection.visible(where_filter),
+            query_embedding_vec,
+            fetch_count,
+            include_list,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create query request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
 
-            let (array, count) = vec_f32_to_c_array(distance_vec);
-            query_result.distances = array;
-            query_result.distances_count = count;
+        if let Err(e) = client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
         }
-    }
 
-    // Set metadata if available
-    if let Some(metadatas) = query_response.metadatas {
-        if !metadatas.is_empty() {
-            let metadata_strings: Vec<String> = metadatas[0]
-                .iter()
-                .map(|m| match m {
-                    Some(metadata) => serde_json::to_string(metadata).unwrap_or_default(),
-                    None => String::new(),
-                })
-                .collect();
+        // Execute query
+        let mut query_response = match client.block_on_with_retry(
+            "query",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.query(request).await }
+            },
+        ) {
+            Ok(resp) => resp,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to execute query",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+        };
 
-            let (array, count) = vec_string_to_c_array(metadata_strings);
-            query_result.metadata_json = array;
-            query_result.metadata_count = count;
+        let mut rerank_scores = None;
+        if let Some(options) = &options {
+            match options.apply(&mut query_response, &mmr_query, n_results as usize) {
+                Ok(scores) => rerank_scores = scores,
+                Err(e) => return e.report(error_out, func_name),
+            }
+            drop_unrequested(
+                &mut query_response,
+                include_embeddings,
+                include_metadatas,
+                include_documents,
+                include_distances,
+            );
         }
-    }
 
-    // Set documents if available
-    if let Some(documents) = query_response.documents {
-        if !documents.is_empty() {
-            let doc_strings: Vec<String> = documents[0]
-                .iter()
-                .map(|d| d.clone().unwrap_or_default())
-                .collect();
+        let query_result = query_result_from_response(
+            query_response,
+            metadata_format,
+            metadata_keys_vec.as_deref(),
+            rerank_scores,
+        );
 
-            let (array, count) = vec_string_to_c_array(doc_strings);
-            query_result.documents = array;
-            query_result.documents_count = count;
+        unsafe {
+            *result = Box::into_raw(query_result);
         }
-    }
-
-    unsafe {
-        *result = query_result_ptr;
-    }
 
-    set_success(error_out);
-    ChromaErrorCode::Success as c_int
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
 }
@@ -0,0 +1,140 @@
+// Result ordering for ChromaDB C# bindings
+use chroma_types::{GetResponse, Metadata, MetadataValue};
+use libc::{c_char, c_int};
+use std::cmp::Ordering;
+
+use crate::error::{ChromaErrorCode, FfiError};
+use crate::utils::c_str_to_string;
+
+/// Fields accepted in `ChromaOrderBy::field`
+pub const CHROMA_ORDER_BY_ID: c_int = 0;
+pub const CHROMA_ORDER_BY_METADATA: c_int = 1;
+
+/// Sort order for `chroma_get`
+#[repr(C)]
+pub struct ChromaOrderBy {
+    /// `CHROMA_ORDER_BY_ID` or `CHROMA_ORDER_BY_METADATA`
+    pub field: c_int,
+    /// Metadata key to sort by when `field` is `CHROMA_ORDER_BY_METADATA`
+    pub metadata_key: *const c_char,
+    /// Sort from largest to smallest instead of smallest to largest
+    pub descending: bool,
+}
+
+/// Parsed form of `ChromaOrderBy`
+pub(crate) struct OrderBy {
+    /// Metadata key to sort by; records are sorted by ID when absent
+    pub(crate) metadata_key: Option<String>,
+    pub(crate) descending: bool,
+}
+
+impl OrderBy {
+    /// Validates a `ChromaOrderBy` passed by the caller
+    pub(crate) unsafe fn from_ffi(order_by: &ChromaOrderBy) -> Result<Self, FfiError> {
+        let metadata_key = match order_by.field {
+            CHROMA_ORDER_BY_ID => None,
+            CHROMA_ORDER_BY_METADATA if order_by.metadata_key.is_null() => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Metadata key pointer is null",
+                    None,
+                ));
+            }
+            CHROMA_ORDER_BY_METADATA => match c_str_to_string(order_by.metadata_key) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    return Err(FfiError::new(
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid order by metadata key",
+                        Some(e.to_string()),
+                    ));
+                }
+            },
+            field => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Unknown order by field",
+                    Some(format!("Got {}, expected 0 (ID) or 1 (metadata)", field)),
+                ));
+            }
+        };
+
+        Ok(OrderBy {
+            metadata_key,
+            descending: order_by.descending,
+        })
+    }
+
+    fn directed(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    fn compare_keys(&self, a: Option<&MetadataValue>, b: Option<&MetadataValue>) -> Ordering {
+        // Records without the key sort last in either direction
+        match (a, b) {
+            (Some(a), Some(b)) => self.directed(compare_values(a, b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// Sorts a get response, then keeps the page starting at `offset` of at most `limit` records
+    pub(crate) fn apply(&self, response: &mut GetResponse, offset: usize, limit: Option<usize>) {
+        let key_of = |i: usize| -> Option<&MetadataValue> {
+            let key = self.metadata_key.as_ref()?;
+            let metadata: &Metadata = response.metadatas.as_ref()?.get(i)?.as_ref()?;
+            metadata.get(key)
+        };
+
+        let mut order: Vec<usize> = (0..response.ids.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.compare_keys(key_of(a), key_of(b))
+                .then_with(|| self.directed(response.ids[a].cmp(&response.ids[b])))
+        });
+        let page: Vec<usize> = order
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+
+        response.ids = take_in_order(std::mem::take(&mut response.ids), &page);
+        response.embeddings = response.embeddings.take().map(|v| take_in_order(v, &page));
+        response.documents = response.documents.take().map(|v| take_in_order(v, &page));
+        response.uris = response.uris.take().map(|v| take_in_order(v, &page));
+        response.metadatas = response.metadatas.take().map(|v| take_in_order(v, &page));
+    }
+}
+
+/// Orders metadata values of the same type naturally; ints and floats compare numerically,
+/// and values of different types are ordered bool < number < string < other
+pub(crate) fn compare_values(a: &MetadataValue, b: &MetadataValue) -> Ordering {
+    match (a, b) {
+        (MetadataValue::Bool(a), MetadataValue::Bool(b)) => a.cmp(b),
+        (MetadataValue::Int(a), MetadataValue::Int(b)) => a.cmp(b),
+        (MetadataValue::Int(a), MetadataValue::Float(b)) => (*a as f64).total_cmp(b),
+        (MetadataValue::Float(a), MetadataValue::Int(b)) => a.total_cmp(&(*b as f64)),
+        (MetadataValue::Float(a), MetadataValue::Float(b)) => a.total_cmp(b),
+        (MetadataValue::Str(a), MetadataValue::Str(b)) => a.cmp(b),
+        _ => type_rank(a).cmp(&type_rank(b)),
+    }
+}
+
+fn type_rank(value: &MetadataValue) -> u8 {
+    match value {
+        MetadataValue::Bool(_) => 0,
+        MetadataValue::Int(_) | MetadataValue::Float(_) => 1,
+        MetadataValue::Str(_) => 2,
+        _ => 3,
+    }
+}
+
+/// Picks `values[i]` for every `i` in `order`
+pub(crate) fn take_in_order<T>(values: Vec<T>, order: &[usize]) -> Vec<T> {
+    let mut slots: Vec<Option<T>> = values.into_iter().map(Some).collect();
+    order.iter().filter_map(|&i| slots.get_mut(i)?.take()).collect()
+}
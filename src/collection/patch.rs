@@ -0,0 +1,255 @@
+// Metadata patches applied to many records in one update
+use chroma_types::{
+    GetRequest, IncludeList, UpdateCollectionRecordsRequest, UpdateMetadata, UpdateMetadataValue,
+};
+use libc::{c_char, c_int, size_t};
+use std::collections::HashMap;
+
+use crate::collection::soft_delete::is_soft_delete_key;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_array_to_vec_string, c_str_to_string};
+
+/// Modes accepted in the `merge_mode` argument of `chroma_update_metadata`
+pub const CHROMA_METADATA_MERGE: c_int = 0;
+pub const CHROMA_METADATA_REPLACE: c_int = 1;
+
+/// Builds the metadata update of each record for `CHROMA_METADATA_REPLACE`: the keys of
+/// the patch are set and every other key of the record, except soft deletion flags, is
+/// removed
+fn replacement_metadatas(
+    collection: &ChromaCollection,
+    ids: &[String],
+    patch: &UpdateMetadata,
+) -> Result<Vec<Option<UpdateMetadata>>, FfiError> {
+    let include_list = IncludeList::try_from(vec!["metadatas".to_string()]).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        Some(ids.to_vec()),
+        None,
+        None,
+        0,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    let response = collection
+        .client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to read records", &e)
+        })?;
+
+    let existing: HashMap<String, Vec<String>> = response
+        .ids
+        .into_iter()
+        .zip(response.metadatas.unwrap_or_default())
+        .map(|(id, metadata)| {
+            let keys = metadata.map_or_else(Vec::new, |m| m.into_keys().collect());
+            (id, keys)
+        })
+        .collect();
+
+    Ok(ids
+        .iter()
+        .map(|id| {
+            let mut update = patch.clone();
+            for key in existing.get(id).into_iter().flatten() {
+                if !is_soft_delete_key(key) {
+                    update.entry(key.clone()).or_insert(UpdateMetadataValue::None);
+                }
+            }
+            Some(update)
+        })
+        .collect())
+}
+
+/// Applies one metadata patch to many records in a single update.
+///
+/// `patch_json` is a JSON object of metadata keys. With `CHROMA_METADATA_MERGE` its keys
+/// are set on every record, keys set to null are removed and other keys are kept. With
+/// `CHROMA_METADATA_REPLACE` the metadata of every record becomes the patch, so keys not
+/// in the patch are removed. Embeddings and documents are left unchanged and IDs that do
+/// not exist are ignored. The soft deletion keys cannot be patched and fail the call with
+/// `InvalidArgument`.
+#[no_mangle]
+pub extern "C" fn chroma_update_metadata(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    patch_json: *const c_char,
+    merge_mode: c_int,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_update_metadata", || {
+        let func_name = "chroma_update_metadata";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || ids.is_null()
+            || ids_count == 0
+            || patch_json.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs pointer is null"
+            } else if ids_count == 0 {
+                "IDs count is zero"
+            } else {
+                "Patch JSON pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if merge_mode != CHROMA_METADATA_MERGE && merge_mode != CHROMA_METADATA_REPLACE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown merge mode",
+                func_name,
+                Some(&format!("Got {}, expected 0 (merge) or 1 (replace)", merge_mode)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let ids_vec = unsafe {
+            match c_array_to_vec_string(ids, ids_count) {
+                Ok(v) => v,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let patch = match unsafe { c_str_to_string(patch_json) }
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<UpdateMetadata>(&s).map_err(|e| e.to_string()))
+        {
+            Ok(patch) => patch,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid patch JSON",
+                    func_name,
+                    Some(&e),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        // Soft deletion flags are only set by soft deletes and `chroma_restore_records`
+        if let Some(key) = patch.keys().find(|key| is_soft_delete_key(key)) {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Patch sets a reserved metadata key",
+                func_name,
+                Some(&format!("{} is managed by soft deletion", key)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        // Held until the write is done, so no other write lands between read and write
+        let _write = (merge_mode == CHROMA_METADATA_REPLACE)
+            .then(|| collection.client.write_locks.lock(&collection.id.to_string()));
+        let metadatas = if merge_mode == CHROMA_METADATA_REPLACE {
+            match replacement_metadatas(&collection, &ids_vec, &patch) {
+                Ok(metadatas) => metadatas,
+                Err(e) => return e.report(error_out, func_name),
+            }
+        } else {
+            vec![Some(patch); ids_vec.len()]
+        };
+
+        let validated =
+            collection.validate_metadata_updates(ids_vec.len(), Some(metadatas.as_slice()), false);
+        if let Err(e) = validated {
+            return e.report(error_out, func_name);
+        }
+
+        let request = match UpdateCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            ids_vec,
+            None,
+            None,
+            None,
+            Some(metadatas),
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match collection.client.block_on_with_retry(
+            "update",
+            collection.scope().with_records(Some(request.ids.len())),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update(request).await }
+            },
+        ) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to update metadata", &e)
+                    .report(error_out, func_name)
+            }
+        }
+    })
+}
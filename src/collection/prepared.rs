@@ -0,0 +1,384 @@
+// Prepared queries, parsed once and executed many times with different query embeddings
+use chroma_types::{IncludeList, QueryRequest, Where};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::sync::Arc;
+
+use crate::abi::{read_sized, SizedInput};
+use crate::collection::filters::{optional_c_str, parse_where};
+use crate::collection::includes::{
+    check_include_flags, CHROMA_INCLUDE_DISTANCES, CHROMA_INCLUDE_DOCUMENTS,
+    CHROMA_INCLUDE_EMBEDDINGS, CHROMA_INCLUDE_METADATAS,
+};
+use crate::collection::operations::query_result_from_response;
+use crate::collection::query_options::{drop_unrequested, ChromaQueryOptions, QueryOptions};
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    collection_from_handle, insert_prepared_query, prepared_query_from_handle,
+    remove_prepared_query, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::types::{ChromaQueryResult, CHROMA_METADATA_FORMAT_JSON, CHROMA_METADATA_FORMAT_TYPED};
+use crate::utils::{c_array_to_vec_f32, c_array_to_vec_string};
+
+/// Parts of a `chroma_query` call that stay the same from one execution to the next
+#[repr(C)]
+pub struct ChromaQueryTemplate {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Metadata filter as JSON; null for none
+    pub where_filter_json: *const c_char,
+    /// Document filter as JSON; null for none
+    pub where_document_filter: *const c_char,
+    /// `CHROMA_INCLUDE_*` flags of the fields to return, or `CHROMA_INCLUDE_DEFAULTS` for
+    /// the default includes of the collection handle when the query is prepared. URIs are
+    /// not returned by queries
+    pub include_flags: c_uint,
+    /// Metadata keys to return; null for every key
+    pub metadata_keys: *const *const c_char,
+    pub metadata_keys_count: size_t,
+    /// `CHROMA_METADATA_FORMAT_*` of the returned metadata
+    pub metadata_format: c_int,
+    /// Post-processing of the hits, as passed to `chroma_query`; null for none
+    pub options: *const ChromaQueryOptions,
+}
+
+unsafe impl SizedInput for ChromaQueryTemplate {}
+
+/// A query template parsed and validated once by `chroma_prepare_query`
+pub struct ChromaPreparedQuery {
+    /// Collection the query runs against; keeps it alive after its handle is destroyed
+    pub(crate) collection: Arc<ChromaCollection>,
+    where_filter: Option<Where>,
+    /// Fields the caller asked for
+    include_flags: c_uint,
+    /// Fields read from the backend, including those only needed for post-processing
+    include_list: IncludeList,
+    metadata_keys: Option<Vec<String>>,
+    metadata_format: c_int,
+    options: Option<QueryOptions>,
+}
+
+impl ChromaPreparedQuery {
+    /// Parses a template against `collection`, reporting the errors `chroma_query` would
+    unsafe fn parse(
+        collection: Arc<ChromaCollection>,
+        template: &ChromaQueryTemplate,
+    ) -> Result<Self, FfiError> {
+        check_include_flags(template.include_flags)?;
+        if template.metadata_format != CHROMA_METADATA_FORMAT_JSON
+            && template.metadata_format != CHROMA_METADATA_FORMAT_TYPED
+        {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Unknown metadata format",
+                Some(format!("Metadata format: {}", template.metadata_format)),
+            ));
+        }
+
+        let where_json = optional_c_str(
+            template.where_filter_json,
+            "Failed to convert where filter JSON string",
+        )?;
+        let where_document = optional_c_str(
+            template.where_document_filter,
+            "Failed to convert document filter string",
+        )?;
+        if let Some(where_json) = where_json.as_deref() {
+            collection.warn_undeclared_filter_keys(where_json);
+        }
+        let where_filter = parse_where(where_json.as_deref(), where_document.as_deref())?;
+
+        let metadata_keys = if template.metadata_keys.is_null() {
+            None
+        } else {
+            let keys = c_array_to_vec_string(template.metadata_keys, template.metadata_keys_count)
+                .map_err(|e| {
+                    FfiError::new(
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert metadata keys array",
+                        Some(e.to_string()),
+                    )
+                })?;
+            Some(keys)
+        };
+
+        let mut options = match read_sized(template.options)? {
+            Some(options) => Some(QueryOptions::from_ffi(&options)?),
+            None => None,
+        };
+        // The client's re-ranker must be set now; each execution runs whichever is set then
+        if let Some(options) = options.as_mut() {
+            options.resolve_reranker(&collection.client)?;
+        }
+
+        let include_flags = collection.resolve_includes(template.include_flags);
+        let read = |flag: c_uint, needed: fn(&QueryOptions) -> bool| {
+            include_flags & flag != 0 || options.as_ref().is_some_and(needed)
+        };
+        let mut include = Vec::new();
+        if read(CHROMA_INCLUDE_EMBEDDINGS, QueryOptions::needs_embeddings) {
+            include.push("embeddings".to_string());
+        }
+        if read(CHROMA_INCLUDE_METADATAS, QueryOptions::needs_metadatas) {
+            include.push("metadatas".to_string());
+        }
+        if read(CHROMA_INCLUDE_DOCUMENTS, QueryOptions::needs_documents) {
+            include.push("documents".to_string());
+        }
+        if read(CHROMA_INCLUDE_DISTANCES, QueryOptions::needs_distances) {
+            include.push("distances".to_string());
+        }
+        let include_list = IncludeList::try_from(include).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid include list",
+                Some(format!("Include list validation error: {:?}", e)),
+            )
+        })?;
+
+        Ok(ChromaPreparedQuery {
+            collection,
+            where_filter,
+            include_flags,
+            include_list,
+            metadata_keys,
+            metadata_format: template.metadata_format,
+            options,
+        })
+    }
+
+    /// Runs the query for one embedding, whose dimension the caller has checked
+    fn execute(
+        &self,
+        query_embedding: Vec<f32>,
+        n_results: u32,
+    ) -> Result<Box<ChromaQueryResult>, FfiError> {
+        let collection = &self.collection;
+        let client = &collection.client;
+        if client.lifecycle.is_shutting_down() {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Client of the prepared query is shutting down",
+                None,
+            ));
+        }
+
+        // MMR compares the candidates to the query, which moves into the request
+        let mmr_query = match &self.options {
+            Some(options) if options.needs_embeddings() => query_embedding.clone(),
+            _ => Vec::new(),
+        };
+        let fetch_count = self.options.as_ref().map_or(n_results, |o| o.fetch_count(n_results));
+
+        let request = QueryRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            None,
+            collection.visible(self.where_filter.clone()),
+            vec![query_embedding],
+            fetch_count,
+            self.include_list.clone(),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create query request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        client.read_barrier(collection.id)?;
+        let mut response = client
+            .block_on_with_retry("query", collection.scope(), |mut frontend| {
+                let request = request.clone();
+                async move { frontend.query(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+            })?;
+
+        let mut rerank_scores = None;
+        if let Some(options) = &self.options {
+            rerank_scores = options.apply(&mut response, &mmr_query, n_results as usize)?;
+            drop_unrequested(
+                &mut response,
+                self.include_flags & CHROMA_INCLUDE_EMBEDDINGS != 0,
+                self.include_flags & CHROMA_INCLUDE_METADATAS != 0,
+                self.include_flags & CHROMA_INCLUDE_DOCUMENTS != 0,
+                self.include_flags & CHROMA_INCLUDE_DISTANCES != 0,
+            );
+        }
+
+        Ok(query_result_from_response(
+            response,
+            self.metadata_format,
+            self.metadata_keys.as_deref(),
+            rerank_scores,
+        ))
+    }
+}
+
+/// Prepares a query on a collection for repeated execution with
+/// `chroma_execute_prepared`.
+///
+/// The filters, include flags, metadata projection and options of `template` are parsed and
+/// validated once, failing here with the errors `chroma_query` would report. Strings and
+/// options passed in `template` are copied and may be released once the call returns; a
+/// re-ranker callback passed in them must stay valid for as long as the prepared query,
+/// while one set with `chroma_set_reranker` is looked up on each execution. The prepared query
+/// keeps the collection alive and is released with `chroma_destroy_prepared_query`.
+#[no_mangle]
+pub extern "C" fn chroma_prepare_query(
+    collection_handle: ChromaHandle,
+    template: *const ChromaQueryTemplate,
+    prepared_out: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_prepare_query", || {
+        let func_name = "chroma_prepare_query";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || prepared_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Prepared query output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *prepared_out = CHROMA_INVALID_HANDLE;
+        }
+
+        let template = match unsafe { read_sized(template) } {
+            Ok(Some(template)) => template,
+            Ok(None) => {
+                return FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Query template pointer is null",
+                    None,
+                )
+                .report(error_out, func_name);
+            }
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match unsafe { ChromaPreparedQuery::parse(collection, &template) } {
+            Ok(prepared) => {
+                unsafe {
+                    *prepared_out = insert_prepared_query(prepared);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Executes a prepared query for one query embedding.
+///
+/// Only the embedding and `n_results` are read; everything else was validated by
+/// `chroma_prepare_query`. The result matches that of `chroma_query` and is released with
+/// `chroma_free_query_result`.
+#[no_mangle]
+pub extern "C" fn chroma_execute_prepared(
+    prepared_handle: ChromaHandle,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    n_results: c_uint,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_execute_prepared", || {
+        let func_name = "chroma_execute_prepared";
+
+        if prepared_handle == CHROMA_INVALID_HANDLE
+            || query_embedding.is_null()
+            || embedding_dim == 0
+            || result.is_null()
+        {
+            let message = if prepared_handle == CHROMA_INVALID_HANDLE {
+                "Prepared query handle is null"
+            } else if query_embedding.is_null() {
+                "Query embedding pointer is null"
+            } else if embedding_dim == 0 {
+                "Embedding dimension is zero"
+            } else {
+                "Result pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let prepared = match prepared_query_from_handle(prepared_handle) {
+            Ok(prepared) => prepared,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = prepared.collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let query_embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+        match prepared.execute(query_embedding, n_results) {
+            Ok(query_result) => {
+                unsafe {
+                    *result = Box::into_raw(query_result);
+                }
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Releases a prepared query
+#[no_mangle]
+pub extern "C" fn chroma_destroy_prepared_query(
+    prepared_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_destroy_prepared_query", || {
+        let func_name = "chroma_destroy_prepared_query";
+
+        if prepared_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Prepared query handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if let Err(e) = remove_prepared_query(prepared_handle) {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
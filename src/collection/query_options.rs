@@ -0,0 +1,351 @@
+// Post-processing of query hits before they are marshaled
+use chroma_types::QueryResponse;
+use libc::{c_char, c_float, c_uint, c_void, size_t};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::abi::SizedInput;
+use crate::client::ChromaClient;
+use crate::collection::ordering::take_in_order;
+use crate::collection::rerank::{ChromaRerankCallback, QueryReranker, Reranker};
+use crate::error::{ChromaErrorCode, FfiError};
+use crate::utils::c_str_to_string;
+
+/// Candidates fetched per requested hit when deduplicating, unless the caller sets one
+const DEFAULT_OVER_FETCH: u32 = 3;
+
+/// Options for `chroma_query`; pass null to return the backend's hits unchanged
+#[repr(C)]
+pub struct ChromaQueryOptions {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Drops hits farther than this distance; 0 or less keeps every hit
+    pub max_distance: c_float,
+    /// Metadata key to deduplicate on: only the closest hit per value of the key is kept,
+    /// hits without the key are all kept. Null to keep duplicates
+    pub dedupe_by_metadata_key: *const c_char,
+    /// Candidates fetched per requested hit when deduplicating; 0 for the default of 3
+    pub over_fetch: c_uint,
+    /// Candidates fetched and re-ranked with maximal marginal relevance (MMR); 0 disables MMR
+    pub mmr_fetch_k: c_uint,
+    /// MMR trade-off between relevance (1) and diversity (0), from 0 to 1
+    pub mmr_lambda: c_float,
+    /// Query text handed to the re-ranker; candidates are re-ranked only when not null.
+    /// Cannot be combined with MMR
+    pub rerank_query: *const c_char,
+    /// Re-ranker for this query; null for the one set with `chroma_set_reranker`
+    pub reranker: ChromaRerankCallback,
+    /// Passed back to `reranker`
+    pub reranker_user_data: *mut c_void,
+    /// Candidates fetched and re-ranked; 0 to re-rank `n_results` candidates
+    pub rerank_fetch_k: c_uint,
+}
+
+unsafe impl SizedInput for ChromaQueryOptions {}
+
+/// Parsed form of `ChromaQueryOptions`
+pub(crate) struct QueryOptions {
+    pub(crate) max_distance: Option<f32>,
+    pub(crate) dedupe_key: Option<String>,
+    pub(crate) over_fetch: u32,
+    /// Number of MMR candidates and lambda, when MMR is enabled
+    pub(crate) mmr: Option<(u32, f32)>,
+    pub(crate) rerank_query: Option<String>,
+    pub(crate) reranker: Option<QueryReranker>,
+    pub(crate) rerank_fetch_k: u32,
+}
+
+impl QueryOptions {
+    /// Validates a `ChromaQueryOptions` passed by the caller
+    pub(crate) unsafe fn from_ffi(options: &ChromaQueryOptions) -> Result<Self, FfiError> {
+        if options.max_distance.is_nan() {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Maximum distance is not a number",
+                None,
+            ));
+        }
+        let max_distance = (options.max_distance > 0.0).then_some(options.max_distance);
+
+        let dedupe_key = if options.dedupe_by_metadata_key.is_null() {
+            None
+        } else {
+            match c_str_to_string(options.dedupe_by_metadata_key) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    return Err(FfiError::new(
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid dedupe metadata key",
+                        Some(e.to_string()),
+                    ));
+                }
+            }
+        };
+
+        let over_fetch = if options.over_fetch > 0 {
+            options.over_fetch
+        } else {
+            DEFAULT_OVER_FETCH
+        };
+
+        let mmr = if options.mmr_fetch_k == 0 {
+            None
+        } else if (0.0..=1.0).contains(&options.mmr_lambda) {
+            Some((options.mmr_fetch_k, options.mmr_lambda))
+        } else {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "MMR lambda must be between 0 and 1",
+                Some(format!("Got {}", options.mmr_lambda)),
+            ));
+        };
+
+        let rerank_query = if options.rerank_query.is_null() {
+            None
+        } else if mmr.is_some() {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Re-ranking cannot be combined with MMR",
+                None,
+            ));
+        } else {
+            match c_str_to_string(options.rerank_query) {
+                Ok(query) => Some(query),
+                Err(e) => {
+                    return Err(FfiError::new(
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid re-rank query",
+                        Some(e.to_string()),
+                    ));
+                }
+            }
+        };
+
+        Ok(QueryOptions {
+            max_distance,
+            dedupe_key,
+            over_fetch,
+            mmr,
+            rerank_query,
+            reranker: Reranker::new(options.reranker, options.reranker_user_data)
+                .map(QueryReranker::Own),
+            rerank_fetch_k: options.rerank_fetch_k,
+        })
+    }
+
+    /// Falls back to the client's re-ranker when re-ranking is asked for without a callback
+    pub(crate) fn resolve_reranker(&mut self, client: &Arc<ChromaClient>) -> Result<(), FfiError> {
+        if self.rerank_query.is_none() || self.reranker.is_some() {
+            return Ok(());
+        }
+        if client.reranker.registration().is_none() {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "No re-ranker is set",
+                Some("Pass one in the query options or set one on the client".to_string()),
+            ));
+        }
+        self.reranker = Some(QueryReranker::Client(Arc::clone(client)));
+        Ok(())
+    }
+
+    /// Number of candidates to fetch from the backend to return `n_results` hits
+    pub(crate) fn fetch_count(&self, n_results: u32) -> u32 {
+        let mut count = n_results;
+        if self.dedupe_key.is_some() {
+            count = count.max(n_results.saturating_mul(self.over_fetch));
+        }
+        if let Some((fetch_k, _)) = self.mmr {
+            count = count.max(fetch_k);
+        }
+        if self.rerank_query.is_some() {
+            count = count.max(self.rerank_fetch_k);
+        }
+        count
+    }
+
+    /// Whether the hits have to be read with their distances, even if the caller did not ask
+    pub(crate) fn needs_distances(&self) -> bool {
+        self.max_distance.is_some()
+    }
+
+    /// Whether the hits have to be read with their metadata, even if the caller did not ask
+    pub(crate) fn needs_metadatas(&self) -> bool {
+        self.dedupe_key.is_some()
+    }
+
+    /// Whether the hits have to be read with their embeddings, even if the caller did not ask
+    pub(crate) fn needs_embeddings(&self) -> bool {
+        self.mmr.is_some()
+    }
+
+    /// Whether the hits have to be read with their documents, even if the caller did not ask
+    pub(crate) fn needs_documents(&self) -> bool {
+        self.rerank_query.is_some()
+    }
+
+    /// Filters the hits of the first query of a response, then keeps at most `n_results`,
+    /// picked with MMR against `query` or ordered by the re-ranker when enabled.
+    ///
+    /// Returns the re-ranker scores of the hits kept, if they were re-ranked.
+    pub(crate) fn apply(
+        &self,
+        response: &mut QueryResponse,
+        query: &[f32],
+        n_results: usize,
+    ) -> Result<Option<Vec<f32>>, FfiError> {
+        let count = response.ids.first().map_or(0, Vec::len);
+        let mut keep: Vec<usize> = (0..count).collect();
+
+        if let Some(max_distance) = self.max_distance {
+            if let Some(distances) = response.distances.as_ref().and_then(|d| d.first()) {
+                keep.retain(|&i| match distances.get(i).copied().flatten() {
+                    Some(distance) => distance <= max_distance,
+                    None => true,
+                });
+            }
+        }
+
+        // Hits come closest first, so the first hit seen for a value is the best one
+        if let Some(key) = &self.dedupe_key {
+            if let Some(metadatas) = response.metadatas.as_ref().and_then(|m| m.first()) {
+                let mut seen = HashSet::new();
+                keep.retain(|&i| match metadatas.get(i).and_then(|m| m.as_ref()?.get(key)) {
+                    Some(value) => seen.insert(serde_json::to_string(value).unwrap_or_default()),
+                    None => true,
+                });
+            }
+        }
+
+        let mut scores = None;
+        if let (Some(rerank_query), Some(reranker)) = (&self.rerank_query, &self.reranker) {
+            let documents = response.documents.as_ref().and_then(|d| d.first());
+            let texts: Vec<&str> = keep
+                .iter()
+                .map(|&i| documents.and_then(|d| d.get(i)?.as_deref()).unwrap_or_default())
+                .collect();
+            let hit_scores = reranker.score(rerank_query, &texts)?;
+
+            // A stable sort keeps the vector search order between equal scores
+            let mut order: Vec<usize> = (0..keep.len()).collect();
+            order.sort_by(|&a, &b| hit_scores[b].total_cmp(&hit_scores[a]));
+            order.truncate(n_results);
+            scores = Some(order.iter().map(|&c| hit_scores[c]).collect());
+            keep = order.into_iter().map(|c| keep[c]).collect();
+        }
+
+        match (self.mmr, response.embeddings.as_ref().and_then(|e| e.first())) {
+            (Some((_, lambda)), Some(embeddings)) => {
+                let candidates: Vec<Option<&[f32]>> =
+                    keep.iter().map(|&i| embeddings.get(i).and_then(|e| e.as_deref())).collect();
+                keep = mmr(query, &candidates, lambda, n_results)
+                    .into_iter()
+                    .map(|c| keep[c])
+                    .collect();
+            }
+            _ => keep.truncate(n_results),
+        }
+
+        select_hits(response, &keep);
+        Ok(scores)
+    }
+}
+
+/// Drops the fields of a response read only for post-processing, which the caller did not ask
+/// to include
+pub(crate) fn drop_unrequested(
+    response: &mut QueryResponse,
+    embeddings: bool,
+    metadatas: bool,
+    documents: bool,
+    distances: bool,
+) {
+    if !embeddings {
+        response.embeddings = None;
+    }
+    if !metadatas {
+        response.metadatas = None;
+    }
+    if !documents {
+        response.documents = None;
+    }
+    if !distances {
+        response.distances = None;
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Picks up to `k` candidates by maximal marginal relevance and returns their indices in
+/// pick order. Each pick maximizes `lambda * sim(query, c) - (1 - lambda) * max sim(c, s)`
+/// over the picks `s` so far; candidates without an embedding score 0 on both terms.
+fn mmr(query: &[f32], candidates: &[Option<&[f32]>], lambda: f32, k: usize) -> Vec<usize> {
+    let similarity = |a: Option<&[f32]>, b: Option<&[f32]>| match (a, b) {
+        (Some(a), Some(b)) => cosine_similarity(a, b),
+        _ => 0.0,
+    };
+    let relevance: Vec<f32> = candidates
+        .iter()
+        .map(|c| similarity(Some(query), *c))
+        .collect();
+
+    let mut picked: Vec<usize> = Vec::with_capacity(k.min(candidates.len()));
+    // Highest similarity of every candidate to the picks so far
+    let mut redundancy = vec![f32::NEG_INFINITY; candidates.len()];
+    while picked.len() < k.min(candidates.len()) {
+        let score = |c: usize| {
+            let penalty = if picked.is_empty() { 0.0 } else { redundancy[c] };
+            lambda * relevance[c] - (1.0 - lambda) * penalty
+        };
+        let Some(best) = (0..candidates.len())
+            .filter(|c| !picked.contains(c))
+            .max_by(|&a, &b| score(a).total_cmp(&score(b)).then(b.cmp(&a)))
+        else {
+            break;
+        };
+
+        picked.push(best);
+        for (c, candidate) in candidates.iter().enumerate() {
+            let sim = similarity(*candidate, candidates[best]);
+            redundancy[c] = redundancy[c].max(sim);
+        }
+    }
+    picked
+}
+
+/// Keeps the hits at `keep`, in that order, in the first query of a response
+fn select_hits(response: &mut QueryResponse, keep: &[usize]) {
+    fn select<T>(rows: &mut [Vec<T>], keep: &[usize]) {
+        if let Some(row) = rows.first_mut() {
+            *row = take_in_order(std::mem::take(row), keep);
+        }
+    }
+
+    select(&mut response.ids, keep);
+    if let Some(rows) = response.embeddings.as_mut() {
+        select(rows, keep);
+    }
+    if let Some(rows) = response.documents.as_mut() {
+        select(rows, keep);
+    }
+    if let Some(rows) = response.uris.as_mut() {
+        select(rows, keep);
+    }
+    if let Some(rows) = response.metadatas.as_mut() {
+        select(rows, keep);
+    }
+    if let Some(rows) = response.distances.as_mut() {
+        select(rows, keep);
+    }
+}
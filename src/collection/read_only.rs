@@ -0,0 +1,164 @@
+// Per-collection read-only flag, for freezing a collection while its data is migrated
+use chroma_types::{
+    CollectionMetadataUpdate, Metadata, MetadataValue, UpdateCollectionRequest, UpdateMetadata,
+    UpdateMetadataValue,
+};
+use libc::c_int;
+use std::sync::atomic::Ordering;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    collection_from_handle, open_collections, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+
+/// Collection metadata key set while the records of the collection must not be written
+pub(crate) const READ_ONLY_KEY: &str = "read_only:enabled";
+
+/// Whether a collection with this metadata is read-only
+pub(crate) fn read_only_enabled(metadata: Option<&Metadata>) -> bool {
+    matches!(
+        metadata.and_then(|m| m.get(READ_ONLY_KEY)),
+        Some(MetadataValue::Bool(true))
+    )
+}
+
+impl ChromaCollection {
+    /// Fails with `ReadOnly` when the client is read-only and with `CollectionReadOnly` when
+    /// the collection was made read-only with `chroma_set_collection_readonly`
+    pub(crate) fn check_writable(&self) -> Result<(), FfiError> {
+        self.client.check_writable()?;
+        if self.read_only.load(Ordering::Relaxed) {
+            return Err(FfiError::new(
+                ChromaErrorCode::CollectionReadOnly,
+                "Collection is read-only",
+                Some(format!(
+                    "Collection {} was made read-only with chroma_set_collection_readonly",
+                    self.id
+                )),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Records the flag in the collection metadata and applies it to every handle of the
+    /// collection open in this client
+    fn set_read_only(&self, read_only: bool) -> Result<(), FfiError> {
+        self.client.check_writable()?;
+
+        let mut update = UpdateMetadata::new();
+        update.insert(READ_ONLY_KEY.to_string(), UpdateMetadataValue::Bool(read_only));
+        let request = UpdateCollectionRequest::try_new(
+            self.id,
+            None,
+            Some(CollectionMetadataUpdate::UpdateMetadata(update)),
+            None,
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create update collection request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        self.client
+            .block_on_with_retry("update_collection", self.scope(), |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update_collection(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to update collection", &e)
+            })?;
+
+        self.read_only.store(read_only, Ordering::Relaxed);
+        for collection in open_collections(&self.client, self.id) {
+            collection.read_only.store(read_only, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+}
+
+/// Makes the records of a collection read-only, or writable again, e.g. to freeze a
+/// production collection while it is migrated: reads keep working, and adds, updates,
+/// upserts, deletes and the other calls writing records fail with `CollectionReadOnly`.
+///
+/// The flag is stored in the collection metadata under `read_only:enabled`, so it survives
+/// restarts. It applies at once to every handle of the collection opened from the same
+/// client; handles opened by other clients or processes see it once reopened. Changing the
+/// collection's configuration, such as its TTL or schema, is still allowed.
+#[no_mangle]
+pub extern "C" fn chroma_set_collection_readonly(
+    collection_handle: ChromaHandle,
+    read_only: bool,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_collection_readonly", || {
+        let func_name = "chroma_set_collection_readonly";
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match collection.set_read_only(read_only) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Reports whether the records of a collection are read-only, as set with
+/// `chroma_set_collection_readonly`
+#[no_mangle]
+pub extern "C" fn chroma_collection_is_readonly(
+    collection_handle: ChromaHandle,
+    read_only_out: *mut bool,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_collection_is_readonly", || {
+        let func_name = "chroma_collection_is_readonly";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || read_only_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Read-only output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *read_only_out = collection.read_only.load(Ordering::Relaxed);
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,160 @@
+// Re-ranking of query candidates by a caller-provided scorer, such as a cross-encoder
+use libc::{c_char, c_float, c_int, c_void, size_t};
+use std::ffi::CString;
+use std::sync::Arc;
+
+use crate::callbacks::Callback;
+use crate::client::ChromaClient;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{client_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+
+/// Scores `count` candidate documents against the query text, writing one score per
+/// document to `scores_out`; higher scores rank first. Returning false fails the query.
+pub type ChromaRerankFn = extern "C" fn(
+    user_data: *mut c_void,
+    query: *const c_char,
+    documents: *const *const c_char,
+    count: size_t,
+    scores_out: *mut c_float,
+) -> bool;
+
+/// A `ChromaRerankFn`, or null for none
+pub type ChromaRerankCallback = Option<ChromaRerankFn>;
+
+/// A re-ranker callback and the caller's `user_data`
+pub(crate) struct Reranker {
+    callback: ChromaRerankFn,
+    user_data: *mut c_void,
+}
+
+// `user_data` is never dereferenced by the library, only handed back to the callback on the
+// thread running the query; keeping it valid is up to the caller
+unsafe impl Send for Reranker {}
+unsafe impl Sync for Reranker {}
+
+impl Callback {
+    fn as_rerank(&self) -> Option<&Reranker> {
+        match self {
+            Callback::Rerank(reranker) => Some(reranker),
+            _ => None,
+        }
+    }
+}
+
+impl Reranker {
+    pub(crate) fn new(callback: ChromaRerankCallback, user_data: *mut c_void) -> Option<Self> {
+        callback.map(|callback| Reranker {
+            callback,
+            user_data,
+        })
+    }
+
+    /// Scores `documents` against `query`
+    pub(crate) fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>, FfiError> {
+        let to_c = |s: &str| {
+            CString::new(s).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Re-ranker input contains a NUL byte",
+                    Some(e.to_string()),
+                )
+            })
+        };
+        let query = to_c(query)?;
+        let documents = documents
+            .iter()
+            .map(|d| to_c(d))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pointers: Vec<*const c_char> = documents.iter().map(|d| d.as_ptr()).collect();
+
+        let mut scores = vec![0.0; documents.len()];
+        let ok = (self.callback)(
+            self.user_data,
+            query.as_ptr(),
+            pointers.as_ptr(),
+            pointers.len(),
+            scores.as_mut_ptr(),
+        );
+        if !ok {
+            return Err(FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Re-ranker callback failed",
+                None,
+            ));
+        }
+        Ok(scores)
+    }
+}
+
+/// Re-ranker a query runs: the one passed in its options, which lives for the call, or
+/// whichever the client has set when the scores are needed
+pub(crate) enum QueryReranker {
+    Own(Reranker),
+    Client(Arc<ChromaClient>),
+}
+
+impl QueryReranker {
+    /// Scores `documents` against `query`; fails if the client's re-ranker was removed
+    pub(crate) fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>, FfiError> {
+        let registration = match self {
+            QueryReranker::Own(reranker) => return reranker.score(query, documents),
+            QueryReranker::Client(client) => client.reranker.registration(),
+        };
+
+        let mut scores = None;
+        if let Some(registration) = registration {
+            registration.invoke(|callback| {
+                if let Some(reranker) = callback.as_rerank() {
+                    scores = Some(reranker.score(query, documents));
+                }
+            });
+        }
+        scores.unwrap_or_else(|| {
+            Err(FfiError::new(
+                ChromaErrorCode::NotFound,
+                "Re-ranker was removed",
+                Some("chroma_set_reranker removed it after the query started".to_string()),
+            ))
+        })
+    }
+}
+
+/// Sets the re-ranker used by queries of a client that ask for re-ranking without passing
+/// their own callback in `ChromaQueryOptions`.
+///
+/// The callback runs on the thread calling `chroma_query`. A null `callback` removes the
+/// re-ranker. Replacing or removing it returns once no query runs the previous callback,
+/// which is never called again, so its `user_data` may be released then; otherwise it must
+/// stay valid until the client is destroyed.
+#[no_mangle]
+pub extern "C" fn chroma_set_reranker(
+    client_handle: ChromaHandle,
+    callback: ChromaRerankCallback,
+    user_data: *mut c_void,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_reranker", || {
+        let func_name = "chroma_set_reranker";
+
+        if client_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Client handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        client.reranker.replace(Reranker::new(callback, user_data).map(Callback::Rerank));
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,368 @@
+// Typed metadata schema of a collection, validated on writes
+use chroma_types::{
+    CollectionMetadataUpdate, Metadata, MetadataValue, UpdateCollectionRequest, UpdateMetadata,
+    UpdateMetadataValue,
+};
+use libc::{c_char, c_int};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ptr;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+/// Collection metadata key holding the metadata schema as JSON
+const METADATA_SCHEMA_KEY: &str = "schema:metadata";
+
+/// Type a metadata field must have
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FieldType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Int => "int",
+            FieldType::Float => "float",
+            FieldType::Bool => "bool",
+        }
+    }
+
+    /// Whether a value of the given type fits; floats also accept ints
+    fn accepts(self, value_type: &str) -> bool {
+        value_type == self.name() || (self == FieldType::Float && value_type == "int")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldSchema {
+    #[serde(rename = "type")]
+    field_type: FieldType,
+    #[serde(default)]
+    required: bool,
+}
+
+/// Declared metadata fields of a collection by key; other keys are not checked
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct MetadataSchema(BTreeMap<String, FieldSchema>);
+
+fn value_type(value: &MetadataValue) -> &'static str {
+    match value {
+        MetadataValue::Str(_) => "string",
+        MetadataValue::Int(_) => "int",
+        MetadataValue::Float(_) => "float",
+        MetadataValue::Bool(_) => "bool",
+        _ => "other",
+    }
+}
+
+/// Type of an update value, or `None` when it removes the key
+fn update_value_type(value: &UpdateMetadataValue) -> Option<&'static str> {
+    match value {
+        UpdateMetadataValue::None => None,
+        UpdateMetadataValue::Str(_) => Some("string"),
+        UpdateMetadataValue::Int(_) => Some("int"),
+        UpdateMetadataValue::Float(_) => Some("float"),
+        UpdateMetadataValue::Bool(_) => Some("bool"),
+        _ => Some("other"),
+    }
+}
+
+impl MetadataSchema {
+    fn parse(json: &str) -> Result<Self, FfiError> {
+        let schema: MetadataSchema = serde_json::from_str(json).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid metadata schema JSON",
+                Some(e.to_string()),
+            )
+        })?;
+        if let Some(key) = schema.0.keys().find(|k| k.is_empty() || k.starts_with('$')) {
+            return Err(FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid metadata schema JSON",
+                Some(format!("Field {:?} is empty or starts with '$'", key)),
+            ));
+        }
+        Ok(schema)
+    }
+
+    /// Checks the fields of one record, given as the type of each value or `None` for
+    /// values removing their key, and appends a message per violation to `errors`.
+    /// `complete` records must carry every required field.
+    fn check_record(
+        &self,
+        index: usize,
+        values: impl Fn(&str) -> Option<Option<&'static str>>,
+        complete: bool,
+        errors: &mut Vec<String>,
+    ) {
+        for (key, field) in &self.0 {
+            let path = format!("metadatas[{}].{}", index, key);
+            match values(key) {
+                Some(Some(actual)) if !field.field_type.accepts(actual) => errors.push(format!(
+                    "{}: expected {}, got {}",
+                    path,
+                    field.field_type.name(),
+                    actual
+                )),
+                Some(None) if field.required => {
+                    errors.push(format!("{}: required field cannot be removed", path))
+                }
+                None if field.required && complete => {
+                    errors.push(format!("{}: required field is missing", path))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Turns schema violations into a `ValidationError` listing their paths
+fn violations(errors: Vec<String>) -> Result<(), FfiError> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(FfiError::new(
+        ChromaErrorCode::ValidationError,
+        "Metadata does not match the collection schema",
+        Some(errors.join("; ")),
+    ))
+}
+
+impl ChromaCollection {
+    /// Metadata schema of the collection, or `None` when it declares none
+    pub(crate) fn metadata_schema(&self) -> Result<Option<MetadataSchema>, FfiError> {
+        let collection_id = self.id;
+        let stored = self
+            .client
+            .block_on_with_retry("get_collection", self.scope(), |mut frontend| async move {
+                frontend.get_cached_collection(collection_id).await
+            })
+            .map_err(|e| FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e))?;
+
+        match stored.metadata.as_ref().and_then(|m| m.get(METADATA_SCHEMA_KEY)) {
+            Some(MetadataValue::Str(json)) => MetadataSchema::parse(json).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Checks the metadata of `count` records about to be inserted against the schema
+    pub(crate) fn validate_new_metadatas(
+        &self,
+        count: usize,
+        metadatas: Option<&[Option<Metadata>]>,
+    ) -> Result<(), FfiError> {
+        let Some(schema) = self.metadata_schema()? else {
+            return Ok(());
+        };
+
+        let mut errors = Vec::new();
+        for index in 0..count {
+            let metadata = metadatas.and_then(|m| m.get(index)).and_then(Option::as_ref);
+            let values = |key: &str| metadata?.get(key).map(|value| Some(value_type(value)));
+            schema.check_record(index, values, true, &mut errors);
+        }
+        violations(errors)
+    }
+
+    /// Checks metadata updates of `count` records against the schema. Upserts pass
+    /// `complete`, since records they insert must carry every required field.
+    pub(crate) fn validate_metadata_updates(
+        &self,
+        count: usize,
+        metadatas: Option<&[Option<UpdateMetadata>]>,
+        complete: bool,
+    ) -> Result<(), FfiError> {
+        let Some(schema) = self.metadata_schema()? else {
+            return Ok(());
+        };
+
+        let mut errors = Vec::new();
+        for index in 0..count {
+            let metadata = metadatas.and_then(|m| m.get(index)).and_then(Option::as_ref);
+            let values = |key: &str| metadata?.get(key).map(update_value_type);
+            schema.check_record(index, values, complete, &mut errors);
+        }
+        violations(errors)
+    }
+}
+
+/// Attaches a metadata schema to a collection, or removes it when `schema_json` is null.
+///
+/// The schema is a JSON object mapping metadata keys to `{"type": ..., "required": ...}`,
+/// where `type` is `string`, `int`, `float` or `bool` (floats also accept ints) and
+/// `required` defaults to false. Keys it does not declare are not checked. Adds, updates,
+/// upserts, metadata patches and Arrow adds are then validated against it, failing with
+/// `ValidationError` whose details list each violation as `metadatas[index].key: reason`.
+/// Upserts must carry every required field, since they may insert records. The schema is
+/// stored in the collection metadata; records written before it was attached are not
+/// checked.
+#[no_mangle]
+pub extern "C" fn chroma_set_metadata_schema(
+    collection_handle: ChromaHandle,
+    schema_json: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_metadata_schema", || {
+        let func_name = "chroma_set_metadata_schema";
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let value = if schema_json.is_null() {
+            UpdateMetadataValue::None
+        } else {
+            let json = match unsafe { c_str_to_string(schema_json) } {
+                Ok(json) => json,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert schema string",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            };
+            // Stored normalized, so `chroma_get_metadata_schema` returns every field in full
+            match MetadataSchema::parse(&json).map(|schema| serde_json::to_string(&schema)) {
+                Ok(Ok(normalized)) => UpdateMetadataValue::Str(normalized),
+                Ok(Err(e)) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InternalError,
+                        "Failed to serialize metadata schema",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InternalError as c_int;
+                }
+                Err(e) => return e.report(error_out, func_name),
+            }
+        };
+
+        let mut update = UpdateMetadata::new();
+        update.insert(METADATA_SCHEMA_KEY.to_string(), value);
+
+        let request = match UpdateCollectionRequest::try_new(
+            collection.id,
+            None,
+            Some(CollectionMetadataUpdate::UpdateMetadata(update)),
+            None,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match collection.client.block_on_with_retry(
+            "update_collection",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update_collection(request).await }
+            },
+        ) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to update collection",
+                &e,
+            )
+            .report(error_out, func_name),
+        }
+    })
+}
+
+/// Reads the metadata schema of a collection as JSON into `*schema_json_out`, or null when
+/// it has none. Release the string with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_get_metadata_schema(
+    collection_handle: ChromaHandle,
+    schema_json_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_metadata_schema", || {
+        let func_name = "chroma_get_metadata_schema";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || schema_json_out.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Schema output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *schema_json_out = ptr::null_mut();
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let schema = match collection.metadata_schema() {
+            Ok(schema) => schema,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if let Some(schema) = schema {
+            let json = serde_json::to_string(&schema).unwrap_or_default();
+            unsafe {
+                *schema_json_out = string_to_c_str(json);
+            }
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
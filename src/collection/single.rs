@@ -0,0 +1,301 @@
+// Single-record convenience calls, which avoid marshaling arrays of length 1
+use chroma_types::{GetRequest, IncludeList};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::ptr;
+
+use crate::collection::includes::{
+    check_include_flags, CHROMA_INCLUDE_DOCUMENTS, CHROMA_INCLUDE_EMBEDDINGS,
+    CHROMA_INCLUDE_METADATAS, CHROMA_INCLUDE_URIS,
+};
+use crate::collection::operations::{chroma_delete, chroma_update};
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::{chroma_free_result_set, ChromaRecord, ChromaResultSet};
+use crate::utils::{c_str_to_string, string_to_c_str, vec_f32_to_c_array};
+
+/// Fields of a `ChromaRecord` requested by `CHROMA_INCLUDE_*` flags
+const RECORD_FIELDS: [(c_uint, &str); 4] = [
+    (CHROMA_INCLUDE_EMBEDDINGS, "embeddings"),
+    (CHROMA_INCLUDE_METADATAS, "metadatas"),
+    (CHROMA_INCLUDE_DOCUMENTS, "documents"),
+    (CHROMA_INCLUDE_URIS, "uris"),
+];
+
+/// Reads a single record by ID.
+///
+/// `include_flags` combines `CHROMA_INCLUDE_*` flags, or is `CHROMA_INCLUDE_DEFAULTS` for
+/// the default includes of the handle; distances do not apply and are ignored. When the
+/// record does not exist, `*out_record` is set to null and the call succeeds. Otherwise
+/// release the record with `chroma_free_record`.
+#[no_mangle]
+pub extern "C" fn chroma_get_one(
+    collection_handle: ChromaHandle,
+    id: *const c_char,
+    include_flags: c_uint,
+    out_record: *mut *mut ChromaRecord,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_one", || {
+        let func_name = "chroma_get_one";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || id.is_null() || out_record.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if id.is_null() {
+                "ID pointer is null"
+            } else {
+                "Output record pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if let Err(e) = check_include_flags(include_flags) {
+            return e.report(error_out, func_name);
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let id = match unsafe { c_str_to_string(id) } {
+            Ok(id) => id,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid ID",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let include_flags = collection.resolve_includes(include_flags);
+        let include: Vec<String> = RECORD_FIELDS
+            .iter()
+            .filter(|(flag, _)| include_flags & flag != 0)
+            .map(|(_, name)| name.to_string())
+            .collect();
+
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let request = match GetRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            Some(vec![id]),
+            collection.visible(None),
+            None,
+            0,
+            include_list,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = collection.client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
+        }
+        let response = match collection.client.block_on_with_retry(
+            "get",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.get(request).await }
+            },
+        ) {
+            Ok(response) => response,
+            Err(e) => {
+                return FfiError::backend(ChromaErrorCode::InternalError, "Failed to get record", &e)
+                    .report(error_out, func_name);
+            }
+        };
+
+        let Some(id) = response.ids.into_iter().next() else {
+            unsafe {
+                *out_record = ptr::null_mut();
+            }
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        };
+
+        let document = response
+            .documents
+            .and_then(|documents| documents.into_iter().next().flatten());
+        let metadata = response
+            .metadatas
+            .and_then(|metadatas| metadatas.into_iter().next().flatten())
+            .and_then(|metadata| serde_json::to_string(&metadata).ok());
+        let embedding = response
+            .embeddings
+            .and_then(|embeddings| embeddings.into_iter().next())
+            .unwrap_or_default();
+        let uri = response
+            .uris
+            .and_then(|uris| uris.into_iter().next().flatten());
+
+        let (embedding, embedding_dim) = vec_f32_to_c_array(embedding);
+        let record = ChromaRecord {
+            id: string_to_c_str(id),
+            document: document.map_or(ptr::null_mut(), string_to_c_str),
+            metadata_json: metadata.map_or(ptr::null_mut(), string_to_c_str),
+            embedding,
+            embedding_dim,
+            uri: uri.map_or(ptr::null_mut(), string_to_c_str),
+        };
+
+        unsafe {
+            *out_record = Box::into_raw(Box::new(record));
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Updates a single record.
+///
+/// Null `embedding`, `metadata_json` or `document` leave that field unchanged, as in
+/// `chroma_update`; metadata keys set to null in `metadata_json` are removed. Updating a
+/// record that does not exist has no effect. Errors are reported as by `chroma_update`.
+#[no_mangle]
+pub extern "C" fn chroma_update_one(
+    collection_handle: ChromaHandle,
+    id: *const c_char,
+    embedding: *const c_float,
+    embedding_dim: size_t,
+    metadata_json: *const c_char,
+    document: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_update_one", || {
+        if id.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "ID pointer is null",
+                "chroma_update_one",
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        // Arrays of one element, or null where the field is left unchanged
+        let ids = [id];
+        let embeddings = [embedding];
+        let metadatas = [metadata_json];
+        let documents = [document];
+        let embeddings_ptr = if embedding.is_null() {
+            ptr::null()
+        } else {
+            embeddings.as_ptr()
+        };
+        let metadatas_ptr = if metadata_json.is_null() {
+            ptr::null()
+        } else {
+            metadatas.as_ptr()
+        };
+        let documents_ptr = if document.is_null() {
+            ptr::null()
+        } else {
+            documents.as_ptr()
+        };
+
+        chroma_update(
+            collection_handle,
+            ids.as_ptr(),
+            1,
+            embeddings_ptr,
+            embedding_dim,
+            metadatas_ptr,
+            documents_ptr,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null_mut(),
+            error_out,
+        )
+    })
+}
+
+/// Deletes a single record, or flags it as deleted with `soft` set (see `chroma_delete`).
+///
+/// `deleted_out`, when not null, receives whether the record existed and was deleted.
+#[no_mangle]
+pub extern "C" fn chroma_delete_one(
+    collection_handle: ChromaHandle,
+    id: *const c_char,
+    soft: bool,
+    deleted_out: *mut bool,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_delete_one", || {
+        if id.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "ID pointer is null",
+                "chroma_delete_one",
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let ids = [id];
+        let mut deleted_ids = ChromaResultSet::empty();
+        let deleted_ids_ptr: *mut ChromaResultSet = if deleted_out.is_null() {
+            ptr::null_mut()
+        } else {
+            &mut deleted_ids
+        };
+        let code = chroma_delete(
+            collection_handle,
+            ids.as_ptr(),
+            1,
+            ptr::null(),
+            ptr::null(),
+            false,
+            soft,
+            ptr::null(),
+            deleted_ids_ptr,
+            error_out,
+        );
+
+        if let Some(deleted_out) = unsafe { deleted_out.as_mut() } {
+            *deleted_out = deleted_ids.count > 0;
+        }
+        chroma_free_result_set(&mut deleted_ids);
+        code
+    })
+}
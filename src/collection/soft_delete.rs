@@ -0,0 +1,348 @@
+// Soft deletion: records are flagged as deleted and hidden from reads until purged
+use chroma_types::{
+    CollectionMetadataUpdate, DeleteCollectionRecordsRequest, Metadata, MetadataComparison,
+    MetadataExpression, MetadataValue, PrimitiveOperator, UpdateCollectionRecordsRequest,
+    UpdateCollectionRequest, UpdateMetadata, UpdateMetadataValue, Where,
+};
+use libc::{c_char, c_int, c_uint, size_t};
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::collection::operations::matching_ids;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    collection_from_handle, open_collections, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::utils::c_array_to_vec_string;
+
+/// Collection metadata key set once a record of the collection has been soft-deleted
+pub(crate) const SOFT_DELETE_ENABLED_KEY: &str = "soft_delete:enabled";
+/// Record metadata key flagging a soft-deleted record
+const DELETED_KEY: &str = "soft_delete:deleted";
+/// Record metadata key holding the Unix time, in seconds, of the soft delete
+const DELETED_AT_KEY: &str = "soft_delete:deleted_at";
+
+/// Whether a collection with this metadata has soft-deleted records to hide
+pub(crate) fn soft_delete_enabled(metadata: Option<&Metadata>) -> bool {
+    matches!(
+        metadata.and_then(|m| m.get(SOFT_DELETE_ENABLED_KEY)),
+        Some(MetadataValue::Bool(true))
+    )
+}
+
+/// Whether a record metadata key is managed by soft deletion
+pub(crate) fn is_soft_delete_key(key: &str) -> bool {
+    key == DELETED_KEY || key == DELETED_AT_KEY
+}
+
+fn deleted_flag(operator: PrimitiveOperator) -> Where {
+    Where::Metadata(MetadataExpression {
+        key: DELETED_KEY.to_string(),
+        comparison: MetadataComparison::Primitive(operator, MetadataValue::Bool(true)),
+    })
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+impl ChromaCollection {
+    /// Restricts a read filter to records that are not soft-deleted
+    pub(crate) fn visible(&self, where_filter: Option<Where>) -> Option<Where> {
+        if !self.soft_delete.load(Ordering::Relaxed) {
+            return where_filter;
+        }
+        let not_deleted = deleted_flag(PrimitiveOperator::NotEqual);
+        Some(match where_filter {
+            Some(where_filter) => Where::conjunction(vec![where_filter, not_deleted]),
+            None => not_deleted,
+        })
+    }
+
+    /// Records in the collection metadata that reads must hide soft-deleted records
+    fn enable_soft_delete(&self) -> Result<(), FfiError> {
+        if self.soft_delete.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let mut update = UpdateMetadata::new();
+        update.insert(SOFT_DELETE_ENABLED_KEY.to_string(), UpdateMetadataValue::Bool(true));
+        let request = UpdateCollectionRequest::try_new(
+            self.id,
+            None,
+            Some(CollectionMetadataUpdate::UpdateMetadata(update)),
+            None,
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create update collection request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        self.client
+            .block_on_with_retry("update_collection", self.scope(), |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update_collection(request).await }
+            })
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to update collection", &e)
+            })?;
+
+        // Other handles of the collection must hide the records from now on as well
+        self.soft_delete.store(true, Ordering::Relaxed);
+        for collection in open_collections(&self.client, self.id) {
+            collection.soft_delete.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Sets or clears the deleted flag of records
+    fn mark_deleted(&self, ids: Vec<String>, deleted: bool) -> Result<(), FfiError> {
+        let deleted_at = unix_now();
+        let metadatas = ids
+            .iter()
+            .map(|_| {
+                let mut update = UpdateMetadata::new();
+                if deleted {
+                    update.insert(DELETED_KEY.to_string(), UpdateMetadataValue::Bool(true));
+                    update.insert(DELETED_AT_KEY.to_string(), UpdateMetadataValue::Int(deleted_at));
+                } else {
+                    update.insert(DELETED_KEY.to_string(), UpdateMetadataValue::None);
+                    update.insert(DELETED_AT_KEY.to_string(), UpdateMetadataValue::None);
+                }
+                Some(update)
+            })
+            .collect();
+
+        let request = UpdateCollectionRecordsRequest::try_new(
+            self.tenant.to_string(),
+            self.database.to_string(),
+            self.id,
+            ids,
+            None,
+            None,
+            None,
+            Some(metadatas),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create update request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        self.client
+            .block_on_with_retry(
+                "update",
+                self.scope().with_records(Some(request.ids.len())),
+                |mut frontend| {
+                    let request = request.clone();
+                    async move { frontend.update(request).await }
+                },
+            )
+            .map(|_| ())
+            .map_err(|e| {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to flag documents", &e)
+            })
+    }
+
+    /// Flags the visible records matching `ids` and `where_filter` as deleted and returns
+    /// their IDs
+    pub(crate) fn soft_delete_records(
+        &self,
+        ids: Option<Vec<String>>,
+        where_filter: Option<Where>,
+    ) -> Result<Vec<String>, FfiError> {
+        let ids = matching_ids(self, ids, self.visible(where_filter))?;
+        if !ids.is_empty() {
+            self.enable_soft_delete()?;
+            self.mark_deleted(ids.clone(), true)?;
+        }
+        Ok(ids)
+    }
+}
+
+/// Restores soft-deleted records, making them visible to reads again.
+///
+/// IDs that do not exist or are not soft-deleted are ignored. When `restored_out` is not
+/// null it receives the number of records restored.
+#[no_mangle]
+pub extern "C" fn chroma_restore_records(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    restored_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_restore_records", || {
+        let func_name = "chroma_restore_records";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || ids.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "IDs array is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let ids_vec = unsafe {
+            match c_array_to_vec_string(ids, ids_count) {
+                Ok(v) => v,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+
+        let flagged = Some(deleted_flag(PrimitiveOperator::Equal));
+        let deleted = match matching_ids(&collection, Some(ids_vec), flagged) {
+            Ok(ids) => ids,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let restored = deleted.len();
+        if !deleted.is_empty() {
+            if let Err(e) = collection.mark_deleted(deleted, false) {
+                return e.report(error_out, func_name);
+            }
+        }
+
+        if let Some(restored_out) = unsafe { restored_out.as_mut() } {
+            *restored_out = restored;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Permanently deletes records that were soft-deleted at least `older_than_secs` seconds ago.
+///
+/// Pass 0 to purge every soft-deleted record. When `purged_out` is not null it receives the
+/// number of records deleted.
+#[no_mangle]
+pub extern "C" fn chroma_purge_deleted(
+    collection_handle: ChromaHandle,
+    older_than_secs: c_uint,
+    purged_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_purge_deleted", || {
+        let func_name = "chroma_purge_deleted";
+
+        if let Some(purged) = unsafe { purged_out.as_mut() } {
+            *purged = 0;
+        }
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let cutoff = unix_now() - i64::from(older_than_secs);
+        let purgeable = Where::conjunction(vec![
+            deleted_flag(PrimitiveOperator::Equal),
+            Where::Metadata(MetadataExpression {
+                key: DELETED_AT_KEY.to_string(),
+                comparison: MetadataComparison::Primitive(
+                    PrimitiveOperator::LessThanOrEqual,
+                    MetadataValue::Int(cutoff),
+                ),
+            }),
+        ]);
+
+        let purged = match matching_ids(&collection, None, Some(purgeable)) {
+            Ok(ids) => ids,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let purged_count = purged.len();
+        if !purged.is_empty() {
+            let request = match DeleteCollectionRecordsRequest::try_new(
+                collection.tenant.to_string(),
+                collection.database.to_string(),
+                collection.id,
+                Some(purged),
+                None,
+                None,
+            ) {
+                Ok(request) => request,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::ValidationError,
+                        "Failed to create delete request",
+                        func_name,
+                        Some(&format!("Validation error: {:?}", e)),
+                    );
+                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            };
+
+            if let Err(e) = collection.client.block_on_with_retry(
+                "delete",
+                collection.scope().with_records(request.ids.as_ref().map(Vec::len)),
+                |mut frontend| {
+                    let request = request.clone();
+                    async move { frontend.delete(request, String::new()).await }
+                },
+            ) {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to purge deleted records",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+        }
+
+        if let Some(purged_out) = unsafe { purged_out.as_mut() } {
+            *purged_out = purged_count;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
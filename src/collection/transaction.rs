@@ -0,0 +1,804 @@
+// Write batches committed to the write log in a single transaction
+use chroma_types::{
+    AddCollectionRecordsRequest, Metadata, UpdateCollectionRecordsRequest, UpdateMetadata,
+    UpdateMetadataValue, Where,
+};
+use libc::{c_char, c_float, c_int, size_t};
+use serde::de::DeserializeOwned;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use crate::client::{log_topic, ChromaClient};
+use crate::collection::changes::DOCUMENT_KEY;
+use crate::collection::conflict::to_update_metadata;
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::operations::matching_ids;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{
+    batch_from_handle, client_from_handle, collection_from_handle, insert_batch, remove_batch,
+    ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::utils::{c_array_to_vec_f32, c_array_to_vec_string, c_str_to_string};
+
+/// Operation codes of the write log, as read by `chroma_get_changes_since`
+const LOG_ADD: i64 = 0;
+const LOG_UPDATE: i64 = 1;
+const LOG_DELETE: i64 = 3;
+
+/// Records passed to `chroma_batch_add`, `chroma_batch_update` or `chroma_enqueue_add`, one
+/// entry per ID
+pub(crate) struct RecordWrites<M = UpdateMetadata> {
+    pub(crate) ids: Vec<String>,
+    pub(crate) embeddings: Vec<Option<Vec<f32>>>,
+    pub(crate) metadatas: Vec<Option<M>>,
+    pub(crate) documents: Vec<Option<String>>,
+}
+
+/// Write queued on a batch until it is committed
+enum QueuedWrite {
+    Records {
+        operation: i64,
+        records: RecordWrites,
+    },
+    Delete {
+        ids: Option<Vec<String>>,
+        where_filter: Option<Where>,
+    },
+}
+
+#[derive(Default)]
+struct BatchState {
+    /// Collection every write of the batch targets, set by the first one
+    collection: Option<Arc<ChromaCollection>>,
+    writes: Vec<QueuedWrite>,
+}
+
+/// Adds, updates and deletes queued for one collection and applied all at once
+pub struct ChromaBatch {
+    /// Client the batch was begun on
+    pub(crate) client: Arc<ChromaClient>,
+    state: Mutex<BatchState>,
+}
+
+impl QueuedWrite {
+    /// Operation the write is authorized and audited as, and the number of records it
+    /// writes when known before it is applied
+    fn access(&self) -> (&'static str, Option<usize>) {
+        match self {
+            QueuedWrite::Records { operation, records } => {
+                let name = if *operation == LOG_ADD { "add" } else { "update" };
+                (name, Some(records.ids.len()))
+            }
+            QueuedWrite::Delete { ids, where_filter } => match where_filter {
+                Some(_) => ("delete", None),
+                None => ("delete", ids.as_ref().map(Vec::len)),
+            },
+        }
+    }
+}
+
+/// One row of the `embeddings_queue` table backing the write log
+struct LogRow {
+    operation: i64,
+    id: String,
+    vector: Option<Vec<u8>>,
+    metadata: Option<String>,
+}
+
+impl ChromaBatch {
+    /// Queues a write, checking that it targets the client and collection of the batch
+    fn queue(
+        &self,
+        collection: Arc<ChromaCollection>,
+        write: QueuedWrite,
+    ) -> Result<(), FfiError> {
+        if !Arc::ptr_eq(&collection.client, &self.client) {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Collection belongs to another client",
+                Some("A batch only writes through the client it was begun on".to_string()),
+            ));
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match &state.collection {
+            Some(target) if target.id != collection.id => {
+                return Err(FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Batch writes to another collection",
+                    Some(format!(
+                        "The batch targets collection {}, got {}",
+                        target.id, collection.id
+                    )),
+                ))
+            }
+            Some(_) => {}
+            None => state.collection = Some(collection),
+        }
+        state.writes.push(write);
+        Ok(())
+    }
+
+    /// Writes every queued write to the log in one SQLite transaction, then applies the log
+    /// to the segments of the collection
+    fn commit(&self) -> Result<(), FfiError> {
+        let state = std::mem::take(&mut *self.state.lock().unwrap_or_else(|e| e.into_inner()));
+        let Some(collection) = state.collection else {
+            return Ok(());
+        };
+        let client = &collection.client;
+        collection.check_writable()?;
+        if client.lifecycle.is_shutting_down() {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Client of the batch is shutting down",
+                None,
+            ));
+        }
+        let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+            return Err(FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Write log is not available",
+                Some("The SQLite database is not registered".to_string()),
+            ));
+        };
+
+        // Filters are resolved and the log written without another write in between
+        let _write = client.write_locks.lock(&collection.id.to_string());
+
+        // Every write is authorized before any is applied, as the same calls would be
+        for write in &state.writes {
+            let (operation, records) = write.access();
+            let scope = collection.scope().with_records(records);
+            if let Err(reason) = client.authorize(operation, &scope) {
+                return Err(FfiError::new(
+                    ChromaErrorCode::PermissionDenied,
+                    "Permission denied",
+                    Some(reason),
+                ));
+            }
+        }
+
+        let mut rows = Vec::new();
+        let mut audited = Vec::new();
+        let mut dimension = None;
+        for write in state.writes {
+            let (name, _) = write.access();
+            match write {
+                QueuedWrite::Records { operation, records } => {
+                    audited.push((name, records.ids.len()));
+                    let entries = records
+                        .ids
+                        .into_iter()
+                        .zip(records.embeddings)
+                        .zip(records.metadatas)
+                        .zip(records.documents);
+                    for (((id, embedding), metadata), document) in entries {
+                        if let Some(embedding) = &embedding {
+                            dimension = Some(embedding.len());
+                        }
+                        rows.push(LogRow {
+                            operation,
+                            id,
+                            vector: embedding.as_deref().map(encode_vector),
+                            metadata: log_metadata(metadata, document),
+                        });
+                    }
+                }
+                // Filters are resolved now, against the records committed before the batch
+                QueuedWrite::Delete { ids, where_filter } => {
+                    let ids = match where_filter {
+                        Some(_) => {
+                            matching_ids(&collection, ids, collection.visible(where_filter))?
+                        }
+                        None => ids.unwrap_or_default(),
+                    };
+                    audited.push((name, ids.len()));
+                    rows.extend(ids.into_iter().map(|id| LogRow {
+                        operation: LOG_DELETE,
+                        id,
+                        vector: None,
+                        metadata: None,
+                    }));
+                }
+            }
+        }
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let scope = collection
+            .scope()
+            .with_records(Some(rows.len()))
+            .with_dimension(dimension);
+        if let Err(reason) = client.check_quota("batch_commit", &scope) {
+            return Err(FfiError::new(
+                ChromaErrorCode::QuotaExceeded,
+                "Quota exceeded",
+                Some(reason),
+            ));
+        }
+        let _permit = client.concurrency.acquire("batch_commit").map_err(|reason| {
+            FfiError::new(ChromaErrorCode::Busy, "Client is busy", Some(reason))
+        })?;
+
+        // Entries go under the topic the backend's log writer uses, which the compactor reads
+        let topic = log_topic(&collection.id);
+        let committed = client
+            .runtime
+            .block_on(async {
+                let mut transaction = sqlite_db.get_conn().begin().await?;
+                // The backend checks this on every write; the collection may have been deleted
+                // since the writes were queued
+                let exists: i64 =
+                    sqlx::query_scalar("SELECT COUNT(*) FROM collections WHERE id = ?")
+                        .bind(collection.id.to_string())
+                        .fetch_one(&mut *transaction)
+                        .await?;
+                if exists == 0 {
+                    return Ok(false);
+                }
+                for row in rows {
+                    let encoding = row.vector.as_ref().map(|_| "FLOAT32");
+                    sqlx::query(
+                        "INSERT INTO embeddings_queue \
+                         (operation, topic, id, vector, encoding, metadata) \
+                         VALUES (?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(row.operation)
+                    .bind(&topic)
+                    .bind(row.id)
+                    .bind(row.vector)
+                    .bind(encoding)
+                    .bind(row.metadata)
+                    .execute(&mut *transaction)
+                    .await?;
+                }
+                transaction.commit().await?;
+                Ok::<_, sqlx::Error>(true)
+            })
+            .map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Failed to write the batch to the log",
+                    Some(e.to_string()),
+                )
+            })?;
+        if !committed {
+            return Err(FfiError::new(
+                ChromaErrorCode::NotFound,
+                "Collection not found",
+                Some(format!("Collection {} was deleted", collection.id)),
+            ));
+        }
+
+        for (operation, records) in audited {
+            client.audit(operation, &collection.scope().with_records(Some(records)));
+        }
+        if let Some(dimension) = dimension {
+            collection.record_dimension(dimension);
+        }
+        client.flush(Some(collection.id))
+    }
+}
+
+/// Encodes an embedding as the log stores it, little-endian `FLOAT32`
+fn encode_vector(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Metadata of a log entry, carrying the document under `DOCUMENT_KEY`
+fn log_metadata(metadata: Option<UpdateMetadata>, document: Option<String>) -> Option<String> {
+    let mut metadata = metadata.unwrap_or_default();
+    if let Some(document) = document {
+        metadata.insert(DOCUMENT_KEY.to_string(), UpdateMetadataValue::Str(document));
+    }
+    (!metadata.is_empty()).then(|| serde_json::to_string(&metadata).unwrap_or_default())
+}
+
+/// Reads the record arrays of `chroma_batch_add`, `chroma_batch_update` or
+/// `chroma_enqueue_add`, parsing each metadata JSON string as `M`
+pub(crate) unsafe fn read_records<M: DeserializeOwned>(
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const *const c_float,
+    embedding_dim: size_t,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+) -> Result<RecordWrites<M>, FfiError> {
+    let ids = c_array_to_vec_string(ids, ids_count).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Failed to convert IDs array",
+            Some(e.to_string()),
+        )
+    })?;
+
+    let mut embeddings_vec = Vec::with_capacity(ids_count);
+    let mut metadatas_vec = Vec::with_capacity(ids_count);
+    let mut documents_vec = Vec::with_capacity(ids_count);
+    for i in 0..ids_count {
+        let embedding_ptr = if embeddings.is_null() {
+            ptr::null()
+        } else {
+            *embeddings.add(i)
+        };
+        embeddings_vec.push(
+            (!embedding_ptr.is_null()).then(|| c_array_to_vec_f32(embedding_ptr, embedding_dim)),
+        );
+
+        let metadata_ptr = if metadatas_json.is_null() {
+            ptr::null()
+        } else {
+            *metadatas_json.add(i)
+        };
+        let metadata = if metadata_ptr.is_null() {
+            None
+        } else {
+            let json = c_str_to_string(metadata_ptr).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert metadata string",
+                    Some(format!("Error at index {}: {}", i, e)),
+                )
+            })?;
+            if json.is_empty() {
+                None
+            } else {
+                Some(serde_json::from_str::<M>(&json).map_err(|e| {
+                    FfiError::new(
+                        ChromaErrorCode::ValidationError,
+                        "Invalid metadata JSON",
+                        Some(format!("Error parsing metadata at index {}: {}", i, e)),
+                    )
+                })?)
+            }
+        };
+        metadatas_vec.push(metadata);
+
+        let document_ptr = if documents.is_null() {
+            ptr::null()
+        } else {
+            *documents.add(i)
+        };
+        let document = if document_ptr.is_null() {
+            None
+        } else {
+            let document = c_str_to_string(document_ptr).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert document string",
+                    Some(format!("Error at index {}: {}", i, e)),
+                )
+            })?;
+            (!document.is_empty()).then_some(document)
+        };
+        documents_vec.push(document);
+    }
+
+    Ok(RecordWrites {
+        ids,
+        embeddings: embeddings_vec,
+        metadatas: metadatas_vec,
+        documents: documents_vec,
+    })
+}
+
+/// Looks up the batch and collection of a queued write and checks they can be written
+fn write_target(
+    batch_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+) -> Result<(Arc<ChromaBatch>, Arc<ChromaCollection>), FfiError> {
+    let batch = batch_from_handle(batch_handle)?;
+    let collection = collection_from_handle(collection_handle)?;
+    collection.check_writable()?;
+    Ok((batch, collection))
+}
+
+/// Checks and queues the records of `chroma_batch_add` (`add` set) or `chroma_batch_update`
+#[allow(clippy::too_many_arguments)]
+fn queue_records(
+    batch_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const *const c_float,
+    embedding_dim: size_t,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+    add: bool,
+) -> Result<(), FfiError> {
+    if batch_handle == CHROMA_INVALID_HANDLE
+        || collection_handle == CHROMA_INVALID_HANDLE
+        || ids.is_null()
+        || ids_count == 0
+        || (add && embeddings.is_null())
+    {
+        let message = if batch_handle == CHROMA_INVALID_HANDLE {
+            "Batch handle is null"
+        } else if collection_handle == CHROMA_INVALID_HANDLE {
+            "Collection handle is null"
+        } else if ids.is_null() {
+            "IDs pointer is null"
+        } else if ids_count == 0 {
+            "IDs count is zero"
+        } else {
+            "Embeddings pointer is null"
+        };
+        return Err(FfiError::new(ChromaErrorCode::InvalidArgument, message, None));
+    }
+
+    let (batch, collection) = write_target(batch_handle, collection_handle)?;
+    if !embeddings.is_null() {
+        collection.check_dimension(embedding_dim)?;
+    }
+
+    let records = if add {
+        let records = unsafe {
+            read_records::<Metadata>(
+                ids,
+                ids_count,
+                embeddings,
+                embedding_dim,
+                metadatas_json,
+                documents,
+            )?
+        };
+        if let Some(index) = records.embeddings.iter().position(Option::is_none) {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Embedding pointer is null",
+                Some(format!("Null embedding at index {}", index)),
+            ));
+        }
+        collection.validate_new_metadatas(ids_count, Some(records.metadatas.as_slice()))?;
+        // Built only to run the checks the backend applies to adds
+        AddCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            records.ids.clone(),
+            records.embeddings.iter().flatten().cloned().collect(),
+            Some(records.documents.clone()),
+            None,
+            Some(records.metadatas.clone()),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create add request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        RecordWrites {
+            ids: records.ids,
+            embeddings: records.embeddings,
+            metadatas: records
+                .metadatas
+                .into_iter()
+                .map(|metadata| metadata.map(to_update_metadata))
+                .collect(),
+            documents: records.documents,
+        }
+    } else {
+        let records = unsafe {
+            read_records::<UpdateMetadata>(
+                ids,
+                ids_count,
+                embeddings,
+                embedding_dim,
+                metadatas_json,
+                documents,
+            )?
+        };
+        collection.validate_metadata_updates(ids_count, Some(records.metadatas.as_slice()), false)?;
+        // Built only to run the checks the backend applies to updates
+        UpdateCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            records.ids.clone(),
+            Some(records.embeddings.clone()),
+            Some(records.documents.clone()),
+            None,
+            Some(records.metadatas.clone()),
+        )
+        .map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Failed to create update request",
+                Some(format!("Validation error: {:?}", e)),
+            )
+        })?;
+
+        records
+    };
+
+    let operation = if add { LOG_ADD } else { LOG_UPDATE };
+    batch.queue(collection, QueuedWrite::Records { operation, records })
+}
+
+/// Begins a write batch on a client.
+///
+/// Adds, updates and deletes queued on the batch with `chroma_batch_add`,
+/// `chroma_batch_update` and `chroma_batch_delete` are checked when queued but not applied
+/// until `chroma_batch_commit`, which writes them all to the write log in one SQLite
+/// transaction: either every write is applied or none is. All writes of a batch target one
+/// collection, set by the first one. `chroma_batch_abort` discards the batch instead.
+#[no_mangle]
+pub extern "C" fn chroma_batch_begin(
+    client_handle: ChromaHandle,
+    batch_out: *mut ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_begin", || {
+        let func_name = "chroma_batch_begin";
+
+        if client_handle == CHROMA_INVALID_HANDLE || batch_out.is_null() {
+            let message = if client_handle == CHROMA_INVALID_HANDLE {
+                "Client handle is null"
+            } else {
+                "Batch output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *batch_out = CHROMA_INVALID_HANDLE;
+        }
+
+        let client = match client_from_handle(client_handle) {
+            Ok(client) => client,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let batch = ChromaBatch {
+            client,
+            state: Mutex::new(BatchState::default()),
+        };
+        unsafe {
+            *batch_out = insert_batch(batch);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Queues records to add on a batch. The arguments match `chroma_add`, except that every
+/// record needs an embedding; adds of existing IDs are ignored when the batch is committed.
+#[no_mangle]
+pub extern "C" fn chroma_batch_add(
+    batch_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const *const c_float,
+    embedding_dim: size_t,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_add", || {
+        let func_name = "chroma_batch_add";
+
+        match queue_records(
+            batch_handle,
+            collection_handle,
+            ids,
+            ids_count,
+            embeddings,
+            embedding_dim,
+            metadatas_json,
+            documents,
+            true,
+        ) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Queues record updates on a batch. The arguments match `chroma_update` without filters;
+/// updates of IDs that do not exist are ignored when the batch is committed.
+#[no_mangle]
+pub extern "C" fn chroma_batch_update(
+    batch_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    embeddings: *const *const c_float,
+    embedding_dim: size_t,
+    metadatas_json: *const *const c_char,
+    documents: *const *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_update", || {
+        let func_name = "chroma_batch_update";
+
+        match queue_records(
+            batch_handle,
+            collection_handle,
+            ids,
+            ids_count,
+            embeddings,
+            embedding_dim,
+            metadatas_json,
+            documents,
+            false,
+        ) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Queues a delete on a batch. The IDs and filters match `chroma_delete`; filters are
+/// evaluated when the batch is committed, against the records committed before it.
+#[no_mangle]
+pub extern "C" fn chroma_batch_delete(
+    batch_handle: ChromaHandle,
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_delete", || {
+        let func_name = "chroma_batch_delete";
+
+        if batch_handle == CHROMA_INVALID_HANDLE
+            || collection_handle == CHROMA_INVALID_HANDLE
+            || (ids.is_null() && where_filter_json.is_null() && where_document_filter.is_null())
+        {
+            let message = if batch_handle == CHROMA_INVALID_HANDLE {
+                "Batch handle is null"
+            } else if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Either document IDs or filter criteria must be specified"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let (batch, collection) = match write_target(batch_handle, collection_handle) {
+            Ok(target) => target,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let ids_vec = if !ids.is_null() && ids_count > 0 {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            None
+        };
+
+        let where_filter = unsafe { parse_where_ptrs(where_filter_json, where_document_filter) };
+        let where_filter = match where_filter {
+            Ok(where_filter) => where_filter,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let write = QueuedWrite::Delete {
+            ids: ids_vec,
+            where_filter,
+        };
+        match batch.queue(collection, write) {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Applies the writes queued on a batch atomically and closes it.
+///
+/// The writes go to the write log in one SQLite transaction, in the order they were queued,
+/// and are then applied to the collection's segments. If writing the log fails nothing is
+/// applied. Each queued write is checked by the authorizer set with `chroma_set_authorizer`
+/// as the matching `add`, `update` or `delete` call, and nothing is applied if any is
+/// denied; once committed, each is recorded in the audit log set with `chroma_set_audit_log`.
+/// Fails with `NotFound`, applying nothing, if the collection was deleted after the writes
+/// were queued. The batch handle is released whether or not the commit succeeds.
+#[no_mangle]
+pub extern "C" fn chroma_batch_commit(
+    batch_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_commit", || {
+        let func_name = "chroma_batch_commit";
+
+        if batch_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Batch handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let batch = match remove_batch(batch_handle) {
+            Ok(batch) => batch,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        match batch.commit() {
+            Ok(()) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => e.report(error_out, func_name),
+        }
+    })
+}
+
+/// Discards the writes queued on a batch and closes it
+#[no_mangle]
+pub extern "C" fn chroma_batch_abort(
+    batch_handle: ChromaHandle,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_batch_abort", || {
+        let func_name = "chroma_batch_abort";
+
+        if batch_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Batch handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if let Err(e) = remove_batch(batch_handle) {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,263 @@
+// Expiration of records after a collection-level time to live
+use chroma_types::{
+    CollectionMetadataUpdate, DeleteCollectionRecordsRequest, MetadataValue,
+    UpdateCollectionRequest, UpdateMetadata, UpdateMetadataValue,
+};
+use libc::{c_char, c_int, c_uint, size_t};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::collection::filters::parse_where;
+use crate::collection::operations::matching_ids;
+use crate::collection::types::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::c_str_to_string;
+
+/// Collection metadata key holding the time to live, in seconds
+const TTL_SECONDS_KEY: &str = "ttl:seconds";
+/// Collection metadata key naming the record metadata key that holds the write time
+const TTL_TIMESTAMP_KEY: &str = "ttl:timestamp_key";
+
+/// Time to live of a collection, as stored in its metadata
+struct Ttl {
+    seconds: i64,
+    timestamp_key: String,
+}
+
+fn read_ttl(collection: &ChromaCollection) -> Result<Option<Ttl>, FfiError> {
+    let collection_id = collection.id;
+    let stored = collection
+        .client
+        .block_on_with_retry("get_collection", collection.scope(), |mut frontend| async move {
+            frontend.get_cached_collection(collection_id).await
+        })
+        .map_err(|e| FfiError::backend(ChromaErrorCode::NotFound, "Collection not found", &e))?;
+
+    let Some(metadata) = stored.metadata else {
+        return Ok(None);
+    };
+    match (metadata.get(TTL_SECONDS_KEY), metadata.get(TTL_TIMESTAMP_KEY)) {
+        (Some(MetadataValue::Int(seconds)), Some(MetadataValue::Str(timestamp_key))) => {
+            Ok(Some(Ttl {
+                seconds: *seconds,
+                timestamp_key: timestamp_key.clone(),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Sets how long records of a collection live.
+///
+/// Records expire `seconds` after the Unix time (in whole seconds, stored as an integer)
+/// found under `timestamp_key` in their metadata; records without that key never expire.
+/// The policy is stored in the collection metadata, so it survives restarts and is shared
+/// by every client. Passing 0 seconds removes it. Expired records are removed by
+/// `chroma_purge_expired`.
+#[no_mangle]
+pub extern "C" fn chroma_set_collection_ttl(
+    collection_handle: ChromaHandle,
+    seconds: c_uint,
+    timestamp_key: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_collection_ttl", || {
+        let func_name = "chroma_set_collection_ttl";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || (seconds > 0 && timestamp_key.is_null()) {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Timestamp key pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.client.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let mut update = UpdateMetadata::new();
+        if seconds > 0 {
+            let timestamp_key = match unsafe { c_str_to_string(timestamp_key) } {
+                Ok(s) if !s.is_empty() => s,
+                Ok(_) | Err(_) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid timestamp key",
+                        func_name,
+                        None,
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            };
+            update.insert(TTL_SECONDS_KEY.to_string(), UpdateMetadataValue::Int(seconds.into()));
+            update.insert(TTL_TIMESTAMP_KEY.to_string(), UpdateMetadataValue::Str(timestamp_key));
+        } else {
+            update.insert(TTL_SECONDS_KEY.to_string(), UpdateMetadataValue::None);
+            update.insert(TTL_TIMESTAMP_KEY.to_string(), UpdateMetadataValue::None);
+        }
+
+        let request = match UpdateCollectionRequest::try_new(
+            collection.id,
+            None,
+            Some(CollectionMetadataUpdate::UpdateMetadata(update)),
+            None,
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update collection request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match collection.client.block_on_with_retry(
+            "update_collection",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update_collection(request).await }
+            },
+        ) {
+            Ok(_) => {
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to update collection",
+                &e,
+            )
+            .report(error_out, func_name),
+        }
+    })
+}
+
+/// Deletes the records of a collection whose time to live has passed.
+///
+/// Does nothing on collections without a TTL. When `purged_out` is not null it receives
+/// the number of records deleted.
+#[no_mangle]
+pub extern "C" fn chroma_purge_expired(
+    collection_handle: ChromaHandle,
+    purged_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_purge_expired", || {
+        let func_name = "chroma_purge_expired";
+
+        if let Some(purged) = unsafe { purged_out.as_mut() } {
+            *purged = 0;
+        }
+
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        let ttl = match read_ttl(&collection) {
+            Ok(Some(ttl)) => ttl,
+            Ok(None) => {
+                set_success(error_out);
+                return ChromaErrorCode::Success as c_int;
+            }
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let filter = serde_json::json!({ (ttl.timestamp_key): { "$lt": now - ttl.seconds } });
+        let where_filter = match parse_where(Some(&filter.to_string()), None) {
+            Ok(where_filter) => where_filter,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        // Held until the delete is done, so the count matches the records deleted and no
+        // write refreshing a timestamp lands between lookup and delete
+        let _write = collection.client.write_locks.lock(&collection.id.to_string());
+        let expired = match matching_ids(&collection, None, where_filter) {
+            Ok(ids) => ids,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if !expired.is_empty() {
+            let purged = expired.len();
+            let request = match DeleteCollectionRecordsRequest::try_new(
+                collection.tenant.to_string(),
+                collection.database.to_string(),
+                collection.id,
+                Some(expired),
+                None,
+                None,
+            ) {
+                Ok(request) => request,
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::ValidationError,
+                        "Failed to create delete request",
+                        func_name,
+                        Some(&format!("Validation error: {:?}", e)),
+                    );
+                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            };
+
+            if let Err(e) = collection.client.block_on_with_retry(
+                "delete",
+                collection.scope().with_records(request.ids.as_ref().map(Vec::len)),
+                |mut frontend| {
+                    let request = request.clone();
+                    async move { frontend.delete(request, String::new()).await }
+                },
+            ) {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to delete expired records",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+
+            if let Some(purged_out) = unsafe { purged_out.as_mut() } {
+                *purged_out = purged;
+            }
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -1,33 +1,125 @@
-use libc::c_int;
+use chroma_types::CollectionUuid;
+use libc::{c_char, c_int};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use crate::error::{set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::client::ChromaClient;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{
+    collection_from_handle, remove_collection, ChromaHandle, CHROMA_INVALID_HANDLE,
+};
+use crate::utils::string_to_c_str;
 
 pub struct ChromaCollection {
-    pub(crate) id: String,
-    pub(crate) tenant: String,
-    pub(crate) database: String,
+    /// Client the collection was opened from; keeps its runtime and frontend alive
+    pub(crate) client: Arc<ChromaClient>,
+    pub(crate) id: CollectionUuid,
+    pub(crate) tenant: Arc<str>,
+    pub(crate) database: Arc<str>,
+    /// Embedding dimension of the collection, 0 until known
+    pub(crate) dimension: AtomicUsize,
+    /// Whether reads hide soft-deleted records. Read from the collection metadata when the
+    /// handle is opened; a soft delete made by another process is only seen after reopening
+    pub(crate) soft_delete: AtomicBool,
+    /// Whether writes to the records are refused, see `chroma_set_collection_readonly`. Read
+    /// from the collection metadata when the handle is opened
+    pub(crate) read_only: AtomicBool,
+    /// `CHROMA_INCLUDE_*` flags used by calls passed `CHROMA_INCLUDE_DEFAULTS`
+    pub(crate) default_includes: AtomicU32,
+    /// Identity of the client handle the collection was opened through, seen by the authorizer
+    pub(crate) principal: Option<Arc<str>>,
+}
+
+impl ChromaCollection {
+    /// Fails with `DimensionMismatch` if embeddings of `actual` dimensions cannot belong
+    /// to the collection
+    pub(crate) fn check_dimension(&self, actual: usize) -> Result<(), FfiError> {
+        let expected = self.dimension.load(Ordering::Relaxed);
+        if expected != 0 && expected != actual {
+            return Err(FfiError::new(
+                ChromaErrorCode::DimensionMismatch,
+                "Embedding dimension does not match the collection",
+                Some(format!("Expected {} dimensions, got {}", expected, actual)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Remembers the dimension once the backend has accepted embeddings
+    pub(crate) fn record_dimension(&self, dimension: usize) {
+        let _ = self
+            .dimension
+            .compare_exchange(0, dimension, Ordering::Relaxed, Ordering::Relaxed);
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn chroma_destroy_collection(
-    collection_handle: *mut ChromaCollection,
+    collection_handle: ChromaHandle,
     error_out: *mut *mut ChromaError,
 ) -> c_int {
-    if collection_handle.is_null() {
-        set_error(
-            error_out,
-            ChromaErrorCode::InvalidArgument,
-            "Collection handle pointer is null",
-            "chroma_destroy_collection",
-            None,
-        );
-        return ChromaErrorCode::InvalidArgument as c_int;
-    }
+    ffi_boundary(error_out, "chroma_destroy_collection", || {
+        if collection_handle == CHROMA_INVALID_HANDLE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Collection handle is null",
+                "chroma_destroy_collection",
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
 
-    unsafe {
-        let _ = Box::from_raw(collection_handle);
-    }
+        if let Err(e) = remove_collection(collection_handle) {
+            return e.report(error_out, "chroma_destroy_collection");
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Returns the UUID of a collection as a string.
+///
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_collection_get_id(
+    collection_handle: ChromaHandle,
+    out_id: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_collection_get_id", || {
+        let func_name = "chroma_collection_get_id";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || out_id.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else {
+                "Output ID pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *out_id = string_to_c_str(collection.id.0.to_string());
+        }
 
-    set_success(error_out);
-    ChromaErrorCode::Success as c_int
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
 }
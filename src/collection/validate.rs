@@ -0,0 +1,202 @@
+// Validation of user-supplied where filters before they are run
+use chroma_types::RawWhereFields;
+use libc::{c_char, c_int};
+use serde_json::{Map, Value};
+use std::ptr;
+
+use crate::collection::filters::optional_c_str;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::utils::string_to_c_str;
+
+/// Which argument of `chroma_validate_filter` a filter was passed in
+#[derive(Clone, Copy)]
+enum FilterKind {
+    Where,
+    WhereDocument,
+}
+
+impl FilterKind {
+    fn name(self) -> &'static str {
+        match self {
+            FilterKind::Where => "where",
+            FilterKind::WhereDocument => "where_document",
+        }
+    }
+
+    /// Parses a filter of this kind on its own with the backend parser
+    fn parse(self, filter: &Value) -> Result<(), String> {
+        let json = filter.to_string();
+        let raw = match self {
+            FilterKind::Where => RawWhereFields::from_json_str(Some(&json), None),
+            FilterKind::WhereDocument => RawWhereFields::from_json_str(None, Some(&json)),
+        }
+        .map_err(|e| format!("{:?}", e))?;
+        raw.parse().map(|_| ()).map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// Finds the innermost part of a rejected filter that the backend parser still rejects, and
+/// returns its path and the parser error
+fn locate_error(
+    kind: FilterKind,
+    filter: &Value,
+    path: String,
+    error: String,
+) -> (String, String) {
+    let Value::Object(object) = filter else {
+        return (path, error);
+    };
+
+    // Several keys are an implicit `$and`; find the first key rejected on its own
+    if object.len() > 1 {
+        for (key, value) in object {
+            let single = Value::Object(Map::from_iter([(key.clone(), value.clone())]));
+            if let Err(error) = kind.parse(&single) {
+                return locate_error(kind, &single, path, error);
+            }
+        }
+        return (path, error);
+    }
+
+    let Some((key, value)) = object.iter().next() else {
+        return (path, error);
+    };
+    let path = format!("{}.{}", path, key);
+    if let ("$and" | "$or", Value::Array(children)) = (key.as_str(), value) {
+        for (i, child) in children.iter().enumerate() {
+            if let Err(error) = kind.parse(child) {
+                return locate_error(kind, child, format!("{}[{}]", path, i), error);
+            }
+        }
+    }
+    (path, error)
+}
+
+/// Rewrites a parsed metadata filter in explicit form: shorthand equality becomes `$eq` and
+/// objects with several keys become `$and`
+fn normalize_where(filter: Value) -> Value {
+    let Value::Object(object) = filter else {
+        return filter;
+    };
+
+    if object.len() > 1 {
+        let children = object
+            .into_iter()
+            .map(|(key, value)| normalize_where(Value::Object(Map::from_iter([(key, value)]))))
+            .collect();
+        return Value::Object(Map::from_iter([("$and".to_string(), Value::Array(children))]));
+    }
+
+    let normalized = object.into_iter().map(|(key, value)| {
+        let value = match (key.as_str(), value) {
+            ("$and" | "$or", Value::Array(children)) => {
+                Value::Array(children.into_iter().map(normalize_where).collect())
+            }
+            (_, Value::Object(operators)) if operators.keys().all(|k| k.starts_with('$')) => {
+                Value::Object(operators)
+            }
+            (_, value) => Value::Object(Map::from_iter([("$eq".to_string(), value)])),
+        };
+        (key, value)
+    });
+    Value::Object(normalized.collect())
+}
+
+/// Parses and checks one filter, returning it in normalized form
+fn validate(kind: FilterKind, json: &str) -> Result<Value, FfiError> {
+    let message = match kind {
+        FilterKind::Where => "Invalid where filter",
+        FilterKind::WhereDocument => "Invalid document filter",
+    };
+
+    let filter: Value = serde_json::from_str(json).map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            message,
+            Some(format!("At {}: {}", kind.name(), e)),
+        )
+    })?;
+
+    if let Err(error) = kind.parse(&filter) {
+        let (path, error) = locate_error(kind, &filter, kind.name().to_string(), error);
+        return Err(FfiError::new(
+            ChromaErrorCode::ValidationError,
+            message,
+            Some(format!("At {}: {}", path, error)),
+        ));
+    }
+
+    Ok(match kind {
+        FilterKind::Where => normalize_where(filter),
+        FilterKind::WhereDocument => filter,
+    })
+}
+
+/// Checks a metadata filter and a document filter without running them.
+///
+/// Either filter may be null. On success `*out_normalized_json` receives a JSON object with
+/// the `where` and `where_document` filters that were given, with shorthand equality written
+/// as `$eq` and multi-key objects as `$and`. On failure the error details name the path of
+/// the rejected part, such as `where.$and[1].year`, and `*out_normalized_json` is null.
+/// The string is allocated with `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_validate_filter(
+    where_json: *const c_char,
+    where_document: *const c_char,
+    out_normalized_json: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_validate_filter", || {
+        let func_name = "chroma_validate_filter";
+
+        if out_normalized_json.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Output JSON pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        unsafe {
+            *out_normalized_json = ptr::null_mut();
+        }
+
+        let where_json =
+            match unsafe { optional_c_str(where_json, "Failed to convert where filter string") } {
+                Ok(s) => s,
+                Err(e) => return e.report(error_out, func_name),
+            };
+        let where_document = match unsafe {
+            optional_c_str(where_document, "Failed to convert document filter string")
+        } {
+            Ok(s) => s,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let mut normalized = Map::new();
+        for (kind, json) in [
+            (FilterKind::Where, where_json),
+            (FilterKind::WhereDocument, where_document),
+        ] {
+            let Some(json) = json else {
+                continue;
+            };
+            match validate(kind, &json) {
+                Ok(filter) => {
+                    normalized.insert(kind.name().to_string(), filter);
+                }
+                Err(e) => return e.report(error_out, func_name),
+            }
+        }
+
+        unsafe {
+            *out_normalized_json = string_to_c_str(Value::Object(normalized).to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
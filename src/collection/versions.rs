@@ -0,0 +1,125 @@
+// Per-record version tokens for optimistic concurrency
+use std::collections::HashMap;
+
+use sqlx::Row;
+
+use crate::collection::types::ChromaCollection;
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Number of IDs looked up per query, well below the SQLite bound-parameter limit
+const VERSION_LOOKUP_CHUNK: usize = 400;
+
+/// Mismatches listed in a conflict error before the rest are summarized
+const MAX_LISTED_CONFLICTS: usize = 10;
+
+impl ChromaCollection {
+    /// Reads the version token of each record.
+    ///
+    /// The version of a record is the sequence number of the last write to it in the log,
+    /// so every add, update, upsert or delete moves it forward. Records that were never
+    /// written have version 0.
+    pub(crate) fn record_versions(&self, ids: &[String]) -> Result<Vec<i64>, FfiError> {
+        let client = &self.client;
+        let Some(sqlite_db) = client.sqlite_db.as_ref() else {
+            return Err(FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Record versions are not available",
+                Some("The SQLite database is not registered".to_string()),
+            ));
+        };
+        let read_error = |e: sqlx::Error| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to read record versions",
+                Some(e.to_string()),
+            )
+        };
+
+        // Applied writes keep their sequence number on the metadata segment, writes still
+        // in the log (or kept there) have theirs in the queue
+        let topic = self.id.to_string();
+        let mut versions = HashMap::with_capacity(ids.len());
+        for chunk in ids.chunks(VERSION_LOOKUP_CHUNK) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let sql = format!(
+                "SELECT id, MAX(seq_id) FROM ( \
+                 SELECT e.embedding_id AS id, e.seq_id AS seq_id \
+                 FROM embeddings e JOIN segments s ON e.segment_id = s.id \
+                 WHERE s.collection = ? AND e.embedding_id IN ({placeholders}) \
+                 UNION ALL \
+                 SELECT id, seq_id FROM embeddings_queue \
+                 WHERE (topic = ? OR topic LIKE '%/' || ?) AND id IN ({placeholders}) \
+                 ) GROUP BY id"
+            );
+            let rows = client
+                .runtime
+                .block_on(async {
+                    let mut query = sqlx::query(&sql).bind(&topic);
+                    for id in chunk {
+                        query = query.bind(id);
+                    }
+                    query = query.bind(&topic).bind(&topic);
+                    for id in chunk {
+                        query = query.bind(id);
+                    }
+                    query.fetch_all(sqlite_db.get_conn()).await
+                })
+                .map_err(read_error)?;
+
+            for row in rows {
+                let id: String = row.try_get(0).map_err(read_error)?;
+                let seq: Option<i64> = row.try_get(1).map_err(read_error)?;
+                versions.insert(id, seq.unwrap_or(0));
+            }
+        }
+
+        Ok(ids
+            .iter()
+            .map(|id| versions.get(id).copied().unwrap_or(0))
+            .collect())
+    }
+
+    /// Fails with `ConflictError` unless each record is still at its expected version.
+    ///
+    /// `expected[i]` belongs to `ids[i]`; a negative entry skips the check for that record.
+    pub(crate) fn check_versions(&self, ids: &[String], expected: &[i64]) -> Result<(), FfiError> {
+        let checked: Vec<(String, i64)> = ids
+            .iter()
+            .zip(expected)
+            .filter(|(_, &version)| version >= 0)
+            .map(|(id, &version)| (id.clone(), version))
+            .collect();
+        if checked.is_empty() {
+            return Ok(());
+        }
+
+        let checked_ids: Vec<String> = checked.iter().map(|(id, _)| id.clone()).collect();
+        let current = self.record_versions(&checked_ids)?;
+        let conflicts: Vec<String> = checked
+            .iter()
+            .zip(&current)
+            .filter(|((_, expected), found)| expected != *found)
+            .map(|((id, expected), found)| {
+                format!("{}: expected {}, found {}", id, expected, found)
+            })
+            .collect();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let mut details = conflicts
+            .iter()
+            .take(MAX_LISTED_CONFLICTS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("; ");
+        if conflicts.len() > MAX_LISTED_CONFLICTS {
+            details.push_str(&format!("; and {} more", conflicts.len() - MAX_LISTED_CONFLICTS));
+        }
+        Err(FfiError::new(
+            ChromaErrorCode::ConflictError,
+            "Records changed since the expected versions",
+            Some(details),
+        ))
+    }
+}
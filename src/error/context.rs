@@ -0,0 +1,54 @@
+// Correlation IDs attached to the calls made on a thread
+use libc::{c_char, c_int};
+use std::cell::RefCell;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::utils::c_str_to_string;
+
+thread_local! {
+    static REQUEST_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Request ID set on the calling thread with `chroma_set_request_id`
+pub(crate) fn request_id() -> Option<String> {
+    REQUEST_ID.with(|id| id.borrow().clone())
+}
+
+/// Sets the request or correlation ID of the calls made on the calling thread, until it is
+/// replaced or cleared with a null `request_id`.
+///
+/// The ID is recorded on every error those calls return (`ChromaError::request_id`, the
+/// `request_id` field of `chroma_error_to_json` and the last error message) and on the
+/// `chroma_ffi` tracing span wrapping their backend calls, so a failure can be traced back
+/// to the request that caused it.
+#[no_mangle]
+pub extern "C" fn chroma_set_request_id(
+    request_id: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_set_request_id", || {
+        let func_name = "chroma_set_request_id";
+
+        let id = if request_id.is_null() {
+            None
+        } else {
+            match unsafe { c_str_to_string(request_id) } {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Invalid request ID",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        };
+        REQUEST_ID.with(|current| *current.borrow_mut() = id);
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
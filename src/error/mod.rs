@@ -1,8 +1,21 @@
-use libc::c_char;
-use std::ffi::CString;
+mod context;
+
+pub use context::*;
+
+use chroma_error::ErrorCodes;
+use libc::{c_char, c_int, size_t};
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 
+use crate::utils::{
+    c_str_to_string, chroma_free_string, chroma_free_string_array, string_to_c_str,
+    vec_string_to_c_array,
+};
+
 #[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChromaErrorCode {
     Success = 0,
     InvalidArgument = 1,
@@ -12,30 +25,163 @@ pub enum ChromaErrorCode {
     ValidationError = 5,
     InvalidUuid = 6,
     NotImplemented = 7,
+    DimensionMismatch = 8,
+    ReadOnly = 9,
+    ConcurrentAccess = 10,
+    MigrationsPending = 11,
+    ConflictError = 12,
+    PermissionDenied = 13,
+    QuotaExceeded = 14,
+    Busy = 15,
+    CollectionReadOnly = 16,
 }
 
+/// Error details returned through `error_out`.
+///
+/// Only allocated on failure: every function writes NULL to `error_out` on success, so
+/// callers need not free anything after a successful call. Owned by the library:
+/// release with `chroma_free_error`, never with `chroma_free`.
 #[repr(C)]
 pub struct ChromaError {
     pub code: ChromaErrorCode,
     pub message: *mut c_char,
     pub source: *mut c_char,
     pub details: *mut c_char,
+    /// Whether retrying the same call may succeed (e.g. the backend was unavailable)
+    pub retryable: bool,
+    /// Messages of the backend error and its causes, outermost first
+    pub chain: *mut *mut c_char,
+    pub chain_count: size_t,
+    /// Request ID set with `chroma_set_request_id` when the error occurred, or null
+    pub request_id: *mut c_char,
 }
 
 impl ChromaError {
     pub fn new(code: ChromaErrorCode, message: &str, source: &str, details: Option<&str>) -> Self {
         ChromaError {
             code,
-            message: crate::utils::string_to_c_str(message.to_string()),
-            source: crate::utils::string_to_c_str(source.to_string()),
+            message: string_to_c_str(message.to_string()),
+            source: string_to_c_str(source.to_string()),
             details: match details {
-                Some(d) => crate::utils::string_to_c_str(d.to_string()),
+                Some(d) => string_to_c_str(d.to_string()),
                 None => ptr::null_mut(),
             },
+            retryable: false,
+            chain: ptr::null_mut(),
+            chain_count: 0,
+            request_id: request_id().map_or(ptr::null_mut(), string_to_c_str),
         }
     }
 }
 
+/// Error produced by internal helpers, reported through `set_error` at the FFI boundary
+#[derive(Clone)]
+pub(crate) struct FfiError {
+    pub(crate) code: ChromaErrorCode,
+    pub(crate) message: &'static str,
+    pub(crate) details: Option<String>,
+    pub(crate) retryable: bool,
+    pub(crate) chain: Vec<String>,
+}
+
+/// Backend error classes that are worth retrying
+pub(crate) fn is_retryable(code: ErrorCodes) -> bool {
+    matches!(
+        code,
+        ErrorCodes::Unavailable
+            | ErrorCodes::ResourceExhausted
+            | ErrorCodes::Aborted
+            | ErrorCodes::DeadlineExceeded
+    )
+}
+
+impl FfiError {
+    pub(crate) fn new(code: ChromaErrorCode, message: &'static str, details: Option<String>) -> Self {
+        FfiError {
+            code,
+            message,
+            details,
+            retryable: false,
+            chain: Vec::new(),
+        }
+    }
+
+    /// Wraps an error returned by the Chroma backend, keeping its cause chain
+    /// and whether it is retryable
+    pub(crate) fn backend(
+        code: ChromaErrorCode,
+        message: &'static str,
+        e: &dyn chroma_error::ChromaError,
+    ) -> Self {
+        let mut chain = vec![e.to_string()];
+        let mut cause = e.source();
+        while let Some(inner) = cause {
+            chain.push(inner.to_string());
+            cause = inner.source();
+        }
+
+        // Calls denied by the authorizer, a quota or the backend, and calls the client or the
+        // backend was too busy to run, keep their code whatever the caller expected to fail
+        let code = match e.code() {
+            ErrorCodes::PermissionDenied => ChromaErrorCode::PermissionDenied,
+            ErrorCodes::ResourceExhausted => ChromaErrorCode::QuotaExceeded,
+            ErrorCodes::Unavailable => ChromaErrorCode::Busy,
+            _ => code,
+        };
+
+        FfiError {
+            code,
+            message,
+            details: Some(format!("Error: {:?}", e)),
+            retryable: is_retryable(e.code()),
+            chain,
+        }
+    }
+
+    /// Writes the error to `error_out` and returns the matching status code
+    pub(crate) fn report(self, error_out: *mut *mut ChromaError, source: &str) -> c_int {
+        let code = self.code as c_int;
+        record_last_error(self.code, self.message, source, self.details.as_deref());
+
+        if !error_out.is_null() {
+            let details = self.details.as_deref();
+            let mut error = ChromaError::new(self.code, self.message, source, details);
+            error.retryable = self.retryable;
+            (error.chain, error.chain_count) = vec_string_to_c_array(self.chain);
+            unsafe {
+                *error_out = Box::into_raw(Box::new(error));
+            }
+        }
+        code
+    }
+}
+
+/// Error recorded by the most recent call on a thread
+struct LastError {
+    code: ChromaErrorCode,
+    message: String,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+fn record_last_error(code: ChromaErrorCode, message: &str, source: &str, details: Option<&str>) {
+    let mut last_message = match details {
+        Some(d) => format!("{} (in {}): {}", message, source, d),
+        None => format!("{} (in {})", message, source),
+    };
+    if let Some(id) = request_id() {
+        last_message.push_str(&format!(" [request {}]", id));
+    }
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = Some(LastError {
+            code,
+            message: last_message,
+        })
+    });
+}
+
 pub fn set_error(
     error_out: *mut *mut ChromaError,
     code: ChromaErrorCode,
@@ -43,6 +189,8 @@ pub fn set_error(
     source: &str,
     details: Option<&str>,
 ) {
+    record_last_error(code, message, source, details);
+
     if !error_out.is_null() {
         let error = Box::new(ChromaError::new(code, message, source, details));
         unsafe {
@@ -51,7 +199,10 @@ pub fn set_error(
     }
 }
 
+/// Marks a call as successful: writes NULL to `error_out` without allocating
 pub fn set_success(error_out: *mut *mut ChromaError) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+
     if !error_out.is_null() {
         unsafe {
             *error_out = ptr::null_mut();
@@ -59,23 +210,198 @@ pub fn set_success(error_out: *mut *mut ChromaError) {
     }
 }
 
+/// Extracts the message carried by a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic payload".to_string()
+    }
+}
+
+/// Runs the body of an exported function, turning a panic into `InternalError`
+/// so it never unwinds across the C ABI into the host process.
+///
+/// `error_out` is reset to NULL up front, so it is never left uninitialized and
+/// only holds an allocation when the body reports an error.
+pub(crate) fn ffi_boundary<F>(error_out: *mut *mut ChromaError, source: &str, body: F) -> c_int
+where
+    F: FnOnce() -> c_int,
+{
+    set_success(error_out);
+
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(code) => code,
+        Err(payload) => {
+            set_error(
+                error_out,
+                ChromaErrorCode::InternalError,
+                "Panic in native library",
+                source,
+                Some(&panic_message(payload.as_ref())),
+            );
+            ChromaErrorCode::InternalError as c_int
+        }
+    }
+}
+
+/// Like `ffi_boundary` for exported functions without `error_out`; a panic yields `fallback`
+pub(crate) fn ffi_boundary_or<T, F>(source: &str, fallback: T, body: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        tracing::error!("Panic in {}: {}", source, panic_message(payload.as_ref()));
+        fallback
+    })
+}
+
+/// `ffi_boundary_or` for exported functions that return nothing
+pub(crate) fn ffi_boundary_void<F>(source: &str, body: F)
+where
+    F: FnOnce(),
+{
+    ffi_boundary_or(source, (), body)
+}
+
+/// Returns the error code of the last function called on this thread that takes
+/// `error_out`, or `Success` if it succeeded.
+///
+/// Those functions record their outcome here whether or not `error_out` is null, so
+/// callers may pass a null `error_out` and query the last error instead.
 #[no_mangle]
-pub extern "C" fn chroma_free_error(error: *mut ChromaError) {
-    if !error.is_null() {
-        unsafe {
-            let error = Box::from_raw(error);
+pub extern "C" fn chroma_get_last_error_code() -> c_int {
+    let internal_error = ChromaErrorCode::InternalError as c_int;
+    ffi_boundary_or("chroma_get_last_error_code", internal_error, || {
+        LAST_ERROR.with(|last| {
+            let code = last
+                .borrow()
+                .as_ref()
+                .map_or(ChromaErrorCode::Success, |e| e.code);
+            code as c_int
+        })
+    })
+}
 
-            if !error.message.is_null() {
-                let _ = CString::from_raw(error.message);
-            }
+/// Copies the message of the last error on this thread into `buf` as a NUL-terminated
+/// string, truncated to fit `len` bytes.
+///
+/// Returns the buffer size needed for the full message including the terminator, or 0
+/// when the last call succeeded. Pass a null `buf` to query the size only.
+#[no_mangle]
+pub extern "C" fn chroma_get_last_error_message(buf: *mut c_char, len: size_t) -> size_t {
+    ffi_boundary_or("chroma_get_last_error_message", 0, || {
+        LAST_ERROR.with(|last| {
+            let last = last.borrow();
+            let Some(error) = last.as_ref() else {
+                return 0;
+            };
 
-            if !error.source.is_null() {
-                let _ = CString::from_raw(error.source);
+            let bytes = error.message.as_bytes();
+            if !buf.is_null() && len > 0 {
+                let copied = bytes.len().min(len - 1);
+                unsafe {
+                    ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buf, copied);
+                    *buf.add(copied) = 0;
+                }
             }
+            bytes.len() + 1
+        })
+    })
+}
+
+/// Panics inside the FFI boundary so bindings can verify panics surface as
+/// `InternalError` instead of aborting the process. Only exported by builds with the
+/// `debug-exports` feature.
+#[cfg(feature = "debug-exports")]
+#[no_mangle]
+pub extern "C" fn chroma_debug_panic(error_out: *mut *mut ChromaError) -> c_int {
+    ffi_boundary(error_out, "chroma_debug_panic", || {
+        panic!("Forced panic requested by chroma_debug_panic")
+    })
+}
 
-            if !error.details.is_null() {
-                let _ = CString::from_raw(error.details);
+/// Frees an error returned through `error_out`; NULL (the success value) is ignored
+#[no_mangle]
+pub extern "C" fn chroma_free_error(error: *mut ChromaError) {
+    ffi_boundary_void("chroma_free_error", || {
+        if !error.is_null() {
+            unsafe {
+                let error = Box::from_raw(error);
+
+                chroma_free_string(error.message);
+                chroma_free_string(error.source);
+                chroma_free_string(error.details);
+                chroma_free_string_array(error.chain, error.chain_count);
+                chroma_free_string(error.request_id);
             }
         }
+    })
+}
+
+/// Reads an optional string field of a `ChromaError`
+unsafe fn error_field(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        None
+    } else {
+        c_str_to_string(s).ok()
     }
 }
+
+/// Converts an error into a JSON object for exception mappers and log aggregators.
+///
+/// The object has the fields `code`, `name`, `message`, `source`, `details`, `retryable`,
+/// `chain` and `request_id`. The string is allocated with `chroma_malloc`; release it with
+/// `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_error_to_json(
+    error: *const ChromaError,
+    out_str: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_error_to_json", || {
+        let func_name = "chroma_error_to_json";
+
+        if error.is_null() || out_str.is_null() {
+            let message = if error.is_null() {
+                "Error pointer is null"
+            } else {
+                "Output string pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let error = unsafe { &*error };
+        let chain: Vec<String> = (0..error.chain_count)
+            .filter_map(|i| unsafe { error_field(*error.chain.add(i)) })
+            .collect();
+
+        let json = serde_json::json!({
+            "code": error.code as c_int,
+            "name": format!("{:?}", error.code),
+            "message": unsafe { error_field(error.message) },
+            "source": unsafe { error_field(error.source) },
+            "details": unsafe { error_field(error.details) },
+            "retryable": error.retryable,
+            "chain": chain,
+            "request_id": unsafe { error_field(error.request_id) },
+        });
+
+        unsafe {
+            *out_str = string_to_c_str(json.to_string());
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
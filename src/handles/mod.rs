@@ -0,0 +1,362 @@
+// Handle registry for ChromaDB C# bindings
+//
+// Clients, collections, write batches, prepared queries and long operations are
+// stored in generational slot maps and exposed to callers as opaque integer handles. A
+// destroyed or never-issued handle fails the lookup instead of dereferencing
+// freed memory, so double frees and use-after-free are reported as errors.
+use chroma_types::CollectionUuid;
+use libc::{c_int, size_t};
+use slotmap::{DefaultKey, Key, KeyData, SlotMap};
+use std::ptr;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use crate::client::{forget_identity, ChromaClient, ChromaOperation};
+use crate::collection::{ChromaBatch, ChromaCollection, ChromaPreparedQuery};
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::utils::chroma_malloc;
+
+/// Opaque handle to a client, collection, write batch, prepared query or operation
+pub type ChromaHandle = u64;
+
+/// Handle value that never refers to a live object
+pub const CHROMA_INVALID_HANDLE: ChromaHandle = 0;
+
+/// Handle kinds accepted by `chroma_list_handles`
+pub const CHROMA_HANDLE_CLIENT: c_int = 0;
+pub const CHROMA_HANDLE_COLLECTION: c_int = 1;
+pub const CHROMA_HANDLE_BATCH: c_int = 2;
+pub const CHROMA_HANDLE_PREPARED_QUERY: c_int = 3;
+pub const CHROMA_HANDLE_OPERATION: c_int = 4;
+
+struct Registry<T> {
+    slots: RwLock<SlotMap<DefaultKey, Arc<T>>>,
+}
+
+impl<T> Registry<T> {
+    fn new() -> Self {
+        Registry {
+            slots: RwLock::new(SlotMap::new()),
+        }
+    }
+
+    fn key(handle: ChromaHandle) -> DefaultKey {
+        KeyData::from_ffi(handle).into()
+    }
+
+    fn insert(&self, value: T) -> ChromaHandle {
+        self.insert_shared(Arc::new(value))
+    }
+
+    fn insert_shared(&self, value: Arc<T>) -> ChromaHandle {
+        let mut slots = self.slots.write().unwrap_or_else(|e| e.into_inner());
+        slots.insert(value).data().as_ffi()
+    }
+
+    fn get(&self, handle: ChromaHandle) -> Option<Arc<T>> {
+        let slots = self.slots.read().unwrap_or_else(|e| e.into_inner());
+        slots.get(Self::key(handle)).cloned()
+    }
+
+    fn remove(&self, handle: ChromaHandle) -> Option<Arc<T>> {
+        let mut slots = self.slots.write().unwrap_or_else(|e| e.into_inner());
+        slots.remove(Self::key(handle))
+    }
+
+    fn handles(&self) -> Vec<ChromaHandle> {
+        let slots = self.slots.read().unwrap_or_else(|e| e.into_inner());
+        slots.keys().map(|k| k.data().as_ffi()).collect()
+    }
+
+    fn find_where(&self, predicate: impl Fn(&Arc<T>) -> bool) -> Vec<Arc<T>> {
+        let slots = self.slots.read().unwrap_or_else(|e| e.into_inner());
+        slots.values().filter(|value| predicate(value)).cloned().collect()
+    }
+
+    fn remove_where(&self, predicate: impl Fn(&Arc<T>) -> bool) -> Vec<Arc<T>> {
+        let mut slots = self.slots.write().unwrap_or_else(|e| e.into_inner());
+        let keys: Vec<DefaultKey> = slots
+            .iter()
+            .filter(|(_, value)| predicate(value))
+            .map(|(key, _)| key)
+            .collect();
+        keys.into_iter().filter_map(|key| slots.remove(key)).collect()
+    }
+
+    fn clear(&self) -> Vec<Arc<T>> {
+        let mut slots = self.slots.write().unwrap_or_else(|e| e.into_inner());
+        slots.drain().map(|(_, value)| value).collect()
+    }
+}
+
+static CLIENTS: LazyLock<Registry<ChromaClient>> = LazyLock::new(Registry::new);
+static COLLECTIONS: LazyLock<Registry<ChromaCollection>> = LazyLock::new(Registry::new);
+static BATCHES: LazyLock<Registry<ChromaBatch>> = LazyLock::new(Registry::new);
+static PREPARED_QUERIES: LazyLock<Registry<ChromaPreparedQuery>> = LazyLock::new(Registry::new);
+static OPERATIONS: LazyLock<Registry<ChromaOperation>> = LazyLock::new(Registry::new);
+
+fn unknown_handle(message: &'static str, handle: ChromaHandle) -> FfiError {
+    FfiError::new(
+        ChromaErrorCode::InvalidArgument,
+        message,
+        Some(format!("Handle {:#x} is not registered", handle)),
+    )
+}
+
+/// Registers a client and returns its handle
+pub(crate) fn insert_client(client: ChromaClient) -> ChromaHandle {
+    CLIENTS.insert(client)
+}
+
+/// Looks up a live client
+pub(crate) fn client_from_handle(handle: ChromaHandle) -> Result<Arc<ChromaClient>, FfiError> {
+    CLIENTS
+        .get(handle)
+        .ok_or_else(|| unknown_handle("Client handle is invalid or already destroyed", handle))
+}
+
+/// Issues another handle to the client behind `handle`, released independently of it
+pub(crate) fn retain_client(handle: ChromaHandle) -> Result<ChromaHandle, FfiError> {
+    client_from_handle(handle).map(|client| CLIENTS.insert_shared(client))
+}
+
+/// Unregisters a client handle; the client is dropped once no other handle, collection or
+/// in-flight call still uses it
+pub(crate) fn remove_client(handle: ChromaHandle) -> Result<(), FfiError> {
+    forget_identity(handle);
+    CLIENTS
+        .remove(handle)
+        .map(drop)
+        .ok_or_else(|| unknown_handle("Client handle is invalid or already destroyed", handle))
+}
+
+/// Unregisters every handle of `client`, including those issued by `chroma_client_retain`
+pub(crate) fn remove_client_handles(client: &Arc<ChromaClient>) -> Vec<Arc<ChromaClient>> {
+    CLIENTS.remove_where(|other| Arc::ptr_eq(other, client))
+}
+
+/// Registers a collection and returns its handle
+pub(crate) fn insert_collection(collection: ChromaCollection) -> ChromaHandle {
+    COLLECTIONS.insert(collection)
+}
+
+/// Looks up a live collection whose client is not shutting down
+pub(crate) fn collection_from_handle(
+    handle: ChromaHandle,
+) -> Result<Arc<ChromaCollection>, FfiError> {
+    let collection = COLLECTIONS.get(handle).ok_or_else(|| {
+        unknown_handle("Collection handle is invalid or already destroyed", handle)
+    })?;
+    if collection.client.lifecycle.is_shutting_down() {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Client of the collection is shutting down",
+            Some(format!("Handle {:#x} was opened from a client being shut down", handle)),
+        ));
+    }
+    Ok(collection)
+}
+
+/// Every open handle of the collection `id` opened from `client`
+pub(crate) fn open_collections(
+    client: &Arc<ChromaClient>,
+    id: CollectionUuid,
+) -> Vec<Arc<ChromaCollection>> {
+    COLLECTIONS
+        .find_where(|collection| Arc::ptr_eq(&collection.client, client) && collection.id == id)
+}
+
+/// Unregisters every collection opened from `client`
+pub(crate) fn remove_client_collections(client: &Arc<ChromaClient>) -> Vec<Arc<ChromaCollection>> {
+    COLLECTIONS.remove_where(|collection| Arc::ptr_eq(&collection.client, client))
+}
+
+/// Unregisters a collection
+pub(crate) fn remove_collection(handle: ChromaHandle) -> Result<(), FfiError> {
+    COLLECTIONS
+        .remove(handle)
+        .map(drop)
+        .ok_or_else(|| unknown_handle("Collection handle is invalid or already destroyed", handle))
+}
+
+/// Registers a write batch and returns its handle
+pub(crate) fn insert_batch(batch: ChromaBatch) -> ChromaHandle {
+    BATCHES.insert(batch)
+}
+
+/// Looks up an open write batch
+pub(crate) fn batch_from_handle(handle: ChromaHandle) -> Result<Arc<ChromaBatch>, FfiError> {
+    BATCHES
+        .get(handle)
+        .ok_or_else(|| unknown_handle("Batch handle is invalid or already closed", handle))
+}
+
+/// Unregisters a write batch, returning it so it can be committed or dropped
+pub(crate) fn remove_batch(handle: ChromaHandle) -> Result<Arc<ChromaBatch>, FfiError> {
+    BATCHES
+        .remove(handle)
+        .ok_or_else(|| unknown_handle("Batch handle is invalid or already closed", handle))
+}
+
+/// Unregisters every write batch begun on `client`, discarding their queued writes
+pub(crate) fn remove_client_batches(client: &Arc<ChromaClient>) -> Vec<Arc<ChromaBatch>> {
+    BATCHES.remove_where(|batch| Arc::ptr_eq(&batch.client, client))
+}
+
+/// Registers a prepared query and returns its handle
+pub(crate) fn insert_prepared_query(query: ChromaPreparedQuery) -> ChromaHandle {
+    PREPARED_QUERIES.insert(query)
+}
+
+/// Looks up a live prepared query
+pub(crate) fn prepared_query_from_handle(
+    handle: ChromaHandle,
+) -> Result<Arc<ChromaPreparedQuery>, FfiError> {
+    PREPARED_QUERIES.get(handle).ok_or_else(|| {
+        unknown_handle("Prepared query handle is invalid or already destroyed", handle)
+    })
+}
+
+/// Unregisters a prepared query
+pub(crate) fn remove_prepared_query(handle: ChromaHandle) -> Result<(), FfiError> {
+    PREPARED_QUERIES
+        .remove(handle)
+        .map(drop)
+        .ok_or_else(|| {
+            unknown_handle("Prepared query handle is invalid or already destroyed", handle)
+        })
+}
+
+/// Unregisters every prepared query of a collection opened from `client`
+pub(crate) fn remove_client_prepared_queries(
+    client: &Arc<ChromaClient>,
+) -> Vec<Arc<ChromaPreparedQuery>> {
+    PREPARED_QUERIES.remove_where(|query| Arc::ptr_eq(&query.collection.client, client))
+}
+
+/// Registers an operation shared with the thread running it and returns its handle
+pub(crate) fn insert_operation(operation: Arc<ChromaOperation>) -> ChromaHandle {
+    OPERATIONS.insert_shared(operation)
+}
+
+/// Looks up a live operation
+pub(crate) fn operation_from_handle(
+    handle: ChromaHandle,
+) -> Result<Arc<ChromaOperation>, FfiError> {
+    OPERATIONS
+        .get(handle)
+        .ok_or_else(|| unknown_handle("Operation handle is invalid or already destroyed", handle))
+}
+
+/// Unregisters an operation; it keeps running until its thread notices it was cancelled
+pub(crate) fn remove_operation(handle: ChromaHandle) -> Result<Arc<ChromaOperation>, FfiError> {
+    OPERATIONS
+        .remove(handle)
+        .ok_or_else(|| unknown_handle("Operation handle is invalid or already destroyed", handle))
+}
+
+/// Unregisters every operation started on `client`
+pub(crate) fn remove_client_operations(client: &Arc<ChromaClient>) -> Vec<Arc<ChromaOperation>> {
+    OPERATIONS.remove_where(|operation| Arc::ptr_eq(&operation.client, client))
+}
+
+/// Destroys every live client and collection, e.g. when the host unloads the library.
+///
+/// Handles issued before the call become invalid and open write batches are aborted.
+#[no_mangle]
+pub extern "C" fn chroma_destroy_all(error_out: *mut *mut ChromaError) -> c_int {
+    ffi_boundary(error_out, "chroma_destroy_all", || {
+        // Batches, prepared queries, operations and collections go first so none outlives the
+        // client it was opened from
+        drop(BATCHES.clear());
+        drop(PREPARED_QUERIES.clear());
+        drop(OPERATIONS.clear());
+        drop(COLLECTIONS.clear());
+        drop(CLIENTS.clear());
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Lists the live handles of one kind for diagnostics.
+///
+/// The array is allocated with `chroma_malloc` and must be released with `chroma_free`.
+/// An empty registry yields a null array and a zero count.
+#[no_mangle]
+pub extern "C" fn chroma_list_handles(
+    kind: c_int,
+    handles_out: *mut *mut ChromaHandle,
+    count_out: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_list_handles", || {
+        let func_name = "chroma_list_handles";
+
+        if handles_out.is_null() || count_out.is_null() {
+            let message = if handles_out.is_null() {
+                "Handles output pointer is null"
+            } else {
+                "Count output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let handles = match kind {
+            CHROMA_HANDLE_CLIENT => CLIENTS.handles(),
+            CHROMA_HANDLE_COLLECTION => COLLECTIONS.handles(),
+            CHROMA_HANDLE_BATCH => BATCHES.handles(),
+            CHROMA_HANDLE_PREPARED_QUERY => PREPARED_QUERIES.handles(),
+            CHROMA_HANDLE_OPERATION => OPERATIONS.handles(),
+            _ => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Unknown handle kind",
+                    func_name,
+                    Some(&format!("Handle kind: {}", kind)),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let count = handles.len();
+        let array = if count == 0 {
+            ptr::null_mut()
+        } else {
+            let array =
+                chroma_malloc(count * std::mem::size_of::<ChromaHandle>()) as *mut ChromaHandle;
+            if array.is_null() {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::MemoryError,
+                    "Failed to allocate handle array",
+                    func_name,
+                    None,
+                );
+                return ChromaErrorCode::MemoryError as c_int;
+            }
+            unsafe {
+                ptr::copy_nonoverlapping(handles.as_ptr(), array, count);
+            }
+            array
+        };
+
+        unsafe {
+            *handles_out = array;
+            *count_out = count;
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -1,15 +1,25 @@
 #![deny(clippy::all)]
 
 // Re-export all modules
+mod abi;
+mod callbacks;
 mod client;
 mod collection;
 mod error;
+mod handles;
+mod search;
+mod serialization;
 mod types;
 mod utils;
 
 // Public exports for C# bindings
+pub use abi::*;
+pub use callbacks::*;
 pub use client::*;
 pub use collection::*;
 pub use error::*;
+pub use handles::*;
+pub use search::*;
+pub use serialization::*;
 pub use types::*;
 pub use utils::*;
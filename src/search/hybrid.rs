@@ -0,0 +1,466 @@
+// Hybrid vector + keyword search for ChromaDB C# bindings
+use chroma_types::{IncludeList, QueryRequest};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::collections::HashMap;
+
+use crate::abi::{read_sized, SizedInput};
+use crate::client::ChromaClient;
+use crate::collection::filters::{optional_c_str, parse_where};
+use crate::collection::ChromaCollection;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::search::{keyword_hits, ChromaRankOptions, KeywordHits};
+use crate::types::ChromaQueryResult;
+use crate::utils::{
+    c_array_to_vec_f32, c_str_to_string, vec_f32_to_c_array, vec_string_to_c_array,
+};
+
+/// Reciprocal rank fusion
+pub const CHROMA_FUSION_RRF: c_int = 0;
+/// Weighted sum of normalized vector similarity and text score
+pub const CHROMA_FUSION_WEIGHTED: c_int = 1;
+
+/// Default RRF rank constant
+pub const DEFAULT_RRF_K: c_float = 60.0;
+
+/// Options controlling how vector and keyword results are fused
+#[repr(C)]
+pub struct ChromaFusionOptions {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Fusion method: 0 = reciprocal rank fusion, 1 = weighted
+    pub method: c_int,
+    /// RRF rank constant; values <= 0 use the default of 60
+    pub rrf_k: c_float,
+    /// Weight of the vector similarity in weighted fusion, in [0, 1]
+    pub vector_weight: c_float,
+    /// Candidates fetched from each retriever before fusion (0 = 2 * n_results)
+    pub candidate_k: c_uint,
+}
+
+unsafe impl SizedInput for ChromaFusionOptions {}
+
+/// Parsed form of `ChromaFusionOptions`
+pub(crate) struct Fusion {
+    method: c_int,
+    rrf_k: f32,
+    vector_weight: f32,
+    /// Candidates fetched from each retriever
+    pub(crate) candidate_k: u32,
+}
+
+impl Fusion {
+    /// Validates the fusion options passed by the caller, falling back to RRF when null
+    pub(crate) unsafe fn from_ffi(
+        fusion: *const ChromaFusionOptions,
+        n_results: u32,
+    ) -> Result<Self, FfiError> {
+        let fusion = read_sized(fusion)?;
+        let fusion = fusion.as_ref();
+        let method = fusion.map(|f| f.method).unwrap_or(CHROMA_FUSION_RRF);
+        if method != CHROMA_FUSION_RRF && method != CHROMA_FUSION_WEIGHTED {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Invalid fusion method",
+                Some(format!("Got {}, expected 0 (RRF) or 1 (Weighted)", method)),
+            ));
+        }
+
+        let rrf_k = fusion
+            .map(|f| f.rrf_k)
+            .filter(|k| *k > 0.0)
+            .unwrap_or(DEFAULT_RRF_K);
+        let vector_weight = fusion.map(|f| f.vector_weight).unwrap_or(0.5);
+        if !(0.0..=1.0).contains(&vector_weight) {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Invalid vector weight",
+                Some(format!("Got {}, expected a value in [0, 1]", vector_weight)),
+            ));
+        }
+        let candidate_k = match fusion.map(|f| f.candidate_k).unwrap_or(0) {
+            0 => n_results.saturating_mul(2),
+            k => k.max(n_results),
+        };
+
+        Ok(Fusion {
+            method,
+            rrf_k,
+            vector_weight,
+            candidate_k,
+        })
+    }
+}
+
+pub(crate) struct VectorHits {
+    ids: Vec<String>,
+    documents: Vec<Option<String>>,
+    metadatas: Vec<Option<String>>,
+    distances: Vec<f32>,
+}
+
+/// A fused result row
+struct FusedHit {
+    document: Option<String>,
+    metadata: Option<String>,
+    distance: f32,
+    text_score: f32,
+    vector_rank: Option<usize>,
+    text_rank: Option<usize>,
+}
+
+pub(crate) fn vector_hits(
+    client: &ChromaClient,
+    collection: &ChromaCollection,
+    embedding: Vec<f32>,
+    n_results: u32,
+    where_json: Option<&str>,
+) -> Result<VectorHits, FfiError> {
+    let collection_id = collection.id;
+    let where_filter = collection.visible(parse_where(where_json, None)?);
+
+    let include_list = IncludeList::try_from(vec![
+        "documents".to_string(),
+        "metadatas".to_string(),
+        "distances".to_string(),
+    ])
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = QueryRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection_id,
+        None,
+        where_filter,
+        vec![embedding],
+        n_results,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create query request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    client.read_barrier(collection_id)?;
+    let response = client
+        .block_on_with_retry("query", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.query(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+        })?;
+
+    let ids = response.ids.into_iter().next().unwrap_or_default();
+    let documents = response
+        .documents
+        .and_then(|d| d.into_iter().next())
+        .unwrap_or_else(|| vec![None; ids.len()]);
+    let metadatas = response
+        .metadatas
+        .and_then(|m| m.into_iter().next())
+        .map(|m| {
+            m.iter()
+                .map(|m| m.as_ref().map(|m| serde_json::to_string(m).unwrap_or_default()))
+                .collect()
+        })
+        .unwrap_or_else(|| vec![None; ids.len()]);
+    let distances = response
+        .distances
+        .and_then(|d| d.into_iter().next())
+        .map(|d| d.into_iter().map(|d| d.unwrap_or(0.0)).collect())
+        .unwrap_or_else(|| vec![0.0; ids.len()]);
+
+    Ok(VectorHits {
+        ids,
+        documents,
+        metadatas,
+        distances,
+    })
+}
+
+/// Min-max normalizes values to [0, 1]; all-equal inputs map to 1
+fn normalize(values: &[f32]) -> Vec<f32> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if max <= min {
+        return vec![1.0; values.len()];
+    }
+    values.iter().map(|v| (v - min) / (max - min)).collect()
+}
+
+/// Merges vector and keyword (or other scored) candidates by ID and ranks them by their
+/// fused score. `Scores` holds the fused score, `Distances` and `TextScores` the scores of
+/// each retriever.
+pub(crate) fn fuse_hits(
+    vector: VectorHits,
+    keyword: KeywordHits,
+    fusion: &Fusion,
+    n_results: u32,
+) -> Box<ChromaQueryResult> {
+    // Merge both candidate lists by ID, keeping the first-seen document and metadata
+    let mut order: Vec<String> = Vec::new();
+    let mut fused: HashMap<String, FusedHit> = HashMap::new();
+
+    for (rank, id) in vector.ids.iter().enumerate() {
+        order.push(id.clone());
+        fused.insert(
+            id.clone(),
+            FusedHit {
+                document: vector.documents.get(rank).cloned().flatten(),
+                metadata: vector.metadatas.get(rank).cloned().flatten(),
+                distance: vector.distances.get(rank).copied().unwrap_or(f32::NAN),
+                text_score: 0.0,
+                vector_rank: Some(rank),
+                text_rank: None,
+            },
+        );
+    }
+
+    for (rank, id) in keyword.ids.iter().enumerate() {
+        let hit = fused.entry(id.clone()).or_insert_with(|| {
+            order.push(id.clone());
+            FusedHit {
+                document: keyword.documents[rank].clone(),
+                metadata: keyword.metadatas[rank].clone(),
+                distance: f32::NAN,
+                text_score: 0.0,
+                vector_rank: None,
+                text_rank: None,
+            }
+        });
+        hit.text_score = keyword.scores[rank];
+        hit.text_rank = Some(rank);
+    }
+
+    let fused_scores: Vec<f32> = if fusion.method == CHROMA_FUSION_RRF {
+        order
+            .iter()
+            .map(|id| {
+                let hit = &fused[id];
+                let vector_part = hit
+                    .vector_rank
+                    .map_or(0.0, |r| 1.0 / (fusion.rrf_k + r as f32 + 1.0));
+                let text_part = hit
+                    .text_rank
+                    .map_or(0.0, |r| 1.0 / (fusion.rrf_k + r as f32 + 1.0));
+                vector_part + text_part
+            })
+            .collect()
+    } else {
+        // Smaller distances are better, so invert the normalized distance
+        let vector_similarity: HashMap<&str, f32> = vector
+            .ids
+            .iter()
+            .map(String::as_str)
+            .zip(normalize(&vector.distances).into_iter().map(|d| 1.0 - d))
+            .collect();
+        let max_text = keyword.scores.iter().copied().fold(0.0, f32::max);
+
+        order
+            .iter()
+            .map(|id| {
+                let hit = &fused[id];
+                let similarity = vector_similarity.get(id.as_str()).copied().unwrap_or(0.0);
+                let text = if max_text > 0.0 {
+                    hit.text_score / max_text
+                } else {
+                    0.0
+                };
+                fusion.vector_weight * similarity + (1.0 - fusion.vector_weight) * text
+            })
+            .collect()
+    };
+
+    let mut ranked: Vec<usize> = (0..order.len()).collect();
+    ranked.sort_by(|&a, &b| fused_scores[b].total_cmp(&fused_scores[a]));
+    ranked.truncate(n_results as usize);
+
+    let mut ids = Vec::with_capacity(ranked.len());
+    let mut documents = Vec::with_capacity(ranked.len());
+    let mut metadatas = Vec::with_capacity(ranked.len());
+    let mut distances = Vec::with_capacity(ranked.len());
+    let mut text_scores = Vec::with_capacity(ranked.len());
+    let mut scores = Vec::with_capacity(ranked.len());
+
+    for i in ranked {
+        let id = &order[i];
+        let Some(hit) = fused.remove(id) else {
+            continue;
+        };
+        ids.push(id.clone());
+        documents.push(hit.document.unwrap_or_default());
+        metadatas.push(hit.metadata.unwrap_or_default());
+        distances.push(hit.distance);
+        text_scores.push(hit.text_score);
+        scores.push(fused_scores[i]);
+    }
+
+    let mut query_result = Box::new(ChromaQueryResult::empty());
+
+    let (array, count) = vec_string_to_c_array(ids);
+    query_result.ids = array;
+    query_result.ids_count = count;
+
+    let (array, count) = vec_string_to_c_array(documents);
+    query_result.documents = array;
+    query_result.documents_count = count;
+
+    let (array, count) = vec_string_to_c_array(metadatas);
+    query_result.metadata_json = array;
+    query_result.metadata_count = count;
+
+    let (array, count) = vec_f32_to_c_array(distances);
+    query_result.distances = array;
+    query_result.distances_count = count;
+
+    let (array, count) = vec_f32_to_c_array(text_scores);
+    query_result.text_scores = array;
+    query_result.text_scores_count = count;
+
+    let (array, count) = vec_f32_to_c_array(scores);
+    query_result.scores = array;
+    query_result.scores_count = count;
+
+    query_result
+}
+
+/// Runs a vector KNN query and a keyword search, fusing their results
+#[no_mangle]
+pub extern "C" fn chroma_hybrid_search(
+    collection_handle: ChromaHandle,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    query_text: *const c_char,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    rank_options: *const ChromaRankOptions,
+    fusion_options: *const ChromaFusionOptions,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_hybrid_search", || {
+        let func_name = "chroma_hybrid_search";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || query_embedding.is_null()
+            || query_text.is_null()
+            || result.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_embedding.is_null() {
+                "Query embedding pointer is null"
+            } else if query_text.is_null() {
+                "Query text pointer is null"
+            } else {
+                "Result pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if embedding_dim == 0 {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Invalid query embedding",
+                func_name,
+                Some("Embedding dimension is zero"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let rank_options = match unsafe { read_sized(rank_options) } {
+            Ok(options) => options,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let fusion = match unsafe { Fusion::from_ffi(fusion_options, n_results) } {
+            Ok(fusion) => fusion,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let candidate_k = fusion.candidate_k;
+
+        let text = match unsafe { c_str_to_string(query_text) } {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid query text",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let where_json = match unsafe {
+            optional_c_str(where_filter_json, "Failed to convert where filter JSON string")
+        } {
+            Ok(s) => s,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+
+        let vector = match vector_hits(
+            client,
+            &collection,
+            embedding,
+            candidate_k,
+            where_json.as_deref(),
+        ) {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let keyword = match keyword_hits(
+            client,
+            &collection,
+            &text,
+            candidate_k as usize,
+            where_json.as_deref(),
+            rank_options.as_ref(),
+        ) {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let query_result = fuse_hits(vector, keyword, &fusion, n_results);
+
+        unsafe {
+            *result = Box::into_raw(query_result);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
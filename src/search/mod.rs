@@ -0,0 +1,306 @@
+// Keyword and hybrid search for ChromaDB C# bindings
+mod hybrid;
+mod multi;
+mod rank;
+mod sparse;
+
+pub use hybrid::*;
+pub use multi::*;
+pub use rank::{DEFAULT_BM25_B, DEFAULT_BM25_K1};
+pub use sparse::*;
+
+use chroma_types::{CountRequest, GetRequest, IncludeList, plan::ReadLevel};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+
+use crate::abi::{read_sized, SizedInput};
+use crate::client::ChromaClient;
+use crate::collection::filters::{optional_c_str, parse_where};
+use crate::collection::ChromaCollection;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::types::ChromaQueryResult;
+use crate::utils::{c_str_to_string, vec_f32_to_c_array, vec_string_to_c_array};
+
+/// Ranking options for keyword search
+#[repr(C)]
+pub struct ChromaRankOptions {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// BM25 term frequency saturation (k1); values <= 0 use the default
+    pub k1: c_float,
+    /// BM25 length normalization (b); values outside [0, 1] use the default
+    pub b: c_float,
+    /// Require every query term to appear in a document instead of any term
+    pub match_all_terms: bool,
+    /// Maximum number of candidates read from the full-text index (0 = unlimited)
+    pub max_candidates: c_uint,
+}
+
+unsafe impl SizedInput for ChromaRankOptions {}
+
+/// Candidate documents matched through the full-text index, scored with BM25
+pub(crate) struct KeywordHits {
+    pub(crate) ids: Vec<String>,
+    pub(crate) documents: Vec<Option<String>>,
+    pub(crate) metadatas: Vec<Option<String>>,
+    pub(crate) scores: Vec<f32>,
+}
+
+/// Builds a `$contains` document filter matching the query terms
+pub(crate) fn terms_document_filter(terms: &[String], match_all_terms: bool) -> String {
+    let clauses: Vec<serde_json::Value> = terms
+        .iter()
+        .map(|t| serde_json::json!({ "$contains": t }))
+        .collect();
+
+    let filter = if clauses.len() == 1 {
+        clauses.into_iter().next().unwrap_or_default()
+    } else if match_all_terms {
+        serde_json::json!({ "$and": clauses })
+    } else {
+        serde_json::json!({ "$or": clauses })
+    };
+
+    filter.to_string()
+}
+
+/// Reads keyword candidates for `query_text` and returns them ordered by BM25 score
+pub(crate) fn keyword_hits(
+    client: &ChromaClient,
+    collection: &ChromaCollection,
+    query_text: &str,
+    n_results: usize,
+    where_json: Option<&str>,
+    options: Option<&ChromaRankOptions>,
+) -> Result<KeywordHits, FfiError> {
+    let terms = rank::query_terms(query_text);
+    if terms.is_empty() {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Query text contains no searchable terms",
+            None,
+        ));
+    }
+
+    let k1 = options
+        .map(|o| o.k1)
+        .filter(|k1| *k1 > 0.0)
+        .unwrap_or(DEFAULT_BM25_K1);
+    let b = options
+        .map(|o| o.b)
+        .filter(|b| (0.0..=1.0).contains(b))
+        .unwrap_or(DEFAULT_BM25_B);
+    let match_all_terms = options.map(|o| o.match_all_terms).unwrap_or(false);
+    let max_candidates = options.map(|o| o.max_candidates).unwrap_or(0);
+
+    let where_document = terms_document_filter(&terms, match_all_terms);
+    let where_filter = collection.visible(parse_where(where_json, Some(&where_document))?);
+    let collection_id = collection.id;
+
+    let include_list = IncludeList::try_from(vec![
+        "documents".to_string(),
+        "metadatas".to_string(),
+    ])
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let count_request = CountRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection_id,
+        ReadLevel::default(),
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create count request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    let get_request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection_id,
+        None,
+        where_filter,
+        if max_candidates > 0 {
+            Some(max_candidates)
+        } else {
+            None
+        },
+        0,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    client.read_barrier(collection_id)?;
+    let total_documents = client
+        .block_on_with_retry("count", collection.scope(), |mut frontend| {
+            let request = count_request.clone();
+            async move { frontend.count(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(ChromaErrorCode::InternalError, "Failed to count documents", &e)
+        })?;
+
+    let get_response = client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = get_request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to execute keyword search",
+                &e,
+            )
+        })?;
+
+    let documents = get_response
+        .documents
+        .unwrap_or_else(|| vec![None; get_response.ids.len()]);
+    let metadatas: Vec<Option<String>> = match get_response.metadatas {
+        Some(metadatas) => metadatas
+            .iter()
+            .map(|m| m.as_ref().map(|m| serde_json::to_string(m).unwrap_or_default()))
+            .collect(),
+        None => vec![None; get_response.ids.len()],
+    };
+
+    let scores = rank::bm25_scores(&terms, &documents, total_documents as usize, k1, b);
+    let order = rank::top_n(&scores, n_results);
+
+    Ok(KeywordHits {
+        ids: order.iter().map(|&i| get_response.ids[i].clone()).collect(),
+        documents: order.iter().map(|&i| documents[i].clone()).collect(),
+        metadatas: order.iter().map(|&i| metadatas[i].clone()).collect(),
+        scores: order.iter().map(|&i| scores[i]).collect(),
+    })
+}
+
+/// Searches a collection by keywords using the full-text index, ranked with BM25
+#[no_mangle]
+pub extern "C" fn chroma_search(
+    collection_handle: ChromaHandle,
+    query_text: *const c_char,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    rank_options: *const ChromaRankOptions,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_search", || {
+        let func_name = "chroma_search";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || query_text.is_null() || result.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_text.is_null() {
+                "Query text pointer is null"
+            } else {
+                "Result pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+        let options = match unsafe { read_sized(rank_options) } {
+            Ok(options) => options,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let text = match unsafe { c_str_to_string(query_text) } {
+            Ok(s) => s,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid query text",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let where_json = match unsafe {
+            optional_c_str(where_filter_json, "Failed to convert where filter JSON string")
+        } {
+            Ok(s) => s,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let hits = match keyword_hits(
+            client,
+            &collection,
+            &text,
+            n_results as usize,
+            where_json.as_deref(),
+            options.as_ref(),
+        ) {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let mut query_result = Box::new(ChromaQueryResult::empty());
+
+        let (array, count) = vec_string_to_c_array(hits.ids);
+        query_result.ids = array;
+        query_result.ids_count = count;
+
+        let (array, count) = vec_string_to_c_array(
+            hits.documents
+                .into_iter()
+                .map(|d| d.unwrap_or_default())
+                .collect(),
+        );
+        query_result.documents = array;
+        query_result.documents_count = count;
+
+        let (array, count) = vec_string_to_c_array(
+            hits.metadatas
+                .into_iter()
+                .map(|m| m.unwrap_or_default())
+                .collect(),
+        );
+        query_result.metadata_json = array;
+        query_result.metadata_count = count;
+
+        let (array, count) = vec_f32_to_c_array(hits.scores);
+        query_result.scores = array;
+        query_result.scores_count = count;
+
+        unsafe {
+            *result = Box::into_raw(query_result);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,313 @@
+// Scatter-gather queries across several collections
+use chroma_types::{IncludeList, QueryRequest, QueryResponse};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+use crate::client::call_with_retry;
+use crate::collection::filters::parse_where_ptrs;
+use crate::collection::ChromaCollection;
+use crate::error::{
+    ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError,
+};
+use crate::handles::{collection_from_handle, ChromaHandle};
+use crate::types::ChromaQueryResult;
+use crate::utils::{c_array_to_vec_f32, vec_f32_to_c_array, vec_string_to_c_array};
+
+/// Merge strategies accepted by `chroma_query_multi`
+pub const CHROMA_MERGE_NONE: c_int = 0;
+pub const CHROMA_MERGE_BY_DISTANCE: c_int = 1;
+
+/// A single query match, flattened out of a query response
+struct Hit {
+    id: String,
+    distance: f32,
+    metadata_json: String,
+    document: String,
+}
+
+fn hits_from_response(response: QueryResponse) -> Vec<Hit> {
+    let ids = response.ids.into_iter().next().unwrap_or_default();
+    let mut distances = response.distances.and_then(|d| d.into_iter().next());
+    let mut metadatas = response.metadatas.and_then(|m| m.into_iter().next());
+    let mut documents = response.documents.and_then(|d| d.into_iter().next());
+
+    ids.into_iter()
+        .enumerate()
+        .map(|(i, id)| Hit {
+            id,
+            distance: distances
+                .as_mut()
+                .and_then(|d| d.get_mut(i)?.take())
+                .unwrap_or(0.0),
+            metadata_json: metadatas
+                .as_mut()
+                .and_then(|m| m.get_mut(i)?.take())
+                .map(|m| serde_json::to_string(&m).unwrap_or_default())
+                .unwrap_or_default(),
+            document: documents
+                .as_mut()
+                .and_then(|d| d.get_mut(i)?.take())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn hits_to_result(
+    hits: Vec<Hit>,
+    include_metadatas: bool,
+    include_documents: bool,
+) -> *mut ChromaQueryResult {
+    let mut result = ChromaQueryResult::empty();
+    let mut ids = Vec::with_capacity(hits.len());
+    let mut distances = Vec::with_capacity(hits.len());
+    let mut metadatas = Vec::with_capacity(hits.len());
+    let mut documents = Vec::with_capacity(hits.len());
+    for hit in hits {
+        ids.push(hit.id);
+        distances.push(hit.distance);
+        metadatas.push(hit.metadata_json);
+        documents.push(hit.document);
+    }
+
+    (result.ids, result.ids_count) = vec_string_to_c_array(ids);
+    (result.distances, result.distances_count) = vec_f32_to_c_array(distances);
+    if include_metadatas {
+        (result.metadata_json, result.metadata_count) = vec_string_to_c_array(metadatas);
+    }
+    if include_documents {
+        (result.documents, result.documents_count) = vec_string_to_c_array(documents);
+    }
+    Box::into_raw(Box::new(result))
+}
+
+/// Runs the same embedding query against several collections concurrently.
+///
+/// All collections must have been opened from the same client. With `CHROMA_MERGE_NONE`,
+/// `results_out` must have room for `collections_count` results, one per collection in the
+/// given order. With `CHROMA_MERGE_BY_DISTANCE` it receives a single result holding the
+/// `n_results` closest matches over all collections. Distances are always included; release
+/// every result with `chroma_free_query_result`.
+#[no_mangle]
+pub extern "C" fn chroma_query_multi(
+    collection_handles: *const ChromaHandle,
+    collections_count: size_t,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    include_metadatas: bool,
+    include_documents: bool,
+    merge_strategy: c_int,
+    results_out: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_multi", || {
+        let func_name = "chroma_query_multi";
+
+        if collection_handles.is_null()
+            || collections_count == 0
+            || query_embedding.is_null()
+            || results_out.is_null()
+        {
+            let message = if collection_handles.is_null() {
+                "Collection handles pointer is null"
+            } else if collections_count == 0 {
+                "Collections count is zero"
+            } else if query_embedding.is_null() {
+                "Query embedding pointer is null"
+            } else {
+                "Result output pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if merge_strategy != CHROMA_MERGE_NONE && merge_strategy != CHROMA_MERGE_BY_DISTANCE {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Unknown merge strategy",
+                func_name,
+                Some(&format!("Got {}, expected 0 (none) or 1 (by distance)", merge_strategy)),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let mut collections: Vec<Arc<ChromaCollection>> = Vec::with_capacity(collections_count);
+        for i in 0..collections_count {
+            let handle = unsafe { *collection_handles.add(i) };
+            let collection = match collection_from_handle(handle) {
+                Ok(collection) => collection,
+                Err(e) => return e.report(error_out, func_name),
+            };
+            if let Err(e) = collection.check_dimension(embedding_dim) {
+                return e.report(error_out, func_name);
+            }
+            if let Some(first) = collections.first() {
+                if !Arc::ptr_eq(&first.client, &collection.client) {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Collections belong to different clients",
+                        func_name,
+                        Some(&format!("Collection at index {} was opened from another client", i)),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+            collections.push(collection);
+        }
+
+        let where_filter =
+            match unsafe { parse_where_ptrs(where_filter_json, where_document_filter) } {
+                Ok(where_filter) => where_filter,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+        let mut include = vec!["distances".to_string()];
+        if include_metadatas {
+            include.push("metadatas".to_string());
+        }
+        if include_documents {
+            include.push("documents".to_string());
+        }
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+        let mut requests = Vec::with_capacity(collections.len());
+        for collection in &collections {
+            match QueryRequest::try_new(
+                collection.tenant.to_string(),
+                collection.database.to_string(),
+                collection.id,
+                None,
+                collection.visible(where_filter.clone()),
+                vec![embedding.clone()],
+                n_results,
+                include_list.clone(),
+            ) {
+                Ok(request) => requests.push(request),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::ValidationError,
+                        "Failed to create query request",
+                        func_name,
+                        Some(&format!("Validation error: {:?}", e)),
+                    );
+                    return ChromaErrorCode::ValidationError as c_int;
+                }
+            }
+        }
+
+        // Fan the query out on the client runtime and gather the responses in input order
+        let client = &collections[0].client;
+        for collection in &collections {
+            if let Err(reason) = client.authorize("query", &collection.scope()) {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::PermissionDenied,
+                    "Permission denied",
+                    func_name,
+                    Some(&reason),
+                );
+                return ChromaErrorCode::PermissionDenied as c_int;
+            }
+            if let Err(e) = client.read_barrier(collection.id) {
+                return e.report(error_out, func_name);
+            }
+        }
+        let _permit = match client.concurrency.acquire("query_multi") {
+            Ok(permit) => permit,
+            Err(reason) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::Busy,
+                    "Client is busy",
+                    func_name,
+                    Some(&reason),
+                );
+                return ChromaErrorCode::Busy as c_int;
+            }
+        };
+        let policy = client.retry_policy();
+        let started = Instant::now();
+        let responses: Result<Vec<Vec<Hit>>, FfiError> = client.runtime.block_on(async {
+            let mut tasks = JoinSet::new();
+            for (index, request) in requests.into_iter().enumerate() {
+                let frontend = client.frontend.clone();
+                tasks.spawn(async move {
+                    let response = call_with_retry(policy, &frontend, |mut frontend| {
+                        let request = request.clone();
+                        async move { frontend.query(request).await }
+                    })
+                    .await;
+                    (index, response)
+                });
+            }
+
+            let mut hits: Vec<Vec<Hit>> = (0..collections_count).map(|_| Vec::new()).collect();
+            while let Some(joined) = tasks.join_next().await {
+                let (index, response) = joined.map_err(|e| {
+                    FfiError::new(
+                        ChromaErrorCode::InternalError,
+                        "Query task failed",
+                        Some(e.to_string()),
+                    )
+                })?;
+                let response = response.map_err(|e| {
+                    FfiError::backend(ChromaErrorCode::InternalError, "Failed to execute query", &e)
+                })?;
+                hits[index] = hits_from_response(response);
+            }
+            Ok(hits)
+        });
+        client.metrics.record("query_multi", started.elapsed(), responses.is_ok());
+
+        let responses = match responses {
+            Ok(responses) => responses,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if merge_strategy == CHROMA_MERGE_BY_DISTANCE {
+            let mut merged: Vec<Hit> = responses.into_iter().flatten().collect();
+            merged.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            merged.truncate(n_results as usize);
+            unsafe {
+                *results_out = hits_to_result(merged, include_metadatas, include_documents);
+            }
+        } else {
+            for (index, hits) in responses.into_iter().enumerate() {
+                unsafe {
+                    *results_out.add(index) =
+                        hits_to_result(hits, include_metadatas, include_documents);
+                }
+            }
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
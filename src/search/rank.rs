@@ -0,0 +1,88 @@
+// Keyword ranking for ChromaDB C# bindings
+use std::collections::HashMap;
+
+/// Default BM25 term frequency saturation
+pub const DEFAULT_BM25_K1: f32 = 1.2;
+/// Default BM25 document length normalization
+pub const DEFAULT_BM25_B: f32 = 0.75;
+
+/// Splits text into lowercase alphanumeric terms
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Returns the distinct terms of a query, preserving their original order
+pub(crate) fn query_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in tokenize(text) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Scores candidate documents against the query terms with Okapi BM25.
+///
+/// `total_documents` is the size of the whole collection, so IDF reflects how
+/// rare a term is overall rather than within the candidate set.
+pub(crate) fn bm25_scores(
+    terms: &[String],
+    documents: &[Option<String>],
+    total_documents: usize,
+    k1: f32,
+    b: f32,
+) -> Vec<f32> {
+    let tokenized: Vec<Vec<String>> = documents
+        .iter()
+        .map(|d| d.as_deref().map(tokenize).unwrap_or_default())
+        .collect();
+
+    let avg_len = if tokenized.is_empty() {
+        0.0
+    } else {
+        tokenized.iter().map(|t| t.len()).sum::<usize>() as f32 / tokenized.len() as f32
+    };
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for tokens in &tokenized {
+        for term in terms {
+            if tokens.iter().any(|t| t == term) {
+                *document_frequency.entry(term.as_str()).or_default() += 1;
+            }
+        }
+    }
+
+    let n = total_documents.max(tokenized.len()) as f32;
+
+    tokenized
+        .iter()
+        .map(|tokens| {
+            let len = tokens.len() as f32;
+            terms
+                .iter()
+                .map(|term| {
+                    let tf = tokens.iter().filter(|t| *t == term).count() as f32;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let df = *document_frequency.get(term.as_str()).unwrap_or(&0) as f32;
+                    let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    let norm = if avg_len > 0.0 { len / avg_len } else { 1.0 };
+                    idf * (tf * (k1 + 1.0)) / (tf + k1 * (1.0 - b + b * norm))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Returns candidate indices ordered by descending score, truncated to `n`
+pub(crate) fn top_n(scores: &[f32], n: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    order.truncate(n);
+    order
+}
@@ -0,0 +1,463 @@
+// Sparse vectors stored in record metadata, with sparse and hybrid search over them
+use chroma_types::{
+    GetRequest, IncludeList, MetadataValue, SparseVector, UpdateCollectionRecordsRequest,
+    UpdateMetadata, UpdateMetadataValue,
+};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use std::collections::HashMap;
+
+use crate::collection::filters::{optional_c_str, parse_where};
+use crate::collection::ChromaCollection;
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode, FfiError};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::search::rank;
+use crate::search::{fuse_hits, vector_hits, ChromaFusionOptions, Fusion, KeywordHits};
+use crate::types::ChromaQueryResult;
+use crate::utils::{
+    c_array_to_vec_f32, c_array_to_vec_string, vec_f32_to_c_array, vec_string_to_c_array,
+};
+
+/// Metadata key holding the sparse vector of a record unless the caller names another
+const DEFAULT_SPARSE_KEY: &str = "sparse_embedding";
+
+/// A sparse vector as parallel arrays of dimension indices and values
+#[repr(C)]
+pub struct ChromaSparseVector {
+    /// Dimension indices, each at most once
+    pub indices: *const u32,
+    /// Value of each index
+    pub values: *const c_float,
+    /// Number of indices and of values
+    pub count: size_t,
+}
+
+/// Validates a sparse vector passed by the caller, sorting it by index
+unsafe fn sparse_from_ffi(vector: &ChromaSparseVector) -> Result<SparseVector, FfiError> {
+    if vector.count == 0 {
+        return Ok(SparseVector::new(Vec::new(), Vec::new()));
+    }
+    if vector.indices.is_null() || vector.values.is_null() {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Sparse vector indices or values pointer is null",
+            None,
+        ));
+    }
+
+    let indices = std::slice::from_raw_parts(vector.indices, vector.count);
+    let values = std::slice::from_raw_parts(vector.values, vector.count);
+    let mut entries: Vec<(u32, f32)> =
+        indices.iter().copied().zip(values.iter().copied()).collect();
+    entries.sort_by_key(|(index, _)| *index);
+
+    if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Sparse vector has a duplicate index",
+            Some(format!("Index {} appears more than once", pair[0].0)),
+        ));
+    }
+    if entries.iter().any(|(_, value)| !value.is_finite()) {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Sparse vector has a value that is not finite",
+            None,
+        ));
+    }
+
+    let (indices, values) = entries.into_iter().unzip();
+    Ok(SparseVector::new(indices, values))
+}
+
+/// Reads the sparse vector key named by the caller, or the default one
+unsafe fn sparse_key(ptr: *const c_char) -> Result<String, FfiError> {
+    Ok(optional_c_str(ptr, "Invalid sparse vector key")?
+        .unwrap_or_else(|| DEFAULT_SPARSE_KEY.to_string()))
+}
+
+fn dot(query: &HashMap<u32, f32>, vector: &SparseVector) -> f32 {
+    vector
+        .indices
+        .iter()
+        .zip(&vector.values)
+        .filter_map(|(index, value)| Some(query.get(index)? * value))
+        .sum()
+}
+
+/// Scores every visible record that has a sparse vector under `key` by its dot product with
+/// `query`, and returns the `n_results` best with a positive score
+fn sparse_hits(
+    collection: &ChromaCollection,
+    query: &SparseVector,
+    key: &str,
+    n_results: usize,
+    where_json: Option<&str>,
+) -> Result<KeywordHits, FfiError> {
+    let include_list = IncludeList::try_from(vec![
+        "documents".to_string(),
+        "metadatas".to_string(),
+    ])
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Invalid include list",
+            Some(format!("Include list validation error: {:?}", e)),
+        )
+    })?;
+
+    let request = GetRequest::try_new(
+        collection.tenant.to_string(),
+        collection.database.to_string(),
+        collection.id,
+        None,
+        collection.visible(parse_where(where_json, None)?),
+        None,
+        0,
+        include_list,
+    )
+    .map_err(|e| {
+        FfiError::new(
+            ChromaErrorCode::ValidationError,
+            "Failed to create get request",
+            Some(format!("Validation error: {:?}", e)),
+        )
+    })?;
+
+    collection.client.read_barrier(collection.id)?;
+    let response = collection
+        .client
+        .block_on_with_retry("get", collection.scope(), |mut frontend| {
+            let request = request.clone();
+            async move { frontend.get(request).await }
+        })
+        .map_err(|e| {
+            FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to execute sparse search",
+                &e,
+            )
+        })?;
+
+    let query: HashMap<u32, f32> = query
+        .indices
+        .iter()
+        .copied()
+        .zip(query.values.iter().copied())
+        .collect();
+    let metadatas = response.metadatas.unwrap_or_default();
+    let scores: Vec<f32> = (0..response.ids.len())
+        .map(|i| match metadatas.get(i).and_then(|m| m.as_ref()?.get(key)) {
+            Some(MetadataValue::SparseVector(vector)) => dot(&query, vector),
+            _ => 0.0,
+        })
+        .collect();
+
+    let mut order = rank::top_n(&scores, n_results);
+    order.retain(|&i| scores[i] > 0.0);
+
+    let documents = response.documents.unwrap_or_default();
+    Ok(KeywordHits {
+        ids: order.iter().map(|&i| response.ids[i].clone()).collect(),
+        documents: order
+            .iter()
+            .map(|&i| documents.get(i).cloned().flatten())
+            .collect(),
+        metadatas: order
+            .iter()
+            .map(|&i| {
+                let metadata = metadatas.get(i)?.as_ref()?;
+                Some(serde_json::to_string(metadata).unwrap_or_default())
+            })
+            .collect(),
+        scores: order.iter().map(|&i| scores[i]).collect(),
+    })
+}
+
+/// Attaches sparse vectors to existing records, one per ID.
+///
+/// Each vector is stored in the record's metadata under `sparse_key`, or `sparse_embedding`
+/// when null, next to the record's dense embedding; IDs that do not exist are ignored.
+/// Indices need not be sorted but must be unique.
+#[no_mangle]
+pub extern "C" fn chroma_add_sparse(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    vectors: *const ChromaSparseVector,
+    sparse_key_ptr: *const c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_add_sparse", || {
+        let func_name = "chroma_add_sparse";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || (ids_count > 0 && (ids.is_null() || vectors.is_null()))
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ids.is_null() {
+                "IDs array is null"
+            } else {
+                "Sparse vectors array is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+
+        if ids_count == 0 {
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
+
+        let ids_vec = match unsafe { c_array_to_vec_string(ids, ids_count) } {
+            Ok(v) => v,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Failed to convert IDs array",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let key = match unsafe { sparse_key(sparse_key_ptr) } {
+            Ok(key) => key,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let vectors = unsafe { std::slice::from_raw_parts(vectors, ids_count) };
+        let mut metadatas = Vec::with_capacity(ids_count);
+        for vector in vectors {
+            let vector = match unsafe { sparse_from_ffi(vector) } {
+                Ok(vector) => vector,
+                Err(e) => return e.report(error_out, func_name),
+            };
+            let mut update = UpdateMetadata::new();
+            update.insert(key.clone(), UpdateMetadataValue::SparseVector(vector));
+            metadatas.push(Some(update));
+        }
+
+        let request = match UpdateCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection.id,
+            ids_vec,
+            None,
+            None,
+            None,
+            Some(metadatas),
+        ) {
+            Ok(request) => request,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create update request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = collection.client.block_on_with_retry(
+            "update",
+            collection.scope().with_records(Some(request.ids.len())),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.update(request).await }
+            },
+        ) {
+            return FfiError::backend(
+                ChromaErrorCode::InternalError,
+                "Failed to store sparse vectors",
+                &e,
+            )
+            .report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Searches a collection by a sparse vector, optionally fused with a dense vector query.
+///
+/// Records are scored by the dot product of their sparse vector under `sparse_key` (or
+/// `sparse_embedding` when null) with `query`; records without one, or with a score of 0,
+/// do not match. The scan reads every record passing `where_filter_json`, so narrow large
+/// collections with a filter.
+///
+/// Without `query_embedding` the best `n_results` sparse matches are returned with their
+/// scores in `scores`. With it, a dense query runs as well and both rankings are fused as
+/// in `chroma_hybrid_search`: `scores` holds the fused score, `distances` the dense distance
+/// and `text_scores` the sparse score.
+#[no_mangle]
+pub extern "C" fn chroma_query_sparse(
+    collection_handle: ChromaHandle,
+    query: *const ChromaSparseVector,
+    sparse_key_ptr: *const c_char,
+    query_embedding: *const c_float,
+    embedding_dim: size_t,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    fusion_options: *const ChromaFusionOptions,
+    result: *mut *mut ChromaQueryResult,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_sparse", || {
+        let func_name = "chroma_query_sparse";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || query.is_null() || result.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query.is_null() {
+                "Sparse query pointer is null"
+            } else {
+                "Result pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let sparse_query = match unsafe { sparse_from_ffi(&*query) } {
+            Ok(vector) => vector,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let key = match unsafe { sparse_key(sparse_key_ptr) } {
+            Ok(key) => key,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let where_json = match unsafe {
+            optional_c_str(where_filter_json, "Failed to convert where filter JSON string")
+        } {
+            Ok(s) => s,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if query_embedding.is_null() {
+            let hits = match sparse_hits(
+                &collection,
+                &sparse_query,
+                &key,
+                n_results as usize,
+                where_json.as_deref(),
+            ) {
+                Ok(hits) => hits,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+            let mut query_result = Box::new(ChromaQueryResult::empty());
+
+            let (array, count) = vec_string_to_c_array(hits.ids);
+            query_result.ids = array;
+            query_result.ids_count = count;
+
+            let (array, count) = vec_string_to_c_array(
+                hits.documents
+                    .into_iter()
+                    .map(|d| d.unwrap_or_default())
+                    .collect(),
+            );
+            query_result.documents = array;
+            query_result.documents_count = count;
+
+            let (array, count) = vec_string_to_c_array(
+                hits.metadatas
+                    .into_iter()
+                    .map(|m| m.unwrap_or_default())
+                    .collect(),
+            );
+            query_result.metadata_json = array;
+            query_result.metadata_count = count;
+
+            let (array, count) = vec_f32_to_c_array(hits.scores);
+            query_result.scores = array;
+            query_result.scores_count = count;
+
+            unsafe {
+                *result = Box::into_raw(query_result);
+            }
+            set_success(error_out);
+            return ChromaErrorCode::Success as c_int;
+        }
+
+        if embedding_dim == 0 {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Invalid query embedding",
+                func_name,
+                Some("Embedding dimension is zero"),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let fusion = match unsafe { Fusion::from_ffi(fusion_options, n_results) } {
+            Ok(fusion) => fusion,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let embedding = unsafe { c_array_to_vec_f32(query_embedding, embedding_dim) };
+        let vector = match vector_hits(
+            &collection.client,
+            &collection,
+            embedding,
+            fusion.candidate_k,
+            where_json.as_deref(),
+        ) {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let sparse = match sparse_hits(
+            &collection,
+            &sparse_query,
+            &key,
+            fusion.candidate_k as usize,
+            where_json.as_deref(),
+        ) {
+            Ok(hits) => hits,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        unsafe {
+            *result = Box::into_raw(fuse_hits(vector, sparse, &fusion, n_results));
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
@@ -0,0 +1,485 @@
+// Apache Arrow IPC encoding of query and get results
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, FixedSizeListArray, Float32Array, Float64Array,
+    Int64Array, StringArray, StructArray, UInt32Array,
+};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{
+    DataType, Field, Fields, Float32Type, Float64Type, Int32Type, Int64Type, Schema,
+};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use chroma_types::{GetResponse, Metadata, MetadataValue, QueryResponse};
+
+use crate::error::{ChromaErrorCode, FfiError};
+
+/// Column type chosen for a metadata key across all records
+#[derive(Clone, Copy, PartialEq)]
+enum MetadataColumnType {
+    Utf8,
+    Int64,
+    Float64,
+    Boolean,
+}
+
+impl MetadataColumnType {
+    fn of(value: &MetadataValue) -> Self {
+        match value {
+            MetadataValue::Str(_) => MetadataColumnType::Utf8,
+            MetadataValue::Int(_) => MetadataColumnType::Int64,
+            MetadataValue::Float(_) => MetadataColumnType::Float64,
+            MetadataValue::Bool(_) => MetadataColumnType::Boolean,
+            _ => MetadataColumnType::Utf8,
+        }
+    }
+
+    /// Widens two observed types; ints and floats share Float64, anything else falls back to Utf8
+    fn merge(self, other: Self) -> Self {
+        use MetadataColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Int64, Float64) | (Float64, Int64) => Float64,
+            _ => Utf8,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            MetadataColumnType::Utf8 => DataType::Utf8,
+            MetadataColumnType::Int64 => DataType::Int64,
+            MetadataColumnType::Float64 => DataType::Float64,
+            MetadataColumnType::Boolean => DataType::Boolean,
+        }
+    }
+}
+
+/// Result rows flattened into columns, ready to be written as one record batch
+#[derive(Default)]
+struct ResultColumns {
+    query_index: Option<Vec<u32>>,
+    ids: Vec<String>,
+    distances: Option<Vec<Option<f32>>>,
+    documents: Option<Vec<Option<String>>>,
+    metadatas: Option<Vec<Option<Metadata>>>,
+    embeddings: Option<Vec<Option<Vec<f32>>>>,
+}
+
+fn arrow_error(message: &'static str, e: impl std::fmt::Display) -> FfiError {
+    FfiError::new(ChromaErrorCode::InternalError, message, Some(e.to_string()))
+}
+
+fn metadata_text(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::Str(s) => s.clone(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
+/// Builds a struct column with one child per metadata key seen in any record
+fn metadata_column(metadatas: &[Option<Metadata>]) -> Result<ArrayRef, FfiError> {
+    let mut keys: Vec<&String> = Vec::new();
+    let mut types: HashMap<&String, MetadataColumnType> = HashMap::new();
+
+    for metadata in metadatas.iter().flatten() {
+        for (key, value) in metadata {
+            let kind = MetadataColumnType::of(value);
+            match types.get_mut(key) {
+                Some(existing) => *existing = existing.merge(kind),
+                None => {
+                    keys.push(key);
+                    types.insert(key, kind);
+                }
+            }
+        }
+    }
+
+    let nulls = NullBuffer::from(metadatas.iter().map(|m| m.is_some()).collect::<Vec<bool>>());
+
+    if keys.is_empty() {
+        return Ok(Arc::new(StructArray::new_empty_fields(
+            metadatas.len(),
+            Some(nulls),
+        )));
+    }
+
+    let mut fields = Vec::with_capacity(keys.len());
+    let mut children: Vec<ArrayRef> = Vec::with_capacity(keys.len());
+
+    for key in keys {
+        let kind = types[key];
+        let values = metadatas
+            .iter()
+            .map(|m| m.as_ref().and_then(|m| m.get(key)));
+
+        let child: ArrayRef = match kind {
+            MetadataColumnType::Utf8 => Arc::new(
+                values
+                    .map(|v| v.map(metadata_text))
+                    .collect::<StringArray>(),
+            ),
+            MetadataColumnType::Int64 => Arc::new(
+                values
+                    .map(|v| match v {
+                        Some(MetadataValue::Int(i)) => Some(*i),
+                        _ => None,
+                    })
+                    .collect::<Int64Array>(),
+            ),
+            MetadataColumnType::Float64 => Arc::new(
+                values
+                    .map(|v| match v {
+                        Some(MetadataValue::Float(f)) => Some(*f),
+                        Some(MetadataValue::Int(i)) => Some(*i as f64),
+                        _ => None,
+                    })
+                    .collect::<Float64Array>(),
+            ),
+            MetadataColumnType::Boolean => Arc::new(
+                values
+                    .map(|v| match v {
+                        Some(MetadataValue::Bool(b)) => Some(*b),
+                        _ => None,
+                    })
+                    .collect::<BooleanArray>(),
+            ),
+        };
+
+        fields.push(Field::new(key.as_str(), kind.data_type(), true));
+        children.push(child);
+    }
+
+    StructArray::try_new(Fields::from(fields), children, Some(nulls))
+        .map(|array| Arc::new(array) as ArrayRef)
+        .map_err(|e| arrow_error("Failed to build metadata column", e))
+}
+
+/// Builds a fixed-size list column; every present embedding must share one dimension
+fn embedding_column(embeddings: &[Option<Vec<f32>>]) -> Result<Option<(Field, ArrayRef)>, FfiError> {
+    let Some(dimension) = embeddings.iter().flatten().map(|e| e.len()).next() else {
+        return Ok(None);
+    };
+
+    if let Some(mismatch) = embeddings.iter().flatten().find(|e| e.len() != dimension) {
+        return Err(FfiError::new(
+            ChromaErrorCode::InternalError,
+            "Embeddings in the result have different dimensions",
+            Some(format!("Expected {}, got {}", dimension, mismatch.len())),
+        ));
+    }
+
+    let mut values = Vec::with_capacity(embeddings.len() * dimension);
+    for embedding in embeddings {
+        match embedding {
+            Some(e) => values.extend_from_slice(e),
+            None => values.resize(values.len() + dimension, 0.0),
+        }
+    }
+
+    let item = Arc::new(Field::new("item", DataType::Float32, false));
+    let nulls = NullBuffer::from(embeddings.iter().map(|e| e.is_some()).collect::<Vec<bool>>());
+    let array = FixedSizeListArray::try_new(
+        item.clone(),
+        dimension as i32,
+        Arc::new(Float32Array::from(values)),
+        Some(nulls),
+    )
+    .map_err(|e| arrow_error("Failed to build embedding column", e))?;
+
+    Ok(Some((
+        Field::new("embedding", DataType::FixedSizeList(item, dimension as i32), true),
+        Arc::new(array),
+    )))
+}
+
+fn write_ipc(columns: ResultColumns) -> Result<Vec<u8>, FfiError> {
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+
+    if let Some(query_index) = columns.query_index {
+        fields.push(Field::new("query_index", DataType::UInt32, false));
+        arrays.push(Arc::new(UInt32Array::from(query_index)));
+    }
+
+    fields.push(Field::new("id", DataType::Utf8, false));
+    arrays.push(Arc::new(StringArray::from(columns.ids)));
+
+    if let Some(distances) = columns.distances {
+        fields.push(Field::new("distance", DataType::Float32, true));
+        arrays.push(Arc::new(Float32Array::from(distances)));
+    }
+
+    if let Some(documents) = columns.documents {
+        fields.push(Field::new("document", DataType::Utf8, true));
+        arrays.push(Arc::new(StringArray::from(documents)));
+    }
+
+    if let Some(metadatas) = columns.metadatas {
+        let array = metadata_column(&metadatas)?;
+        fields.push(Field::new("metadata", array.data_type().clone(), true));
+        arrays.push(array);
+    }
+
+    if let Some(embeddings) = columns.embeddings {
+        if let Some((field, array)) = embedding_column(&embeddings)? {
+            fields.push(field);
+            arrays.push(array);
+        }
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)
+        .map_err(|e| arrow_error("Failed to build record batch", e))?;
+
+    let mut buffer = Vec::new();
+    {
+        let mut writer = StreamWriter::try_new(&mut buffer, &schema)
+            .map_err(|e| arrow_error("Failed to create Arrow IPC writer", e))?;
+        writer
+            .write(&batch)
+            .map_err(|e| arrow_error("Failed to write Arrow record batch", e))?;
+        writer
+            .finish()
+            .map_err(|e| arrow_error("Failed to finish Arrow IPC stream", e))?;
+    }
+
+    Ok(buffer)
+}
+
+/// Encodes a query response as an Arrow IPC stream with one row per result.
+///
+/// Rows of all queries share the batch; `query_index` identifies the query each row belongs to.
+pub(crate) fn query_response_to_ipc(response: &QueryResponse) -> Result<Vec<u8>, FfiError> {
+    let mut columns = ResultColumns {
+        query_index: Some(Vec::new()),
+        distances: response.distances.as_ref().map(|_| Vec::new()),
+        documents: response.documents.as_ref().map(|_| Vec::new()),
+        metadatas: response.metadatas.as_ref().map(|_| Vec::new()),
+        embeddings: response.embeddings.as_ref().map(|_| Vec::new()),
+        ..Default::default()
+    };
+
+    for (q, ids) in response.ids.iter().enumerate() {
+        for (i, id) in ids.iter().enumerate() {
+            if let Some(query_index) = columns.query_index.as_mut() {
+                query_index.push(q as u32);
+            }
+            columns.ids.push(id.clone());
+
+            if let (Some(out), Some(values)) = (columns.distances.as_mut(), &response.distances) {
+                out.push(values.get(q).and_then(|v| v.get(i)).copied().flatten());
+            }
+            if let (Some(out), Some(values)) = (columns.documents.as_mut(), &response.documents) {
+                out.push(values.get(q).and_then(|v| v.get(i)).cloned().flatten());
+            }
+            if let (Some(out), Some(values)) = (columns.metadatas.as_mut(), &response.metadatas) {
+                out.push(values.get(q).and_then(|v| v.get(i)).cloned().flatten());
+            }
+            if let (Some(out), Some(values)) = (columns.embeddings.as_mut(), &response.embeddings) {
+                out.push(values.get(q).and_then(|v| v.get(i)).cloned().flatten());
+            }
+        }
+    }
+
+    write_ipc(columns)
+}
+
+/// Encodes a get response as an Arrow IPC stream with one row per record
+pub(crate) fn get_response_to_ipc(response: &GetResponse) -> Result<Vec<u8>, FfiError> {
+    let columns = ResultColumns {
+        ids: response.ids.clone(),
+        documents: response.documents.clone(),
+        metadatas: response.metadatas.clone(),
+        embeddings: response
+            .embeddings
+            .as_ref()
+            .map(|e| e.iter().cloned().map(Some).collect()),
+        ..Default::default()
+    };
+
+    write_ipc(columns)
+}
+
+/// Records decoded from an Arrow IPC stream, in the shape of an add request
+pub(crate) struct ArrowRecords {
+    pub(crate) ids: Vec<String>,
+    pub(crate) embeddings: Vec<Vec<f32>>,
+    pub(crate) documents: Option<Vec<Option<String>>>,
+    pub(crate) metadatas: Option<Vec<Option<Metadata>>>,
+}
+
+fn invalid_batch(message: &'static str, details: String) -> FfiError {
+    FfiError::new(ChromaErrorCode::InvalidArgument, message, Some(details))
+}
+
+/// Reads an embedding from a `FixedSizeList<Float32>` or `List<Float32>` column
+fn embedding_at(column: &dyn Array, row: usize) -> Result<Vec<f32>, FfiError> {
+    if column.is_null(row) {
+        return Err(invalid_batch(
+            "Embedding is null",
+            format!("Null embedding at row {}", row),
+        ));
+    }
+
+    let values = if let Some(list) = column.as_fixed_size_list_opt() {
+        list.value(row)
+    } else if let Some(list) = column.as_list_opt::<i32>() {
+        list.value(row)
+    } else {
+        return Err(invalid_batch(
+            "Unsupported embedding column type",
+            format!("Expected a list of Float32, got {}", column.data_type()),
+        ));
+    };
+
+    values
+        .as_primitive_opt::<Float32Type>()
+        .map(|v| v.values().to_vec())
+        .ok_or_else(|| {
+            invalid_batch(
+                "Unsupported embedding column type",
+                format!("Expected Float32 items, got {}", values.data_type()),
+            )
+        })
+}
+
+/// Reads one metadata value from a struct child column
+fn metadata_value_at(column: &dyn Array, row: usize) -> Result<Option<MetadataValue>, FfiError> {
+    if column.is_null(row) {
+        return Ok(None);
+    }
+
+    let value = match column.data_type() {
+        DataType::Utf8 => MetadataValue::Str(column.as_string::<i32>().value(row).to_string()),
+        DataType::Int64 => MetadataValue::Int(column.as_primitive::<Int64Type>().value(row)),
+        DataType::Int32 => MetadataValue::Int(column.as_primitive::<Int32Type>().value(row) as i64),
+        DataType::Float64 => MetadataValue::Float(column.as_primitive::<Float64Type>().value(row)),
+        DataType::Float32 => {
+            MetadataValue::Float(column.as_primitive::<Float32Type>().value(row) as f64)
+        }
+        DataType::Boolean => MetadataValue::Bool(column.as_boolean().value(row)),
+        other => {
+            return Err(invalid_batch(
+                "Unsupported metadata column type",
+                format!(
+                    "Metadata fields must be Utf8, Int32/64, Float32/64 or Boolean, got {}",
+                    other
+                ),
+            ))
+        }
+    };
+
+    Ok(Some(value))
+}
+
+/// Reads a metadata entry from a struct column or a Utf8 column of JSON objects
+fn metadata_at(column: &dyn Array, row: usize) -> Result<Option<Metadata>, FfiError> {
+    if column.is_null(row) {
+        return Ok(None);
+    }
+
+    if let Some(json) = column.as_string_opt::<i32>() {
+        let json = json.value(row);
+        if json.is_empty() {
+            return Ok(None);
+        }
+        return serde_json::from_str::<Metadata>(json).map(Some).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::ValidationError,
+                "Invalid metadata JSON",
+                Some(format!("Error parsing metadata at row {}: {}", row, e)),
+            )
+        });
+    }
+
+    let Some(fields) = column.as_struct_opt() else {
+        return Err(invalid_batch(
+            "Unsupported metadata column type",
+            format!("Expected a struct or Utf8 JSON column, got {}", column.data_type()),
+        ));
+    };
+
+    let mut metadata = Metadata::new();
+    for (name, child) in fields.column_names().into_iter().zip(fields.columns()) {
+        if let Some(value) = metadata_value_at(child.as_ref(), row)? {
+            metadata.insert(name.to_string(), value);
+        }
+    }
+
+    Ok((!metadata.is_empty()).then_some(metadata))
+}
+
+/// Decodes an Arrow IPC stream with `id`, `embedding` and optional `document` and
+/// `metadata` columns, the same layout produced by the Arrow result format
+pub(crate) fn ipc_to_records(buffer: &[u8]) -> Result<ArrowRecords, FfiError> {
+    let reader = StreamReader::try_new(std::io::Cursor::new(buffer), None)
+        .map_err(|e| invalid_batch("Failed to read Arrow IPC stream", e.to_string()))?;
+
+    let mut records = ArrowRecords {
+        ids: Vec::new(),
+        embeddings: Vec::new(),
+        documents: None,
+        metadatas: None,
+    };
+
+    for batch in reader {
+        let batch =
+            batch.map_err(|e| invalid_batch("Failed to read Arrow record batch", e.to_string()))?;
+
+        let ids = batch
+            .column_by_name("id")
+            .and_then(|c| c.as_string_opt::<i32>())
+            .ok_or_else(|| {
+                invalid_batch("Missing id column", "Expected a Utf8 column named 'id'".to_string())
+            })?;
+        let embeddings = batch.column_by_name("embedding").ok_or_else(|| {
+            invalid_batch(
+                "Missing embedding column",
+                "Expected a list column named 'embedding'".to_string(),
+            )
+        })?;
+        let documents = match batch.column_by_name("document") {
+            Some(c) => Some(c.as_string_opt::<i32>().ok_or_else(|| {
+                invalid_batch(
+                    "Unsupported document column type",
+                    format!("Expected Utf8, got {}", c.data_type()),
+                )
+            })?),
+            None => None,
+        };
+        let metadatas = batch.column_by_name("metadata");
+
+        for row in 0..batch.num_rows() {
+            if ids.is_null(row) {
+                return Err(invalid_batch("ID is null", format!("Null id at row {}", row)));
+            }
+
+            records.ids.push(ids.value(row).to_string());
+            records.embeddings.push(embedding_at(embeddings.as_ref(), row)?);
+
+            if let Some(documents) = documents {
+                let document = (!documents.is_null(row))
+                    .then(|| documents.value(row).to_string())
+                    .filter(|d| !d.is_empty());
+                records.documents.get_or_insert_with(Vec::new).push(document);
+            }
+
+            if let Some(metadatas) = metadatas {
+                let metadata = metadata_at(metadatas.as_ref(), row)?;
+                records.metadatas.get_or_insert_with(Vec::new).push(metadata);
+            }
+        }
+    }
+
+    if records.ids.is_empty() {
+        return Err(invalid_batch(
+            "Arrow stream contains no records",
+            "At least one row is required".to_string(),
+        ));
+    }
+
+    Ok(records)
+}
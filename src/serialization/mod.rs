@@ -0,0 +1,520 @@
+// Serialized result formats and bulk ingestion for ChromaDB C# bindings
+mod ipc;
+
+use chroma_types::{AddCollectionRecordsRequest, GetRequest, IncludeList, QueryRequest};
+use libc::{c_char, c_float, c_int, c_uint, size_t};
+use serde::Serialize;
+
+use crate::collection::filters::parse_where_ptrs;
+use crate::error::{
+    ffi_boundary, ffi_boundary_void, set_error, set_success, ChromaError, ChromaErrorCode,
+    FfiError,
+};
+use crate::handles::{collection_from_handle, ChromaHandle, CHROMA_INVALID_HANDLE};
+use crate::utils::{c_array_to_vec_f32, c_array_to_vec_string, chroma_free, vec_u8_to_c_array};
+
+/// Results encoded as MessagePack, with struct fields written as map keys
+pub const CHROMA_SERIALIZATION_MSGPACK: c_int = 0;
+/// Results encoded as CBOR
+pub const CHROMA_SERIALIZATION_CBOR: c_int = 1;
+/// Results encoded as an Apache Arrow IPC stream holding a single record batch
+pub const CHROMA_SERIALIZATION_ARROW_IPC: c_int = 2;
+
+/// Encodes a response in the requested format
+fn encode_response<T: Serialize>(
+    response: &T,
+    format: c_int,
+    to_arrow: fn(&T) -> Result<Vec<u8>, FfiError>,
+) -> Result<Vec<u8>, FfiError> {
+    match format {
+        CHROMA_SERIALIZATION_MSGPACK => rmp_serde::to_vec_named(response).map_err(|e| {
+            FfiError::new(
+                ChromaErrorCode::InternalError,
+                "Failed to encode result as MessagePack",
+                Some(e.to_string()),
+            )
+        }),
+        CHROMA_SERIALIZATION_CBOR => {
+            let mut buffer = Vec::new();
+            ciborium::into_writer(response, &mut buffer).map_err(|e| {
+                FfiError::new(
+                    ChromaErrorCode::InternalError,
+                    "Failed to encode result as CBOR",
+                    Some(e.to_string()),
+                )
+            })?;
+            Ok(buffer)
+        }
+        CHROMA_SERIALIZATION_ARROW_IPC => to_arrow(response),
+        _ => Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Unknown serialization format",
+            Some(format!("Serialization format: {}", format)),
+        )),
+    }
+}
+
+/// Queries a collection and returns the whole response encoded in a single buffer.
+///
+/// `query_embeddings` holds `query_count` embeddings of `embedding_dim` floats each.
+/// The buffer must be released with `chroma_free_buffer`.
+#[no_mangle]
+pub extern "C" fn chroma_query_serialized(
+    collection_handle: ChromaHandle,
+    query_embeddings: *const c_float,
+    embedding_dim: size_t,
+    query_count: size_t,
+    n_results: c_uint,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    include_embeddings: bool,
+    include_metadatas: bool,
+    include_documents: bool,
+    include_distances: bool,
+    format: c_int,
+    out_buffer: *mut *mut u8,
+    out_len: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_query_serialized", || {
+        let func_name = "chroma_query_serialized";
+
+        if collection_handle == CHROMA_INVALID_HANDLE
+            || query_embeddings.is_null()
+            || out_buffer.is_null()
+            || out_len.is_null()
+        {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if query_embeddings.is_null() {
+                "Query embeddings pointer is null"
+            } else if out_buffer.is_null() {
+                "Output buffer pointer is null"
+            } else {
+                "Output length pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        if embedding_dim == 0 || query_count == 0 {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Invalid query embeddings",
+                func_name,
+                Some(&format!(
+                    "Embedding dimension {} and query count {} must be non-zero",
+                    embedding_dim, query_count
+                )),
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+
+        if let Err(e) = collection.check_dimension(embedding_dim) {
+            return e.report(error_out, func_name);
+        }
+
+        let collection_id = collection.id;
+
+        let where_filter =
+            match unsafe { parse_where_ptrs(where_filter_json, where_document_filter) } {
+                Ok(w) => w,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+        let flat = unsafe { c_array_to_vec_f32(query_embeddings, embedding_dim * query_count) };
+        let embeddings: Vec<Vec<f32>> = flat.chunks(embedding_dim).map(|c| c.to_vec()).collect();
+
+        let mut include = Vec::new();
+        if include_embeddings {
+            include.push("embeddings".to_string());
+        }
+        if include_metadatas {
+            include.push("metadatas".to_string());
+        }
+        if include_documents {
+            include.push("documents".to_string());
+        }
+        if include_distances {
+            include.push("distances".to_string());
+        }
+
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let request = match QueryRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            None,
+            collection.visible(where_filter),
+            embeddings,
+            n_results,
+            include_list,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create query request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
+        }
+        let query_response = match client.block_on_with_retry(
+            "query",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.query(request).await }
+            },
+        ) {
+            Ok(resp) => resp,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to execute query",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+        };
+
+        let encoded = match encode_response(&query_response, format, ipc::query_response_to_ipc) {
+            Ok(bytes) => bytes,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if let Err(e) = unsafe { write_buffer(encoded, out_buffer, out_len) } {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Copies encoded bytes into a malloc'd buffer owned by the caller
+pub(crate) unsafe fn write_buffer(
+    encoded: Vec<u8>,
+    out_buffer: *mut *mut u8,
+    out_len: *mut size_t,
+) -> Result<(), FfiError> {
+    let expected_len = encoded.len();
+    let (buffer, len) = vec_u8_to_c_array(encoded);
+    if buffer.is_null() && expected_len > 0 {
+        return Err(FfiError::new(
+            ChromaErrorCode::MemoryError,
+            "Failed to allocate result buffer",
+            Some(format!("Requested {} bytes", expected_len)),
+        ));
+    }
+
+    *out_buffer = buffer;
+    *out_len = len;
+    Ok(())
+}
+
+/// Gets records from a collection and returns the whole response encoded in a single buffer.
+///
+/// The buffer must be released with `chroma_free_buffer`.
+#[no_mangle]
+pub extern "C" fn chroma_get_serialized(
+    collection_handle: ChromaHandle,
+    ids: *const *const c_char,
+    ids_count: size_t,
+    where_filter_json: *const c_char,
+    where_document_filter: *const c_char,
+    limit: c_uint,
+    offset: c_uint,
+    include_embeddings: bool,
+    include_metadatas: bool,
+    include_documents: bool,
+    format: c_int,
+    out_buffer: *mut *mut u8,
+    out_len: *mut size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_get_serialized", || {
+        let func_name = "chroma_get_serialized";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || out_buffer.is_null() || out_len.is_null() {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if out_buffer.is_null() {
+                "Output buffer pointer is null"
+            } else {
+                "Output length pointer is null"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        let client = &collection.client;
+
+        let ids_vec = if !ids.is_null() && ids_count > 0 {
+            match unsafe { c_array_to_vec_string(ids, ids_count) } {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    set_error(
+                        error_out,
+                        ChromaErrorCode::InvalidArgument,
+                        "Failed to convert IDs array",
+                        func_name,
+                        Some(&e.to_string()),
+                    );
+                    return ChromaErrorCode::InvalidArgument as c_int;
+                }
+            }
+        } else {
+            None
+        };
+
+        let collection_id = collection.id;
+
+        let where_filter =
+            match unsafe { parse_where_ptrs(where_filter_json, where_document_filter) } {
+                Ok(w) => w,
+                Err(e) => return e.report(error_out, func_name),
+            };
+
+        let mut include = Vec::new();
+        if include_embeddings {
+            include.push("embeddings".to_string());
+        }
+        if include_metadatas {
+            include.push("metadatas".to_string());
+        }
+        if include_documents {
+            include.push("documents".to_string());
+        }
+
+        let include_list = match IncludeList::try_from(include) {
+            Ok(list) => list,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Invalid include list",
+                    func_name,
+                    Some(&format!("Include list validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        let request = match GetRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            ids_vec,
+            collection.visible(where_filter),
+            if limit > 0 { Some(limit) } else { None },
+            offset,
+            include_list,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create get request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        if let Err(e) = client.read_barrier(collection.id) {
+            return e.report(error_out, func_name);
+        }
+        let get_response = match client.block_on_with_retry(
+            "get",
+            collection.scope(),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.get(request).await }
+            },
+        ) {
+            Ok(resp) => resp,
+            Err(e) => {
+                return FfiError::backend(
+                    ChromaErrorCode::InternalError,
+                    "Failed to get documents",
+                    &e,
+                )
+                .report(error_out, func_name);
+            }
+        };
+
+        let encoded = match encode_response(&get_response, format, ipc::get_response_to_ipc) {
+            Ok(bytes) => bytes,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        if let Err(e) = unsafe { write_buffer(encoded, out_buffer, out_len) } {
+            return e.report(error_out, func_name);
+        }
+
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Adds records decoded from an Arrow IPC stream.
+///
+/// The stream must contain an `id` Utf8 column and an `embedding` column of
+/// `FixedSizeList<Float32>` or `List<Float32>`. Optional `document` (Utf8) and
+/// `metadata` (struct, or Utf8 JSON objects) columns are read when present.
+#[no_mangle]
+pub extern "C" fn chroma_add_arrow(
+    collection_handle: ChromaHandle,
+    ipc_buffer: *const u8,
+    ipc_len: size_t,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_add_arrow", || {
+        let func_name = "chroma_add_arrow";
+
+        if collection_handle == CHROMA_INVALID_HANDLE || ipc_buffer.is_null() || ipc_len == 0 {
+            let message = if collection_handle == CHROMA_INVALID_HANDLE {
+                "Collection handle is null"
+            } else if ipc_buffer.is_null() {
+                "IPC buffer pointer is null"
+            } else {
+                "IPC buffer length is zero"
+            };
+
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                message,
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let collection = match collection_from_handle(collection_handle) {
+            Ok(collection) => collection,
+            Err(e) => return e.report(error_out, func_name),
+        };
+        if let Err(e) = collection.check_writable() {
+            return e.report(error_out, func_name);
+        }
+        let client = &collection.client;
+        let buffer = unsafe { std::slice::from_raw_parts(ipc_buffer, ipc_len) };
+
+        let records = match ipc::ipc_to_records(buffer) {
+            Ok(records) => records,
+            Err(e) => return e.report(error_out, func_name),
+        };
+
+        let collection_id = collection.id;
+
+        let record_count = records.ids.len();
+        let dimension = records.embeddings.first().map(Vec::len);
+        if let Err(e) =
+            collection.validate_new_metadatas(record_count, records.metadatas.as_deref())
+        {
+            return e.report(error_out, func_name);
+        }
+
+        let request = match AddCollectionRecordsRequest::try_new(
+            collection.tenant.to_string(),
+            collection.database.to_string(),
+            collection_id,
+            records.ids,
+            records.embeddings,
+            records.documents,
+            None, // uris
+            records.metadatas,
+        ) {
+            Ok(req) => req,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::ValidationError,
+                    "Failed to create add request",
+                    func_name,
+                    Some(&format!("Validation error: {:?}", e)),
+                );
+                return ChromaErrorCode::ValidationError as c_int;
+            }
+        };
+
+        match client.block_on_with_retry(
+            "add",
+            collection
+                .scope()
+                .with_records(Some(request.ids.len()))
+                .with_dimension(dimension),
+            |mut frontend| {
+                let request = request.clone();
+                async move { frontend.add(request).await }
+            },
+        ) {
+            Ok(_) => {
+                client.metrics.add_records(record_count);
+                set_success(error_out);
+                ChromaErrorCode::Success as c_int
+            }
+            Err(e) => {
+                FfiError::backend(ChromaErrorCode::InternalError, "Failed to add documents", &e)
+                    .report(error_out, func_name)
+            }
+        }
+    })
+}
+
+/// Frees a buffer returned by a serialized result function
+#[no_mangle]
+pub extern "C" fn chroma_free_buffer(buffer: *mut u8) {
+    ffi_boundary_void("chroma_free_buffer", || {
+        chroma_free(buffer as *mut libc::c_void);
+    })
+}
@@ -1,12 +1,103 @@
-use libc::{c_char, c_float, c_int, size_t};
+use chroma_types::{Metadata, MetadataValue};
+use libc::{c_char, c_float, c_int, c_uint, c_void, size_t};
+use std::ptr;
 
+use crate::abi::SizedInput;
+use crate::error::{ffi_boundary_void, ChromaErrorCode, FfiError};
+use crate::utils::{chroma_free, chroma_malloc, string_to_c_str};
+
+/// Metadata returned as one JSON object string per record
+pub const CHROMA_METADATA_FORMAT_JSON: c_int = 0;
+/// Metadata returned as a flat array of typed `ChromaMetadataEntry` values
+pub const CHROMA_METADATA_FORMAT_TYPED: c_int = 1;
+
+/// Value types of a `ChromaMetadataEntry`
+pub const CHROMA_METADATA_VALUE_STRING: c_int = 0;
+pub const CHROMA_METADATA_VALUE_INT: c_int = 1;
+pub const CHROMA_METADATA_VALUE_FLOAT: c_int = 2;
+pub const CHROMA_METADATA_VALUE_BOOL: c_int = 3;
+/// Values without a scalar representation, serialized as JSON in `str_val`
+pub const CHROMA_METADATA_VALUE_JSON: c_int = 4;
+
+/// SQLite settings of the client.
+///
+/// Zero in any of the tuning fields keeps the backend default.
 #[repr(C)]
 pub struct SqliteConfigFFI {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Ignored; the database lives in the persist directory. May be null
     pub url: *const c_char,
     pub hash_type: c_int,
     pub migration_mode: c_int,
+    /// One of the `CHROMA_JOURNAL_MODE_*` modes
+    pub journal_mode: c_int,
+    /// One of the `CHROMA_SYNCHRONOUS_*` levels
+    pub synchronous: c_int,
+    /// How long a connection waits for a locked database, in milliseconds
+    pub busy_timeout_ms: c_uint,
+    /// Page cache of each connection, in KiB
+    pub cache_size_kib: c_uint,
+}
+
+/// Disk tier of the HNSW index cache; indexes evicted from memory are kept on disk
+#[repr(C)]
+pub struct DiskCacheConfigFFI {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Directory holding the cached indexes, created if missing
+    pub dir: *const c_char,
+    /// Disk space for the cache, in MiB
+    pub disk_mib: size_t,
+    /// Memory in front of the disk tier, in MiB (0 = Foyer default)
+    pub memory_mib: size_t,
+}
+
+/// Cache of collections and their segments, which saves a sysdb lookup per operation
+#[repr(C)]
+pub struct CollectionCacheConfigFFI {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// Number of collections cached in memory (0 = no cache)
+    pub capacity: size_t,
+    /// Seconds a cached collection stays valid (0 = 60)
+    pub ttl_secs: c_uint,
+    /// Maximum concurrent sysdb lookups (0 = 32)
+    pub permitted_parallelism: c_uint,
+}
+
+/// Advisory lock taken on the persist directory when the client is created
+#[repr(C)]
+pub struct LockConfigFFI {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// One of the `CHROMA_LOCK_*` modes
+    pub mode: c_int,
+    /// How long to wait for other clients to release the lock, in milliseconds
+    pub wait_ms: c_uint,
+}
+
+/// Encryption at rest of the persist directory, see `chroma_create_client`
+#[repr(C)]
+pub struct EncryptionConfigFFI {
+    /// Size of this struct as compiled by the caller, see `SizedInput`
+    pub struct_size: size_t,
+    /// AES-256 key the persist directory is sealed with
+    pub key: *const u8,
+    /// Length of `key` in bytes, `CHROMA_ENCRYPTION_KEY_LEN`
+    pub key_len: size_t,
 }
 
+unsafe impl SizedInput for SqliteConfigFFI {}
+unsafe impl SizedInput for DiskCacheConfigFFI {}
+unsafe impl SizedInput for CollectionCacheConfigFFI {}
+unsafe impl SizedInput for LockConfigFFI {}
+unsafe impl SizedInput for EncryptionConfigFFI {}
+
+/// Result of a get, query or search call.
+///
+/// Owned by the library: release with `chroma_free_query_result`, which frees
+/// every array and string it points to.
 #[repr(C)]
 pub struct ChromaQueryResult {
     pub ids: *mut *mut c_char,
@@ -17,6 +108,182 @@ pub struct ChromaQueryResult {
     pub metadata_count: size_t,
     pub documents: *mut *mut c_char,
     pub documents_count: size_t,
+    pub scores: *mut c_float,
+    pub scores_count: size_t,
+    pub text_scores: *mut c_float,
+    pub text_scores_count: size_t,
+    pub metadata_entries: *mut ChromaMetadataEntry,
+    pub metadata_entries_count: size_t,
+    /// Embeddings as one row-major block of `embeddings_count` rows of `embedding_dim`
+    /// floats, row `i` belonging to `ids[i]`; rows of records without an embedding are NaN
+    pub embeddings: *mut c_float,
+    pub embeddings_count: size_t,
+    pub embedding_dim: size_t,
+    /// Whether `chroma_get` found more matching records beyond the returned page
+    pub has_more: bool,
+    /// Number of records matching a `chroma_get` across all pages, or -1 when it was not
+    /// asked to count them
+    pub total_matching_count: i64,
+    /// Version token of each record returned by `chroma_get`, `versions[i]` belonging to
+    /// `ids[i]`; null unless versions were asked for
+    pub versions: *mut i64,
+    pub versions_count: size_t,
+}
+
+impl ChromaQueryResult {
+    /// Creates a result with every column empty
+    pub(crate) fn empty() -> Self {
+        ChromaQueryResult {
+            ids: ptr::null_mut(),
+            ids_count: 0,
+            distances: ptr::null_mut(),
+            distances_count: 0,
+            metadata_json: ptr::null_mut(),
+            metadata_count: 0,
+            documents: ptr::null_mut(),
+            documents_count: 0,
+            scores: ptr::null_mut(),
+            scores_count: 0,
+            text_scores: ptr::null_mut(),
+            text_scores_count: 0,
+            metadata_entries: ptr::null_mut(),
+            metadata_entries_count: 0,
+            embeddings: ptr::null_mut(),
+            embeddings_count: 0,
+            embedding_dim: 0,
+            has_more: false,
+            total_matching_count: -1,
+            versions: ptr::null_mut(),
+            versions_count: 0,
+        }
+    }
+
+    /// Copies embedding rows into one contiguous block
+    pub(crate) fn set_embeddings(&mut self, rows: &[Option<&[f32]>]) {
+        let dimension = rows.iter().flatten().map(|row| row.len()).max().unwrap_or(0);
+        if dimension == 0 {
+            return;
+        }
+
+        let mut values = Vec::with_capacity(rows.len() * dimension);
+        for row in rows {
+            let row = row.unwrap_or_default();
+            values.extend_from_slice(row);
+            values.resize(values.len() + dimension - row.len(), f32::NAN);
+        }
+
+        let (array, _) = crate::utils::vec_f32_to_c_array(values);
+        self.embeddings = array;
+        self.embeddings_count = rows.len();
+        self.embedding_dim = dimension;
+    }
+}
+
+/// A single metadata key/value of a result record
+#[repr(C)]
+pub struct ChromaMetadataEntry {
+    /// Index of the record in the result's ID list
+    pub record_index: size_t,
+    pub key: *mut c_char,
+    /// One of the `CHROMA_METADATA_VALUE_*` constants
+    pub value_type: c_int,
+    pub str_val: *mut c_char,
+    pub int_val: i64,
+    pub float_val: f64,
+    pub bool_val: bool,
+}
+
+/// Flattens per-record metadata into a C array of typed entries.
+///
+/// When `keys` is given, only those keys are emitted.
+pub(crate) fn metadata_entries_to_c_array(
+    metadatas: &[Option<Metadata>],
+    keys: Option<&[String]>,
+) -> (*mut ChromaMetadataEntry, size_t) {
+    let mut entries = Vec::new();
+
+    for (record_index, metadata) in metadatas.iter().enumerate() {
+        let Some(metadata) = metadata else {
+            continue;
+        };
+
+        for (key, value) in metadata {
+            if keys.is_some_and(|keys| !keys.contains(key)) {
+                continue;
+            }
+
+            let mut entry = ChromaMetadataEntry {
+                record_index,
+                key: string_to_c_str(key.clone()),
+                value_type: CHROMA_METADATA_VALUE_STRING,
+                str_val: ptr::null_mut(),
+                int_val: 0,
+                float_val: 0.0,
+                bool_val: false,
+            };
+
+            match value {
+                MetadataValue::Str(s) => entry.str_val = string_to_c_str(s.clone()),
+                MetadataValue::Int(i) => {
+                    entry.value_type = CHROMA_METADATA_VALUE_INT;
+                    entry.int_val = *i;
+                }
+                MetadataValue::Float(f) => {
+                    entry.value_type = CHROMA_METADATA_VALUE_FLOAT;
+                    entry.float_val = *f;
+                }
+                MetadataValue::Bool(b) => {
+                    entry.value_type = CHROMA_METADATA_VALUE_BOOL;
+                    entry.bool_val = *b;
+                }
+                other => {
+                    entry.value_type = CHROMA_METADATA_VALUE_JSON;
+                    entry.str_val = string_to_c_str(serde_json::to_string(other).unwrap_or_default());
+                }
+            }
+
+            entries.push(entry);
+        }
+    }
+
+    let count = entries.len();
+    if count == 0 {
+        return (ptr::null_mut(), 0);
+    }
+
+    unsafe {
+        let array = chroma_malloc(count * std::mem::size_of::<ChromaMetadataEntry>())
+            as *mut ChromaMetadataEntry;
+        if array.is_null() {
+            for entry in entries {
+                free_metadata_entry(&entry);
+            }
+            return (ptr::null_mut(), 0);
+        }
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            array.add(i).write(entry);
+        }
+
+        (array, count)
+    }
+}
+
+unsafe fn free_metadata_entry(entry: &ChromaMetadataEntry) {
+    crate::utils::chroma_free_string(entry.key);
+    crate::utils::chroma_free_string(entry.str_val);
+}
+
+/// Frees an array of metadata entries
+pub(crate) unsafe fn free_metadata_entries(entries: *mut ChromaMetadataEntry, count: size_t) {
+    if entries.is_null() {
+        return;
+    }
+
+    for i in 0..count {
+        free_metadata_entry(&*entries.add(i));
+    }
+    chroma_free(entries as *mut c_void);
 }
 
 #[repr(C)]
@@ -25,26 +292,159 @@ pub struct ChromaEmbedding {
     pub dimension: size_t,
 }
 
+/// Element types accepted in a `ChromaEmbeddingBatch`
+pub const CHROMA_DTYPE_F32: c_int = 0;
+pub const CHROMA_DTYPE_F16: c_int = 1;
+pub const CHROMA_DTYPE_F64: c_int = 2;
+
+/// A contiguous, row-major block of `count` embeddings of `dimension` elements each
+#[repr(C)]
+pub struct ChromaEmbeddingBatch {
+    pub data: *const c_void,
+    /// One of the `CHROMA_DTYPE_*` constants
+    pub dtype: c_int,
+    pub count: size_t,
+    pub dimension: size_t,
+}
+
+/// Converts an embedding batch of any supported element type to f32 rows
+pub(crate) unsafe fn embedding_batch_to_vecs(
+    batch: &ChromaEmbeddingBatch,
+) -> Result<Vec<Vec<f32>>, FfiError> {
+    if batch.data.is_null() || batch.count == 0 || batch.dimension == 0 {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid embedding batch",
+            Some(format!(
+                "Data must be non-null with non-zero count ({}) and dimension ({})",
+                batch.count, batch.dimension
+            )),
+        ));
+    }
+
+    // The slices below must span at most `isize::MAX` bytes; f64 is the widest element type
+    let len = batch
+        .count
+        .checked_mul(batch.dimension)
+        .filter(|len| len.checked_mul(8).is_some_and(|bytes| bytes <= isize::MAX as usize));
+    let Some(len) = len else {
+        return Err(FfiError::new(
+            ChromaErrorCode::InvalidArgument,
+            "Invalid embedding batch",
+            Some(format!(
+                "Count ({}) times dimension ({}) overflows the addressable size",
+                batch.count, batch.dimension
+            )),
+        ));
+    };
+    let values: Vec<f32> = match batch.dtype {
+        CHROMA_DTYPE_F32 => std::slice::from_raw_parts(batch.data as *const f32, len).to_vec(),
+        CHROMA_DTYPE_F16 => std::slice::from_raw_parts(batch.data as *const u16, len)
+            .iter()
+            .map(|&bits| half::f16::from_bits(bits).to_f32())
+            .collect(),
+        CHROMA_DTYPE_F64 => std::slice::from_raw_parts(batch.data as *const f64, len)
+            .iter()
+            .map(|&v| v as f32)
+            .collect(),
+        other => {
+            return Err(FfiError::new(
+                ChromaErrorCode::InvalidArgument,
+                "Unknown embedding dtype",
+                Some(format!("Embedding dtype: {}", other)),
+            ))
+        }
+    };
+
+    Ok(values
+        .chunks(batch.dimension)
+        .map(|row| row.to_vec())
+        .collect())
+}
+
+/// List of record IDs, filled in by the library in a caller-owned struct
 #[repr(C)]
 pub struct ChromaResultSet {
     pub ids: *mut *mut c_char,
     pub count: size_t,
 }
 
+impl ChromaResultSet {
+    pub(crate) fn empty() -> Self {
+        ChromaResultSet {
+            ids: ptr::null_mut(),
+            count: 0,
+        }
+    }
+
+    pub(crate) fn from_ids(ids: Vec<String>) -> Self {
+        let (ids, count) = crate::utils::vec_string_to_c_array(ids);
+        ChromaResultSet { ids, count }
+    }
+}
+
+/// Frees the IDs held by a result set and resets it to empty; the struct itself is not freed
+#[no_mangle]
+pub extern "C" fn chroma_free_result_set(result_set: *mut ChromaResultSet) {
+    ffi_boundary_void("chroma_free_result_set", || {
+        if let Some(result_set) = unsafe { result_set.as_mut() } {
+            crate::utils::chroma_free_string_array(result_set.ids, result_set.count);
+            *result_set = ChromaResultSet::empty();
+        }
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn chroma_free_query_result(result: *mut ChromaQueryResult) {
-    if !result.is_null() {
-        unsafe {
-            let result = Box::from_raw(result);
+    ffi_boundary_void("chroma_free_query_result", || {
+        if !result.is_null() {
+            unsafe {
+                let result = Box::from_raw(result);
+
+                crate::utils::chroma_free_string_array(result.ids, result.ids_count);
+
+                chroma_free(result.distances as *mut c_void);
 
-            crate::utils::chroma_free_string_array(result.ids, result.ids_count);
+                crate::utils::chroma_free_string_array(result.metadata_json, result.metadata_count);
+                crate::utils::chroma_free_string_array(result.documents, result.documents_count);
 
-            if !result.distances.is_null() {
-                libc::free(result.distances as *mut libc::c_void);
+                chroma_free(result.scores as *mut c_void);
+                chroma_free(result.text_scores as *mut c_void);
+                chroma_free(result.embeddings as *mut c_void);
+                chroma_free(result.versions as *mut c_void);
+
+                free_metadata_entries(result.metadata_entries, result.metadata_entries_count);
             }
+        }
+    })
+}
 
-            crate::utils::chroma_free_string_array(result.metadata_json, result.metadata_count);
-            crate::utils::chroma_free_string_array(result.documents, result.documents_count);
+/// A single record returned by `chroma_get_one`.
+///
+/// Owned by the library: release with `chroma_free_record`. Fields that were not included
+/// or are not set on the record are null, with `embedding_dim` 0 when there is no embedding.
+#[repr(C)]
+pub struct ChromaRecord {
+    pub id: *mut c_char,
+    pub document: *mut c_char,
+    /// Metadata serialized as a JSON object
+    pub metadata_json: *mut c_char,
+    pub embedding: *mut c_float,
+    pub embedding_dim: size_t,
+    pub uri: *mut c_char,
+}
+
+/// Frees a record returned by `chroma_get_one` and every string and array it points to
+#[no_mangle]
+pub extern "C" fn chroma_free_record(record: *mut ChromaRecord) {
+    ffi_boundary_void("chroma_free_record", || {
+        if !record.is_null() {
+            let record = unsafe { Box::from_raw(record) };
+            chroma_free(record.id as *mut c_void);
+            chroma_free(record.document as *mut c_void);
+            chroma_free(record.metadata_json as *mut c_void);
+            chroma_free(record.embedding as *mut c_void);
+            chroma_free(record.uri as *mut c_void);
         }
-    }
+    })
 }
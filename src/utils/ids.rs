@@ -0,0 +1,131 @@
+// UUID generation and checks in the formats the library reads and writes
+use libc::{c_char, c_int};
+use std::ptr;
+use uuid::Uuid;
+
+use crate::error::{ffi_boundary, set_error, set_success, ChromaError, ChromaErrorCode};
+use crate::utils::{c_str_to_string, string_to_c_str};
+
+/// Versions of the UUIDs generated by `chroma_new_uuid`
+pub const CHROMA_UUID_V4: c_int = 4;
+pub const CHROMA_UUID_V7: c_int = 7;
+
+/// Generates a UUID and writes it to `*out_str` in the hyphenated lowercase form the library
+/// uses for collection and database IDs, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+///
+/// `version` is `CHROMA_UUID_V4` for a random UUID, or `CHROMA_UUID_V7` for one that starts
+/// with a millisecond timestamp, so IDs generated in order also sort in order; 0 selects
+/// `CHROMA_UUID_V4`. The string is allocated with `chroma_malloc`; release it with
+/// `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_new_uuid(
+    version: c_int,
+    out_str: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_new_uuid", || {
+        let func_name = "chroma_new_uuid";
+
+        if out_str.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "Output string pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let id = match version {
+            0 | CHROMA_UUID_V4 => Uuid::new_v4(),
+            CHROMA_UUID_V7 => Uuid::now_v7(),
+            _ => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Unknown UUID version",
+                    func_name,
+                    Some(&format!(
+                        "Got {}, expected CHROMA_UUID_V4 (4) or CHROMA_UUID_V7 (7)",
+                        version
+                    )),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        unsafe {
+            *out_str = string_to_c_str(id.hyphenated().to_string());
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
+
+/// Checks that `uuid_str` is a UUID the library accepts as an ID. Besides the hyphenated form,
+/// the simple (32 hex digits), braced and `urn:uuid:` forms are accepted, in any case.
+///
+/// Fails with `InvalidUuid`, whose details give the parse error, for anything else. On
+/// success `*canonical_out`, when not null, receives the hyphenated lowercase form the
+/// library stores and returns, which IDs should be compared in; it is allocated with
+/// `chroma_malloc`; release it with `chroma_free_string`.
+#[no_mangle]
+pub extern "C" fn chroma_validate_uuid(
+    uuid_str: *const c_char,
+    canonical_out: *mut *mut c_char,
+    error_out: *mut *mut ChromaError,
+) -> c_int {
+    ffi_boundary(error_out, "chroma_validate_uuid", || {
+        let func_name = "chroma_validate_uuid";
+
+        if let Some(canonical_out) = unsafe { canonical_out.as_mut() } {
+            *canonical_out = ptr::null_mut();
+        }
+
+        if uuid_str.is_null() {
+            set_error(
+                error_out,
+                ChromaErrorCode::InvalidArgument,
+                "UUID string pointer is null",
+                func_name,
+                None,
+            );
+            return ChromaErrorCode::InvalidArgument as c_int;
+        }
+
+        let text = match unsafe { c_str_to_string(uuid_str) } {
+            Ok(text) => text,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidArgument,
+                    "Invalid UUID string",
+                    func_name,
+                    Some(&e.to_string()),
+                );
+                return ChromaErrorCode::InvalidArgument as c_int;
+            }
+        };
+
+        let id = match Uuid::parse_str(&text) {
+            Ok(id) => id,
+            Err(e) => {
+                set_error(
+                    error_out,
+                    ChromaErrorCode::InvalidUuid,
+                    "Invalid UUID",
+                    func_name,
+                    Some(&format!("UUID parse error: {}", e)),
+                );
+                return ChromaErrorCode::InvalidUuid as c_int;
+            }
+        };
+
+        if let Some(canonical_out) = unsafe { canonical_out.as_mut() } {
+            *canonical_out = string_to_c_str(id.hyphenated().to_string());
+        }
+        set_success(error_out);
+        ChromaErrorCode::Success as c_int
+    })
+}
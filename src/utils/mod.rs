@@ -1,9 +1,22 @@
 // Utility functions for ChromaDB C# bindings
+//
+// Memory ownership: every string, array and buffer handed to the caller is
+// allocated with `chroma_malloc` (the C allocator of this library) and may be
+// released with `chroma_free`. Result structs (`ChromaError`, `ChromaQueryResult`)
+// and handles are owned by Rust and must be released with their dedicated
+// `chroma_free_*` / `chroma_destroy_*` function, which also frees their fields.
+// Callers must never release library memory with their own runtime's allocator.
 use anyhow::{anyhow, Result};
-use libc::{c_char, c_float, size_t};
+use libc::{c_char, c_float, c_void, size_t};
 use std::ffi::{CStr, CString};
 use std::ptr;
 
+use crate::error::{ffi_boundary_or, ffi_boundary_void};
+
+mod ids;
+
+pub use ids::*;
+
 /// Constants
 pub const DEFAULT_DATABASE: &str = "default_database";
 pub const DEFAULT_TENANT: &str = "default_tenant";
@@ -17,38 +30,69 @@ pub unsafe fn c_str_to_string(s: *const c_char) -> Result<String> {
     Ok(CStr::from_ptr(s).to_string_lossy().into_owned())
 }
 
-/// Converts a Rust string to a C string
+/// Allocates memory with the library's allocator; release it with `chroma_free`
+#[no_mangle]
+pub extern "C" fn chroma_malloc(size: size_t) -> *mut c_void {
+    ffi_boundary_or("chroma_malloc", ptr::null_mut(), || {
+        if size == 0 {
+            return ptr::null_mut();
+        }
+
+        unsafe { libc::malloc(size) }
+    })
+}
+
+/// Frees memory allocated by the library, including strings, arrays and buffers
+#[no_mangle]
+pub extern "C" fn chroma_free(ptr: *mut c_void) {
+    ffi_boundary_void("chroma_free", || {
+        if !ptr.is_null() {
+            unsafe {
+                libc::free(ptr);
+            }
+        }
+    })
+}
+
+/// Converts a Rust string to a C string allocated with `chroma_malloc`
 pub fn string_to_c_str(s: String) -> *mut c_char {
-    match CString::new(s) {
-        Ok(c_string) => c_string.into_raw(),
-        Err(_) => ptr::null_mut(),
+    let Ok(c_string) = CString::new(s) else {
+        return ptr::null_mut();
+    };
+
+    let bytes = c_string.as_bytes_with_nul();
+    let buffer = chroma_malloc(bytes.len()) as *mut c_char;
+    if buffer.is_null() {
+        return ptr::null_mut();
+    }
+
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buffer, bytes.len());
     }
+    buffer
 }
 
 /// Frees memory allocated for C strings
 #[no_mangle]
 pub extern "C" fn chroma_free_string(s: *mut c_char) {
-    if !s.is_null() {
-        unsafe {
-            let _ = CString::from_raw(s);
-        }
-    }
+    ffi_boundary_void("chroma_free_string", || {
+        chroma_free(s as *mut c_void);
+    })
 }
 
 /// Frees memory allocated for C string arrays
 #[no_mangle]
 pub extern "C" fn chroma_free_string_array(array: *mut *mut c_char, count: size_t) {
-    if !array.is_null() {
-        unsafe {
-            for i in 0..count {
-                let ptr = *array.add(i);
-                if !ptr.is_null() {
-                    let _ = CString::from_raw(ptr);
+    ffi_boundary_void("chroma_free_string_array", || {
+        if !array.is_null() {
+            unsafe {
+                for i in 0..count {
+                    chroma_free_string(*array.add(i));
                 }
+                chroma_free(array as *mut c_void);
             }
-            libc::free(array as *mut libc::c_void);
         }
-    }
+    })
 }
 
 /// Converts a Rust vector of strings to a C string array
@@ -59,7 +103,7 @@ pub fn vec_string_to_c_array(strings: Vec<String>) -> (*mut *mut c_char, size_t)
     }
 
     unsafe {
-        let array = libc::malloc(count * std::mem::size_of::<*mut c_char>()) as *mut *mut c_char;
+        let array = chroma_malloc(count * std::mem::size_of::<*mut c_char>()) as *mut *mut c_char;
         if array.is_null() {
             return (ptr::null_mut(), 0);
         }
@@ -112,7 +156,7 @@ pub fn vec_f32_to_c_array(values: Vec<f32>) -> (*mut c_float, size_t) {
     }
 
     unsafe {
-        let array = libc::malloc(count * std::mem::size_of::<c_float>()) as *mut c_float;
+        let array = chroma_malloc(count * std::mem::size_of::<c_float>()) as *mut c_float;
         if array.is_null() {
             return (ptr::null_mut(), 0);
         }
@@ -124,3 +168,41 @@ pub fn vec_f32_to_c_array(values: Vec<f32>) -> (*mut c_float, size_t) {
         (array, count)
     }
 }
+
+/// Copies a Rust vector of i64 values into memory allocated with malloc
+pub fn vec_i64_to_c_array(values: Vec<i64>) -> (*mut i64, size_t) {
+    let count = values.len();
+    if count == 0 {
+        return (ptr::null_mut(), 0);
+    }
+
+    unsafe {
+        let array = chroma_malloc(count * std::mem::size_of::<i64>()) as *mut i64;
+        if array.is_null() {
+            return (ptr::null_mut(), 0);
+        }
+
+        ptr::copy_nonoverlapping(values.as_ptr(), array, count);
+
+        (array, count)
+    }
+}
+
+/// Copies a byte buffer into memory allocated with malloc
+pub fn vec_u8_to_c_array(bytes: Vec<u8>) -> (*mut u8, size_t) {
+    let count = bytes.len();
+    if count == 0 {
+        return (ptr::null_mut(), 0);
+    }
+
+    unsafe {
+        let array = chroma_malloc(count) as *mut u8;
+        if array.is_null() {
+            return (ptr::null_mut(), 0);
+        }
+
+        ptr::copy_nonoverlapping(bytes.as_ptr(), array, count);
+
+        (array, count)
+    }
+}